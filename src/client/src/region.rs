@@ -27,7 +27,9 @@ use common_recordbatch::{RecordBatchStreamAdaptor, SendableRecordBatchStream};
 use common_telemetry::error;
 use prost::Message;
 use snafu::{location, Location, OptionExt, ResultExt};
+use store_api::region_request::{WriteAckLevel, WRITE_ACK_LEVEL_KEY};
 use tokio_stream::StreamExt;
+use tonic::metadata::MetadataValue;
 
 use crate::error::Error::RegionServer;
 use crate::error::{
@@ -44,18 +46,19 @@ pub struct RegionRequester {
 #[async_trait]
 impl Datanode for RegionRequester {
     async fn handle(&self, request: RegionRequest) -> MetaResult<AffectedRows> {
-        self.handle_inner(request).await.map_err(|err| {
-            if matches!(err, RegionServer { .. }) {
-                meta_error::Error::RetryLater {
-                    source: BoxedError::new(err),
-                }
-            } else {
-                meta_error::Error::External {
-                    source: BoxedError::new(err),
-                    location: location!(),
-                }
-            }
-        })
+        self.handle_inner(request, None)
+            .await
+            .map_err(to_meta_error)
+    }
+
+    async fn handle_write(
+        &self,
+        request: RegionRequest,
+        ack_level: WriteAckLevel,
+    ) -> MetaResult<AffectedRows> {
+        self.handle_inner(request, Some(ack_level))
+            .await
+            .map_err(to_meta_error)
     }
 
     async fn handle_query(&self, request: QueryRequest) -> MetaResult<SendableRecordBatchStream> {
@@ -69,6 +72,19 @@ impl Datanode for RegionRequester {
     }
 }
 
+fn to_meta_error(err: Error) -> meta_error::Error {
+    if matches!(err, RegionServer { .. }) {
+        meta_error::Error::RetryLater {
+            source: BoxedError::new(err),
+        }
+    } else {
+        meta_error::Error::External {
+            source: BoxedError::new(err),
+            location: location!(),
+        }
+    }
+}
+
 impl RegionRequester {
     pub fn new(client: Client) -> Self {
         Self { client }
@@ -145,7 +161,13 @@ impl RegionRequester {
         Ok(Box::pin(record_batch_stream))
     }
 
-    async fn handle_inner(&self, request: RegionRequest) -> Result<AffectedRows> {
+    /// Sends the `request` to the region server, with the `ack_level` of its writes if
+    /// it's given.
+    async fn handle_inner(
+        &self,
+        request: RegionRequest,
+        ack_level: Option<WriteAckLevel>,
+    ) -> Result<AffectedRows> {
         let request_type = request
             .body
             .as_ref()
@@ -158,6 +180,13 @@ impl RegionRequester {
 
         let mut client = self.client.raw_region_client()?;
 
+        let mut request = tonic::Request::new(request);
+        if let Some(ack_level) = ack_level {
+            let _ = request.metadata_mut().insert(
+                WRITE_ACK_LEVEL_KEY,
+                MetadataValue::from_static(ack_level.into()),
+            );
+        }
         let RegionResponse {
             header,
             affected_rows,
@@ -181,7 +210,7 @@ impl RegionRequester {
     }
 
     pub async fn handle(&self, request: RegionRequest) -> Result<AffectedRows> {
-        self.handle_inner(request).await
+        self.handle_inner(request, None).await
     }
 }
 
@@ -14,4 +14,5 @@
 
 fn main() {
     common_version::setup_git_versions();
+    common_version::setup_rustc_version();
 }
@@ -15,12 +15,16 @@
 #![doc = include_str!("../../../../README.md")]
 
 use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
-use cmd::error::Result;
-use cmd::options::{Options, TopLevelOptions};
-use cmd::{cli, datanode, frontend, metasrv, standalone};
-use common_telemetry::logging::{error, info, TracingOptions};
+use cmd::error::{Error, InstallSignalHandlersSnafu, Result};
+use cmd::options::{env_flag, instance_tags, Options, TopLevelOptions};
+use cmd::{cli, config_template, datanode, frontend, metasrv, standalone};
+use common_telemetry::logging::{error, info, warn, LogFormat, TracingOptions};
+use common_telemetry::PanicHookOptions;
+use snafu::ResultExt;
 
 lazy_static::lazy_static! {
     static ref APP_VERSION: prometheus::IntGaugeVec =
@@ -30,10 +34,39 @@ lazy_static::lazy_static! {
 #[derive(Parser)]
 #[clap(name = "greptimedb", version = print_version())]
 struct Command {
+    /// Directory of the log files, overrides the `logging.dir` option of the config
+    /// file and the env vars.
     #[clap(long)]
     log_dir: Option<String>,
+    /// Log level like `info,servers=debug`, overrides the `logging.level` option of the
+    /// config file and the env vars, which override the `RUST_LOG` env var.
     #[clap(long)]
     log_level: Option<String>,
+    /// Format of the log on the stdout, `pretty`, `compact` or `json`, overrides the
+    /// `logging.log_format` option.
+    #[clap(long)]
+    log_format: Option<LogFormat>,
+    /// Fail on unknown keys in the config file instead of ignoring them.
+    #[clap(long, alias = "reject-unknown-config-keys")]
+    strict_config: bool,
+    /// Id of the instance attached to the logs and metrics, defaults to the hostname.
+    #[clap(long)]
+    instance_id: Option<String>,
+    /// Name of the cluster attached to the logs and metrics.
+    #[clap(long)]
+    cluster_name: Option<String>,
+    /// Writes a snapshot of all metrics in the Prometheus text format to this file
+    /// when the process exits, whether it stops cleanly, on a signal or on an error.
+    #[clap(long)]
+    dump_metrics_on_shutdown: Option<PathBuf>,
+    /// Appends each panic with its backtrace as a JSON line to this file, which is
+    /// written even if the logging isn't working.
+    #[clap(long)]
+    crash_log: Option<PathBuf>,
+    /// Aborts the process on a panic instead of unwinding, also enabled by the
+    /// `GREPTIMEDB_PANIC_ABORT` env var set to `true`.
+    #[clap(long)]
+    panic_abort: bool,
     #[clap(subcommand)]
     subcmd: SubCommand,
 
@@ -70,6 +103,14 @@ impl Application {
             Application::Cli(instance) => instance.stop().await,
         }
     }
+
+    /// Time to stop gracefully after a shutdown signal, unbounded if `None`.
+    fn shutdown_grace_period(&self) -> Option<Duration> {
+        match self {
+            Application::Standalone(instance) => Some(instance.shutdown_grace_period()),
+            _ => None,
+        }
+    }
 }
 
 impl Command {
@@ -77,6 +118,22 @@ impl Command {
         self.subcmd.build(opts).await
     }
 
+    /// Returns true if the options are only validated, then the process exits
+    /// without starting.
+    fn validate_only(&self) -> bool {
+        match &self.subcmd {
+            SubCommand::Standalone(cmd) => cmd.validate_only(),
+            _ => false,
+        }
+    }
+
+    async fn validate(&self, opts: Options) -> Result<()> {
+        match (&self.subcmd, opts) {
+            (SubCommand::Standalone(cmd), Options::Standalone(opts)) => cmd.validate(*opts).await,
+            _ => Ok(()),
+        }
+    }
+
     fn load_options(&self) -> Result<Options> {
         let top_level_opts = self.top_level_options();
         self.subcmd.load_options(top_level_opts)
@@ -86,6 +143,8 @@ impl Command {
         TopLevelOptions {
             log_dir: self.log_dir.clone(),
             log_level: self.log_level.clone(),
+            log_format: self.log_format,
+            strict_config: self.strict_config,
         }
     }
 }
@@ -102,6 +161,8 @@ enum SubCommand {
     Standalone(standalone::Command),
     #[clap(name = "cli")]
     Cli(cli::Command),
+    #[clap(name = "config")]
+    Config(config_template::Command),
 }
 
 impl SubCommand {
@@ -139,6 +200,7 @@ impl SubCommand {
             SubCommand::Metasrv(cmd) => cmd.load_options(top_level_opts),
             SubCommand::Standalone(cmd) => cmd.load_options(top_level_opts),
             SubCommand::Cli(cmd) => cmd.load_options(top_level_opts),
+            SubCommand::Config(_) => unreachable!("config commands run before the options"),
         }
     }
 }
@@ -151,6 +213,7 @@ impl fmt::Display for SubCommand {
             SubCommand::Metasrv(..) => write!(f, "greptime-metasrv"),
             SubCommand::Standalone(..) => write!(f, "greptime-standalone"),
             SubCommand::Cli(_) => write!(f, "greptime-cli"),
+            SubCommand::Config(_) => write!(f, "greptime-config"),
         }
     }
 }
@@ -184,6 +247,9 @@ fn full_version() -> &'static str {
     )
 }
 
+/// Env var aborting the process on a panic like `--panic-abort`, if `true` or `1`.
+const PANIC_ABORT_ENV: &str = "GREPTIMEDB_PANIC_ABORT";
+
 fn log_env_flags() {
     info!("command line arguments");
     for argument in std::env::args() {
@@ -191,53 +257,376 @@ fn log_env_flags() {
     }
 }
 
+/// Why the process is exiting.
+enum ShutdownReason {
+    /// Received the named signal.
+    Signal(&'static str),
+    /// Received the named signal again while stopping gracefully.
+    ForcedBySignal(&'static str),
+    /// Didn't stop gracefully within the grace period.
+    GracePeriodElapsed(Duration),
+    /// The application stopped on its own without an error.
+    Exited,
+    /// The application failed to build or run.
+    FatalError { error: String, exit_code: i32 },
+}
+
+impl ShutdownReason {
+    fn fatal_error(err: &Error) -> Self {
+        ShutdownReason::FatalError {
+            error: err.to_string(),
+            exit_code: err.exit_code(),
+        }
+    }
+}
+
+/// Logs a single structured event naming the shutdown reason, how long the
+/// process ran and its exit code. The event is emitted on drop, so exits
+/// caused by a panic unwinding out of `main` are recorded as well.
+struct ShutdownReport {
+    app_name: String,
+    started_at: Instant,
+    reason: Option<ShutdownReason>,
+}
+
+impl ShutdownReport {
+    fn new(app_name: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            started_at: Instant::now(),
+            reason: None,
+        }
+    }
+
+    fn set_reason(&mut self, reason: ShutdownReason) {
+        self.reason = Some(reason);
+    }
+}
+
+impl Drop for ShutdownReport {
+    fn drop(&mut self) {
+        let uptime_secs = self.started_at.elapsed().as_secs_f64();
+        if std::thread::panicking() {
+            // The exit code of a process terminated by an uncaught panic.
+            error!(
+                app = %self.app_name,
+                reason = "panic",
+                uptime_secs,
+                exit_code = 101,
+                "Process exiting"
+            );
+            return;
+        }
+
+        match &self.reason {
+            Some(ShutdownReason::Signal(signal)) => info!(
+                app = %self.app_name,
+                reason = "signal",
+                signal = %signal,
+                uptime_secs,
+                exit_code = 0,
+                "Process exiting"
+            ),
+            Some(ShutdownReason::ForcedBySignal(signal)) => warn!(
+                app = %self.app_name,
+                reason = "forced_by_signal",
+                signal = %signal,
+                uptime_secs,
+                exit_code = FORCED_EXIT_CODE,
+                "Process exiting"
+            ),
+            Some(ShutdownReason::GracePeriodElapsed(period)) => warn!(
+                app = %self.app_name,
+                reason = "grace_period_elapsed",
+                grace_period = ?period,
+                uptime_secs,
+                exit_code = FORCED_EXIT_CODE,
+                "Process exiting"
+            ),
+            Some(ShutdownReason::Exited) | None => info!(
+                app = %self.app_name,
+                reason = "exited",
+                uptime_secs,
+                exit_code = 0,
+                "Process exiting"
+            ),
+            Some(ShutdownReason::FatalError { error, exit_code }) => error!(
+                app = %self.app_name,
+                reason = "fatal_error",
+                error = %error,
+                uptime_secs,
+                exit_code,
+                "Process exiting"
+            ),
+        }
+    }
+}
+
+/// Writes the final metrics to a file on drop, so they are kept for a process that
+/// exits before being scraped, like a benchmark run.
+struct MetricsDump {
+    path: PathBuf,
+}
+
+impl Drop for MetricsDump {
+    fn drop(&mut self) {
+        let result = common_telemetry::dump_metrics()
+            .and_then(|text| std::fs::write(&self.path, text).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!("Dumped metrics to {}", self.path.display()),
+            Err(e) => error!("Failed to dump metrics to {}: {}", self.path.display(), e),
+        }
+    }
+}
+
 #[cfg(not(windows))]
 #[global_allocator]
 static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    if let Err(err) = run().await {
+        // Printed like an error returned by `main`, which always exits with 1.
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("Error: {err:?}");
+        }
+        std::process::exit(err.exit_code());
+    }
+}
+
+async fn run() -> Result<()> {
     let cmd = Command::parse();
+    // Runs before the logging starts, which would write to the stdout as well.
+    if let SubCommand::Config(cmd) = &cmd.subcmd {
+        return cmd.run();
+    }
     let app_name = &cmd.subcmd.to_string();
 
     let opts = cmd.load_options()?;
+    // Printed before the logging starts too.
+    if let Options::PrintConfig(config) = &opts {
+        #[allow(clippy::print_stdout)]
+        {
+            print!("{config}");
+        }
+        return Ok(());
+    }
+    let tags = instance_tags(cmd.instance_id.clone(), cmd.cluster_name.clone())?;
     let logging_opts = opts.logging_options();
     let tracing_opts = TracingOptions {
         #[cfg(feature = "tokio-console")]
         tokio_console_addr: cmd.tokio_console_addr.clone(),
+        tags: tags.clone(),
+        app_version: Some(short_version().to_string()),
     };
 
-    common_telemetry::set_panic_hook();
-    let _guard = common_telemetry::init_global_logging(app_name, logging_opts, tracing_opts);
+    common_telemetry::set_panic_hook(PanicHookOptions {
+        crash_log: cmd.crash_log.clone(),
+        abort: cmd.panic_abort || env_flag(PANIC_ABORT_ENV),
+    });
+    let logging_guard = common_telemetry::init_global_logging(app_name, logging_opts, tracing_opts);
+    #[cfg(unix)]
+    {
+        let level_file = PathBuf::from(&logging_opts.dir).join(LOG_LEVEL_FILE);
+        let _handle = tokio::spawn(reload_log_level_on_hangup(level_file));
+    }
+    // The same tags as the logs, set before any metrics are gathered.
+    common_telemetry::metric::init_const_labels(tags.clone());
+    // Dropped after the application on every exit path, including errors and panics.
+    let metrics_dump = cmd
+        .dump_metrics_on_shutdown
+        .clone()
+        .map(|path| MetricsDump { path });
 
     // Report app version as gauge.
     APP_VERSION
         .with_label_values(&[short_version(), full_version()])
         .inc();
 
-    // Log version and argument flags.
+    // Log version, tags and argument flags.
     info!(
         "short_version: {}, full_version: {}",
         short_version(),
         full_version()
     );
+    info!(
+        "Starting {} {} (commit: {}, rustc: {})",
+        app_name,
+        short_version(),
+        env!("GIT_COMMIT"),
+        env!("RUSTC_VERSION")
+    );
+    if let Some(protocols) = opts.protocol_summary() {
+        info!("Protocol servers: {protocols}");
+    }
+    for (key, value) in &tags {
+        info!("{key}: {value}");
+    }
     log_env_flags();
 
-    let mut app = cmd.build(opts).await?;
+    if cmd.validate_only() {
+        cmd.validate(opts).await?;
+        #[allow(clippy::print_stdout)]
+        {
+            println!("configuration valid");
+        }
+        return Ok(());
+    }
+
+    let mut report = ShutdownReport::new(app_name);
+
+    let mut signals = match ShutdownSignals::new().context(InstallSignalHandlersSnafu) {
+        Ok(signals) => signals,
+        Err(err) => {
+            error!(err; "Failed to install the signal handlers");
+            report.set_reason(ShutdownReason::fatal_error(&err));
+            return Err(err);
+        }
+    };
+
+    let mut app = match cmd.build(opts).await {
+        Ok(app) => app,
+        Err(err) => {
+            error!(err; "Failed to build the application");
+            report.set_reason(ShutdownReason::fatal_error(&err));
+            return Err(err);
+        }
+    };
 
     tokio::select! {
         result = app.start() => {
             if let Err(err) = result {
                 error!(err; "Fatal error occurs!");
+                report.set_reason(ShutdownReason::fatal_error(&err));
+                return Err(err);
             }
+            report.set_reason(ShutdownReason::Exited);
         }
-        _ = tokio::signal::ctrl_c() => {
-            if let Err(err) = app.stop().await {
-                error!(err; "Fatal error occurs!");
+        signal = signals.recv() => {
+            report.set_reason(ShutdownReason::Signal(signal));
+            info!("Received {signal}, stopping gracefully, send it again to stop immediately");
+            let grace_period = app.shutdown_grace_period();
+            let grace_period_elapsed = async {
+                match grace_period {
+                    Some(period) => tokio::time::sleep(period).await,
+                    None => std::future::pending().await,
+                }
+            };
+            // Stopping the servers rejects new connections and waits for the running
+            // requests.
+            let forced = tokio::select! {
+                result = app.stop() => {
+                    if let Err(err) = result {
+                        error!(err; "Fatal error occurs!");
+                        report.set_reason(ShutdownReason::fatal_error(&err));
+                        return Err(err);
+                    }
+                    info!("Goodbye!");
+                    None
+                }
+                signal = signals.recv() => Some(ShutdownReason::ForcedBySignal(signal)),
+                _ = grace_period_elapsed => {
+                    // The grace period is only set if the future completes.
+                    grace_period.map(ShutdownReason::GracePeriodElapsed)
+                }
+            };
+            if let Some(reason) = forced {
+                report.set_reason(reason);
+                // Exits without waiting for the blocking tasks still running, which
+                // dropping the runtime would. So the report, the metrics dump and the
+                // log writers are flushed explicitly first.
+                drop(report);
+                drop(metrics_dump);
+                drop(logging_guard);
+                std::process::exit(FORCED_EXIT_CODE);
             }
-            info!("Goodbye!");
         }
     }
 
     Ok(())
 }
+
+/// File in the log dir holding the log level applied on SIGHUP, like
+/// `info,servers=debug`. The initial level is restored if the file doesn't exist.
+#[cfg(unix)]
+const LOG_LEVEL_FILE: &str = "LOG_LEVEL";
+
+/// Reloads the log level from `level_file` on every SIGHUP, so the verbosity of a
+/// running process changes without a restart.
+#[cfg(unix)]
+async fn reload_log_level_on_hangup(level_file: PathBuf) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!("Failed to install the SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        let level = match tokio::fs::read_to_string(&level_file).await {
+            Ok(level) => Some(level.trim().to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                error!(
+                    "Failed to read the log level of {}: {}",
+                    level_file.display(),
+                    e
+                );
+                continue;
+            }
+        };
+        if let Err(e) = common_telemetry::logging::reload_log_level(level.as_deref()) {
+            error!("Failed to reload the log level on SIGHUP: {}", e);
+        }
+    }
+}
+
+/// Exit code of a process stopped by a second shutdown signal, or the grace period
+/// elapsing, before it stopped gracefully. It's the code of a process interrupted by
+/// SIGINT, which can't be mistaken for the exit codes of the errors.
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// Listens to the shutdown signals, SIGINT and also SIGTERM on unix. The handlers
+/// stay registered between the calls of [ShutdownSignals::recv], so a repeated
+/// signal is never handled by the default action killing the process.
+struct ShutdownSignals {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignals {
+    fn new() -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            Ok(Self {
+                interrupt: signal(SignalKind::interrupt())?,
+                terminate: signal(SignalKind::terminate())?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    /// Waits for the next shutdown signal and returns its name.
+    async fn recv(&mut self) -> &'static str {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.interrupt.recv() => "SIGINT",
+                _ = self.terminate.recv() => "SIGTERM",
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            "SIGINT"
+        }
+    }
+}
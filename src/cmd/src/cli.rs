@@ -14,22 +14,41 @@
 
 mod bench;
 mod cmd;
+mod copy;
+mod editor;
 mod export;
+mod generate_unit;
 mod helper;
+mod import;
+mod logs;
+mod output;
+mod peek;
 mod repl;
+mod transaction;
 // TODO(weny): Removes it
 #[allow(deprecated)]
 mod upgrade;
+mod validate;
+
+use std::io::Read;
+use std::path::PathBuf;
 
 use async_trait::async_trait;
 use bench::BenchTableMetadataCommand;
 use clap::Parser;
+use common_grpc::channel_manager::ClientTlsOption;
 use common_telemetry::logging::LoggingOptions;
 pub use repl::Repl;
+use snafu::{OptionExt, ResultExt};
 use upgrade::UpgradeCommand;
 
 use self::export::ExportCommand;
-use crate::error::Result;
+use self::generate_unit::GenerateUnitCommand;
+use self::import::ImportCommand;
+use self::logs::LogsCommand;
+use self::output::OutputFormat;
+use self::validate::ValidateSqlCommand;
+use crate::error::{FileIoSnafu, MissingConfigSnafu, Result};
 use crate::options::{Options, TopLevelOptions};
 
 #[async_trait]
@@ -74,6 +93,9 @@ impl Command {
         if top_level_opts.log_level.is_some() {
             logging_opts.level = top_level_opts.log_level;
         }
+        if let Some(log_format) = top_level_opts.log_format {
+            logging_opts.log_format = log_format;
+        }
         Ok(Options::Cli(Box::new(logging_opts)))
     }
 }
@@ -84,6 +106,10 @@ enum SubCommand {
     Upgrade(UpgradeCommand),
     Bench(BenchTableMetadataCommand),
     Export(ExportCommand),
+    Import(ImportCommand),
+    Logs(LogsCommand),
+    ValidateSql(ValidateSqlCommand),
+    GenerateUnit(GenerateUnitCommand),
 }
 
 impl SubCommand {
@@ -93,6 +119,10 @@ impl SubCommand {
             SubCommand::Upgrade(cmd) => cmd.build().await,
             SubCommand::Bench(cmd) => cmd.build().await,
             SubCommand::Export(cmd) => cmd.build().await,
+            SubCommand::Import(cmd) => cmd.build().await,
+            SubCommand::Logs(cmd) => cmd.build().await,
+            SubCommand::ValidateSql(cmd) => cmd.build().await,
+            SubCommand::GenerateUnit(cmd) => cmd.build().await,
         }
     }
 }
@@ -105,6 +135,50 @@ pub(crate) struct AttachCommand {
     pub(crate) meta_addr: Option<String>,
     #[clap(long, action)]
     pub(crate) disable_helper: bool,
+    /// File the history of the typed commands is kept in across sessions,
+    /// `$XDG_DATA_HOME/greptimedb/cli_history` or `~/.greptimedb_cli_history` by default.
+    #[clap(long)]
+    pub(crate) history_file: Option<PathBuf>,
+    /// Neither loads nor keeps the history of the typed commands.
+    #[clap(long, action, conflicts_with = "history_file")]
+    pub(crate) no_history: bool,
+    /// Connects to the gRPC server over TLS, verifying its certificate by `--tls-ca`.
+    #[clap(long, action)]
+    pub(crate) tls: bool,
+    /// CA certificate the certificate of the server is verified against.
+    #[clap(long, requires = "tls")]
+    pub(crate) tls_ca: Option<String>,
+    /// Certificate of the client for mutual TLS, along with `--tls-key`.
+    #[clap(long, requires_all = &["tls", "tls_key"])]
+    pub(crate) tls_cert: Option<String>,
+    /// Private key of the `--tls-cert`.
+    #[clap(long, requires_all = &["tls", "tls_cert"])]
+    pub(crate) tls_key: Option<String>,
+    /// Name the certificate of the server is verified against instead of the host of
+    /// `--grpc-addr`, e.g. to connect by IP.
+    #[clap(long, requires = "tls")]
+    pub(crate) tls_server_name: Option<String>,
+    /// Timeout in seconds of an attempt to connect to the gRPC server.
+    #[clap(long)]
+    pub(crate) connect_timeout: Option<u64>,
+    /// Times to retry connecting to the gRPC server with a growing interval, e.g. while
+    /// it's starting. Fails on the first unreachable attempt by default.
+    #[clap(long, default_value = "0")]
+    pub(crate) connect_retries: usize,
+    /// Format of the query results, `table`, `csv` or `json` (an object per line).
+    #[clap(long, value_enum, default_value = "table")]
+    pub(crate) output_format: OutputFormat,
+    /// Tracks `BEGIN`, `COMMIT` and `ROLLBACK`, and offers to rollback the open
+    /// transaction on exit.
+    #[clap(long, action)]
+    pub(crate) transaction: bool,
+    /// Executes the statements separated by ';' and exits instead of reading the
+    /// commands interactively.
+    #[clap(long, conflicts_with = "execute_file")]
+    pub(crate) execute: Option<String>,
+    /// Executes the statements of the file and exits, `-` reads them from stdin.
+    #[clap(long)]
+    pub(crate) execute_file: Option<PathBuf>,
 }
 
 impl AttachCommand {
@@ -113,4 +187,128 @@ impl AttachCommand {
         let repl = Repl::try_new(&self).await?;
         Ok(Instance::Repl(repl))
     }
+
+    /// Returns the statements of `--execute` or `--execute-file`, `None` for the
+    /// interactive mode.
+    pub(crate) fn batch_input(&self) -> Result<Option<String>> {
+        match (&self.execute, &self.execute_file) {
+            (Some(sql), _) => Ok(Some(sql.clone())),
+            (None, Some(path)) if path.as_os_str() == "-" => {
+                let mut input = String::new();
+                let _ = std::io::stdin()
+                    .read_to_string(&mut input)
+                    .context(FileIoSnafu)?;
+                Ok(Some(input))
+            }
+            (None, Some(path)) => std::fs::read_to_string(path).map(Some).context(FileIoSnafu),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Returns the TLS option of the gRPC client, `None` without `--tls`.
+    pub(crate) fn client_tls_option(&self) -> Result<Option<ClientTlsOption>> {
+        if !self.tls {
+            return Ok(None);
+        }
+        // Tonic is built without the system root certificates.
+        let server_ca_cert_path = self.tls_ca.clone().context(MissingConfigSnafu {
+            msg: "--tls-ca is required by --tls",
+        })?;
+        Ok(Some(ClientTlsOption {
+            server_ca_cert_path,
+            client_cert_path: self.tls_cert.clone().unwrap_or_default(),
+            client_key_path: self.tls_key.clone().unwrap_or_default(),
+            domain_name: self.tls_server_name.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_client_tls_option() {
+        let cmd = AttachCommand::parse_from(["attach", "--grpc-addr", "127.0.0.1:4001"]);
+        assert_eq!(None, cmd.client_tls_option().unwrap());
+
+        let cmd = AttachCommand::parse_from([
+            "attach",
+            "--grpc-addr",
+            "10.0.0.1:4001",
+            "--tls",
+            "--tls-ca",
+            "/etc/greptimedb/ca.pem",
+            "--tls-server-name",
+            "greptimedb.example.com",
+        ]);
+        assert_eq!(
+            Some(ClientTlsOption {
+                server_ca_cert_path: "/etc/greptimedb/ca.pem".to_string(),
+                client_cert_path: String::new(),
+                client_key_path: String::new(),
+                domain_name: Some("greptimedb.example.com".to_string()),
+            }),
+            cmd.client_tls_option().unwrap()
+        );
+
+        let cmd = AttachCommand::parse_from(["attach", "--grpc-addr", "127.0.0.1:4001", "--tls"]);
+        assert!(cmd.client_tls_option().is_err());
+
+        // The certificate without the key.
+        assert!(AttachCommand::try_parse_from([
+            "attach",
+            "--grpc-addr",
+            "127.0.0.1:4001",
+            "--tls",
+            "--tls-ca",
+            "ca.pem",
+            "--tls-cert",
+            "client.pem",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_batch_input() {
+        let cmd = AttachCommand::parse_from(["attach", "--grpc-addr", "127.0.0.1:4001"]);
+        assert_eq!(None, cmd.batch_input().unwrap());
+
+        let cmd = AttachCommand::parse_from([
+            "attach",
+            "--grpc-addr",
+            "127.0.0.1:4001",
+            "--execute",
+            "select 1; select 2",
+        ]);
+        assert_eq!(
+            Some("select 1; select 2"),
+            cmd.batch_input().unwrap().as_deref()
+        );
+
+        let dir = create_temp_dir("test_batch_input");
+        let path = dir.path().join("queries.sql");
+        std::fs::write(&path, "select 1;\n").unwrap();
+        let cmd = AttachCommand::parse_from([
+            "attach",
+            "--grpc-addr",
+            "127.0.0.1:4001",
+            "--execute-file",
+            path.to_str().unwrap(),
+        ]);
+        assert_eq!(Some("select 1;\n"), cmd.batch_input().unwrap().as_deref());
+
+        assert!(AttachCommand::try_parse_from([
+            "attach",
+            "--grpc-addr",
+            "127.0.0.1:4001",
+            "--execute",
+            "select 1",
+            "--execute-file",
+            "queries.sql",
+        ])
+        .is_err());
+    }
 }
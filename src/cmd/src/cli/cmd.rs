@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
+use crate::cli::transaction::TransactionStatement;
 use crate::error::{Error, InvalidReplCommandSnafu, Result};
 
 /// Represents the parsed command from the user (which may be over many lines)
@@ -20,9 +23,47 @@ pub(crate) enum ReplCommand {
     Help,
     UseDatabase { db_name: String },
     Sql { sql: String },
+    Peek { table: String, limit: usize },
+    Watch(WatchOptions),
+    Copy(CopyOptions),
+    Edit,
+    Connect { addr: String },
     Exit,
 }
 
+/// Options of `\watch`, which re-runs the last SQL periodically.
+#[derive(Debug, PartialEq)]
+pub(crate) struct WatchOptions {
+    /// Interval between two runs.
+    pub(crate) interval: Duration,
+    /// Whether to stop watching when a run fails.
+    pub(crate) stop_on_error: bool,
+}
+
+/// Options of `\copy`, which copies the rows of a table between the server and a local
+/// CSV file.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CopyOptions {
+    pub(crate) table: String,
+    pub(crate) direction: CopyDirection,
+    /// Path of the file on the host of the REPL.
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CopyDirection {
+    /// Exports the table to the file.
+    To,
+    /// Imports the file into the table.
+    From,
+}
+
+/// Default number of rows sampled by `\peek`.
+const DEFAULT_PEEK_LIMIT: usize = 5;
+
+/// Option of `\watch` to stop watching when the query fails.
+const WATCH_STOP_ON_ERROR: &str = "--stop-on-error";
+
 impl TryFrom<&str> for ReplCommand {
     type Error = Error;
 
@@ -41,19 +82,38 @@ impl TryFrom<&str> for ReplCommand {
 
         let input = input.strip_suffix(';').map(|x| x.trim()).unwrap_or(input);
         let lowercase = input.to_lowercase();
+        if lowercase.split_whitespace().next() == Some("\\peek") {
+            return Self::parse_peek(&input["\\peek".len()..]);
+        }
+        if lowercase.split_whitespace().next() == Some("\\watch") {
+            return Self::parse_watch(&lowercase["\\watch".len()..]);
+        }
+        if lowercase.split_whitespace().next() == Some("\\copy") {
+            return Self::parse_copy(&input["\\copy".len()..]);
+        }
+        if let Some(command @ ("\\connect" | "\\c")) = lowercase.split_whitespace().next() {
+            return Self::parse_connect(&input[command.len()..]);
+        }
         match lowercase.as_str() {
             "help" => Ok(Self::Help),
             "exit" | "quit" => Ok(Self::Exit),
+            "\\e" => Ok(Self::Edit),
             _ => match input.split_once(' ') {
                 Some((maybe_use, database)) if maybe_use.to_lowercase() == "use" => {
                     Ok(Self::UseDatabase {
                         db_name: database.trim().to_string(),
                     })
                 }
-                // Any valid SQL must contains at least one whitespace.
+                // Any valid SQL must contains at least one whitespace, except the
+                // transaction statements like `BEGIN;`.
                 Some(_) if input_is_completed => Ok(Self::Sql {
                     sql: input.to_string(),
                 }),
+                None if input_is_completed && TransactionStatement::parse(input).is_some() => {
+                    Ok(Self::Sql {
+                        sql: input.to_string(),
+                    })
+                }
                 _ => InvalidReplCommandSnafu {
                     reason: format!("unknown command '{input}', maybe input is not completed"),
                 }
@@ -64,12 +124,172 @@ impl TryFrom<&str> for ReplCommand {
 }
 
 impl ReplCommand {
+    /// Parses the arguments of `\peek <table> [n]`.
+    fn parse_peek(args: &str) -> Result<Self> {
+        let mut args = args.split_whitespace();
+        let Some(table) = args.next() else {
+            return InvalidReplCommandSnafu {
+                reason: "usage: \\peek <table> [n]".to_string(),
+            }
+            .fail();
+        };
+        let limit = match args.next() {
+            Some(n) => match n.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return InvalidReplCommandSnafu {
+                        reason: format!("invalid row count '{n}' for \\peek"),
+                    }
+                    .fail()
+                }
+            },
+            None => DEFAULT_PEEK_LIMIT,
+        };
+        if let Some(extra) = args.next() {
+            return InvalidReplCommandSnafu {
+                reason: format!("unexpected argument '{extra}' for \\peek"),
+            }
+            .fail();
+        }
+        Ok(Self::Peek {
+            table: table.to_string(),
+            limit,
+        })
+    }
+
+    /// Parses the arguments of `\watch <seconds> [--stop-on-error]`.
+    fn parse_watch(args: &str) -> Result<Self> {
+        let mut args = args.split_whitespace();
+        let Some(seconds) = args.next() else {
+            return InvalidReplCommandSnafu {
+                reason: format!("usage: \\watch <seconds> [{WATCH_STOP_ON_ERROR}]"),
+            }
+            .fail();
+        };
+        let interval = match seconds.parse::<f64>() {
+            Ok(x) if x.is_finite() && x > 0.0 => Duration::from_secs_f64(x),
+            _ => {
+                return InvalidReplCommandSnafu {
+                    reason: format!("invalid interval '{seconds}' for \\watch"),
+                }
+                .fail()
+            }
+        };
+        let stop_on_error = match args.next() {
+            Some(WATCH_STOP_ON_ERROR) => true,
+            Some(extra) => {
+                return InvalidReplCommandSnafu {
+                    reason: format!("unexpected argument '{extra}' for \\watch"),
+                }
+                .fail()
+            }
+            None => false,
+        };
+        if let Some(extra) = args.next() {
+            return InvalidReplCommandSnafu {
+                reason: format!("unexpected argument '{extra}' for \\watch"),
+            }
+            .fail();
+        }
+        Ok(Self::Watch(WatchOptions {
+            interval,
+            stop_on_error,
+        }))
+    }
+
+    /// Parses the arguments of `\copy <table> TO|FROM '<file>'`.
+    fn parse_copy(args: &str) -> Result<Self> {
+        let usage = || {
+            InvalidReplCommandSnafu {
+                reason: "usage: \\copy <table> TO|FROM '<file>'".to_string(),
+            }
+            .fail()
+        };
+        let args = args.trim();
+        let Some((table, rest)) = args.split_once(char::is_whitespace) else {
+            return usage();
+        };
+        let Some((direction, path)) = rest.trim_start().split_once(char::is_whitespace) else {
+            return usage();
+        };
+        let direction = match direction.to_lowercase().as_str() {
+            "to" => CopyDirection::To,
+            "from" => CopyDirection::From,
+            _ => return usage(),
+        };
+        let path = path.trim();
+        let path = match path.strip_prefix('\'') {
+            // A quoted path, in which a quote is escaped by another quote.
+            Some(quoted) => match quoted.strip_suffix('\'') {
+                Some(quoted) if !quoted.replace("''", "").contains('\'') => {
+                    quoted.replace("''", "'")
+                }
+                _ => {
+                    return InvalidReplCommandSnafu {
+                        reason: format!("invalid quoted file {path} for \\copy"),
+                    }
+                    .fail()
+                }
+            },
+            None if !path.contains(char::is_whitespace) => path.to_string(),
+            None => {
+                return InvalidReplCommandSnafu {
+                    reason: format!("unexpected argument '{path}' for \\copy, quote the file"),
+                }
+                .fail()
+            }
+        };
+        if path.is_empty() {
+            return usage();
+        }
+        Ok(Self::Copy(CopyOptions {
+            table: table.to_string(),
+            direction,
+            path,
+        }))
+    }
+
+    /// Parses the arguments of `\connect <grpc-addr>`.
+    fn parse_connect(args: &str) -> Result<Self> {
+        let mut args = args.split_whitespace();
+        let Some(addr) = args.next() else {
+            return InvalidReplCommandSnafu {
+                reason: "usage: \\connect <grpc-addr>".to_string(),
+            }
+            .fail();
+        };
+        if let Some(extra) = args.next() {
+            return InvalidReplCommandSnafu {
+                reason: format!("unexpected argument '{extra}' for \\connect"),
+            }
+            .fail();
+        }
+        Ok(Self::Connect {
+            addr: addr.to_string(),
+        })
+    }
+
     pub fn help() -> &'static str {
         r#"
 Available commands (case insensitive):
 - 'help': print this help
 - 'exit' or 'quit': exit the REPL
 - 'use <your database name>': switch to another database/schema context
+- '\peek <table> [n]': sample the first n (default 5) rows of a table, rows
+  wider than the terminal are printed vertically
+- '\watch <seconds> [--stop-on-error]': re-run the last SQL every <seconds> and
+  redraw its result until Ctrl-C, a failed run is shown and watched again unless
+  '--stop-on-error' is given
+- '\copy <table> TO|FROM '<file>'': export a table to a CSV file, or import a CSV
+  file into a table, on the host of the REPL. The file has a header line of the
+  column names, an empty field is NULL and '""' is an empty string
+- '\connect <grpc-addr>' or '\c <grpc-addr>': switch to the server at <grpc-addr>,
+  keeping the database and the settings. The current connection is kept if the
+  server is unreachable, and can't be switched while a transaction is open
+- '\e': edit the last SQL (or an empty buffer) in $EDITOR and execute it on save,
+  nothing is executed if the editor fails or the buffer is unchanged or empty
+- 'BEGIN;', 'COMMIT;' and 'ROLLBACK;': in the '--transaction' mode, an open
+  transaction is marked by a '*' in the prompt
 - Other typed in text will be treated as SQL.
   You can enter new line while typing, just remember to end it with ';'.
 "#
@@ -142,6 +362,112 @@ mod tests {
                 sql: "SELECT * from foo".to_string(),
             },
         );
+        test_ok(
+            "\\peek foo",
+            ReplCommand::Peek {
+                table: "foo".to_string(),
+                limit: DEFAULT_PEEK_LIMIT,
+            },
+        );
+        test_ok(
+            "  \\PEEK Foo.Bar 20;  ",
+            ReplCommand::Peek {
+                table: "Foo.Bar".to_string(),
+                limit: 20,
+            },
+        );
+        test_err("\\peek");
+        test_err("\\peek foo 0");
+        test_err("\\peek foo bar");
+        test_err("\\peek foo 1 2");
+
+        test_ok(
+            "\\watch 2",
+            ReplCommand::Watch(WatchOptions {
+                interval: Duration::from_secs(2),
+                stop_on_error: false,
+            }),
+        );
+        test_ok(
+            "  \\WATCH 0.5 --Stop-On-Error;  ",
+            ReplCommand::Watch(WatchOptions {
+                interval: Duration::from_millis(500),
+                stop_on_error: true,
+            }),
+        );
+        test_err("\\watch");
+        test_err("\\watch 0");
+        test_err("\\watch -1");
+        test_err("\\watch foo");
+        test_err("\\watch 1 --stop");
+        test_err("\\watch 1 --stop-on-error 2");
+
+        test_ok(
+            "\\copy foo TO '/tmp/foo.csv'",
+            ReplCommand::Copy(CopyOptions {
+                table: "foo".to_string(),
+                direction: CopyDirection::To,
+                path: "/tmp/foo.csv".to_string(),
+            }),
+        );
+        test_ok(
+            "  \\COPY Foo.Bar from '/tmp/it''s here.csv';  ",
+            ReplCommand::Copy(CopyOptions {
+                table: "Foo.Bar".to_string(),
+                direction: CopyDirection::From,
+                path: "/tmp/it's here.csv".to_string(),
+            }),
+        );
+        test_ok(
+            "\\copy foo to foo.csv",
+            ReplCommand::Copy(CopyOptions {
+                table: "foo".to_string(),
+                direction: CopyDirection::To,
+                path: "foo.csv".to_string(),
+            }),
+        );
+        test_err("\\copy");
+        test_err("\\copy foo");
+        test_err("\\copy foo to");
+        test_err("\\copy foo into 'foo.csv'");
+        test_err("\\copy foo to ''");
+        test_err("\\copy foo to 'foo.csv");
+        test_err("\\copy foo to 'a'b'");
+        test_err("\\copy foo to foo.csv bar");
+
+        test_ok(
+            "\\connect 127.0.0.1:4001",
+            ReplCommand::Connect {
+                addr: "127.0.0.1:4001".to_string(),
+            },
+        );
+        test_ok(
+            "  \\C Frontend-2:4001;  ",
+            ReplCommand::Connect {
+                addr: "Frontend-2:4001".to_string(),
+            },
+        );
+        test_err("\\connect");
+        test_err("\\connect 127.0.0.1:4001 127.0.0.1:4002");
+
+        test_ok("\\e", ReplCommand::Edit);
+        test_ok("  \\E;  ", ReplCommand::Edit);
+        test_err("\\e foo");
+
+        test_ok(
+            "BEGIN;",
+            ReplCommand::Sql {
+                sql: "BEGIN".to_string(),
+            },
+        );
+        test_ok(
+            " commit ; ",
+            ReplCommand::Sql {
+                sql: "commit".to_string(),
+            },
+        );
+        test_err("ROLLBACK");
+
         // Input line (that don't belong to any other cases above) must ends with ';' to make it a valid SQL.
         test_err("insert blah");
         test_ok(
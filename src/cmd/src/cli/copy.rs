@@ -0,0 +1,376 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! CSV files of the REPL `\copy` command, which copies a table between the server and
+//! the host of the REPL, unlike `COPY` that reads and writes the files on the server.
+//!
+//! The first line of a file is the column names. A field is quoted if it contains a
+//! comma, a quote or a line break, an empty field is NULL and `""` is an empty string.
+//! The rows are written and read by batches, so a large file is never loaded entirely.
+
+use common_recordbatch::RecordBatch;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::value::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Number of rows inserted by a statement of `\copy FROM`.
+pub(crate) const INSERT_BATCH_ROWS: usize = 1000;
+
+/// Appends a CSV record of the `fields` to `buf`, `None` is NULL.
+pub(crate) fn push_record<I, S>(buf: &mut String, fields: I)
+where
+    I: IntoIterator<Item = Option<S>>,
+    S: AsRef<str>,
+{
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            buf.push(',');
+        }
+        let Some(field) = field else {
+            continue;
+        };
+        let field = field.as_ref();
+        if field.is_empty() || field.contains([',', '"', '\n', '\r']) {
+            buf.push('"');
+            buf.push_str(&field.replace('"', "\"\""));
+            buf.push('"');
+        } else {
+            buf.push_str(field);
+        }
+    }
+    buf.push('\n');
+}
+
+/// Appends the CSV records of the rows in `batch` to `buf`.
+pub(crate) fn push_batch(buf: &mut String, batch: &RecordBatch) {
+    for row in batch.rows() {
+        push_record(
+            buf,
+            row.into_iter().map(|value| match value {
+                Value::Null => None,
+                // Binary values are written in hex.
+                value => Some(value.to_string()),
+            }),
+        );
+    }
+}
+
+/// A field of a CSV record.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Field {
+    pub(crate) value: String,
+    /// Whether the field is quoted, which tells an empty string from NULL.
+    pub(crate) quoted: bool,
+}
+
+/// Reads the CSV records of a file line by line.
+pub(crate) struct CsvReader<R> {
+    reader: R,
+    /// Number of lines read.
+    lines: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> CsvReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self { reader, lines: 0 }
+    }
+
+    /// Reads the next record with the line it starts at, skipping the blank lines.
+    /// A quoted field may span several lines.
+    pub(crate) async fn next_record(&mut self) -> std::io::Result<Option<(usize, String)>> {
+        let mut record = String::new();
+        let mut start = 0;
+        loop {
+            let len = record.len();
+            if self.reader.read_line(&mut record).await? == 0 {
+                // An unterminated quote is reported by the parser.
+                return Ok((!record.trim().is_empty()).then_some((start, record)));
+            }
+            self.lines += 1;
+            if len == 0 {
+                if record.trim().is_empty() {
+                    record.clear();
+                    continue;
+                }
+                start = self.lines;
+            }
+            if record.matches('"').count() % 2 == 0 {
+                return Ok(Some((start, record)));
+            }
+        }
+    }
+}
+
+/// Parses the fields of a `record`, returns the reason if it's malformed.
+pub(crate) fn parse_record(record: &str) -> std::result::Result<Vec<Field>, String> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        /// At the start of a field.
+        Start,
+        Unquoted,
+        Quoted,
+        /// After a quote in a quoted field, which either escapes a quote or closes
+        /// the field.
+        QuoteInQuoted,
+    }
+
+    let record = record.strip_suffix('\n').unwrap_or(record);
+    let record = record.strip_suffix('\r').unwrap_or(record);
+    let mut fields = Vec::new();
+    let mut value = String::new();
+    let mut state = State::Start;
+    for c in record.chars() {
+        state = match (state, c) {
+            (State::Quoted, '"') => State::QuoteInQuoted,
+            (State::Quoted, c) => {
+                value.push(c);
+                State::Quoted
+            }
+            (State::QuoteInQuoted, '"') => {
+                value.push('"');
+                State::Quoted
+            }
+            (State::Start | State::Unquoted | State::QuoteInQuoted, ',') => {
+                fields.push(Field {
+                    value: std::mem::take(&mut value),
+                    quoted: state == State::QuoteInQuoted,
+                });
+                State::Start
+            }
+            (State::QuoteInQuoted, c) => {
+                return Err(format!("unexpected character '{c}' after a closing quote"))
+            }
+            (State::Start, '"') => State::Quoted,
+            (State::Unquoted, '"') => {
+                return Err("unexpected quote in an unquoted field".to_string())
+            }
+            (State::Start | State::Unquoted, c) => {
+                value.push(c);
+                State::Unquoted
+            }
+        };
+    }
+    if state == State::Quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(Field {
+        value,
+        quoted: state == State::QuoteInQuoted,
+    });
+    Ok(fields)
+}
+
+/// Converts a field to the SQL literal of a column of `data_type`, returns the reason
+/// if the value is invalid for the type.
+pub(crate) fn to_sql_literal(
+    field: &Field,
+    data_type: &ConcreteDataType,
+) -> std::result::Result<String, String> {
+    let value = field.value.as_str();
+    if value.is_empty() && !field.quoted {
+        return Ok("NULL".to_string());
+    }
+    if data_type.is_numeric() {
+        // Only plain numbers, the literal is put in the statement as is.
+        let valid = value
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+            && value.parse::<f64>().is_ok();
+        return if valid {
+            Ok(value.to_string())
+        } else {
+            Err(format!("invalid number '{value}'"))
+        };
+    }
+    if data_type.is_boolean() {
+        return match value.to_lowercase().as_str() {
+            "true" => Ok("TRUE".to_string()),
+            "false" => Ok("FALSE".to_string()),
+            _ => Err(format!("invalid boolean '{value}'")),
+        };
+    }
+    if let ConcreteDataType::Binary(_) = data_type {
+        let valid = value.len() % 2 == 0 && value.chars().all(|c| c.is_ascii_hexdigit());
+        return if valid {
+            Ok(format!("X'{value}'"))
+        } else {
+            Err(format!("invalid hex binary '{value}'"))
+        };
+    }
+    Ok(format!("'{}'", value.replace('\'', "''")))
+}
+
+/// Builds the statement inserting the `rows` of SQL literals to the `columns` of the
+/// `table`.
+pub(crate) fn insert_statement(table: &str, columns: &[String], rows: &[Vec<String>]) -> String {
+    let columns = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let rows = rows
+        .iter()
+        .map(|row| format!("({})", row.join(", ")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("INSERT INTO {table} ({columns}) VALUES {rows}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{BinaryVector, Int32Vector, StringVector};
+
+    use super::*;
+
+    #[test]
+    fn test_push_batch() {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("id", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("name", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("payload", ConcreteDataType::binary_datatype(), true),
+        ]));
+        let batch = RecordBatch::new(
+            schema,
+            vec![
+                Arc::new(Int32Vector::from(vec![Some(1), None, Some(3)])) as _,
+                Arc::new(StringVector::from(vec![
+                    Some("a,b"),
+                    Some(""),
+                    Some("say \"hi\"\nbye"),
+                ])) as _,
+                Arc::new(BinaryVector::from(vec![
+                    Some(vec![0xab, 0x01]),
+                    None,
+                    Some(vec![]),
+                ])) as _,
+            ],
+        )
+        .unwrap();
+
+        let mut buf = String::new();
+        push_record(&mut buf, ["id", "name", "payload"].map(Some));
+        push_batch(&mut buf, &batch);
+        let expected = "id,name,payload\n\
+            1,\"a,b\",ab01\n\
+            ,\"\",\n\
+            3,\"say \"\"hi\"\"\nbye\",\"\"\n";
+        assert_eq!(expected, buf);
+    }
+
+    #[tokio::test]
+    async fn test_read_records() {
+        let input = "a,b\n\n1,\"x\ny\"\r\n2,\"\"\n3,\"unterminated\n";
+        let mut reader = CsvReader::new(input.as_bytes());
+        let mut records = Vec::new();
+        while let Some(record) = reader.next_record().await.unwrap() {
+            records.push(record);
+        }
+        assert_eq!(
+            vec![
+                (1, "a,b\n".to_string()),
+                (3, "1,\"x\ny\"\r\n".to_string()),
+                (5, "2,\"\"\n".to_string()),
+                (6, "3,\"unterminated\n".to_string()),
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let field = |value: &str, quoted| Field {
+            value: value.to_string(),
+            quoted,
+        };
+        assert_eq!(
+            vec![
+                field("1", false),
+                field("", false),
+                field("", true),
+                field("a,\"b\"\nc", true),
+                field(" d ", false),
+            ],
+            parse_record("1,,\"\",\"a,\"\"b\"\"\nc\", d \r\n").unwrap()
+        );
+        assert_eq!(vec![field("", false)], parse_record("\n").unwrap());
+
+        assert!(parse_record("\"a\"b").is_err());
+        assert!(parse_record("a\"b").is_err());
+        assert!(parse_record("\"a").is_err());
+    }
+
+    #[test]
+    fn test_to_sql_literal() {
+        let literal = |value: &str, quoted, data_type| {
+            to_sql_literal(
+                &Field {
+                    value: value.to_string(),
+                    quoted,
+                },
+                &data_type,
+            )
+        };
+        let int = ConcreteDataType::int64_datatype;
+        let string = ConcreteDataType::string_datatype;
+        assert_eq!("NULL", literal("", false, int()).unwrap());
+        assert_eq!("NULL", literal("", false, string()).unwrap());
+        assert_eq!("''", literal("", true, string()).unwrap());
+        assert_eq!("-15", literal("-15", false, int()).unwrap());
+        assert_eq!(
+            "1.5e3",
+            literal("1.5e3", false, ConcreteDataType::float64_datatype()).unwrap()
+        );
+        assert_eq!("'it''s'", literal("it's", false, string()).unwrap());
+        assert_eq!(
+            "'2023-01-01 00:00:00'",
+            literal(
+                "2023-01-01 00:00:00",
+                false,
+                ConcreteDataType::timestamp_millisecond_datatype()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "TRUE",
+            literal("true", false, ConcreteDataType::boolean_datatype()).unwrap()
+        );
+        assert_eq!(
+            "X'ab01'",
+            literal("ab01", false, ConcreteDataType::binary_datatype()).unwrap()
+        );
+
+        assert!(literal("1; DROP TABLE t", false, int()).is_err());
+        assert!(literal("", true, int()).is_err());
+        assert!(literal("yes", false, ConcreteDataType::boolean_datatype()).is_err());
+        assert!(literal("abc", false, ConcreteDataType::binary_datatype()).is_err());
+    }
+
+    #[test]
+    fn test_insert_statement() {
+        assert_eq!(
+            "INSERT INTO t (\"id\", \"a\"\"b\") VALUES (1, 'x'), (NULL, '')",
+            insert_statement(
+                "t",
+                &["id".to_string(), "a\"b".to_string()],
+                &[
+                    vec!["1".to_string(), "'x'".to_string()],
+                    vec!["NULL".to_string(), "''".to_string()],
+                ]
+            )
+        );
+    }
+}
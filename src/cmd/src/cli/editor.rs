@@ -0,0 +1,113 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Editing a statement in an external editor, for the REPL `\e` command.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use snafu::ResultExt;
+
+use crate::error::{FileIoSnafu, Result};
+
+/// Editor used if `$EDITOR` is not set.
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Outcome of editing a statement.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Edited {
+    /// The edited statement to execute.
+    Statement(String),
+    /// The editor exited with an error.
+    Aborted(ExitStatus),
+    /// The buffer is left unchanged or empty.
+    Unchanged,
+}
+
+/// Opens the `sql` in `$EDITOR`, or `vi` if it's not set, and waits for the editor
+/// to exit.
+pub(crate) fn edit(sql: &str) -> Result<Edited> {
+    let path = buffer_file();
+    std::fs::write(&path, sql).context(FileIoSnafu)?;
+    let result = run_editor(&path);
+    let _ = std::fs::remove_file(&path);
+
+    Ok(match result? {
+        Ok(edited) => edited_statement(sql, &edited)
+            .map(Edited::Statement)
+            .unwrap_or(Edited::Unchanged),
+        Err(status) => Edited::Aborted(status),
+    })
+}
+
+/// Runs the editor on the file at `path`, returning the edited content, or the exit
+/// status if the editor fails.
+fn run_editor(path: &Path) -> Result<std::result::Result<String, ExitStatus>> {
+    let editor = std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    // The editor may come with arguments, like `code --wait`.
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .context(FileIoSnafu)?;
+    if !status.success() {
+        return Ok(Err(status));
+    }
+    std::fs::read_to_string(path).map(Ok).context(FileIoSnafu)
+}
+
+/// Returns the statement in the `edited` buffer, or `None` if it's empty or the
+/// same as the `original` one.
+fn edited_statement(original: &str, edited: &str) -> Option<String> {
+    let statement = edited.trim();
+    let statement = statement
+        .strip_suffix(';')
+        .map(|x| x.trim_end())
+        .unwrap_or(statement);
+    (!statement.is_empty() && statement != original.trim()).then(|| statement.to_string())
+}
+
+/// Returns a temporary file to edit the statement in, unique to this process.
+fn buffer_file() -> PathBuf {
+    std::env::temp_dir().join(format!("greptime-cli-{}.sql", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edited_statement() {
+        assert_eq!(
+            Some("select 2".to_string()),
+            edited_statement("select 1", "select 2;\n")
+        );
+        assert_eq!(
+            Some("select *\nfrom t".to_string()),
+            edited_statement("", "\nselect *\nfrom t ;\n")
+        );
+        // Unchanged, regardless of the trailing newline the editor adds.
+        assert_eq!(None, edited_statement("select 1", "select 1\n"));
+        assert_eq!(None, edited_statement("select 1", "select 1;"));
+        assert_eq!(None, edited_statement("", ""));
+        assert_eq!(None, edited_statement("select 1", "  \n"));
+        assert_eq!(None, edited_statement("select 1", ";"));
+    }
+}
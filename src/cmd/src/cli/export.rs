@@ -406,7 +406,7 @@ impl Tool for Export {
 }
 
 /// Split at `-`.
-fn split_database(database: &str) -> Result<(String, Option<String>)> {
+pub(super) fn split_database(database: &str) -> Result<(String, Option<String>)> {
     let (catalog, schema) = database
         .split_once('-')
         .with_context(|| InvalidDatabaseNameSnafu {
@@ -0,0 +1,428 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates a systemd service or a docker-compose service running a standalone
+//! instance.
+//!
+//! The ports, the data home and the stop timeout come from the options the instance
+//! starts with, loaded from the same config file and environment variables as
+//! `standalone start`, so the unit stays in sync with the options. The HTTP and gRPC
+//! servers are always listed, the MySQL, PostgreSQL and OpenTSDB servers only if
+//! enabled.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use snafu::{OptionExt, ResultExt};
+
+use crate::cli::{Instance, Tool};
+use crate::error::{FileIoSnafu, IllegalConfigSnafu, Result};
+use crate::options::{Options, ENV_VAR_SEP};
+use crate::standalone::{StandaloneOptions, DEFAULT_ENV_PREFIX};
+
+/// Image of the docker-compose service, the release of this binary.
+const DEFAULT_IMAGE: &str = concat!("greptime/greptimedb:v", env!("CARGO_PKG_VERSION"));
+
+/// Path of the config file in the container.
+const CONTAINER_CONFIG_FILE: &str = "/etc/greptimedb/standalone.toml";
+
+/// Time given to the process to exit on its own after the grace period, before
+/// systemd or docker kills it.
+const STOP_TIMEOUT_MARGIN: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum UnitKind {
+    Systemd,
+    DockerCompose,
+}
+
+#[derive(Debug, Parser)]
+pub struct GenerateUnitCommand {
+    /// Kind of the unit to generate.
+    #[clap(value_enum)]
+    kind: UnitKind,
+
+    /// Config file the instance starts with, also read for the options of the unit.
+    #[clap(short, long)]
+    config_file: Option<String>,
+
+    /// Prefix of the environment variables of the options, same as `standalone start`.
+    #[clap(long, default_value = DEFAULT_ENV_PREFIX)]
+    env_prefix: String,
+
+    /// Path of the binary in the systemd service, the running binary by default.
+    #[clap(long)]
+    binary_path: Option<String>,
+
+    /// Image of the docker-compose service.
+    #[clap(long, default_value = DEFAULT_IMAGE)]
+    image: String,
+}
+
+impl GenerateUnitCommand {
+    pub async fn build(&self) -> Result<Instance> {
+        let opts: StandaloneOptions = Options::load_layered_options(
+            self.config_file.as_deref(),
+            self.env_prefix.as_ref(),
+            None,
+        )?;
+
+        // A relative path in a unit is resolved against the working directory of the
+        // service, not the one generating it.
+        let config_file = self
+            .config_file
+            .as_ref()
+            .map(|path| {
+                std::fs::canonicalize(path)
+                    .map(|path| path.to_string_lossy().to_string())
+                    .context(FileIoSnafu)
+            })
+            .transpose()?;
+        let binary_path = match &self.binary_path {
+            Some(path) => path.clone(),
+            None => std::env::current_exe()
+                .context(FileIoSnafu)?
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        Ok(Instance::Tool(Box::new(GenerateUnit {
+            kind: self.kind,
+            unit: Unit::try_new(
+                &opts,
+                config_file,
+                &self.env_prefix,
+                binary_path,
+                &self.image,
+            )?,
+        })))
+    }
+}
+
+/// A server listening on a port.
+#[derive(Debug, PartialEq, Eq)]
+struct Port {
+    name: &'static str,
+    /// Flag of `standalone start` setting the address of the server.
+    flag: &'static str,
+    port: u16,
+}
+
+impl Port {
+    fn try_new(name: &'static str, flag: &'static str, addr: &str) -> Result<Self> {
+        let port = addr
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse().ok())
+            .with_context(|| IllegalConfigSnafu {
+                msg: format!("the {name} address '{addr}' has no valid port"),
+            })?;
+        Ok(Self { name, flag, port })
+    }
+}
+
+/// The parts of the units to generate.
+#[derive(Debug)]
+struct Unit {
+    /// Absolute path of the config file, if any.
+    config_file: Option<String>,
+    env_prefix: String,
+    binary_path: String,
+    image: String,
+    ports: Vec<Port>,
+    data_home: String,
+    stop_timeout: Duration,
+}
+
+impl Unit {
+    fn try_new(
+        opts: &StandaloneOptions,
+        config_file: Option<String>,
+        env_prefix: &str,
+        binary_path: String,
+        image: &str,
+    ) -> Result<Self> {
+        let mut ports = vec![
+            Port::try_new("HTTP", "--http-addr", &opts.http.addr)?,
+            Port::try_new("gRPC", "--rpc-addr", &opts.grpc.addr)?,
+        ];
+        if opts.mysql.enable {
+            ports.push(Port::try_new("MySQL", "--mysql-addr", &opts.mysql.addr)?);
+        }
+        if opts.postgres.enable {
+            ports.push(Port::try_new(
+                "PostgreSQL",
+                "--postgres-addr",
+                &opts.postgres.addr,
+            )?);
+        }
+        if opts.opentsdb.enable {
+            ports.push(Port::try_new(
+                "OpenTSDB",
+                "--opentsdb-addr",
+                &opts.opentsdb.addr,
+            )?);
+        }
+
+        Ok(Self {
+            config_file,
+            env_prefix: env_prefix.to_string(),
+            binary_path,
+            image: image.to_string(),
+            ports,
+            data_home: opts.storage.data_home.clone(),
+            stop_timeout: opts.shutdown_grace_period + STOP_TIMEOUT_MARGIN,
+        })
+    }
+
+    /// Arguments of the binary starting the instance with the `config_file`. The
+    /// servers listen on all the interfaces if `bind_all`, for a container.
+    fn start_args(&self, config_file: Option<&str>, bind_all: bool) -> Vec<String> {
+        let mut args = vec!["standalone".to_string(), "start".to_string()];
+        if let Some(config_file) = config_file {
+            args.extend(["--config-file".to_string(), config_file.to_string()]);
+        }
+        if self.env_prefix != DEFAULT_ENV_PREFIX {
+            args.extend(["--env-prefix".to_string(), self.env_prefix.clone()]);
+        }
+        if bind_all {
+            for port in &self.ports {
+                args.extend([port.flag.to_string(), format!("0.0.0.0:{}", port.port)]);
+            }
+        }
+        args
+    }
+
+    /// An example of the environment variables overriding the options.
+    fn env_example(&self) -> String {
+        format!(
+            "{}{ENV_VAR_SEP}LOGGING{ENV_VAR_SEP}LEVEL=info",
+            self.env_prefix
+        )
+    }
+
+    fn ports_comment(&self) -> String {
+        self.ports
+            .iter()
+            .map(|port| format!("{} {}", port.name, port.port))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn systemd(&self) -> String {
+        let exec_start = std::iter::once(self.binary_path.clone())
+            .chain(self.start_args(self.config_file.as_deref(), false))
+            .map(|arg| systemd_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut unit = String::new();
+        let _ = writeln!(unit, "[Unit]");
+        let _ = writeln!(unit, "Description=GreptimeDB standalone");
+        let _ = writeln!(unit, "Documentation=https://docs.greptime.com");
+        let _ = writeln!(unit, "After=network-online.target");
+        let _ = writeln!(unit, "Wants=network-online.target");
+        let _ = writeln!(unit);
+        let _ = writeln!(unit, "[Service]");
+        let _ = writeln!(unit, "# Listens on {}.", self.ports_comment());
+        let _ = writeln!(unit, "# Stores the data in {}.", self.data_home);
+        let _ = writeln!(unit, "Type=simple");
+        let _ = writeln!(unit, "ExecStart={exec_start}");
+        let _ = writeln!(unit, "# Environment={}", self.env_example());
+        let _ = writeln!(unit, "Restart=on-failure");
+        let _ = writeln!(unit, "KillSignal=SIGTERM");
+        let _ = writeln!(unit, "TimeoutStopSec={}", self.stop_timeout.as_secs());
+        let _ = writeln!(unit, "LimitNOFILE=1048576");
+        let _ = writeln!(unit);
+        let _ = writeln!(unit, "[Install]");
+        let _ = writeln!(unit, "WantedBy=multi-user.target");
+        unit
+    }
+
+    fn docker_compose(&self) -> String {
+        let config_file = self.config_file.as_ref().map(|_| CONTAINER_CONFIG_FILE);
+        let command = self
+            .start_args(config_file, true)
+            .iter()
+            .map(|arg| yaml_quote(arg))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut unit = String::new();
+        let _ = writeln!(unit, "services:");
+        let _ = writeln!(unit, "  greptimedb:");
+        let _ = writeln!(unit, "    image: {}", yaml_quote(&self.image));
+        let _ = writeln!(unit, "    command: [{command}]");
+        let _ = writeln!(unit, "    # Listens on {}.", self.ports_comment());
+        let _ = writeln!(unit, "    ports:");
+        for port in &self.ports {
+            let _ = writeln!(unit, "      - \"{0}:{0}\"", port.port);
+        }
+        let _ = writeln!(unit, "    volumes:");
+        if let Some(path) = &self.config_file {
+            let volume = format!("{path}:{CONTAINER_CONFIG_FILE}:ro");
+            let _ = writeln!(unit, "      - {}", yaml_quote(&volume));
+        }
+        let volume = format!("greptimedb_data:{}", self.data_home);
+        let _ = writeln!(unit, "      - {}", yaml_quote(&volume));
+        let _ = writeln!(unit, "    # environment:");
+        let _ = writeln!(unit, "    #   - {}", self.env_example());
+        let _ = writeln!(unit, "    stop_signal: SIGTERM");
+        let _ = writeln!(
+            unit,
+            "    stop_grace_period: {}s",
+            self.stop_timeout.as_secs()
+        );
+        let _ = writeln!(unit, "    restart: on-failure");
+        let _ = writeln!(unit);
+        let _ = writeln!(unit, "volumes:");
+        let _ = writeln!(unit, "  greptimedb_data:");
+        unit
+    }
+}
+
+/// Quotes an argument of a systemd command line if it has whitespaces or quotes.
+fn systemd_quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "\"'\\".contains(c)) {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Quotes a YAML scalar in double quotes.
+fn yaml_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub struct GenerateUnit {
+    kind: UnitKind,
+    unit: Unit,
+}
+
+#[async_trait]
+impl Tool for GenerateUnit {
+    #[allow(clippy::print_stdout)]
+    async fn do_work(&self) -> Result<()> {
+        let unit = match self.kind {
+            UnitKind::Systemd => self.unit.systemd(),
+            UnitKind::DockerCompose => self.unit.docker_compose(),
+        };
+        print!("{unit}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_unit(config_file: Option<&str>, env_prefix: &str) -> Unit {
+        Unit::try_new(
+            &StandaloneOptions::default(),
+            config_file.map(String::from),
+            env_prefix,
+            "/usr/local/bin/greptime".to_string(),
+            "greptime/greptimedb:latest",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_systemd() {
+        let unit = default_unit(Some("/etc/greptimedb/my config.toml"), DEFAULT_ENV_PREFIX);
+        let expected = r#"[Unit]
+Description=GreptimeDB standalone
+Documentation=https://docs.greptime.com
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+# Listens on HTTP 4000, gRPC 4001, MySQL 4002, PostgreSQL 4003, OpenTSDB 4242.
+# Stores the data in /tmp/greptimedb.
+Type=simple
+ExecStart=/usr/local/bin/greptime standalone start --config-file "/etc/greptimedb/my config.toml"
+# Environment=GREPTIMEDB_STANDALONE__LOGGING__LEVEL=info
+Restart=on-failure
+KillSignal=SIGTERM
+TimeoutStopSec=40
+LimitNOFILE=1048576
+
+[Install]
+WantedBy=multi-user.target
+"#;
+        assert_eq!(expected, unit.systemd());
+    }
+
+    #[test]
+    fn test_docker_compose() {
+        let unit = default_unit(Some("/etc/greptimedb/standalone.toml"), "GREPTIMEDB");
+        let expected = r#"services:
+  greptimedb:
+    image: "greptime/greptimedb:latest"
+    command: ["standalone", "start", "--config-file", "/etc/greptimedb/standalone.toml", "--env-prefix", "GREPTIMEDB", "--http-addr", "0.0.0.0:4000", "--rpc-addr", "0.0.0.0:4001", "--mysql-addr", "0.0.0.0:4002", "--postgres-addr", "0.0.0.0:4003", "--opentsdb-addr", "0.0.0.0:4242"]
+    # Listens on HTTP 4000, gRPC 4001, MySQL 4002, PostgreSQL 4003, OpenTSDB 4242.
+    ports:
+      - "4000:4000"
+      - "4001:4001"
+      - "4002:4002"
+      - "4003:4003"
+      - "4242:4242"
+    volumes:
+      - "/etc/greptimedb/standalone.toml:/etc/greptimedb/standalone.toml:ro"
+      - "greptimedb_data:/tmp/greptimedb"
+    # environment:
+    #   - GREPTIMEDB__LOGGING__LEVEL=info
+    stop_signal: SIGTERM
+    stop_grace_period: 40s
+    restart: on-failure
+
+volumes:
+  greptimedb_data:
+"#;
+        assert_eq!(expected, unit.docker_compose());
+    }
+
+    #[test]
+    fn test_start_args_are_valid() {
+        let unit = default_unit(Some("/etc/greptimedb/standalone.toml"), "GREPTIMEDB");
+        for bind_all in [false, true] {
+            let args = unit.start_args(unit.config_file.as_deref(), bind_all);
+            let cmd = crate::standalone::Command::try_parse_from(
+                std::iter::once("greptime".to_string()).chain(args),
+            );
+            assert!(cmd.is_ok(), "{:?}", cmd.err());
+        }
+    }
+
+    #[test]
+    fn test_disabled_servers() {
+        let mut opts = StandaloneOptions::default();
+        opts.mysql.enable = false;
+        opts.opentsdb.enable = false;
+        let unit =
+            Unit::try_new(&opts, None, DEFAULT_ENV_PREFIX, "greptime".to_string(), "").unwrap();
+        assert_eq!(
+            vec!["HTTP", "gRPC", "PostgreSQL"],
+            unit.ports.iter().map(|port| port.name).collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["standalone", "start"], unit.start_args(None, false));
+
+        opts.http.addr = "localhost".to_string();
+        assert!(
+            Unit::try_new(&opts, None, DEFAULT_ENV_PREFIX, "greptime".to_string(), "").is_err()
+        );
+    }
+}
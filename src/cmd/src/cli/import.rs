@@ -0,0 +1,457 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Imports the files written by `export`.
+//!
+//! Each exported `CREATE TABLE` statement and each exported data file is a unit of
+//! the import. The units imported successfully are appended to a checkpoint file in
+//! the input directory, so an interrupted import re-run with `--resume` skips them.
+//!
+//! A data file is imported by a single `COPY FROM`, so a unit interrupted in the middle
+//! may be partly imported. Importing it again doesn't duplicate the rows, because rows
+//! with the same tags and timestamp overwrite each other.
+
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use clap::{Parser, ValueEnum};
+use client::api::v1::auth_header::AuthScheme;
+use client::api::v1::Basic;
+use client::{Client, Database, DEFAULT_SCHEMA_NAME};
+use common_telemetry::{error, info, warn};
+use snafu::{OptionExt, ResultExt};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::cli::export::split_database;
+use crate::cli::{Instance, Tool};
+use crate::error::{
+    ConnectServerSnafu, Error, FileIoSnafu, IllegalConfigSnafu, RequestDatabaseSnafu, Result,
+};
+
+/// Name of the checkpoint file in the input directory.
+const CHECKPOINT_FILE: &str = ".import_checkpoint";
+
+/// Interval before the first retry of a failed statement, which grows with the retries.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default, Clone, ValueEnum)]
+enum ImportTarget {
+    /// The `CREATE TABLE` statements exported by the `create-table` target
+    #[default]
+    CreateTable,
+    /// The data files exported by the `table-data` target
+    TableData,
+}
+
+#[derive(Debug, Default, Parser)]
+pub struct ImportCommand {
+    /// Server address to connect
+    #[clap(long)]
+    addr: String,
+
+    /// Directory of the exported data. E.g.: /tmp/greptimedb-export
+    #[clap(long)]
+    input_dir: String,
+
+    /// The name of the catalog to import. Default to "greptime-*"".
+    #[clap(long, default_value = "")]
+    database: String,
+
+    /// Parallelism of the import.
+    #[clap(long, short = 'j', default_value = "1")]
+    import_jobs: usize,
+
+    /// Max retry times for each statement.
+    #[clap(long, default_value = "3")]
+    max_retry: usize,
+
+    /// Things to import
+    #[clap(long, short = 't', value_enum)]
+    target: ImportTarget,
+
+    /// Skip the units imported by the previous runs, which are recorded in the
+    /// checkpoint file of the input directory.
+    #[clap(long)]
+    resume: bool,
+
+    /// basic authentication for connecting to the server
+    #[clap(long)]
+    auth_basic: Option<String>,
+}
+
+impl ImportCommand {
+    pub async fn build(&self) -> Result<Instance> {
+        let client = Client::with_urls([self.addr.clone()]);
+        client
+            .health_check()
+            .await
+            .with_context(|_| ConnectServerSnafu {
+                addr: self.addr.clone(),
+            })?;
+        let (catalog, schema) = split_database(&self.database)?;
+        let mut database_client = Database::new(
+            catalog.clone(),
+            schema.clone().unwrap_or(DEFAULT_SCHEMA_NAME.to_string()),
+            client,
+        );
+
+        if let Some(auth_basic) = &self.auth_basic {
+            let (username, password) = auth_basic.split_once(':').context(IllegalConfigSnafu {
+                msg: "auth_basic cannot be split by ':'".to_string(),
+            })?;
+            database_client.set_auth(AuthScheme::Basic(Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            }));
+        }
+
+        Ok(Instance::Tool(Box::new(Import {
+            client: database_client,
+            catalog,
+            schema,
+            input_dir: self.input_dir.clone(),
+            parallelism: self.import_jobs,
+            max_retry: self.max_retry,
+            target: self.target.clone(),
+            resume: self.resume,
+        })))
+    }
+}
+
+/// A statement to import, identified by the key recorded in the checkpoint.
+struct Unit {
+    key: String,
+    sql: String,
+}
+
+/// Units of a database, which are imported in order.
+struct Job {
+    catalog: String,
+    schema: String,
+    units: Vec<Unit>,
+}
+
+pub struct Import {
+    client: Database,
+    catalog: String,
+    schema: Option<String>,
+    input_dir: String,
+    parallelism: usize,
+    max_retry: usize,
+    target: ImportTarget,
+    resume: bool,
+}
+
+impl Import {
+    /// Lists the jobs of the databases exported to the input directory, which are named
+    /// `<catalog>-<schema>.sql` for the `CREATE TABLE`s and `<catalog>-<schema>/` for
+    /// the data.
+    async fn list_jobs(&self) -> Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.input_dir)
+            .await
+            .context(FileIoSnafu)?;
+        while let Some(entry) = entries.next_entry().await.context(FileIoSnafu)? {
+            let Ok(file_name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let db_name = match self.target {
+                ImportTarget::CreateTable => file_name
+                    .strip_suffix(".sql")
+                    .filter(|name| !name.ends_with("_copy_from")),
+                ImportTarget::TableData => entry
+                    .file_type()
+                    .await
+                    .context(FileIoSnafu)?
+                    .is_dir()
+                    .then_some(file_name.as_str()),
+            };
+            let Some((catalog, schema)) = db_name.and_then(|name| name.split_once('-')) else {
+                continue;
+            };
+            if catalog != self.catalog || self.schema.as_ref().is_some_and(|s| s != schema) {
+                continue;
+            }
+
+            let units = match self.target {
+                ImportTarget::CreateTable => {
+                    let content = tokio::fs::read_to_string(entry.path())
+                        .await
+                        .context(FileIoSnafu)?;
+                    create_table_units(&file_name, &content)
+                }
+                ImportTarget::TableData => table_data_units(&file_name, &entry.path()).await?,
+            };
+            jobs.push(Job {
+                catalog: catalog.to_string(),
+                schema: schema.to_string(),
+                units,
+            });
+        }
+        jobs.sort_unstable_by(|a, b| (&a.catalog, &a.schema).cmp(&(&b.catalog, &b.schema)));
+        Ok(jobs)
+    }
+
+    /// Executes the statement, retrying at most `max_retry` times on failure.
+    async fn execute(&self, client: &Database, sql: &str) -> Result<()> {
+        let mut retry = 0;
+        loop {
+            match client.sql(sql).await {
+                Ok(_) => return Ok(()),
+                Err(e) if retry < self.max_retry => {
+                    retry += 1;
+                    warn!(e; "Failed to execute {sql}, retry {retry}/{}", self.max_retry);
+                    tokio::time::sleep(RETRY_INTERVAL * retry as u32).await;
+                }
+                Err(e) => return Err(e).context(RequestDatabaseSnafu { sql }),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for Import {
+    async fn do_work(&self) -> Result<()> {
+        let checkpoint = Checkpoint::open(
+            &Path::new(&self.input_dir).join(CHECKPOINT_FILE),
+            self.resume,
+        )
+        .await?;
+        let checkpoint = &checkpoint;
+        let semaphore = Arc::new(Semaphore::new(self.parallelism));
+        let jobs = self.list_jobs().await?;
+        let job_count = jobs.len();
+        let mut tasks = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            let semaphore_moved = semaphore.clone();
+            tasks.push(async move {
+                let _permit = semaphore_moved.acquire().await.unwrap();
+                let mut client = self.client.clone();
+                client.set_catalog(job.catalog.clone());
+                client.set_schema(job.schema.clone());
+
+                let mut skipped = 0;
+                for unit in &job.units {
+                    if checkpoint.is_done(&unit.key) {
+                        skipped += 1;
+                        continue;
+                    }
+                    self.execute(&client, &unit.sql).await?;
+                    checkpoint.record(&unit.key).await?;
+                }
+                info!(
+                    "finished importing {}.{} with {} units, {skipped} skipped by the checkpoint",
+                    job.catalog,
+                    job.schema,
+                    job.units.len()
+                );
+                Ok::<(), Error>(())
+            });
+        }
+
+        let success = futures::future::join_all(tasks)
+            .await
+            .into_iter()
+            .filter(|r| match r {
+                Ok(_) => true,
+                Err(e) => {
+                    error!(e; "import job failed");
+                    false
+                }
+            })
+            .count();
+
+        info!("success {success}/{job_count} jobs");
+        if success < job_count {
+            warn!("Re-run with --resume to skip the units already imported");
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the `CREATE TABLE` statements exported to `file_name`.
+fn create_table_units(file_name: &str, content: &str) -> Vec<Unit> {
+    content
+        .split(";\n")
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+        .enumerate()
+        .map(|(i, statement)| Unit {
+            key: format!("{file_name}#{i}"),
+            sql: format!("{statement};"),
+        })
+        .collect()
+}
+
+/// Lists the data files of the tables exported to the directory `dir_name`.
+async fn table_data_units(dir_name: &str, dir: &Path) -> Result<Vec<Unit>> {
+    let mut units = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.context(FileIoSnafu)?;
+    while let Some(entry) = entries.next_entry().await.context(FileIoSnafu)? {
+        let Ok(file_name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let Some(table_name) = file_name.strip_suffix(".parquet") else {
+            continue;
+        };
+        units.push(Unit {
+            key: format!("{dir_name}/{file_name}"),
+            sql: format!(
+                "copy {} from '{}' with (format='parquet');",
+                table_name,
+                entry.path().to_str().unwrap()
+            ),
+        });
+    }
+    units.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+    Ok(units)
+}
+
+/// Records the keys of the imported units, one per line.
+struct Checkpoint {
+    /// Keys recorded by the previous runs.
+    done: HashSet<String>,
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint file at `path`, which is cleared unless `resume` is set.
+    async fn open(path: &Path, resume: bool) -> Result<Self> {
+        let content = if resume {
+            match tokio::fs::read_to_string(path).await {
+                Ok(content) => content,
+                Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e).context(FileIoSnafu),
+            }
+        } else {
+            String::new()
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resume)
+            .truncate(!resume)
+            .open(path)
+            .await
+            .context(FileIoSnafu)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            // Terminates the line left incomplete by an interrupted run.
+            file.write_all(b"\n").await.context(FileIoSnafu)?;
+        }
+        if resume {
+            info!("Resuming the import from the checkpoint {}", path.display());
+        }
+
+        Ok(Self {
+            done: parse_checkpoint(&content),
+            file: Mutex::new(file),
+        })
+    }
+
+    fn is_done(&self, key: &str) -> bool {
+        self.done.contains(key)
+    }
+
+    /// Appends the key and syncs it to the disk, so it survives a crash.
+    async fn record(&self, key: &str) -> Result<()> {
+        let mut file = self.file.lock().await;
+        file.write_all(format!("{key}\n").as_bytes())
+            .await
+            .context(FileIoSnafu)?;
+        file.sync_data().await.context(FileIoSnafu)
+    }
+}
+
+/// Parses the keys of the checkpoint, ignoring the incomplete last line.
+fn parse_checkpoint(content: &str) -> HashSet<String> {
+    content
+        .split_inclusive('\n')
+        .filter_map(|line| line.strip_suffix('\n'))
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_create_table_units() {
+        let content = "CREATE TABLE IF NOT EXISTS \"a\" (\n  ts TIMESTAMP TIME INDEX\n);\n\
+            CREATE TABLE IF NOT EXISTS \"b\" (\n  ts TIMESTAMP TIME INDEX\n);\n";
+        let units = create_table_units("greptime-public.sql", content);
+        assert_eq!(2, units.len());
+        assert_eq!("greptime-public.sql#0", units[0].key);
+        assert_eq!(
+            "CREATE TABLE IF NOT EXISTS \"a\" (\n  ts TIMESTAMP TIME INDEX\n);",
+            units[0].sql
+        );
+        assert_eq!("greptime-public.sql#1", units[1].key);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint() {
+        let dir = create_temp_dir("test_import_checkpoint");
+        let path = dir.path().join(CHECKPOINT_FILE);
+
+        let checkpoint = Checkpoint::open(&path, true).await.unwrap();
+        assert!(!checkpoint.is_done("greptime-public/a.parquet"));
+        checkpoint
+            .record("greptime-public/a.parquet")
+            .await
+            .unwrap();
+        checkpoint
+            .record("greptime-public/b.parquet")
+            .await
+            .unwrap();
+        drop(checkpoint);
+
+        // Simulates a run interrupted while recording a key.
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(b"greptime-public/c.par").await.unwrap();
+        file.flush().await.unwrap();
+        drop(file);
+
+        let checkpoint = Checkpoint::open(&path, true).await.unwrap();
+        assert!(checkpoint.is_done("greptime-public/a.parquet"));
+        assert!(checkpoint.is_done("greptime-public/b.parquet"));
+        assert!(!checkpoint.is_done("greptime-public/c.par"));
+        checkpoint
+            .record("greptime-public/c.parquet")
+            .await
+            .unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(&path, true).await.unwrap();
+        assert!(checkpoint.is_done("greptime-public/c.parquet"));
+        drop(checkpoint);
+
+        // Starts over without resuming.
+        let checkpoint = Checkpoint::open(&path, false).await.unwrap();
+        assert!(!checkpoint.is_done("greptime-public/a.parquet"));
+        drop(checkpoint);
+        assert!(tokio::fs::read_to_string(&path).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tails the logs of a server by `GET /admin/logs/stream`, which the server serves if
+//! both `http.enable_log_stream` and `logging.enable_log_stream` are set.
+
+use async_trait::async_trait;
+use clap::Parser;
+use snafu::{OptionExt, ResultExt};
+
+use crate::cli::{Instance, Tool};
+use crate::error::{IllegalConfigSnafu, Result, StreamLogsSnafu};
+
+#[derive(Debug, Default, Parser)]
+pub struct LogsCommand {
+    /// HTTP address of the server to connect, e.g. 127.0.0.1:4000
+    #[clap(long)]
+    addr: String,
+
+    /// Number of recent lines to print before the live lines.
+    #[clap(long, default_value = "100")]
+    recent: usize,
+
+    /// basic authentication for connecting to the server
+    #[clap(long)]
+    auth_basic: Option<String>,
+}
+
+impl LogsCommand {
+    pub async fn build(&self) -> Result<Instance> {
+        let auth = self
+            .auth_basic
+            .as_ref()
+            .map(|auth_basic| {
+                auth_basic
+                    .split_once(':')
+                    .map(|(username, password)| (username.to_string(), password.to_string()))
+                    .context(IllegalConfigSnafu {
+                        msg: "auth_basic cannot be split by ':'".to_string(),
+                    })
+            })
+            .transpose()?;
+
+        let addr = if self.addr.starts_with("http://") || self.addr.starts_with("https://") {
+            self.addr.clone()
+        } else {
+            format!("http://{}", self.addr)
+        };
+
+        Ok(Instance::Tool(Box::new(Logs {
+            url: format!("{addr}/admin/logs/stream?recent={}", self.recent),
+            auth,
+        })))
+    }
+}
+
+pub struct Logs {
+    url: String,
+    /// Username and password of the basic authentication.
+    auth: Option<(String, String)>,
+}
+
+#[async_trait]
+impl Tool for Logs {
+    #[allow(clippy::print_stdout)]
+    async fn do_work(&self) -> Result<()> {
+        let mut request = reqwest::Client::new().get(&self.url);
+        if let Some((username, password)) = &self.auth {
+            request = request.basic_auth(username, Some(password));
+        }
+        let mut response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context(StreamLogsSnafu { url: &self.url })?;
+
+        let mut parser = EventParser::default();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .context(StreamLogsSnafu { url: &self.url })?
+        {
+            for line in parser.feed(&chunk) {
+                println!("{line}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parser of the server-sent events, which returns the data of each event.
+#[derive(Default)]
+struct EventParser {
+    /// Bytes of the incomplete line.
+    buf: Vec<u8>,
+    /// Data lines of the incomplete event.
+    data: Vec<String>,
+}
+
+impl EventParser {
+    /// Feeds a chunk of the stream, returns the data of the completed events.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buf.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|x| *x == b'\n') {
+            let line = self.buf.drain(..=pos).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+
+            if line.is_empty() {
+                // An empty line ends the event.
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data
+                    .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+            // Comments, like the keep alive, and other fields are ignored.
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let mut parser = EventParser::default();
+        assert!(parser.feed(b"data: first li").is_empty());
+        assert_eq!(vec!["first line"], parser.feed(b"ne\n\n:\n\n"));
+        assert_eq!(
+            vec!["second\nline", "third"],
+            parser.feed(b"data: second\r\ndata:line\r\n\r\ndata: third\n\ndata: fo")
+        );
+        assert_eq!(vec!["fourth"], parser.feed(b"urth\n\n"));
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Formats of the query results printed by the REPL, set by `--output-format`.
+//!
+//! - `table`, the default: a bordered table, NULL is an empty cell and a binary value
+//!   is in hex.
+//! - `csv`: the column names on the first line then a record per row, like the files
+//!   of `\copy`. An empty field is NULL and `""` is an empty string, a binary value is
+//!   in hex.
+//! - `json`: a JSON object per row and per line (newline-delimited JSON), keyed by the
+//!   column names. NULL is `null`, the numbers and booleans are JSON numbers and
+//!   booleans, a binary value is a string in hex, and the other values (timestamps,
+//!   dates, etc.) are strings as displayed in the table. A NaN or infinite float is the
+//!   string `NaN`, `inf` or `-inf`.
+
+use clap::ValueEnum;
+use common_recordbatch::RecordBatches;
+use datatypes::value::Value;
+use snafu::ResultExt;
+
+use crate::cli::copy;
+use crate::error::{PrettyPrintRecordBatchesSnafu, Result};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    #[default]
+    Table,
+    Csv,
+    Json,
+}
+
+/// Renders the `recordbatches` in the `format`, always ends with a line break unless
+/// it's empty.
+pub(crate) fn render(format: OutputFormat, recordbatches: &RecordBatches) -> Result<String> {
+    let mut buf = String::new();
+    match format {
+        OutputFormat::Table => {
+            buf = recordbatches
+                .pretty_print()
+                .context(PrettyPrintRecordBatchesSnafu)?;
+            buf.push('\n');
+        }
+        OutputFormat::Csv => {
+            let schema = recordbatches.schema();
+            let columns = schema.column_schemas().iter().map(|c| Some(&c.name));
+            copy::push_record(&mut buf, columns);
+            for batch in recordbatches.iter() {
+                copy::push_batch(&mut buf, batch);
+            }
+        }
+        OutputFormat::Json => {
+            let schema = recordbatches.schema();
+            for batch in recordbatches.iter() {
+                for row in batch.rows() {
+                    // Written by hand to keep the keys in the order of the columns.
+                    buf.push('{');
+                    for (i, (column, value)) in schema.column_schemas().iter().zip(row).enumerate()
+                    {
+                        if i > 0 {
+                            buf.push(',');
+                        }
+                        buf.push_str(&serde_json::Value::from(column.name.as_str()).to_string());
+                        buf.push(':');
+                        buf.push_str(&json_value(value).to_string());
+                    }
+                    buf.push_str("}\n");
+                }
+            }
+        }
+    }
+    Ok(buf)
+}
+
+fn json_value(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Boolean(v) => v.into(),
+        Value::UInt8(v) => v.into(),
+        Value::UInt16(v) => v.into(),
+        Value::UInt32(v) => v.into(),
+        Value::UInt64(v) => v.into(),
+        Value::Int8(v) => v.into(),
+        Value::Int16(v) => v.into(),
+        Value::Int32(v) => v.into(),
+        Value::Int64(v) => v.into(),
+        Value::Float32(v) => json_float(v.0.into()),
+        Value::Float64(v) => json_float(v.0),
+        Value::String(v) => v.as_utf8().into(),
+        // Binary values are displayed in hex.
+        value => value.to_string().into(),
+    }
+}
+
+/// JSON can't represent a NaN or infinite float, which is a string instead.
+fn json_float(v: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(v).map_or_else(|| v.to_string().into(), Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_recordbatch::RecordBatch;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{BinaryVector, Float64Vector, Int64Vector, StringVector};
+
+    use super::*;
+
+    fn recordbatches() -> RecordBatches {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new("count", ConcreteDataType::int64_datatype(), true),
+            ColumnSchema::new("payload", ConcreteDataType::binary_datatype(), true),
+        ]));
+        let batch = RecordBatch::new(
+            schema.clone(),
+            vec![
+                Arc::new(StringVector::from(vec![Some("a,b"), None])) as _,
+                Arc::new(Float64Vector::from(vec![Some(0.5), Some(f64::NAN)])) as _,
+                Arc::new(Int64Vector::from(vec![Some(1), None])) as _,
+                Arc::new(BinaryVector::from(vec![Some(vec![0xde, 0xad]), None])) as _,
+            ],
+        )
+        .unwrap();
+        RecordBatches::try_new(schema, vec![batch]).unwrap()
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let csv = render(OutputFormat::Csv, &recordbatches()).unwrap();
+        assert_eq!("host,cpu,count,payload\n\"a,b\",0.5,1,dead\n,NaN,,\n", csv);
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = render(OutputFormat::Json, &recordbatches()).unwrap();
+        assert_eq!(
+            r#"{"host":"a,b","cpu":0.5,"count":1,"payload":"dead"}
+{"host":null,"cpu":"NaN","count":null,"payload":null}
+"#,
+            json
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = render(OutputFormat::Table, &recordbatches()).unwrap();
+        assert!(table.starts_with("+------+"));
+        assert!(table.ends_with("+\n"));
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering of the rows sampled by the REPL `\peek` command.
+
+use common_recordbatch::RecordBatches;
+use datatypes::value::Value;
+
+/// Terminal width used when it can't be detected.
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Max number of bytes of a binary value shown in the hex preview.
+const BINARY_PREVIEW_BYTES: usize = 16;
+
+/// Returns the width of the terminal, read from `$COLUMNS`.
+pub(crate) fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|x| x.trim().parse::<usize>().ok())
+        .filter(|x| *x > 0)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Renders the sampled rows as a table, or vertically (one `column: value` line per
+/// column, like MySQL's `\G`) if the table would be wider than `max_width`.
+pub(crate) fn render(recordbatches: &RecordBatches, max_width: usize) -> String {
+    let schema = recordbatches.schema();
+    let header = schema
+        .column_schemas()
+        .iter()
+        .map(|c| c.name.clone())
+        .collect::<Vec<_>>();
+
+    let mut rows = Vec::new();
+    for batch in recordbatches.iter() {
+        for row in 0..batch.num_rows() {
+            let values = (0..batch.num_columns())
+                .map(|col| format_value(&batch.column(col).get(row)))
+                .collect::<Vec<_>>();
+            rows.push(values);
+        }
+    }
+
+    let widths = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            rows.iter()
+                .map(|row| display_width(&row[i]))
+                .chain(std::iter::once(display_width(name)))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    // Every column is padded by one space on both sides and followed by a '|'.
+    let table_width = widths.iter().map(|w| w + 3).sum::<usize>() + 1;
+
+    if table_width <= max_width {
+        render_table(&header, &rows, &widths)
+    } else {
+        render_vertical(&header, &rows)
+    }
+}
+
+fn render_table(header: &[String], rows: &[Vec<String>], widths: &[usize]) -> String {
+    let separator = widths.iter().fold("+".to_string(), |mut s, w| {
+        s.push_str(&"-".repeat(w + 2));
+        s.push('+');
+        s
+    });
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(widths)
+            .fold("|".to_string(), |mut s, (value, w)| {
+                s.push(' ');
+                s.push_str(value);
+                s.push_str(&" ".repeat(w - display_width(value) + 1));
+                s.push('|');
+                s
+            })
+    };
+
+    let mut lines = vec![separator.clone(), format_row(header), separator.clone()];
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines.push(separator);
+    lines.join("\n")
+}
+
+fn render_vertical(header: &[String], rows: &[Vec<String>]) -> String {
+    let name_width = header
+        .iter()
+        .map(|name| display_width(name))
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        lines.push(format!(
+            "*************************** {}. row ***************************",
+            i + 1
+        ));
+        for (name, value) in header.iter().zip(row) {
+            let padding = " ".repeat(name_width - display_width(name));
+            lines.push(format!("{padding}{name}: {value}"));
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Binary(bytes) => {
+            let preview = bytes
+                .iter()
+                .take(BINARY_PREVIEW_BYTES)
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>();
+            if bytes.len() > BINARY_PREVIEW_BYTES {
+                format!("0x{preview}... ({} bytes)", bytes.len())
+            } else {
+                format!("0x{preview}")
+            }
+        }
+        // Keep each row on one line, no matter what is stored in the strings.
+        Value::String(s) => s.as_utf8().escape_debug().to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn display_width(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_recordbatch::RecordBatch;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{BinaryVector, Int32Vector, StringVector};
+
+    use super::*;
+
+    fn sample() -> RecordBatches {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("id", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("name", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("payload", ConcreteDataType::binary_datatype(), true),
+        ]));
+        let batch = RecordBatch::new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Vector::from(vec![Some(1), None])) as _,
+                Arc::new(StringVector::from(vec!["a", "bc"])) as _,
+                Arc::new(BinaryVector::from(vec![vec![0xab, 0x01], vec![0xff; 20]])) as _,
+            ],
+        )
+        .unwrap();
+        RecordBatches::try_new(schema, vec![batch]).unwrap()
+    }
+
+    #[test]
+    fn test_render_table() {
+        let expected = r#"
++------+------+--------------------------------------------------+
+| id   | name | payload                                          |
++------+------+--------------------------------------------------+
+| 1    | a    | 0xab01                                           |
+| NULL | bc   | 0xffffffffffffffffffffffffffffffff... (20 bytes) |
++------+------+--------------------------------------------------+"#;
+        assert_eq!(expected.trim_start(), render(&sample(), 200));
+    }
+
+    #[test]
+    fn test_render_vertical() {
+        let expected = r#"*************************** 1. row ***************************
+     id: 1
+   name: a
+payload: 0xab01
+*************************** 2. row ***************************
+     id: NULL
+   name: bc
+payload: 0xffffffffffffffffffffffffffffffff... (20 bytes)"#;
+        assert_eq!(expected, render(&sample(), 40));
+    }
+}
@@ -12,19 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use catalog::kvbackend::{CachedMetaKvBackend, KvBackendCatalogManager};
+use chrono::Local;
 use client::client_manager::DatanodeClients;
 use client::{Client, Database, DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_base::Plugins;
 use common_error::ext::ErrorExt;
+use common_grpc::channel_manager::{ChannelConfig, ChannelManager};
 use common_query::Output;
 use common_recordbatch::RecordBatches;
 use common_telemetry::logging;
 use either::Either;
+use futures::StreamExt;
 use meta_client::client::MetaClientBuilder;
 use query::datafusion::DatafusionQueryEngine;
 use query::logical_optimizer::LogicalOptimizer;
@@ -35,18 +39,31 @@ use query::QueryEngine;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 use session::context::QueryContext;
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
 
-use crate::cli::cmd::ReplCommand;
+use crate::cli::cmd::{CopyDirection, CopyOptions, ReplCommand, WatchOptions};
+use crate::cli::copy::{self, CsvReader, INSERT_BATCH_ROWS};
+use crate::cli::editor::{self, Edited};
 use crate::cli::helper::RustylineHelper;
-use crate::cli::AttachCommand;
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::transaction::{transaction_prompt, TransactionStatement};
+use crate::cli::validate::split_statements;
+use crate::cli::{peek, AttachCommand};
 use crate::error::{
-    CollectRecordBatchesSnafu, ParseSqlSnafu, PlanStatementSnafu, PrettyPrintRecordBatchesSnafu,
-    ReadlineSnafu, ReplCreationSnafu, RequestDatabaseSnafu, Result, StartMetaClientSnafu,
-    SubstraitEncodeLogicalPlanSnafu,
+    ClientTlsSnafu, CollectRecordBatchesSnafu, Error, FileIoSnafu, InvalidCsvSnafu,
+    NotDataFromOutputSnafu, ParseSqlSnafu, PlanStatementSnafu, ReadlineSnafu, ReplCreationSnafu,
+    RequestDatabaseSnafu, Result, ServerUnreachableSnafu, StartMetaClientSnafu,
+    StatementFailedSnafu, SubstraitEncodeLogicalPlanSnafu,
 };
 
+/// Interval before the first retry to connect to the server, doubled on each retry.
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+/// Max interval between two retries to connect to the server.
+const MAX_CONNECT_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
 /// Captures the state of the repl, gathers commands and executes them one by one
 pub struct Repl {
     /// Rustyline editor for interacting with user on command line
@@ -58,7 +75,32 @@ pub struct Repl {
     /// Client for interacting with GreptimeDB
     database: Database,
 
+    /// gRPC address of the server the `database` is connected to
+    grpc_addr: String,
+
+    /// Config of the channels to the server, kept for `\connect`
+    channel_config: ChannelConfig,
+
     query_engine: Option<DatafusionQueryEngine>,
+
+    /// Whether `BEGIN`, `COMMIT` and `ROLLBACK` are tracked
+    transaction_mode: bool,
+
+    /// Whether a transaction is open, only tracked in the transaction mode
+    in_transaction: bool,
+
+    /// The last SQL typed in, which is re-run by `\watch`
+    last_sql: Option<String>,
+
+    /// File the history is appended to, `None` if the history is disabled
+    history_file: Option<PathBuf>,
+
+    /// Format of the query results
+    output_format: OutputFormat,
+
+    /// Statements executed instead of the interactive loop, by `--execute` or
+    /// `--execute-file`
+    batch_input: Option<String>,
 }
 
 #[allow(clippy::print_stdout)]
@@ -72,17 +114,39 @@ impl Repl {
 
         if !cmd.disable_helper {
             rl.set_helper(Some(RustylineHelper::default()));
+        }
 
-            let history_file = history_file();
-            if let Err(e) = rl.load_history(&history_file) {
+        let history_file = history_file(cmd);
+        if let Some(history_file) = &history_file {
+            if let Err(e) = rl.load_history(history_file) {
                 logging::debug!(
                     "failed to load history file on {}, error: {e}",
                     history_file.display()
                 );
             }
+            // The default file under `XDG_DATA_HOME` may be in a directory to create.
+            if let Some(dir) = history_file
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+            {
+                if let Err(e) = std::fs::create_dir_all(dir) {
+                    logging::debug!(
+                        "failed to create history directory {}, error: {e}",
+                        dir.display()
+                    );
+                }
+            }
         }
 
-        let client = Client::with_urls([&cmd.grpc_addr]);
+        let mut config = ChannelConfig::new();
+        if let Some(secs) = cmd.connect_timeout {
+            config = config.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(tls) = cmd.client_tls_option()? {
+            config = config.client_tls_config(tls);
+        }
+        let client = new_client(&config, &cmd.grpc_addr)?;
+        wait_for_server(&client, &cmd.grpc_addr, cmd.connect_retries).await?;
         let database = Database::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, client);
 
         let query_engine = if let Some(meta_addr) = &cmd.meta_addr {
@@ -95,17 +159,48 @@ impl Repl {
             rl,
             prompt: "> ".to_string(),
             database,
+            grpc_addr: cmd.grpc_addr.clone(),
+            channel_config: config,
             query_engine,
+            transaction_mode: cmd.transaction,
+            in_transaction: false,
+            last_sql: None,
+            history_file,
+            output_format: cmd.output_format,
+            batch_input: cmd.batch_input()?,
         })
     }
 
+    /// Adds the `entry` to the history, and appends it to the history file right away
+    /// so it's kept even if the process is killed.
+    fn add_history_entry(&mut self, entry: String) {
+        let _ = self.rl.add_history_entry(entry);
+        if let Some(history_file) = &self.history_file {
+            if let Err(e) = self.rl.append_history(history_file) {
+                logging::debug!(
+                    "failed to append history file on {}, error: {e}",
+                    history_file.display()
+                );
+            }
+        }
+    }
+
+    /// Returns the prompt to show, which is marked while a transaction is open.
+    fn current_prompt(&self) -> String {
+        if self.in_transaction {
+            transaction_prompt(&self.prompt)
+        } else {
+            self.prompt.clone()
+        }
+    }
+
     /// Parse the next command
     fn next_command(&mut self) -> Result<ReplCommand> {
-        match self.rl.readline(&self.prompt) {
+        match self.rl.readline(&self.current_prompt()) {
             Ok(ref line) => {
                 let request = line.trim();
 
-                let _ = self.rl.add_history_entry(request.to_string());
+                self.add_history_entry(request.to_string());
 
                 request.try_into()
             }
@@ -119,6 +214,10 @@ impl Repl {
     ///
     /// Inspired / based on repl.rs from InfluxDB IOX
     pub(crate) async fn run(&mut self) -> Result<()> {
+        if let Some(input) = self.batch_input.take() {
+            return self.run_batch(&input).await;
+        }
+
         println!("Ready for commands. (Hint: try 'help')");
 
         loop {
@@ -134,29 +233,434 @@ impl Repl {
                     }
                 }
                 ReplCommand::Sql { sql } => {
-                    let _ = self.execute_sql(sql).await;
+                    self.run_sql(sql).await;
+                }
+                ReplCommand::Peek { table, limit } => {
+                    let _ = self.peek(&table, limit).await;
+                }
+                ReplCommand::Watch(options) => {
+                    self.watch(options).await;
+                }
+                ReplCommand::Copy(options) => {
+                    let _ = self.copy(options).await;
+                }
+                ReplCommand::Edit => {
+                    self.edit().await;
+                }
+                ReplCommand::Connect { addr } => {
+                    self.connect(addr).await;
                 }
                 ReplCommand::Exit => {
+                    if self.in_transaction {
+                        self.offer_rollback().await?;
+                    }
                     return Ok(());
                 }
             }
         }
     }
 
+    /// Executes the statements of `input` one by one on the same connection, stopping
+    /// at the first failed one. `USE <database>` switches the database of the
+    /// statements after it, the other REPL commands aren't supported.
+    async fn run_batch(&mut self, input: &str) -> Result<()> {
+        for statement in split_statements(input) {
+            let db_name = match statement.sql.split_once(char::is_whitespace) {
+                Some((maybe_use, db_name)) if maybe_use.eq_ignore_ascii_case("use") => {
+                    Some(db_name.trim().to_string())
+                }
+                _ => None,
+            };
+            if let Err(e) = self.do_execute_sql(statement.sql).await {
+                // On the stderr like the summaries, to keep the stdout machine-readable.
+                #[allow(clippy::print_stderr)]
+                {
+                    let status_code = e.status_code();
+                    eprintln!(
+                        "Error at line {}: {}({status_code}), {}",
+                        statement.line,
+                        status_code as u32,
+                        e.output_msg()
+                    );
+                }
+                return StatementFailedSnafu {
+                    line: statement.line,
+                }
+                .fail();
+            }
+            if let Some(db_name) = db_name {
+                self.database.set_schema(&db_name);
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_sql(&mut self, sql: String) {
+        self.last_sql = Some(sql.clone());
+        if self.transaction_mode {
+            self.execute_sql_in_transaction_mode(sql).await;
+        } else {
+            let _ = self.execute_sql(sql).await;
+        }
+    }
+
+    /// Switches to the server at `addr` on new channels, like `\c` of psql. The current
+    /// connection is kept if the server is unreachable, and while a transaction is
+    /// open since it isn't carried over to the other server.
+    async fn connect(&mut self, addr: String) {
+        if self.in_transaction {
+            println!("A transaction is open, commit or roll it back before switching servers");
+            return;
+        }
+        match self.try_connect(&addr).await {
+            Ok(client) => {
+                let catalog = self.database.catalog().clone();
+                let schema = self.database.schema().clone();
+                self.database = Database::new(catalog, schema, client);
+                println!("Connected to {addr}");
+                self.grpc_addr = addr;
+            }
+            Err(e) => {
+                print_error(e);
+                println!("Still connected to {}", self.grpc_addr);
+            }
+        }
+    }
+
+    /// Connects to the server at `addr` without retrying, so a mistyped address fails
+    /// right away.
+    async fn try_connect(&self, addr: &str) -> Result<Client> {
+        let client = new_client(&self.channel_config, addr)?;
+        wait_for_server(&client, addr, 0).await?;
+        Ok(client)
+    }
+
+    /// Edits the last SQL in the external editor, and executes the edited one.
+    async fn edit(&mut self) {
+        let sql = self.last_sql.clone().unwrap_or_default();
+        match editor::edit(&sql) {
+            Ok(Edited::Statement(sql)) => {
+                println!("{sql};");
+                self.add_history_entry(format!("{sql};"));
+                self.run_sql(sql).await;
+            }
+            Ok(Edited::Aborted(status)) => {
+                println!("Editor exited with {status}, nothing is executed")
+            }
+            Ok(Edited::Unchanged) => println!("Buffer is unchanged or empty, nothing is executed"),
+            Err(e) => print_error(e),
+        }
+    }
+
+    /// Executes the `sql` and tracks whether a transaction is open. The transaction
+    /// state only changes if the server accepts the statement, so an engine that
+    /// doesn't support transactions never shows one as open.
+    async fn execute_sql_in_transaction_mode(&mut self, sql: String) {
+        let statement = TransactionStatement::parse(&sql);
+        if statement == Some(TransactionStatement::Begin) && self.in_transaction {
+            println!("Warning: a transaction is already open");
+        }
+
+        if self.execute_sql(sql).await {
+            if let Some(statement) = statement {
+                self.in_transaction = statement.opens();
+            }
+        } else if statement.is_some() {
+            println!(
+                "The server rejected the transaction statement, the transaction state is unchanged"
+            );
+        }
+    }
+
+    /// Asks whether to roll back the open transaction before exiting.
+    async fn offer_rollback(&mut self) -> Result<()> {
+        let answer = match self
+            .rl
+            .readline("A transaction is open, rollback before exit? [Y/n] ")
+        {
+            Ok(answer) => answer,
+            // Exits without rollback on another Ctrl-C or Ctrl-D.
+            Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => return Ok(()),
+            Err(e) => return Err(e).context(ReadlineSnafu),
+        };
+
+        if matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes")
+            && self.execute_sql("ROLLBACK".to_string()).await
+        {
+            self.in_transaction = false;
+            println!("Rolled back");
+        }
+        Ok(())
+    }
+
+    /// Re-runs the last SQL every interval, clearing the screen and redrawing the
+    /// result under a timestamp header, until interrupted by Ctrl-C.
+    async fn watch(&self, options: WatchOptions) {
+        let Some(sql) = self.last_sql.clone() else {
+            println!("No SQL to watch, run a query first");
+            return;
+        };
+
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        loop {
+            // Clears the screen and moves the cursor to the top left.
+            print!("\x1b[2J\x1b[H");
+            println!(
+                "Every {:?}: {sql}    {}\n",
+                options.interval,
+                Local::now().format("%Y-%m-%d %H:%M:%S")
+            );
+
+            let result = tokio::select! {
+                result = self.do_execute_sql(sql.clone()) => result,
+                _ = &mut ctrl_c => break,
+            };
+            if let Err(e) = result {
+                print_error(e);
+                if options.stop_on_error {
+                    println!("Stopped watching on error");
+                    return;
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(options.interval) => {}
+                _ = &mut ctrl_c => break,
+            }
+        }
+        println!("Stopped watching");
+    }
+
     async fn execute_sql(&self, sql: String) -> bool {
-        self.do_execute_sql(sql)
+        self.do_execute_sql(sql).await.map_err(print_error).is_ok()
+    }
+
+    /// Samples the first `limit` rows of `table`, switching to the vertical layout
+    /// if the rows don't fit in the terminal.
+    async fn peek(&self, table: &str, limit: usize) -> bool {
+        self.do_peek(table, limit)
             .await
-            .map_err(|e| {
-                let status_code = e.status_code();
-                let root_cause = e.output_msg();
-                println!("Error: {}({status_code}), {root_cause}", status_code as u32)
-            })
+            .map_err(print_error)
             .is_ok()
     }
 
+    async fn do_peek(&self, table: &str, limit: usize) -> Result<()> {
+        let start = Instant::now();
+
+        let sql = format!("SELECT * FROM {table} LIMIT {limit}");
+        let recordbatches = match self.query(sql).await? {
+            Either::Left(recordbatches) => recordbatches,
+            Either::Right(rows) => {
+                println!("Affected Rows: {rows}");
+                return Ok(());
+            }
+        };
+
+        let end = Instant::now();
+
+        let total_rows: usize = recordbatches.iter().map(|x| x.num_rows()).sum();
+        if total_rows > 0 {
+            println!("{}", peek::render(&recordbatches, peek::terminal_width()));
+        }
+        println!("Total Rows: {total_rows}");
+        println!("Cost {} ms", (end - start).as_millis());
+        Ok(())
+    }
+
+    /// Copies the rows of a table between the server and a local CSV file.
+    async fn copy(&self, options: CopyOptions) -> bool {
+        self.do_copy(options).await.map_err(print_error).is_ok()
+    }
+
+    async fn do_copy(&self, options: CopyOptions) -> Result<()> {
+        let start = Instant::now();
+
+        let mut rows = 0;
+        let result = match options.direction {
+            CopyDirection::To => self.copy_to(&options.table, &options.path, &mut rows).await,
+            CopyDirection::From => {
+                self.copy_from(&options.table, &options.path, &mut rows)
+                    .await
+            }
+        };
+        if result.is_err() && rows > 0 {
+            println!("Copied {rows} rows before the error");
+        }
+        result?;
+
+        let end = Instant::now();
+
+        println!("Copied {rows} rows");
+        println!("Cost {} ms", (end - start).as_millis());
+        Ok(())
+    }
+
+    /// Writes the rows of `table` to the CSV file at `path` batch by batch, counting
+    /// the written rows in `rows`.
+    async fn copy_to(&self, table: &str, path: &str, rows: &mut usize) -> Result<()> {
+        let mut stream = match self.query_output(format!("SELECT * FROM {table}")).await? {
+            Output::Stream(s) => s,
+            Output::RecordBatches(x) => x.as_stream(),
+            Output::AffectedRows(_) => return NotDataFromOutputSnafu.fail(),
+        };
+
+        let mut writer = BufWriter::new(File::create(path).await.context(FileIoSnafu)?);
+        let mut buf = String::new();
+        copy::push_record(
+            &mut buf,
+            stream
+                .schema()
+                .column_schemas()
+                .iter()
+                .map(|column| Some(&column.name)),
+        );
+        writer
+            .write_all(buf.as_bytes())
+            .await
+            .context(FileIoSnafu)?;
+        buf.clear();
+        while let Some(batch) = stream.next().await {
+            let batch = batch.context(CollectRecordBatchesSnafu)?;
+            copy::push_batch(&mut buf, &batch);
+            writer
+                .write_all(buf.as_bytes())
+                .await
+                .context(FileIoSnafu)?;
+            buf.clear();
+            *rows += batch.num_rows();
+        }
+        writer.flush().await.context(FileIoSnafu)
+    }
+
+    /// Inserts the rows of the CSV file at `path` to `table`, by a statement of every
+    /// [INSERT_BATCH_ROWS] rows, counting the inserted rows in `rows`. The columns are
+    /// named by the header line of the file.
+    async fn copy_from(&self, table: &str, path: &str, rows: &mut usize) -> Result<()> {
+        let invalid = |line: usize, reason: String| InvalidCsvSnafu { path, line, reason };
+
+        let file = File::open(path).await.context(FileIoSnafu)?;
+        let mut reader = CsvReader::new(BufReader::new(file));
+        let Some((line, header)) = reader.next_record().await.context(FileIoSnafu)? else {
+            return invalid(1, "missing the header line".to_string()).fail();
+        };
+        let columns = copy::parse_record(&header)
+            .map_err(|reason| invalid(line, reason).build())?
+            .into_iter()
+            .map(|field| field.value)
+            .collect::<Vec<_>>();
+
+        let schema = match self.query(format!("SELECT * FROM {table} LIMIT 0")).await? {
+            Either::Left(recordbatches) => recordbatches.schema(),
+            Either::Right(_) => return NotDataFromOutputSnafu.fail(),
+        };
+        let data_types = columns
+            .iter()
+            .map(|column| {
+                schema
+                    .column_schema_by_name(column)
+                    .map(|column_schema| column_schema.data_type.clone())
+                    .with_context(|| invalid(line, format!("unknown column '{column}'")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut batch = Vec::with_capacity(INSERT_BATCH_ROWS);
+        while let Some((line, record)) = reader.next_record().await.context(FileIoSnafu)? {
+            let fields =
+                copy::parse_record(&record).map_err(|reason| invalid(line, reason).build())?;
+            ensure!(
+                fields.len() == columns.len(),
+                invalid(
+                    line,
+                    format!("expect {} fields, found {}", columns.len(), fields.len())
+                )
+            );
+            let row = fields
+                .iter()
+                .zip(&data_types)
+                .map(|(field, data_type)| copy::to_sql_literal(field, data_type))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|reason| invalid(line, reason).build())?;
+            batch.push(row);
+
+            if batch.len() == INSERT_BATCH_ROWS {
+                *rows += self.insert(table, &columns, &batch).await?;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            *rows += self.insert(table, &columns, &batch).await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the `rows` of SQL literals, returns the number of inserted rows.
+    async fn insert(&self, table: &str, columns: &[String], rows: &[Vec<String>]) -> Result<usize> {
+        // Inserts are always sent as SQL, they can't be planned by the query engine.
+        let sql = copy::insert_statement(table, columns, rows);
+        match self
+            .database
+            .sql(&sql)
+            .await
+            .context(RequestDatabaseSnafu { sql: &sql })?
+        {
+            Output::AffectedRows(rows) => Ok(rows),
+            Output::Stream(_) | Output::RecordBatches(_) => NotDataFromOutputSnafu.fail(),
+        }
+    }
+
     async fn do_execute_sql(&self, sql: String) -> Result<()> {
         let start = Instant::now();
 
+        let either = self.query(sql).await?;
+
+        let end = Instant::now();
+
+        // Only the rows of the machine-readable formats are on the stdout, so it can be
+        // piped to other tools.
+        let print_summary = |summary: String| {
+            if self.output_format == OutputFormat::Table {
+                println!("{summary}");
+            } else {
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!("{summary}");
+                }
+            }
+        };
+        match either {
+            Either::Left(recordbatches) => {
+                let total_rows: usize = recordbatches.iter().map(|x| x.num_rows()).sum();
+                if total_rows > 0 || self.output_format == OutputFormat::Csv {
+                    print!("{}", output::render(self.output_format, &recordbatches)?);
+                }
+                print_summary(format!("Total Rows: {total_rows}"));
+            }
+            Either::Right(rows) => print_summary(format!("Affected Rows: {rows}")),
+        };
+
+        print_summary(format!("Cost {} ms", (end - start).as_millis()));
+        Ok(())
+    }
+
+    /// Executes the `sql` and collects its output, which is either the queried
+    /// record batches or the number of affected rows.
+    async fn query(&self, sql: String) -> Result<Either<RecordBatches, usize>> {
+        let either = match self.query_output(sql).await? {
+            Output::Stream(s) => {
+                let x = RecordBatches::try_collect(s)
+                    .await
+                    .context(CollectRecordBatchesSnafu)?;
+                Either::Left(x)
+            }
+            Output::RecordBatches(x) => Either::Left(x),
+            Output::AffectedRows(rows) => Either::Right(rows),
+        };
+        Ok(either)
+    }
+
+    /// Executes the `sql` and returns its output without collecting the stream.
+    async fn query_output(&self, sql: String) -> Result<Output> {
         let output = if let Some(query_engine) = &self.query_engine {
             let stmt = QueryLanguageParser::parse_sql(&sql)
                 .with_context(|_| ParseSqlSnafu { sql: sql.clone() })?;
@@ -179,63 +683,77 @@ impl Repl {
             self.database.logical_plan(plan.to_vec(), 0).await
         } else {
             self.database.sql(&sql).await
-        }
-        .context(RequestDatabaseSnafu { sql: &sql })?;
-
-        let either = match output {
-            Output::Stream(s) => {
-                let x = RecordBatches::try_collect(s)
-                    .await
-                    .context(CollectRecordBatchesSnafu)?;
-                Either::Left(x)
-            }
-            Output::RecordBatches(x) => Either::Left(x),
-            Output::AffectedRows(rows) => Either::Right(rows),
         };
-
-        let end = Instant::now();
-
-        match either {
-            Either::Left(recordbatches) => {
-                let total_rows: usize = recordbatches.iter().map(|x| x.num_rows()).sum();
-                if total_rows > 0 {
-                    println!(
-                        "{}",
-                        recordbatches
-                            .pretty_print()
-                            .context(PrettyPrintRecordBatchesSnafu)?
-                    );
-                }
-                println!("Total Rows: {total_rows}")
-            }
-            Either::Right(rows) => println!("Affected Rows: {rows}"),
-        };
-
-        println!("Cost {} ms", (end - start).as_millis());
-        Ok(())
+        output.context(RequestDatabaseSnafu { sql: &sql })
     }
 }
 
-impl Drop for Repl {
-    fn drop(&mut self) {
-        if self.rl.helper().is_some() {
-            let history_file = history_file();
-            if let Err(e) = self.rl.save_history(&history_file) {
-                logging::debug!(
-                    "failed to save history file on {}, error: {e}",
-                    history_file.display()
+/// Checks the server at `addr` is reachable, retrying at most `retries` times with a
+/// growing interval.
+async fn wait_for_server(client: &Client, addr: &str, retries: usize) -> Result<()> {
+    let mut interval = CONNECT_RETRY_INTERVAL;
+    let mut retry = 0;
+    loop {
+        match client.health_check().await {
+            Ok(()) => return Ok(()),
+            Err(e) if retry < retries => {
+                retry += 1;
+                logging::warn!(
+                    e; "Failed to connect to {addr}, retry {retry}/{retries} in {interval:?}"
                 );
+                tokio::time::sleep(interval).await;
+                interval = (interval * 2).min(MAX_CONNECT_RETRY_INTERVAL);
+            }
+            Err(e) => {
+                return Err(e).context(ServerUnreachableSnafu {
+                    addr,
+                    attempts: retry + 1,
+                })
             }
         }
     }
 }
 
-/// Return the location of the history file (defaults to $HOME/".greptimedb_cli_history")
-fn history_file() -> PathBuf {
-    let mut buf = match std::env::var("HOME") {
-        Ok(home) => PathBuf::from(home),
-        Err(_) => PathBuf::new(),
+/// Creates a client of the server at `addr`, on a channel manager of its own so no
+/// channel is shared with the other clients.
+fn new_client(config: &ChannelConfig, addr: &str) -> Result<Client> {
+    let channel_manager = if config.client_tls.is_some() {
+        ChannelManager::with_tls_config(config.clone()).context(ClientTlsSnafu)?
+    } else {
+        ChannelManager::with_config(config.clone())
     };
+    Ok(Client::with_manager_and_urls(channel_manager, [addr]))
+}
+
+#[allow(clippy::print_stdout)]
+fn print_error(e: Error) {
+    let status_code = e.status_code();
+    let root_cause = e.output_msg();
+    println!("Error: {}({status_code}), {root_cause}", status_code as u32)
+}
+
+/// Returns the history file of `cmd`, `None` if the history is disabled by
+/// `--no-history`.
+fn history_file(cmd: &AttachCommand) -> Option<PathBuf> {
+    if cmd.no_history {
+        return None;
+    }
+    let history_file = cmd.history_file.clone().unwrap_or_else(|| {
+        default_history_file(std::env::var_os("XDG_DATA_HOME"), std::env::var_os("HOME"))
+    });
+    Some(history_file)
+}
+
+/// Returns `$XDG_DATA_HOME/greptimedb/cli_history`, or `$HOME/.greptimedb_cli_history`
+/// if `XDG_DATA_HOME` isn't an absolute path as the XDG spec requires.
+fn default_history_file(xdg_data_home: Option<OsString>, home: Option<OsString>) -> PathBuf {
+    if let Some(data_home) = xdg_data_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+    {
+        return data_home.join("greptimedb").join("cli_history");
+    }
+    let mut buf = home.map(PathBuf::from).unwrap_or_default();
     buf.push(".greptimedb_cli_history");
     buf
 }
@@ -268,3 +786,29 @@ async fn create_query_engine(meta_addr: &str) -> Result<DatafusionQueryEngine> {
 
     Ok(DatafusionQueryEngine::new(state, plugins))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_history_file() {
+        assert_eq!(
+            PathBuf::from("/data/greptimedb/cli_history"),
+            default_history_file(Some("/data".into()), Some("/home/greptime".into()))
+        );
+        // A relative `XDG_DATA_HOME` is ignored.
+        assert_eq!(
+            PathBuf::from("/home/greptime/.greptimedb_cli_history"),
+            default_history_file(Some("data".into()), Some("/home/greptime".into()))
+        );
+        assert_eq!(
+            PathBuf::from("/home/greptime/.greptimedb_cli_history"),
+            default_history_file(None, Some("/home/greptime".into()))
+        );
+        assert_eq!(
+            PathBuf::from(".greptimedb_cli_history"),
+            default_history_file(None, None)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracking of the explicit transactions opened in the REPL `--transaction` mode.
+
+/// A statement that opens or closes a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransactionStatement {
+    /// `BEGIN` or `START TRANSACTION`.
+    Begin,
+    /// `COMMIT` or `END`.
+    Commit,
+    /// `ROLLBACK` or `ABORT`.
+    Rollback,
+}
+
+impl TransactionStatement {
+    /// Returns the transaction statement of the `sql`, or `None` if the `sql` doesn't
+    /// open or close a transaction. `ROLLBACK TO SAVEPOINT` keeps the transaction open
+    /// so it isn't one.
+    pub(crate) fn parse(sql: &str) -> Option<Self> {
+        let sql = sql.trim().trim_end_matches(';').to_lowercase();
+        let mut words = sql.split_whitespace();
+        let first = words.next()?;
+        let second = words.next();
+        match (first, second) {
+            ("begin", None | Some("work" | "transaction")) => Some(Self::Begin),
+            ("start", Some("transaction")) => Some(Self::Begin),
+            ("commit" | "end", None | Some("work" | "transaction")) => Some(Self::Commit),
+            ("rollback" | "abort", None | Some("work" | "transaction")) => {
+                if words.next() == Some("to") {
+                    None
+                } else {
+                    Some(Self::Rollback)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether a transaction is open after this statement succeeds.
+    pub(crate) fn opens(&self) -> bool {
+        matches!(self, Self::Begin)
+    }
+}
+
+/// Returns the prompt shown while a transaction is open, which marks the `prompt`
+/// with a `*`, e.g. `[public] > ` becomes `[public] *> `.
+pub(crate) fn transaction_prompt(prompt: &str) -> String {
+    match prompt.strip_suffix("> ") {
+        Some(prefix) => format!("{prefix}*> "),
+        None => format!("{prompt}*"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transaction_statement() {
+        use TransactionStatement::*;
+
+        let cases = [
+            ("BEGIN", Some(Begin)),
+            ("begin;", Some(Begin)),
+            ("  Begin Transaction ; ", Some(Begin)),
+            ("START TRANSACTION", Some(Begin)),
+            ("START TRANSACTION READ ONLY", Some(Begin)),
+            ("COMMIT", Some(Commit)),
+            ("commit work;", Some(Commit)),
+            ("END", Some(Commit)),
+            ("ROLLBACK", Some(Rollback)),
+            ("abort;", Some(Rollback)),
+            ("ROLLBACK TO SAVEPOINT foo", None),
+            ("ROLLBACK TRANSACTION TO foo", None),
+            ("START", None),
+            ("SELECT * FROM begin", None),
+            ("BEGIN foo", None),
+            ("", None),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(expected, TransactionStatement::parse(sql), "'{sql}'");
+        }
+
+        assert!(Begin.opens());
+        assert!(!Commit.opens());
+        assert!(!Rollback.opens());
+    }
+
+    #[test]
+    fn test_transaction_prompt() {
+        assert_eq!("*> ", transaction_prompt("> "));
+        assert_eq!("[public] *> ", transaction_prompt("[public] > "));
+        assert_eq!("greptime*", transaction_prompt("greptime"));
+    }
+}
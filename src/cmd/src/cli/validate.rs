@@ -0,0 +1,296 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validates SQL statements by `POST /v1/sql/validate`, which parses and plans them on
+//! the server without executing them.
+//!
+//! The statements are split on the client and validated one by one, so an error in a
+//! statement doesn't stop validating the others.
+
+use async_trait::async_trait;
+use clap::Parser;
+use servers::http::handler::{ValidateSqlResponse, ValidationStage};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::cli::{Instance, Tool};
+use crate::error::{
+    FileIoSnafu, IllegalConfigSnafu, InvalidStatementsSnafu, Result, ValidateSqlSnafu,
+};
+
+#[derive(Debug, Default, Parser)]
+pub struct ValidateSqlCommand {
+    /// HTTP address of the server to connect, e.g. 127.0.0.1:4000
+    #[clap(long)]
+    addr: String,
+
+    /// The database to validate in, e.g. "public" or "greptime-public".
+    #[clap(long)]
+    database: Option<String>,
+
+    /// File of the statements to validate, separated by ';'.
+    #[clap(long, conflicts_with = "sql")]
+    file: Option<String>,
+
+    /// Statements to validate, separated by ';'.
+    #[clap(long)]
+    sql: Option<String>,
+
+    /// basic authentication for connecting to the server
+    #[clap(long)]
+    auth_basic: Option<String>,
+}
+
+impl ValidateSqlCommand {
+    pub async fn build(&self) -> Result<Instance> {
+        let sql = match (&self.file, &self.sql) {
+            (Some(file), _) => tokio::fs::read_to_string(file).await.context(FileIoSnafu)?,
+            (None, Some(sql)) => sql.clone(),
+            (None, None) => {
+                return IllegalConfigSnafu {
+                    msg: "either --file or --sql is required".to_string(),
+                }
+                .fail()
+            }
+        };
+
+        let auth = self
+            .auth_basic
+            .as_ref()
+            .map(|auth_basic| {
+                auth_basic
+                    .split_once(':')
+                    .map(|(username, password)| (username.to_string(), password.to_string()))
+                    .context(IllegalConfigSnafu {
+                        msg: "auth_basic cannot be split by ':'".to_string(),
+                    })
+            })
+            .transpose()?;
+
+        let addr = if self.addr.starts_with("http://") || self.addr.starts_with("https://") {
+            self.addr.clone()
+        } else {
+            format!("http://{}", self.addr)
+        };
+
+        Ok(Instance::Tool(Box::new(ValidateSql {
+            url: format!("{addr}/v1/sql/validate"),
+            database: self.database.clone(),
+            auth,
+            statements: split_statements(&sql),
+        })))
+    }
+}
+
+/// A statement of the input.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Statement {
+    /// Line of the input where the statement starts, from 1.
+    pub(crate) line: usize,
+    pub(crate) sql: String,
+}
+
+pub struct ValidateSql {
+    url: String,
+    database: Option<String>,
+    /// Username and password of the basic authentication.
+    auth: Option<(String, String)>,
+    statements: Vec<Statement>,
+}
+
+impl ValidateSql {
+    async fn validate(&self, client: &reqwest::Client, sql: &str) -> Result<ValidateSqlResponse> {
+        let mut request = client.post(&self.url).form(&[("sql", sql)]);
+        if let Some(database) = &self.database {
+            request = request.query(&[("db", database)]);
+        }
+        if let Some((username, password)) = &self.auth {
+            request = request.basic_auth(username, Some(password));
+        }
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context(ValidateSqlSnafu { url: &self.url })?
+            .json()
+            .await
+            .context(ValidateSqlSnafu { url: &self.url })
+    }
+}
+
+#[async_trait]
+impl Tool for ValidateSql {
+    #[allow(clippy::print_stdout)]
+    async fn do_work(&self) -> Result<()> {
+        let client = reqwest::Client::new();
+        let mut failed = 0;
+        for statement in &self.statements {
+            let resp = self.validate(&client, &statement.sql).await?;
+            let summary = first_line(&statement.sql);
+            match resp.error {
+                None if resp.planned > 0 => println!("line {}: ok: {summary}", statement.line),
+                None => println!("line {}: ok, parsed only: {summary}", statement.line),
+                Some(error) => {
+                    failed += 1;
+                    let stage = match error.stage {
+                        ValidationStage::Syntax => "syntax error",
+                        ValidationStage::Plan => "plan error",
+                    };
+                    println!(
+                        "line {}: {stage} ({}): {summary}\n    {}",
+                        statement.line, error.code, error.error
+                    );
+                }
+            }
+        }
+        println!(
+            "{} statements validated, {failed} failed",
+            self.statements.len()
+        );
+
+        ensure!(failed == 0, InvalidStatementsSnafu { count: failed });
+        Ok(())
+    }
+}
+
+/// Returns the first line of the statement, marking the rest as omitted.
+fn first_line(sql: &str) -> String {
+    match sql.split_once('\n') {
+        Some((line, _)) => format!("{} ...", line.trim_end()),
+        None => sql.to_string(),
+    }
+}
+
+/// Splits the input on the `;` outside the quotes and the comments, skipping the empty
+/// statements.
+pub(crate) fn split_statements(input: &str) -> Vec<Statement> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Normal,
+        Quoted(char),
+        LineComment,
+        BlockComment,
+    }
+
+    let mut statements = Vec::new();
+    let mut state = State::Normal;
+    // Line and offset of the first character of the statement out of the comments.
+    let mut start = None;
+    let mut line = 1;
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match state {
+            State::Normal => match c {
+                ';' => {
+                    if let Some((line, offset)) = start.take() {
+                        statements.push(Statement {
+                            line,
+                            sql: input[offset..i].trim_end().to_string(),
+                        });
+                    }
+                }
+                '-' if chars.peek().map(|(_, c)| *c) == Some('-') => state = State::LineComment,
+                '/' if chars.peek().map(|(_, c)| *c) == Some('*') => {
+                    let _ = chars.next();
+                    state = State::BlockComment;
+                }
+                '\'' | '"' | '`' => {
+                    let _ = start.get_or_insert((line, i));
+                    state = State::Quoted(c);
+                }
+                c if !c.is_whitespace() => {
+                    let _ = start.get_or_insert((line, i));
+                }
+                _ => {}
+            },
+            State::Quoted(quote) => {
+                if c == '\\' {
+                    // Escaped character, which can't close the quote.
+                    if let Some((_, '\n')) = chars.next() {
+                        line += 1;
+                    }
+                } else if c == quote {
+                    state = State::Normal;
+                }
+            }
+            State::LineComment => {
+                if c == '\n' {
+                    state = State::Normal;
+                }
+            }
+            State::BlockComment => {
+                if c == '*' && chars.peek().map(|(_, c)| *c) == Some('/') {
+                    let _ = chars.next();
+                    state = State::Normal;
+                }
+            }
+        }
+        if c == '\n' {
+            line += 1;
+        }
+    }
+    // The last statement may end without ';'.
+    if let Some((line, offset)) = start {
+        statements.push(Statement {
+            line,
+            sql: input[offset..].trim_end().to_string(),
+        });
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        let input = "-- comment; not a statement\n\
+            select 1;\n\
+            insert into t values ('a;b''c', \"d;\\\"\");;\n\
+            /* block;\n comment */ select\n  2\n";
+        assert_eq!(
+            vec![
+                Statement {
+                    line: 2,
+                    sql: "select 1".to_string(),
+                },
+                Statement {
+                    line: 3,
+                    sql: "insert into t values ('a;b''c', \"d;\\\"\")".to_string(),
+                },
+                Statement {
+                    line: 5,
+                    sql: "select\n  2".to_string(),
+                },
+            ],
+            split_statements(input)
+        );
+
+        assert!(split_statements(" ; -- only comments\n/* ; */").is_empty());
+        // An unterminated quote takes the rest of the input.
+        assert_eq!(
+            vec![Statement {
+                line: 1,
+                sql: "select 'a; select 1".to_string(),
+            }],
+            split_statements("select 'a; select 1")
+        );
+    }
+
+    #[test]
+    fn test_first_line() {
+        assert_eq!("select 1", first_line("select 1"));
+        assert_eq!("select ...", first_line("select \n  1"));
+    }
+}
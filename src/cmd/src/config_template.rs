@@ -0,0 +1,234 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `config template` writes the default options of `standalone start` as a TOML
+//! config file to edit, with comments on the main keys.
+//!
+//! The template is serialized from [StandaloneOptions::default], so it always has all
+//! the keys this binary supports. The options unset by default aren't in it, see
+//! `config/standalone.example.toml` for them.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+use snafu::ResultExt;
+
+use crate::error::{FileIoSnafu, Result, TomlFormatSnafu};
+use crate::standalone::StandaloneOptions;
+
+/// Comments of the keys and tables in the template, by their dotted paths.
+const ANNOTATIONS: &[(&str, &str)] = &[
+    ("mode", "Node running mode, \"standalone\" or \"distributed\"."),
+    (
+        "enable_telemetry",
+        "Whether to enable greptimedb telemetry, also disabled by `--disable-telemetry`.",
+    ),
+    (
+        "shutdown_grace_period",
+        "Time to stop gracefully on SIGTERM or SIGINT, the process exits immediately\nonce it elapses or on a second signal.",
+    ),
+    ("http", "HTTP server options."),
+    ("http.addr", "Server address."),
+    ("http.timeout", "HTTP request timeout."),
+    (
+        "http.body_limit",
+        "HTTP request body limit, in B, KB, KiB, MB, MiB, GB, GiB, TB, TiB, PB or PiB.",
+    ),
+    ("grpc", "gRPC server options."),
+    ("grpc.addr", "Server address."),
+    ("grpc.runtime_size", "The number of server worker threads."),
+    (
+        "grpc.max_recv_message_size",
+        "Max size of a received message, a larger request fails with \"OUT_OF_RANGE\".",
+    ),
+    (
+        "grpc.max_send_message_size",
+        "Max size of a sent message, a larger response fails with \"OUT_OF_RANGE\".",
+    ),
+    ("mysql", "MySQL server options."),
+    ("mysql.enable", "Whether to start the server."),
+    ("mysql.addr", "Server address."),
+    ("postgres", "PostgreSQL server options."),
+    ("postgres.enable", "Whether to start the server."),
+    ("postgres.addr", "Server address."),
+    ("opentsdb", "OpenTSDB server options."),
+    ("opentsdb.enable", "Whether to start the server."),
+    ("opentsdb.addr", "Server address."),
+    ("influxdb", "InfluxDB protocol options, served by the HTTP server."),
+    ("influxdb.enable", "Whether to serve the protocol."),
+    (
+        "prom_store",
+        "Prometheus remote storage options, served by the HTTP server.",
+    ),
+    ("prom_store.enable", "Whether to serve the protocol."),
+    ("wal", "WAL options."),
+    (
+        "wal.provider",
+        "Where the WAL entries are appended, \"raft_engine\" or \"kafka\".",
+    ),
+    ("metadata_store", "Metadata storage options."),
+    (
+        "metadata_backend",
+        "Where the metadata is stored, \"local\" for the kv store under the data home, or\n\"etcd\" to share the metadata in an external etcd.",
+    ),
+    ("procedure", "Procedure storage options."),
+    ("storage", "Storage options."),
+    ("storage.data_home", "The working home directory."),
+    ("storage.type", "Storage type, \"File\", \"S3\", \"Oss\", \"Azblob\" or \"Gcs\"."),
+    ("logging", "Logging options."),
+    ("logging.dir", "Directory of the log files."),
+    (
+        "logging.log_format",
+        "Format of the log on the stdout, \"pretty\", \"compact\" or \"json\".",
+    ),
+    ("region_engine", "Options of the region engines, one table per engine."),
+    ("region_engine.mito.num_workers", "Number of region workers."),
+];
+
+#[derive(Parser)]
+pub struct Command {
+    #[clap(subcommand)]
+    subcmd: SubCommand,
+}
+
+impl Command {
+    /// Runs the command, which doesn't start any instance.
+    pub fn run(&self) -> Result<()> {
+        match &self.subcmd {
+            SubCommand::Template(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Parser)]
+enum SubCommand {
+    /// Writes the default options of `standalone start` with comments, to edit as the
+    /// config file.
+    Template(TemplateCommand),
+}
+
+#[derive(Debug, Parser)]
+struct TemplateCommand {
+    /// Path to write the template to, stdout by default.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl TemplateCommand {
+    fn run(&self) -> Result<()> {
+        let template = standalone_template()?;
+        match &self.output {
+            Some(path) => std::fs::write(path, template).context(FileIoSnafu),
+            None => {
+                #[allow(clippy::print_stdout)]
+                {
+                    print!("{template}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Serializes the default standalone options to TOML, with the comment of each
+/// annotated key or table above its first occurrence.
+fn standalone_template() -> Result<String> {
+    let toml = toml::to_string_pretty(&StandaloneOptions::default()).context(TomlFormatSnafu)?;
+
+    let mut template = String::new();
+    let mut table = String::new();
+    let mut annotated = HashSet::new();
+    for line in toml.lines() {
+        // The values of a multi-line array are indented.
+        let path = if line.starts_with('[') {
+            table = line.trim_matches(|c| c == '[' || c == ']').to_string();
+            Some(table.clone())
+        } else if line.starts_with(' ') {
+            None
+        } else {
+            line.split_once(" = ").map(|(key, _)| {
+                if table.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{table}.{key}")
+                }
+            })
+        };
+        if let Some(comment) = path
+            .filter(|path| annotated.insert(path.clone()))
+            .as_deref()
+            .and_then(annotation)
+        {
+            for comment_line in comment.lines() {
+                let _ = writeln!(template, "# {comment_line}");
+            }
+        }
+        let _ = writeln!(template, "{line}");
+    }
+    Ok(template)
+}
+
+fn annotation(path: &str) -> Option<&'static str> {
+    ANNOTATIONS
+        .iter()
+        .find(|(key, _)| *key == path)
+        .map(|(_, comment)| *comment)
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+
+    #[test]
+    fn test_standalone_template() {
+        let template = standalone_template().unwrap();
+
+        // Loaded as the default options.
+        let opts: StandaloneOptions = toml::from_str(&template).unwrap();
+        assert_eq!(
+            toml::to_string(&StandaloneOptions::default()).unwrap(),
+            toml::to_string(&opts).unwrap()
+        );
+
+        assert!(template.contains("# HTTP server options.\n[http]\n"));
+        assert!(template.contains("# Server address.\naddr = \"127.0.0.1:4000\"\n"));
+        assert!(template.contains("# Number of region workers.\nnum_workers = "));
+    }
+
+    #[test]
+    fn test_annotations_are_in_template() {
+        let template = standalone_template().unwrap();
+        for (_, comment) in ANNOTATIONS {
+            assert!(
+                template.contains(comment.lines().next().unwrap()),
+                "{comment}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_template_output() {
+        let dir = create_temp_dir("test_template_output");
+        let output = dir.path().join("standalone.toml");
+        let cmd = Command::parse_from(["config", "template", "--output", output.to_str().unwrap()]);
+        cmd.run().unwrap();
+
+        let template = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(standalone_template().unwrap(), template);
+    }
+}
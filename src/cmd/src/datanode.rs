@@ -15,15 +15,22 @@
 use std::time::Duration;
 
 use clap::Parser;
+use common_base::readable_size::ReadableSize;
+use common_config::WalProvider;
 use common_telemetry::logging;
-use datanode::config::DatanodeOptions;
+use datanode::config::{DatanodeOptions, RegionEngineConfig};
 use datanode::datanode::{Datanode, DatanodeBuilder};
 use meta_client::MetaClientOptions;
+use mito2::region::options::{CompactionStrategy, DuplicateTimestampPolicy};
 use servers::Mode;
 use snafu::ResultExt;
 
-use crate::error::{MissingConfigSnafu, Result, ShutdownDatanodeSnafu, StartDatanodeSnafu};
-use crate::options::{Options, TopLevelOptions};
+use crate::error::{
+    IllegalConfigSnafu, MissingConfigSnafu, Result, ShutdownDatanodeSnafu, StartDatanodeSnafu,
+};
+use crate::options::{
+    check_object_store_config, check_wal_dir, set_storage_cache, Options, TopLevelOptions,
+};
 
 pub struct Instance {
     datanode: Datanode,
@@ -98,15 +105,49 @@ struct StartCommand {
     #[clap(long)]
     wal_dir: Option<String>,
     #[clap(long)]
+    wal_provider: Option<String>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    kafka_brokers: Option<Vec<String>>,
+    #[clap(long)]
+    kafka_topic_name_prefix: Option<String>,
+    #[clap(long)]
+    kafka_num_topics: Option<usize>,
+    #[clap(long)]
     http_addr: Option<String>,
     #[clap(long)]
     http_timeout: Option<u64>,
+    #[clap(long)]
+    keep_versions: Option<usize>,
+    #[clap(long)]
+    keep_versions_duration: Option<u64>,
+    #[clap(long)]
+    flush_interval: Option<u64>,
+    #[clap(long)]
+    compaction_strategy: Option<CompactionStrategy>,
+    #[clap(long)]
+    duplicate_timestamp_policy: Option<DuplicateTimestampPolicy>,
+    #[clap(long)]
+    startup_open_regions_concurrency: Option<usize>,
+    #[clap(long)]
+    allow_version_mismatch: bool,
+    #[clap(long)]
+    collect_os_metrics: bool,
+    #[clap(long)]
+    storage_cache_dir: Option<String>,
+    #[clap(long)]
+    storage_cache_size: Option<ReadableSize>,
     #[clap(long, default_value = "GREPTIMEDB_DATANODE")]
     env_prefix: String,
 }
 
 impl StartCommand {
     fn load_options(&self, top_level_opts: TopLevelOptions) -> Result<Options> {
+        if top_level_opts.strict_config {
+            if let Some(config_file) = &self.config_file {
+                Options::check_unknown_keys::<DatanodeOptions>(config_file)?;
+            }
+        }
+
         let mut opts: DatanodeOptions = Options::load_layered_options(
             self.config_file.as_deref(),
             self.env_prefix.as_ref(),
@@ -121,6 +162,10 @@ impl StartCommand {
             opts.logging.level = top_level_opts.log_level;
         }
 
+        if let Some(log_format) = top_level_opts.log_format {
+            opts.logging.log_format = log_format;
+        }
+
         if let Some(addr) = &self.rpc_addr {
             opts.rpc_addr = addr.clone();
         }
@@ -151,10 +196,52 @@ impl StartCommand {
             opts.storage.data_home = data_home.clone();
         }
 
+        set_storage_cache(
+            &mut opts.storage.store,
+            self.storage_cache_dir.as_ref(),
+            self.storage_cache_size,
+        )?;
+        check_object_store_config(&opts.storage.store)?;
+
         if let Some(wal_dir) = &self.wal_dir {
             opts.wal.dir = Some(wal_dir.clone());
         }
 
+        if let Some(provider) = &self.wal_provider {
+            opts.wal.provider = match provider.as_str() {
+                "raft_engine" => WalProvider::RaftEngine,
+                "kafka" => WalProvider::Kafka,
+                _ => {
+                    return IllegalConfigSnafu {
+                        msg: format!(
+                            "Unknown WAL provider: {provider}, expected raft_engine or kafka"
+                        ),
+                    }
+                    .fail()
+                }
+            };
+        }
+
+        if let Some(brokers) = &self.kafka_brokers {
+            opts.wal.kafka.broker_endpoints = brokers.clone();
+        }
+
+        if let Some(prefix) = &self.kafka_topic_name_prefix {
+            opts.wal.kafka.topic_name_prefix = prefix.clone();
+        }
+
+        if let Some(num_topics) = self.kafka_num_topics {
+            opts.wal.kafka.num_topics = num_topics;
+        }
+
+        // The local and the remote WAL are exclusive.
+        if opts.wal.provider == WalProvider::Kafka && opts.wal.dir.is_some() {
+            return IllegalConfigSnafu {
+                msg: "The WAL dir can't be set with the kafka WAL provider",
+            }
+            .fail();
+        }
+
         if let Some(http_addr) = &self.http_addr {
             opts.http.addr = http_addr.clone();
         }
@@ -163,6 +250,38 @@ impl StartCommand {
             opts.http.timeout = Duration::from_secs(http_timeout)
         }
 
+        if let Some(concurrency) = self.startup_open_regions_concurrency {
+            opts.startup_open_regions_concurrency = concurrency;
+        }
+
+        if self.allow_version_mismatch {
+            opts.allow_version_mismatch = true;
+        }
+
+        if self.collect_os_metrics {
+            opts.collect_os_metrics = true;
+        }
+
+        for engine in &mut opts.region_engine {
+            if let RegionEngineConfig::Mito(config) = engine {
+                if let Some(versions) = self.keep_versions {
+                    config.compaction_keep_versions = versions;
+                }
+                if let Some(duration) = self.keep_versions_duration {
+                    config.compaction_keep_versions_duration = Some(Duration::from_secs(duration));
+                }
+                if let Some(interval) = self.flush_interval {
+                    config.periodic_flush_interval = Some(Duration::from_secs(interval));
+                }
+                if let Some(strategy) = self.compaction_strategy {
+                    config.compaction_strategy = strategy;
+                }
+                if let Some(policy) = self.duplicate_timestamp_policy {
+                    config.duplicate_timestamp_policy = policy;
+                }
+            }
+        }
+
         // Disable dashboard in datanode.
         opts.http.disable_dashboard = true;
 
@@ -177,6 +296,7 @@ impl StartCommand {
         logging::info!("Datanode start command: {:#?}", self);
         logging::info!("Datanode options: {:#?}", opts);
 
+        check_wal_dir(&opts.wal, &opts.wal_dir())?;
         let datanode = DatanodeBuilder::new(opts, None, plugins)
             .build()
             .await
@@ -351,6 +471,154 @@ mod tests {
         .is_ok());
     }
 
+    #[test]
+    fn test_keep_versions_from_cmd() {
+        let cmd = StartCommand {
+            keep_versions: Some(3),
+            keep_versions_duration: Some(3600),
+            flush_interval: Some(600),
+            compaction_strategy: Some(CompactionStrategy::Leveled),
+            duplicate_timestamp_policy: Some(DuplicateTimestampPolicy::KeepFirst),
+            ..Default::default()
+        };
+        let Options::Datanode(opts) = cmd.load_options(TopLevelOptions::default()).unwrap() else {
+            unreachable!()
+        };
+        let RegionEngineConfig::Mito(config) = &opts.region_engine[0] else {
+            unreachable!()
+        };
+        assert_eq!(3, config.compaction_keep_versions);
+        assert_eq!(
+            Some(Duration::from_secs(3600)),
+            config.compaction_keep_versions_duration
+        );
+        assert_eq!(
+            Some(Duration::from_secs(600)),
+            config.periodic_flush_interval
+        );
+        assert_eq!(CompactionStrategy::Leveled, config.compaction_strategy);
+        assert_eq!(
+            DuplicateTimestampPolicy::KeepFirst,
+            config.duplicate_timestamp_policy
+        );
+    }
+
+    #[test]
+    fn test_startup_open_regions_concurrency_from_cmd() {
+        let Options::Datanode(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(16, opts.startup_open_regions_concurrency);
+
+        let cmd = StartCommand {
+            startup_open_regions_concurrency: Some(4),
+            ..Default::default()
+        };
+        let Options::Datanode(opts) = cmd.load_options(TopLevelOptions::default()).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(4, opts.startup_open_regions_concurrency);
+    }
+
+    #[test]
+    fn test_allow_version_mismatch_from_cmd() {
+        let Options::Datanode(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.allow_version_mismatch);
+
+        let cmd = StartCommand {
+            allow_version_mismatch: true,
+            ..Default::default()
+        };
+        let Options::Datanode(opts) = cmd.load_options(TopLevelOptions::default()).unwrap() else {
+            unreachable!()
+        };
+        assert!(opts.allow_version_mismatch);
+    }
+
+    #[test]
+    fn test_storage_cache_from_cmd() {
+        let cmd = StartCommand {
+            storage_cache_dir: Some("/tmp/greptimedb/cache".to_string()),
+            ..Default::default()
+        };
+        assert!(cmd.load_options(TopLevelOptions::default()).is_err());
+
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            [storage]
+            type = "S3"
+            bucket = "greptimedb"
+            root = "data"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+        let cmd = StartCommand {
+            config_file: Some(file.path().to_str().unwrap().to_string()),
+            storage_cache_dir: Some("/tmp/greptimedb/cache".to_string()),
+            storage_cache_size: Some(ReadableSize::gb(10)),
+            ..Default::default()
+        };
+        let Options::Datanode(opts) = cmd.load_options(TopLevelOptions::default()).unwrap() else {
+            unreachable!()
+        };
+        let cache = opts.storage.store.cache().unwrap();
+        assert_eq!(Some("/tmp/greptimedb/cache"), cache.cache_path.as_deref());
+        assert_eq!(Some(ReadableSize::gb(10)), cache.cache_capacity);
+    }
+
+    #[test]
+    fn test_wal_provider_from_cmd() {
+        let Options::Datanode(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(WalProvider::RaftEngine, opts.wal.provider);
+
+        let cmd = StartCommand {
+            wal_provider: Some("kafka".to_string()),
+            kafka_brokers: Some(vec![
+                "127.0.0.1:9092".to_string(),
+                "127.0.0.1:9093".to_string(),
+            ]),
+            kafka_topic_name_prefix: Some("wal".to_string()),
+            kafka_num_topics: Some(8),
+            ..Default::default()
+        };
+        let Options::Datanode(opts) = cmd.load_options(TopLevelOptions::default()).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(WalProvider::Kafka, opts.wal.provider);
+        assert_eq!(
+            vec!["127.0.0.1:9092".to_string(), "127.0.0.1:9093".to_string()],
+            opts.wal.kafka.broker_endpoints
+        );
+        assert_eq!("wal", opts.wal.kafka.topic_name_prefix);
+        assert_eq!(8, opts.wal.kafka.num_topics);
+
+        // The local WAL can't be configured with the remote WAL.
+        let cmd = StartCommand {
+            wal_provider: Some("kafka".to_string()),
+            wal_dir: Some("/tmp/wal".to_string()),
+            ..Default::default()
+        };
+        assert!(cmd.load_options(TopLevelOptions::default()).is_err());
+
+        let cmd = StartCommand {
+            wal_provider: Some("rocksdb".to_string()),
+            ..Default::default()
+        };
+        assert!(cmd.load_options(TopLevelOptions::default()).is_err());
+    }
+
     #[test]
     fn test_top_level_options() {
         let cmd = StartCommand::default();
@@ -359,6 +627,7 @@ mod tests {
             .load_options(TopLevelOptions {
                 log_dir: Some("/tmp/greptimedb/test/logs".to_string()),
                 log_level: Some("debug".to_string()),
+                ..Default::default()
             })
             .unwrap();
 
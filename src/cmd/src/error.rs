@@ -140,6 +140,12 @@ pub enum Error {
         source: common_recordbatch::error::Error,
     },
 
+    #[snafu(display("Failed to set up the TLS of the gRPC client"))]
+    ClientTls {
+        location: Location,
+        source: common_grpc::error::Error,
+    },
+
     #[snafu(display("Failed to start Meta client"))]
     StartMetaClient {
         location: Location,
@@ -178,6 +184,13 @@ pub enum Error {
         source: catalog::error::Error,
     },
 
+    #[snafu(display("Failed to connect to the metadata backend at {endpoints}"))]
+    ConnectMetadataBackend {
+        endpoints: String,
+        location: Location,
+        source: common_meta::error::Error,
+    },
+
     #[snafu(display("Failed to connect to Etcd at {etcd_addr}"))]
     ConnectEtcd {
         etcd_addr: String,
@@ -193,6 +206,44 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Server at {addr} is unreachable after {attempts} attempt(s)"))]
+    ServerUnreachable {
+        addr: String,
+        attempts: usize,
+        source: client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to stream logs from {url}"))]
+    StreamLogs {
+        url: String,
+        #[snafu(source)]
+        error: reqwest::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to validate sql by {url}"))]
+    ValidateSql {
+        url: String,
+        #[snafu(source)]
+        error: reqwest::Error,
+        location: Location,
+    },
+
+    #[snafu(display("{count} statement(s) failed the validation"))]
+    InvalidStatements { count: usize, location: Location },
+
+    #[snafu(display("Statement at line {line} failed, the statements after it are skipped"))]
+    StatementFailed { line: usize, location: Location },
+
+    #[snafu(display("Invalid CSV file {path} at line {line}: {reason}"))]
+    InvalidCsv {
+        path: String,
+        line: usize,
+        reason: String,
+        location: Location,
+    },
+
     #[snafu(display("Failed to serde json"))]
     SerdeJson {
         #[snafu(source)]
@@ -200,6 +251,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to serialize options to TOML"))]
+    TomlFormat {
+        #[snafu(source)]
+        error: toml::ser::Error,
+        location: Location,
+    },
+
     #[snafu(display("Expect data from output, but got another thing"))]
     NotDataFromOutput { location: Location },
 
@@ -225,10 +283,41 @@ pub enum Error {
         #[snafu(source)]
         error: std::io::Error,
     },
+
+    #[snafu(display("Failed to install the shutdown signal handlers"))]
+    InstallSignalHandlers {
+        location: Location,
+        #[snafu(source)]
+        error: std::io::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Exit code of a process failing on an invalid config.
+pub const CONFIG_ERROR_EXIT_CODE: i32 = 2;
+/// Exit code of a process failing to start a component.
+pub const STARTUP_ERROR_EXIT_CODE: i32 = 3;
+
+impl Error {
+    /// Returns the exit code of a process failing with the error, so scripts can tell
+    /// a config to fix from a failed start, 1 for the other errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::MissingConfig { .. }
+            | Error::IllegalConfig { .. }
+            | Error::LoadLayeredConfig { .. }
+            | Error::CreateDir { .. } => CONFIG_ERROR_EXIT_CODE,
+            Error::StartDatanode { .. }
+            | Error::StartFrontend { .. }
+            | Error::StartProcedureManager { .. }
+            | Error::StartMetaServer { .. }
+            | Error::InstallSignalHandlers { .. } => STARTUP_ERROR_EXIT_CODE,
+            _ => 1,
+        }
+    }
+}
+
 impl ErrorExt for Error {
     fn status_code(&self) -> StatusCode {
         match self {
@@ -243,7 +332,9 @@ impl ErrorExt for Error {
             Error::IterStream { source, .. } | Error::InitMetadata { source, .. } => {
                 source.status_code()
             }
-            Error::ConnectServer { source, .. } => source.status_code(),
+            Error::ConnectServer { source, .. } | Error::ServerUnreachable { source, .. } => {
+                source.status_code()
+            }
             Error::MissingConfig { .. }
             | Error::LoadLayeredConfig { .. }
             | Error::IllegalConfig { .. }
@@ -252,21 +343,31 @@ impl ErrorExt for Error {
             | Error::NotDataFromOutput { .. }
             | Error::CreateDir { .. }
             | Error::EmptyResult { .. }
-            | Error::InvalidDatabaseName { .. } => StatusCode::InvalidArguments,
+            | Error::InvalidDatabaseName { .. }
+            | Error::InvalidStatements { .. }
+            | Error::StatementFailed { .. }
+            | Error::InvalidCsv { .. } => StatusCode::InvalidArguments,
             Error::StartProcedureManager { source, .. }
             | Error::StopProcedureManager { source, .. } => source.status_code(),
-            Error::ReplCreation { .. } | Error::Readline { .. } => StatusCode::Internal,
+            Error::ConnectMetadataBackend { source, .. } => source.status_code(),
+            Error::ReplCreation { .. }
+            | Error::Readline { .. }
+            | Error::InstallSignalHandlers { .. } => StatusCode::Internal,
+            Error::StreamLogs { .. } | Error::ValidateSql { .. } => StatusCode::Internal,
             Error::RequestDatabase { source, .. } => source.status_code(),
             Error::CollectRecordBatches { source, .. }
             | Error::PrettyPrintRecordBatches { source, .. } => source.status_code(),
             Error::StartMetaClient { source, .. } => source.status_code(),
+            Error::ClientTls { source, .. } => source.status_code(),
             Error::ParseSql { source, .. } | Error::PlanStatement { source, .. } => {
                 source.status_code()
             }
             Error::SubstraitEncodeLogicalPlan { source, .. } => source.status_code(),
             Error::StartCatalogManager { source, .. } => source.status_code(),
 
-            Error::SerdeJson { .. } | Error::FileIo { .. } => StatusCode::Unexpected,
+            Error::SerdeJson { .. } | Error::TomlFormat { .. } | Error::FileIo { .. } => {
+                StatusCode::Unexpected
+            }
         }
     }
 
@@ -274,3 +375,42 @@ impl ErrorExt for Error {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use snafu::IntoError;
+
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        let io_error = || std::io::Error::new(std::io::ErrorKind::Other, "test");
+
+        let config_errors = [
+            MissingConfigSnafu { msg: "test" }.build(),
+            IllegalConfigSnafu { msg: "test" }.build(),
+            LoadLayeredConfigSnafu.into_error(ConfigError::Message("test".to_string())),
+            CreateDirSnafu { dir: "/test" }.into_error(io_error()),
+        ];
+        for err in config_errors {
+            assert_eq!(CONFIG_ERROR_EXIT_CODE, err.exit_code(), "{err}");
+        }
+
+        let startup_errors = [
+            StartFrontendSnafu
+                .into_error(frontend::error::InvalidSqlSnafu { err_msg: "test" }.build()),
+            InstallSignalHandlersSnafu.into_error(io_error()),
+        ];
+        for err in startup_errors {
+            assert_eq!(STARTUP_ERROR_EXIT_CODE, err.exit_code(), "{err}");
+        }
+
+        assert_eq!(1, EmptyResultSnafu.build().exit_code());
+        assert_eq!(
+            1,
+            InvalidReplCommandSnafu { reason: "test" }
+                .build()
+                .exit_code()
+        );
+    }
+}
@@ -15,16 +15,29 @@
 use std::time::Duration;
 
 use clap::Parser;
+use common_base::readable_size::ReadableSize;
+use common_error::ext::ErrorVerbosity;
 use common_telemetry::logging;
-use frontend::frontend::FrontendOptions;
+use frontend::frontend::{FrontendOptions, IngestProtocol};
 use frontend::instance::{FrontendInstance, Instance as FeInstance};
+use frontend::service_config::{QueryLogFormat, QueryOverflowPolicy};
 use meta_client::MetaClientOptions;
+use operator::float_special::FloatSpecialPolicy;
+use query::query_engine::options::FillPolicy;
+use servers::influxdb::TimeIndexPolicy;
+use servers::proxy_protocol::{ProxyProtocolOptions, ProxyProtocolVersion};
 use servers::tls::{TlsMode, TlsOption};
 use servers::Mode;
 use snafu::ResultExt;
+use sql::statements::statement::StatementKind;
+use store_api::region_request::WriteAckLevel;
 
 use crate::error::{self, Result, StartFrontendSnafu};
-use crate::options::{Options, TopLevelOptions};
+use crate::options::{
+    check_mysql_server_version, check_query_max_parallelism, check_tls_option,
+    check_user_provider_file, load_connection_init_sql, parse_external_labels, Options,
+    TopLevelOptions,
+};
 
 pub struct Instance {
     frontend: FeInstance,
@@ -89,7 +102,29 @@ pub struct StartCommand {
     #[clap(long)]
     http_timeout: Option<u64>,
     #[clap(long)]
+    http_keep_alive: Option<bool>,
+    #[clap(long)]
+    http_keep_alive_timeout: Option<u64>,
+    #[clap(long)]
+    http_max_requests_per_connection: Option<usize>,
+    #[clap(long)]
+    response_timeout_header: bool,
+    #[clap(long)]
+    accept_ranges: bool,
+    #[clap(long)]
+    admin_addr: Option<String>,
+    #[clap(long)]
+    admin_bind_localhost_only: Option<bool>,
+    #[clap(long)]
+    admin_allow_remote: bool,
+    #[clap(long)]
     rpc_addr: Option<String>,
+    /// Max size of a gRPC message received, like `512MiB`.
+    #[clap(long)]
+    rpc_max_recv_message_size: Option<ReadableSize>,
+    /// Max size of a gRPC message sent, like `512MiB`.
+    #[clap(long)]
+    rpc_max_send_message_size: Option<ReadableSize>,
     #[clap(long)]
     mysql_addr: Option<String>,
     #[clap(long)]
@@ -100,6 +135,38 @@ pub struct StartCommand {
     config_file: Option<String>,
     #[clap(short, long)]
     influxdb_enable: Option<bool>,
+    /// Whether to start the MySQL server, overriding the config and `--mysql-addr`.
+    #[clap(long, alias = "enable-mysql")]
+    mysql_enable: Option<bool>,
+    /// Whether to start the PostgreSQL server, overriding the config and
+    /// `--postgres-addr`.
+    #[clap(long, alias = "enable-postgres")]
+    postgres_enable: Option<bool>,
+    /// Whether to start the OpenTSDB server, overriding the config and
+    /// `--opentsdb-addr`.
+    #[clap(long, alias = "enable-opentsdb")]
+    opentsdb_enable: Option<bool>,
+    /// Whether to serve the Prometheus remote storage and the PromQL APIs.
+    #[clap(long, alias = "enable-prom-store")]
+    prom_store_enable: Option<bool>,
+    /// Whether to serve the InfluxDB 2.x write endpoint, true by default.
+    #[clap(long)]
+    influxdb_v2_api: Option<bool>,
+    #[clap(long)]
+    time_index_field: Option<String>,
+    #[clap(long)]
+    time_index_policy: Option<TimeIndexPolicy>,
+    #[clap(long)]
+    prom_store_native_histogram: bool,
+    /// Labels added to every series of Prometheus remote write, like `region=us-west`,
+    /// overriding the labels of the same names.
+    #[clap(
+        long,
+        alias = "prom-external-labels",
+        multiple = true,
+        value_delimiter = ','
+    )]
+    prom_store_external_labels: Option<Vec<String>>,
     #[clap(long, multiple = true, value_delimiter = ',')]
     metasrv_addr: Option<Vec<String>>,
     #[clap(long)]
@@ -109,15 +176,91 @@ pub struct StartCommand {
     #[clap(long)]
     tls_key_path: Option<String>,
     #[clap(long)]
+    tls_cert_watch: bool,
+    #[clap(long)]
+    connection_init_sql: Option<String>,
+    #[clap(long)]
     user_provider: Option<String>,
     #[clap(long)]
     disable_dashboard: Option<bool>,
+    #[clap(long)]
+    query_result_cache: bool,
+    #[clap(long)]
+    query_cache_size: Option<ReadableSize>,
+    #[clap(long)]
+    query_cache_ttl: Option<u64>,
+    #[clap(long)]
+    cardinality_warn_threshold: Option<u64>,
+    #[clap(long)]
+    query_log_format: Option<QueryLogFormat>,
+    #[clap(long)]
+    query_concurrency_limit: Option<usize>,
+    #[clap(long)]
+    query_concurrency_overflow: Option<QueryOverflowPolicy>,
+    #[clap(long, alias = "prometheus-self-monitoring")]
+    self_monitoring: bool,
+    #[clap(long)]
+    ingest_worker_threads: Option<usize>,
+    #[clap(long)]
+    query_worker_threads: Option<usize>,
+    #[clap(long)]
+    tcp_send_buffer: Option<ReadableSize>,
+    #[clap(long)]
+    tcp_recv_buffer: Option<ReadableSize>,
+    #[clap(long)]
+    fail_fast_on_missing_table: bool,
+    #[clap(long)]
+    listen_proxy_protocol: Option<ProxyProtocolVersion>,
+    #[clap(long)]
+    listen_proxy_protocol_allow_missing_header: bool,
+    #[clap(long)]
+    query_max_parallelism: Option<usize>,
+    #[clap(long)]
+    deny_full_table_scan: bool,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    full_table_scan_exempt_tables: Option<Vec<String>>,
+    #[clap(long)]
+    default_fill_policy: Option<FillPolicy>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    ingest_protocol_allowlist: Option<Vec<IngestProtocol>>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    sql_statement_allowlist: Option<Vec<StatementKind>>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    sql_statement_blocklist: Option<Vec<StatementKind>>,
+    #[clap(long)]
+    deny_sql_writes: bool,
+    #[clap(long)]
+    error_verbosity: Option<ErrorVerbosity>,
+    #[clap(long)]
+    max_table_count: Option<usize>,
+    #[clap(long)]
+    max_column_count: Option<usize>,
+    #[clap(long)]
+    write_ack_level: Option<WriteAckLevel>,
+    /// What to do with the NaN and infinite floats on ingest, `store`, `drop`, `error`
+    /// or `to-null`. Each protocol follows its convention if not set.
+    #[clap(long)]
+    float_special_policy: Option<FloatSpecialPolicy>,
+    #[clap(long)]
+    max_connections_per_user: Option<usize>,
+    #[clap(long)]
+    max_unauthenticated_connections: Option<usize>,
+    #[clap(long)]
+    mysql_server_version: Option<String>,
+    #[clap(long)]
+    client_idle_in_transaction_timeout: Option<u64>,
     #[clap(long, default_value = "GREPTIMEDB_FRONTEND")]
     env_prefix: String,
 }
 
 impl StartCommand {
     fn load_options(&self, top_level_opts: TopLevelOptions) -> Result<Options> {
+        if top_level_opts.strict_config {
+            if let Some(config_file) = &self.config_file {
+                Options::check_unknown_keys::<FrontendOptions>(config_file)?;
+            }
+        }
+
         let mut opts: FrontendOptions = Options::load_layered_options(
             self.config_file.as_deref(),
             self.env_prefix.as_ref(),
@@ -132,11 +275,16 @@ impl StartCommand {
             opts.logging.level = top_level_opts.log_level;
         }
 
-        let tls_opts = TlsOption::new(
+        if let Some(log_format) = top_level_opts.log_format {
+            opts.logging.log_format = log_format;
+        }
+
+        let mut tls_opts = TlsOption::new(
             self.tls_mode.clone(),
             self.tls_cert_path.clone(),
             self.tls_key_path.clone(),
         );
+        tls_opts.watch = self.tls_cert_watch;
 
         if let Some(addr) = &self.http_addr {
             opts.http.addr = addr.clone()
@@ -146,6 +294,40 @@ impl StartCommand {
             opts.http.timeout = Duration::from_secs(http_timeout)
         }
 
+        if let Some(keep_alive) = self.http_keep_alive {
+            opts.http.keep_alive = keep_alive;
+        }
+
+        if let Some(timeout) = self.http_keep_alive_timeout {
+            opts.http.keep_alive_timeout = Duration::from_secs(timeout);
+        }
+
+        if let Some(max_requests) = self.http_max_requests_per_connection {
+            opts.http.max_requests_per_connection = max_requests;
+        }
+
+        if self.response_timeout_header {
+            opts.http.response_timeout_header = true;
+        }
+
+        if self.accept_ranges {
+            opts.http.accept_ranges = true;
+        }
+
+        if let Some(addr) = &self.admin_addr {
+            opts.http.admin_addr = Some(addr.clone());
+        }
+
+        if self.admin_allow_remote {
+            opts.http.admin_allow_remote = true;
+            opts.http.admin_bind_localhost_only = false;
+        }
+
+        // Applied last, so an explicit restriction wins over `--admin-allow-remote`.
+        if let Some(localhost_only) = self.admin_bind_localhost_only {
+            opts.http.admin_bind_localhost_only = localhost_only;
+        }
+
         if let Some(disable_dashboard) = self.disable_dashboard {
             opts.http.disable_dashboard = disable_dashboard;
         }
@@ -154,6 +336,14 @@ impl StartCommand {
             opts.grpc.addr = addr.clone()
         }
 
+        if let Some(size) = self.rpc_max_recv_message_size {
+            opts.grpc.max_recv_message_size = size;
+        }
+
+        if let Some(size) = self.rpc_max_send_message_size {
+            opts.grpc.max_send_message_size = size;
+        }
+
         if let Some(addr) = &self.mysql_addr {
             opts.mysql.enable = true;
             opts.mysql.addr = addr.clone();
@@ -166,15 +356,55 @@ impl StartCommand {
             opts.postgres.tls = tls_opts;
         }
 
+        if let Some(init_sql) = &self.connection_init_sql {
+            let init_sql = load_connection_init_sql(init_sql)?;
+            opts.mysql.connection_init_sql = Some(init_sql.clone());
+            opts.postgres.connection_init_sql = Some(init_sql);
+        }
+
         if let Some(addr) = &self.opentsdb_addr {
             opts.opentsdb.enable = true;
             opts.opentsdb.addr = addr.clone();
         }
 
+        // Applied after the addresses, which enable the servers.
+        for (enable, option) in [
+            (self.mysql_enable, &mut opts.mysql.enable),
+            (self.postgres_enable, &mut opts.postgres.enable),
+            (self.opentsdb_enable, &mut opts.opentsdb.enable),
+            (self.prom_store_enable, &mut opts.prom_store.enable),
+        ] {
+            if let Some(enable) = enable {
+                *option = enable;
+            }
+        }
+
         if let Some(enable) = self.influxdb_enable {
             opts.influxdb.enable = enable;
         }
 
+        if let Some(enable) = self.influxdb_v2_api {
+            opts.influxdb.v2_api = enable;
+        }
+
+        if let Some(field) = &self.time_index_field {
+            opts.influxdb.time_index_field = Some(field.clone());
+        }
+
+        if let Some(policy) = self.time_index_policy {
+            opts.influxdb.time_index_policy = policy;
+        }
+
+        if self.prom_store_native_histogram {
+            opts.prom_store.native_histogram = true;
+        }
+
+        if let Some(labels) = &self.prom_store_external_labels {
+            opts.prom_store
+                .external_labels
+                .extend(parse_external_labels(labels)?);
+        }
+
         if let Some(metasrv_addrs) = &self.metasrv_addr {
             opts.meta_client
                 .get_or_insert_with(MetaClientOptions::default)
@@ -182,7 +412,152 @@ impl StartCommand {
             opts.mode = Mode::Distributed;
         }
 
+        if self.query_result_cache {
+            opts.query_cache.enable = true;
+        }
+
+        if let Some(size) = self.query_cache_size {
+            opts.query_cache.size = size;
+        }
+
+        if let Some(ttl) = self.query_cache_ttl {
+            opts.query_cache.ttl = Duration::from_secs(ttl);
+        }
+
+        if let Some(threshold) = self.cardinality_warn_threshold {
+            opts.cardinality.enable = true;
+            opts.cardinality.warn_threshold = threshold;
+        }
+
+        if let Some(format) = self.query_log_format {
+            opts.slow_query.format = format;
+        }
+
+        if let Some(limit) = self.query_concurrency_limit {
+            opts.query_admission.concurrency_limit = Some(limit);
+        }
+
+        if let Some(overflow) = self.query_concurrency_overflow {
+            opts.query_admission.overflow = overflow;
+        }
+
+        if self.self_monitoring {
+            opts.self_monitoring.enable = true;
+        }
+
+        if let Some(threads) = self.ingest_worker_threads {
+            opts.ingest_worker_threads = Some(threads);
+        }
+
+        if let Some(threads) = self.query_worker_threads {
+            opts.query_worker_threads = Some(threads);
+        }
+
+        if let Some(size) = self.tcp_send_buffer {
+            opts.tcp_send_buffer = Some(size);
+        }
+
+        if let Some(size) = self.tcp_recv_buffer {
+            opts.tcp_recv_buffer = Some(size);
+        }
+
+        if self.fail_fast_on_missing_table {
+            opts.http.fail_fast_on_missing_table = true;
+        }
+
+        if let Some(version) = self.listen_proxy_protocol {
+            let proxy_protocol = ProxyProtocolOptions {
+                version: Some(version),
+                allow_missing_header: self.listen_proxy_protocol_allow_missing_header,
+                ..Default::default()
+            };
+            opts.mysql.proxy_protocol = proxy_protocol;
+            opts.postgres.proxy_protocol = proxy_protocol;
+            opts.opentsdb.proxy_protocol = proxy_protocol;
+        }
+
+        if let Some(parallelism) = self.query_max_parallelism {
+            opts.query_max_parallelism = Some(parallelism);
+        }
+        check_query_max_parallelism(opts.query_max_parallelism)?;
+
+        if self.deny_full_table_scan {
+            opts.deny_full_table_scan = true;
+        }
+
+        if let Some(tables) = &self.full_table_scan_exempt_tables {
+            opts.full_table_scan_exempt_tables = tables.clone();
+        }
+
+        if let Some(policy) = self.default_fill_policy {
+            opts.default_fill_policy = policy;
+        }
+
+        if let Some(protocols) = &self.ingest_protocol_allowlist {
+            opts.ingest_protocol_allowlist = Some(protocols.clone());
+        }
+
+        if let Some(kinds) = &self.sql_statement_allowlist {
+            opts.sql_statement_allowlist = Some(kinds.clone());
+        }
+
+        if let Some(kinds) = &self.sql_statement_blocklist {
+            opts.sql_statement_blocklist = kinds.clone();
+        }
+
+        if self.deny_sql_writes {
+            opts.deny_sql_writes = true;
+        }
+
+        if let Some(verbosity) = self.error_verbosity {
+            opts.error_verbosity = verbosity;
+        }
+
+        if let Some(max) = self.max_table_count {
+            opts.max_table_count = Some(max);
+        }
+
+        if let Some(max) = self.max_column_count {
+            opts.max_column_count = Some(max);
+        }
+
+        if let Some(level) = self.write_ack_level {
+            opts.write_ack_level = level;
+        }
+
+        if let Some(policy) = self.float_special_policy {
+            opts.float_special_policy = Some(policy);
+        }
+
+        if let Some(max) = self.max_connections_per_user {
+            opts.max_connections_per_user = Some(max);
+        }
+
+        if let Some(max) = self.max_unauthenticated_connections {
+            opts.max_unauthenticated_connections = Some(max);
+        }
+
+        if let Some(version) = &self.mysql_server_version {
+            opts.mysql.server_version = version.clone();
+        }
+        check_mysql_server_version(&opts.mysql.server_version)?;
+        for (enabled, tls) in [
+            (opts.mysql.enable, &opts.mysql.tls),
+            (opts.postgres.enable, &opts.postgres.tls),
+        ] {
+            if enabled {
+                check_tls_option(tls)?;
+            }
+        }
+
+        if let Some(timeout) = self.client_idle_in_transaction_timeout {
+            opts.mysql.idle_in_transaction_timeout = Some(Duration::from_secs(timeout));
+        }
+
         opts.user_provider = self.user_provider.clone();
+        if let Some(user_provider) = &opts.user_provider {
+            check_user_provider_file(user_provider)?;
+        }
 
         Ok(Options::Frontend(Box::new(opts)))
     }
@@ -215,10 +590,10 @@ mod tests {
     use std::time::Duration;
 
     use auth::{Identity, Password, UserProviderRef};
-    use common_base::readable_size::ReadableSize;
     use common_test_util::temp_dir::create_named_temp_file;
     use frontend::service_config::GrpcOptions;
     use servers::http::HttpOptions;
+    use servers::mysql::server::DEFAULT_MYSQL_SERVER_VERSION;
 
     use super::*;
     use crate::options::ENV_VAR_SEP;
@@ -265,6 +640,529 @@ mod tests {
         assert!(!opts.influxdb.enable);
     }
 
+    #[test]
+    fn test_protocol_enable_from_cmd() {
+        let command = StartCommand::parse_from([
+            "frontend",
+            "--mysql-addr",
+            "127.0.0.1:5678",
+            "--enable-mysql=false",
+            "--postgres-enable",
+            "false",
+            "--opentsdb-enable=true",
+            "--prom-store-enable=false",
+        ]);
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+
+        assert!(!opts.mysql.enable);
+        assert_eq!(opts.mysql.addr, "127.0.0.1:5678");
+        assert!(!opts.postgres.enable);
+        assert!(opts.opentsdb.enable);
+        assert!(!opts.prom_store.enable);
+        assert!(opts.influxdb.enable);
+    }
+
+    #[test]
+    fn test_reject_zero_query_max_parallelism() {
+        let command = StartCommand {
+            query_max_parallelism: Some(0),
+            ..Default::default()
+        };
+        assert!(command.load_options(TopLevelOptions::default()).is_err());
+
+        let command = StartCommand {
+            query_max_parallelism: Some(1),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(1), opts.query_max_parallelism);
+    }
+
+    #[test]
+    fn test_listen_proxy_protocol_from_cmd() {
+        let command = StartCommand {
+            listen_proxy_protocol: Some(ProxyProtocolVersion::V2),
+            ..Default::default()
+        };
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+
+        let expected = ProxyProtocolOptions {
+            version: Some(ProxyProtocolVersion::V2),
+            ..Default::default()
+        };
+        assert_eq!(expected, opts.mysql.proxy_protocol);
+        assert_eq!(expected, opts.postgres.proxy_protocol);
+        assert_eq!(expected, opts.opentsdb.proxy_protocol);
+    }
+
+    #[test]
+    fn test_mysql_server_version_from_cmd() {
+        let Options::Frontend(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(DEFAULT_MYSQL_SERVER_VERSION, opts.mysql.server_version);
+
+        let command = StartCommand {
+            mysql_server_version: Some("8.4.2".to_string()),
+            ..Default::default()
+        };
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("8.4.2", opts.mysql.server_version);
+
+        let command = StartCommand {
+            mysql_server_version: Some("greptimedb".to_string()),
+            ..Default::default()
+        };
+        assert!(command.load_options(TopLevelOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_client_idle_in_transaction_timeout_from_cmd() {
+        let command = StartCommand::default();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(None, opts.mysql.idle_in_transaction_timeout);
+
+        let command = StartCommand {
+            client_idle_in_transaction_timeout: Some(300),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            Some(Duration::from_secs(300)),
+            opts.mysql.idle_in_transaction_timeout
+        );
+    }
+
+    #[test]
+    fn test_http_keep_alive_from_cmd() {
+        let command = StartCommand {
+            http_keep_alive_timeout: Some(75),
+            http_max_requests_per_connection: Some(1000),
+            ..Default::default()
+        };
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.http.keep_alive);
+        assert_eq!(Duration::from_secs(75), opts.http.keep_alive_timeout);
+        assert_eq!(1000, opts.http.max_requests_per_connection);
+
+        let command = StartCommand {
+            http_keep_alive: Some(false),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.http.keep_alive);
+    }
+
+    #[test]
+    fn test_tcp_buffer_from_cmd() {
+        let command = StartCommand {
+            tcp_send_buffer: Some(ReadableSize::mb(4)),
+            tcp_recv_buffer: Some(ReadableSize::kb(512)),
+            ..Default::default()
+        };
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(ReadableSize::mb(4)), opts.tcp_send_buffer);
+        assert_eq!(Some(ReadableSize::kb(512)), opts.tcp_recv_buffer);
+
+        let Options::Frontend(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.tcp_send_buffer.is_none());
+        assert!(opts.tcp_recv_buffer.is_none());
+    }
+
+    #[test]
+    fn test_time_index_from_cmd() {
+        let command = StartCommand {
+            time_index_field: Some("time".to_string()),
+            time_index_policy: Some(TimeIndexPolicy::Reject),
+            influxdb_v2_api: Some(false),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some("time"), opts.influxdb.time_index_field.as_deref());
+        assert_eq!(TimeIndexPolicy::Reject, opts.influxdb.time_index_policy);
+        assert!(!opts.influxdb.v2_api);
+    }
+
+    #[test]
+    fn test_prom_store_native_histogram_from_cmd() {
+        let command = StartCommand {
+            prom_store_native_histogram: true,
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.prom_store.native_histogram);
+    }
+
+    #[test]
+    fn test_prom_store_external_labels_from_cmd() {
+        let command = StartCommand::try_parse_from([
+            "frontend",
+            "--prom-store-external-labels",
+            "region=us-west,replica=a",
+            "--prom-external-labels",
+            "region=us-east",
+        ])
+        .unwrap();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            Some("us-east"),
+            opts.prom_store
+                .external_labels
+                .get("region")
+                .map(String::as_str)
+        );
+        assert_eq!(
+            Some("a"),
+            opts.prom_store
+                .external_labels
+                .get("replica")
+                .map(String::as_str)
+        );
+
+        let command = StartCommand {
+            prom_store_external_labels: Some(vec!["region".to_string()]),
+            ..Default::default()
+        };
+        assert!(command.load_options(TopLevelOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_response_timeout_header_from_cmd() {
+        let command = StartCommand {
+            http_timeout: Some(60),
+            response_timeout_header: true,
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.http.response_timeout_header);
+        assert_eq!(Duration::from_secs(60), opts.http.timeout);
+    }
+
+    #[test]
+    fn test_admin_listener_from_cmd() {
+        let command = StartCommand {
+            admin_addr: Some("0.0.0.0:4010".to_string()),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some("0.0.0.0:4010"), opts.http.admin_addr.as_deref());
+        assert!(opts.http.admin_bind_localhost_only);
+        assert!(!opts.http.admin_allow_remote);
+
+        let command = StartCommand {
+            admin_addr: Some("0.0.0.0:4010".to_string()),
+            admin_allow_remote: true,
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.http.admin_bind_localhost_only);
+        assert!(opts.http.admin_allow_remote);
+
+        // The localhost restriction wins.
+        let command = StartCommand {
+            admin_addr: Some("0.0.0.0:4010".to_string()),
+            admin_allow_remote: true,
+            admin_bind_localhost_only: Some(true),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.http.admin_bind_localhost_only);
+        assert_eq!(
+            "127.0.0.1:4010".parse::<std::net::SocketAddr>().unwrap(),
+            opts.http.admin_bind_addr("0.0.0.0:4010".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_default_fill_policy_from_cmd() {
+        let Options::Frontend(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(FillPolicy::Null, opts.default_fill_policy);
+
+        let command = StartCommand {
+            default_fill_policy: Some("prev".parse().unwrap()),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(FillPolicy::Prev, opts.default_fill_policy);
+    }
+
+    #[test]
+    fn test_accept_ranges_from_cmd() {
+        let Options::Frontend(opts) = StartCommand::default()
+            .load_options(TopLevelOptions::default())
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.http.accept_ranges);
+
+        let command = StartCommand {
+            accept_ranges: true,
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.http.accept_ranges);
+    }
+
+    #[test]
+    fn test_ingest_protocol_allowlist_from_cmd() {
+        let command = StartCommand {
+            ingest_protocol_allowlist: Some(vec![IngestProtocol::PromStore]),
+            deny_sql_writes: true,
+            ..Default::default()
+        };
+
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            Some(vec![IngestProtocol::PromStore]),
+            opts.ingest_protocol_allowlist
+        );
+        assert!(opts.is_ingest_allowed(IngestProtocol::PromStore));
+        assert!(!opts.is_ingest_allowed(IngestProtocol::Influxdb));
+        assert!(opts.deny_sql_writes);
+    }
+
+    #[test]
+    fn test_sql_statement_lists_from_cmd() {
+        let command = StartCommand {
+            sql_statement_allowlist: Some(vec![StatementKind::Query, StatementKind::Insert]),
+            sql_statement_blocklist: Some(vec![StatementKind::Delete]),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            Some(vec![StatementKind::Query, StatementKind::Insert]),
+            opts.sql_statement_allowlist
+        );
+        assert_eq!(vec![StatementKind::Delete], opts.sql_statement_blocklist);
+    }
+
+    #[test]
+    fn test_error_verbosity_from_cmd() {
+        let command = StartCommand::default();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(ErrorVerbosity::Normal, opts.error_verbosity);
+
+        let command = StartCommand {
+            error_verbosity: Some(ErrorVerbosity::Minimal),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(ErrorVerbosity::Minimal, opts.error_verbosity);
+    }
+
+    #[test]
+    fn test_schema_limits_from_cmd() {
+        let command = StartCommand {
+            max_table_count: Some(1000),
+            max_column_count: Some(200),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(1000), opts.max_table_count);
+        assert_eq!(Some(200), opts.max_column_count);
+    }
+
+    #[test]
+    fn test_write_ack_level_from_cmd() {
+        let command = StartCommand::default();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(WriteAckLevel::Memtable, opts.write_ack_level);
+
+        let command = StartCommand {
+            write_ack_level: Some(WriteAckLevel::Flush),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(WriteAckLevel::Flush, opts.write_ack_level);
+    }
+
+    #[test]
+    fn test_float_special_policy_from_cmd() {
+        let command =
+            StartCommand::try_parse_from(["frontend", "--float-special-policy", "to-null"])
+                .unwrap();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(FloatSpecialPolicy::ToNull), opts.float_special_policy);
+    }
+
+    #[test]
+    fn test_connection_limits_from_cmd() {
+        let command = StartCommand {
+            max_connections_per_user: Some(10),
+            max_unauthenticated_connections: Some(2),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(10), opts.max_connections_per_user);
+        assert_eq!(Some(2), opts.max_unauthenticated_connections);
+    }
+
+    #[test]
+    fn test_cardinality_from_cmd() {
+        let command = StartCommand::default();
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.cardinality.enable);
+
+        let command = StartCommand {
+            cardinality_warn_threshold: Some(5000),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.cardinality.enable);
+        assert_eq!(5000, opts.cardinality.warn_threshold);
+    }
+
+    #[test]
+    fn test_query_log_format_from_cmd() {
+        let command = StartCommand {
+            query_log_format: Some(QueryLogFormat::Json),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(QueryLogFormat::Json, opts.slow_query.format);
+        assert!(!opts.slow_query.enable);
+    }
+
+    #[test]
+    fn test_query_concurrency_from_cmd() {
+        let command = StartCommand {
+            query_concurrency_limit: Some(32),
+            query_concurrency_overflow: Some(QueryOverflowPolicy::Reject),
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(Some(32), opts.query_admission.concurrency_limit);
+        assert_eq!(QueryOverflowPolicy::Reject, opts.query_admission.overflow);
+        assert!(opts.query_admission.exempt_metadata);
+    }
+
+    #[test]
+    fn test_self_monitoring_from_cmd() {
+        let command = StartCommand {
+            self_monitoring: true,
+            ..Default::default()
+        };
+        let Options::Frontend(opts) = command.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(opts.self_monitoring.enable);
+        assert_eq!("greptime_private", opts.self_monitoring.database);
+        assert_eq!("metrics", opts.self_monitoring.table);
+    }
+
     #[test]
     fn test_read_from_config_file() {
         let mut file = create_named_temp_file();
@@ -337,6 +1235,7 @@ mod tests {
             .load_options(TopLevelOptions {
                 log_dir: Some("/tmp/greptimedb/test/logs".to_string()),
                 log_level: Some("debug".to_string()),
+                ..Default::default()
             })
             .unwrap();
 
@@ -419,6 +1318,7 @@ mod tests {
                 let top_level_opts = TopLevelOptions {
                     log_dir: None,
                     log_level: Some("error".to_string()),
+                    ..Default::default()
                 };
                 let Options::Frontend(fe_opts) = command.load_options(top_level_opts).unwrap()
                 else {
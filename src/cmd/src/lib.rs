@@ -15,6 +15,7 @@
 #![feature(assert_matches)]
 
 pub mod cli;
+pub mod config_template;
 pub mod datanode;
 pub mod error;
 pub mod frontend;
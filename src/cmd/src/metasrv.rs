@@ -21,7 +21,7 @@ use meta_srv::metasrv::MetaSrvOptions;
 use snafu::ResultExt;
 
 use crate::error::{self, Result, StartMetaServerSnafu};
-use crate::options::{Options, TopLevelOptions};
+use crate::options::{check_procedure_config, Options, TopLevelOptions};
 
 pub struct Instance {
     instance: MetaSrvInstance,
@@ -104,6 +104,12 @@ struct StartCommand {
 
 impl StartCommand {
     fn load_options(&self, top_level_opts: TopLevelOptions) -> Result<Options> {
+        if top_level_opts.strict_config {
+            if let Some(config_file) = &self.config_file {
+                Options::check_unknown_keys::<MetaSrvOptions>(config_file)?;
+            }
+        }
+
         let mut opts: MetaSrvOptions = Options::load_layered_options(
             self.config_file.as_deref(),
             self.env_prefix.as_ref(),
@@ -118,6 +124,10 @@ impl StartCommand {
             opts.logging.level = top_level_opts.log_level;
         }
 
+        if let Some(log_format) = top_level_opts.log_format {
+            opts.logging.log_format = log_format;
+        }
+
         if let Some(addr) = &self.bind_addr {
             opts.bind_addr = addr.clone();
         }
@@ -155,6 +165,8 @@ impl StartCommand {
         // Disable dashboard in metasrv.
         opts.http.disable_dashboard = true;
 
+        check_procedure_config(&opts.procedure)?;
+
         Ok(Options::Metasrv(Box::new(opts)))
     }
 
@@ -275,6 +287,7 @@ mod tests {
             .load_options(TopLevelOptions {
                 log_dir: Some("/tmp/greptimedb/test/logs".to_string()),
                 log_level: Some("debug".to_string()),
+                ..Default::default()
             })
             .unwrap();
 
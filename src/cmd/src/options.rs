@@ -12,27 +12,61 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_config::KvBackendConfig;
-use common_telemetry::logging::LoggingOptions;
-use config::{Config, Environment, File, FileFormat};
-use datanode::config::{DatanodeOptions, ProcedureConfig};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use common_base::readable_size::ReadableSize;
+use common_config::{KvBackendConfig, MetadataBackend, WalConfig, WalProvider};
+use common_telemetry::logging::{LogFormat, LoggingOptions};
+use config::builder::DefaultState;
+use config::{Config, ConfigBuilder, Environment, File, FileFormat};
+use datanode::config::{DatanodeOptions, ObjectStoreConfig, ProcedureConfig};
 use frontend::error::{Result as FeResult, TomlFormatSnafu};
 use frontend::frontend::{FrontendOptions, TomlSerializable};
 use meta_srv::metasrv::MetaSrvOptions;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use snafu::ResultExt;
+use servers::mysql::server::is_valid_server_version;
+use servers::prom_store::METRIC_NAME_LABEL;
+use servers::tls::{TlsMode, TlsOption};
+use snafu::{ensure, ResultExt};
 
-use crate::error::{LoadLayeredConfigSnafu, Result, SerdeJsonSnafu};
+use crate::error::{
+    FileIoSnafu, IllegalConfigSnafu, LoadLayeredConfigSnafu, Result, SerdeJsonSnafu,
+};
 
 pub const ENV_VAR_SEP: &str = "__";
 pub const ENV_LIST_SEP: &str = ",";
+/// Prefix of a command line value that refers to a file to read it from.
+pub const FILE_VALUE_PREFIX: &str = "file:";
+/// Max length of the instance id and the cluster name, which are attached to all
+/// the metrics as labels.
+const MAX_INSTANCE_TAG_LEN: usize = 128;
+/// File written and removed to check the WAL directory is writable.
+const WAL_DIR_PROBE_FILE: &str = ".greptimedb_write_check";
+
+/// Keys of the object store secrets, which are skipped while serializing the options.
+const SECRET_KEYS: &[&str] = &[
+    "access_key_id",
+    "secret_access_key",
+    "access_key_secret",
+    "account_name",
+    "account_key",
+    "credential_path",
+];
 
 /// Options mixed up from datanode, frontend and metasrv.
 #[derive(Serialize)]
 pub struct MixOptions {
     pub data_home: String,
+    /// Time to stop gracefully after a shutdown signal, the process exits immediately
+    /// once it elapses.
+    #[serde(with = "humantime_serde")]
+    pub shutdown_grace_period: Duration,
     pub procedure: ProcedureConfig,
     pub metadata_store: KvBackendConfig,
+    pub metadata_backend: MetadataBackend,
     pub frontend: FrontendOptions,
     pub datanode: DatanodeOptions,
     pub logging: LoggingOptions,
@@ -44,6 +78,46 @@ impl From<MixOptions> for FrontendOptions {
     }
 }
 
+/// Returns true if the env var `name` is set to `true` or `1`, for the switches set by
+/// an env var besides a flag.
+pub fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| value == "true" || value == "1")
+}
+
+/// Returns the protocol servers of the frontend and whether they start, like
+/// `mysql: 127.0.0.1:4002, influxdb: enabled, opentsdb: disabled`.
+pub fn protocol_summary(opts: &FrontendOptions) -> String {
+    let listener = |enable: bool, addr: &str| {
+        if enable {
+            addr.to_string()
+        } else {
+            "disabled".to_string()
+        }
+    };
+    let handler = |enable: bool| if enable { "enabled" } else { "disabled" }.to_string();
+
+    [
+        ("http", opts.http.addr.clone()),
+        ("grpc", opts.grpc.addr.clone()),
+        ("mysql", listener(opts.mysql.enable, &opts.mysql.addr)),
+        (
+            "postgres",
+            listener(opts.postgres.enable, &opts.postgres.addr),
+        ),
+        (
+            "opentsdb",
+            listener(opts.opentsdb.enable, &opts.opentsdb.addr),
+        ),
+        ("influxdb", handler(opts.influxdb.enable)),
+        ("prom_store", handler(opts.prom_store.enable)),
+        ("otlp", handler(opts.otlp.enable)),
+    ]
+    .iter()
+    .map(|(protocol, state)| format!("{protocol}: {state}"))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
 impl TomlSerializable for MixOptions {
     fn to_toml(&self) -> FeResult<String> {
         toml::to_string(self).context(TomlFormatSnafu)
@@ -56,12 +130,17 @@ pub enum Options {
     Metasrv(Box<MetaSrvOptions>),
     Standalone(Box<MixOptions>),
     Cli(Box<LoggingOptions>),
+    /// The resolved options printed by `--config-print` instead of starting.
+    PrintConfig(String),
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct TopLevelOptions {
     pub log_dir: Option<String>,
     pub log_level: Option<String>,
+    pub log_format: Option<LogFormat>,
+    /// Rejects unknown keys in the config file instead of ignoring them.
+    pub strict_config: bool,
 }
 
 impl Options {
@@ -72,6 +151,7 @@ impl Options {
             Options::Metasrv(opts) => &opts.logging,
             Options::Standalone(opts) => &opts.logging,
             Options::Cli(opts) => opts,
+            Options::PrintConfig(_) => unreachable!("the config is printed before the logging"),
         }
     }
 
@@ -87,6 +167,71 @@ impl Options {
         env_prefix: &str,
         list_keys: Option<&[&str]>,
     ) -> Result<T> {
+        let opts = Self::layered_config::<T>(config_file, env_prefix, list_keys)?
+            .build()
+            .context(LoadLayeredConfigSnafu)?
+            .try_deserialize()
+            .context(LoadLayeredConfigSnafu)?;
+
+        Ok(opts)
+    }
+
+    /// Applies the `dotted.path=value` overrides of `--set` to `opts`, which are loaded
+    /// by [Options::load_layered_options] from the same sources and then changed by
+    /// the flags, e.g. `region_engine[0].mito.num_workers=4`. The value is parsed as the
+    /// type of the key.
+    ///
+    /// The overrides take precedence over all the sources and the flags. The `opts` are
+    /// layered over the sources rather than deserialized alone, so the fields they don't
+    /// serialize, like the secrets of the object stores, are kept from the sources.
+    pub fn apply_overrides<'de, T: Serialize + Deserialize<'de> + Default>(
+        opts: T,
+        config_file: Option<&str>,
+        env_prefix: &str,
+        list_keys: Option<&[&str]>,
+        overrides: &[String],
+    ) -> Result<T> {
+        if overrides.is_empty() {
+            return Ok(opts);
+        }
+
+        // See the workaround in `layered_config`.
+        let json_str = serde_json::to_string(&opts).context(SerdeJsonSnafu)?;
+        let mut layered_config = Self::layered_config::<T>(config_file, env_prefix, list_keys)?
+            .add_source(File::from_str(&json_str, FileFormat::Json));
+
+        // The options loaded so far tell the keys and their types.
+        let loaded: serde_json::Value = layered_config
+            .build_cloned()
+            .context(LoadLayeredConfigSnafu)?
+            .try_deserialize()
+            .context(LoadLayeredConfigSnafu)?;
+        for token in overrides {
+            let (key, value) = parse_config_override(token, &loaded)?;
+            layered_config = layered_config.set_override(key, value).map_err(|e| {
+                IllegalConfigSnafu {
+                    msg: format!("invalid config override {token}: {e}"),
+                }
+                .build()
+            })?;
+        }
+
+        let opts = layered_config
+            .build()
+            .context(LoadLayeredConfigSnafu)?
+            .try_deserialize()
+            .context(LoadLayeredConfigSnafu)?;
+
+        Ok(opts)
+    }
+
+    /// Returns the sources of the options, the default values, the environment
+    /// variables and the config file from the lowest precedence.
+    fn layered_config<T: Serialize + Default>(
+        config_file: Option<&str>,
+        env_prefix: &str,
+        list_keys: Option<&[&str]>,
+    ) -> Result<ConfigBuilder<DefaultState>> {
         let default_opts = T::default();
 
         let env_source = {
@@ -125,14 +270,416 @@ impl Options {
             layered_config = layered_config.add_source(File::new(config_file, FileFormat::Toml));
         }
 
-        let opts = layered_config
+        Ok(layered_config)
+    }
+
+    /// Checks that every key in `config_file` is known to the options `T`, so that a
+    /// typo (e.g. `htpp.addr`) fails loudly instead of being silently ignored.
+    ///
+    /// The keys `T` doesn't know are ignored by deserializing the file, so keys are
+    /// checked against the options deserialized from the file and serialized back,
+    /// which include the tables of options unset by default and the variant of tagged
+    /// enums (e.g. `storage.type = "S3"`) set in the file.
+    pub fn check_unknown_keys<T: Serialize + DeserializeOwned>(config_file: &str) -> Result<()> {
+        let config = Config::builder()
+            .add_source(File::new(config_file, FileFormat::Toml))
             .build()
-            .context(LoadLayeredConfigSnafu)?
+            .context(LoadLayeredConfigSnafu)?;
+        let actual: serde_json::Value = config
+            .clone()
             .try_deserialize()
             .context(LoadLayeredConfigSnafu)?;
+        let loaded: T = config.try_deserialize().context(LoadLayeredConfigSnafu)?;
+        let expected = serde_json::to_value(loaded).context(SerdeJsonSnafu)?;
 
-        Ok(opts)
+        let mut unknown_keys = vec![];
+        collect_unknown_keys("", &actual, &expected, &mut unknown_keys);
+        unknown_keys.sort();
+
+        ensure!(
+            unknown_keys.is_empty(),
+            IllegalConfigSnafu {
+                msg: format!(
+                    "unknown config keys in {config_file}: {}",
+                    unknown_keys.join(", ")
+                ),
+            }
+        );
+        Ok(())
+    }
+
+    /// Returns the [protocol_summary] of the frontend, `None` if the process doesn't
+    /// run a frontend.
+    pub fn protocol_summary(&self) -> Option<String> {
+        match self {
+            Options::Frontend(opts) => Some(protocol_summary(opts)),
+            Options::Standalone(opts) => Some(protocol_summary(&opts.frontend)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the `dotted.path=value` of `--set` into the key and the value, which is parsed
+/// as the type of the key in the `loaded` options.
+fn parse_config_override(
+    token: &str,
+    loaded: &serde_json::Value,
+) -> Result<(String, config::Value)> {
+    use serde_json::Value;
+
+    let invalid = |reason: &str| {
+        IllegalConfigSnafu {
+            msg: format!("invalid config override {token}: {reason}"),
+        }
+        .build()
+    };
+
+    let (key, value) = token
+        .split_once('=')
+        .map(|(key, value)| (key.trim(), value))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| invalid("expected key=value"))?;
+    let current = lookup_config_path(loaded, key).ok_or_else(|| invalid("unknown key"))?;
+    let value = match current {
+        Value::Bool(_) => value
+            .parse::<bool>()
+            .map_err(|_| invalid("expected a bool"))?
+            .into(),
+        Value::Number(n) if n.is_f64() => value
+            .parse::<f64>()
+            .map_err(|_| invalid("expected a float"))?
+            .into(),
+        Value::Number(_) => value
+            .parse::<i64>()
+            .map_err(|_| invalid("expected an integer"))?
+            .into(),
+        Value::Object(_) | Value::Array(_) => return Err(invalid("not a single value")),
+        // A string, or an option unset by default, is parsed by the options.
+        Value::String(_) | Value::Null => value.into(),
+    };
+    Ok((key.to_string(), value))
+}
+
+/// Returns the value at `path` like `region_engine[0].mito.num_workers`.
+fn lookup_config_path<'a>(
+    value: &'a serde_json::Value,
+    path: &str,
+) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(value, |value, segment| {
+        let mut parts = segment.split('[');
+        let mut value = value.get(parts.next().filter(|name| !name.is_empty())?)?;
+        for index in parts {
+            value = value.get(index.strip_suffix(']')?.parse::<usize>().ok()?)?;
+        }
+        Some(value)
+    })
+}
+
+fn collect_unknown_keys(
+    path: &str,
+    actual: &serde_json::Value,
+    expected: &serde_json::Value,
+    unknown_keys: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, value) in actual {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match expected.get(key) {
+                    Some(expected) => {
+                        collect_unknown_keys(&key_path, value, expected, unknown_keys)
+                    }
+                    // Secrets are known but never serialized.
+                    None if SECRET_KEYS.contains(&key.as_str()) => {}
+                    // An empty table may be an empty map that isn't serialized.
+                    None if value.as_object().is_some_and(|table| table.is_empty()) => {}
+                    None => unknown_keys.push(key_path),
+                }
+            }
+        }
+        (Value::Array(actual), Value::Array(expected)) => {
+            for (i, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                collect_unknown_keys(&format!("{path}[{i}]"), actual, expected, unknown_keys);
+            }
+        }
+        // A value rather than a table of options.
+        _ => {}
+    }
+}
+
+/// Resolves the value of `--connection-init-sql`, which is either the SQL
+/// itself or `file:<path>` of a file containing it.
+pub fn load_connection_init_sql(value: &str) -> Result<String> {
+    match value.strip_prefix(FILE_VALUE_PREFIX) {
+        Some(path) => std::fs::read_to_string(path).context(FileIoSnafu),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Checks the credential file of a `<provider>:file:<path>` user provider is
+/// readable, so a missing secret fails on startup with its path rather than as an
+/// invalid auth config.
+pub fn check_user_provider_file(user_provider: &str) -> Result<()> {
+    let Some(path) = user_provider
+        .split_once(':')
+        .and_then(|(_, option)| option.strip_prefix(FILE_VALUE_PREFIX))
+    else {
+        return Ok(());
+    };
+    let readable = std::fs::File::open(path).and_then(|file| file.metadata());
+    match readable {
+        Ok(metadata) if metadata.is_file() => Ok(()),
+        Ok(_) => IllegalConfigSnafu {
+            msg: format!("user provider file {path} is not a file"),
+        }
+        .fail(),
+        Err(e) => IllegalConfigSnafu {
+            msg: format!("failed to read user provider file {path}: {e}"),
+        }
+        .fail(),
+    }
+}
+
+/// Checks the cert and key files of `tls` are readable and pair, unless the TLS is
+/// disabled, so a broken TLS setup fails on startup rather than on a handshake.
+pub fn check_tls_option(tls: &TlsOption) -> Result<()> {
+    if tls.mode == TlsMode::Disable {
+        return Ok(());
+    }
+    for (name, path) in [("cert", &tls.cert_path), ("key", &tls.key_path)] {
+        if let Err(e) = std::fs::File::open(path) {
+            return IllegalConfigSnafu {
+                msg: format!("failed to read TLS {name} file {path:?}: {e}"),
+            }
+            .fail();
+        }
+    }
+    if let Err(e) = tls.setup() {
+        return IllegalConfigSnafu {
+            msg: format!(
+                "invalid TLS cert file {} and key file {}: {e}",
+                tls.cert_path, tls.key_path
+            ),
+        }
+        .fail();
     }
+    Ok(())
+}
+
+/// Checks the raft-engine WAL directory `dir` is a writable directory, creating it if
+/// missing, and has the `wal.min_free_space` at least, so a read-only or full WAL volume
+/// fails on startup rather than on a write.
+pub fn check_wal_dir(wal: &WalConfig, dir: &str) -> Result<()> {
+    if wal.provider != WalProvider::RaftEngine {
+        return Ok(());
+    }
+    let path = Path::new(dir);
+    if let Err(e) = std::fs::create_dir_all(path) {
+        return IllegalConfigSnafu {
+            msg: format!("failed to create WAL directory {dir}: {e}"),
+        }
+        .fail();
+    }
+    let probe = path.join(WAL_DIR_PROBE_FILE);
+    if let Err(e) = std::fs::write(&probe, b"").and_then(|_| std::fs::remove_file(&probe)) {
+        return IllegalConfigSnafu {
+            msg: format!("WAL directory {dir} is not writable: {e}"),
+        }
+        .fail();
+    }
+    match fs2::available_space(path) {
+        Ok(available) if available < wal.min_free_space.0 => IllegalConfigSnafu {
+            msg: format!(
+                "WAL directory {dir} has {} free space, less than the wal.min_free_space {}",
+                ReadableSize(available),
+                wal.min_free_space
+            ),
+        }
+        .fail(),
+        Ok(_) => Ok(()),
+        Err(e) => IllegalConfigSnafu {
+            msg: format!("failed to get the free space of WAL directory {dir}: {e}"),
+        }
+        .fail(),
+    }
+}
+
+/// Checks that the MySQL server version is parseable by strict clients.
+pub fn check_mysql_server_version(version: &str) -> Result<()> {
+    ensure!(
+        is_valid_server_version(version),
+        IllegalConfigSnafu {
+            msg: format!(
+                "invalid MySQL server version: {version:?}, expect a version like \"8.4.2\" or \"8.4.2-suffix\""
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Checks that a query may be split into at least one partition.
+pub fn check_query_max_parallelism(parallelism: Option<usize>) -> Result<()> {
+    ensure!(
+        parallelism != Some(0),
+        IllegalConfigSnafu {
+            msg: "query max parallelism must be at least 1, 1 executes queries serially",
+        }
+    );
+    Ok(())
+}
+
+/// Checks that the procedures can be submitted.
+pub fn check_procedure_config(procedure: &ProcedureConfig) -> Result<()> {
+    ensure!(
+        procedure.max_running_procedures > 0,
+        IllegalConfigSnafu {
+            msg: "procedure.max_running_procedures must be at least 1, otherwise all procedures are rejected",
+        }
+    );
+    Ok(())
+}
+
+/// Sets the local read cache of the object storage from `--storage-cache-dir` and
+/// `--storage-cache-size`, which the file storage doesn't have.
+pub fn set_storage_cache(
+    store: &mut ObjectStoreConfig,
+    dir: Option<&String>,
+    size: Option<ReadableSize>,
+) -> Result<()> {
+    if dir.is_none() && size.is_none() {
+        return Ok(());
+    }
+    let Some(cache) = store.cache_mut() else {
+        return IllegalConfigSnafu {
+            msg: "The storage cache only applies to the object storage like S3, not the file storage",
+        }
+        .fail();
+    };
+    if let Some(dir) = dir {
+        cache.cache_path = Some(dir.clone());
+    }
+    if let Some(size) = size {
+        cache.cache_capacity = Some(size);
+    }
+    Ok(())
+}
+
+/// Checks the object storage of the data has the keys it can't be built without, so a
+/// missing bucket fails on startup with the keys to set rather than on the first write.
+pub fn check_object_store_config(store: &ObjectStoreConfig) -> Result<()> {
+    let missing = store.missing_keys();
+    ensure!(
+        missing.is_empty(),
+        IllegalConfigSnafu {
+            msg: format!(
+                "the {} storage requires {}",
+                store.name(),
+                missing
+                    .iter()
+                    .map(|key| format!("storage.{key}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Splits a listen address into the host and the port, `None` if it has no port.
+fn split_listen_addr(addr: &str) -> Option<(&str, u16)> {
+    let (host, port) = addr.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Some((host, port.parse().ok()?))
+}
+
+/// Checks that the listen addresses of the servers, named by their protocols, don't
+/// collide, so all the servers can bind. An address of all the interfaces collides with
+/// any address of the same port. Port 0, picked by the OS, never collides.
+pub fn check_listen_addrs(addrs: &[(&str, &str)]) -> Result<()> {
+    let mut bound: Vec<(&str, &str, &str, u16)> = Vec::with_capacity(addrs.len());
+    for (protocol, addr) in addrs {
+        let Some((host, port)) = split_listen_addr(addr) else {
+            continue;
+        };
+        if port == 0 {
+            continue;
+        }
+        let is_unspecified = |host: &str| matches!(host, "" | "0.0.0.0" | "::");
+        let conflict = bound.iter().find(|(_, _, other_host, other_port)| {
+            *other_port == port
+                && (host == *other_host || is_unspecified(host) || is_unspecified(other_host))
+        });
+        if let Some((other_protocol, other_addr, _, _)) = conflict {
+            return IllegalConfigSnafu {
+                msg: format!(
+                    "{protocol} listen address {addr} conflicts with {other_protocol} listen address {other_addr}"
+                ),
+            }
+            .fail();
+        }
+        bound.push((protocol, addr, host, port));
+    }
+    Ok(())
+}
+
+/// Parses the `key=value` external labels of Prometheus remote write. A label name is
+/// `[a-zA-Z_][a-zA-Z0-9_]*`, and the metric name `__name__` can't be set.
+pub fn parse_external_labels(labels: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut parsed = BTreeMap::new();
+    for label in labels {
+        let Some((name, value)) = label.split_once('=') else {
+            return IllegalConfigSnafu {
+                msg: format!("invalid external label {label}, expecting key=value"),
+            }
+            .fail();
+        };
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && name != METRIC_NAME_LABEL;
+        ensure!(
+            valid,
+            IllegalConfigSnafu {
+                msg: format!("invalid external label name {name}"),
+            }
+        );
+        let _ = parsed.insert(name.to_string(), value.to_string());
+    }
+    Ok(parsed)
+}
+
+/// Returns the tags of the process, which are attached to all the logs as fields and
+/// to all the metrics as labels. The instance id defaults to the hostname.
+pub fn instance_tags(
+    instance_id: Option<String>,
+    cluster_name: Option<String>,
+) -> Result<Vec<(String, String)>> {
+    let instance_id =
+        instance_id.or_else(|| hostname::get().ok().and_then(|x| x.into_string().ok()));
+
+    let mut tags = Vec::new();
+    for (key, value) in [("instance_id", instance_id), ("cluster_name", cluster_name)] {
+        let Some(value) = value else {
+            continue;
+        };
+        ensure!(
+            !value.is_empty()
+                && value.len() <= MAX_INSTANCE_TAG_LEN
+                && value.chars().all(|c| c.is_ascii_graphic()),
+            IllegalConfigSnafu {
+                msg: format!(
+                    "invalid {key}: {value:?}, expect at most {MAX_INSTANCE_TAG_LEN} printable ASCII characters without spaces"
+                ),
+            }
+        );
+        tags.push((key.to_string(), value));
+    }
+    Ok(tags)
 }
 
 #[cfg(test)]
@@ -140,11 +687,64 @@ mod tests {
     use std::io::Write;
     use std::time::Duration;
 
-    use common_test_util::temp_dir::create_named_temp_file;
-    use datanode::config::{DatanodeOptions, ObjectStoreConfig};
+    use common_test_util::temp_dir::{create_named_temp_file, create_temp_dir};
+    use datanode::config::{DatanodeOptions, ObjectStoreConfig, OssConfig};
 
     use super::*;
 
+    #[test]
+    fn test_check_unknown_keys() {
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            mode = "distributed"
+            rpc_addr = "127.0.0.1:3001"
+            mysql_addr = "127.0.0.1:4406"
+
+            [htpp]
+            addr = "127.0.0.1:4000"
+
+            [http]
+            adr = "127.0.0.1:4000"
+
+            [wal]
+            dir = "/tmp/greptimedb/wal"
+
+            [storage]
+            type = "S3"
+            bucket = "mybucket"
+            access_key_id = "id"
+            buckte = "mybucket"
+
+            [meta_client]
+            timeot = "3s"
+
+            [[region_engine]]
+            [region_engine.mito]
+            num_workers = 8
+            num_worker = 8
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+
+        let err = Options::check_unknown_keys::<DatanodeOptions>(file.path().to_str().unwrap())
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with(": htpp, http.adr, meta_client.timeot, mysql_addr, region_engine[0].mito.num_worker, storage.buckte"),
+            "{err}"
+        );
+
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            mode = "distributed"
+            rpc_addr = "127.0.0.1:3001"
+
+            [wal]
+            dir = "/tmp/greptimedb/wal"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+        Options::check_unknown_keys::<DatanodeOptions>(file.path().to_str().unwrap()).unwrap();
+    }
+
     #[test]
     fn test_load_layered_options() {
         let mut file = create_named_temp_file();
@@ -285,4 +885,221 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_load_connection_init_sql() {
+        let sql = "SET time_zone = '+08:00'";
+        assert_eq!(sql, load_connection_init_sql(sql).unwrap());
+
+        let mut file = create_named_temp_file();
+        write!(file, "{}", sql).unwrap();
+        let value = format!("{FILE_VALUE_PREFIX}{}", file.path().to_str().unwrap());
+        assert_eq!(sql, load_connection_init_sql(&value).unwrap());
+
+        assert!(load_connection_init_sql("file:/not/exist.sql").is_err());
+    }
+
+    #[test]
+    fn test_check_listen_addrs() {
+        assert!(check_listen_addrs(&[
+            ("HTTP", "127.0.0.1:4000"),
+            ("gRPC", "127.0.0.1:4001"),
+            ("MySQL", "0.0.0.0:4002"),
+            ("PostgreSQL", "127.0.0.1:0"),
+            ("OpenTSDB", "127.0.0.1:0"),
+        ])
+        .is_ok());
+        // Same port on different interfaces.
+        assert!(check_listen_addrs(&[
+            ("MySQL", "127.0.0.1:4002"),
+            ("PostgreSQL", "10.0.0.1:4002")
+        ])
+        .is_ok());
+
+        let err = check_listen_addrs(&[
+            ("HTTP", "127.0.0.1:4000"),
+            ("MySQL", "127.0.0.1:4002"),
+            ("PostgreSQL", "127.0.0.1:4002"),
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains(
+            "PostgreSQL listen address 127.0.0.1:4002 conflicts with MySQL listen address 127.0.0.1:4002"
+        ));
+        assert!(
+            check_listen_addrs(&[("MySQL", "0.0.0.0:4002"), ("PostgreSQL", "127.0.0.1:4002")])
+                .is_err()
+        );
+        assert!(check_listen_addrs(&[("HTTP", "127.0.0.1:4000"), ("gRPC", "[::]:4000")]).is_err());
+    }
+
+    #[test]
+    fn test_check_user_provider_file() {
+        let mut file = create_named_temp_file();
+        writeln!(file, "root=123456").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        check_user_provider_file(&format!("static_user_provider:file:{path}")).unwrap();
+        check_user_provider_file("static_user_provider:cmd:root=123456").unwrap();
+
+        let err = check_user_provider_file("static_user_provider:file:/not/exist/users")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/not/exist/users"), "{err}");
+
+        let dir = file.path().parent().unwrap().to_str().unwrap();
+        let err = check_user_provider_file(&format!("static_user_provider:file:{dir}"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is not a file"), "{err}");
+    }
+
+    #[test]
+    fn test_check_object_store_config() {
+        check_object_store_config(&ObjectStoreConfig::default()).unwrap();
+
+        let store = ObjectStoreConfig::Oss(OssConfig {
+            bucket: "greptimedb".to_string(),
+            ..Default::default()
+        });
+        let err = check_object_store_config(&store).unwrap_err().to_string();
+        assert!(
+            err.contains("the Oss storage requires storage.endpoint"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_check_wal_dir() {
+        let dir = create_temp_dir("test_check_wal_dir");
+        let wal_dir = dir.path().join("wal");
+        let wal_dir = wal_dir.to_str().unwrap();
+
+        // Created if missing.
+        check_wal_dir(&WalConfig::default(), wal_dir).unwrap();
+        assert!(Path::new(wal_dir).is_dir());
+        assert!(!Path::new(wal_dir).join(WAL_DIR_PROBE_FILE).exists());
+
+        let wal = WalConfig {
+            min_free_space: ReadableSize(u64::MAX),
+            ..Default::default()
+        };
+        let err = check_wal_dir(&wal, wal_dir).unwrap_err().to_string();
+        assert!(err.contains("less than the wal.min_free_space"), "{err}");
+
+        let file = create_named_temp_file();
+        let file = file.path().to_str().unwrap();
+        let err = check_wal_dir(&WalConfig::default(), file)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("failed to create WAL directory"), "{err}");
+
+        // Not checked for the other providers.
+        let wal = WalConfig {
+            provider: WalProvider::Kafka,
+            min_free_space: ReadableSize(u64::MAX),
+            ..Default::default()
+        };
+        check_wal_dir(&wal, file).unwrap();
+    }
+
+    #[test]
+    fn test_check_query_max_parallelism() {
+        check_query_max_parallelism(None).unwrap();
+        check_query_max_parallelism(Some(1)).unwrap();
+        assert!(check_query_max_parallelism(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_check_procedure_config() {
+        check_procedure_config(&ProcedureConfig::default()).unwrap();
+        let procedure = ProcedureConfig {
+            max_running_procedures: 0,
+            ..Default::default()
+        };
+        assert!(check_procedure_config(&procedure).is_err());
+    }
+
+    #[test]
+    fn test_check_tls_option() {
+        let ssl_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../servers/tests/ssl");
+        let tls = |cert: &str, key: &str| {
+            TlsOption::new(
+                Some(TlsMode::Require),
+                Some(format!("{ssl_dir}/{cert}")),
+                Some(format!("{ssl_dir}/{key}")),
+            )
+        };
+
+        check_tls_option(&TlsOption::default()).unwrap();
+        check_tls_option(&tls("server.crt", "server-rsa.key")).unwrap();
+
+        let err = check_tls_option(&tls("server.crt", "missing.key"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("failed to read TLS key file"), "{err}");
+        assert!(err.contains("missing.key"), "{err}");
+
+        let err = check_tls_option(&tls("server.crt", "root-ca.key"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("the key doesn't match the cert"), "{err}");
+    }
+
+    #[test]
+    fn test_protocol_summary() {
+        let mut opts = FrontendOptions::default();
+        opts.opentsdb.enable = false;
+        opts.otlp.enable = false;
+        let summary = protocol_summary(&opts);
+        assert!(summary
+            .starts_with("http: 127.0.0.1:4000, grpc: 127.0.0.1:4001, mysql: 127.0.0.1:4002"));
+        assert!(summary.contains("opentsdb: disabled"), "{summary}");
+        assert!(summary.contains("influxdb: enabled"), "{summary}");
+        assert!(summary.ends_with("otlp: disabled"), "{summary}");
+
+        assert!(Options::Cli(Box::default()).protocol_summary().is_none());
+    }
+
+    #[test]
+    fn test_parse_external_labels() {
+        let labels = parse_external_labels(&[
+            "region=us-west".to_string(),
+            "replica=".to_string(),
+            "region=us-east".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            BTreeMap::from([
+                ("region".to_string(), "us-east".to_string()),
+                ("replica".to_string(), String::new()),
+            ]),
+            labels
+        );
+
+        assert!(parse_external_labels(&["region".to_string()]).is_err());
+        assert!(parse_external_labels(&["1region=a".to_string()]).is_err());
+        assert!(parse_external_labels(&["re-gion=a".to_string()]).is_err());
+        assert!(parse_external_labels(&["__name__=up".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_instance_tags() {
+        let tags = instance_tags(Some("node-1".to_string()), Some("prod".to_string())).unwrap();
+        assert_eq!(
+            vec![
+                ("instance_id".to_string(), "node-1".to_string()),
+                ("cluster_name".to_string(), "prod".to_string()),
+            ],
+            tags
+        );
+
+        // Defaults to the hostname.
+        let hostname = hostname::get().unwrap().into_string().unwrap();
+        let tags = instance_tags(None, None).unwrap();
+        assert_eq!(vec![("instance_id".to_string(), hostname)], tags);
+
+        assert!(instance_tags(Some(String::new()), None).is_err());
+        assert!(instance_tags(Some("node 1".to_string()), None).is_err());
+        assert!(instance_tags(None, Some("x".repeat(MAX_INSTANCE_TAG_LEN + 1))).is_err());
+    }
 }
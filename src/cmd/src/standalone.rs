@@ -13,40 +13,68 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 use std::{fs, path};
 
 use catalog::kvbackend::KvBackendCatalogManager;
 use catalog::CatalogManagerRef;
 use clap::Parser;
+use common_base::readable_size::ReadableSize;
 use common_base::Plugins;
-use common_config::{metadata_store_dir, KvBackendConfig, WalConfig};
+use common_config::{metadata_store_dir, KvBackendConfig, MetadataBackend, WalConfig};
+use common_error::ext::ErrorVerbosity;
 use common_meta::cache_invalidator::DummyKvCacheInvalidator;
+use common_meta::kv_backend::etcd::EtcdStore;
 use common_meta::kv_backend::KvBackendRef;
+use common_meta::state_store::KvStateStore;
 use common_procedure::ProcedureManagerRef;
-use common_telemetry::info;
+use common_runtime::RepeatedTask;
 use common_telemetry::logging::LoggingOptions;
-use datanode::config::{DatanodeOptions, ProcedureConfig, RegionEngineConfig, StorageConfig};
+use common_telemetry::{error, info};
+use datanode::config::{
+    BackupConfig, DatanodeOptions, ProcedureConfig, RegionEngineConfig, StorageConfig,
+    DEFAULT_STARTUP_OPEN_REGIONS_CONCURRENCY,
+};
 use datanode::datanode::{Datanode, DatanodeBuilder};
 use datanode::region_server::RegionServer;
 use file_engine::config::EngineConfig as FileEngineConfig;
-use frontend::frontend::FrontendOptions;
-use frontend::instance::{FrontendInstance, Instance as FeInstance, StandaloneDatanodeManager};
+use frontend::frontend::{FrontendOptions, IngestProtocol};
+use frontend::instance::{Instance as FeInstance, StandaloneDatanodeManager};
 use frontend::service_config::{
-    GrpcOptions, InfluxdbOptions, MysqlOptions, OpentsdbOptions, PostgresOptions, PromStoreOptions,
+    CardinalityOptions, GrpcOptions, InfluxdbOptions, MysqlOptions, OpentsdbOptions,
+    PostgresOptions, PromStoreOptions, QueryAdmissionOptions, QueryCacheOptions, QueryLogFormat,
+    QueryOverflowPolicy, SelfMonitoringOptions, SlowQueryOptions,
 };
 use mito2::config::MitoConfig;
+use mito2::region::options::{CompactionStrategy, DuplicateTimestampPolicy};
+use operator::float_special::FloatSpecialPolicy;
+use operator::insert::DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL;
+use query::query_engine::options::FillPolicy;
 use serde::{Deserialize, Serialize};
+use servers::http::readiness::{
+    new_liveness_watchdog, new_metadata_store_check, Liveness, Readiness, StartupPhase,
+};
 use servers::http::HttpOptions;
+use servers::influxdb::TimeIndexPolicy;
+use servers::proxy_protocol::{ProxyProtocolOptions, ProxyProtocolVersion};
+use servers::query_handler::BackupHandlerRef;
 use servers::tls::{TlsMode, TlsOption};
 use servers::Mode;
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::statement::StatementKind;
+use store_api::region_request::WriteAckLevel;
 
 use crate::error::{
-    CreateDirSnafu, IllegalConfigSnafu, InitMetadataSnafu, Result, ShutdownDatanodeSnafu,
-    ShutdownFrontendSnafu, StartDatanodeSnafu, StartFrontendSnafu, StartProcedureManagerSnafu,
-    StopProcedureManagerSnafu,
+    ConnectMetadataBackendSnafu, CreateDirSnafu, IllegalConfigSnafu, InitMetadataSnafu, Result,
+    ShutdownDatanodeSnafu, ShutdownFrontendSnafu, StartDatanodeSnafu, StartFrontendSnafu,
+    StartProcedureManagerSnafu, StopProcedureManagerSnafu, TomlFormatSnafu,
+};
+use crate::options::{
+    check_listen_addrs, check_mysql_server_version, check_object_store_config,
+    check_procedure_config, check_query_max_parallelism, check_tls_option,
+    check_user_provider_file, check_wal_dir, env_flag, load_connection_init_sql,
+    parse_external_labels, set_storage_cache, MixOptions, Options, TopLevelOptions,
 };
-use crate::options::{MixOptions, Options, TopLevelOptions};
 
 #[derive(Parser)]
 pub struct Command {
@@ -62,6 +90,20 @@ impl Command {
     pub fn load_options(&self, top_level_options: TopLevelOptions) -> Result<Options> {
         self.subcmd.load_options(top_level_options)
     }
+
+    /// Returns true if the options are only validated, by [Command::validate] instead
+    /// of [Command::build].
+    pub fn validate_only(&self) -> bool {
+        match &self.subcmd {
+            SubCommand::Start(cmd) => cmd.validate_only,
+        }
+    }
+
+    pub async fn validate(&self, opts: MixOptions) -> Result<()> {
+        match &self.subcmd {
+            SubCommand::Start(cmd) => cmd.validate(&opts).await.map(|_| ()),
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -83,11 +125,27 @@ impl SubCommand {
     }
 }
 
+/// Default prefix of the environment variables of the options.
+pub(crate) const DEFAULT_ENV_PREFIX: &str = "GREPTIMEDB_STANDALONE";
+
+/// Env var disabling the telemetry like `--disable-telemetry`, if `true` or `1`.
+const DISABLE_TELEMETRY_ENV: &str = "GREPTIMEDB_DISABLE_TELEMETRY";
+
+/// Node id of the datanode unless `--node-id` is set.
+const DEFAULT_NODE_ID: u64 = 0;
+
+/// The largest node id, exact as a JSON number in the HTTP APIs and the tools.
+const MAX_NODE_ID: u64 = (1 << 53) - 1;
+
+/// Default time to stop gracefully after a shutdown signal.
+const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct StandaloneOptions {
     pub mode: Mode,
     pub enable_telemetry: bool,
+    pub collect_os_metrics: bool,
     pub http: HttpOptions,
     pub grpc: GrpcOptions,
     pub mysql: MysqlOptions,
@@ -95,9 +153,41 @@ pub struct StandaloneOptions {
     pub opentsdb: OpentsdbOptions,
     pub influxdb: InfluxdbOptions,
     pub prom_store: PromStoreOptions,
+    pub query_cache: QueryCacheOptions,
+    pub cardinality: CardinalityOptions,
+    pub slow_query: SlowQueryOptions,
+    pub self_monitoring: SelfMonitoringOptions,
+    pub query_admission: QueryAdmissionOptions,
+    pub ingest_worker_threads: Option<usize>,
+    pub query_worker_threads: Option<usize>,
+    pub tcp_send_buffer: Option<ReadableSize>,
+    pub tcp_recv_buffer: Option<ReadableSize>,
+    pub query_max_parallelism: Option<usize>,
+    pub deny_full_table_scan: bool,
+    pub full_table_scan_exempt_tables: Vec<String>,
+    pub default_fill_policy: FillPolicy,
+    pub ingest_protocol_allowlist: Option<Vec<IngestProtocol>>,
+    pub sql_statement_allowlist: Option<Vec<StatementKind>>,
+    pub sql_statement_blocklist: Vec<StatementKind>,
+    pub deny_sql_writes: bool,
+    pub error_verbosity: ErrorVerbosity,
+    pub max_table_count: Option<usize>,
+    pub max_column_count: Option<usize>,
+    pub write_ack_level: WriteAckLevel,
+    pub max_request_write_ack_level: WriteAckLevel,
+    pub float_special_policy: Option<FloatSpecialPolicy>,
+    pub max_connections_per_user: Option<usize>,
+    pub max_unauthenticated_connections: Option<usize>,
+    /// Time to stop gracefully after a shutdown signal, see [MixOptions].
+    #[serde(with = "humantime_serde")]
+    pub shutdown_grace_period: Duration,
+    pub startup_open_regions_concurrency: usize,
+    pub allow_version_mismatch: bool,
     pub wal: WalConfig,
     pub storage: StorageConfig,
+    pub backup: BackupConfig,
     pub metadata_store: KvBackendConfig,
+    pub metadata_backend: MetadataBackend,
     pub procedure: ProcedureConfig,
     pub logging: LoggingOptions,
     pub user_provider: Option<String>,
@@ -110,6 +200,7 @@ impl Default for StandaloneOptions {
         Self {
             mode: Mode::Standalone,
             enable_telemetry: true,
+            collect_os_metrics: false,
             http: HttpOptions::default(),
             grpc: GrpcOptions::default(),
             mysql: MysqlOptions::default(),
@@ -117,9 +208,39 @@ impl Default for StandaloneOptions {
             opentsdb: OpentsdbOptions::default(),
             influxdb: InfluxdbOptions::default(),
             prom_store: PromStoreOptions::default(),
+            query_cache: QueryCacheOptions::default(),
+            cardinality: CardinalityOptions::default(),
+            slow_query: SlowQueryOptions::default(),
+            self_monitoring: SelfMonitoringOptions::default(),
+            query_admission: QueryAdmissionOptions::default(),
+            ingest_worker_threads: None,
+            query_worker_threads: None,
+            tcp_send_buffer: None,
+            tcp_recv_buffer: None,
+            query_max_parallelism: None,
+            deny_full_table_scan: false,
+            full_table_scan_exempt_tables: vec![],
+            default_fill_policy: FillPolicy::default(),
+            ingest_protocol_allowlist: None,
+            sql_statement_allowlist: None,
+            sql_statement_blocklist: vec![],
+            deny_sql_writes: false,
+            error_verbosity: ErrorVerbosity::default(),
+            max_table_count: None,
+            max_column_count: None,
+            write_ack_level: WriteAckLevel::default(),
+            max_request_write_ack_level: DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL,
+            float_special_policy: None,
+            max_connections_per_user: None,
+            max_unauthenticated_connections: None,
+            shutdown_grace_period: DEFAULT_SHUTDOWN_GRACE_PERIOD,
+            startup_open_regions_concurrency: DEFAULT_STARTUP_OPEN_REGIONS_CONCURRENCY,
+            allow_version_mismatch: false,
             wal: WalConfig::default(),
             storage: StorageConfig::default(),
+            backup: BackupConfig::default(),
             metadata_store: KvBackendConfig::default(),
+            metadata_backend: MetadataBackend::default(),
             procedure: ProcedureConfig::default(),
             logging: LoggingOptions::default(),
             user_provider: None,
@@ -142,6 +263,31 @@ impl StandaloneOptions {
             opentsdb: self.opentsdb,
             influxdb: self.influxdb,
             prom_store: self.prom_store,
+            query_cache: self.query_cache,
+            cardinality: self.cardinality,
+            slow_query: self.slow_query,
+            query_admission: self.query_admission,
+            self_monitoring: self.self_monitoring,
+            ingest_worker_threads: self.ingest_worker_threads,
+            query_worker_threads: self.query_worker_threads,
+            tcp_send_buffer: self.tcp_send_buffer,
+            tcp_recv_buffer: self.tcp_recv_buffer,
+            query_max_parallelism: self.query_max_parallelism,
+            deny_full_table_scan: self.deny_full_table_scan,
+            full_table_scan_exempt_tables: self.full_table_scan_exempt_tables,
+            default_fill_policy: self.default_fill_policy,
+            ingest_protocol_allowlist: self.ingest_protocol_allowlist,
+            sql_statement_allowlist: self.sql_statement_allowlist,
+            sql_statement_blocklist: self.sql_statement_blocklist,
+            deny_sql_writes: self.deny_sql_writes,
+            error_verbosity: self.error_verbosity,
+            max_table_count: self.max_table_count,
+            max_column_count: self.max_column_count,
+            write_ack_level: self.write_ack_level,
+            max_request_write_ack_level: self.max_request_write_ack_level,
+            float_special_policy: self.float_special_policy,
+            max_connections_per_user: self.max_connections_per_user,
+            max_unauthenticated_connections: self.max_unauthenticated_connections,
             meta_client: None,
             logging: self.logging,
             user_provider: self.user_provider,
@@ -149,12 +295,16 @@ impl StandaloneOptions {
         }
     }
 
-    fn datanode_options(self) -> DatanodeOptions {
+    fn datanode_options(self, node_id: u64) -> DatanodeOptions {
         DatanodeOptions {
-            node_id: Some(0),
+            node_id: Some(node_id),
             enable_telemetry: self.enable_telemetry,
+            collect_os_metrics: self.collect_os_metrics,
+            startup_open_regions_concurrency: self.startup_open_regions_concurrency,
+            allow_version_mismatch: self.allow_version_mismatch,
             wal: self.wal,
             storage: self.storage,
+            backup: self.backup,
             region_engine: self.region_engine,
             ..Default::default()
         }
@@ -165,28 +315,76 @@ pub struct Instance {
     datanode: Datanode,
     frontend: FeInstance,
     procedure_manager: ProcedureManagerRef,
+    shutdown_grace_period: Duration,
+    /// Reported by `/readyz`, advanced as the components are started.
+    readiness: Readiness,
+    /// Beats the heartbeat reported by `/livez`.
+    liveness_watchdog: RepeatedTask<servers::error::Error>,
+    /// Fails the readiness while the metadata store is unreachable.
+    metadata_store_check: RepeatedTask<servers::error::Error>,
 }
 
 impl Instance {
+    /// Starts the components, the catalog is already initialized by the build.
+    ///
+    /// The datanode starts first, as both the procedures and the requests write its
+    /// regions. Then the procedure manager recovers while the other components of the
+    /// frontend start. The servers are only started after the recovery, otherwise the
+    /// DDL of the requests received meanwhile fails as the manager isn't started.
     pub async fn start(&mut self) -> Result<()> {
+        if let Err(e) = self.liveness_watchdog.start(common_runtime::bg_runtime()) {
+            error!(e; "Failed to start liveness watchdog");
+        }
+
         // Start datanode instance before starting services, to avoid requests come in before internal components are started.
         self.datanode.start().await.context(StartDatanodeSnafu)?;
         info!("Datanode instance started");
-
-        self.procedure_manager
-            .start()
+        self.readiness.advance(StartupPhase::DatanodeStarted);
+
+        let start_procedure_manager = async {
+            self.procedure_manager
+                .start()
+                .await
+                .context(StartProcedureManagerSnafu)?;
+            self.readiness
+                .advance(StartupPhase::ProcedureManagerStarted);
+            Result::Ok(())
+        };
+        let start_frontend = async {
+            self.frontend
+                .start_components()
+                .await
+                .context(StartFrontendSnafu)
+        };
+        tokio::try_join!(start_procedure_manager, start_frontend)?;
+        self.frontend
+            .start_servers()
             .await
-            .context(StartProcedureManagerSnafu)?;
+            .context(StartFrontendSnafu)?;
+        self.readiness.advance(StartupPhase::Ready);
 
-        self.frontend.start().await.context(StartFrontendSnafu)?;
+        if let Err(e) = self
+            .metadata_store_check
+            .start(common_runtime::bg_runtime())
+        {
+            error!(e; "Failed to start metadata store check");
+        }
         Ok(())
     }
 
+    /// Stops the servers first so no more requests come in, then flushes the regions
+    /// so the next start doesn't replay the WAL, before stopping the procedures and
+    /// the datanode.
     pub async fn stop(&self) -> Result<()> {
         self.frontend
             .shutdown()
             .await
             .context(ShutdownFrontendSnafu)?;
+        info!("Frontend servers stopped");
+        let _ = self.metadata_store_check.stop().await;
+        let _ = self.liveness_watchdog.stop().await;
+
+        self.datanode.region_server().flush_regions().await;
 
         self.procedure_manager
             .stop()
@@ -201,6 +399,11 @@ impl Instance {
 
         Ok(())
     }
+
+    /// Returns the time to stop gracefully after a shutdown signal.
+    pub fn shutdown_grace_period(&self) -> Duration {
+        self.shutdown_grace_period
+    }
 }
 
 #[derive(Debug, Default, Parser)]
@@ -208,7 +411,29 @@ struct StartCommand {
     #[clap(long)]
     http_addr: Option<String>,
     #[clap(long)]
+    http_keep_alive: Option<bool>,
+    #[clap(long)]
+    http_keep_alive_timeout: Option<u64>,
+    #[clap(long)]
+    http_max_requests_per_connection: Option<usize>,
+    #[clap(long)]
+    response_timeout_header: bool,
+    #[clap(long)]
+    accept_ranges: bool,
+    #[clap(long)]
+    admin_addr: Option<String>,
+    #[clap(long)]
+    admin_bind_localhost_only: Option<bool>,
+    #[clap(long)]
+    admin_allow_remote: bool,
+    #[clap(long)]
     rpc_addr: Option<String>,
+    /// Max size of a gRPC message received, like `512MiB`.
+    #[clap(long)]
+    rpc_max_recv_message_size: Option<ReadableSize>,
+    /// Max size of a gRPC message sent, like `512MiB`.
+    #[clap(long)]
+    rpc_max_send_message_size: Option<ReadableSize>,
     #[clap(long)]
     mysql_addr: Option<String>,
     #[clap(long)]
@@ -217,6 +442,38 @@ struct StartCommand {
     opentsdb_addr: Option<String>,
     #[clap(short, long)]
     influxdb_enable: bool,
+    /// Whether to start the MySQL server, overriding the config and `--mysql-addr`.
+    #[clap(long, alias = "enable-mysql")]
+    mysql_enable: Option<bool>,
+    /// Whether to start the PostgreSQL server, overriding the config and
+    /// `--postgres-addr`.
+    #[clap(long, alias = "enable-postgres")]
+    postgres_enable: Option<bool>,
+    /// Whether to start the OpenTSDB server, overriding the config and
+    /// `--opentsdb-addr`.
+    #[clap(long, alias = "enable-opentsdb")]
+    opentsdb_enable: Option<bool>,
+    /// Whether to serve the Prometheus remote storage and the PromQL APIs.
+    #[clap(long, alias = "enable-prom-store")]
+    prom_store_enable: Option<bool>,
+    /// Whether to serve the InfluxDB 2.x write endpoint, true by default.
+    #[clap(long)]
+    influxdb_v2_api: Option<bool>,
+    #[clap(long)]
+    time_index_field: Option<String>,
+    #[clap(long)]
+    time_index_policy: Option<TimeIndexPolicy>,
+    #[clap(long)]
+    prom_store_native_histogram: bool,
+    /// Labels added to every series of Prometheus remote write, like `region=us-west`,
+    /// overriding the labels of the same names.
+    #[clap(
+        long,
+        alias = "prom-external-labels",
+        multiple = true,
+        value_delimiter = ','
+    )]
+    prom_store_external_labels: Option<Vec<String>>,
     #[clap(short, long)]
     config_file: Option<String>,
     #[clap(long)]
@@ -226,13 +483,138 @@ struct StartCommand {
     #[clap(long)]
     tls_key_path: Option<String>,
     #[clap(long)]
+    tls_cert_watch: bool,
+    #[clap(long)]
+    connection_init_sql: Option<String>,
+    #[clap(long)]
     user_provider: Option<String>,
-    #[clap(long, default_value = "GREPTIMEDB_STANDALONE")]
+    #[clap(long)]
+    query_result_cache: bool,
+    #[clap(long)]
+    query_cache_size: Option<ReadableSize>,
+    #[clap(long)]
+    query_cache_ttl: Option<u64>,
+    #[clap(long)]
+    cardinality_warn_threshold: Option<u64>,
+    #[clap(long)]
+    query_log_format: Option<QueryLogFormat>,
+    #[clap(long)]
+    query_concurrency_limit: Option<usize>,
+    #[clap(long)]
+    query_concurrency_overflow: Option<QueryOverflowPolicy>,
+    #[clap(long, alias = "prometheus-self-monitoring")]
+    self_monitoring: bool,
+    #[clap(long)]
+    ingest_worker_threads: Option<usize>,
+    #[clap(long)]
+    query_worker_threads: Option<usize>,
+    #[clap(long)]
+    tcp_send_buffer: Option<ReadableSize>,
+    #[clap(long)]
+    tcp_recv_buffer: Option<ReadableSize>,
+    #[clap(long)]
+    fail_fast_on_missing_table: bool,
+    #[clap(long)]
+    listen_proxy_protocol: Option<ProxyProtocolVersion>,
+    #[clap(long)]
+    listen_proxy_protocol_allow_missing_header: bool,
+    #[clap(long)]
+    query_max_parallelism: Option<usize>,
+    #[clap(long)]
+    deny_full_table_scan: bool,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    full_table_scan_exempt_tables: Option<Vec<String>>,
+    #[clap(long)]
+    default_fill_policy: Option<FillPolicy>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    ingest_protocol_allowlist: Option<Vec<IngestProtocol>>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    sql_statement_allowlist: Option<Vec<StatementKind>>,
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    sql_statement_blocklist: Option<Vec<StatementKind>>,
+    #[clap(long)]
+    deny_sql_writes: bool,
+    #[clap(long)]
+    error_verbosity: Option<ErrorVerbosity>,
+    #[clap(long)]
+    max_table_count: Option<usize>,
+    #[clap(long)]
+    max_column_count: Option<usize>,
+    #[clap(long)]
+    write_ack_level: Option<WriteAckLevel>,
+    /// What to do with the NaN and infinite floats on ingest, `store`, `drop`, `error`
+    /// or `to-null`. Each protocol follows its convention if not set.
+    #[clap(long)]
+    float_special_policy: Option<FloatSpecialPolicy>,
+    #[clap(long)]
+    max_connections_per_user: Option<usize>,
+    #[clap(long)]
+    max_unauthenticated_connections: Option<usize>,
+    #[clap(long)]
+    mysql_server_version: Option<String>,
+    #[clap(long)]
+    client_idle_in_transaction_timeout: Option<u64>,
+    #[clap(long)]
+    keep_versions: Option<usize>,
+    #[clap(long)]
+    keep_versions_duration: Option<u64>,
+    #[clap(long)]
+    flush_interval: Option<u64>,
+    #[clap(long)]
+    compaction_strategy: Option<CompactionStrategy>,
+    #[clap(long)]
+    duplicate_timestamp_policy: Option<DuplicateTimestampPolicy>,
+    #[clap(long)]
+    startup_open_regions_concurrency: Option<usize>,
+    #[clap(long)]
+    allow_version_mismatch: bool,
+    #[clap(long)]
+    collect_os_metrics: bool,
+    /// Disables the telemetry, also disabled by the `GREPTIMEDB_DISABLE_TELEMETRY`
+    /// env var set to `true`.
+    #[clap(long)]
+    disable_telemetry: bool,
+    #[clap(long)]
+    data_home: Option<String>,
+    /// Node id of the datanode, 0 by default, or `auto` to derive it from the hostname
+    /// and the gRPC port, so the instances sharing a metadata backend don't collide.
+    #[clap(long)]
+    node_id: Option<String>,
+    /// Endpoints of the etcd storing the metadata, instead of the kv store under the
+    /// data home.
+    #[clap(long, multiple = true, value_delimiter = ',')]
+    metasrv_addr: Option<Vec<String>>,
+    #[clap(long)]
+    storage_cache_dir: Option<String>,
+    #[clap(long)]
+    storage_cache_size: Option<ReadableSize>,
+    #[clap(long, default_value = DEFAULT_ENV_PREFIX)]
     env_prefix: String,
+    /// Overrides of the options without a flag, like
+    /// `--set region_engine[0].mito.num_workers=4`, over the config file, the env vars
+    /// and the other flags.
+    #[clap(long = "set")]
+    overrides: Vec<String>,
+    /// Prints the options resolved from the config file, the environment variables
+    /// and the flags as TOML, then exits without starting.
+    #[clap(long)]
+    config_print: bool,
+    /// Prints the secrets of the user provider and the TLS key paths as well.
+    #[clap(long, requires = "config_print")]
+    config_print_unredacted: bool,
+    /// Validates the options like a start, then exits without starting anything.
+    #[clap(long)]
+    validate_only: bool,
 }
 
 impl StartCommand {
     fn load_options(&self, top_level_options: TopLevelOptions) -> Result<Options> {
+        if top_level_options.strict_config {
+            if let Some(config_file) = &self.config_file {
+                Options::check_unknown_keys::<StandaloneOptions>(config_file)?;
+            }
+        }
+
         let mut opts: StandaloneOptions = Options::load_layered_options(
             self.config_file.as_deref(),
             self.env_prefix.as_ref(),
@@ -249,16 +631,55 @@ impl StartCommand {
             opts.logging.level = top_level_options.log_level;
         }
 
-        let tls_opts = TlsOption::new(
+        if let Some(log_format) = top_level_options.log_format {
+            opts.logging.log_format = log_format;
+        }
+
+        let mut tls_opts = TlsOption::new(
             self.tls_mode.clone(),
             self.tls_cert_path.clone(),
             self.tls_key_path.clone(),
         );
+        tls_opts.watch = self.tls_cert_watch;
 
         if let Some(addr) = &self.http_addr {
             opts.http.addr = addr.clone()
         }
 
+        if let Some(keep_alive) = self.http_keep_alive {
+            opts.http.keep_alive = keep_alive;
+        }
+
+        if let Some(timeout) = self.http_keep_alive_timeout {
+            opts.http.keep_alive_timeout = Duration::from_secs(timeout);
+        }
+
+        if let Some(max_requests) = self.http_max_requests_per_connection {
+            opts.http.max_requests_per_connection = max_requests;
+        }
+
+        if self.response_timeout_header {
+            opts.http.response_timeout_header = true;
+        }
+
+        if self.accept_ranges {
+            opts.http.accept_ranges = true;
+        }
+
+        if let Some(addr) = &self.admin_addr {
+            opts.http.admin_addr = Some(addr.clone());
+        }
+
+        if self.admin_allow_remote {
+            opts.http.admin_allow_remote = true;
+            opts.http.admin_bind_localhost_only = false;
+        }
+
+        // Applied last, so an explicit restriction wins over `--admin-allow-remote`.
+        if let Some(localhost_only) = self.admin_bind_localhost_only {
+            opts.http.admin_bind_localhost_only = localhost_only;
+        }
+
         if let Some(addr) = &self.rpc_addr {
             // frontend grpc addr conflict with datanode default grpc addr
             let datanode_grpc_addr = DatanodeOptions::default().rpc_addr;
@@ -273,6 +694,14 @@ impl StartCommand {
             opts.grpc.addr = addr.clone()
         }
 
+        if let Some(size) = self.rpc_max_recv_message_size {
+            opts.grpc.max_recv_message_size = size;
+        }
+
+        if let Some(size) = self.rpc_max_send_message_size {
+            opts.grpc.max_send_message_size = size;
+        }
+
         if let Some(addr) = &self.mysql_addr {
             opts.mysql.enable = true;
             opts.mysql.addr = addr.clone();
@@ -285,44 +714,348 @@ impl StartCommand {
             opts.postgres.tls = tls_opts;
         }
 
+        if let Some(init_sql) = &self.connection_init_sql {
+            let init_sql = load_connection_init_sql(init_sql)?;
+            opts.mysql.connection_init_sql = Some(init_sql.clone());
+            opts.postgres.connection_init_sql = Some(init_sql);
+        }
+
         if let Some(addr) = &self.opentsdb_addr {
             opts.opentsdb.enable = true;
             opts.opentsdb.addr = addr.clone();
         }
 
+        // Applied after the addresses, which enable the servers.
+        for (enable, option) in [
+            (self.mysql_enable, &mut opts.mysql.enable),
+            (self.postgres_enable, &mut opts.postgres.enable),
+            (self.opentsdb_enable, &mut opts.opentsdb.enable),
+            (self.prom_store_enable, &mut opts.prom_store.enable),
+        ] {
+            if let Some(enable) = enable {
+                *option = enable;
+            }
+        }
+
         if self.influxdb_enable {
             opts.influxdb.enable = self.influxdb_enable;
         }
 
+        if let Some(enable) = self.influxdb_v2_api {
+            opts.influxdb.v2_api = enable;
+        }
+
+        if let Some(field) = &self.time_index_field {
+            opts.influxdb.time_index_field = Some(field.clone());
+        }
+
+        if let Some(policy) = self.time_index_policy {
+            opts.influxdb.time_index_policy = policy;
+        }
+
+        if self.prom_store_native_histogram {
+            opts.prom_store.native_histogram = true;
+        }
+
+        if let Some(labels) = &self.prom_store_external_labels {
+            opts.prom_store
+                .external_labels
+                .extend(parse_external_labels(labels)?);
+        }
+
+        if self.query_result_cache {
+            opts.query_cache.enable = true;
+        }
+
+        if let Some(size) = self.query_cache_size {
+            opts.query_cache.size = size;
+        }
+
+        if let Some(ttl) = self.query_cache_ttl {
+            opts.query_cache.ttl = Duration::from_secs(ttl);
+        }
+
+        if let Some(threshold) = self.cardinality_warn_threshold {
+            opts.cardinality.enable = true;
+            opts.cardinality.warn_threshold = threshold;
+        }
+
+        if let Some(format) = self.query_log_format {
+            opts.slow_query.format = format;
+        }
+
+        if let Some(limit) = self.query_concurrency_limit {
+            opts.query_admission.concurrency_limit = Some(limit);
+        }
+
+        if let Some(overflow) = self.query_concurrency_overflow {
+            opts.query_admission.overflow = overflow;
+        }
+
+        if self.self_monitoring {
+            opts.self_monitoring.enable = true;
+        }
+
+        if let Some(threads) = self.ingest_worker_threads {
+            opts.ingest_worker_threads = Some(threads);
+        }
+
+        if let Some(threads) = self.query_worker_threads {
+            opts.query_worker_threads = Some(threads);
+        }
+
+        if let Some(size) = self.tcp_send_buffer {
+            opts.tcp_send_buffer = Some(size);
+        }
+
+        if let Some(size) = self.tcp_recv_buffer {
+            opts.tcp_recv_buffer = Some(size);
+        }
+
+        if self.fail_fast_on_missing_table {
+            opts.http.fail_fast_on_missing_table = true;
+        }
+
+        if let Some(version) = self.listen_proxy_protocol {
+            let proxy_protocol = ProxyProtocolOptions {
+                version: Some(version),
+                allow_missing_header: self.listen_proxy_protocol_allow_missing_header,
+                ..Default::default()
+            };
+            opts.mysql.proxy_protocol = proxy_protocol;
+            opts.postgres.proxy_protocol = proxy_protocol;
+            opts.opentsdb.proxy_protocol = proxy_protocol;
+        }
+
+        if let Some(parallelism) = self.query_max_parallelism {
+            opts.query_max_parallelism = Some(parallelism);
+        }
+
+        if self.deny_full_table_scan {
+            opts.deny_full_table_scan = true;
+        }
+
+        if let Some(tables) = &self.full_table_scan_exempt_tables {
+            opts.full_table_scan_exempt_tables = tables.clone();
+        }
+
+        if let Some(policy) = self.default_fill_policy {
+            opts.default_fill_policy = policy;
+        }
+
+        if let Some(protocols) = &self.ingest_protocol_allowlist {
+            opts.ingest_protocol_allowlist = Some(protocols.clone());
+        }
+
+        if let Some(kinds) = &self.sql_statement_allowlist {
+            opts.sql_statement_allowlist = Some(kinds.clone());
+        }
+
+        if let Some(kinds) = &self.sql_statement_blocklist {
+            opts.sql_statement_blocklist = kinds.clone();
+        }
+
+        if self.deny_sql_writes {
+            opts.deny_sql_writes = true;
+        }
+
+        if let Some(verbosity) = self.error_verbosity {
+            opts.error_verbosity = verbosity;
+        }
+
+        if let Some(max) = self.max_table_count {
+            opts.max_table_count = Some(max);
+        }
+
+        if let Some(max) = self.max_column_count {
+            opts.max_column_count = Some(max);
+        }
+
+        if let Some(level) = self.write_ack_level {
+            opts.write_ack_level = level;
+        }
+
+        if let Some(policy) = self.float_special_policy {
+            opts.float_special_policy = Some(policy);
+        }
+
+        if let Some(max) = self.max_connections_per_user {
+            opts.max_connections_per_user = Some(max);
+        }
+
+        if let Some(max) = self.max_unauthenticated_connections {
+            opts.max_unauthenticated_connections = Some(max);
+        }
+
+        if let Some(version) = &self.mysql_server_version {
+            opts.mysql.server_version = version.clone();
+        }
+
+        if let Some(timeout) = self.client_idle_in_transaction_timeout {
+            opts.mysql.idle_in_transaction_timeout = Some(Duration::from_secs(timeout));
+        }
+
+        if let Some(concurrency) = self.startup_open_regions_concurrency {
+            opts.startup_open_regions_concurrency = concurrency;
+        }
+
+        if self.allow_version_mismatch {
+            opts.allow_version_mismatch = true;
+        }
+
+        if self.collect_os_metrics {
+            opts.collect_os_metrics = true;
+        }
+
+        if self.disable_telemetry || env_flag(DISABLE_TELEMETRY_ENV) {
+            opts.enable_telemetry = false;
+        }
+
+        if let Some(data_home) = &self.data_home {
+            opts.storage.data_home = data_home.clone();
+        }
+
+        if let Some(endpoints) = &self.metasrv_addr {
+            opts.metadata_backend = MetadataBackend::Etcd {
+                endpoints: endpoints.clone(),
+            };
+        }
+
+        set_storage_cache(
+            &mut opts.storage.store,
+            self.storage_cache_dir.as_ref(),
+            self.storage_cache_size,
+        )?;
+
+        for engine in &mut opts.region_engine {
+            if let RegionEngineConfig::Mito(config) = engine {
+                if let Some(versions) = self.keep_versions {
+                    config.compaction_keep_versions = versions;
+                }
+                if let Some(duration) = self.keep_versions_duration {
+                    config.compaction_keep_versions_duration = Some(Duration::from_secs(duration));
+                }
+                if let Some(interval) = self.flush_interval {
+                    config.periodic_flush_interval = Some(Duration::from_secs(interval));
+                }
+                if let Some(strategy) = self.compaction_strategy {
+                    config.compaction_strategy = strategy;
+                }
+                if let Some(policy) = self.duplicate_timestamp_policy {
+                    config.duplicate_timestamp_policy = policy;
+                }
+            }
+        }
+
         opts.user_provider = self.user_provider.clone();
 
+        let opts = Options::apply_overrides(
+            opts,
+            self.config_file.as_deref(),
+            self.env_prefix.as_ref(),
+            None,
+            &self.overrides,
+        )?;
+
+        // The options are checked after the overrides, which may change any of them.
+        check_query_max_parallelism(opts.query_max_parallelism)?;
+        check_mysql_server_version(&opts.mysql.server_version)?;
+        for (enabled, tls) in [
+            (opts.mysql.enable, &opts.mysql.tls),
+            (opts.postgres.enable, &opts.postgres.tls),
+        ] {
+            if enabled {
+                check_tls_option(tls)?;
+            }
+        }
+        if let MetadataBackend::Etcd { endpoints } = &opts.metadata_backend {
+            ensure!(
+                !endpoints.is_empty(),
+                IllegalConfigSnafu {
+                    msg: "the etcd metadata backend requires at least one endpoint",
+                }
+            );
+        }
+        check_object_store_config(&opts.storage.store)?;
+        check_procedure_config(&opts.procedure)?;
+        if let Some(user_provider) = &opts.user_provider {
+            check_user_provider_file(user_provider)?;
+        }
+
+        let mut listen_addrs = vec![("HTTP", opts.http.addr.as_str())];
+        if let Some(addr) = &opts.http.admin_addr {
+            listen_addrs.push(("admin HTTP", addr));
+        }
+        listen_addrs.push(("gRPC", &opts.grpc.addr));
+        for (protocol, enable, addr) in [
+            ("MySQL", opts.mysql.enable, &opts.mysql.addr),
+            ("PostgreSQL", opts.postgres.enable, &opts.postgres.addr),
+            ("OpenTSDB", opts.opentsdb.enable, &opts.opentsdb.addr),
+        ] {
+            if enable {
+                listen_addrs.push((protocol, addr));
+            }
+        }
+        check_listen_addrs(&listen_addrs)?;
+
+        if self.config_print {
+            return Ok(Options::PrintConfig(print_config(
+                &opts,
+                self.config_print_unredacted,
+            )?));
+        }
+
         let metadata_store = opts.metadata_store.clone();
+        let metadata_backend = opts.metadata_backend.clone();
         let procedure = opts.procedure.clone();
+        let shutdown_grace_period = opts.shutdown_grace_period;
         let frontend = opts.clone().frontend_options();
         let logging = opts.logging.clone();
-        let datanode = opts.datanode_options();
+        let node_id = match &self.node_id {
+            Some(node_id) => resolve_node_id(node_id, &opts.grpc.addr)?,
+            None => DEFAULT_NODE_ID,
+        };
+        let datanode = opts.datanode_options(node_id);
 
         Ok(Options::Standalone(Box::new(MixOptions {
             procedure,
             metadata_store,
+            metadata_backend,
             data_home: datanode.storage.data_home.to_string(),
+            shutdown_grace_period,
             frontend,
             datanode,
             logging,
         })))
     }
 
-    #[allow(unreachable_code)]
-    #[allow(unused_variables)]
-    #[allow(clippy::diverging_sub_expression)]
-    async fn build(self, opts: MixOptions) -> Result<Instance> {
+    /// Validates the options loaded by [StartCommand::load_options] beyond what it
+    /// checks, without starting anything, and sets up the frontend plugins. The data
+    /// home and the WAL directories are created if missing, and the WAL directory is
+    /// checked writable, as on a start.
+    async fn validate(&self, opts: &MixOptions) -> Result<(FrontendOptions, Plugins)> {
+        // Ensure the data_home directory exists.
+        fs::create_dir_all(path::Path::new(&opts.data_home)).context(CreateDirSnafu {
+            dir: &opts.data_home,
+        })?;
+        check_wal_dir(&opts.datanode.wal, &opts.datanode.wal_dir())?;
+
         let mut fe_opts = opts.frontend.clone();
         #[allow(clippy::unnecessary_mut_passed)]
         let fe_plugins = plugins::setup_frontend_plugins(&mut fe_opts) // mut ref is MUST, DO NOT change it
             .await
             .context(StartFrontendSnafu)?;
+        Ok((fe_opts, fe_plugins))
+    }
+
+    #[allow(unreachable_code)]
+    #[allow(unused_variables)]
+    #[allow(clippy::diverging_sub_expression)]
+    async fn build(self, opts: MixOptions) -> Result<Instance> {
+        let (fe_opts, fe_plugins) = self.validate(&opts).await?;
 
         let dn_opts = opts.datanode.clone();
+        let shutdown_grace_period = opts.shutdown_grace_period;
 
         info!("Standalone start command: {:#?}", self);
         info!(
@@ -330,19 +1063,38 @@ impl StartCommand {
             fe_opts, dn_opts
         );
 
-        // Ensure the data_home directory exists.
-        fs::create_dir_all(path::Path::new(&opts.data_home)).context(CreateDirSnafu {
-            dir: &opts.data_home,
-        })?;
-
-        let metadata_dir = metadata_store_dir(&opts.data_home);
-        let (kv_backend, procedure_manager) = FeInstance::try_build_standalone_components(
-            metadata_dir,
-            opts.metadata_store.clone(),
-            opts.procedure.clone(),
-        )
-        .await
-        .context(StartFrontendSnafu)?;
+        let (kv_backend, procedure_manager) = match &opts.metadata_backend {
+            MetadataBackend::Local => {
+                let metadata_dir = metadata_store_dir(&opts.data_home);
+                FeInstance::try_build_standalone_components(
+                    metadata_dir,
+                    opts.metadata_store.clone(),
+                    opts.procedure.clone(),
+                )
+                .await
+                .context(StartFrontendSnafu)?
+            }
+            MetadataBackend::Etcd { endpoints } => {
+                let kv_backend = EtcdStore::with_endpoints(endpoints).await.context(
+                    ConnectMetadataBackendSnafu {
+                        endpoints: endpoints.join(","),
+                    },
+                )?;
+                info!("Standalone metadata is stored in etcd {:?}", endpoints);
+                // The instances sharing the etcd only recover their own procedures, told
+                // apart by the node ids, which must be unique among them.
+                let node_id = dn_opts.node_id.unwrap_or(DEFAULT_NODE_ID);
+                let state_store = KvStateStore::with_prefix(
+                    kv_backend.clone(),
+                    standalone_procedure_prefix(node_id),
+                );
+                let procedure_manager = FeInstance::build_standalone_procedure_manager(
+                    state_store,
+                    opts.procedure.clone(),
+                );
+                (kv_backend, procedure_manager)
+            }
+        };
 
         let datanode = DatanodeBuilder::new(
             dn_opts.clone(),
@@ -353,6 +1105,15 @@ impl StartCommand {
         .await
         .context(StartDatanodeSnafu)?;
         let region_server = datanode.region_server();
+        if let Some(backup_manager) = datanode.backup_manager() {
+            fe_plugins.insert::<BackupHandlerRef>(Arc::new(backup_manager));
+        }
+        let readiness = Readiness::new();
+        fe_plugins.insert(readiness.clone());
+        let liveness = Liveness::default();
+        fe_plugins.insert(liveness.clone());
+        let liveness_watchdog = new_liveness_watchdog(liveness);
+        let metadata_store_check = new_metadata_store_check(kv_backend.clone(), readiness.clone());
 
         let catalog_manager = KvBackendCatalogManager::new(
             kv_backend.clone(),
@@ -373,8 +1134,19 @@ impl StartCommand {
             procedure_manager.clone(),
             catalog_manager,
             region_server,
+            fe_opts.write_ack_level,
+            fe_opts.max_request_write_ack_level,
         )
         .await?;
+        frontend.set_query_cache(&fe_opts.query_cache);
+        frontend
+            .set_workload_runtimes(&fe_opts)
+            .context(StartFrontendSnafu)?;
+        frontend.set_cardinality_estimation(&fe_opts.cardinality);
+        frontend.set_slow_query_log(&fe_opts.slow_query);
+        frontend.set_query_admission(&fe_opts.query_admission);
+        frontend.set_self_monitoring(&fe_opts.self_monitoring);
+        frontend.set_prom_store_downsampling(&fe_opts.prom_store.downsampling);
 
         frontend
             .build_servers(opts)
@@ -385,6 +1157,10 @@ impl StartCommand {
             datanode,
             frontend,
             procedure_manager,
+            shutdown_grace_period,
+            readiness,
+            liveness_watchdog,
+            metadata_store_check,
         })
     }
 }
@@ -396,6 +1172,8 @@ async fn build_frontend(
     procedure_manager: ProcedureManagerRef,
     catalog_manager: CatalogManagerRef,
     region_server: RegionServer,
+    write_ack_level: WriteAckLevel,
+    max_request_write_ack_level: WriteAckLevel,
 ) -> Result<FeInstance> {
     let frontend_instance = FeInstance::try_new_standalone(
         kv_backend,
@@ -403,12 +1181,90 @@ async fn build_frontend(
         catalog_manager,
         plugins,
         region_server,
+        write_ack_level,
+        max_request_write_ack_level,
     )
     .await
     .context(StartFrontendSnafu)?;
     Ok(frontend_instance)
 }
 
+/// Placeholder of the redacted options printed by `--config-print`.
+const REDACTED: &str = "[REDACTED]";
+
+/// Serializes the resolved `opts` for `--config-print`. Unless `unredacted`, the
+/// content of the user provider, which may have the passwords, and the TLS key paths
+/// are redacted. The secrets of the object stores are never serialized.
+fn print_config(opts: &StandaloneOptions, unredacted: bool) -> Result<String> {
+    let mut opts = opts.clone();
+    if !unredacted {
+        // Keeps the name of the provider, e.g. `static_user_provider`.
+        opts.user_provider = opts
+            .user_provider
+            .map(|provider| match provider.split_once(':') {
+                Some((name, _)) => format!("{name}:{REDACTED}"),
+                None => provider,
+            });
+        for tls in [&mut opts.mysql.tls, &mut opts.postgres.tls] {
+            if !tls.key_path.is_empty() {
+                tls.key_path = REDACTED.to_string();
+            }
+        }
+    }
+    toml::to_string_pretty(&opts).context(TomlFormatSnafu)
+}
+
+/// Prefix of the procedures of the standalone instance `node_id` in a shared etcd,
+/// apart from the procedures of the Metasrv.
+fn standalone_procedure_prefix(node_id: u64) -> String {
+    format!("/__standalone_procedure__/{node_id}/")
+}
+
+/// Resolves the `--node-id` of the datanode, `auto` for the hash of the hostname and
+/// the port of the gRPC address.
+fn resolve_node_id(node_id: &str, grpc_addr: &str) -> Result<u64> {
+    let node_id = if node_id.eq_ignore_ascii_case("auto") {
+        let hostname = hostname::get()
+            .ok()
+            .and_then(|x| x.into_string().ok())
+            .context(IllegalConfigSnafu {
+                msg: "failed to get the hostname for `--node-id auto`",
+            })?;
+        let port = grpc_addr
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .with_context(|| IllegalConfigSnafu {
+                msg: format!("invalid gRPC address {grpc_addr} for `--node-id auto`"),
+            })?;
+        auto_node_id(&hostname, port)
+    } else {
+        node_id.parse().ok().with_context(|| IllegalConfigSnafu {
+            msg: format!("invalid node id: {node_id}, expect an integer or `auto`"),
+        })?
+    };
+    ensure!(
+        node_id <= MAX_NODE_ID,
+        IllegalConfigSnafu {
+            msg: format!("node id {node_id} out of range, expect at most {MAX_NODE_ID}"),
+        }
+    );
+    Ok(node_id)
+}
+
+/// Derives a node id from the hostname and the port with FNV-1a, which unlike the std
+/// hasher is stable across builds, so an instance keeps its id over restarts.
+fn auto_node_id(hostname: &str, port: u16) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let hash = format!("{hostname}:{port}")
+        .bytes()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+    hash % (MAX_NODE_ID + 1)
+}
+
 #[cfg(test)]
 mod tests {
     use std::default::Default;
@@ -416,8 +1272,8 @@ mod tests {
     use std::time::Duration;
 
     use auth::{Identity, Password, UserProviderRef};
-    use common_base::readable_size::ReadableSize;
-    use common_test_util::temp_dir::create_named_temp_file;
+    use common_telemetry::logging::LogFormat;
+    use common_test_util::temp_dir::{create_named_temp_file, create_temp_dir};
     use servers::Mode;
 
     use super::*;
@@ -457,6 +1313,7 @@ mod tests {
             mode = "distributed"
 
             enable_memory_catalog = true
+            shutdown_grace_period = "1m"
 
             [wal]
             dir = "/tmp/greptimedb/test/wal"
@@ -468,6 +1325,7 @@ mod tests {
 
             [storage]
             type = "S3"
+            bucket = "greptimedb"
             access_key_id = "access_key_id"
             secret_access_key = "secret_access_key"
 
@@ -500,6 +1358,7 @@ mod tests {
         else {
             unreachable!()
         };
+        assert_eq!(Duration::from_secs(60), options.shutdown_grace_period);
         let fe_opts = options.frontend;
         let dn_opts = options.datanode;
         let logging_opts = options.logging;
@@ -532,6 +1391,369 @@ mod tests {
         assert_eq!("/tmp/greptimedb/test/logs".to_string(), logging_opts.dir);
     }
 
+    #[test]
+    fn test_print_config() {
+        let mut opts = StandaloneOptions {
+            user_provider: Some("static_user_provider:cmd:test=secret".to_string()),
+            ..Default::default()
+        };
+        opts.mysql.tls.key_path = "/etc/greptimedb/tls/server.key".to_string();
+        opts.http.addr = "127.0.0.1:4100".to_string();
+
+        let printed = print_config(&opts, false).unwrap();
+        assert!(printed.contains("static_user_provider:[REDACTED]"));
+        assert!(!printed.contains("secret"));
+        assert!(!printed.contains("server.key"));
+        assert!(printed.contains("127.0.0.1:4100"));
+        // The printed options are loaded back as they are.
+        let parsed: StandaloneOptions = toml::from_str(&printed).unwrap();
+        assert_eq!("127.0.0.1:4100", parsed.http.addr);
+        // Empty key paths are left as they are.
+        assert_eq!("", parsed.postgres.tls.key_path);
+
+        let printed = print_config(&opts, true).unwrap();
+        assert!(printed.contains("static_user_provider:cmd:test=secret"));
+        assert!(printed.contains("/etc/greptimedb/tls/server.key"));
+
+        let cmd = StartCommand {
+            http_addr: Some("127.0.0.1:4100".to_string()),
+            config_print: true,
+            ..Default::default()
+        };
+        let Options::PrintConfig(printed) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(printed.contains("127.0.0.1:4100"));
+    }
+
+    #[test]
+    fn test_listen_addrs_conflict() {
+        let cmd = StartCommand {
+            mysql_addr: Some("127.0.0.1:4003".to_string()),
+            ..Default::default()
+        };
+        let err = cmd.load_options(TopLevelOptions::default()).unwrap_err();
+        assert!(err.to_string().contains(
+            "PostgreSQL listen address 127.0.0.1:4003 conflicts with MySQL listen address 127.0.0.1:4003"
+        ));
+
+        let cmd = StartCommand {
+            http_addr: Some("0.0.0.0:4001".to_string()),
+            ..Default::default()
+        };
+        assert!(cmd.load_options(TopLevelOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_grpc_message_size() {
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            [grpc]
+            addr = "127.0.0.1:4001"
+            max_recv_message_size = "1GiB"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+
+        let cmd = StartCommand::parse_from([
+            "standalone",
+            "--config-file",
+            file.path().to_str().unwrap(),
+            "--rpc-max-send-message-size",
+            "16MiB",
+        ]);
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            ReadableSize::gb(1),
+            opts.frontend.grpc.max_recv_message_size
+        );
+        assert_eq!(
+            ReadableSize::mb(16),
+            opts.frontend.grpc.max_send_message_size
+        );
+
+        let cmd = StartCommand::parse_from([
+            "standalone",
+            "--config-file",
+            file.path().to_str().unwrap(),
+            "--rpc-max-recv-message-size",
+            "2GiB",
+        ]);
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            ReadableSize::gb(2),
+            opts.frontend.grpc.max_recv_message_size
+        );
+        assert_eq!(
+            GrpcOptions::default().max_send_message_size,
+            opts.frontend.grpc.max_send_message_size
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_only() {
+        let dir = create_temp_dir("test_validate_only");
+        let data_home = dir.path().to_str().unwrap().to_string();
+        let cmd = StartCommand {
+            user_provider: Some("static_user_provider:cmd:test=test".to_string()),
+            data_home: Some(data_home.clone()),
+            validate_only: true,
+            ..Default::default()
+        };
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        cmd.validate(&opts).await.unwrap();
+
+        // The same error as a start.
+        let cmd = StartCommand {
+            user_provider: Some("unknown_user_provider:cmd:test=test".to_string()),
+            data_home: Some(data_home.clone()),
+            validate_only: true,
+            ..Default::default()
+        };
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(cmd.validate(&opts).await.is_err());
+
+        // The WAL directory is checked as on a start.
+        std::fs::remove_dir_all(dir.path().join("wal")).unwrap();
+        std::fs::write(dir.path().join("wal"), b"").unwrap();
+        let cmd = StartCommand {
+            data_home: Some(data_home),
+            validate_only: true,
+            ..Default::default()
+        };
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        let err = cmd.validate(&opts).await.unwrap_err().to_string();
+        assert!(err.contains("failed to create WAL directory"), "{err}");
+    }
+
+    #[test]
+    fn test_metadata_backend_from_cmd() {
+        let cmd = StartCommand::default();
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(MetadataBackend::Local, opts.metadata_backend);
+
+        let cmd = StartCommand {
+            metasrv_addr: Some(vec![
+                "127.0.0.1:2379".to_string(),
+                "127.0.0.1:2380".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            MetadataBackend::Etcd {
+                endpoints: vec!["127.0.0.1:2379".to_string(), "127.0.0.1:2380".to_string()]
+            },
+            opts.metadata_backend
+        );
+
+        let cmd = StartCommand {
+            metasrv_addr: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(cmd.load_options(TopLevelOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_disable_telemetry_from_cmd() {
+        let cmd = StartCommand {
+            disable_telemetry: true,
+            ..Default::default()
+        };
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(!opts.datanode.enable_telemetry);
+
+        temp_env::with_var(DISABLE_TELEMETRY_ENV, Some("true"), || {
+            let Options::Standalone(opts) = StartCommand::default()
+                .load_options(TopLevelOptions::default())
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            assert!(!opts.datanode.enable_telemetry);
+        });
+    }
+
+    #[test]
+    fn test_data_home_from_cmd() {
+        let cmd = StartCommand {
+            data_home: Some("/tmp/greptimedb/test/data".to_string()),
+            ..Default::default()
+        };
+
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+
+        assert_eq!("/tmp/greptimedb/test/data", opts.data_home);
+        assert_eq!("/tmp/greptimedb/test/data", opts.datanode.storage.data_home);
+    }
+
+    #[test]
+    fn test_node_id_from_cmd() {
+        let node_id = |args: &[&str]| {
+            let cmd = StartCommand::parse_from([&["standalone"][..], args].concat());
+            cmd.load_options(TopLevelOptions::default())
+                .map(|opts| match opts {
+                    Options::Standalone(opts) => opts.datanode.node_id.unwrap(),
+                    _ => unreachable!(),
+                })
+        };
+
+        assert_eq!(DEFAULT_NODE_ID, node_id(&[]).unwrap());
+        assert_eq!(42, node_id(&["--node-id", "42"]).unwrap());
+        assert!(node_id(&["--node-id", "1.5"]).is_err());
+        assert!(node_id(&["--node-id", "datanode-1"]).is_err());
+        assert!(node_id(&["--node-id", &(MAX_NODE_ID + 1).to_string()]).is_err());
+
+        // Stable, and distinct for the instances on the other ports.
+        let auto = node_id(&["--node-id", "auto"]).unwrap();
+        assert!(auto <= MAX_NODE_ID);
+        assert_eq!(auto, node_id(&["--node-id", "auto"]).unwrap());
+        assert_ne!(
+            auto,
+            node_id(&["--node-id", "auto", "--rpc-addr", "127.0.0.1:14001"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_auto_node_id() {
+        assert_eq!(
+            auto_node_id("localhost", 4001),
+            auto_node_id("localhost", 4001)
+        );
+        assert_ne!(
+            auto_node_id("localhost", 4001),
+            auto_node_id("localhost", 4002)
+        );
+        assert_ne!(auto_node_id("host-a", 4001), auto_node_id("host-b", 4001));
+    }
+
+    #[test]
+    fn test_overrides_from_cmd() {
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            [http]
+            addr = "127.0.0.1:4000"
+            timeout = "10s"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+
+        let cmd = StartCommand::parse_from([
+            "standalone",
+            "--config-file",
+            file.path().to_str().unwrap(),
+            "--set",
+            "region_engine[0].mito.num_workers=4",
+            "--set",
+            "http.timeout=60s",
+            "--set",
+            "logging.level=debug",
+        ]);
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        let RegionEngineConfig::Mito(mito) = &opts.datanode.region_engine[0] else {
+            unreachable!()
+        };
+        assert_eq!(4, mito.num_workers);
+        assert_eq!(Duration::from_secs(60), opts.frontend.http.timeout);
+        assert_eq!("127.0.0.1:4000", opts.frontend.http.addr);
+        assert_eq!("debug", opts.logging.level.unwrap());
+
+        // The overrides take precedence over the flags.
+        let cmd = StartCommand::parse_from([
+            "standalone",
+            "--config-file",
+            file.path().to_str().unwrap(),
+            "--http-addr",
+            "127.0.0.1:5000",
+            "--set",
+            "http.addr=127.0.0.1:6000",
+        ]);
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("127.0.0.1:6000", opts.frontend.http.addr);
+
+        let cmd = StartCommand::parse_from([
+            "standalone",
+            "--config-file",
+            file.path().to_str().unwrap(),
+            "--http-addr",
+            "127.0.0.1:5000",
+            "--set",
+            "http.timeout=60s",
+        ]);
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("127.0.0.1:5000", opts.frontend.http.addr);
+        assert_eq!(Duration::from_secs(60), opts.frontend.http.timeout);
+
+        // The overridden options are checked too.
+        let cmd = StartCommand {
+            overrides: vec!["query_max_parallelism=0".to_string()],
+            ..Default::default()
+        };
+        let err = cmd.load_options(TopLevelOptions::default()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("query max parallelism must be at least 1"),
+            "{err}"
+        );
+
+        for (token, reason) in [
+            ("http.timeout", "expected key=value"),
+            ("htpp.timeout=60s", "unknown key"),
+            ("region_engine[9].mito.num_workers=4", "unknown key"),
+            (
+                "region_engine[0].mito.num_workers=four",
+                "expected an integer",
+            ),
+            ("http=127.0.0.1:4000", "not a single value"),
+        ] {
+            let cmd = StartCommand {
+                overrides: vec![token.to_string()],
+                ..Default::default()
+            };
+            let err = cmd.load_options(TopLevelOptions::default()).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("invalid config override {token}: {reason}")),
+                "{err}"
+            );
+        }
+    }
+
     #[test]
     fn test_top_level_options() {
         let cmd = StartCommand {
@@ -543,6 +1765,8 @@ mod tests {
             .load_options(TopLevelOptions {
                 log_dir: Some("/tmp/greptimedb/test/logs".to_string()),
                 log_level: Some("debug".to_string()),
+                log_format: Some(LogFormat::Json),
+                ..Default::default()
             })
             .unwrap()
         else {
@@ -551,6 +1775,43 @@ mod tests {
 
         assert_eq!("/tmp/greptimedb/test/logs", opts.logging.dir);
         assert_eq!("debug", opts.logging.level.unwrap());
+        assert_eq!(LogFormat::Json, opts.logging.log_format);
+    }
+
+    #[test]
+    fn test_top_level_options_over_config_file() {
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            [logging]
+            level = "debug"
+            dir = "/tmp/greptimedb/test/logs"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+        let cmd = StartCommand {
+            config_file: Some(file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+
+        let Options::Standalone(opts) = cmd.load_options(TopLevelOptions::default()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("debug", opts.logging.level.unwrap());
+        assert_eq!("/tmp/greptimedb/test/logs", opts.logging.dir);
+
+        // The command line wins over the config file.
+        let Options::Standalone(opts) = cmd
+            .load_options(TopLevelOptions {
+                log_dir: Some("/tmp/greptimedb/cli/logs".to_string()),
+                log_level: Some("warn,servers=info".to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("warn,servers=info", opts.logging.level.unwrap());
+        assert_eq!("/tmp/greptimedb/cli/logs", opts.logging.dir);
     }
 
     #[test]
@@ -612,6 +1873,7 @@ mod tests {
                 let top_level_opts = TopLevelOptions {
                     log_dir: None,
                     log_level: None,
+                    ..Default::default()
                 };
                 let Options::Standalone(opts) = command.load_options(top_level_opts).unwrap()
                 else {
@@ -17,9 +17,22 @@ use std::time::Duration;
 use common_base::readable_size::ReadableSize;
 use serde::{Deserialize, Serialize};
 
+/// Where the WAL entries are appended.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WalProvider {
+    /// The local raft-engine under the WAL directory.
+    #[default]
+    RaftEngine,
+    /// Topics of a remote Kafka cluster, which are shared and replicated.
+    Kafka,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct WalConfig {
+    // wal provider, the raft-engine only options are ignored by other providers
+    pub provider: WalProvider,
     // wal directory
     pub dir: Option<String>,
     // wal file size in bytes
@@ -33,21 +46,88 @@ pub struct WalConfig {
     pub read_batch_size: usize,
     // whether to sync log file after every write
     pub sync_write: bool,
+    // min free space of the wal directory checked on startup, raft-engine only
+    pub min_free_space: ReadableSize,
+    // kafka options, only used by the kafka provider
+    pub kafka: KafkaWalConfig,
 }
 
 impl Default for WalConfig {
     fn default() -> Self {
         Self {
+            provider: WalProvider::default(),
             dir: None,
             file_size: ReadableSize::mb(256), // log file size 256MB
             purge_threshold: ReadableSize::gb(4), // purge threshold 4GB
             purge_interval: Duration::from_secs(600),
             read_batch_size: 128,
             sync_write: false,
+            min_free_space: ReadableSize::mb(256),
+            kafka: KafkaWalConfig::default(),
+        }
+    }
+}
+
+/// Options of the Kafka WAL. Regions share a fixed set of single partition topics,
+/// a region is assigned to a topic by its id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct KafkaWalConfig {
+    /// Kafka brokers to bootstrap from.
+    pub broker_endpoints: Vec<String>,
+    /// Prefix of the topic names, the topics are named `{prefix}_{index}`.
+    pub topic_name_prefix: String,
+    /// Number of topics created on startup, which can't be changed once the topics have
+    /// records as it decides the topics of the regions.
+    pub num_topics: usize,
+    /// Replication factor of the created topics.
+    pub replication_factor: i16,
+    #[serde(with = "humantime_serde")]
+    pub create_topic_timeout: Duration,
+    /// Max bytes of the records produced or fetched in a request.
+    pub max_batch_size: ReadableSize,
+    /// Initial backoff to retry connecting to the brokers.
+    #[serde(with = "humantime_serde")]
+    pub backoff_init: Duration,
+    /// Max backoff to retry connecting to the brokers.
+    #[serde(with = "humantime_serde")]
+    pub backoff_max: Duration,
+    /// Exponential base of the backoff.
+    pub backoff_base: u32,
+    /// Gives up connecting to the brokers after retrying for this long.
+    #[serde(with = "humantime_serde")]
+    pub backoff_deadline: Duration,
+}
+
+impl Default for KafkaWalConfig {
+    fn default() -> Self {
+        Self {
+            broker_endpoints: vec!["127.0.0.1:9092".to_string()],
+            topic_name_prefix: "greptimedb_wal_topic".to_string(),
+            num_topics: 64,
+            replication_factor: 1,
+            create_topic_timeout: Duration::from_secs(30),
+            max_batch_size: ReadableSize::mb(1),
+            backoff_init: Duration::from_millis(500),
+            backoff_max: Duration::from_secs(10),
+            backoff_base: 2,
+            backoff_deadline: Duration::from_secs(300),
         }
     }
 }
 
+/// Where the metadata of the standalone mode is stored.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MetadataBackend {
+    /// The kv store under the data home, configured by [KvBackendConfig].
+    #[default]
+    Local,
+    /// An external etcd, so the metadata can be shared. The procedures of each instance
+    /// are stored apart by its node id.
+    Etcd { endpoints: Vec<String> },
+}
+
 pub fn metadata_store_dir(store_dir: &str) -> String {
     format!("{store_dir}/metadata")
 }
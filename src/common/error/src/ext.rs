@@ -13,10 +13,51 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
 use crate::status_code::StatusCode;
 
+/// How much of an error is returned to clients by [ErrorExt::output_msg]. The full
+/// error is still logged by the servers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ErrorVerbosity {
+    /// Only the status code, which clients can branch on.
+    Minimal = 0,
+    /// The innermost error and its external cause, internal errors are masked.
+    #[default]
+    Normal = 1,
+    /// The whole error chain, also of internal errors. For development only as it
+    /// may expose table internals and file paths.
+    Full = 2,
+}
+
+static ERROR_VERBOSITY: AtomicU8 = AtomicU8::new(ErrorVerbosity::Normal as u8);
+
+/// Sets the verbosity of the error messages returned to clients by this process.
+pub fn set_error_verbosity(verbosity: ErrorVerbosity) {
+    ERROR_VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn error_verbosity() -> ErrorVerbosity {
+    match ERROR_VERBOSITY.load(Ordering::Relaxed) {
+        0 => ErrorVerbosity::Minimal,
+        2 => ErrorVerbosity::Full,
+        _ => ErrorVerbosity::Normal,
+    }
+}
+
+/// Returns the message of an error with the `status_code` under
+/// [ErrorVerbosity::Minimal], for the errors not implementing [ErrorExt].
+pub fn minimal_msg(status_code: StatusCode) -> String {
+    format!("{status_code}, error code: {}", status_code as u32)
+}
+
 /// Extension to [`Error`](std::error::Error) in std.
 pub trait ErrorExt: StackError {
     /// Map this error to [StatusCode].
@@ -35,16 +76,34 @@ pub trait ErrorExt: StackError {
     /// downcast to a specific implementation.
     fn as_any(&self) -> &dyn Any;
 
+    /// Returns the message of the error for clients, according to the
+    /// [error_verbosity] of the process.
     fn output_msg(&self) -> String
     where
         Self: Sized,
     {
-        match self.status_code() {
-            StatusCode::Unknown | StatusCode::Internal => {
+        self.output_msg_with(error_verbosity())
+    }
+
+    fn output_msg_with(&self, verbosity: ErrorVerbosity) -> String
+    where
+        Self: Sized,
+    {
+        let status_code = self.status_code();
+        match (verbosity, status_code) {
+            (ErrorVerbosity::Minimal, _) => minimal_msg(status_code),
+            (ErrorVerbosity::Full, _) => {
+                std::iter::successors(Some(self as &dyn std::error::Error), |e| e.source())
+                    .map(|e| e.to_string())
+                    .filter(|msg| !msg.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(": ")
+            }
+            (ErrorVerbosity::Normal, StatusCode::Unknown | StatusCode::Internal) => {
                 // masks internal error from end user
                 format!("Internal error: {}", self.status_code() as u32)
             }
-            _ => {
+            (ErrorVerbosity::Normal, _) => {
                 let error = self.last();
                 if let Some(external_error) = error.source() {
                     let external_root = external_error.sources().last().unwrap();
@@ -214,3 +273,45 @@ impl StackError for PlainError {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockError;
+
+    #[test]
+    fn test_output_msg_with_verbosity() {
+        let internal = MockError::with_source(MockError::new(StatusCode::Internal));
+        assert_eq!(
+            format!("Internal error: {}", StatusCode::Internal as u32),
+            internal.output_msg_with(ErrorVerbosity::Normal)
+        );
+        assert_eq!(
+            "Internal: Internal",
+            internal.output_msg_with(ErrorVerbosity::Full)
+        );
+        assert_eq!(
+            format!("Internal, error code: {}", StatusCode::Internal as u32),
+            internal.output_msg_with(ErrorVerbosity::Minimal)
+        );
+
+        let not_found = MockError::new(StatusCode::TableNotFound);
+        assert_eq!(
+            "TableNotFound",
+            not_found.output_msg_with(ErrorVerbosity::Normal)
+        );
+        assert_eq!(
+            format!(
+                "TableNotFound, error code: {}",
+                StatusCode::TableNotFound as u32
+            ),
+            not_found.output_msg_with(ErrorVerbosity::Minimal)
+        );
+
+        assert_eq!(
+            ErrorVerbosity::Minimal,
+            "minimal".parse::<ErrorVerbosity>().unwrap()
+        );
+        assert!("verbose".parse::<ErrorVerbosity>().is_err());
+    }
+}
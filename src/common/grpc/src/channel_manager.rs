@@ -82,17 +82,22 @@ impl ChannelManager {
         let server_root_ca_cert = std::fs::read_to_string(path_config.server_ca_cert_path)
             .context(InvalidConfigFilePathSnafu)?;
         let server_root_ca_cert = Certificate::from_pem(server_root_ca_cert);
-        let client_cert = std::fs::read_to_string(path_config.client_cert_path)
-            .context(InvalidConfigFilePathSnafu)?;
-        let client_key = std::fs::read_to_string(path_config.client_key_path)
-            .context(InvalidConfigFilePathSnafu)?;
-        let client_identity = Identity::from_pem(client_cert, client_key);
+        let mut client_tls_config = ClientTlsConfig::new().ca_certificate(server_root_ca_cert);
+
+        // The client only authenticates with a certificate for mutual TLS.
+        if !path_config.client_cert_path.is_empty() || !path_config.client_key_path.is_empty() {
+            let client_cert = std::fs::read_to_string(path_config.client_cert_path)
+                .context(InvalidConfigFilePathSnafu)?;
+            let client_key = std::fs::read_to_string(path_config.client_key_path)
+                .context(InvalidConfigFilePathSnafu)?;
+            client_tls_config =
+                client_tls_config.identity(Identity::from_pem(client_cert, client_key));
+        }
+        if let Some(domain_name) = path_config.domain_name {
+            client_tls_config = client_tls_config.domain_name(domain_name);
+        }
 
-        cm.client_tls_config = Some(
-            ClientTlsConfig::new()
-                .ca_certificate(server_root_ca_cert)
-                .identity(client_identity),
-        );
+        cm.client_tls_config = Some(client_tls_config);
 
         Ok(cm)
     }
@@ -238,8 +243,13 @@ impl ChannelManager {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ClientTlsOption {
     pub server_ca_cert_path: String,
+    /// Empty, as well as the `client_key_path`, if the client doesn't authenticate with
+    /// a certificate.
     pub client_cert_path: String,
     pub client_key_path: String,
+    /// Name the certificate of the server is verified against, instead of the host of
+    /// the address, e.g. to connect by IP.
+    pub domain_name: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -568,6 +578,7 @@ mod tests {
                 server_ca_cert_path: "some_server_path".to_string(),
                 client_cert_path: "some_cert_path".to_string(),
                 client_key_path: "some_key_path".to_string(),
+                domain_name: None,
             });
 
         assert_eq!(
@@ -588,6 +599,7 @@ mod tests {
                     server_ca_cert_path: "some_server_path".to_string(),
                     client_cert_path: "some_cert_path".to_string(),
                     client_key_path: "some_key_path".to_string(),
+                    domain_name: None,
                 }),
                 max_recv_message_size: DEFAULT_MAX_GRPC_RECV_MESSAGE_SIZE,
                 max_send_message_size: DEFAULT_MAX_GRPC_SEND_MESSAGE_SIZE,
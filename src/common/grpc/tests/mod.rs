@@ -26,6 +26,7 @@ async fn test_mtls_config() {
         server_ca_cert_path: "tests/tls/wrong_server.cert.pem".to_string(),
         client_cert_path: "tests/tls/wrong_client.cert.pem".to_string(),
         client_key_path: "tests/tls/wrong_client.key.pem".to_string(),
+        domain_name: None,
     });
 
     let re = ChannelManager::with_tls_config(config);
@@ -36,6 +37,7 @@ async fn test_mtls_config() {
         server_ca_cert_path: "tests/tls/server.cert.pem".to_string(),
         client_cert_path: "tests/tls/client.cert.pem".to_string(),
         client_key_path: "tests/tls/corrupted".to_string(),
+        domain_name: None,
     });
 
     let re = ChannelManager::with_tls_config(config).unwrap();
@@ -47,9 +49,31 @@ async fn test_mtls_config() {
         server_ca_cert_path: "tests/tls/server.cert.pem".to_string(),
         client_cert_path: "tests/tls/client.cert.pem".to_string(),
         client_key_path: "tests/tls/client.key.pem".to_string(),
+        domain_name: None,
     });
 
     let re = ChannelManager::with_tls_config(config).unwrap();
     let re = re.get("127.0.0.1:0");
     let _ = re.unwrap();
 }
+
+#[tokio::test]
+async fn test_tls_config_without_client_cert() {
+    let config = ChannelConfig::new().client_tls_config(ClientTlsOption {
+        server_ca_cert_path: "tests/tls/server.cert.pem".to_string(),
+        client_cert_path: String::new(),
+        client_key_path: String::new(),
+        domain_name: Some("greptime.test".to_string()),
+    });
+    let re = ChannelManager::with_tls_config(config).unwrap();
+    let _ = re.get("127.0.0.1:0").unwrap();
+
+    // The key without the certificate.
+    let config = ChannelConfig::new().client_tls_config(ClientTlsOption {
+        server_ca_cert_path: "tests/tls/server.cert.pem".to_string(),
+        client_cert_path: String::new(),
+        client_key_path: "tests/tls/client.key.pem".to_string(),
+        domain_name: None,
+    });
+    assert!(ChannelManager::with_tls_config(config).is_err());
+}
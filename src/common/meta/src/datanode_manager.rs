@@ -16,6 +16,7 @@ use std::sync::Arc;
 
 use api::v1::region::{QueryRequest, RegionRequest};
 use common_recordbatch::SendableRecordBatchStream;
+use store_api::region_request::WriteAckLevel;
 
 use crate::error::Result;
 use crate::peer::Peer;
@@ -27,6 +28,13 @@ pub trait Datanode: Send + Sync {
     /// Handles DML, and DDL requests.
     async fn handle(&self, request: RegionRequest) -> Result<AffectedRows>;
 
+    /// Handles DML requests, acknowledging their writes at the `ack_level`.
+    async fn handle_write(
+        &self,
+        request: RegionRequest,
+        ack_level: WriteAckLevel,
+    ) -> Result<AffectedRows>;
+
     async fn handle_query(&self, request: QueryRequest) -> Result<SendableRecordBatchStream>;
 }
 
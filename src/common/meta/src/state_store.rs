@@ -32,33 +32,40 @@ const DELIMITER: &str = "/";
 
 const PROCEDURE_PREFIX: &str = "/__procedure__/";
 
-fn with_prefix(key: &str) -> String {
-    format!("{PROCEDURE_PREFIX}{key}")
-}
-
-fn strip_prefix(key: &str) -> String {
-    key.trim_start_matches(PROCEDURE_PREFIX).to_string()
-}
-
 pub struct KvStateStore {
     kv_backend: KvBackendRef,
     // limit is set to 0, it is treated as no limit.
     max_size_per_range: usize,
+    /// Prefix of the keys of the procedures.
+    prefix: String,
 }
 
 impl KvStateStore {
     // `max_size_per_range` is set to 0, it is treated as no limit.
     pub fn new(kv_backend: KvBackendRef) -> Self {
+        Self::with_prefix(kv_backend, PROCEDURE_PREFIX)
+    }
+
+    /// Stores the procedures under `prefix`, which ends with the delimiter, so the
+    /// managers sharing a kv backend don't recover the procedures of each other.
+    pub fn with_prefix(kv_backend: KvBackendRef, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        debug_assert!(prefix.ends_with(DELIMITER));
         Self {
             kv_backend,
             max_size_per_range: 0,
+            prefix,
         }
     }
+
+    fn with_prefix_key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
 }
 
-fn decode_kv(kv: KeyValue) -> Result<(String, Vec<u8>)> {
+fn decode_kv(kv: KeyValue, prefix: &str) -> Result<(String, Vec<u8>)> {
     let key = String::from_utf8_lossy(&kv.key);
-    let key = strip_prefix(&key);
+    let key = key.trim_start_matches(prefix).to_string();
     let value = kv.value;
 
     Ok((key, value))
@@ -70,7 +77,7 @@ impl StateStore for KvStateStore {
         let _ = self
             .kv_backend
             .put(PutRequest {
-                key: with_prefix(key).into_bytes(),
+                key: self.with_prefix_key(key).into_bytes(),
                 value,
                 ..Default::default()
             })
@@ -84,14 +91,17 @@ impl StateStore for KvStateStore {
         // extend their lifetimes to be used in the stream
         let path = path.to_string();
 
-        let key = with_prefix(path.trim_start_matches(DELIMITER)).into_bytes();
+        let key = self
+            .with_prefix_key(path.trim_start_matches(DELIMITER))
+            .into_bytes();
         let req = RangeRequest::new().with_prefix(key);
 
+        let prefix = self.prefix.clone();
         let stream = PaginationStream::new(
             self.kv_backend.clone(),
             req,
             self.max_size_per_range,
-            Arc::new(decode_kv),
+            Arc::new(move |kv: KeyValue| decode_kv(kv, &prefix)),
         );
 
         let stream = stream.map(move |r| {
@@ -109,7 +119,7 @@ impl StateStore for KvStateStore {
             .batch_delete(BatchDeleteRequest {
                 keys: keys
                     .iter()
-                    .map(|x| with_prefix(x).into_bytes())
+                    .map(|x| self.with_prefix_key(x).into_bytes())
                     .collect::<Vec<_>>(),
                 ..Default::default()
             })
@@ -141,6 +151,7 @@ mod tests {
         let store = &KvStateStore {
             kv_backend: Arc::new(MemoryKvBackend::new()),
             max_size_per_range: 1, // for testing "more" in range
+            prefix: PROCEDURE_PREFIX.to_string(),
         };
 
         let walk_top_down = async move |path: &str| -> Vec<KeyValue> {
@@ -189,4 +200,43 @@ mod tests {
         let data = walk_top_down("a/").await;
         assert_eq!(vec![("a/1".to_string(), b"v1".to_vec()),], data);
     }
+
+    #[tokio::test]
+    async fn test_state_store_with_prefix() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let store = KvStateStore::new(kv_backend.clone());
+        let node1 = KvStateStore::with_prefix(kv_backend.clone(), "/__procedure__/1/");
+        let node2 = KvStateStore::with_prefix(kv_backend, "/__standalone_procedure__/2/");
+
+        store.put("a/1", b"v1".to_vec()).await.unwrap();
+        node2.put("a/1", b"v2".to_vec()).await.unwrap();
+
+        let data: Vec<_> = node2
+            .walk_top_down("/")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec![("a/1".to_string(), b"v2".to_vec())], data);
+
+        let data: Vec<_> = node1
+            .walk_top_down("/")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(data.is_empty());
+
+        node2.delete("a/1").await.unwrap();
+        let data: Vec<_> = store
+            .walk_top_down("/")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(vec![("a/1".to_string(), b"v1".to_vec())], data);
+    }
 }
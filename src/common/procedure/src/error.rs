@@ -50,6 +50,15 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Too many running procedures, max running procedures: {}",
+        max_running_procedures
+    ))]
+    TooManyRunningProcedures {
+        max_running_procedures: usize,
+        location: Location,
+    },
+
     #[snafu(display("Failed to put state, key: '{key}'"))]
     PutState {
         key: String,
@@ -156,6 +165,7 @@ impl ErrorExt for Error {
             Error::LoaderConflict { .. } | Error::DuplicateProcedure { .. } => {
                 StatusCode::InvalidArguments
             }
+            Error::TooManyRunningProcedures { .. } => StatusCode::RateLimited,
             Error::ProcedurePanic { .. } | Error::CorruptedData { .. } => StatusCode::Unexpected,
             Error::ProcedureExec { source, .. } => source.status_code(),
             Error::StartRemoveOutdatedMetaTask { source, .. }
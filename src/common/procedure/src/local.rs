@@ -31,6 +31,7 @@ use tokio::sync::{Mutex as TokioMutex, Notify};
 use crate::error::{
     DuplicateProcedureSnafu, Error, LoaderConflictSnafu, ManagerNotStartSnafu, Result,
     StartRemoveOutdatedMetaTaskSnafu, StopRemoveOutdatedMetaTaskSnafu,
+    TooManyRunningProceduresSnafu,
 };
 use crate::local::lock::LockMap;
 use crate::local::runner::Runner;
@@ -201,6 +202,39 @@ impl ManagerContext {
         true
     }
 
+    /// Inserts the root procedure like [ManagerContext::try_insert_procedure], if less
+    /// than `max_running` root procedures are running or retrying. They are counted
+    /// under the same lock as the insertion, so the concurrent submissions can't exceed
+    /// the limit.
+    fn try_insert_root_procedure(&self, meta: ProcedureMetaRef, max_running: usize) -> Result<()> {
+        let mut procedures = self.procedures.write().unwrap();
+        ensure!(
+            !procedures.contains_key(&meta.id),
+            DuplicateProcedureSnafu {
+                procedure_id: meta.id
+            }
+        );
+        let running = procedures
+            .values()
+            .filter(|meta| meta.parent_id.is_none())
+            .filter(|meta| {
+                let state = meta.state();
+                state.is_running() || state.is_retrying()
+            })
+            .count();
+        ensure!(
+            running < max_running,
+            TooManyRunningProceduresSnafu {
+                max_running_procedures: max_running,
+            }
+        );
+
+        let old = procedures.insert(meta.id, meta);
+        debug_assert!(old.is_none());
+
+        Ok(())
+    }
+
     /// Returns the [ProcedureState] of specific `procedure_id`.
     fn state(&self, procedure_id: ProcedureId) -> Option<ProcedureState> {
         let procedures = self.procedures.read().unwrap();
@@ -369,6 +403,8 @@ pub struct ManagerConfig {
     pub parent_path: String,
     pub max_retry_times: usize,
     pub retry_delay: Duration,
+    /// Max number of the root procedures running at the same time.
+    pub max_running_procedures: usize,
     pub remove_outdated_meta_task_interval: Duration,
     pub remove_outdated_meta_ttl: Duration,
 }
@@ -379,6 +415,7 @@ impl Default for ManagerConfig {
             parent_path: "".to_string(),
             max_retry_times: 3,
             retry_delay: Duration::from_millis(500),
+            max_running_procedures: 128,
             remove_outdated_meta_task_interval: Duration::from_secs(60 * 10),
             remove_outdated_meta_ttl: META_TTL,
         }
@@ -391,6 +428,7 @@ pub struct LocalManager {
     procedure_store: Arc<ProcedureStore>,
     max_retry_times: usize,
     retry_delay: Duration,
+    max_running_procedures: usize,
     /// GC task.
     remove_outdated_meta_task: TokioMutex<Option<RepeatedTask<Error>>>,
     config: ManagerConfig,
@@ -406,6 +444,7 @@ impl LocalManager {
             procedure_store: Arc::new(ProcedureStore::new(&config.parent_path, state_store)),
             max_retry_times: config.max_retry_times,
             retry_delay: config.retry_delay,
+            max_running_procedures: config.max_running_procedures,
             remove_outdated_meta_task: TokioMutex::new(None),
             config,
         }
@@ -422,12 +461,14 @@ impl LocalManager {
         )
     }
 
-    /// Submit a root procedure with given `procedure_id`.
+    /// Submit a root procedure with given `procedure_id`, if less than `max_running` root
+    /// procedures are running.
     fn submit_root(
         &self,
         procedure_id: ProcedureId,
         step: u32,
         procedure: BoxedProcedure,
+        max_running: usize,
     ) -> Result<Watcher> {
         ensure!(self.manager_ctx.running(), ManagerNotStartSnafu);
 
@@ -447,10 +488,8 @@ impl LocalManager {
         let watcher = meta.state_receiver.clone();
 
         // Inserts meta into the manager before actually spawnd the runner.
-        ensure!(
-            self.manager_ctx.try_insert_procedure(meta),
-            DuplicateProcedureSnafu { procedure_id },
-        );
+        self.manager_ctx
+            .try_insert_root_procedure(meta, max_running)?;
 
         let _handle = common_runtime::spawn_bg(async move {
             // Run the root procedure.
@@ -486,10 +525,12 @@ impl LocalManager {
                     loaded_procedure.step
                 );
 
+                // The recovered procedures aren't limited, they were accepted before.
                 if let Err(e) = self.submit_root(
                     *procedure_id,
                     loaded_procedure.step,
                     loaded_procedure.procedure,
+                    usize::MAX,
                 ) {
                     logging::error!(e; "Failed to recover procedure {}", procedure_id);
                 }
@@ -572,7 +613,12 @@ impl ProcedureManager for LocalManager {
             DuplicateProcedureSnafu { procedure_id }
         );
 
-        self.submit_root(procedure.id, 0, procedure.procedure)
+        self.submit_root(
+            procedure.id,
+            0,
+            procedure.procedure,
+            self.max_running_procedures,
+        )
     }
 
     async fn procedure_state(&self, procedure_id: ProcedureId) -> Result<Option<ProcedureState>> {
@@ -625,6 +671,7 @@ pub(crate) mod test_util {
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
+    use std::sync::atomic::AtomicUsize;
 
     use common_error::mock::MockError;
     use common_error::status_code::StatusCode;
@@ -660,6 +707,33 @@ mod tests {
         assert!(!ctx.try_insert_procedure(meta));
     }
 
+    #[test]
+    fn test_manager_context_insert_root_limited() {
+        let ctx = ManagerContext::new();
+        let meta = Arc::new(test_util::procedure_meta_for_test());
+        ctx.try_insert_root_procedure(meta.clone(), 1).unwrap();
+        assert_matches!(
+            ctx.try_insert_root_procedure(meta.clone(), 2).unwrap_err(),
+            Error::DuplicateProcedure { .. }
+        );
+
+        // The running children aren't counted.
+        let _child = new_child(meta.id, &ctx);
+        let other = Arc::new(test_util::procedure_meta_for_test());
+        assert_matches!(
+            ctx.try_insert_root_procedure(other.clone(), 1).unwrap_err(),
+            Error::TooManyRunningProcedures { .. }
+        );
+        assert!(!ctx.contains_procedure(other.id));
+        ctx.try_insert_root_procedure(other.clone(), 2).unwrap();
+
+        // The finished procedures aren't counted.
+        meta.set_state(ProcedureState::Done);
+        other.set_state(ProcedureState::Done);
+        ctx.try_insert_root_procedure(Arc::new(test_util::procedure_meta_for_test()), 1)
+            .unwrap();
+    }
+
     fn new_child(parent_id: ProcedureId, ctx: &ManagerContext) -> ProcedureMetaRef {
         let mut child = test_util::procedure_meta_for_test();
         child.parent_id = Some(parent_id);
@@ -994,6 +1068,7 @@ mod tests {
             retry_delay: Duration::from_millis(500),
             remove_outdated_meta_task_interval: Duration::from_millis(1),
             remove_outdated_meta_ttl: Duration::from_millis(1),
+            ..Default::default()
         };
         let state_store = Arc::new(ObjectStateStore::new(object_store.clone()));
         let manager = LocalManager::new(config, state_store);
@@ -1065,4 +1140,112 @@ mod tests {
             .unwrap()
             .is_none());
     }
+
+    #[tokio::test]
+    async fn test_retry_configured_times() {
+        let dir = create_temp_dir("retry_configured_times");
+        let config = ManagerConfig {
+            parent_path: "data/".to_string(),
+            max_retry_times: 2,
+            retry_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let state_store = Arc::new(ObjectStateStore::new(test_util::new_object_store(&dir)));
+        let manager = LocalManager::new(config, state_store);
+        manager.start().await.unwrap();
+
+        #[derive(Debug)]
+        struct AlwaysRetryProcedure {
+            executions: Arc<AtomicUsize>,
+        }
+
+        #[async_trait]
+        impl Procedure for AlwaysRetryProcedure {
+            fn type_name(&self) -> &str {
+                "AlwaysRetryProcedure"
+            }
+
+            async fn execute(&mut self, _ctx: &Context) -> Result<Status> {
+                let _ = self.executions.fetch_add(1, Ordering::Relaxed);
+                Err(Error::retry_later(MockError::new(
+                    StatusCode::StorageUnavailable,
+                )))
+            }
+
+            fn dump(&self) -> Result<String> {
+                Ok(String::new())
+            }
+
+            fn lock_key(&self) -> LockKey {
+                LockKey::single("test.retry")
+            }
+        }
+
+        let executions = Arc::new(AtomicUsize::new(0));
+        let mut watcher = manager
+            .submit(ProcedureWithId::with_random_id(Box::new(
+                AlwaysRetryProcedure {
+                    executions: executions.clone(),
+                },
+            )))
+            .await
+            .unwrap();
+        let err = crate::watcher::wait(&mut watcher).await.unwrap_err();
+        let error::Error::ProcedureExec { source, .. } = &err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_matches!(**source, error::Error::RetryTimesExceeded { .. });
+        // The first execution and the 2 retries.
+        assert_eq!(3, executions.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_too_many_running_procedures() {
+        let dir = create_temp_dir("too_many_running_procedures");
+        let config = ManagerConfig {
+            parent_path: "data/".to_string(),
+            max_running_procedures: 1,
+            ..Default::default()
+        };
+        let state_store = Arc::new(ObjectStateStore::new(test_util::new_object_store(&dir)));
+        let manager = LocalManager::new(config, state_store);
+        manager.start().await.unwrap();
+
+        #[derive(Debug)]
+        struct PendingProcedure;
+
+        #[async_trait]
+        impl Procedure for PendingProcedure {
+            fn type_name(&self) -> &str {
+                "PendingProcedure"
+            }
+
+            async fn execute(&mut self, _ctx: &Context) -> Result<Status> {
+                std::future::pending().await
+            }
+
+            fn dump(&self) -> Result<String> {
+                Ok(String::new())
+            }
+
+            fn lock_key(&self) -> LockKey {
+                LockKey::default()
+            }
+        }
+
+        let _watcher = manager
+            .submit(ProcedureWithId::with_random_id(Box::new(PendingProcedure)))
+            .await
+            .unwrap();
+        assert_matches!(
+            manager
+                .submit(ProcedureWithId::with_random_id(Box::new(PendingProcedure)))
+                .await
+                .unwrap_err(),
+            error::Error::TooManyRunningProcedures {
+                max_running_procedures: 1,
+                ..
+            }
+        );
+    }
 }
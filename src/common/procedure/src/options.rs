@@ -26,6 +26,9 @@ pub struct ProcedureConfig {
     /// Initial retry delay of procedures, increases exponentially.
     #[serde(with = "humantime_serde")]
     pub retry_delay: Duration,
+    /// Max number of the root procedures running at the same time, a procedure
+    /// submitted beyond it is rejected.
+    pub max_running_procedures: usize,
 }
 
 impl Default for ProcedureConfig {
@@ -33,6 +36,7 @@ impl Default for ProcedureConfig {
         ProcedureConfig {
             max_retry_times: 3,
             retry_delay: Duration::from_millis(500),
+            max_running_procedures: 128,
         }
     }
 }
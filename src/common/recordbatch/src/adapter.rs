@@ -22,7 +22,9 @@ use datafusion::arrow::datatypes::SchemaRef as DfSchemaRef;
 use datafusion::error::Result as DfResult;
 use datafusion::parquet::arrow::async_reader::{AsyncFileReader, ParquetRecordBatchStream};
 use datafusion::physical_plan::metrics::BaselineMetrics;
-use datafusion::physical_plan::RecordBatchStream as DfRecordBatchStream;
+use datafusion::physical_plan::{
+    displayable, ExecutionPlan, RecordBatchStream as DfRecordBatchStream,
+};
 use datafusion_common::DataFusionError;
 use datatypes::schema::{Schema, SchemaRef};
 use futures::ready;
@@ -30,8 +32,8 @@ use snafu::ResultExt;
 
 use crate::error::{self, Result};
 use crate::{
-    DfRecordBatch, DfSendableRecordBatchStream, RecordBatch, RecordBatchStream,
-    SendableRecordBatchStream, Stream,
+    DfRecordBatch, DfSendableRecordBatchStream, OrderOption, PlanMetrics, RecordBatch,
+    RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream, Stream,
 };
 
 type FutureStream =
@@ -216,6 +218,82 @@ impl Stream for RecordBatchStreamAdapter {
     }
 }
 
+/// A [RecordBatchStream] that reports the execution metrics of the DataFusion plan
+/// producing it.
+pub struct PlanMetricsStream {
+    stream: SendableRecordBatchStream,
+    plan: Arc<dyn ExecutionPlan>,
+}
+
+impl PlanMetricsStream {
+    /// Creates a stream reporting the metrics of `plan`, which must be the very
+    /// plan (not a copy) that `stream` is executed from.
+    pub fn new(stream: SendableRecordBatchStream, plan: Arc<dyn ExecutionPlan>) -> Self {
+        Self { stream, plan }
+    }
+}
+
+impl RecordBatchStream for PlanMetricsStream {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.stream.output_ordering()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        let mut plan_metrics = Vec::new();
+        collect_plan_metrics(self.plan.as_ref(), 0, &mut plan_metrics);
+        Some(RecordBatchMetrics { plan_metrics })
+    }
+}
+
+impl Stream for PlanMetricsStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+fn collect_plan_metrics(plan: &dyn ExecutionPlan, level: usize, result: &mut Vec<PlanMetrics>) {
+    let metrics = plan
+        .metrics()
+        .map(|metrics| {
+            metrics
+                .aggregate_by_name()
+                .sorted_for_display()
+                .timestamps_removed()
+                .iter()
+                .map(|metric| {
+                    let value = metric.value();
+                    (value.name().to_string(), value.as_usize())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    result.push(PlanMetrics {
+        plan: displayable(plan)
+            .one_line()
+            .to_string()
+            .trim_end()
+            .to_string(),
+        level,
+        metrics,
+    });
+
+    for child in plan.children() {
+        collect_plan_metrics(child.as_ref(), level + 1, result);
+    }
+}
+
 enum AsyncRecordBatchStreamAdapterState {
     Uninit(FutureStream),
     Ready(SendableRecordBatchStream),
@@ -31,6 +31,7 @@ use error::Result;
 use futures::task::{Context, Poll};
 use futures::{Stream, TryStreamExt};
 pub use recordbatch::RecordBatch;
+use serde::{Deserialize, Serialize};
 use snafu::{ensure, ResultExt};
 
 pub trait RecordBatchStream: Stream<Item = Result<RecordBatch>> {
@@ -39,6 +40,31 @@ pub trait RecordBatchStream: Stream<Item = Result<RecordBatch>> {
     fn output_ordering(&self) -> Option<&[OrderOption]> {
         None
     }
+
+    /// Returns the execution metrics of the plan that produces this stream, if it
+    /// reports them. They are complete only after the stream is exhausted.
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        None
+    }
+}
+
+/// Execution metrics of the physical plan that produces a [RecordBatchStream].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordBatchMetrics {
+    /// Metrics of every operator, in pre-order of the plan tree.
+    pub plan_metrics: Vec<PlanMetrics>,
+}
+
+/// Execution metrics of one operator in the physical plan.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanMetrics {
+    /// One line description of the operator.
+    pub plan: String,
+    /// Depth of the operator in the plan tree, the root is at level 0.
+    pub level: usize,
+    /// Metrics aggregated over all partitions, as (name, value) pairs. Time
+    /// metrics are in nanoseconds.
+    pub metrics: Vec<(String, usize)>,
 }
 
 pub type SendableRecordBatchStream = Pin<Box<dyn RecordBatchStream + Send>>;
@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod log_stream;
 pub mod logging;
 mod macros;
 pub mod metric;
+mod otlp;
 mod panic_hook;
 
 use std::collections::hash_map::DefaultHasher;
@@ -23,7 +25,7 @@ use std::hash::{Hash, Hasher};
 pub use logging::{init_default_ut_logging, init_global_logging, trace_id, TRACE_ID};
 pub use metric::dump_metrics;
 use once_cell::sync::OnceCell;
-pub use panic_hook::set_panic_hook;
+pub use panic_hook::{set_panic_hook, PanicHookOptions};
 use parking_lot::Mutex;
 use rand::random;
 use snowflake::SnowflakeIdBucket;
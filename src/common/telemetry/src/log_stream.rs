@@ -0,0 +1,165 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Broadcasts the formatted log lines, so they can be tailed by remote clients.
+//!
+//! Subscribers receive the lines through a bounded channel. Logging never waits for
+//! them: a subscriber that falls behind loses its oldest lines, the others and the
+//! logging pipeline aren't affected. The lines are only kept while someone subscribes,
+//! so the recent lines are the ones logged since.
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Number of recent lines kept for new subscribers.
+const RECENT_LINES: usize = 1000;
+
+/// Number of lines a subscriber may fall behind before losing lines.
+const CHANNEL_CAPACITY: usize = 1024;
+
+static LOG_STREAM: Lazy<LogStream> = Lazy::new(|| LogStream::new(RECENT_LINES, CHANNEL_CAPACITY));
+
+struct LogStream {
+    sender: broadcast::Sender<Arc<str>>,
+    recent: Mutex<VecDeque<Arc<str>>>,
+    max_recent: usize,
+}
+
+impl LogStream {
+    fn new(max_recent: usize, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            recent: Mutex::new(VecDeque::with_capacity(max_recent)),
+            max_recent,
+        }
+    }
+
+    fn push(&self, line: Arc<str>) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        // Sends under the lock, so a new subscriber sees a line either in the recent
+        // lines or in its receiver.
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == self.max_recent {
+            let _ = recent.pop_front();
+        }
+        recent.push_back(line.clone());
+        // Fails if there is no subscriber.
+        let _ = self.sender.send(line);
+    }
+
+    fn subscribe(&self, recent: usize) -> (Vec<Arc<str>>, broadcast::Receiver<Arc<str>>) {
+        let lines = self.recent.lock().unwrap();
+        let skip = lines.len().saturating_sub(recent);
+        let receiver = self.sender.subscribe();
+        (lines.iter().skip(skip).cloned().collect(), receiver)
+    }
+}
+
+/// Returns whether someone subscribes to the log lines.
+pub fn has_subscribers() -> bool {
+    LOG_STREAM.sender.receiver_count() > 0
+}
+
+/// Subscribes to the log lines, returns at most `recent` lines logged before and the
+/// receiver of the lines logged after. The receiver returns
+/// [Lagged](broadcast::error::RecvError::Lagged) if it falls behind.
+pub fn subscribe(recent: usize) -> (Vec<Arc<str>>, broadcast::Receiver<Arc<str>>) {
+    LOG_STREAM.subscribe(recent)
+}
+
+/// Makes the writers of a fmt layer, which push each formatted event to the log stream.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LogStreamWriter;
+
+impl<'a> MakeWriter<'a> for LogStreamWriter {
+    type Writer = LineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LineWriter::default()
+    }
+}
+
+/// Buffers an event and pushes it to the log stream on drop.
+#[derive(Default)]
+pub(crate) struct LineWriter {
+    buf: Vec<u8>,
+}
+
+impl io::Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LineWriter {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.buf);
+        let line = line.trim_end();
+        if !line.is_empty() {
+            LOG_STREAM.push(line.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::broadcast::error::RecvError;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_log_stream() {
+        let stream = LogStream::new(3, 2);
+        // Dropped as nobody subscribes.
+        stream.push("dropped".into());
+        let (recent, _receiver) = stream.subscribe(10);
+        assert!(recent.is_empty());
+
+        for line in ["a", "b", "c", "d"] {
+            stream.push(line.into());
+        }
+
+        let (recent, mut receiver) = stream.subscribe(2);
+        assert_eq!(vec![Arc::from("c"), Arc::from("d")], recent);
+        let (recent, mut slow_receiver) = stream.subscribe(10);
+        assert_eq!(3, recent.len());
+
+        stream.push("e".into());
+        assert_eq!("e", &*receiver.recv().await.unwrap());
+
+        // The slow receiver loses the oldest line, while the other still receives all.
+        stream.push("f".into());
+        stream.push("g".into());
+        assert!(matches!(
+            slow_receiver.recv().await,
+            Err(RecvError::Lagged(1))
+        ));
+        assert_eq!("f", &*slow_receiver.recv().await.unwrap());
+        assert_eq!("f", &*receiver.recv().await.unwrap());
+        assert_eq!("g", &*receiver.recv().await.unwrap());
+    }
+}
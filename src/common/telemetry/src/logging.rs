@@ -13,10 +13,12 @@
 // limitations under the License.
 
 //! logging stuffs, inspired by databend
-use std::env;
+use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex, Once};
+use std::{env, fmt};
 
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use opentelemetry::global;
 use opentelemetry::sdk::propagation::TraceContextPropagator;
 use serde::{Deserialize, Serialize};
@@ -25,11 +27,15 @@ use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
-use tracing_subscriber::fmt::Layer;
+use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, Layer, MakeWriter};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::{filter, EnvFilter, Registry};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{filter, reload, EnvFilter, Registry};
 
+use crate::log_stream::{self, LogStreamWriter};
+use crate::otlp;
 pub use crate::{debug, error, info, log, trace, warn};
 
 tokio::task_local! {
@@ -62,6 +68,18 @@ pub struct LoggingOptions {
     pub dir: String,
     pub level: Option<String>,
     pub enable_jaeger_tracing: bool,
+    /// Format of the log on the stdout, the log files are always in JSON.
+    pub log_format: LogFormat,
+    /// Interval to start a new log file at.
+    pub rotation: LogRotation,
+    /// Prefix of the names of the log files, the name of the app by default.
+    pub file_prefix: Option<String>,
+    /// Endpoint of the OpenTelemetry collector the spans and the metrics are exported
+    /// to over OTLP/gRPC, like `http://127.0.0.1:4317`.
+    pub otlp_endpoint: Option<String>,
+    /// Whether the log lines can be tailed by remote clients, by `/admin/logs/stream`
+    /// if `http.enable_log_stream` is also set.
+    pub enable_log_stream: bool,
 }
 
 impl Default for LoggingOptions {
@@ -70,6 +88,62 @@ impl Default for LoggingOptions {
             dir: "/tmp/greptimedb/logs".to_string(),
             level: None,
             enable_jaeger_tracing: false,
+            log_format: LogFormat::default(),
+            rotation: LogRotation::default(),
+            file_prefix: None,
+            otlp_endpoint: None,
+            enable_log_stream: false,
+        }
+    }
+}
+
+/// Interval to start a new log file at, the start time is appended to the file name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Minutely,
+    #[default]
+    Hourly,
+    Daily,
+    /// A single file that is never rotated.
+    Never,
+}
+
+impl From<LogRotation> for Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        match rotation {
+            LogRotation::Minutely => Rotation::MINUTELY,
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        }
+    }
+}
+
+/// Format of the log on the stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// A line per event with the timestamp, level, spans, target and fields.
+    #[default]
+    Pretty,
+    /// Like `Pretty` without the names of the spans, only their fields.
+    Compact,
+    /// A JSON object per event and per line, in the format of the log files.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "compact" => Ok(LogFormat::Compact),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "invalid log format '{s}', expected pretty, compact or json"
+            )),
         }
     }
 }
@@ -78,6 +152,55 @@ impl Default for LoggingOptions {
 pub struct TracingOptions {
     #[cfg(feature = "tokio-console")]
     pub tokio_console_addr: Option<String>,
+    /// Fields attached to every log event, like the instance id.
+    pub tags: Vec<(String, String)>,
+    /// Version of the app in the exported spans and metrics.
+    pub app_version: Option<String>,
+}
+
+/// Writes the tags before each event of the stdout log.
+struct TaggedFormat<F> {
+    /// The tags formatted as `key=value `.
+    prefix: String,
+    inner: F,
+}
+
+impl<F> TaggedFormat<F> {
+    fn new(tags: &[(String, String)], inner: F) -> Self {
+        let prefix = tags.iter().map(|(k, v)| format!("{k}={v} ")).collect();
+        Self { prefix, inner }
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for TaggedFormat<F>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> fmt::Result {
+        writer.write_str(&self.prefix)?;
+        self.inner.format_event(ctx, writer, event)
+    }
+}
+
+/// Creates the layer writing the events as JSON lines to `writer`, in the format of
+/// the log files with the `tags` as fields. The fields of the spans are only written
+/// under a [JsonStorageLayer].
+fn json_layer<W>(app_name: &str, tags: &[(String, String)], writer: W) -> BunyanFormattingLayer<W>
+where
+    W: for<'writer> MakeWriter<'writer> + 'static,
+{
+    let default_fields: HashMap<_, _> = tags
+        .iter()
+        .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+        .collect();
+    BunyanFormattingLayer::with_default_fields(app_name.to_string(), writer, default_fields)
 }
 
 /// Init tracing for unittest.
@@ -118,6 +241,30 @@ static GLOBAL_UT_LOG_GUARD: Lazy<Arc<Mutex<Option<Vec<WorkerGuard>>>>> =
 
 const DEFAULT_LOG_TARGETS: &str = "info";
 
+/// The log level the process started with, restored by [reload_log_level].
+static INITIAL_LOG_TARGETS: OnceCell<String> = OnceCell::new();
+
+/// Handle swapping the log level of the global subscriber.
+static LOG_FILTER_HANDLE: OnceCell<reload::Handle<filter::Targets, Registry>> = OnceCell::new();
+
+/// Replaces the log level of the running process with `targets`, in the format of the
+/// `level` option like `info,servers=debug`, or restores the initial level if `None`.
+/// Returns the reason if the level is invalid or the logging isn't initialized.
+pub fn reload_log_level(targets: Option<&str>) -> std::result::Result<(), String> {
+    let handle = LOG_FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "the log level isn't reloadable".to_string())?;
+    let targets = targets
+        .or(INITIAL_LOG_TARGETS.get().map(String::as_str))
+        .unwrap_or(DEFAULT_LOG_TARGETS);
+    let filter = targets
+        .parse::<filter::Targets>()
+        .map_err(|e| format!("invalid log level '{targets}': {e}"))?;
+    handle.reload(filter).map_err(|e| e.to_string())?;
+    info!("Reloaded the log level to '{}'", targets);
+    Ok(())
+}
+
 #[allow(clippy::print_stdout)]
 pub fn init_global_logging(
     app_name: &str,
@@ -132,24 +279,71 @@ pub fn init_global_logging(
     // Enable log compatible layer to convert log record to tracing span.
     LogTracer::init().expect("log tracer must be valid");
 
-    // Stdout layer.
+    // Stdout layer, only the one of the `log_format` is set.
     let (stdout_writer, stdout_guard) = tracing_appender::non_blocking(std::io::stdout());
-    let stdout_logging_layer = Layer::new().with_writer(stdout_writer);
+    let log_format = opts.log_format;
+    let stdout_pretty_layer = (log_format == LogFormat::Pretty).then(|| {
+        Layer::new()
+            .event_format(TaggedFormat::new(
+                &tracing_opts.tags,
+                format::Format::default(),
+            ))
+            .with_writer(stdout_writer.clone())
+    });
+    let stdout_compact_layer = (log_format == LogFormat::Compact).then(|| {
+        Layer::new()
+            .event_format(TaggedFormat::new(
+                &tracing_opts.tags,
+                format::Format::default().compact(),
+            ))
+            .with_writer(stdout_writer.clone())
+    });
+    let stdout_json_layer = (log_format == LogFormat::Json)
+        .then(|| json_layer(app_name, &tracing_opts.tags, stdout_writer));
     guards.push(stdout_guard);
 
+    // Log stream layer, which serves the remote log tailing. The events aren't formatted
+    // while nobody tails the logs.
+    let log_stream_layer = opts.enable_log_stream.then(|| {
+        Layer::new()
+            .event_format(TaggedFormat::new(
+                &tracing_opts.tags,
+                format::Format::default(),
+            ))
+            .with_ansi(false)
+            .with_writer(LogStreamWriter)
+            .with_filter(filter::filter_fn(|_| log_stream::has_subscribers()))
+    });
+
     // JSON log layer.
-    let rolling_appender = RollingFileAppender::new(Rotation::HOURLY, dir, app_name);
+    let file_prefix = opts.file_prefix.as_deref().unwrap_or(app_name);
+    let rolling_appender = RollingFileAppender::new(opts.rotation.into(), dir, file_prefix);
     let (rolling_writer, rolling_writer_guard) = tracing_appender::non_blocking(rolling_appender);
-    let file_logging_layer = BunyanFormattingLayer::new(app_name.to_string(), rolling_writer);
+    let default_fields: HashMap<_, _> = tracing_opts
+        .tags
+        .iter()
+        .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+        .collect();
+    let file_logging_layer = BunyanFormattingLayer::with_default_fields(
+        app_name.to_string(),
+        rolling_writer,
+        default_fields.clone(),
+    );
     guards.push(rolling_writer_guard);
 
     // error JSON log layer.
-    let err_rolling_appender =
-        RollingFileAppender::new(Rotation::HOURLY, dir, format!("{}-{}", app_name, "err"));
+    let err_rolling_appender = RollingFileAppender::new(
+        opts.rotation.into(),
+        dir,
+        format!("{}-{}", file_prefix, "err"),
+    );
     let (err_rolling_writer, err_rolling_writer_guard) =
         tracing_appender::non_blocking(err_rolling_appender);
-    let err_file_logging_layer =
-        BunyanFormattingLayer::new(app_name.to_string(), err_rolling_writer);
+    let err_file_logging_layer = BunyanFormattingLayer::with_default_fields(
+        app_name.to_string(),
+        err_rolling_writer,
+        default_fields,
+    );
     guards.push(err_rolling_writer_guard);
 
     // resolve log level settings from:
@@ -164,6 +358,7 @@ pub fn init_global_logging(
     let filter = targets_string
         .parse::<filter::Targets>()
         .expect("error parsing log level string");
+    let _ = INITIAL_LOG_TARGETS.set(targets_string.to_string());
 
     // Must enable 'tokio_unstable' cfg to use this feature.
     // For example: `RUSTFLAGS="--cfg tokio_unstable" cargo run -F common-telemetry/console -- standalone start`
@@ -185,44 +380,160 @@ pub fn init_global_logging(
             None
         };
 
-        let stdout_logging_layer = stdout_logging_layer.with_filter(filter.clone());
+        let stdout_pretty_layer = stdout_pretty_layer.with_filter(filter.clone());
+        let stdout_compact_layer = stdout_compact_layer.with_filter(filter.clone());
+        let stdout_json_layer = stdout_json_layer.with_filter(filter.clone());
+
+        let log_stream_layer = log_stream_layer.with_filter(filter.clone());
 
         let file_logging_layer = file_logging_layer.with_filter(filter);
 
         Registry::default()
             .with(tokio_console_layer)
             .with(JsonStorageLayer)
-            .with(stdout_logging_layer)
+            .with(stdout_pretty_layer)
+            .with(stdout_compact_layer)
+            .with(stdout_json_layer)
+            .with(log_stream_layer)
             .with(file_logging_layer)
             .with(err_file_logging_layer.with_filter(filter::LevelFilter::ERROR))
     };
 
+    // OTLP layer, the process runs without it if it can't be installed.
+    let otlp_layer = opts.otlp_endpoint.as_deref().and_then(|endpoint| {
+        let attributes = otlp::resource_attributes(
+            app_name,
+            tracing_opts.app_version.as_deref(),
+            &tracing_opts.tags,
+        );
+        if let Err(e) = otlp::start_metrics_push(endpoint, &attributes) {
+            println!("Failed to start pushing the metrics to {endpoint}: {e}");
+        }
+        // Jaeger installs the global tracer provider instead.
+        match otlp::install_tracer(endpoint, &attributes, !enable_jaeger_tracing) {
+            Ok(tracer) => Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+            Err(e) => {
+                println!("Failed to install the OTLP exporter of {endpoint}: {e}");
+                None
+            }
+        }
+    });
+
     // consume the `tracing_opts`, to avoid "unused" warnings
     let _ = tracing_opts;
 
+    // The level of the layers filtered separately for the tokio console isn't
+    // reloadable.
+    #[cfg(not(feature = "tokio-console"))]
+    let filter = {
+        let (filter, handle) = reload::Layer::new(filter);
+        let _ = LOG_FILTER_HANDLE.set(handle);
+        filter
+    };
+
     #[cfg(not(feature = "tokio-console"))]
     let subscriber = Registry::default()
         .with(filter)
         .with(JsonStorageLayer)
-        .with(stdout_logging_layer)
+        .with(stdout_pretty_layer)
+        .with(stdout_compact_layer)
+        .with(stdout_json_layer)
+        .with(log_stream_layer)
         .with(file_logging_layer)
         .with(err_file_logging_layer.with_filter(filter::LevelFilter::ERROR));
 
-    if enable_jaeger_tracing {
-        // Jaeger layer.
+    if enable_jaeger_tracing || otlp_layer.is_some() {
         global::set_text_map_propagator(TraceContextPropagator::new());
+    }
+    // Jaeger layer.
+    let jaeger_layer = enable_jaeger_tracing.then(|| {
         let tracer = opentelemetry_jaeger::new_pipeline()
             .with_service_name(app_name)
             .install_batch(opentelemetry::runtime::Tokio)
             .expect("install");
-        let jaeger_layer = Some(tracing_opentelemetry::layer().with_tracer(tracer));
-        let subscriber = subscriber.with(jaeger_layer);
-        tracing::subscriber::set_global_default(subscriber)
-            .expect("error setting global tracing subscriber");
-    } else {
-        tracing::subscriber::set_global_default(subscriber)
-            .expect("error setting global tracing subscriber");
-    }
+        tracing_opentelemetry::layer().with_tracer(tracer)
+    });
+    let subscriber = subscriber.with(jaeger_layer).with(otlp_layer);
+    tracing::subscriber::set_global_default(subscriber)
+        .expect("error setting global tracing subscriber");
 
     guards
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_layer() {
+        let buf = SharedBuf::default();
+        let writer = buf.clone();
+        let tags = vec![("instance_id".to_string(), "node-\"1\"".to_string())];
+        let subscriber =
+            Registry::default()
+                .with(JsonStorageLayer)
+                .with(json_layer("test", &tags, move || writer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("region", region_id = 42).entered();
+            // The fields of the panic hook.
+            tracing::error!(
+                message = "panicked at 'boom'",
+                backtrace = "0: main\n1: start",
+                panic.file = "src/main.rs",
+                panic.line = 7,
+            );
+        });
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(1, output.lines().count());
+        let event: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!("node-\"1\"", event["instance_id"]);
+        assert_eq!("test", event["name"]);
+        // The bunyan level of ERROR.
+        assert_eq!(50, event["level"]);
+        assert!(event["msg"]
+            .as_str()
+            .unwrap()
+            .ends_with("panicked at 'boom'"));
+        assert_eq!("0: main\n1: start", event["backtrace"]);
+        assert_eq!("src/main.rs", event["panic.file"]);
+        assert_eq!(7, event["panic.line"]);
+        assert_eq!(42, event["region_id"]);
+    }
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!(LogFormat::Json, "json".parse().unwrap());
+        assert_eq!(LogFormat::Compact, "Compact".parse().unwrap());
+        assert!("text".parse::<LogFormat>().is_err());
+        assert_eq!(LogFormat::Pretty, LoggingOptions::default().log_format);
+    }
+
+    #[test]
+    fn test_deserialize_logging_options() {
+        let opts: LoggingOptions =
+            serde_json::from_str(r#"{"rotation": "daily", "file_prefix": "greptimedb"}"#).unwrap();
+        assert_eq!(LogRotation::Daily, opts.rotation);
+        assert_eq!(Some("greptimedb"), opts.file_prefix.as_deref());
+        assert_eq!(LogFormat::Pretty, opts.log_format);
+
+        let opts: LoggingOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(LogRotation::Hourly, opts.rotation);
+    }
+}
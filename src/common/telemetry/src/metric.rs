@@ -14,14 +14,85 @@
 
 // metric stuffs, inspired by databend
 
+use once_cell::sync::OnceCell;
+use prometheus::proto::{LabelPair, MetricFamily};
 use prometheus::{Encoder, TextEncoder};
 
+/// Labels attached to all the gathered metrics, like the instance id.
+static CONST_LABELS: OnceCell<Vec<(String, String)>> = OnceCell::new();
+
+/// Sets the labels attached to all the gathered metrics, only the first call takes effect.
+pub fn init_const_labels(labels: Vec<(String, String)>) {
+    if CONST_LABELS.set(labels).is_err() {
+        crate::warn!("const labels of metrics are already initialized");
+    }
+}
+
+/// Gathers the metrics of the default registry, with the const labels attached.
+/// A metric keeps its own value of a label it already has.
+pub fn gather() -> Vec<MetricFamily> {
+    let mut metric_families = prometheus::gather();
+    if let Some(labels) = CONST_LABELS.get() {
+        attach_labels(&mut metric_families, labels);
+    }
+    metric_families
+}
+
+fn attach_labels(metric_families: &mut [MetricFamily], labels: &[(String, String)]) {
+    for family in metric_families {
+        for metric in family.mut_metric().iter_mut() {
+            for (name, value) in labels {
+                if metric.get_label().iter().any(|x| x.get_name() == name) {
+                    continue;
+                }
+                let mut label = LabelPair::new();
+                label.set_name(name.clone());
+                label.set_value(value.clone());
+                metric.mut_label().push(label);
+            }
+        }
+    }
+}
+
 pub fn dump_metrics() -> Result<String, String> {
     let mut buffer = Vec::new();
     let encoder = TextEncoder::new();
-    let metric_families = prometheus::gather();
+    let metric_families = gather();
     encoder
         .encode(&metric_families, &mut buffer)
         .map_err(|_| "Encode metrics failed".to_string())?;
     String::from_utf8(buffer).map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{IntCounterVec, Opts, Registry};
+
+    use super::*;
+
+    #[test]
+    fn test_attach_labels() {
+        let registry = Registry::new();
+        let counter =
+            IntCounterVec::new(Opts::new("test_counter", "test"), &["cluster_name"]).unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        counter.with_label_values(&["own"]).inc();
+
+        let mut metric_families = registry.gather();
+        let labels = vec![
+            ("instance_id".to_string(), "host-1".to_string()),
+            ("cluster_name".to_string(), "prod".to_string()),
+        ];
+        attach_labels(&mut metric_families, &labels);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(
+            text.contains(r#"test_counter{cluster_name="own",instance_id="host-1"} 1"#),
+            "{text}"
+        );
+    }
+}
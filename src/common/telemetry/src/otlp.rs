@@ -0,0 +1,459 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of the spans and the metrics to an OpenTelemetry collector over OTLP/gRPC by
+//! `opentelemetry_otlp`, enabled by the `otlp_endpoint` logging option.
+//!
+//! The collector is connected lazily and may be unreachable, e.g. not started yet: the
+//! spans of a batch failed to export are dropped and the metrics are pushed again on
+//! the next interval. Nothing is retried and the process keeps running.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::OnceCell;
+use opentelemetry::metrics::{
+    Descriptor, InstrumentKind, Meter, MeterProvider as _, ObserverResult, SumObserver,
+    ValueObserver,
+};
+use opentelemetry::sdk::export::metrics::{Aggregator, AggregatorSelector};
+use opentelemetry::sdk::metrics::aggregators;
+use opentelemetry::sdk::trace::{self, Tracer, TracerProvider};
+use opentelemetry::sdk::Resource;
+use opentelemetry::trace::{TraceError, TracerProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{ExportConfig, TonicConfig, WithExportConfig};
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+
+use crate::warn;
+
+/// Value of the `service.name` resource attribute.
+const SERVICE_NAME: &str = "greptimedb";
+/// Interval to push the metrics at, and to look for the metrics registered meanwhile.
+const METRICS_PUSH_INTERVAL: Duration = Duration::from_secs(30);
+/// Timeout of a push of the metrics.
+const METRICS_PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Timeout of an export of a batch of spans.
+const SPANS_EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Max age of the metrics gathered for a push, shared by the observers of the push.
+const GATHER_MAX_AGE: Duration = Duration::from_secs(1);
+/// Min interval between two logged failures of the exports.
+const FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The provider of the OTLP tracer, kept alive here since the tracer only holds a weak
+/// reference to it, and it's only the global provider without jaeger.
+static TRACER_PROVIDER: OnceCell<TracerProvider> = OnceCell::new();
+
+/// Attributes of the process in the exported spans and metrics, as `(key, value)`.
+pub(crate) fn resource_attributes(
+    app_name: &str,
+    app_version: Option<&str>,
+    tags: &[(String, String)],
+) -> Vec<(String, String)> {
+    let mut attributes = vec![
+        ("service.name".to_string(), SERVICE_NAME.to_string()),
+        ("service.component".to_string(), app_name.to_string()),
+    ];
+    if let Some(version) = app_version {
+        attributes.push(("service.version".to_string(), version.to_string()));
+    }
+    attributes.extend(tags.iter().cloned());
+    attributes
+}
+
+fn key_values(attributes: &[(String, String)]) -> Vec<KeyValue> {
+    attributes
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .collect()
+}
+
+fn export_config(endpoint: &str, timeout: Duration) -> ExportConfig {
+    ExportConfig {
+        endpoint: endpoint.to_string(),
+        timeout,
+        ..Default::default()
+    }
+}
+
+/// Builds the tracer exporting the spans to the collector at `endpoint` in batches.
+///
+/// Its provider is only installed as the global one if `global` is set, i.e. jaeger
+/// isn't configured, which installs its own global provider and error handler.
+pub(crate) fn install_tracer(
+    endpoint: &str,
+    attributes: &[(String, String)],
+    global: bool,
+) -> Result<Tracer, TraceError> {
+    let exporter = opentelemetry_otlp::SpanExporter::new_tonic(
+        export_config(endpoint, SPANS_EXPORT_TIMEOUT),
+        TonicConfig::default(),
+    )
+    .map_err(|e| TraceError::from(format!("invalid OTLP endpoint '{endpoint}': {e}")))?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry::runtime::Tokio)
+        .with_config(trace::config().with_resource(Resource::new(key_values(attributes))))
+        .build();
+    let tracer = provider.tracer(SERVICE_NAME);
+    let provider = TRACER_PROVIDER.get_or_init(|| provider);
+
+    if global {
+        let _ = opentelemetry::global::set_tracer_provider(provider.clone());
+        // The default handler prints every failed export to the stderr.
+        let limiter = FailureLimiter::default();
+        let _ = opentelemetry::global::set_error_handler(move |e| {
+            if limiter.should_log() {
+                warn!("Failed to export by OTLP, the failed spans or metrics are dropped: {e}");
+            }
+        });
+    }
+    Ok(tracer)
+}
+
+/// Starts pushing the metrics of the default registry to the collector at `endpoint`
+/// every [METRICS_PUSH_INTERVAL].
+///
+/// The Prometheus metrics are bridged by observers of their series, as in the text
+/// format: the counters and the `_count`, `_sum` and `_bucket` series of the histograms
+/// and summaries are cumulative sums, the others are gauges.
+pub(crate) fn start_metrics_push(
+    endpoint: &str,
+    attributes: &[(String, String)],
+) -> Result<(), String> {
+    let runtime = tokio::runtime::Handle::try_current().map_err(|e| e.to_string())?;
+    let controller = opentelemetry_otlp::new_pipeline()
+        .metrics(tokio::spawn, opentelemetry::util::tokio_interval_stream)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_export_config(export_config(endpoint, METRICS_PUSH_TIMEOUT)),
+        )
+        .with_aggregator_selector(SeriesAggregatorSelector)
+        .with_resource(key_values(attributes))
+        .with_period(METRICS_PUSH_INTERVAL)
+        .build()
+        .map_err(|e| format!("invalid OTLP endpoint '{endpoint}': {e}"))?;
+    let mut bridge = PrometheusBridge::new(controller.provider().meter(SERVICE_NAME, None));
+
+    let _handle = runtime.spawn(async move {
+        // Pushes until the process exits.
+        let _controller = controller;
+        let mut interval = tokio::time::interval(METRICS_PUSH_INTERVAL);
+        loop {
+            let _ = interval.tick().await;
+            bridge.observe_new_series();
+        }
+    });
+    Ok(())
+}
+
+/// Aggregates the sums of the series by sum, and the gauges by last value.
+#[derive(Debug)]
+struct SeriesAggregatorSelector;
+
+impl AggregatorSelector for SeriesAggregatorSelector {
+    fn aggregator_for(&self, descriptor: &Descriptor) -> Option<Arc<dyn Aggregator + Send + Sync>> {
+        match descriptor.instrument_kind() {
+            InstrumentKind::ValueObserver => Some(Arc::new(aggregators::last_value())),
+            _ => Some(Arc::new(aggregators::sum())),
+        }
+    }
+}
+
+/// Registers an observer for each series of the Prometheus metrics.
+struct PrometheusBridge {
+    meter: Meter,
+    gathered: Arc<GatheredMetrics>,
+    observed: HashSet<String>,
+    /// The observers registered, kept with the bridge.
+    _sums: Vec<SumObserver<f64>>,
+    _gauges: Vec<ValueObserver<f64>>,
+}
+
+impl PrometheusBridge {
+    fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            gathered: Arc::default(),
+            observed: HashSet::new(),
+            _sums: Vec::new(),
+            _gauges: Vec::new(),
+        }
+    }
+
+    /// Registers the observers of the series not observed yet, as the metrics are
+    /// registered on their first use.
+    fn observe_new_series(&mut self) {
+        for family in self.gathered.get().iter() {
+            for sample in series(family) {
+                if !self.observed.insert(sample.name.clone()) {
+                    continue;
+                }
+                let gathered = self.gathered.clone();
+                let family_name = family.get_name().to_string();
+                let series_name = sample.name.clone();
+                let callback = move |result: ObserverResult<f64>| {
+                    let metrics = gathered.get();
+                    let Some(family) = metrics.iter().find(|f| f.get_name() == family_name) else {
+                        return;
+                    };
+                    for sample in series(family) {
+                        if sample.name == series_name {
+                            result.observe(sample.value, &sample.labels);
+                        }
+                    }
+                };
+                if sample.monotonic {
+                    self._sums.push(
+                        self.meter
+                            .f64_sum_observer(sample.name, callback)
+                            .with_description(family.get_help())
+                            .init(),
+                    );
+                } else {
+                    self._gauges.push(
+                        self.meter
+                            .f64_value_observer(sample.name, callback)
+                            .with_description(family.get_help())
+                            .init(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The metrics of the default registry, gathered once for all the observers of a push.
+#[derive(Default)]
+struct GatheredMetrics {
+    last: Mutex<Option<(Instant, Arc<Vec<MetricFamily>>)>>,
+}
+
+impl GatheredMetrics {
+    fn get(&self) -> Arc<Vec<MetricFamily>> {
+        let mut last = self.last.lock().unwrap();
+        match &*last {
+            Some((at, metrics)) if at.elapsed() < GATHER_MAX_AGE => metrics.clone(),
+            _ => {
+                let metrics = Arc::new(crate::metric::gather());
+                *last = Some((Instant::now(), metrics.clone()));
+                metrics
+            }
+        }
+    }
+}
+
+/// A sample of a series of a Prometheus metric family.
+#[derive(Debug, PartialEq)]
+struct Sample {
+    name: String,
+    labels: Vec<KeyValue>,
+    value: f64,
+    monotonic: bool,
+}
+
+/// Flattens `family` to the samples of its series, like the Prometheus text format.
+fn series(family: &MetricFamily) -> Vec<Sample> {
+    let name = family.get_name();
+    let mut samples = Vec::new();
+    let mut push = |suffix: &str, labels: Vec<KeyValue>, value: f64, monotonic: bool| {
+        samples.push(Sample {
+            name: format!("{name}{suffix}"),
+            labels,
+            value,
+            monotonic,
+        })
+    };
+    for m in family.get_metric() {
+        let labels = label_values(m.get_label());
+        let with_label = |key: &'static str, value: f64| {
+            let mut labels = labels.clone();
+            let value = if value == f64::INFINITY {
+                "+Inf".to_string()
+            } else {
+                value.to_string()
+            };
+            labels.push(KeyValue::new(key, value));
+            labels
+        };
+        match family.get_field_type() {
+            MetricType::COUNTER => push("", labels.clone(), m.get_counter().get_value(), true),
+            MetricType::GAUGE => push("", labels.clone(), m.get_gauge().get_value(), false),
+            MetricType::UNTYPED => push("", labels.clone(), m.get_untyped().get_value(), false),
+            MetricType::HISTOGRAM => {
+                let histogram = m.get_histogram();
+                // The `+Inf` bucket is implied, and has all the samples.
+                for bucket in histogram.get_bucket() {
+                    if bucket.get_upper_bound().is_infinite() {
+                        continue;
+                    }
+                    push(
+                        "_bucket",
+                        with_label("le", bucket.get_upper_bound()),
+                        bucket.get_cumulative_count() as f64,
+                        true,
+                    );
+                }
+                push(
+                    "_bucket",
+                    with_label("le", f64::INFINITY),
+                    histogram.get_sample_count() as f64,
+                    true,
+                );
+                push("_sum", labels.clone(), histogram.get_sample_sum(), true);
+                push(
+                    "_count",
+                    labels.clone(),
+                    histogram.get_sample_count() as f64,
+                    true,
+                );
+            }
+            MetricType::SUMMARY => {
+                let summary = m.get_summary();
+                for quantile in summary.get_quantile() {
+                    push(
+                        "",
+                        with_label("quantile", quantile.get_quantile()),
+                        quantile.get_value(),
+                        false,
+                    );
+                }
+                push("_sum", labels.clone(), summary.get_sample_sum(), true);
+                push(
+                    "_count",
+                    labels.clone(),
+                    summary.get_sample_count() as f64,
+                    true,
+                );
+            }
+        }
+    }
+    samples
+}
+
+fn label_values(labels: &[LabelPair]) -> Vec<KeyValue> {
+    labels
+        .iter()
+        .map(|label| KeyValue::new(label.get_name().to_string(), label.get_value().to_string()))
+        .collect()
+}
+
+/// Allows to log a failure once per [FAILURE_LOG_INTERVAL].
+#[derive(Default)]
+struct FailureLimiter {
+    last_logged: Mutex<Option<Instant>>,
+}
+
+impl FailureLimiter {
+    fn should_log(&self) -> bool {
+        let mut last_logged = self.last_logged.lock().unwrap();
+        match *last_logged {
+            Some(last) if last.elapsed() < FAILURE_LOG_INTERVAL => false,
+            _ => {
+                *last_logged = Some(Instant::now());
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{HistogramOpts, HistogramVec, IntCounter, Registry};
+
+    use super::*;
+
+    #[test]
+    fn test_resource_attributes() {
+        let tags = vec![("instance_id".to_string(), "host-1".to_string())];
+        assert_eq!(
+            vec![
+                ("service.name".to_string(), "greptimedb".to_string()),
+                (
+                    "service.component".to_string(),
+                    "greptime-standalone".to_string()
+                ),
+                ("service.version".to_string(), "0.4.0".to_string()),
+                ("instance_id".to_string(), "host-1".to_string()),
+            ],
+            resource_attributes("greptime-standalone", Some("0.4.0"), &tags)
+        );
+    }
+
+    #[test]
+    fn test_series() {
+        let registry = Registry::new();
+        let counter = IntCounter::new("test_requests", "requests").unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        counter.inc_by(3);
+        let histogram = HistogramVec::new(
+            HistogramOpts::new("test_latency", "latency").buckets(vec![0.1, 1.0]),
+            &["method"],
+        )
+        .unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        for v in [0.05, 0.5, 0.7, 5.0] {
+            histogram.with_label_values(&["get"]).observe(v);
+        }
+
+        let families = registry.gather();
+        let requests = families
+            .iter()
+            .find(|f| f.get_name() == "test_requests")
+            .unwrap();
+        assert_eq!(
+            vec![Sample {
+                name: "test_requests".to_string(),
+                labels: vec![],
+                value: 3.0,
+                monotonic: true,
+            }],
+            series(requests)
+        );
+
+        let latency = families
+            .iter()
+            .find(|f| f.get_name() == "test_latency")
+            .unwrap();
+        let samples = series(latency);
+        let buckets: Vec<_> = samples
+            .iter()
+            .filter(|s| s.name == "test_latency_bucket")
+            .map(|s| (s.labels[1].value.as_str().to_string(), s.value))
+            .collect();
+        assert_eq!(
+            vec![
+                ("0.1".to_string(), 1.0),
+                ("1".to_string(), 3.0),
+                ("+Inf".to_string(), 4.0)
+            ],
+            buckets
+        );
+        let count = samples
+            .iter()
+            .find(|s| s.name == "test_latency_count")
+            .unwrap();
+        assert_eq!(4.0, count.value);
+        assert!(count.monotonic);
+        assert_eq!(vec![KeyValue::new("method", "get")], count.labels);
+    }
+
+    #[test]
+    fn test_failure_limiter() {
+        let limiter = FailureLimiter::default();
+        assert!(limiter.should_log());
+        assert!(!limiter.should_log());
+    }
+}
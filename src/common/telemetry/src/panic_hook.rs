@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::panic;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::panic::{self, PanicInfo};
+use std::path::{Path, PathBuf};
 #[cfg(feature = "deadlock_detection")]
 use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use backtrace::Backtrace;
 use lazy_static::lazy_static;
@@ -25,7 +29,20 @@ lazy_static! {
         register_int_counter!("panic_counter", "panic_counter").unwrap();
 }
 
-pub fn set_panic_hook() {
+/// Options of the panic hook.
+#[derive(Debug, Default, Clone)]
+pub struct PanicHookOptions {
+    /// File the panics are appended to as JSON lines, which keeps the backtrace even if
+    /// the logging isn't working.
+    pub crash_log: Option<PathBuf>,
+    /// Aborts the process after a panic is recorded instead of unwinding, so no state
+    /// shared between the threads is left half mutated, and a core dump is produced.
+    pub abort: bool,
+}
+
+/// Sets the panic hook logging the panics, see [PanicHookOptions].
+pub fn set_panic_hook(opts: PanicHookOptions) {
+    let PanicHookOptions { crash_log, abort } = opts;
     // Set a panic hook that records the panic as a `tracing` event at the
     // `ERROR` verbosity level.
     //
@@ -47,8 +64,14 @@ pub fn set_panic_hook() {
         } else {
             tracing::error!(message = %panic, backtrace = %backtrace);
         }
+        if let Some(path) = &crash_log {
+            append_crash_record(path, &crash_record(panic, &backtrace));
+        }
         PANIC_COUNTER.inc();
         default_hook(panic);
+        if abort {
+            std::process::abort();
+        }
     }));
 
     #[cfg(feature = "deadlock_detection")]
@@ -69,3 +92,72 @@ pub fn set_panic_hook() {
         }
     });
 }
+
+/// Formats the panic as a JSON line.
+fn crash_record(panic: &PanicInfo, backtrace: &str) -> String {
+    let message = if let Some(message) = panic.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let record = serde_json::json!({
+        "timestamp_ms": timestamp_ms,
+        "thread": std::thread::current().name().unwrap_or("<unnamed>"),
+        "location": panic.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        "message": message,
+        "backtrace": backtrace,
+    });
+    let mut line = record.to_string();
+    line.push('\n');
+    line
+}
+
+/// Appends the `record` to the crash log, ignoring any error since the process is
+/// already panicking.
+fn append_crash_record(path: &Path, record: &str) {
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        // A single write, so the records of the concurrent panics don't interleave.
+        let _ = file.write_all(record.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_log() {
+        let path = std::env::temp_dir().join(format!("test_crash_log_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let crash_log = path.clone();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic| {
+            append_crash_record(&crash_log, &crash_record(panic, "backtrace"));
+        }));
+        let result = std::thread::Builder::new()
+            .name("crashing".to_string())
+            .spawn(|| panic!("boom {}", 42))
+            .unwrap()
+            .join();
+        panic::set_hook(default_hook);
+        assert!(result.is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let record: serde_json::Value = serde_json::from_str(content.trim_end()).unwrap();
+        assert_eq!("crashing", record["thread"]);
+        assert_eq!("boom 42", record["message"]);
+        assert_eq!("backtrace", record["backtrace"]);
+        assert!(record["location"]
+            .as_str()
+            .unwrap()
+            .contains("panic_hook.rs"));
+        assert!(record["timestamp_ms"].as_u64().unwrap() > 0);
+    }
+}
@@ -33,3 +33,11 @@ pub fn setup_git_versions() {
         build_data::get_git_dirty().map_or(DEFAULT_VALUE.to_string(), |v| v.to_string())
     );
 }
+
+#[allow(clippy::print_stdout)]
+pub fn setup_rustc_version() {
+    println!(
+        "cargo:rustc-env=RUSTC_VERSION={}",
+        build_data::get_rustc_version().unwrap_or_else(|_| DEFAULT_VALUE.to_string())
+    );
+}
@@ -0,0 +1,264 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Manual backups of the regions of the datanode.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
+use common_telemetry::{error, info, warn};
+use common_time::util::current_time_millis;
+use mito2::engine::{MitoEngine, MITO_ENGINE_NAME};
+use object_store::ObjectStore;
+use servers::error::{HandleBackupRequestSnafu, Result as ServerResult};
+use servers::http::backup::{BackupState, BackupStatus};
+use servers::query_handler::BackupHandler;
+use snafu::ResultExt;
+use store_api::region_request::{RegionFlushRequest, RegionRequest};
+use store_api::storage::RegionId;
+
+use crate::error::{
+    BackupInProgressSnafu, BackupRegionSnafu, EncodeJsonSnafu, Result, WriteBackupMetaSnafu,
+};
+use crate::region_server::RegionServer;
+
+/// Number of finished backups whose status is kept.
+const MAX_FINISHED_BACKUPS: usize = 16;
+
+/// Name of the file that records the status of a finished backup.
+const BACKUP_META_FILE: &str = "backup.json";
+
+/// Runs backups of the leader mito regions in the background and tracks their
+/// status. Only one backup runs at a time.
+#[derive(Clone)]
+pub struct BackupManager {
+    inner: Arc<BackupManagerInner>,
+}
+
+struct BackupManagerInner {
+    region_server: RegionServer,
+    mito: MitoEngine,
+    object_store: ObjectStore,
+    backups: Mutex<Backups>,
+}
+
+#[derive(Default)]
+struct Backups {
+    running: Option<String>,
+    statuses: HashMap<String, BackupStatus>,
+    /// Ids of finished backups, oldest first.
+    finished: VecDeque<String>,
+}
+
+impl BackupManager {
+    pub fn new(region_server: RegionServer, mito: MitoEngine, object_store: ObjectStore) -> Self {
+        Self {
+            inner: Arc::new(BackupManagerInner {
+                region_server,
+                mito,
+                object_store,
+                backups: Mutex::new(Backups::default()),
+            }),
+        }
+    }
+
+    /// Starts a backup in the background and returns its initial status.
+    pub fn start(&self) -> Result<BackupStatus> {
+        let regions: Vec<_> = self
+            .inner
+            .region_server
+            .opened_regions()
+            .into_iter()
+            .filter(|stat| stat.engine == MITO_ENGINE_NAME && stat.role.writable())
+            .map(|stat| stat.region_id)
+            .collect();
+
+        let status = {
+            let mut backups = self.inner.backups.lock().unwrap();
+            if let Some(id) = &backups.running {
+                return BackupInProgressSnafu { id }.fail();
+            }
+
+            let id = uuid::Uuid::new_v4().to_string();
+            let status = BackupStatus {
+                id: id.clone(),
+                state: BackupState::Running,
+                location: format!("{id}/"),
+                total_regions: regions.len(),
+                finished_regions: 0,
+                files: 0,
+                bytes: 0,
+                start_time_ms: current_time_millis(),
+                snapshot_time_ms: None,
+                end_time_ms: None,
+                error: None,
+            };
+            backups.running = Some(id.clone());
+            let _ = backups.statuses.insert(id, status.clone());
+            status
+        };
+
+        info!(
+            "Starting backup {} of {} regions to {}",
+            status.id, status.total_regions, status.location
+        );
+
+        let inner = self.inner.clone();
+        let id = status.id.clone();
+        let location = status.location.clone();
+        let _handle = common_runtime::spawn_bg(async move {
+            let result = inner.run(&id, &location, regions).await;
+            inner.finish(&id, result).await;
+        });
+
+        Ok(status)
+    }
+
+    /// Returns the status of the backup, or `None` if it is unknown.
+    pub fn status(&self, id: &str) -> Option<BackupStatus> {
+        let backups = self.inner.backups.lock().unwrap();
+        backups.statuses.get(id).cloned()
+    }
+}
+
+impl BackupManagerInner {
+    async fn run(&self, id: &str, location: &str, regions: Vec<RegionId>) -> Result<()> {
+        // Flushes all the regions before copying any of them, so the backups of the
+        // regions are taken at the same point rather than one after another.
+        let snapshot_time_ms = current_time_millis();
+        let flushed = futures::future::join_all(
+            regions
+                .iter()
+                .map(|region_id| async move { (*region_id, self.flush_region(*region_id).await) }),
+        )
+        .await;
+        let mut flushed_regions = Vec::with_capacity(flushed.len());
+        for (region_id, result) in flushed {
+            match result {
+                Ok(()) => flushed_regions.push(region_id),
+                Err(e) if e.status_code() == StatusCode::RegionNotFound => {
+                    // The region may be closed or migrated after the backup starts.
+                    warn!("Skip backing up region {region_id} in backup {id}, region not found");
+                    self.update(id, |status| status.total_regions -= 1);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.update(id, |status| {
+            status.snapshot_time_ms = Some(snapshot_time_ms)
+        });
+
+        for region_id in flushed_regions {
+            let stat = self
+                .mito
+                .backup_region(region_id, &self.object_store, location)
+                .await
+                .context(BackupRegionSnafu { region_id })?;
+            self.update(id, |status| {
+                status.finished_regions += 1;
+                status.files += stat.files;
+                status.bytes += stat.bytes;
+            });
+        }
+        Ok(())
+    }
+
+    /// Flushes the region so the data in its memtables is backed up.
+    async fn flush_region(&self, region_id: RegionId) -> Result<()> {
+        let _ = self
+            .region_server
+            .handle_request(
+                region_id,
+                RegionRequest::Flush(RegionFlushRequest {
+                    row_group_size: None,
+                }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn finish(&self, id: &str, result: Result<()>) {
+        match &result {
+            Ok(()) => self.update(id, |status| status.state = BackupState::Succeeded),
+            Err(e) => {
+                error!(e; "Failed to run backup {id}");
+                self.update(id, |status| {
+                    status.state = BackupState::Failed;
+                    status.error = Some(e.output_msg());
+                });
+            }
+        }
+        self.update(id, |status| {
+            status.end_time_ms = Some(current_time_millis())
+        });
+
+        if result.is_ok() {
+            if let Err(e) = self.write_meta(id).await {
+                error!(e; "Failed to write metadata of backup {id}");
+                self.update(id, |status| {
+                    status.state = BackupState::Failed;
+                    status.error = Some(e.output_msg());
+                });
+            }
+        }
+
+        let mut backups = self.backups.lock().unwrap();
+        backups.running = None;
+        backups.finished.push_back(id.to_string());
+        while backups.finished.len() > MAX_FINISHED_BACKUPS {
+            if let Some(oldest) = backups.finished.pop_front() {
+                let _ = backups.statuses.remove(&oldest);
+            }
+        }
+        info!("Backup {id} finished: {:?}", backups.statuses.get(id));
+    }
+
+    /// Writes the status of the backup into its location, so a complete backup
+    /// can be told apart from a partial one.
+    async fn write_meta(&self, id: &str) -> Result<()> {
+        let status = self.backups.lock().unwrap().statuses.get(id).cloned();
+        let Some(status) = status else {
+            return Ok(());
+        };
+        let path = format!("{}{BACKUP_META_FILE}", status.location);
+        let content = serde_json::to_vec(&status).context(EncodeJsonSnafu)?;
+        self.object_store
+            .write(&path, content)
+            .await
+            .context(WriteBackupMetaSnafu { path: &path })
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut BackupStatus)) {
+        let mut backups = self.backups.lock().unwrap();
+        if let Some(status) = backups.statuses.get_mut(id) {
+            f(status);
+        }
+    }
+}
+
+#[async_trait]
+impl BackupHandler for BackupManager {
+    async fn start_backup(&self) -> ServerResult<BackupStatus> {
+        self.start()
+            .map_err(BoxedError::new)
+            .context(HandleBackupRequestSnafu)
+    }
+
+    async fn backup_status(&self, id: &str) -> ServerResult<Option<BackupStatus>> {
+        Ok(self.status(id))
+    }
+}
@@ -26,7 +26,8 @@ use common_telemetry::logging::LoggingOptions;
 use file_engine::config::EngineConfig as FileEngineConfig;
 use meta_client::MetaClientOptions;
 use mito2::config::MitoConfig;
-use secrecy::SecretString;
+use object_store::util::normalize_dir;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use servers::heartbeat_options::HeartbeatOptions;
 use servers::http::HttpOptions;
@@ -36,12 +37,19 @@ use storage::config::{
     DEFAULT_PICKER_SCHEDULE_INTERVAL, DEFAULT_REGION_WRITE_BUFFER_SIZE,
 };
 use storage::scheduler::SchedulerConfig;
+use store_api::path_utils::WAL_DIR;
 
 pub const DEFAULT_OBJECT_STORE_CACHE_SIZE: ReadableSize = ReadableSize::mb(256);
 
+/// Default number of regions opened in parallel on startup.
+pub const DEFAULT_STARTUP_OPEN_REGIONS_CONCURRENCY: usize = 16;
+
 /// Default data home in file storage
 const DEFAULT_DATA_HOME: &str = "/tmp/greptimedb";
 
+/// Default data home of backups in file storage
+const DEFAULT_BACKUP_DATA_HOME: &str = "/tmp/greptimedb/backup";
+
 /// Object storage config
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -86,6 +94,28 @@ impl Default for StorageConfig {
     }
 }
 
+/// Destination of manual backups triggered by the admin API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Whether to enable the backup API.
+    pub enable: bool,
+    /// The directory of backups in file storage.
+    pub data_home: String,
+    #[serde(flatten)]
+    pub store: ObjectStoreConfig,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            data_home: DEFAULT_BACKUP_DATA_HOME.to_string(),
+            store: ObjectStoreConfig::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Default, Deserialize, Eq, PartialEq)]
 #[serde(default)]
 pub struct FileConfig {}
@@ -216,6 +246,103 @@ impl Default for ObjectStoreConfig {
     }
 }
 
+impl ObjectStoreConfig {
+    /// Returns the local read cache config, `None` for the file storage which isn't
+    /// cached.
+    pub fn cache(&self) -> Option<&ObjectStorageCacheConfig> {
+        match self {
+            ObjectStoreConfig::File(_) => None,
+            ObjectStoreConfig::S3(s3_config) => Some(&s3_config.cache),
+            ObjectStoreConfig::Oss(oss_config) => Some(&oss_config.cache),
+            ObjectStoreConfig::Azblob(azblob_config) => Some(&azblob_config.cache),
+            ObjectStoreConfig::Gcs(gcs_config) => Some(&gcs_config.cache),
+        }
+    }
+
+    /// Mutable version of [ObjectStoreConfig::cache].
+    pub fn cache_mut(&mut self) -> Option<&mut ObjectStorageCacheConfig> {
+        match self {
+            ObjectStoreConfig::File(_) => None,
+            ObjectStoreConfig::S3(s3_config) => Some(&mut s3_config.cache),
+            ObjectStoreConfig::Oss(oss_config) => Some(&mut oss_config.cache),
+            ObjectStoreConfig::Azblob(azblob_config) => Some(&mut azblob_config.cache),
+            ObjectStoreConfig::Gcs(gcs_config) => Some(&mut gcs_config.cache),
+        }
+    }
+
+    /// Returns the name of the storage, as its `type`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObjectStoreConfig::File(_) => "File",
+            ObjectStoreConfig::S3(_) => "S3",
+            ObjectStoreConfig::Oss(_) => "Oss",
+            ObjectStoreConfig::Azblob(_) => "Azblob",
+            ObjectStoreConfig::Gcs(_) => "Gcs",
+        }
+    }
+
+    /// Returns the keys the object storage can't be built without but left empty.
+    ///
+    /// The credentials are optional, the storage clients load them from the environment
+    /// if unset, but a key pair is set either both or none.
+    pub fn missing_keys(&self) -> Vec<&'static str> {
+        let (required, key_pair) = match self {
+            ObjectStoreConfig::File(_) => return vec![],
+            ObjectStoreConfig::S3(s3_config) => (
+                vec![("bucket", s3_config.bucket.as_str())],
+                Some([
+                    ("access_key_id", s3_config.access_key_id.expose_secret()),
+                    (
+                        "secret_access_key",
+                        s3_config.secret_access_key.expose_secret(),
+                    ),
+                ]),
+            ),
+            ObjectStoreConfig::Oss(oss_config) => (
+                vec![
+                    ("bucket", oss_config.bucket.as_str()),
+                    ("endpoint", oss_config.endpoint.as_str()),
+                ],
+                Some([
+                    ("access_key_id", oss_config.access_key_id.expose_secret()),
+                    (
+                        "access_key_secret",
+                        oss_config.access_key_secret.expose_secret(),
+                    ),
+                ]),
+            ),
+            ObjectStoreConfig::Azblob(azblob_config) => (
+                vec![
+                    ("container", azblob_config.container.as_str()),
+                    ("account_name", azblob_config.account_name.expose_secret()),
+                    ("endpoint", azblob_config.endpoint.as_str()),
+                ],
+                None,
+            ),
+            ObjectStoreConfig::Gcs(gcs_config) => {
+                (vec![("bucket", gcs_config.bucket.as_str())], None)
+            }
+        };
+
+        let mut missing: Vec<_> = required
+            .into_iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(key, _)| key)
+            .collect();
+        if let Some(key_pair) = key_pair {
+            if key_pair.iter().any(|(_, value)| !value.is_empty()) {
+                missing.extend(
+                    key_pair
+                        .iter()
+                        .filter(|(_, value)| value.is_empty())
+                        .map(|(key, _)| *key),
+                );
+            }
+        }
+        missing
+    }
+}
+
 /// Options for region manifest
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(default)]
@@ -325,6 +452,9 @@ pub struct DatanodeOptions {
     pub mode: Mode,
     pub node_id: Option<u64>,
     pub require_lease_before_startup: bool,
+    /// Max number of regions opened in parallel on startup. A higher value speeds up
+    /// cold start at the cost of more I/O and memory while replaying the WAL.
+    pub startup_open_regions_concurrency: usize,
     pub rpc_addr: String,
     pub rpc_hostname: Option<String>,
     pub rpc_runtime_size: usize,
@@ -337,10 +467,16 @@ pub struct DatanodeOptions {
     pub meta_client: Option<MetaClientOptions>,
     pub wal: WalConfig,
     pub storage: StorageConfig,
+    pub backup: BackupConfig,
     /// Options for different store engines.
     pub region_engine: Vec<RegionEngineConfig>,
     pub logging: LoggingOptions,
     pub enable_telemetry: bool,
+    /// Whether to sample host cpu, memory and data home disk usage as metrics.
+    pub collect_os_metrics: bool,
+    /// Whether to start even if the data home was written by a data format this
+    /// binary doesn't support.
+    pub allow_version_mismatch: bool,
 }
 
 impl Default for DatanodeOptions {
@@ -349,6 +485,7 @@ impl Default for DatanodeOptions {
             mode: Mode::Standalone,
             node_id: None,
             require_lease_before_startup: false,
+            startup_open_regions_concurrency: DEFAULT_STARTUP_OPEN_REGIONS_CONCURRENCY,
             rpc_addr: "127.0.0.1:3001".to_string(),
             rpc_hostname: None,
             rpc_runtime_size: 8,
@@ -358,6 +495,7 @@ impl Default for DatanodeOptions {
             meta_client: None,
             wal: WalConfig::default(),
             storage: StorageConfig::default(),
+            backup: BackupConfig::default(),
             region_engine: vec![
                 RegionEngineConfig::Mito(MitoConfig::default()),
                 RegionEngineConfig::File(FileEngineConfig::default()),
@@ -365,6 +503,8 @@ impl Default for DatanodeOptions {
             logging: LoggingOptions::default(),
             heartbeat: HeartbeatOptions::datanode_default(),
             enable_telemetry: true,
+            collect_os_metrics: false,
+            allow_version_mismatch: false,
         }
     }
 }
@@ -377,6 +517,14 @@ impl DatanodeOptions {
     pub fn to_toml_string(&self) -> String {
         toml::to_string(&self).unwrap()
     }
+
+    /// Returns the directory of the raft-engine WAL, under the data home unless set.
+    pub fn wal_dir(&self) -> String {
+        match &self.wal.dir {
+            Some(dir) => dir.clone(),
+            None => format!("{}{WAL_DIR}", normalize_dir(&self.storage.data_home)),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -389,8 +537,6 @@ pub enum RegionEngineConfig {
 
 #[cfg(test)]
 mod tests {
-    use secrecy::ExposeSecret;
-
     use super::*;
 
     #[test]
@@ -420,4 +566,52 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_missing_keys() {
+        assert!(ObjectStoreConfig::default().missing_keys().is_empty());
+
+        let s3 = |toml_str: &str| {
+            toml::from_str::<StorageConfig>(&format!("type = \"S3\"\n{toml_str}"))
+                .unwrap()
+                .store
+        };
+        assert_eq!(vec!["bucket"], s3("").missing_keys());
+        // The credentials are loaded from the environment.
+        assert!(s3(r#"bucket = "greptimedb""#).missing_keys().is_empty());
+        assert_eq!(
+            vec!["secret_access_key"],
+            s3("bucket = \"greptimedb\"\naccess_key_id = \"access_key_id\"").missing_keys()
+        );
+
+        let azblob: StorageConfig = toml::from_str(r#"type = "Azblob""#).unwrap();
+        assert_eq!(
+            vec!["container", "account_name", "endpoint"],
+            azblob.store.missing_keys()
+        );
+    }
+
+    #[test]
+    fn test_backup_config() {
+        let opts: DatanodeOptions = toml::from_str("").unwrap();
+        assert!(!opts.backup.enable);
+        assert!(matches!(opts.backup.store, ObjectStoreConfig::File(_)));
+
+        let toml_str = r#"
+            [backup]
+            enable = true
+            type = "S3"
+            bucket = "backup_bucket"
+            root = "greptimedb"
+        "#;
+        let opts: DatanodeOptions = toml::from_str(toml_str).unwrap();
+        assert!(opts.backup.enable);
+        match opts.backup.store {
+            ObjectStoreConfig::S3(cfg) => {
+                assert_eq!("backup_bucket", cfg.bucket);
+                assert_eq!("greptimedb", cfg.root);
+            }
+            _ => unreachable!(),
+        }
+    }
 }
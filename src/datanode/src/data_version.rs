@@ -0,0 +1,205 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Version of the data format in the data home, checked on startup so a binary never
+//! opens the data of a format it doesn't support.
+//!
+//! A data home without the version file is written by a version before the check, and
+//! is assumed to be compatible.
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use common_telemetry::{info, warn};
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, ResultExt};
+use tokio::fs;
+
+use crate::error::{
+    DataVersionFileSnafu, EncodeJsonSnafu, IncompatibleDataVersionSnafu, ParseDataVersionSnafu,
+    Result,
+};
+
+/// Name of the version file in the data home.
+const DATA_VERSION_FILE: &str = "DATA_VERSION";
+
+/// Data format written by this binary. Bump it with the changes older binaries can't
+/// read.
+const CURRENT_FORMAT_VERSION: u32 = 1;
+
+/// Data formats this binary reads.
+const SUPPORTED_FORMAT_VERSIONS: RangeInclusive<u32> = 1..=CURRENT_FORMAT_VERSION;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DataVersion {
+    format_version: u32,
+    /// Version of the binary writing the file.
+    written_by: String,
+}
+
+impl DataVersion {
+    fn current() -> Self {
+        Self {
+            format_version: CURRENT_FORMAT_VERSION,
+            written_by: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Checks the version of the data in `data_home`, then records the current version. A
+/// mismatched version only logs a warning if `allow_mismatch`, and is left as is.
+pub(crate) async fn check_data_version(data_home: &str, allow_mismatch: bool) -> Result<()> {
+    check_versions(
+        data_home,
+        DataVersion::current(),
+        SUPPORTED_FORMAT_VERSIONS,
+        allow_mismatch,
+    )
+    .await
+}
+
+async fn check_versions(
+    data_home: &str,
+    current: DataVersion,
+    supported: RangeInclusive<u32>,
+    allow_mismatch: bool,
+) -> Result<()> {
+    let path = Path::new(data_home).join(DATA_VERSION_FILE);
+    let path_str = path.to_string_lossy().to_string();
+
+    if fs::try_exists(&path)
+        .await
+        .context(DataVersionFileSnafu { path: &path_str })?
+    {
+        let content = fs::read(&path)
+            .await
+            .context(DataVersionFileSnafu { path: &path_str })?;
+        let version: DataVersion =
+            serde_json::from_slice(&content).context(ParseDataVersionSnafu { path: &path_str })?;
+        if version == current {
+            return Ok(());
+        }
+
+        if !supported.contains(&version.format_version) {
+            ensure!(
+                allow_mismatch,
+                IncompatibleDataVersionSnafu {
+                    data_home,
+                    written_by: version.written_by,
+                    format_version: version.format_version,
+                    min_format_version: *supported.start(),
+                    max_format_version: *supported.end(),
+                }
+            );
+            warn!(
+                "Data home {} was written by version {} (data format {}), this binary supports data format {}-{}, starting as --allow-version-mismatch is set",
+                data_home,
+                version.written_by,
+                version.format_version,
+                supported.start(),
+                supported.end()
+            );
+            return Ok(());
+        }
+        info!(
+            "Upgrading data version of {} from {:?} to {:?}",
+            data_home, version, current
+        );
+    } else {
+        info!(
+            "Data home {} has no data version, writing {:?}",
+            data_home, current
+        );
+    }
+
+    fs::create_dir_all(data_home)
+        .await
+        .context(DataVersionFileSnafu { path: data_home })?;
+    // Renamed in place, so a crash never leaves a partial file.
+    let tmp_path = path.with_extension("tmp");
+    let content = serde_json::to_vec(&current).context(EncodeJsonSnafu)?;
+    fs::write(&tmp_path, content)
+        .await
+        .context(DataVersionFileSnafu { path: &path_str })?;
+    fs::rename(&tmp_path, &path)
+        .await
+        .context(DataVersionFileSnafu { path: &path_str })
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::ext::ErrorExt;
+    use common_error::status_code::StatusCode;
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+
+    fn version(format_version: u32, written_by: &str) -> DataVersion {
+        DataVersion {
+            format_version,
+            written_by: written_by.to_string(),
+        }
+    }
+
+    async fn read_version(data_home: &str) -> DataVersion {
+        let content = fs::read(Path::new(data_home).join(DATA_VERSION_FILE))
+            .await
+            .unwrap();
+        serde_json::from_slice(&content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_data_version() {
+        let dir = create_temp_dir("test_check_data_version");
+        let data_home = dir.path().to_str().unwrap();
+
+        // A data home without the version is assumed to be compatible.
+        check_versions(data_home, version(2, "0.5.0"), 1..=2, false)
+            .await
+            .unwrap();
+        assert_eq!(version(2, "0.5.0"), read_version(data_home).await);
+
+        // A newer binary supporting the format upgrades the version.
+        check_versions(data_home, version(3, "0.6.0"), 2..=3, false)
+            .await
+            .unwrap();
+        assert_eq!(version(3, "0.6.0"), read_version(data_home).await);
+
+        // An older binary can't read the newer format.
+        let err = check_versions(data_home, version(2, "0.5.0"), 1..=2, false)
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        assert!(err.to_string().contains(
+            "was written by version 0.6.0 (data format 3), this binary supports data format 1-2"
+        ));
+
+        // Unless it's allowed, and the version is kept.
+        check_versions(data_home, version(2, "0.5.0"), 1..=2, true)
+            .await
+            .unwrap();
+        assert_eq!(version(3, "0.6.0"), read_version(data_home).await);
+    }
+
+    #[tokio::test]
+    async fn test_data_home_not_exist() {
+        let dir = create_temp_dir("test_data_home_not_exist");
+        let data_home = dir.path().join("data");
+        let data_home = data_home.to_str().unwrap();
+
+        check_data_version(data_home, false).await.unwrap();
+        assert_eq!(DataVersion::current(), read_version(data_home).await);
+        check_data_version(data_home, false).await.unwrap();
+    }
+}
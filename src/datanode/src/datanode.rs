@@ -15,40 +15,44 @@
 //! Datanode implementation.
 
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use catalog::kvbackend::MetaKvBackend;
 use catalog::memory::MemoryCatalogManager;
 use common_base::Plugins;
+use common_config::WalProvider;
 use common_error::ext::BoxedError;
 use common_greptimedb_telemetry::GreptimeDBTelemetryTask;
 use common_meta::key::datanode_table::DatanodeTableManager;
 use common_meta::kv_backend::KvBackendRef;
 pub use common_procedure::options::ProcedureConfig;
-use common_runtime::Runtime;
+use common_runtime::{RepeatedTask, Runtime};
 use common_telemetry::{error, info};
 use file_engine::engine::FileRegionEngine;
 use futures_util::future::try_join_all;
 use futures_util::StreamExt;
+use log_store::kafka::log_store::KafkaLogStore;
 use log_store::raft_engine::log_store::RaftEngineLogStore;
 use meta_client::client::MetaClient;
 use mito2::engine::MitoEngine;
 use object_store::manager::{ObjectStoreManager, ObjectStoreManagerRef};
-use object_store::util::normalize_dir;
 use query::QueryEngineFactory;
 use servers::Mode;
 use snafu::{OptionExt, ResultExt};
 use store_api::logstore::LogStore;
-use store_api::path_utils::{region_dir, WAL_DIR};
+use store_api::path_utils::region_dir;
 use store_api::region_engine::RegionEngineRef;
 use store_api::region_request::{RegionOpenRequest, RegionRequest};
 use store_api::storage::RegionId;
 use tokio::fs;
 use tokio::sync::Notify;
 
+use crate::backup::BackupManager;
 use crate::config::{DatanodeOptions, RegionEngineConfig};
 use crate::error::{
-    CreateDirSnafu, GetMetadataSnafu, MissingKvBackendSnafu, MissingMetaClientSnafu,
+    CreateDirSnafu, Error, GetMetadataSnafu, MissingKvBackendSnafu, MissingMetaClientSnafu,
     MissingMetasrvOptsSnafu, MissingNodeIdSnafu, OpenLogStoreSnafu, Result, RuntimeResourceSnafu,
     ShutdownInstanceSnafu,
 };
@@ -58,11 +62,13 @@ use crate::event_listener::{
 };
 use crate::greptimedb_telemetry::get_greptimedb_telemetry_task;
 use crate::heartbeat::{new_metasrv_client, HeartbeatTask};
+use crate::os_metrics::new_os_metrics_task;
 use crate::region_server::RegionServer;
 use crate::server::Services;
-use crate::store;
+use crate::{data_version, store};
 
-const OPEN_REGION_PARALLELISM: usize = 16;
+/// Number of progress logs emitted while opening regions on startup.
+const OPEN_REGION_PROGRESS_LOGS: usize = 10;
 
 /// Datanode service.
 pub struct Datanode {
@@ -73,6 +79,8 @@ pub struct Datanode {
     region_server: RegionServer,
     greptimedb_telemetry_task: Arc<GreptimeDBTelemetryTask>,
     leases_notifier: Option<Arc<Notify>>,
+    backup_manager: Option<BackupManager>,
+    os_metrics_task: Option<RepeatedTask<Error>>,
     plugins: Plugins,
 }
 
@@ -84,6 +92,11 @@ impl Datanode {
         self.wait_coordinated().await;
 
         let _ = self.greptimedb_telemetry_task.start();
+        if let Some(task) = &self.os_metrics_task {
+            if let Err(e) = task.start(common_runtime::bg_runtime()) {
+                error!(e; "Failed to start OS metrics task");
+            }
+        }
         self.start_services().await
     }
 
@@ -125,6 +138,9 @@ impl Datanode {
         // We must shutdown services first
         self.shutdown_services().await?;
         let _ = self.greptimedb_telemetry_task.stop().await;
+        if let Some(task) = &self.os_metrics_task {
+            let _ = task.stop().await;
+        }
         if let Some(heartbeat_task) = &self.heartbeat_task {
             heartbeat_task
                 .close()
@@ -140,6 +156,11 @@ impl Datanode {
         self.region_server.clone()
     }
 
+    /// Returns the [BackupManager] if the backup API is enabled.
+    pub fn backup_manager(&self) -> Option<BackupManager> {
+        self.backup_manager.clone()
+    }
+
     pub fn plugins(&self) -> Plugins {
         self.plugins.clone()
     }
@@ -204,7 +225,6 @@ impl DatanodeBuilder {
         };
 
         // build and initialize region server
-        let log_store = Self::build_log_store(&self.opts).await?;
         let (region_event_listener, region_event_receiver) = match mode {
             Mode::Distributed => {
                 let (tx, rx) = new_region_server_event_channel();
@@ -216,13 +236,34 @@ impl DatanodeBuilder {
             ),
         };
 
-        let region_server = Self::new_region_server(
-            &self.opts,
-            self.plugins.clone(),
-            log_store,
-            region_event_listener,
+        data_version::check_data_version(
+            &self.opts.storage.data_home,
+            self.opts.allow_version_mismatch,
         )
         .await?;
+
+        let (region_server, mito_engine) = match self.opts.wal.provider {
+            WalProvider::RaftEngine => {
+                let log_store = Self::build_raft_engine_log_store(&self.opts).await?;
+                Self::new_region_server(
+                    &self.opts,
+                    self.plugins.clone(),
+                    log_store,
+                    region_event_listener,
+                )
+                .await?
+            }
+            WalProvider::Kafka => {
+                let log_store = Self::build_kafka_log_store(&self.opts).await?;
+                Self::new_region_server(
+                    &self.opts,
+                    self.plugins.clone(),
+                    log_store,
+                    region_event_listener,
+                )
+                .await?
+            }
+        };
         self.initialize_region_server(&region_server, kv_backend, matches!(mode, Mode::Standalone))
             .await?;
 
@@ -237,8 +278,23 @@ impl DatanodeBuilder {
             Mode::Standalone => None,
         };
 
+        let backup_manager = match mito_engine {
+            Some(mito_engine) if self.opts.backup.enable => {
+                let object_store = store::new_backup_object_store(&self.opts.backup).await?;
+                Some(BackupManager::new(
+                    region_server.clone(),
+                    mito_engine,
+                    object_store,
+                ))
+            }
+            _ => None,
+        };
+
         let services = match mode {
-            Mode::Distributed => Some(Services::try_new(region_server.clone(), &self.opts).await?),
+            Mode::Distributed => Some(
+                Services::try_new(region_server.clone(), backup_manager.clone(), &self.opts)
+                    .await?,
+            ),
             Mode::Standalone => None,
         };
 
@@ -249,6 +305,11 @@ impl DatanodeBuilder {
         )
         .await;
 
+        let os_metrics_task = self
+            .opts
+            .collect_os_metrics
+            .then(|| new_os_metrics_task(self.opts.storage.data_home.clone()));
+
         let leases_notifier =
             if self.opts.require_lease_before_startup && matches!(mode, Mode::Distributed) {
                 Some(Arc::new(Notify::new()))
@@ -264,6 +325,8 @@ impl DatanodeBuilder {
             greptimedb_telemetry_task,
             region_event_receiver,
             leases_notifier,
+            backup_manager,
+            os_metrics_task,
             plugins: self.plugins.clone(),
         })
     }
@@ -292,13 +355,19 @@ impl DatanodeBuilder {
             }
         }
 
-        info!("going to open {} regions", regions.len());
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(OPEN_REGION_PARALLELISM));
+        let total = regions.len();
+        let concurrency = self.opts.startup_open_regions_concurrency.max(1);
+        info!("going to open {total} regions, concurrency: {concurrency}");
+        let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
+        let opened = AtomicUsize::new(0);
+        let progress_step = (total / OPEN_REGION_PROGRESS_LOGS).max(1);
+        let start = Instant::now();
         let mut tasks = vec![];
 
         for (region_id, engine, store_path, options) in regions {
             let region_dir = region_dir(&store_path, region_id);
             let semaphore_moved = semaphore.clone();
+            let opened = &opened;
             tasks.push(async move {
                 let _permit = semaphore_moved.acquire().await;
                 region_server
@@ -318,22 +387,32 @@ impl DatanodeBuilder {
                         );
                     }
                 }
+                let opened = opened.fetch_add(1, Ordering::Relaxed) + 1;
+                if opened % progress_step == 0 && opened < total {
+                    info!(
+                        "opened {opened}/{total} regions, elapsed: {:?}",
+                        start.elapsed()
+                    );
+                }
                 Ok(())
             });
         }
         let _ = try_join_all(tasks).await?;
 
-        info!("region server is initialized");
+        info!(
+            "region server is initialized, opened {total} regions in {:?}",
+            start.elapsed()
+        );
 
         Ok(())
     }
 
-    async fn new_region_server(
+    async fn new_region_server<S: LogStore>(
         opts: &DatanodeOptions,
         plugins: Plugins,
-        log_store: Arc<RaftEngineLogStore>,
+        log_store: Arc<S>,
         event_listener: RegionServerEventListenerRef,
-    ) -> Result<RegionServer> {
+    ) -> Result<(RegionServer, Option<MitoEngine>)> {
         let query_engine_factory = QueryEngineFactory::new_with_plugins(
             // query engine in datanode only executes plan with resolved table source.
             MemoryCatalogManager::with_default_setup(),
@@ -359,24 +438,22 @@ impl DatanodeBuilder {
             "default", // TODO: use a name which is set in the configuration when #919 is done.
             object_store,
         );
-        let engines =
+        let (engines, mito_engine) =
             Self::build_store_engines(opts, log_store, Arc::new(object_store_manager)).await?;
         for engine in engines {
             region_server.register_engine(engine);
         }
 
-        Ok(region_server)
+        Ok((region_server, mito_engine))
     }
 
     // internal utils
 
     /// Build [RaftEngineLogStore]
-    async fn build_log_store(opts: &DatanodeOptions) -> Result<Arc<RaftEngineLogStore>> {
-        let data_home = normalize_dir(&opts.storage.data_home);
-        let wal_dir = match &opts.wal.dir {
-            Some(dir) => dir.clone(),
-            None => format!("{}{WAL_DIR}", data_home),
-        };
+    async fn build_raft_engine_log_store(
+        opts: &DatanodeOptions,
+    ) -> Result<Arc<RaftEngineLogStore>> {
+        let wal_dir = opts.wal_dir();
         let wal_config = opts.wal.clone();
 
         // create WAL directory
@@ -394,16 +471,28 @@ impl DatanodeBuilder {
         Ok(Arc::new(logstore))
     }
 
-    /// Build [RegionEngineRef] from `store_engine` section in `opts`
+    /// Build [KafkaLogStore], which waits for the Kafka brokers to be available.
+    async fn build_kafka_log_store(opts: &DatanodeOptions) -> Result<Arc<KafkaLogStore>> {
+        info!("Creating Kafka logstore with config: {:?}", opts.wal.kafka);
+        let logstore = KafkaLogStore::try_new(opts.wal.clone())
+            .await
+            .map_err(Box::new)
+            .context(OpenLogStoreSnafu)?;
+        Ok(Arc::new(logstore))
+    }
+
+    /// Build [RegionEngineRef] from `store_engine` section in `opts`, also returns
+    /// the [MitoEngine] if it is configured.
     async fn build_store_engines<S>(
         opts: &DatanodeOptions,
         log_store: Arc<S>,
         object_store_manager: ObjectStoreManagerRef,
-    ) -> Result<Vec<RegionEngineRef>>
+    ) -> Result<(Vec<RegionEngineRef>, Option<MitoEngine>)>
     where
         S: LogStore,
     {
         let mut engines = vec![];
+        let mut mito_engine = None;
         for engine in &opts.region_engine {
             match engine {
                 RegionEngineConfig::Mito(config) => {
@@ -412,6 +501,7 @@ impl DatanodeBuilder {
                         log_store.clone(),
                         object_store_manager.clone(),
                     );
+                    mito_engine = Some(engine.clone());
                     engines.push(Arc::new(engine) as _);
                 }
                 RegionEngineConfig::File(config) => {
@@ -423,7 +513,7 @@ impl DatanodeBuilder {
                 }
             }
         }
-        Ok(engines)
+        Ok((engines, mito_engine))
     }
 }
 
@@ -430,6 +430,57 @@ pub enum Error {
         location: Location,
         source: BoxedError,
     },
+
+    #[snafu(display("Failed to back up region {}", region_id))]
+    BackupRegion {
+        region_id: RegionId,
+        location: Location,
+        source: mito2::error::Error,
+    },
+
+    #[snafu(display("Failed to write backup metadata to {}", path))]
+    WriteBackupMeta {
+        path: String,
+        #[snafu(source)]
+        error: object_store::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Backup {} is still running", id))]
+    BackupInProgress { id: String, location: Location },
+
+    #[snafu(display("Failed to access data version file {}", path))]
+    DataVersionFile {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to parse data version file {}", path))]
+    ParseDataVersion {
+        path: String,
+        #[snafu(source)]
+        error: JsonError,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Data home {} was written by version {} (data format {}), this binary supports data format {}-{}; run the migration, or start with --allow-version-mismatch to skip the check",
+        data_home,
+        written_by,
+        format_version,
+        min_format_version,
+        max_format_version
+    ))]
+    IncompatibleDataVersion {
+        data_home: String,
+        written_by: String,
+        format_version: u32,
+        min_format_version: u32,
+        max_format_version: u32,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -483,7 +534,8 @@ impl ErrorExt for Error {
             | PrepareImmutableTable { .. }
             | ColumnDataType { .. }
             | MissingKvBackend { .. }
-            | MissingMetaClient { .. } => StatusCode::InvalidArguments,
+            | MissingMetaClient { .. }
+            | IncompatibleDataVersion { .. } => StatusCode::InvalidArguments,
 
             EncodeJson { .. } | PayloadNotExist { .. } | Unexpected { .. } => {
                 StatusCode::Unexpected
@@ -501,7 +553,9 @@ impl ErrorExt for Error {
             | JoinTask { .. }
             | RegionEngineNotFound { .. }
             | UnsupportedOutput { .. }
-            | GetRegionMetadata { .. } => StatusCode::Internal,
+            | GetRegionMetadata { .. }
+            | DataVersionFile { .. }
+            | ParseDataVersion { .. } => StatusCode::Internal,
 
             RegionNotFound { .. } => StatusCode::RegionNotFound,
 
@@ -509,7 +563,7 @@ impl ErrorExt for Error {
             | ShutdownServer { source, .. }
             | WaitForGrpcServing { source, .. } => source.status_code(),
 
-            InitBackend { .. } => StatusCode::StorageUnavailable,
+            InitBackend { .. } | WriteBackupMeta { .. } => StatusCode::StorageUnavailable,
 
             OpenLogStore { source, .. } => source.status_code(),
             RuntimeResource { .. } => StatusCode::RuntimeResourcesExhausted,
@@ -524,6 +578,8 @@ impl ErrorExt for Error {
             WaitProcedure { source, .. } => source.status_code(),
             HandleRegionRequest { source, .. } => source.status_code(),
             StopRegionEngine { source, .. } => source.status_code(),
+            BackupRegion { source, .. } => source.status_code(),
+            BackupInProgress { .. } => StatusCode::RateLimited,
         }
     }
 
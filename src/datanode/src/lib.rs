@@ -16,13 +16,16 @@
 #![feature(trait_upcasting)]
 
 pub mod alive_keeper;
+pub mod backup;
 pub mod config;
+mod data_version;
 pub mod datanode;
 pub mod error;
 pub mod event_listener;
 mod greptimedb_telemetry;
 pub mod heartbeat;
 pub mod metrics;
+mod os_metrics;
 pub mod region_server;
 pub mod server;
 mod store;
@@ -26,4 +26,28 @@ lazy_static! {
         &[REGION_REQUEST_TYPE]
     )
     .unwrap();
+    /// Host CPU usage ratio between two samples, in [0, 1].
+    pub static ref OS_HOST_CPU_USAGE: Gauge =
+        register_gauge!("os_host_cpu_usage", "host cpu usage ratio").unwrap();
+    /// Total memory of the host.
+    pub static ref OS_HOST_MEMORY_TOTAL_BYTES: IntGauge =
+        register_int_gauge!("os_host_memory_total_bytes", "host total memory in bytes").unwrap();
+    /// Memory of the host available for starting new applications.
+    pub static ref OS_HOST_MEMORY_AVAILABLE_BYTES: IntGauge = register_int_gauge!(
+        "os_host_memory_available_bytes",
+        "host available memory in bytes"
+    )
+    .unwrap();
+    /// Total size of the filesystem of the data home.
+    pub static ref OS_DATA_HOME_DISK_TOTAL_BYTES: IntGauge = register_int_gauge!(
+        "os_data_home_disk_total_bytes",
+        "total bytes of the data home filesystem"
+    )
+    .unwrap();
+    /// Available size of the filesystem of the data home.
+    pub static ref OS_DATA_HOME_DISK_AVAILABLE_BYTES: IntGauge = register_int_gauge!(
+        "os_data_home_disk_available_bytes",
+        "available bytes of the data home filesystem"
+    )
+    .unwrap();
 }
@@ -0,0 +1,270 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic sampling of host and disk metrics, for deployments without a node exporter.
+//!
+//! Process metrics (cpu, resident memory, open fds) are exported by the process
+//! collector of prometheus on Linux, so only host wide metrics are sampled here.
+//! A metric that is unavailable on the platform is left unset.
+
+use std::time::Duration;
+
+use common_runtime::{RepeatedTask, TaskFunction};
+use common_telemetry::debug;
+
+use crate::error::{Error, Result};
+use crate::metrics::{
+    OS_DATA_HOME_DISK_AVAILABLE_BYTES, OS_DATA_HOME_DISK_TOTAL_BYTES, OS_HOST_CPU_USAGE,
+    OS_HOST_MEMORY_AVAILABLE_BYTES, OS_HOST_MEMORY_TOTAL_BYTES,
+};
+
+/// Interval to sample the metrics.
+const COLLECT_INTERVAL: Duration = Duration::from_secs(15);
+
+pub(crate) fn new_os_metrics_task(data_home: String) -> RepeatedTask<Error> {
+    RepeatedTask::new(
+        COLLECT_INTERVAL,
+        Box::new(OsMetricsCollector {
+            data_home,
+            last_cpu_times: None,
+        }),
+    )
+    .with_initial_delay(Some(Duration::ZERO))
+}
+
+struct OsMetricsCollector {
+    data_home: String,
+    last_cpu_times: Option<CpuTimes>,
+}
+
+#[async_trait::async_trait]
+impl TaskFunction<Error> for OsMetricsCollector {
+    async fn call(&mut self) -> Result<()> {
+        if let Some(cpu_times) = read_cpu_times() {
+            if let Some(usage) = self
+                .last_cpu_times
+                .and_then(|last| cpu_times.usage_since(&last))
+            {
+                OS_HOST_CPU_USAGE.set(usage);
+            }
+            self.last_cpu_times = Some(cpu_times);
+        }
+
+        if let Some(memory) = read_memory() {
+            OS_HOST_MEMORY_TOTAL_BYTES.set(memory.total as i64);
+            OS_HOST_MEMORY_AVAILABLE_BYTES.set(memory.available as i64);
+        }
+
+        if let Some(disk) = read_disk(&self.data_home) {
+            OS_DATA_HOME_DISK_TOTAL_BYTES.set(disk.total as i64);
+            OS_DATA_HOME_DISK_AVAILABLE_BYTES.set(disk.available as i64);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "OsMetricsCollector"
+    }
+}
+
+/// Accumulated cpu time of all cpus of the host, in clock ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuTimes {
+    total: u64,
+    idle: u64,
+}
+
+impl CpuTimes {
+    /// Returns the ratio of busy time since the `last` sample.
+    fn usage_since(&self, last: &CpuTimes) -> Option<f64> {
+        let total = self.total.checked_sub(last.total)?;
+        let idle = self.idle.checked_sub(last.idle)?;
+        if total == 0 {
+            return None;
+        }
+        Some(total.saturating_sub(idle) as f64 / total as f64)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct MemoryInfo {
+    total: u64,
+    available: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct DiskInfo {
+    total: u64,
+    available: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_times() -> Option<CpuTimes> {
+    read_proc_file("/proc/stat").and_then(|content| parse_cpu_times(&content))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_cpu_times() -> Option<CpuTimes> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_memory() -> Option<MemoryInfo> {
+    read_proc_file("/proc/meminfo").and_then(|content| parse_memory(&content))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_memory() -> Option<MemoryInfo> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc_file(path: &str) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) => {
+            debug!("Failed to read {path}: {e}");
+            None
+        }
+    }
+}
+
+/// Parses the aggregated `cpu` line of `/proc/stat`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpu_times(content: &str) -> Option<CpuTimes> {
+    let line = content.lines().find(|line| line.starts_with("cpu "))?;
+    let values = line
+        .split_whitespace()
+        .skip(1)
+        .map(|v| v.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    // user, nice, system, idle, iowait, irq, softirq, steal. Guest time is already
+    // counted in user time.
+    if values.len() < 4 {
+        return None;
+    }
+    let total = values.iter().take(8).sum();
+    let idle = values[3] + values.get(4).copied().unwrap_or(0);
+    Some(CpuTimes { total, idle })
+}
+
+/// Parses `MemTotal` and `MemAvailable` of `/proc/meminfo`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_memory(content: &str) -> Option<MemoryInfo> {
+    let mut total = None;
+    let mut available = None;
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let value = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key, value.parse::<u64>().ok()),
+            _ => continue,
+        };
+        match value {
+            ("MemTotal:", Some(kb)) => total = Some(kb * 1024),
+            ("MemAvailable:", Some(kb)) => available = Some(kb * 1024),
+            _ => {}
+        }
+    }
+    Some(MemoryInfo {
+        total: total?,
+        available: available?,
+    })
+}
+
+#[cfg(unix)]
+fn read_disk(path: &str) -> Option<DiskInfo> {
+    let c_path = std::ffi::CString::new(path).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // Safety: `c_path` is a valid C string and `stat` is only read if the call succeeds.
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    if ret != 0 {
+        debug!(
+            "Failed to statvfs {path}: {}",
+            std::io::Error::last_os_error()
+        );
+        return None;
+    }
+    // Safety: the call succeeded so `stat` is initialized.
+    let stat = unsafe { stat.assume_init() };
+    let fragment_size = stat.f_frsize as u64;
+    Some(DiskInfo {
+        total: stat.f_blocks as u64 * fragment_size,
+        available: stat.f_bavail as u64 * fragment_size,
+    })
+}
+
+#[cfg(not(unix))]
+fn read_disk(_path: &str) -> Option<DiskInfo> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_times() {
+        let content = "\
+cpu  100 10 50 800 40 0 0 0 0 0
+cpu0 50 5 25 400 20 0 0 0 0 0
+intr 12345";
+        let times = parse_cpu_times(content).unwrap();
+        assert_eq!(
+            CpuTimes {
+                total: 1000,
+                idle: 840,
+            },
+            times
+        );
+
+        let next = CpuTimes {
+            total: 1100,
+            idle: 890,
+        };
+        assert_eq!(Some(0.5), next.usage_since(&times));
+        assert_eq!(None, times.usage_since(&times));
+        assert_eq!(None, times.usage_since(&next));
+
+        assert!(parse_cpu_times("intr 12345").is_none());
+        assert!(parse_cpu_times("cpu  1 2 x 4").is_none());
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        let content = "\
+MemTotal:       16384 kB
+MemFree:         1024 kB
+MemAvailable:    8192 kB";
+        assert_eq!(
+            Some(MemoryInfo {
+                total: 16384 * 1024,
+                available: 8192 * 1024,
+            }),
+            parse_memory(content)
+        );
+        assert!(parse_memory("MemTotal:       16384 kB").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_disk() {
+        let dir = common_test_util::temp_dir::create_temp_dir("test_read_disk");
+        let disk = read_disk(dir.path().to_str().unwrap()).unwrap();
+        assert!(disk.total > 0);
+        assert!(disk.available <= disk.total);
+
+        assert!(read_disk("/path/not/exists").is_none());
+    }
+}
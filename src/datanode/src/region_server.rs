@@ -38,7 +38,8 @@ use datafusion::execution::context::SessionState;
 use datafusion_common::DataFusionError;
 use datafusion_expr::{Expr as DfExpr, TableProviderFilterPushDown, TableType};
 use datatypes::arrow::datatypes::SchemaRef;
-use futures_util::future::try_join_all;
+use futures_util::future::{join_all, try_join_all};
+use mito2::engine::MITO_ENGINE_NAME;
 use prost::Message;
 use query::QueryEngineRef;
 use servers::error::{self as servers_error, ExecuteGrpcRequestSnafu, Result as ServerResult};
@@ -48,7 +49,9 @@ use session::context::{QueryContextBuilder, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
 use store_api::metadata::RegionMetadataRef;
 use store_api::region_engine::{RegionEngineRef, RegionRole};
-use store_api::region_request::{RegionCloseRequest, RegionRequest};
+use store_api::region_request::{
+    RegionCloseRequest, RegionFlushRequest, RegionRequest, WriteAckLevel,
+};
 use store_api::storage::{RegionId, ScanRequest};
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use table::table::scan::StreamScanAdapter;
@@ -141,6 +144,26 @@ impl RegionServer {
         }
     }
 
+    /// Flushes the writable regions of the mito engine, so they don't replay the WAL
+    /// on the next start. A region failing to flush is logged and skipped.
+    pub async fn flush_regions(&self) {
+        let regions: Vec<_> = self
+            .opened_regions()
+            .into_iter()
+            .filter(|stat| stat.engine == MITO_ENGINE_NAME && stat.role.writable())
+            .map(|stat| stat.region_id)
+            .collect();
+        info!("Flushing {} regions", regions.len());
+
+        let _ = join_all(regions.into_iter().map(|region_id| async move {
+            let request = RegionRequest::Flush(RegionFlushRequest::default());
+            if let Err(e) = self.handle_request(region_id, request).await {
+                warn!(e; "Failed to flush region {region_id}");
+            }
+        }))
+        .await;
+    }
+
     /// Stop the region server.
     pub async fn stop(&self) -> Result<()> {
         self.inner.stop().await
@@ -149,12 +172,17 @@ impl RegionServer {
 
 #[async_trait]
 impl RegionServerHandler for RegionServer {
-    async fn handle(&self, request: region_request::Body) -> ServerResult<RegionResponse> {
+    async fn handle(
+        &self,
+        request: region_request::Body,
+        ack_level: WriteAckLevel,
+    ) -> ServerResult<RegionResponse> {
         let requests = RegionRequest::try_from_request_body(request)
             .context(BuildRegionRequestsSnafu)
             .map_err(BoxedError::new)
             .context(ExecuteGrpcRequestSnafu)?;
-        let join_tasks = requests.into_iter().map(|(region_id, req)| {
+        let join_tasks = requests.into_iter().map(|(region_id, mut req)| {
+            req.set_write_ack_level(ack_level);
             let self_to_move = self.clone();
             async move { self_to_move.handle_request(region_id, req).await }
         });
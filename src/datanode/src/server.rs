@@ -22,6 +22,7 @@ use servers::metrics_handler::MetricsHandler;
 use servers::server::Server;
 use snafu::ResultExt;
 
+use crate::backup::BackupManager;
 use crate::config::DatanodeOptions;
 use crate::error::{
     ParseAddrSnafu, Result, ShutdownServerSnafu, StartServerSnafu, WaitForGrpcServingSnafu,
@@ -35,15 +36,28 @@ pub struct Services {
 }
 
 impl Services {
-    pub async fn try_new(region_server: RegionServer, opts: &DatanodeOptions) -> Result<Self> {
+    pub async fn try_new(
+        region_server: RegionServer,
+        backup_manager: Option<BackupManager>,
+        opts: &DatanodeOptions,
+    ) -> Result<Self> {
         let flight_handler = Some(Arc::new(region_server.clone()) as _);
         let region_server_handler = Some(Arc::new(region_server.clone()) as _);
         let runtime = region_server.runtime();
         let grpc_config = GrpcServerConfig {
             max_recv_message_size: opts.rpc_max_recv_message_size.as_bytes() as usize,
             max_send_message_size: opts.rpc_max_send_message_size.as_bytes() as usize,
+            ..Default::default()
         };
 
+        let mut http_server_builder = HttpServerBuilder::new(opts.http.clone());
+        let _ = http_server_builder
+            .with_metrics_handler(MetricsHandler)
+            .with_greptime_config_options(opts.to_toml_string());
+        if let Some(backup_manager) = backup_manager {
+            let _ = http_server_builder.with_backup_handler(Arc::new(backup_manager));
+        }
+
         Ok(Self {
             grpc_server: GrpcServer::new(
                 Some(grpc_config),
@@ -54,10 +68,7 @@ impl Services {
                 None,
                 runtime,
             ),
-            http_server: HttpServerBuilder::new(opts.http.clone())
-                .with_metrics_handler(MetricsHandler)
-                .with_greptime_config_options(opts.to_toml_string())
-                .build(),
+            http_server: http_server_builder.build(),
         })
     }
 
@@ -32,22 +32,13 @@ use object_store::util::normalize_dir;
 use object_store::{util, HttpClient, ObjectStore, ObjectStoreBuilder};
 use snafu::prelude::*;
 
-use crate::config::{DatanodeOptions, ObjectStoreConfig, DEFAULT_OBJECT_STORE_CACHE_SIZE};
+use crate::config::{
+    BackupConfig, DatanodeOptions, ObjectStoreConfig, DEFAULT_OBJECT_STORE_CACHE_SIZE,
+};
 use crate::error::{self, Result};
 
 pub(crate) async fn new_object_store(opts: &DatanodeOptions) -> Result<ObjectStore> {
-    let data_home = normalize_dir(&opts.storage.data_home);
-    let object_store = match &opts.storage.store {
-        ObjectStoreConfig::File(file_config) => {
-            fs::new_fs_object_store(&data_home, file_config).await
-        }
-        ObjectStoreConfig::S3(s3_config) => s3::new_s3_object_store(s3_config).await,
-        ObjectStoreConfig::Oss(oss_config) => oss::new_oss_object_store(oss_config).await,
-        ObjectStoreConfig::Azblob(azblob_config) => {
-            azblob::new_azblob_object_store(azblob_config).await
-        }
-        ObjectStoreConfig::Gcs(gcs_config) => gcs::new_gcs_object_store(gcs_config).await,
-    }?;
+    let object_store = new_raw_object_store(&opts.storage.store, &opts.storage.data_home).await?;
 
     // Enable retry layer and cache layer for non-fs object storages
     let object_store = if !matches!(opts.storage.store, ObjectStoreConfig::File(..)) {
@@ -85,44 +76,54 @@ pub(crate) async fn new_object_store(opts: &DatanodeOptions) -> Result<ObjectSto
     }
 }
 
-async fn create_object_store_with_cache(
-    object_store: ObjectStore,
+/// Builds the object store of the backup destination. Cache and metrics are
+/// not needed as backups are only written.
+pub(crate) async fn new_backup_object_store(config: &BackupConfig) -> Result<ObjectStore> {
+    let object_store = new_raw_object_store(&config.store, &config.data_home).await?;
+
+    let object_store = if !matches!(config.store, ObjectStoreConfig::File(..)) {
+        object_store.layer(RetryLayer::new().with_jitter())
+    } else {
+        object_store
+    };
+
+    Ok(object_store.layer(
+        LoggingLayer::default()
+            .with_error_level(Some("debug"))
+            .expect("input error level must be valid"),
+    ))
+}
+
+async fn new_raw_object_store(
     store_config: &ObjectStoreConfig,
+    data_home: &str,
 ) -> Result<ObjectStore> {
-    let (cache_path, cache_capacity) = match store_config {
-        ObjectStoreConfig::S3(s3_config) => {
-            let path = s3_config.cache.cache_path.as_ref();
-            let capacity = s3_config
-                .cache
-                .cache_capacity
-                .unwrap_or(DEFAULT_OBJECT_STORE_CACHE_SIZE);
-            (path, capacity)
-        }
-        ObjectStoreConfig::Oss(oss_config) => {
-            let path = oss_config.cache.cache_path.as_ref();
-            let capacity = oss_config
-                .cache
-                .cache_capacity
-                .unwrap_or(DEFAULT_OBJECT_STORE_CACHE_SIZE);
-            (path, capacity)
+    let data_home = normalize_dir(data_home);
+    match store_config {
+        ObjectStoreConfig::File(file_config) => {
+            fs::new_fs_object_store(&data_home, file_config).await
         }
+        ObjectStoreConfig::S3(s3_config) => s3::new_s3_object_store(s3_config).await,
+        ObjectStoreConfig::Oss(oss_config) => oss::new_oss_object_store(oss_config).await,
         ObjectStoreConfig::Azblob(azblob_config) => {
-            let path = azblob_config.cache.cache_path.as_ref();
-            let capacity = azblob_config
-                .cache
-                .cache_capacity
-                .unwrap_or(DEFAULT_OBJECT_STORE_CACHE_SIZE);
-            (path, capacity)
+            azblob::new_azblob_object_store(azblob_config).await
         }
-        ObjectStoreConfig::Gcs(gcs_config) => {
-            let path = gcs_config.cache.cache_path.as_ref();
-            let capacity = gcs_config
-                .cache
+        ObjectStoreConfig::Gcs(gcs_config) => gcs::new_gcs_object_store(gcs_config).await,
+    }
+}
+
+async fn create_object_store_with_cache(
+    object_store: ObjectStore,
+    store_config: &ObjectStoreConfig,
+) -> Result<ObjectStore> {
+    let (cache_path, cache_capacity) = match store_config.cache() {
+        Some(cache) => (
+            cache.cache_path.as_ref(),
+            cache
                 .cache_capacity
-                .unwrap_or(DEFAULT_OBJECT_STORE_CACHE_SIZE);
-            (path, capacity)
-        }
-        _ => (None, ReadableSize(0)),
+                .unwrap_or(DEFAULT_OBJECT_STORE_CACHE_SIZE),
+        ),
+        None => (None, ReadableSize(0)),
     };
 
     if let Some(path) = cache_path {
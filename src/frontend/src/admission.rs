@@ -0,0 +1,248 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admission control bounding the number of the queries executing at the same time,
+//! a coarse protection against overload beyond the parallelism of each query.
+//!
+//! A query holds its permit until its output is consumed, so a streamed result counts
+//! as running until the client reads it all. The statements of a request share one
+//! permit, so a request doesn't wait for the outputs of its own earlier statements.
+//!
+//! Only the queries are limited, the writes and the DDL are not, including the
+//! `INSERT ... SELECT` statements.
+
+use std::sync::Arc;
+
+use common_query::Output;
+use common_recordbatch::RecordBatchStreamAdaptor;
+use futures::StreamExt;
+use sql::statements::statement::Statement;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use crate::error::{Result, ServerBusySnafu};
+use crate::metrics::{METRIC_QUERY_ADMISSION_QUEUED, METRIC_QUERY_ADMISSION_REJECTED};
+use crate::service_config::{QueryAdmissionOptions, QueryOverflowPolicy};
+
+pub(crate) struct QueryAdmission {
+    semaphore: Arc<Semaphore>,
+    opts: QueryAdmissionOptions,
+}
+
+impl QueryAdmission {
+    /// Creates the admission control if `opts` has a concurrency limit.
+    pub(crate) fn from_options(opts: &QueryAdmissionOptions) -> Option<Arc<Self>> {
+        opts.concurrency_limit.map(|limit| {
+            Arc::new(Self {
+                semaphore: Arc::new(Semaphore::new(limit)),
+                opts: opts.clone(),
+            })
+        })
+    }
+
+    /// Admits a query, the statement if it's SQL, queuing or rejecting it beyond the
+    /// limit. Returns `None` if the query bypasses the limit.
+    pub(crate) async fn admit(&self, stmt: Option<&Statement>) -> Result<Option<Permit>> {
+        if let Some(stmt) = stmt {
+            let limited = is_query(stmt) || (is_metadata(stmt) && !self.opts.exempt_metadata);
+            if !limited {
+                return Ok(None);
+            }
+        }
+
+        let permit = match self.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(TryAcquireError::NoPermits) => {
+                if self.opts.overflow == QueryOverflowPolicy::Reject {
+                    METRIC_QUERY_ADMISSION_REJECTED.inc();
+                    return ServerBusySnafu {
+                        limit: self.opts.concurrency_limit.unwrap_or_default(),
+                    }
+                    .fail();
+                }
+                METRIC_QUERY_ADMISSION_QUEUED.inc();
+                self.semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("the semaphore is never closed")
+            }
+            Err(TryAcquireError::Closed) => unreachable!("the semaphore is never closed"),
+        };
+        Ok(Some(Permit(Arc::new(permit))))
+    }
+}
+
+/// A running query counted by the [QueryAdmission], released once all its clones are
+/// dropped.
+#[derive(Clone)]
+pub(crate) struct Permit(Arc<OwnedSemaphorePermit>);
+
+impl Permit {
+    /// Holds the permit until the `output` is consumed.
+    pub(crate) fn hold_until_consumed(self, output: Output) -> Output {
+        match output {
+            Output::Stream(stream) => {
+                let schema = stream.schema();
+                let output_ordering = stream.output_ordering().map(|ordering| ordering.to_vec());
+                let stream = stream.map(move |batch| {
+                    let _permit = &self;
+                    batch
+                });
+                Output::Stream(Box::pin(RecordBatchStreamAdaptor {
+                    schema,
+                    stream,
+                    output_ordering,
+                }))
+            }
+            output => output,
+        }
+    }
+}
+
+/// Whether the statement queries the data.
+fn is_query(stmt: &Statement) -> bool {
+    matches!(
+        stmt,
+        Statement::Query(_) | Statement::Explain(_) | Statement::Tql(_)
+    )
+}
+
+/// Whether the statement only reads the metadata, which is cheap.
+fn is_metadata(stmt: &Statement) -> bool {
+    matches!(
+        stmt,
+        Statement::ShowDatabases(_)
+            | Statement::ShowTables(_)
+            | Statement::ShowCreateTable(_)
+            | Statement::DescribeTable(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::ext::ErrorExt;
+    use common_error::status_code::StatusCode;
+    use sql::dialect::GreptimeDbDialect;
+    use sql::parser::ParserContext;
+
+    use super::*;
+
+    fn parse(sql: &str) -> Statement {
+        ParserContext::create_with_dialect(sql, &GreptimeDbDialect {})
+            .unwrap()
+            .remove(0)
+    }
+
+    fn admission(overflow: QueryOverflowPolicy, exempt_metadata: bool) -> Arc<QueryAdmission> {
+        QueryAdmission::from_options(&QueryAdmissionOptions {
+            concurrency_limit: Some(1),
+            overflow,
+            exempt_metadata,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unlimited() {
+        assert!(QueryAdmission::from_options(&QueryAdmissionOptions::default()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reject() {
+        let admission = admission(QueryOverflowPolicy::Reject, true);
+        let query = parse("SELECT * FROM t");
+        let permit = admission.admit(Some(&query)).await.unwrap();
+        assert!(permit.is_some());
+
+        let err = admission.admit(Some(&query)).await.err().unwrap();
+        assert_eq!(StatusCode::RuntimeResourcesExhausted, err.status_code());
+        assert!(err.status_code().is_retryable());
+        assert!(admission.admit(None).await.is_err());
+        // Metadata statements bypass the limit.
+        assert!(admission
+            .admit(Some(&parse("SHOW TABLES")))
+            .await
+            .unwrap()
+            .is_none());
+
+        drop(permit);
+        assert!(admission.admit(Some(&query)).await.unwrap().is_some());
+
+        let admission = self::admission(QueryOverflowPolicy::Reject, false);
+        let _permit = admission.admit(None).await.unwrap();
+        assert!(admission.admit(Some(&parse("SHOW TABLES"))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_writes_and_ddl_not_limited() {
+        let admission = admission(QueryOverflowPolicy::Reject, false);
+        let _permit = admission.admit(None).await.unwrap();
+        for sql in [
+            "INSERT INTO t VALUES (1)",
+            "DELETE FROM t WHERE ts = 1",
+            "CREATE TABLE t (ts TIMESTAMP TIME INDEX)",
+            "DROP TABLE t",
+        ] {
+            assert!(admission.admit(Some(&parse(sql))).await.unwrap().is_none());
+        }
+        for sql in ["SELECT 1", "EXPLAIN SELECT 1", "TQL EVAL (0, 10, '5s') up"] {
+            assert!(admission.admit(Some(&parse(sql))).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queue() {
+        let admission = admission(QueryOverflowPolicy::Queue, true);
+        let permit = admission.admit(None).await.unwrap();
+
+        let queued = {
+            let admission = admission.clone();
+            tokio::spawn(async move { admission.admit(None).await.unwrap().is_some() })
+        };
+        tokio::task::yield_now().await;
+        assert!(!queued.is_finished());
+
+        drop(permit);
+        assert!(queued.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hold_until_consumed() {
+        let admission = admission(QueryOverflowPolicy::Reject, true);
+        let permit = admission.admit(None).await.unwrap().unwrap();
+        let output = permit.hold_until_consumed(Output::AffectedRows(1));
+        assert!(matches!(output, Output::AffectedRows(1)));
+        assert!(admission.admit(None).await.unwrap().is_some());
+
+        let permit = admission.admit(None).await.unwrap().unwrap();
+        let stream = common_recordbatch::RecordBatches::empty().as_stream();
+        let output = permit.hold_until_consumed(Output::Stream(stream));
+        assert!(admission.admit(None).await.is_err());
+        drop(output);
+        assert!(admission.admit(None).await.unwrap().is_some());
+
+        // The permit is released once the outputs holding its clones are all dropped.
+        let permit = admission.admit(None).await.unwrap().unwrap();
+        let first = permit.clone().hold_until_consumed(Output::Stream(
+            common_recordbatch::RecordBatches::empty().as_stream(),
+        ));
+        let second = permit.hold_until_consumed(Output::Stream(
+            common_recordbatch::RecordBatches::empty().as_stream(),
+        ));
+        drop(first);
+        assert!(admission.admit(None).await.is_err());
+        drop(second);
+        assert!(admission.admit(None).await.unwrap().is_some());
+    }
+}
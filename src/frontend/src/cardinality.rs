@@ -0,0 +1,374 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimation of the number of distinct values of tag columns at ingest time.
+//!
+//! Each tracked column keeps a HyperLogLog sketch of a fixed size, so the memory
+//! doesn't grow with the true cardinality. The number of tracked columns is bounded
+//! too, the columns seen after the limit is reached are ignored.
+
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use ahash::RandomState;
+use api::v1::value::ValueData;
+use api::v1::{RowInsertRequests, SemanticType};
+use common_catalog::format_full_table_name;
+use common_telemetry::logging::warn;
+use servers::http::cardinality::ColumnCardinality;
+use servers::query_handler::CardinalityHandler;
+use session::context::QueryContextRef;
+
+use crate::service_config::CardinalityOptions;
+
+/// Number of bits of the hash selecting a register.
+const PRECISION: u32 = 12;
+/// Number of registers, the standard error of the estimate is about 1.04 / sqrt(4096) = 1.6%.
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+/// Number of shards of the tracked columns.
+const NUM_SHARDS: usize = 16;
+
+/// A HyperLogLog sketch, which takes 4 KiB of memory.
+struct HyperLogLog {
+    registers: Box<[u8]>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; NUM_REGISTERS].into_boxed_slice(),
+        }
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // The sentinel bit bounds the rank when the remaining bits are all zero.
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() + 1;
+        let register = &mut self.registers[index];
+        *register = (*register).max(rank as u8);
+    }
+
+    fn insert_hashes(&mut self, hashes: &[u64]) {
+        for hash in hashes {
+            self.insert_hash(*hash);
+        }
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // Linear counting is more accurate for small cardinalities.
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// Hashes the value of a tag, or returns `None` if tags of its type aren't tracked.
+fn hash_value(hasher: &RandomState, value: &ValueData) -> Option<u64> {
+    let hash = match value {
+        ValueData::StringValue(v) => hasher.hash_one(v),
+        ValueData::BinaryValue(v) => hasher.hash_one(v),
+        ValueData::BoolValue(v) => hasher.hash_one(v),
+        ValueData::I8Value(v) => hasher.hash_one(v),
+        ValueData::I16Value(v) => hasher.hash_one(v),
+        ValueData::I32Value(v) => hasher.hash_one(v),
+        ValueData::I64Value(v) => hasher.hash_one(v),
+        ValueData::U8Value(v) => hasher.hash_one(v),
+        ValueData::U16Value(v) => hasher.hash_one(v),
+        ValueData::U32Value(v) => hasher.hash_one(v),
+        ValueData::U64Value(v) => hasher.hash_one(v),
+        _ => return None,
+    };
+    Some(hash)
+}
+
+struct ColumnSketch {
+    sketch: HyperLogLog,
+    /// Whether the column is reported as exceeding the threshold.
+    warned: bool,
+}
+
+/// Sketches of the columns by the full names of their tables and their names.
+type Shard = HashMap<String, HashMap<String, ColumnSketch>>;
+
+/// Estimates the cardinality of tag columns written through the frontend, and warns
+/// when a column exceeds the threshold.
+///
+/// The columns are sharded, and the values are hashed before locking their shard, so
+/// concurrent writes only contend when they update the sketch of the same shard.
+pub(crate) struct CardinalityEstimator {
+    opts: CardinalityOptions,
+    hasher: RandomState,
+    shards: Vec<Mutex<Shard>>,
+    num_columns: AtomicUsize,
+    /// Whether the limit of tracked columns is reported.
+    full_warned: AtomicBool,
+    start: Instant,
+    /// Milliseconds since the `start` the estimates were last checked at.
+    last_check_ms: AtomicU64,
+}
+
+impl CardinalityEstimator {
+    fn new(opts: &CardinalityOptions) -> Self {
+        Self {
+            opts: opts.clone(),
+            hasher: RandomState::new(),
+            shards: (0..NUM_SHARDS).map(|_| Mutex::default()).collect(),
+            num_columns: AtomicUsize::new(0),
+            full_warned: AtomicBool::new(false),
+            start: Instant::now(),
+            last_check_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates the estimator if it's enabled in `opts`.
+    pub(crate) fn from_options(opts: &CardinalityOptions) -> Option<Arc<Self>> {
+        opts.enable.then(|| Arc::new(Self::new(opts)))
+    }
+
+    fn shard(&self, table: &str, column: &str) -> &Mutex<Shard> {
+        let index = self.hasher.hash_one((table, column)) as usize % NUM_SHARDS;
+        &self.shards[index]
+    }
+
+    /// Adds the tags of the rows to the sketches of their columns.
+    pub(crate) fn observe(&self, requests: &RowInsertRequests, ctx: &QueryContextRef) {
+        for request in &requests.inserts {
+            let Some(rows) = &request.rows else {
+                continue;
+            };
+            let table = format_full_table_name(
+                ctx.current_catalog(),
+                ctx.current_schema(),
+                &request.table_name,
+            );
+
+            for (index, column) in rows.schema.iter().enumerate() {
+                if column.semantic_type != SemanticType::Tag as i32 {
+                    continue;
+                }
+                let hashes = rows
+                    .rows
+                    .iter()
+                    .filter_map(|row| {
+                        let value = row.values.get(index)?.value_data.as_ref()?;
+                        hash_value(&self.hasher, value)
+                    })
+                    .collect::<Vec<_>>();
+                self.insert_hashes(&table, &column.column_name, &hashes);
+            }
+        }
+
+        let now_ms = self.start.elapsed().as_millis() as u64;
+        let last_check_ms = self.last_check_ms.load(Ordering::Relaxed);
+        if now_ms.saturating_sub(last_check_ms) >= self.opts.check_interval.as_millis() as u64
+            && self
+                .last_check_ms
+                .compare_exchange(last_check_ms, now_ms, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.check();
+        }
+    }
+
+    fn insert_hashes(&self, table: &str, column: &str, hashes: &[u64]) {
+        let mut shard = self.shard(table, column).lock().unwrap();
+        if let Some(sketch) = shard
+            .get_mut(table)
+            .and_then(|columns| columns.get_mut(column))
+        {
+            sketch.sketch.insert_hashes(hashes);
+            return;
+        }
+
+        let tracked = self
+            .num_columns
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < self.opts.max_columns).then_some(n + 1)
+            })
+            .is_ok();
+        if !tracked {
+            if !self.full_warned.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "Cardinality of {} tag columns is tracked, ignoring new columns like {}.{}",
+                    self.opts.max_columns, table, column
+                );
+            }
+            return;
+        }
+        let mut sketch = HyperLogLog::new();
+        sketch.insert_hashes(hashes);
+        let _ = shard.entry(table.to_string()).or_default().insert(
+            column.to_string(),
+            ColumnSketch {
+                sketch,
+                warned: false,
+            },
+        );
+    }
+
+    /// Warns once for each column whose estimate exceeds the threshold.
+    fn check(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            for (table, columns) in shard.iter_mut() {
+                for (column, sketch) in columns.iter_mut() {
+                    if sketch.warned {
+                        continue;
+                    }
+                    let estimate = sketch.sketch.estimate();
+                    if estimate > self.opts.warn_threshold {
+                        sketch.warned = true;
+                        warn!(
+                            "Tag column {}.{} has about {} distinct values, exceeding the threshold {}",
+                            table, column, estimate, self.opts.warn_threshold
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CardinalityHandler for CardinalityEstimator {
+    fn cardinality(&self) -> Vec<ColumnCardinality> {
+        let mut result = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+            for (table, columns) in shard.iter() {
+                result.extend(columns.iter().map(|(column, sketch)| ColumnCardinality {
+                    table: table.clone(),
+                    column: column.clone(),
+                    estimate: sketch.sketch.estimate(),
+                }));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use api::v1::{ColumnDataType, ColumnSchema, Row, RowInsertRequest, Rows, Value};
+    use session::context::QueryContext;
+
+    use super::*;
+
+    fn new_requests(
+        table: &str,
+        tags: &[&str],
+        num_rows: usize,
+        offset: usize,
+    ) -> RowInsertRequests {
+        let schema = tags
+            .iter()
+            .map(|tag| ColumnSchema {
+                column_name: tag.to_string(),
+                datatype: ColumnDataType::String as i32,
+                semantic_type: SemanticType::Tag as i32,
+            })
+            .chain(std::iter::once(ColumnSchema {
+                column_name: "v".to_string(),
+                datatype: ColumnDataType::Float64 as i32,
+                semantic_type: SemanticType::Field as i32,
+            }))
+            .collect();
+        let rows = (offset..offset + num_rows)
+            .map(|i| Row {
+                values: tags
+                    .iter()
+                    .map(|_| Value {
+                        value_data: Some(ValueData::StringValue(format!("value-{i}"))),
+                    })
+                    .chain(std::iter::once(Value {
+                        value_data: Some(ValueData::F64Value(i as f64)),
+                    }))
+                    .collect(),
+            })
+            .collect();
+        RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: table.to_string(),
+                rows: Some(Rows { schema, rows }),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_hyper_log_log() {
+        for n in [0, 10, 1000, 100_000] {
+            let hasher = RandomState::new();
+            let mut hll = HyperLogLog::new();
+            for i in 0..n {
+                hll.insert_hash(hash_value(&hasher, &ValueData::U64Value(i)).unwrap());
+                // Duplicates don't change the estimate.
+                hll.insert_hash(hash_value(&hasher, &ValueData::U64Value(i)).unwrap());
+            }
+            let estimate = hll.estimate() as f64;
+            let error = (estimate - n as f64).abs() / (n as f64).max(1.0);
+            assert!(error < 0.05, "n: {n}, estimate: {estimate}");
+        }
+    }
+
+    #[test]
+    fn test_observe() {
+        let estimator = CardinalityEstimator::new(&CardinalityOptions {
+            enable: true,
+            warn_threshold: 100,
+            max_columns: 3,
+            check_interval: Duration::ZERO,
+        });
+        let ctx = QueryContext::arc();
+
+        estimator.observe(&new_requests("cpu", &["host", "dc"], 50, 0), &ctx);
+        estimator.observe(&new_requests("cpu", &["host"], 150, 50), &ctx);
+        let mut columns = estimator.cardinality();
+        columns.sort_unstable_by(|a, b| a.column.cmp(&b.column));
+        assert_eq!(2, columns.len());
+        assert_eq!("greptime.public.cpu", columns[0].table);
+        assert_eq!("dc", columns[0].column);
+        assert!((48..=52).contains(&columns[0].estimate));
+        assert_eq!("host", columns[1].column);
+        assert!((195..=205).contains(&columns[1].estimate));
+
+        let warned = |column: &str| {
+            estimator
+                .shard("greptime.public.cpu", column)
+                .lock()
+                .unwrap()["greptime.public.cpu"][column]
+                .warned
+        };
+        assert!(warned("host"));
+        assert!(!warned("dc"));
+
+        // Columns beyond the limit aren't tracked.
+        estimator.observe(&new_requests("mem", &["host", "dc"], 10, 0), &ctx);
+        assert_eq!(3, estimator.cardinality().len());
+        assert!(estimator.full_warned.load(Ordering::Relaxed));
+    }
+}
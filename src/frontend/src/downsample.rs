@@ -0,0 +1,457 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ingest-time downsampling of metrics written by Prometheus remote write.
+//!
+//! Samples of metrics matching a [DownsamplingRule] are aggregated per series
+//! into fixed-size time buckets. A bucket is emitted as one sample of the
+//! rollup metric once a sample of a later bucket arrives. The latest bucket of
+//! a series that stops receiving samples is emitted by [Downsampler::drain]
+//! once it's closed, or when the frontend stops.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use api::prom_store::remote::{Label, Sample, TimeSeries, WriteRequest};
+use servers::prom_store::METRIC_NAME_LABEL;
+
+use crate::metrics::PROM_STORE_DOWNSAMPLING_LATE_SAMPLES;
+use crate::service_config::prom_store::{DownsamplingRule, RollupAggregation, RollupRule};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SeriesKey {
+    catalog: String,
+    schema: String,
+    /// Name of the rollup table.
+    table: String,
+    /// Labels of the series, excluding the metric name.
+    labels: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    start: i64,
+    interval: i64,
+    aggregation: RollupAggregation,
+    sum: f64,
+    min: f64,
+    max: f64,
+    count: u64,
+}
+
+impl Bucket {
+    fn new(start: i64, rollup: &RollupRule, value: f64) -> Self {
+        Self {
+            start,
+            interval: rollup_interval(rollup),
+            aggregation: rollup.aggregation,
+            sum: value,
+            min: value,
+            max: value,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.count += 1;
+    }
+
+    fn to_sample(&self) -> Sample {
+        let value = match self.aggregation {
+            RollupAggregation::Avg => self.sum / self.count as f64,
+            RollupAggregation::Min => self.min,
+            RollupAggregation::Max => self.max,
+            RollupAggregation::Sum => self.sum,
+            RollupAggregation::Count => self.count as f64,
+        };
+        Sample {
+            value,
+            timestamp: self.start,
+        }
+    }
+
+    /// Returns true if samples arriving at `now` can't fall into the bucket anymore,
+    /// allowing samples to be late by one interval.
+    fn is_closed(&self, now: i64) -> bool {
+        now >= self.start + 2 * self.interval
+    }
+}
+
+/// The rollup samples emitted for the metrics of one database.
+pub(crate) struct Rollups {
+    pub(crate) catalog: String,
+    pub(crate) schema: String,
+    pub(crate) request: WriteRequest,
+}
+
+pub(crate) struct Downsampler {
+    rules: Vec<DownsamplingRule>,
+    buckets: Mutex<HashMap<SeriesKey, Bucket>>,
+}
+
+impl Downsampler {
+    /// Creates a downsampler, or `None` if there are no rules.
+    pub(crate) fn new(rules: &[DownsamplingRule]) -> Option<Self> {
+        if rules.is_empty() {
+            return None;
+        }
+        Some(Self {
+            rules: rules.to_vec(),
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the first rule matching `metric`.
+    pub(crate) fn rule(&self, metric: &str) -> Option<&DownsamplingRule> {
+        self.rules.iter().find(|rule| rule.matches(metric))
+    }
+
+    /// Returns the interval to drain the closed buckets, the shortest interval of
+    /// the rollups.
+    pub(crate) fn drain_interval(&self) -> Duration {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.rollups.iter().map(|rollup| rollup.interval))
+            .min()
+            .unwrap_or(Duration::from_secs(60))
+            .max(Duration::from_secs(1))
+    }
+
+    /// Returns the TTL of the raw or rollup table named `table`, if any rule
+    /// configures one.
+    pub(crate) fn table_ttl(&self, table: &str) -> Option<Duration> {
+        for rule in &self.rules {
+            for rollup in &rule.rollups {
+                let is_rollup = if rule.metric == "*" {
+                    table.ends_with(&rollup.table_name(""))
+                } else {
+                    table == rollup.table_name(&rule.metric)
+                };
+                if is_rollup {
+                    return rollup.ttl;
+                }
+            }
+        }
+        self.rule(table).and_then(|rule| rule.raw_ttl)
+    }
+
+    /// Returns the series in `request` matching any rule, to be ingested once the
+    /// raw samples are written.
+    pub(crate) fn select(&self, request: &WriteRequest) -> Vec<TimeSeries> {
+        request
+            .timeseries
+            .iter()
+            .filter(|series| metric_name(series).is_some_and(|metric| self.rule(metric).is_some()))
+            .cloned()
+            .collect()
+    }
+
+    /// Feeds the samples of `timeseries` written into `catalog.schema` into the
+    /// rollup buckets, and returns the samples of buckets completed by them.
+    ///
+    /// It must be called only after the raw samples are written, so samples of a
+    /// failed write the client retries aren't aggregated twice.
+    pub(crate) fn ingest(
+        &self,
+        timeseries: &[TimeSeries],
+        catalog: &str,
+        schema: &str,
+    ) -> WriteRequest {
+        let mut completed: HashMap<SeriesKey, Vec<Sample>> = HashMap::new();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        for series in timeseries {
+            let Some(metric) = metric_name(series) else {
+                continue;
+            };
+            let Some(rule) = self.rule(metric) else {
+                continue;
+            };
+
+            let mut labels = series
+                .labels
+                .iter()
+                .filter(|label| label.name != METRIC_NAME_LABEL)
+                .map(|label| (label.name.clone(), label.value.clone()))
+                .collect::<Vec<_>>();
+            labels.sort();
+
+            for rollup in &rule.rollups {
+                let key = SeriesKey {
+                    catalog: catalog.to_string(),
+                    schema: schema.to_string(),
+                    table: rollup.table_name(metric),
+                    labels: labels.clone(),
+                };
+                for sample in &series.samples {
+                    if let Some(done) = add_sample(&mut buckets, &key, rollup, sample) {
+                        completed.entry(key.clone()).or_default().push(done);
+                    }
+                }
+            }
+        }
+
+        to_write_request(completed)
+    }
+
+    /// Removes the buckets closed at `now` (in milliseconds), or all buckets if
+    /// `now` is `None`, and returns their samples by database.
+    pub(crate) fn drain(&self, now: Option<i64>) -> Vec<Rollups> {
+        let mut completed: HashMap<(String, String), HashMap<SeriesKey, Vec<Sample>>> =
+            HashMap::new();
+        self.buckets.lock().unwrap().retain(|key, bucket| {
+            if now.is_some_and(|now| !bucket.is_closed(now)) {
+                return true;
+            }
+            let _ = completed
+                .entry((key.catalog.clone(), key.schema.clone()))
+                .or_default()
+                .insert(key.clone(), vec![bucket.to_sample()]);
+            false
+        });
+
+        completed
+            .into_iter()
+            .map(|((catalog, schema), completed)| Rollups {
+                catalog,
+                schema,
+                request: to_write_request(completed),
+            })
+            .collect()
+    }
+}
+
+fn metric_name(series: &TimeSeries) -> Option<&str> {
+    series
+        .labels
+        .iter()
+        .find(|label| label.name == METRIC_NAME_LABEL)
+        .map(|label| label.value.as_str())
+}
+
+fn rollup_interval(rollup: &RollupRule) -> i64 {
+    (rollup.interval.as_millis() as i64).max(1)
+}
+
+fn to_write_request(completed: HashMap<SeriesKey, Vec<Sample>>) -> WriteRequest {
+    let timeseries = completed
+        .into_iter()
+        .map(|(key, samples)| {
+            let mut labels = Vec::with_capacity(key.labels.len() + 1);
+            labels.push(Label {
+                name: METRIC_NAME_LABEL.to_string(),
+                value: key.table,
+            });
+            labels.extend(
+                key.labels
+                    .into_iter()
+                    .map(|(name, value)| Label { name, value }),
+            );
+            TimeSeries {
+                labels,
+                samples,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    WriteRequest {
+        timeseries,
+        ..Default::default()
+    }
+}
+
+/// Adds `sample` to the bucket of `key`, returning the rollup sample of the
+/// previous bucket if `sample` starts a new one.
+fn add_sample(
+    buckets: &mut HashMap<SeriesKey, Bucket>,
+    key: &SeriesKey,
+    rollup: &RollupRule,
+    sample: &Sample,
+) -> Option<Sample> {
+    let interval = rollup_interval(rollup);
+    let start = sample.timestamp - sample.timestamp.rem_euclid(interval);
+
+    let Some(bucket) = buckets.get_mut(key) else {
+        let _ = buckets.insert(key.clone(), Bucket::new(start, rollup, sample.value));
+        return None;
+    };
+
+    if start == bucket.start {
+        bucket.add(sample.value);
+        None
+    } else if start > bucket.start {
+        let done = bucket.to_sample();
+        *bucket = Bucket::new(start, rollup, sample.value);
+        Some(done)
+    } else {
+        // The bucket of a late sample has been emitted already.
+        PROM_STORE_DOWNSAMPLING_LATE_SAMPLES.inc();
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_downsampler(aggregation: RollupAggregation) -> Downsampler {
+        Downsampler::new(&[DownsamplingRule {
+            metric: "cpu".to_string(),
+            raw_ttl: Some(Duration::from_secs(3600)),
+            rollups: vec![RollupRule {
+                interval: Duration::from_secs(60),
+                aggregation,
+                ttl: Some(Duration::from_secs(7200)),
+            }],
+        }])
+        .unwrap()
+    }
+
+    fn ingest(downsampler: &Downsampler, metric: &str, samples: &[(i64, f64)]) -> WriteRequest {
+        let request = new_request(metric, samples);
+        downsampler.ingest(&downsampler.select(&request), "greptime", "public")
+    }
+
+    fn new_request(metric: &str, samples: &[(i64, f64)]) -> WriteRequest {
+        WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![
+                    Label {
+                        name: METRIC_NAME_LABEL.to_string(),
+                        value: metric.to_string(),
+                    },
+                    Label {
+                        name: "host".to_string(),
+                        value: "a".to_string(),
+                    },
+                ],
+                samples: samples
+                    .iter()
+                    .map(|(timestamp, value)| Sample {
+                        value: *value,
+                        timestamp: *timestamp,
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_downsample_avg() {
+        let downsampler = new_downsampler(RollupAggregation::Avg);
+
+        let out = ingest(&downsampler, "cpu", &[(0, 1.0), (30_000, 3.0)]);
+        assert!(out.timeseries.is_empty());
+
+        // Unmatched metrics are ignored.
+        assert!(downsampler
+            .select(&new_request("mem", &[(0, 1.0)]))
+            .is_empty());
+        let out = ingest(&downsampler, "mem", &[(0, 1.0), (90_000, 3.0)]);
+        assert!(out.timeseries.is_empty());
+
+        let out = ingest(&downsampler, "cpu", &[(60_000, 5.0), (10_000, 100.0)]);
+        assert_eq!(1, out.timeseries.len());
+        let series = &out.timeseries[0];
+        assert_eq!("cpu_1m_avg", series.labels[0].value);
+        assert_eq!("host", series.labels[1].name);
+        assert_eq!(
+            vec![Sample {
+                value: 2.0,
+                timestamp: 0
+            }],
+            series.samples
+        );
+    }
+
+    #[test]
+    fn test_downsample_aggregations() {
+        for (aggregation, expected) in [
+            (RollupAggregation::Min, 1.0),
+            (RollupAggregation::Max, 4.0),
+            (RollupAggregation::Sum, 7.0),
+            (RollupAggregation::Count, 3.0),
+        ] {
+            let downsampler = new_downsampler(aggregation);
+            let out = ingest(
+                &downsampler,
+                "cpu",
+                &[(1_000, 2.0), (2_000, 1.0), (3_000, 4.0), (61_000, 0.0)],
+            );
+            assert_eq!(expected, out.timeseries[0].samples[0].value);
+        }
+    }
+
+    #[test]
+    fn test_downsample_per_database() {
+        let downsampler = new_downsampler(RollupAggregation::Sum);
+        let request = new_request("cpu", &[(0, 1.0)]);
+        let selected = downsampler.select(&request);
+        let _ = downsampler.ingest(&selected, "greptime", "a");
+        let _ = downsampler.ingest(&selected, "greptime", "b");
+
+        // The same series of different databases are aggregated separately.
+        let request = new_request("cpu", &[(60_000, 1.0)]);
+        let out = downsampler.ingest(&downsampler.select(&request), "greptime", "a");
+        assert_eq!(1.0, out.timeseries[0].samples[0].value);
+    }
+
+    #[test]
+    fn test_drain() {
+        let downsampler = new_downsampler(RollupAggregation::Sum);
+        let _ = ingest(&downsampler, "cpu", &[(0, 1.0), (1_000, 2.0)]);
+
+        // The bucket still accepts late samples.
+        assert!(downsampler.drain(Some(90_000)).is_empty());
+
+        let rollups = downsampler.drain(Some(120_000));
+        assert_eq!(1, rollups.len());
+        assert_eq!("public", rollups[0].schema);
+        let series = &rollups[0].request.timeseries;
+        assert_eq!(
+            vec![Sample {
+                value: 3.0,
+                timestamp: 0
+            }],
+            series[0].samples
+        );
+        // Drained buckets are evicted.
+        assert!(downsampler.drain(None).is_empty());
+
+        let _ = ingest(&downsampler, "cpu", &[(120_000, 5.0)]);
+        let rollups = downsampler.drain(None);
+        assert_eq!(5.0, rollups[0].request.timeseries[0].samples[0].value);
+    }
+
+    #[test]
+    fn test_table_ttl() {
+        let downsampler = new_downsampler(RollupAggregation::Avg);
+        assert_eq!(
+            Some(Duration::from_secs(3600)),
+            downsampler.table_ttl("cpu")
+        );
+        assert_eq!(
+            Some(Duration::from_secs(7200)),
+            downsampler.table_ttl("cpu_1m_avg")
+        );
+        assert_eq!(None, downsampler.table_ttl("mem"));
+    }
+}
@@ -20,6 +20,7 @@ use common_error::status_code::StatusCode;
 use common_macro::stack_trace_debug;
 use servers::define_into_tonic_status;
 use snafu::{Location, Snafu};
+use sql::statements::statement::StatementKind;
 use store_api::storage::RegionNumber;
 
 #[derive(Snafu)]
@@ -62,6 +63,13 @@ pub enum Error {
         source: common_runtime::error::Error,
     },
 
+    #[snafu(display("Failed to join the task running the request"))]
+    JoinTask {
+        location: Location,
+        #[snafu(source)]
+        error: tokio::task::JoinError,
+    },
+
     #[snafu(display("Failed to start server"))]
     StartServer {
         location: Location,
@@ -188,6 +196,18 @@ pub enum Error {
     #[snafu(display("Not supported: {}", feat))]
     NotSupported { feat: String },
 
+    #[snafu(display("Server busy, {} queries are running, retry later", limit))]
+    ServerBusy { limit: usize, location: Location },
+
+    #[snafu(display("SQL writes are denied, write through an allowed ingest protocol instead"))]
+    SqlWriteDenied { location: Location },
+
+    #[snafu(display("Statement type not permitted: {}", kind))]
+    StatementNotPermitted {
+        kind: StatementKind,
+        location: Location,
+    },
+
     #[snafu(display("SQL execution intercepted"))]
     SqlExecIntercepted {
         location: Location,
@@ -202,6 +222,12 @@ pub enum Error {
         source: servers::error::Error,
     },
 
+    #[snafu(display("Failed to convert the Prometheus remote write request"))]
+    ConvertPromStoreRequest {
+        location: Location,
+        source: servers::error::Error,
+    },
+
     #[snafu(display("Failed to create logical plan for prometheus query"))]
     PromStoreRemoteQueryPlan {
         location: Location,
@@ -273,6 +299,12 @@ pub enum Error {
     #[snafu(display("Invalid auth config"))]
     IllegalAuthConfig { source: auth::error::Error },
 
+    #[snafu(display("Failed to collect recordbatch"))]
+    CollectRecordbatch {
+        source: common_recordbatch::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to serialize options to TOML"))]
     TomlFormat {
         #[snafu(source)]
@@ -302,6 +334,12 @@ impl ErrorExt for Error {
 
             Error::NotSupported { .. } => StatusCode::Unsupported,
 
+            Error::ServerBusy { .. } => StatusCode::RuntimeResourcesExhausted,
+
+            Error::SqlWriteDenied { .. } | Error::StatementNotPermitted { .. } => {
+                StatusCode::PermissionDenied
+            }
+
             Error::Permission { source, .. } => source.status_code(),
 
             Error::DescribeStatement { source, .. } => source.status_code(),
@@ -310,11 +348,13 @@ impl ErrorExt for Error {
             | Error::TableMetadataManager { source, .. } => source.status_code(),
 
             Error::RuntimeResource { source, .. } => source.status_code(),
+            Error::JoinTask { .. } => StatusCode::Internal,
             Error::PromStoreRemoteQueryPlan { source, .. }
             | Error::ExecutePromql { source, .. } => source.status_code(),
 
             Error::SqlExecIntercepted { source, .. } => source.status_code(),
             Error::StartServer { source, .. } => source.status_code(),
+            Error::ConvertPromStoreRequest { source, .. } => source.status_code(),
             Error::ShutdownServer { source, .. } => source.status_code(),
 
             Error::ParseSql { source, .. } => source.status_code(),
@@ -357,6 +397,8 @@ impl ErrorExt for Error {
             Error::StartScriptManager { source, .. } => source.status_code(),
 
             Error::TableOperation { source, .. } => source.status_code(),
+
+            Error::CollectRecordbatch { source, .. } => source.status_code(),
         }
     }
 
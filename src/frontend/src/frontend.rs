@@ -12,18 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_base::readable_size::ReadableSize;
+use common_error::ext::ErrorVerbosity;
 use common_telemetry::logging::LoggingOptions;
 use meta_client::MetaClientOptions;
+use operator::float_special::FloatSpecialPolicy;
+use operator::insert::DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL;
+use query::query_engine::options::FillPolicy;
 use serde::{Deserialize, Serialize};
 use servers::heartbeat_options::HeartbeatOptions;
 use servers::http::HttpOptions;
 use servers::Mode;
 use snafu::prelude::*;
+use sql::statements::statement::StatementKind;
+use store_api::region_request::WriteAckLevel;
+use strum::EnumString;
 
 use crate::error::{Result, TomlFormatSnafu};
 use crate::service_config::{
-    DatanodeOptions, GrpcOptions, InfluxdbOptions, MysqlOptions, OpentsdbOptions, OtlpOptions,
-    PostgresOptions, PromStoreOptions,
+    CardinalityOptions, DatanodeOptions, GrpcOptions, InfluxdbOptions, MysqlOptions,
+    OpentsdbOptions, OtlpOptions, PostgresOptions, PromStoreOptions, QueryAdmissionOptions,
+    QueryCacheOptions, SelfMonitoringOptions, SlowQueryOptions,
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,6 +49,66 @@ pub struct FrontendOptions {
     pub influxdb: InfluxdbOptions,
     pub prom_store: PromStoreOptions,
     pub otlp: OtlpOptions,
+    pub query_cache: QueryCacheOptions,
+    pub cardinality: CardinalityOptions,
+    pub slow_query: SlowQueryOptions,
+    pub self_monitoring: SelfMonitoringOptions,
+    pub query_admission: QueryAdmissionOptions,
+    /// Worker threads of the runtime executing the writes of all protocols. They run
+    /// on the runtime of the server receiving them when not set.
+    pub ingest_worker_threads: Option<usize>,
+    /// Worker threads of the runtime executing the SQL and PromQL queries of all
+    /// protocols. They run on the runtime of the server receiving them when not set.
+    pub query_worker_threads: Option<usize>,
+    /// Size of `SO_SNDBUF` of the server sockets, the OS default if not set.
+    pub tcp_send_buffer: Option<ReadableSize>,
+    /// Size of `SO_RCVBUF` of the server sockets, the OS default if not set.
+    pub tcp_recv_buffer: Option<ReadableSize>,
+    /// Max degree of parallelism of a query, see [QueryOptions](query::query_engine::options::QueryOptions).
+    pub query_max_parallelism: Option<usize>,
+    /// Rejects the queries that scan a table without a lower bound on its time index.
+    pub deny_full_table_scan: bool,
+    /// Tables (`table` or `schema.table`) that may be fully scanned when
+    /// `deny_full_table_scan` is set.
+    pub full_table_scan_exempt_tables: Vec<String>,
+    /// Fill strategy of the range queries without a `FILL` option.
+    pub default_fill_policy: FillPolicy,
+    /// Ingest protocols served when their servers are enabled, all of them if not set.
+    /// The HTTP routes of a disallowed protocol respond with 404.
+    pub ingest_protocol_allowlist: Option<Vec<IngestProtocol>>,
+    /// Rejects the SQL statements writing data (`INSERT`, `DELETE` and `COPY FROM`),
+    /// independently of the `ingest_protocol_allowlist`.
+    pub deny_sql_writes: bool,
+    /// Kinds of SQL statements that may be executed, like `query` or `insert`, all of
+    /// them if not set.
+    pub sql_statement_allowlist: Option<Vec<StatementKind>>,
+    /// Kinds of SQL statements that are rejected, even if they are in the allowlist.
+    pub sql_statement_blocklist: Vec<StatementKind>,
+    /// How much of an error is returned to clients, the full error is still logged.
+    pub error_verbosity: ErrorVerbosity,
+    /// Max number of tables of the instance, checked when a table is created by DDL
+    /// or on demand by ingest. Unlimited if not set.
+    pub max_table_count: Option<usize>,
+    /// Max number of columns of a table, checked when a table is created or columns are
+    /// added. Unlimited if not set.
+    pub max_column_count: Option<usize>,
+    /// When the writes are acknowledged, overridden by the `x-greptime-write-ack-level`
+    /// header of an HTTP or gRPC request. The default `memtable` level loses the writes
+    /// not synced to the WAL if the machine crashes.
+    pub write_ack_level: WriteAckLevel,
+    /// Max level the header of a request can ask for, a request asking for a higher
+    /// level is rejected. The `flush` level flushes the regions on each write, so it's
+    /// only allowed if set here.
+    pub max_request_write_ack_level: WriteAckLevel,
+    /// What to do with the NaN and infinite floats of all the ingest protocols. If not
+    /// set, each protocol follows its convention, see [IngestProtocol].
+    pub float_special_policy: Option<FloatSpecialPolicy>,
+    /// Max number of the MySQL and PostgreSQL connections of a user, unlimited if not
+    /// set. A client over the limit is rejected when it logs in.
+    pub max_connections_per_user: Option<usize>,
+    /// Max number of the MySQL and PostgreSQL connections not logged in yet,
+    /// unlimited if not set.
+    pub max_unauthenticated_connections: Option<usize>,
     pub meta_client: Option<MetaClientOptions>,
     pub logging: LoggingOptions,
     pub datanode: DatanodeOptions,
@@ -60,6 +129,31 @@ impl Default for FrontendOptions {
             influxdb: InfluxdbOptions::default(),
             prom_store: PromStoreOptions::default(),
             otlp: OtlpOptions::default(),
+            query_cache: QueryCacheOptions::default(),
+            cardinality: CardinalityOptions::default(),
+            slow_query: SlowQueryOptions::default(),
+            self_monitoring: SelfMonitoringOptions::default(),
+            query_admission: QueryAdmissionOptions::default(),
+            ingest_worker_threads: None,
+            query_worker_threads: None,
+            tcp_send_buffer: None,
+            tcp_recv_buffer: None,
+            query_max_parallelism: None,
+            deny_full_table_scan: false,
+            full_table_scan_exempt_tables: vec![],
+            default_fill_policy: FillPolicy::default(),
+            ingest_protocol_allowlist: None,
+            deny_sql_writes: false,
+            sql_statement_allowlist: None,
+            sql_statement_blocklist: vec![],
+            error_verbosity: ErrorVerbosity::default(),
+            max_table_count: None,
+            max_column_count: None,
+            write_ack_level: WriteAckLevel::default(),
+            max_request_write_ack_level: DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL,
+            float_special_policy: None,
+            max_connections_per_user: None,
+            max_unauthenticated_connections: None,
             meta_client: None,
             logging: LoggingOptions::default(),
             datanode: DatanodeOptions::default(),
@@ -76,6 +170,45 @@ impl FrontendOptions {
     pub fn to_toml_string(&self) -> String {
         toml::to_string(&self).unwrap()
     }
+
+    /// Returns whether the `protocol` may be served according to the
+    /// `ingest_protocol_allowlist`.
+    pub fn is_ingest_allowed(&self, protocol: IngestProtocol) -> bool {
+        self.ingest_protocol_allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.contains(&protocol))
+    }
+}
+
+/// An ingest protocol that can be disallowed even though the server serving it,
+/// like the shared HTTP server, is running.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumString)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestProtocol {
+    /// InfluxDB line protocol on HTTP.
+    #[strum(serialize = "influxdb")]
+    Influxdb,
+    /// OpenTSDB on HTTP and its telnet server.
+    #[strum(serialize = "opentsdb")]
+    Opentsdb,
+    /// Prometheus remote write and remote read on HTTP.
+    #[strum(serialize = "prom_store")]
+    PromStore,
+    /// OpenTelemetry metrics and traces on HTTP.
+    #[strum(serialize = "otlp")]
+    Otlp,
+}
+
+impl IngestProtocol {
+    /// Returns the policy of the NaN and infinite floats following the convention of
+    /// the protocol. InfluxDB and OpenTSDB don't accept such values, while Prometheus
+    /// marks the stale series by NaN.
+    pub fn default_float_special_policy(&self) -> FloatSpecialPolicy {
+        match self {
+            IngestProtocol::Influxdb | IngestProtocol::Opentsdb => FloatSpecialPolicy::Error,
+            IngestProtocol::PromStore | IngestProtocol::Otlp => FloatSpecialPolicy::Store,
+        }
+    }
 }
 
 pub trait TomlSerializable {
@@ -98,4 +231,26 @@ mod tests {
         let toml_string = toml::to_string(&opts).unwrap();
         let _parsed: FrontendOptions = toml::from_str(&toml_string).unwrap();
     }
+
+    #[test]
+    fn test_ingest_protocol_allowlist() {
+        let opts: FrontendOptions =
+            toml::from_str(r#"ingest_protocol_allowlist = ["prom_store", "otlp"]"#).unwrap();
+        assert!(opts.is_ingest_allowed(IngestProtocol::PromStore));
+        assert!(opts.is_ingest_allowed(IngestProtocol::Otlp));
+        assert!(!opts.is_ingest_allowed(IngestProtocol::Influxdb));
+        assert!(!opts.is_ingest_allowed(IngestProtocol::Opentsdb));
+
+        let opts = FrontendOptions::default();
+        assert!(opts.is_ingest_allowed(IngestProtocol::Influxdb));
+
+        assert!(
+            toml::from_str::<FrontendOptions>(r#"ingest_protocol_allowlist = ["mqtt"]"#).is_err()
+        );
+        assert_eq!(
+            IngestProtocol::PromStore,
+            "prom_store".parse::<IngestProtocol>().unwrap()
+        );
+        assert!("prometheus".parse::<IngestProtocol>().is_err());
+    }
 }
@@ -19,9 +19,11 @@ mod otlp;
 mod prom_store;
 mod region_query;
 mod script;
+mod self_monitoring;
 mod standalone;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use api::v1::meta::Role;
 use async_trait::async_trait;
@@ -40,18 +42,21 @@ use common_meta::heartbeat::handler::HandlerGroupExecutor;
 use common_meta::key::TableMetadataManager;
 use common_meta::kv_backend::KvBackendRef;
 use common_meta::state_store::KvStateStore;
+use common_meta::table_name::TableName;
 use common_procedure::local::{LocalManager, ManagerConfig};
 use common_procedure::options::ProcedureConfig;
 use common_procedure::ProcedureManagerRef;
 use common_query::Output;
+use common_runtime::RepeatedTask;
 use common_telemetry::error;
 use common_telemetry::logging::info;
 use datanode::region_server::RegionServer;
 use log_store::raft_engine::RaftEngineBackend;
 use meta_client::client::{MetaClient, MetaClientBuilder};
 use operator::delete::{Deleter, DeleterRef};
+use operator::float_special::FloatSpecialPolicy;
 use operator::insert::{Inserter, InserterRef};
-use operator::statement::StatementExecutor;
+use operator::statement::{SchemaLimits, StatementExecutor};
 use operator::table::{table_idents_to_full_name, TableMutationOperator};
 use partition::manager::PartitionRuleManager;
 use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
@@ -69,8 +74,8 @@ use servers::prometheus_handler::PrometheusHandler;
 use servers::query_handler::grpc::GrpcQueryHandler;
 use servers::query_handler::sql::SqlQueryHandler;
 use servers::query_handler::{
-    InfluxdbLineProtocolHandler, OpenTelemetryProtocolHandler, OpentsdbProtocolHandler,
-    PromStoreProtocolHandler, ScriptHandler,
+    CardinalityHandlerRef, InfluxdbLineProtocolHandler, OpenTelemetryProtocolHandler,
+    OpentsdbProtocolHandler, PromStoreProtocolHandler, ScriptHandler,
 };
 use session::context::QueryContextRef;
 use snafu::prelude::*;
@@ -80,20 +85,34 @@ use sql::statements::copy::CopyTable;
 use sql::statements::statement::Statement;
 use sqlparser::ast::ObjectName;
 pub use standalone::StandaloneDatanodeManager;
+use store_api::region_request::WriteAckLevel;
 
 use self::region_query::FrontendRegionQueryHandler;
 use self::standalone::StandaloneTableMetadataCreator;
+use crate::admission::{Permit, QueryAdmission};
+use crate::cardinality::CardinalityEstimator;
+use crate::downsample::Downsampler;
 use crate::error::{
     self, Error, ExecLogicalPlanSnafu, ExecutePromqlSnafu, ExternalSnafu, MissingMetasrvOptsSnafu,
     ParseSqlSnafu, PermissionSnafu, PlanStatementSnafu, Result, SqlExecInterceptedSnafu,
-    TableOperationSnafu,
+    SqlWriteDeniedSnafu, StatementNotPermittedSnafu, TableOperationSnafu,
 };
 use crate::frontend::{FrontendOptions, TomlSerializable};
 use crate::heartbeat::handler::invalidate_table_cache::InvalidateTableCacheHandler;
 use crate::heartbeat::HeartbeatTask;
+use crate::instance::prom_store::new_drain_rollups_task;
+use crate::instance::self_monitoring::new_self_monitoring_task;
 use crate::metrics;
+use crate::query_cache::{self, Lookup, QueryResultCache, QueryResultCacheRef};
 use crate::script::ScriptExecutor;
 use crate::server::{start_server, ServerHandlers, Services};
+use crate::service_config::prom_store::DownsamplingRule;
+use crate::service_config::{
+    CardinalityOptions, QueryAdmissionOptions, QueryCacheOptions, SelfMonitoringOptions,
+    SlowQueryOptions,
+};
+use crate::slow_query::SlowQueryLogger;
+use crate::workload::WorkloadRuntimes;
 
 #[async_trait]
 pub trait FrontendInstance:
@@ -126,6 +145,15 @@ pub struct Instance {
     heartbeat_task: Option<HeartbeatTask>,
     inserter: InserterRef,
     deleter: DeleterRef,
+    query_cache: Option<QueryResultCacheRef>,
+    prom_store_downsampler: Option<Arc<Downsampler>>,
+    cardinality_estimator: Option<Arc<CardinalityEstimator>>,
+    slow_query_logger: Option<Arc<SlowQueryLogger>>,
+    self_monitoring: Option<SelfMonitoringOptions>,
+    query_admission: Option<Arc<QueryAdmission>>,
+    /// The repeated tasks started with the instance, stopped on shutdown.
+    background_tasks: Arc<Mutex<Vec<RepeatedTask<Error>>>>,
+    workload_runtimes: Arc<WorkloadRuntimes>,
 }
 
 impl Instance {
@@ -157,16 +185,25 @@ impl Instance {
             catalog_manager.datanode_manager().clone(),
         );
 
-        let inserter = Arc::new(Inserter::new(
-            catalog_manager.clone(),
-            partition_manager.clone(),
-            datanode_clients.clone(),
-        ));
-        let deleter = Arc::new(Deleter::new(
-            catalog_manager.clone(),
-            partition_manager,
-            datanode_clients,
-        ));
+        let inserter = Arc::new(
+            Inserter::new(
+                catalog_manager.clone(),
+                partition_manager.clone(),
+                datanode_clients.clone(),
+                opts.write_ack_level,
+            )
+            .with_max_request_write_ack_level(opts.max_request_write_ack_level)
+            .with_float_special_policy(plugins.get::<FloatSpecialPolicy>().unwrap_or_default()),
+        );
+        let deleter = Arc::new(
+            Deleter::new(
+                catalog_manager.clone(),
+                partition_manager,
+                datanode_clients,
+                opts.write_ack_level,
+            )
+            .with_max_request_write_ack_level(opts.max_request_write_ack_level),
+        );
 
         let table_mutation_handler = Arc::new(TableMutationOperator::new(
             inserter.clone(),
@@ -182,14 +219,17 @@ impl Instance {
         )
         .query_engine();
 
-        let statement_executor = Arc::new(StatementExecutor::new(
-            catalog_manager.clone(),
-            query_engine.clone(),
-            meta_client.clone(),
-            meta_backend.clone(),
-            catalog_manager.clone(),
-            inserter.clone(),
-        ));
+        let statement_executor = Arc::new(
+            StatementExecutor::new(
+                catalog_manager.clone(),
+                query_engine.clone(),
+                meta_client.clone(),
+                meta_backend.clone(),
+                catalog_manager.clone(),
+                inserter.clone(),
+            )
+            .with_schema_limits(plugins.get::<SchemaLimits>().unwrap_or_default()),
+        );
 
         plugins.insert::<StatementExecutorRef>(statement_executor.clone());
 
@@ -209,6 +249,11 @@ impl Instance {
 
         common_telemetry::init_node_id(opts.node_id.clone());
 
+        let cardinality_estimator = CardinalityEstimator::from_options(&opts.cardinality);
+        if let Some(estimator) = &cardinality_estimator {
+            plugins.insert::<CardinalityHandlerRef>(estimator.clone());
+        }
+
         Ok(Instance {
             catalog_manager,
             script_executor,
@@ -219,6 +264,20 @@ impl Instance {
             heartbeat_task,
             inserter,
             deleter,
+            query_cache: QueryResultCache::from_options(&opts.query_cache),
+            prom_store_downsampler: Downsampler::new(&opts.prom_store.downsampling).map(Arc::new),
+            cardinality_estimator,
+            slow_query_logger: SlowQueryLogger::from_options(&opts.slow_query),
+            self_monitoring: opts
+                .self_monitoring
+                .enable
+                .then(|| opts.self_monitoring.clone()),
+            query_admission: QueryAdmission::from_options(&opts.query_admission),
+            background_tasks: Arc::new(Mutex::new(Vec::new())),
+            workload_runtimes: Arc::new(WorkloadRuntimes::try_new(
+                opts.ingest_worker_threads,
+                opts.query_worker_threads,
+            )?),
         })
     }
 
@@ -273,16 +332,28 @@ impl Instance {
             .context(error::OpenRaftEngineBackendSnafu)?,
         );
 
-        let state_store = Arc::new(KvStateStore::new(kv_backend.clone()));
+        let state_store = KvStateStore::new(kv_backend.clone());
+        let procedure_manager =
+            Self::build_standalone_procedure_manager(state_store, procedure_config);
+
+        Ok((kv_backend, procedure_manager))
+    }
+
+    /// Builds the procedure manager of the standalone mode, storing the procedures in
+    /// `state_store`.
+    pub fn build_standalone_procedure_manager(
+        state_store: KvStateStore,
+        procedure_config: ProcedureConfig,
+    ) -> ProcedureManagerRef {
+        let state_store = Arc::new(state_store);
 
         let manager_config = ManagerConfig {
             max_retry_times: procedure_config.max_retry_times,
             retry_delay: procedure_config.retry_delay,
+            max_running_procedures: procedure_config.max_running_procedures,
             ..Default::default()
         };
-        let procedure_manager = Arc::new(LocalManager::new(manager_config, state_store));
-
-        Ok((kv_backend, procedure_manager))
+        Arc::new(LocalManager::new(manager_config, state_store))
     }
 
     pub async fn try_new_standalone(
@@ -291,6 +362,8 @@ impl Instance {
         catalog_manager: CatalogManagerRef,
         plugins: Plugins,
         region_server: RegionServer,
+        write_ack_level: WriteAckLevel,
+        max_request_write_ack_level: WriteAckLevel,
     ) -> Result<Self> {
         let partition_manager = Arc::new(PartitionRuleManager::new(kv_backend.clone()));
         let datanode_manager = Arc::new(StandaloneDatanodeManager(region_server));
@@ -298,16 +371,25 @@ impl Instance {
         let region_query_handler =
             FrontendRegionQueryHandler::arc(partition_manager.clone(), datanode_manager.clone());
 
-        let inserter = Arc::new(Inserter::new(
-            catalog_manager.clone(),
-            partition_manager.clone(),
-            datanode_manager.clone(),
-        ));
-        let deleter = Arc::new(Deleter::new(
-            catalog_manager.clone(),
-            partition_manager,
-            datanode_manager.clone(),
-        ));
+        let inserter = Arc::new(
+            Inserter::new(
+                catalog_manager.clone(),
+                partition_manager.clone(),
+                datanode_manager.clone(),
+                write_ack_level,
+            )
+            .with_max_request_write_ack_level(max_request_write_ack_level)
+            .with_float_special_policy(plugins.get::<FloatSpecialPolicy>().unwrap_or_default()),
+        );
+        let deleter = Arc::new(
+            Deleter::new(
+                catalog_manager.clone(),
+                partition_manager,
+                datanode_manager.clone(),
+                write_ack_level,
+            )
+            .with_max_request_write_ack_level(max_request_write_ack_level),
+        );
         let table_mutation_handler = Arc::new(TableMutationOperator::new(
             inserter.clone(),
             deleter.clone(),
@@ -336,14 +418,17 @@ impl Instance {
             Arc::new(StandaloneTableMetadataCreator::new(kv_backend.clone())),
         ));
 
-        let statement_executor = Arc::new(StatementExecutor::new(
-            catalog_manager.clone(),
-            query_engine.clone(),
-            ddl_executor,
-            kv_backend.clone(),
-            cache_invalidator,
-            inserter.clone(),
-        ));
+        let statement_executor = Arc::new(
+            StatementExecutor::new(
+                catalog_manager.clone(),
+                query_engine.clone(),
+                ddl_executor,
+                kv_backend.clone(),
+                cache_invalidator,
+                inserter.clone(),
+            )
+            .with_schema_limits(plugins.get::<SchemaLimits>().unwrap_or_default()),
+        );
 
         Ok(Instance {
             catalog_manager: catalog_manager.clone(),
@@ -355,6 +440,14 @@ impl Instance {
             heartbeat_task: None,
             inserter,
             deleter,
+            query_cache: None,
+            prom_store_downsampler: None,
+            cardinality_estimator: None,
+            slow_query_logger: None,
+            self_monitoring: None,
+            query_admission: None,
+            background_tasks: Arc::new(Mutex::new(Vec::new())),
+            workload_runtimes: Arc::new(WorkloadRuntimes::default()),
         })
     }
 
@@ -368,6 +461,57 @@ impl Instance {
         Ok(())
     }
 
+    /// Enables the query result cache according to `opts`.
+    pub fn set_query_cache(&mut self, opts: &QueryCacheOptions) {
+        self.query_cache = QueryResultCache::from_options(opts);
+    }
+
+    /// Builds the dedicated runtimes of the writes and queries according to `opts`.
+    pub fn set_workload_runtimes(&mut self, opts: &FrontendOptions) -> Result<()> {
+        self.workload_runtimes = Arc::new(WorkloadRuntimes::try_new(
+            opts.ingest_worker_threads,
+            opts.query_worker_threads,
+        )?);
+        Ok(())
+    }
+
+    /// Enables ingest-time downsampling of Prometheus remote write metrics.
+    pub fn set_prom_store_downsampling(&mut self, rules: &[DownsamplingRule]) {
+        self.prom_store_downsampler = Downsampler::new(rules).map(Arc::new);
+    }
+
+    /// Enables the slow query log according to `opts`.
+    pub fn set_slow_query_log(&mut self, opts: &SlowQueryOptions) {
+        self.slow_query_logger = SlowQueryLogger::from_options(opts);
+    }
+
+    /// Limits the number of the queries executing at the same time according to `opts`.
+    pub fn set_query_admission(&mut self, opts: &QueryAdmissionOptions) {
+        self.query_admission = QueryAdmission::from_options(opts);
+    }
+
+    /// Enables writing the metrics of the instance into a table according to `opts`.
+    pub fn set_self_monitoring(&mut self, opts: &SelfMonitoringOptions) {
+        self.self_monitoring = opts.enable.then(|| opts.clone());
+    }
+
+    /// Enables the cardinality estimation of tag columns according to `opts`, must be
+    /// called before building the servers to serve `/admin/cardinality`.
+    pub fn set_cardinality_estimation(&mut self, opts: &CardinalityOptions) {
+        self.cardinality_estimator = CardinalityEstimator::from_options(opts);
+        if let Some(estimator) = &self.cardinality_estimator {
+            self.plugins
+                .insert::<CardinalityHandlerRef>(estimator.clone());
+        }
+    }
+
+    /// Drops cached query results reading `tables` after their data or schema changes.
+    pub(crate) fn invalidate_query_cache(&self, tables: impl IntoIterator<Item = TableName>) {
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate_tables(tables);
+        }
+    }
+
     pub fn catalog_manager(&self) -> &CatalogManagerRef {
         &self.catalog_manager
     }
@@ -379,8 +523,22 @@ impl Instance {
     pub async fn shutdown(&self) -> Result<()> {
         futures::future::try_join_all(self.servers.values().map(|server| server.0.shutdown()))
             .await
-            .context(error::ShutdownServerSnafu)
-            .map(|_| ())
+            .context(error::ShutdownServerSnafu)?;
+
+        let tasks = std::mem::take(&mut *self.background_tasks.lock().unwrap());
+        for task in tasks {
+            task.stop().await.context(error::RuntimeResourceSnafu)?;
+        }
+        // No more samples arrive once the servers are stopped.
+        self.drain_rollups(None).await
+    }
+
+    /// Starts the repeated `task`, which is stopped on shutdown.
+    fn start_background_task(&self, task: RepeatedTask<Error>) -> Result<()> {
+        task.start(common_runtime::bg_runtime())
+            .context(error::RuntimeResourceSnafu)?;
+        self.background_tasks.lock().unwrap().push(task);
+        Ok(())
     }
 
     pub fn statement_executor(&self) -> Arc<StatementExecutor> {
@@ -391,12 +549,35 @@ impl Instance {
 #[async_trait]
 impl FrontendInstance for Instance {
     async fn start(&self) -> Result<()> {
+        self.start_components().await?;
+        self.start_servers().await
+    }
+}
+
+impl Instance {
+    /// Starts the components other than the servers, so no request is received yet.
+    pub async fn start_components(&self) -> Result<()> {
         if let Some(heartbeat_task) = &self.heartbeat_task {
             heartbeat_task.start().await?;
         }
 
         self.script_executor.start(self)?;
 
+        if let Some(opts) = &self.self_monitoring {
+            let task = new_self_monitoring_task(self.clone(), opts.clone());
+            self.start_background_task(task)?;
+        }
+
+        if let Some(downsampler) = &self.prom_store_downsampler {
+            let task = new_drain_rollups_task(self.clone(), downsampler.drain_interval());
+            self.start_background_task(task)?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the servers receiving the requests.
+    pub async fn start_servers(&self) -> Result<()> {
         futures::future::try_join_all(self.servers.values().map(start_server))
             .await
             .context(error::StartServerSnafu)
@@ -409,9 +590,77 @@ fn parse_stmt(sql: &str, dialect: &(dyn Dialect + Send + Sync)) -> Result<Vec<St
 }
 
 impl Instance {
+    /// Executes `stmt` on the ingest runtime if it's an insert, otherwise on the query
+    /// runtime, which also polls the stream of its output.
+    async fn query_statement_routed(
+        &self,
+        stmt: Statement,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let instance = self.clone();
+        if matches!(stmt, Statement::Insert(_)) {
+            return self
+                .workload_runtimes
+                .ingest(async move { instance.query_statement_cached(stmt, query_ctx).await })
+                .await?;
+        }
+
+        let output = self
+            .workload_runtimes
+            .query(async move { instance.query_statement_cached(stmt, query_ctx).await })
+            .await??;
+        Ok(self.workload_runtimes.relay(output))
+    }
+
+    async fn query_statement_cached(
+        &self,
+        stmt: Statement,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let Some(cache) = &self.query_cache else {
+            return self.query_statement(stmt, query_ctx).await;
+        };
+
+        if !query_cache::is_read_only(&stmt) {
+            let tables = query_cache::written_tables(&stmt, &query_ctx);
+            let result = self.query_statement(stmt, query_ctx).await;
+            match tables {
+                Some(tables) => cache.invalidate_tables(tables),
+                None => cache.invalidate_all(),
+            }
+            return result;
+        }
+
+        check_permission(self.plugins.clone(), &stmt, &query_ctx)?;
+        match cache.lookup(&stmt, &query_ctx) {
+            Lookup::Hit(output) => Ok(output),
+            Lookup::Miss(slot) => {
+                let output = self.execute_statement(stmt, query_ctx).await?;
+                Ok(cache.fill(slot, output))
+            }
+            Lookup::Bypass => self.execute_statement(stmt, query_ctx).await,
+        }
+    }
+
+    /// Admits a query by the concurrency limit if any, the statement if it's SQL.
+    async fn admit_query(&self, stmt: Option<&Statement>) -> Result<Option<Permit>> {
+        match &self.query_admission {
+            Some(admission) => admission.admit(stmt).await,
+            None => Ok(None),
+        }
+    }
+
     async fn query_statement(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
         check_permission(self.plugins.clone(), &stmt, &query_ctx)?;
+        self.execute_statement(stmt, query_ctx).await
+    }
 
+    /// Executes `stmt` whose permission is already checked.
+    async fn execute_statement(
+        &self,
+        stmt: Statement,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
         let stmt = QueryStatement::Sql(stmt);
         self.statement_executor
             .execute_stmt(stmt, query_ctx)
@@ -441,6 +690,10 @@ impl SqlQueryHandler for Instance {
         {
             Ok(stmts) => {
                 let mut results = Vec::with_capacity(stmts.len());
+                // The statements share the permit of the first one admitted, otherwise a
+                // statement waits for the unconsumed outputs of the earlier ones under the
+                // queue policy, which are only consumed after all of them are executed.
+                let mut permit = None;
                 for stmt in stmts {
                     // TODO(sunng87): figure out at which stage we can call
                     // this hook after ArrowFlight adoption. We need to provide
@@ -461,8 +714,31 @@ impl SqlQueryHandler for Instance {
                         break;
                     }
 
-                    match self.query_statement(stmt, query_ctx.clone()).await {
+                    if permit.is_none() {
+                        match self.admit_query(Some(&stmt)).await {
+                            Ok(admitted) => permit = admitted,
+                            Err(e) => {
+                                results.push(Err(e));
+                                break;
+                            }
+                        }
+                    }
+
+                    let start = Instant::now();
+                    // Only the statement itself, not the whole request of them.
+                    let statement = self.slow_query_logger.as_ref().map(|_| stmt.to_string());
+                    match self.query_statement_routed(stmt, query_ctx.clone()).await {
                         Ok(output) => {
+                            let output = match (&self.slow_query_logger, statement) {
+                                (Some(logger), Some(statement)) => {
+                                    logger.observe(statement, start, output, &query_ctx)
+                                }
+                                _ => output,
+                            };
+                            let output = match &permit {
+                                Some(permit) => permit.clone().hold_until_consumed(output),
+                                None => output,
+                            };
                             let output_result =
                                 query_interceptor.post_execute(output, query_ctx.clone());
                             results.push(output_result);
@@ -486,12 +762,20 @@ impl SqlQueryHandler for Instance {
 
     async fn do_exec_plan(&self, plan: LogicalPlan, query_ctx: QueryContextRef) -> Result<Output> {
         let _timer = metrics::METRIC_EXEC_PLAN_ELAPSED.start_timer();
+        let permit = self.admit_query(None).await?;
         // plan should be prepared before exec
         // we'll do check there
-        self.query_engine
-            .execute(plan, query_ctx)
-            .await
-            .context(ExecLogicalPlanSnafu)
+        let query_engine = self.query_engine.clone();
+        let output = self
+            .workload_runtimes
+            .query(async move { query_engine.execute(plan, query_ctx).await })
+            .await?
+            .context(ExecLogicalPlanSnafu)?;
+        let output = self.workload_runtimes.relay(output);
+        Ok(match permit {
+            Some(permit) => permit.hold_until_consumed(output),
+            None => output,
+        })
     }
 
     async fn do_promql_query(
@@ -517,6 +801,8 @@ impl SqlQueryHandler for Instance {
             stmt,
             Statement::Insert(_) | Statement::Query(_) | Statement::Delete(_)
         ) {
+            // Prepared statements are executed as plans, so writes are rejected here.
+            check_sql_write(&self.plugins, &stmt)?;
             self.plugins
                 .get::<PermissionCheckerRef>()
                 .as_ref()
@@ -570,14 +856,29 @@ impl PrometheusHandler for Instance {
             query: query.clone(),
         })?;
 
+        let permit = self
+            .admit_query(None)
+            .await
+            .map_err(BoxedError::new)
+            .with_context(|_| ExecuteQuerySnafu {
+                query: format!("{query:?}"),
+            })?;
+        let statement_executor = self.statement_executor.clone();
+        let ctx = query_ctx.clone();
         let output = self
-            .statement_executor
-            .execute_stmt(stmt, query_ctx.clone())
+            .workload_runtimes
+            .query(async move { statement_executor.execute_stmt(stmt, ctx).await })
             .await
+            .and_then(|result| result.context(TableOperationSnafu))
             .map_err(BoxedError::new)
             .with_context(|_| ExecuteQuerySnafu {
                 query: format!("{query:?}"),
             })?;
+        let output = self.workload_runtimes.relay(output);
+        let output = match permit {
+            Some(permit) => permit.hold_until_consumed(output),
+            None => output,
+        };
 
         Ok(interceptor.post_execute(output, query_ctx)?)
     }
@@ -592,6 +893,9 @@ pub fn check_permission(
     stmt: &Statement,
     query_ctx: &QueryContextRef,
 ) -> Result<()> {
+    check_sql_write(&plugins, stmt)?;
+    check_statement_kind(&plugins, stmt)?;
+
     let need_validate = plugins
         .get::<QueryOptions>()
         .map(|opts| opts.disallow_cross_schema_query)
@@ -645,6 +949,42 @@ pub fn check_permission(
     Ok(())
 }
 
+/// Rejects the `stmt` if it writes data into tables while SQL writes are denied.
+fn check_sql_write(plugins: &Plugins, stmt: &Statement) -> Result<()> {
+    let deny_sql_writes = plugins
+        .get::<QueryOptions>()
+        .map(|opts| opts.deny_sql_writes)
+        .unwrap_or_default();
+    let is_write = matches!(
+        stmt,
+        Statement::Insert(_)
+            | Statement::Delete(_)
+            | Statement::Copy(sql::statements::copy::Copy::CopyTable(CopyTable::From(_)))
+    );
+    ensure!(!(deny_sql_writes && is_write), SqlWriteDeniedSnafu);
+    Ok(())
+}
+
+/// Rejects the `stmt` if its kind isn't in the statement allowlist or is in the
+/// blocklist. The kind is taken from the parsed statement, so comments or odd
+/// spacing in the SQL text can't bypass the lists.
+fn check_statement_kind(plugins: &Plugins, stmt: &Statement) -> Result<()> {
+    let Some(opts) = plugins.get::<QueryOptions>() else {
+        return Ok(());
+    };
+    let kind = stmt.kind();
+    let allowed = opts
+        .statement_allowlist
+        .as_ref()
+        .map(|allowlist| allowlist.contains(&kind))
+        .unwrap_or(true);
+    ensure!(
+        allowed && !opts.statement_blocklist.contains(&kind),
+        StatementNotPermittedSnafu { kind }
+    );
+    Ok(())
+}
+
 fn validate_param(name: &ObjectName, query_ctx: &QueryContextRef) -> Result<()> {
     let (catalog, schema, _) = table_idents_to_full_name(name, query_ctx.clone())
         .map_err(BoxedError::new)
@@ -663,16 +1003,98 @@ mod tests {
     use query::query_engine::options::QueryOptions;
     use session::context::QueryContext;
     use sql::dialect::GreptimeDbDialect;
+    use sql::statements::statement::StatementKind;
     use strfmt::Format;
 
     use super::*;
 
+    #[test]
+    fn test_deny_sql_writes() {
+        let query_ctx = QueryContext::arc();
+        let plugins: Plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            deny_sql_writes: true,
+            ..Default::default()
+        });
+
+        let sql = r#"
+        SELECT * FROM demo;
+        COPY demo TO '/tmp/demo.parquet';
+        CREATE TABLE foo (ts TIMESTAMP TIME INDEX);
+        "#;
+        for stmt in parse_stmt(sql, &GreptimeDbDialect {}).unwrap() {
+            check_permission(plugins.clone(), &stmt, &query_ctx).unwrap();
+        }
+
+        let sql = r#"
+        INSERT INTO demo VALUES (1);
+        DELETE FROM demo WHERE ts = 1;
+        COPY demo FROM '/tmp/demo.parquet';
+        "#;
+        let stmts = parse_stmt(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(stmts.len(), 3);
+        for stmt in stmts {
+            let err = check_permission(plugins.clone(), &stmt, &query_ctx).unwrap_err();
+            assert!(matches!(err, Error::SqlWriteDenied { .. }), "{err:?}");
+        }
+
+        // SQL writes are allowed by default.
+        let stmts = parse_stmt("INSERT INTO demo VALUES (1)", &GreptimeDbDialect {}).unwrap();
+        check_permission(Plugins::new(), &stmts[0], &query_ctx).unwrap();
+    }
+
+    #[test]
+    fn test_statement_allowlist_and_blocklist() {
+        let query_ctx = QueryContext::arc();
+        let plugins: Plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            statement_allowlist: Some(vec![
+                StatementKind::Query,
+                StatementKind::Insert,
+                StatementKind::Delete,
+            ]),
+            statement_blocklist: vec![StatementKind::Delete, StatementKind::Alter],
+            ..Default::default()
+        });
+
+        let sql = r#"
+        SELECT * FROM demo;
+        /* ALTER TABLE demo ADD COLUMN c INT; */ INSERT INTO demo VALUES (1);
+        "#;
+        for stmt in parse_stmt(sql, &GreptimeDbDialect {}).unwrap() {
+            check_permission(plugins.clone(), &stmt, &query_ctx).unwrap();
+        }
+
+        // Denied by the blocklist, or absent from the allowlist.
+        let sql = r#"
+        DELETE FROM demo WHERE ts = 1;
+          alter   TABLE demo ADD COLUMN c INT;
+        -- SELECT 1;
+        DROP TABLE demo;
+        "#;
+        let stmts = parse_stmt(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(stmts.len(), 3);
+        for stmt in stmts {
+            let err = check_permission(plugins.clone(), &stmt, &query_ctx).unwrap_err();
+            assert!(
+                matches!(err, Error::StatementNotPermitted { kind, .. } if kind == stmt.kind()),
+                "{err:?}"
+            );
+        }
+        assert_eq!("drop_table", StatementKind::DropTable.to_string());
+
+        // All statements are allowed by default.
+        let stmts = parse_stmt("DROP TABLE demo", &GreptimeDbDialect {}).unwrap();
+        check_permission(Plugins::new(), &stmts[0], &query_ctx).unwrap();
+    }
+
     #[test]
     fn test_exec_validation() {
         let query_ctx = QueryContext::arc();
         let plugins: Plugins = Plugins::new();
         plugins.insert(QueryOptions {
             disallow_cross_schema_query: true,
+            ..Default::default()
         });
 
         let sql = r#"
@@ -20,6 +20,8 @@ use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
 use common_meta::table_name::TableName;
 use common_query::Output;
+use operator::float_special::{apply_float_special_policy, FloatSpecialPolicy};
+use operator::req_convert::insert::ColumnToRow;
 use query::parser::PromQuery;
 use servers::interceptor::{GrpcQueryInterceptor, GrpcQueryInterceptorRef};
 use servers::query_handler::grpc::GrpcQueryHandler;
@@ -31,6 +33,7 @@ use crate::error::{
     Error, IncompleteGrpcRequestSnafu, NotSupportedSnafu, PermissionSnafu, Result,
     TableOperationSnafu,
 };
+use crate::frontend::IngestProtocol;
 use crate::instance::Instance;
 
 #[async_trait]
@@ -100,7 +103,8 @@ impl GrpcQueryHandler for Instance {
 
                 fill_catalog_and_schema_from_context(&mut expr, &ctx);
 
-                match expr {
+                let table = ddl_table_name(&expr);
+                let output = match expr {
                     DdlExpr::CreateTable(mut expr) => {
                         // TODO(weny): supports to create multiple region table.
                         let _ = self
@@ -129,7 +133,9 @@ impl GrpcQueryHandler for Instance {
                             TableName::new(&expr.catalog_name, &expr.schema_name, &expr.table_name);
                         self.statement_executor.truncate_table(table_name).await?
                     }
-                }
+                };
+                self.invalidate_query_cache(table);
+                output
             }
         };
 
@@ -138,6 +144,33 @@ impl GrpcQueryHandler for Instance {
     }
 }
 
+/// Returns the name of the table changed by the DDL `expr`, if any.
+fn ddl_table_name(expr: &DdlExpr) -> Option<TableName> {
+    match expr {
+        Expr::CreateDatabase(_) => None,
+        Expr::CreateTable(expr) => Some(TableName::new(
+            &expr.catalog_name,
+            &expr.schema_name,
+            &expr.table_name,
+        )),
+        Expr::Alter(expr) => Some(TableName::new(
+            &expr.catalog_name,
+            &expr.schema_name,
+            &expr.table_name,
+        )),
+        Expr::DropTable(expr) => Some(TableName::new(
+            &expr.catalog_name,
+            &expr.schema_name,
+            &expr.table_name,
+        )),
+        Expr::TruncateTable(expr) => Some(TableName::new(
+            &expr.catalog_name,
+            &expr.schema_name,
+            &expr.table_name,
+        )),
+    }
+}
+
 fn fill_catalog_and_schema_from_context(ddl_expr: &mut DdlExpr, ctx: &QueryContextRef) {
     let catalog = ctx.current_catalog();
     let schema = ctx.current_schema();
@@ -176,10 +209,9 @@ impl Instance {
         requests: InsertRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
-        self.inserter
-            .handle_column_inserts(requests, ctx, self.statement_executor.as_ref())
+        let requests = ColumnToRow::convert(requests).context(TableOperationSnafu)?;
+        self.insert_rows(requests, ctx, self.float_special_policy(None))
             .await
-            .context(TableOperationSnafu)
     }
 
     pub async fn handle_row_inserts(
@@ -187,10 +219,59 @@ impl Instance {
         requests: RowInsertRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
-        self.inserter
-            .handle_row_inserts(requests, ctx, self.statement_executor.as_ref())
+        self.insert_rows(requests, ctx, self.float_special_policy(None))
             .await
-            .context(TableOperationSnafu)
+    }
+
+    /// Inserts the rows written by the ingest `protocol`.
+    pub async fn handle_protocol_row_inserts(
+        &self,
+        requests: RowInsertRequests,
+        ctx: QueryContextRef,
+        protocol: IngestProtocol,
+    ) -> Result<Output> {
+        self.insert_rows(requests, ctx, self.float_special_policy(Some(protocol)))
+            .await
+    }
+
+    /// Returns the policy of the NaN and infinite floats written by the `protocol`, or
+    /// by gRPC if `None`.
+    fn float_special_policy(&self, protocol: Option<IngestProtocol>) -> FloatSpecialPolicy {
+        self.plugins.get::<FloatSpecialPolicy>().unwrap_or_else(|| {
+            protocol
+                .map(|protocol| protocol.default_float_special_policy())
+                .unwrap_or_default()
+        })
+    }
+
+    async fn insert_rows(
+        &self,
+        mut requests: RowInsertRequests,
+        ctx: QueryContextRef,
+        policy: FloatSpecialPolicy,
+    ) -> Result<Output> {
+        for request in &mut requests.inserts {
+            if let Some(rows) = &mut request.rows {
+                apply_float_special_policy(policy, rows).context(TableOperationSnafu)?;
+            }
+        }
+        if let Some(estimator) = &self.cardinality_estimator {
+            estimator.observe(&requests, &ctx);
+        }
+        let tables = self.changed_tables(requests.inserts.iter().map(|r| &r.table_name), &ctx);
+        let instance = self.clone();
+        let output = self
+            .workload_runtimes
+            .ingest(async move {
+                instance
+                    .inserter
+                    .handle_row_inserts(requests, ctx, instance.statement_executor.as_ref())
+                    .await
+            })
+            .await?
+            .context(TableOperationSnafu)?;
+        self.invalidate_query_cache(tables);
+        Ok(output)
     }
 
     pub async fn handle_deletes(
@@ -198,10 +279,15 @@ impl Instance {
         requests: DeleteRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
-        self.deleter
-            .handle_column_deletes(requests, ctx)
-            .await
-            .context(TableOperationSnafu)
+        let tables = self.changed_tables(requests.deletes.iter().map(|r| &r.table_name), &ctx);
+        let deleter = self.deleter.clone();
+        let output = self
+            .workload_runtimes
+            .ingest(async move { deleter.handle_column_deletes(requests, ctx).await })
+            .await?
+            .context(TableOperationSnafu)?;
+        self.invalidate_query_cache(tables);
+        Ok(output)
     }
 
     pub async fn handle_row_deletes(
@@ -209,9 +295,29 @@ impl Instance {
         requests: RowDeleteRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
-        self.deleter
-            .handle_row_deletes(requests, ctx)
-            .await
-            .context(TableOperationSnafu)
+        let tables = self.changed_tables(requests.deletes.iter().map(|r| &r.table_name), &ctx);
+        let deleter = self.deleter.clone();
+        let output = self
+            .workload_runtimes
+            .ingest(async move { deleter.handle_row_deletes(requests, ctx).await })
+            .await?
+            .context(TableOperationSnafu)?;
+        self.invalidate_query_cache(tables);
+        Ok(output)
+    }
+
+    /// Returns the tables in the current schema of `ctx` written by the requests, empty if
+    /// there's no query cache to invalidate.
+    fn changed_tables<'a>(
+        &self,
+        table_names: impl Iterator<Item = &'a String>,
+        ctx: &QueryContextRef,
+    ) -> Vec<TableName> {
+        if self.query_cache.is_none() {
+            return Vec::new();
+        }
+        table_names
+            .map(|table| TableName::new(ctx.current_catalog(), ctx.current_schema(), table))
+            .collect()
     }
 }
@@ -16,11 +16,12 @@ use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
 use common_error::ext::BoxedError;
 use servers::error::AuthSnafu;
-use servers::influxdb::InfluxdbRequest;
+use servers::influxdb::{self, InfluxdbRequest, TimeIndexOptions};
 use servers::query_handler::InfluxdbLineProtocolHandler;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
 
+use crate::frontend::IngestProtocol;
 use crate::instance::Instance;
 
 #[async_trait]
@@ -36,9 +37,10 @@ impl InfluxdbLineProtocolHandler for Instance {
             .check_permission(ctx.current_user(), PermissionReq::LineProtocol)
             .context(AuthSnafu)?;
 
-        let requests = request.try_into()?;
+        let time_index = self.plugins.get::<TimeIndexOptions>().unwrap_or_default();
+        let requests = influxdb::to_row_insert_requests(request, &time_index)?;
         let _ = self
-            .handle_row_inserts(requests, ctx)
+            .handle_protocol_row_inserts(requests, ctx, IngestProtocol::Influxdb)
             .await
             .map_err(BoxedError::new)
             .context(servers::error::ExecuteGrpcQuerySnafu)?;
@@ -23,6 +23,7 @@ use servers::query_handler::OpentsdbProtocolHandler;
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 
+use crate::frontend::IngestProtocol;
 use crate::instance::Instance;
 
 #[async_trait]
@@ -40,7 +41,7 @@ impl OpentsdbProtocolHandler for Instance {
 
         let (requests, _) = data_point_to_grpc_row_insert_requests(data_points)?;
         let output = self
-            .handle_row_inserts(requests, ctx)
+            .handle_protocol_row_inserts(requests, ctx, IngestProtocol::Opentsdb)
             .await
             .map_err(BoxedError::new)
             .context(servers::error::ExecuteGrpcQuerySnafu)?;
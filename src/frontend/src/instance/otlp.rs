@@ -28,6 +28,7 @@ use servers::query_handler::OpenTelemetryProtocolHandler;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
 
+use crate::frontend::IngestProtocol;
 use crate::instance::Instance;
 use crate::metrics::{OTLP_METRICS_ROWS, OTLP_TRACES_ROWS};
 
@@ -45,7 +46,7 @@ impl OpenTelemetryProtocolHandler for Instance {
             .context(AuthSnafu)?;
         let (requests, rows) = otlp::metrics::to_grpc_insert_requests(request)?;
         let _ = self
-            .handle_row_inserts(requests, ctx)
+            .handle_protocol_row_inserts(requests, ctx, IngestProtocol::Otlp)
             .await
             .map_err(BoxedError::new)
             .context(error::ExecuteGrpcQuerySnafu)?;
@@ -81,7 +82,7 @@ impl OpenTelemetryProtocolHandler for Instance {
         let (requests, rows) = otlp::trace::to_grpc_insert_requests(table_name, spans)?;
 
         let _ = self
-            .handle_row_inserts(requests, ctx)
+            .handle_protocol_row_inserts(requests, ctx, IngestProtocol::Otlp)
             .await
             .map_err(BoxedError::new)
             .context(error::ExecuteGrpcQuerySnafu)?;
@@ -12,26 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use api::prom_store::remote::read_request::ResponseType;
 use api::prom_store::remote::{Query, QueryResult, ReadRequest, ReadResponse, WriteRequest};
+use api::v1::RowInsertRequests;
 use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
+use common_catalog::consts::default_engine;
 use common_catalog::format_full_table_name;
 use common_error::ext::BoxedError;
 use common_query::Output;
 use common_recordbatch::RecordBatches;
+use common_runtime::{RepeatedTask, TaskFunction};
 use common_telemetry::logging;
+use common_time::Timestamp;
+use humantime_serde::re::humantime;
+use operator::expr_factory::CreateExprFactory;
 use prost::Message;
 use servers::error::{self, AuthSnafu, Result as ServerResult};
 use servers::prom_store::{self, Metrics};
 use servers::query_handler::{PromStoreProtocolHandler, PromStoreResponse};
-use session::context::QueryContextRef;
+use session::context::{QueryContext, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
+use table::engine::TableReference;
+use table::requests::TTL_KEY;
 
+use crate::downsample::Downsampler;
 use crate::error::{
-    CatalogSnafu, ExecLogicalPlanSnafu, PromStoreRemoteQueryPlanSnafu, ReadTableSnafu, Result,
-    TableNotFoundSnafu,
+    CatalogSnafu, ConvertPromStoreRequestSnafu, Error, ExecLogicalPlanSnafu,
+    PromStoreRemoteQueryPlanSnafu, ReadTableSnafu, Result, TableNotFoundSnafu, TableOperationSnafu,
 };
+use crate::frontend::IngestProtocol;
 use crate::instance::Instance;
 use crate::metrics::PROM_STORE_REMOTE_WRITE_SAMPLES;
 
@@ -145,6 +157,121 @@ impl Instance {
     }
 }
 
+/// Returns the task writing the rollups of the closed downsampling buckets every
+/// `interval`.
+pub(crate) fn new_drain_rollups_task(
+    instance: Instance,
+    interval: Duration,
+) -> RepeatedTask<Error> {
+    RepeatedTask::new(interval, Box::new(DrainRollupsTask { instance }))
+}
+
+struct DrainRollupsTask {
+    instance: Instance,
+}
+
+#[async_trait]
+impl TaskFunction<Error> for DrainRollupsTask {
+    async fn call(&mut self) -> Result<()> {
+        self.instance
+            .drain_rollups(Some(Timestamp::current_millis().value()))
+            .await
+    }
+
+    fn name(&self) -> &str {
+        "DrainRollupsTask"
+    }
+}
+
+impl Instance {
+    async fn write_prom_rows(
+        &self,
+        requests: RowInsertRequests,
+        ctx: &QueryContextRef,
+    ) -> Result<()> {
+        if let Some(downsampler) = &self.prom_store_downsampler {
+            self.create_tables_with_ttl(downsampler, &requests, ctx)
+                .await?;
+        }
+        let _ = self
+            .handle_protocol_row_inserts(requests, ctx.clone(), IngestProtocol::PromStore)
+            .await?;
+        Ok(())
+    }
+
+    async fn write_rollups(&self, rollups: WriteRequest, ctx: &QueryContextRef) -> Result<()> {
+        if rollups.timeseries.is_empty() {
+            return Ok(());
+        }
+        let (requests, _) = prom_store::to_grpc_row_insert_requests(rollups)
+            .context(ConvertPromStoreRequestSnafu)?;
+        self.write_prom_rows(requests, ctx).await
+    }
+
+    /// Writes the rollups of the downsampling buckets closed at `now` (in
+    /// milliseconds), or of all buckets if `now` is `None`.
+    pub(crate) async fn drain_rollups(&self, now: Option<i64>) -> Result<()> {
+        let Some(downsampler) = &self.prom_store_downsampler else {
+            return Ok(());
+        };
+        for rollups in downsampler.drain(now) {
+            let ctx = QueryContext::with(&rollups.catalog, &rollups.schema);
+            if let Err(e) = self.write_rollups(rollups.request, &ctx).await {
+                logging::warn!(
+                    e; "Failed to write the rollups of {}.{}",
+                    rollups.catalog, rollups.schema
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the tables that downsampling rules configure a TTL for, since
+    /// tables created on insertion don't carry any table options.
+    async fn create_tables_with_ttl(
+        &self,
+        downsampler: &Downsampler,
+        requests: &RowInsertRequests,
+        ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let catalog = ctx.current_catalog();
+        let schema = ctx.current_schema();
+        for request in &requests.inserts {
+            let Some(ttl) = downsampler.table_ttl(&request.table_name) else {
+                continue;
+            };
+            if self
+                .catalog_manager
+                .table_exists(catalog, schema, &request.table_name)
+                .await
+                .context(CatalogSnafu)?
+            {
+                continue;
+            }
+
+            let table_ref = TableReference::full(catalog, schema, &request.table_name);
+            let column_schemas = request
+                .rows
+                .as_ref()
+                .map(|rows| rows.schema.as_slice())
+                .unwrap_or_default();
+            let mut expr = CreateExprFactory
+                .create_table_expr_by_column_schemas(&table_ref, column_schemas, default_engine())
+                .context(TableOperationSnafu)?;
+            let _ = expr.table_options.insert(
+                TTL_KEY.to_string(),
+                humantime::format_duration(ttl).to_string(),
+            );
+            let _ = self
+                .statement_executor
+                .create_table_inner(&mut expr, None)
+                .await
+                .context(TableOperationSnafu)?;
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl PromStoreProtocolHandler for Instance {
     async fn write(&self, request: WriteRequest, ctx: QueryContextRef) -> ServerResult<()> {
@@ -153,13 +280,25 @@ impl PromStoreProtocolHandler for Instance {
             .as_ref()
             .check_permission(ctx.current_user(), PermissionReq::PromStoreWrite)
             .context(AuthSnafu)?;
+        let downsampled = self
+            .prom_store_downsampler
+            .as_ref()
+            .map(|downsampler| downsampler.select(&request));
+
         let (requests, samples) = prom_store::to_grpc_row_insert_requests(request)?;
-        let _ = self
-            .handle_row_inserts(requests, ctx)
+        self.write_prom_rows(requests, &ctx)
             .await
             .map_err(BoxedError::new)
             .context(error::ExecuteGrpcQuerySnafu)?;
 
+        if let (Some(downsampler), Some(series)) = (&self.prom_store_downsampler, downsampled) {
+            let rollups = downsampler.ingest(&series, ctx.current_catalog(), ctx.current_schema());
+            self.write_rollups(rollups, &ctx)
+                .await
+                .map_err(BoxedError::new)
+                .context(error::ExecuteGrpcQuerySnafu)?;
+        }
+
         PROM_STORE_REMOTE_WRITE_SAMPLES.inc_by(samples as u64);
         Ok(())
     }
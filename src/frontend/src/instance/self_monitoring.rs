@@ -0,0 +1,258 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self-monitoring, which writes the metrics of the instance into a table periodically,
+//! so they can be queried by SQL without an external Prometheus.
+//!
+//! The rows are written into the inserter directly, bypassing the protocol handlers,
+//! so they are neither counted by the ingest metrics and the cardinality estimation
+//! nor limited like the ingest requests. The number of rows written each time is the
+//! number of series of the instance, which doesn't grow with the writes themselves.
+
+use api::v1::value::ValueData;
+use api::v1::{
+    ColumnDataType, ColumnSchema, Row, RowInsertRequest, RowInsertRequests, Rows, SemanticType,
+    Value,
+};
+use async_trait::async_trait;
+use common_catalog::consts::DEFAULT_CATALOG_NAME;
+use common_runtime::{RepeatedTask, TaskFunction};
+use common_telemetry::logging::info;
+use common_telemetry::metric;
+use common_time::Timestamp;
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+use servers::prom_store::{FIELD_COLUMN_NAME, TIMESTAMP_COLUMN_NAME};
+use session::context::QueryContext;
+use snafu::ResultExt;
+
+use crate::error::{Error, Result, TableOperationSnafu};
+use crate::instance::Instance;
+use crate::service_config::SelfMonitoringOptions;
+
+/// Column of the metric names.
+const METRIC_COLUMN_NAME: &str = "metric";
+/// Column of the labels of a series, like `a="x",b="y"`.
+const LABELS_COLUMN_NAME: &str = "labels";
+
+/// Returns the task writing the metrics of the instance every `opts.interval`.
+pub(crate) fn new_self_monitoring_task(
+    instance: Instance,
+    opts: SelfMonitoringOptions,
+) -> RepeatedTask<Error> {
+    info!(
+        "Writing self-monitoring metrics into {}.{} every {:?}",
+        opts.database, opts.table, opts.interval
+    );
+    RepeatedTask::new(
+        opts.interval,
+        Box::new(SelfMonitoringTask {
+            instance,
+            opts,
+            database_created: false,
+        }),
+    )
+}
+
+struct SelfMonitoringTask {
+    instance: Instance,
+    opts: SelfMonitoringOptions,
+    database_created: bool,
+}
+
+#[async_trait]
+impl TaskFunction<Error> for SelfMonitoringTask {
+    async fn call(&mut self) -> Result<()> {
+        if !self.database_created {
+            let _ = self
+                .instance
+                .statement_executor
+                .create_database(DEFAULT_CATALOG_NAME, &self.opts.database, true)
+                .await
+                .context(TableOperationSnafu)?;
+            self.database_created = true;
+        }
+        self.instance.write_self_metrics(&self.opts).await
+    }
+
+    fn name(&self) -> &str {
+        "SelfMonitoringTask"
+    }
+}
+
+impl Instance {
+    /// Writes the metrics of the instance. The query cache isn't invalidated, so the
+    /// cached results of the queries on the table may lag until they expire.
+    async fn write_self_metrics(&self, opts: &SelfMonitoringOptions) -> Result<()> {
+        let rows = metric_rows(&metric::gather(), Timestamp::current_millis().value());
+        let requests = RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: opts.table.clone(),
+                rows: Some(rows),
+            }],
+        };
+        let ctx = QueryContext::with(DEFAULT_CATALOG_NAME, &opts.database);
+        let _ = self
+            .inserter
+            .handle_row_inserts(requests, ctx, self.statement_executor.as_ref())
+            .await
+            .context(TableOperationSnafu)?;
+        Ok(())
+    }
+}
+
+/// Converts the metric families to rows, one for each series. Histograms and summaries
+/// only keep their `_sum` and `_count` series.
+fn metric_rows(families: &[MetricFamily], timestamp_millis: i64) -> Rows {
+    let schema = vec![
+        new_column(
+            METRIC_COLUMN_NAME,
+            ColumnDataType::String,
+            SemanticType::Tag,
+        ),
+        new_column(
+            LABELS_COLUMN_NAME,
+            ColumnDataType::String,
+            SemanticType::Tag,
+        ),
+        new_column(
+            FIELD_COLUMN_NAME,
+            ColumnDataType::Float64,
+            SemanticType::Field,
+        ),
+        new_column(
+            TIMESTAMP_COLUMN_NAME,
+            ColumnDataType::TimestampMillisecond,
+            SemanticType::Timestamp,
+        ),
+    ];
+
+    let mut rows = Vec::new();
+    for family in families {
+        let name = family.get_name();
+        for metric in family.get_metric() {
+            let labels = format_labels(metric.get_label());
+            let mut push = |metric: String, value: f64| {
+                rows.push(Row {
+                    values: vec![
+                        new_value(ValueData::StringValue(metric)),
+                        new_value(ValueData::StringValue(labels.clone())),
+                        new_value(ValueData::F64Value(value)),
+                        new_value(ValueData::TimestampMillisecondValue(timestamp_millis)),
+                    ],
+                })
+            };
+            match family.get_field_type() {
+                MetricType::COUNTER => push(name.to_string(), metric.get_counter().get_value()),
+                MetricType::GAUGE => push(name.to_string(), metric.get_gauge().get_value()),
+                MetricType::UNTYPED => push(name.to_string(), metric.get_untyped().get_value()),
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    push(format!("{name}_sum"), histogram.get_sample_sum());
+                    push(format!("{name}_count"), histogram.get_sample_count() as f64);
+                }
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    push(format!("{name}_sum"), summary.get_sample_sum());
+                    push(format!("{name}_count"), summary.get_sample_count() as f64);
+                }
+            }
+        }
+    }
+    Rows { schema, rows }
+}
+
+fn format_labels(labels: &[LabelPair]) -> String {
+    labels
+        .iter()
+        .map(|label| format!("{}={:?}", label.get_name(), label.get_value()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn new_column(name: &str, datatype: ColumnDataType, semantic_type: SemanticType) -> ColumnSchema {
+    ColumnSchema {
+        column_name: name.to_string(),
+        datatype: datatype as i32,
+        semantic_type: semantic_type as i32,
+    }
+}
+
+fn new_value(value_data: ValueData) -> Value {
+    Value {
+        value_data: Some(value_data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+
+    use super::*;
+
+    fn row_strings(rows: &Rows) -> Vec<(String, String, f64)> {
+        rows.rows
+            .iter()
+            .map(|row| {
+                let string = |i: usize| match &row.values[i].value_data {
+                    Some(ValueData::StringValue(s)) => s.clone(),
+                    _ => unreachable!(),
+                };
+                let Some(ValueData::F64Value(value)) = row.values[2].value_data else {
+                    unreachable!()
+                };
+                (string(0), string(1), value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_metric_rows() {
+        let registry = Registry::new();
+        let counter =
+            IntCounterVec::new(Opts::new("test_requests", "help"), &["method", "path"]).unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        counter.with_label_values(&["GET", "/v1/sql"]).inc_by(3);
+        let histogram =
+            HistogramVec::new(HistogramOpts::new("test_elapsed", "help"), &["path"]).unwrap();
+        registry.register(Box::new(histogram.clone())).unwrap();
+        histogram.with_label_values(&["/metrics"]).observe(0.5);
+        histogram.with_label_values(&["/metrics"]).observe(1.5);
+
+        let rows = metric_rows(&registry.gather(), 1000);
+        assert_eq!(4, rows.schema.len());
+        assert_eq!(
+            vec![
+                (
+                    "test_elapsed_sum".to_string(),
+                    "path=\"/metrics\"".to_string(),
+                    2.0
+                ),
+                (
+                    "test_elapsed_count".to_string(),
+                    "path=\"/metrics\"".to_string(),
+                    2.0
+                ),
+                (
+                    "test_requests".to_string(),
+                    "method=\"GET\",path=\"/v1/sql\"".to_string(),
+                    3.0
+                ),
+            ],
+            row_strings(&rows)
+        );
+        assert!(rows.rows.iter().all(
+            |row| row.values[3].value_data == Some(ValueData::TimestampMillisecondValue(1000))
+        ));
+    }
+}
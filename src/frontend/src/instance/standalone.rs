@@ -30,6 +30,7 @@ use common_recordbatch::SendableRecordBatchStream;
 use datanode::region_server::RegionServer;
 use servers::grpc::region_server::RegionServerHandler;
 use snafu::{OptionExt, ResultExt};
+use store_api::region_request::WriteAckLevel;
 use store_api::storage::{RegionId, TableId};
 use table::metadata::RawTableInfo;
 
@@ -56,13 +57,17 @@ impl RegionInvoker {
         Arc::new(Self { region_server })
     }
 
-    async fn handle_inner(&self, request: RegionRequest) -> Result<RegionResponse> {
+    async fn handle_inner(
+        &self,
+        request: RegionRequest,
+        ack_level: WriteAckLevel,
+    ) -> Result<RegionResponse> {
         let body = request.body.with_context(|| InvalidRegionRequestSnafu {
             reason: "body not found",
         })?;
 
         self.region_server
-            .handle(body)
+            .handle(body, ack_level)
             .await
             .context(InvokeRegionServerSnafu)
     }
@@ -71,8 +76,16 @@ impl RegionInvoker {
 #[async_trait]
 impl Datanode for RegionInvoker {
     async fn handle(&self, request: RegionRequest) -> MetaResult<AffectedRows> {
+        self.handle_write(request, WriteAckLevel::default()).await
+    }
+
+    async fn handle_write(
+        &self,
+        request: RegionRequest,
+        ack_level: WriteAckLevel,
+    ) -> MetaResult<AffectedRows> {
         let response = self
-            .handle_inner(request)
+            .handle_inner(request, ack_level)
             .await
             .map_err(BoxedError::new)
             .context(meta_error::ExternalSnafu)?;
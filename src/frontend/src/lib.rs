@@ -15,11 +15,17 @@
 #![feature(assert_matches)]
 #![feature(trait_upcasting)]
 
+mod admission;
+mod cardinality;
+mod downsample;
 pub mod error;
 pub mod frontend;
 pub mod heartbeat;
 pub mod instance;
 pub(crate) mod metrics;
+pub mod query_cache;
 mod script;
 mod server;
 pub mod service_config;
+mod slow_query;
+mod workload;
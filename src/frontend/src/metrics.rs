@@ -38,11 +38,39 @@ lazy_static! {
         "frontend prometheus remote write samples"
     )
     .unwrap();
+    /// The samples dropped by downsampling because their bucket had been written.
+    pub static ref PROM_STORE_DOWNSAMPLING_LATE_SAMPLES: IntCounter = register_int_counter!(
+        "frontend_prometheus_downsampling_late_samples",
+        "frontend prometheus downsampling late samples"
+    )
+    .unwrap();
     pub static ref OTLP_METRICS_ROWS: IntCounter = register_int_counter!(
         "frontend_otlp_metrics_rows",
         "frontend otlp metrics rows"
     )
     .unwrap();
+    pub static ref METRIC_QUERY_CACHE_HIT: IntCounter = register_int_counter!(
+        "frontend_query_cache_hit",
+        "frontend query result cache hit"
+    )
+    .unwrap();
+    pub static ref METRIC_QUERY_CACHE_MISS: IntCounter = register_int_counter!(
+        "frontend_query_cache_miss",
+        "frontend query result cache miss"
+    )
+    .unwrap();
+    /// The queries waiting for a running query to finish beyond the concurrency limit.
+    pub static ref METRIC_QUERY_ADMISSION_QUEUED: IntCounter = register_int_counter!(
+        "frontend_query_admission_queued",
+        "frontend queries queued by the concurrency limit"
+    )
+    .unwrap();
+    /// The queries rejected beyond the concurrency limit.
+    pub static ref METRIC_QUERY_ADMISSION_REJECTED: IntCounter = register_int_counter!(
+        "frontend_query_admission_rejected",
+        "frontend queries rejected by the concurrency limit"
+    )
+    .unwrap();
     pub static ref OTLP_TRACES_ROWS: IntCounter = register_int_counter!(
         "frontend_otlp_traces_rows",
         "frontend otlp traces rows"
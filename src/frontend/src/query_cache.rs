@@ -0,0 +1,520 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A result cache for repeated read-only SQL queries, such as the ones issued
+//! by dashboards refreshing the same panels.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use common_meta::table_name::TableName;
+use common_query::Output;
+use common_recordbatch::{
+    RecordBatch, RecordBatchMetrics, RecordBatchStream, RecordBatches, SendableRecordBatchStream,
+};
+use common_telemetry::warn;
+use datatypes::schema::SchemaRef;
+use futures::Stream;
+use moka::sync::Cache;
+use operator::table::table_idents_to_full_name;
+use session::context::QueryContextRef;
+use sql::statements::copy::{self, CopyTable};
+use sql::statements::query::Query;
+use sql::statements::statement::Statement;
+use sqlparser::ast::{visit_expressions, visit_relations, Expr, ObjectName};
+
+use crate::metrics;
+use crate::service_config::QueryCacheOptions;
+
+/// Functions whose results differ between executions, queries calling any of
+/// them are never cached.
+const NON_DETERMINISTIC_FUNCTIONS: &[&str] = &[
+    "now",
+    "current_timestamp",
+    "current_time",
+    "current_date",
+    "localtime",
+    "localtimestamp",
+    "random",
+    "rand",
+    "uuid",
+];
+
+pub type QueryResultCacheRef = Arc<QueryResultCache>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QueryCacheKey {
+    catalog: String,
+    schema: String,
+    time_zone: Option<String>,
+    /// The normalized query, produced by displaying the parsed statement.
+    query: String,
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    /// The tables read by the query.
+    tables: HashSet<TableName>,
+}
+
+/// The cache entry to fill with the output of a query missing in the cache.
+pub struct CacheSlot {
+    key: QueryCacheKey,
+    tables: HashSet<TableName>,
+    /// The generation of the cache when the query started, results are dropped
+    /// if any table is invalidated while the query is executing.
+    generation: u64,
+}
+
+/// The result of looking up a statement in the cache.
+pub enum Lookup {
+    Hit(Output),
+    Miss(CacheSlot),
+    /// The statement must be executed without being cached.
+    Bypass,
+}
+
+/// Caches the outputs of read-only queries, bounded by their memory size and TTL.
+///
+/// Writes and DDL going through the owning frontend invalidate the results
+/// reading the affected tables. Changes made elsewhere (e.g. by another frontend)
+/// may be served stale for at most the configured TTL.
+pub struct QueryResultCache {
+    handle: CacheHandle,
+    max_result_size: usize,
+}
+
+impl QueryResultCache {
+    pub fn new(opts: &QueryCacheOptions) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(opts.size.as_bytes())
+            .weigher(|key: &QueryCacheKey, value: &Arc<CachedResult>| {
+                (key.query.len() + batches_size(&value.batches)).min(u32::MAX as usize) as u32
+            })
+            .time_to_live(opts.ttl)
+            .support_invalidation_closures()
+            .build();
+        Self {
+            handle: CacheHandle {
+                cache,
+                generation: Arc::new(AtomicU64::new(0)),
+            },
+            max_result_size: opts.max_result_size.as_bytes() as usize,
+        }
+    }
+
+    /// Creates the cache if it's enabled in `opts`.
+    pub fn from_options(opts: &QueryCacheOptions) -> Option<QueryResultCacheRef> {
+        opts.enable.then(|| Arc::new(Self::new(opts)))
+    }
+
+    /// Looks up the cached output of `stmt`.
+    pub fn lookup(&self, stmt: &Statement, query_ctx: &QueryContextRef) -> Lookup {
+        let Statement::Query(query) = stmt else {
+            return Lookup::Bypass;
+        };
+        // The execution metrics asked for are only reported by executing the query.
+        if query_ctx.explain_analyze() || is_non_deterministic(query) {
+            return Lookup::Bypass;
+        }
+        let Some(tables) = read_tables(query, query_ctx) else {
+            return Lookup::Bypass;
+        };
+
+        let key = QueryCacheKey {
+            catalog: query_ctx.current_catalog().to_string(),
+            schema: query_ctx.current_schema().to_string(),
+            time_zone: query_ctx.time_zone().map(|tz| tz.to_string()),
+            query: stmt.to_string(),
+        };
+        let generation = self.handle.generation.load(Ordering::Acquire);
+        let Some(cached) = self.handle.cache.get(&key) else {
+            metrics::METRIC_QUERY_CACHE_MISS.inc();
+            return Lookup::Miss(CacheSlot {
+                key,
+                tables,
+                generation,
+            });
+        };
+        metrics::METRIC_QUERY_CACHE_HIT.inc();
+
+        // The batches were collected under this schema, so this never fails.
+        match RecordBatches::try_new(cached.schema.clone(), cached.batches.clone()) {
+            Ok(batches) => Lookup::Hit(Output::RecordBatches(batches)),
+            Err(_) => Lookup::Bypass,
+        }
+    }
+
+    /// Caches `output` into the `slot` unless it exceeds `max_result_size`, and
+    /// returns an equivalent output to the caller.
+    ///
+    /// Streaming outputs are cached once they are fully consumed, batches are
+    /// collected only until they exceed `max_result_size`.
+    pub fn fill(&self, slot: CacheSlot, output: Output) -> Output {
+        match output {
+            Output::AffectedRows(_) => output,
+            Output::RecordBatches(batches) => {
+                let batches_vec = batches.iter().cloned().collect::<Vec<_>>();
+                if batches_size(&batches_vec) <= self.max_result_size {
+                    self.handle.insert(slot, batches.schema(), batches_vec);
+                }
+                Output::RecordBatches(batches)
+            }
+            Output::Stream(stream) => Output::Stream(Box::pin(CachingStream {
+                inner: stream,
+                cache: self.handle.clone(),
+                slot: Some(slot),
+                batches: Vec::new(),
+                size: 0,
+                max_result_size: self.max_result_size,
+            })),
+        }
+    }
+
+    /// Drops the cached results reading any of `tables`.
+    pub fn invalidate_tables(&self, tables: impl IntoIterator<Item = TableName>) {
+        let tables = tables.into_iter().collect::<HashSet<_>>();
+        if tables.is_empty() {
+            return;
+        }
+        let _ = self.handle.generation.fetch_add(1, Ordering::AcqRel);
+        let result = self
+            .handle
+            .cache
+            .invalidate_entries_if(move |_, cached| !cached.tables.is_disjoint(&tables));
+        if let Err(e) = result {
+            warn!(e; "Failed to invalidate the query cache by tables, invalidating all");
+            self.handle.cache.invalidate_all();
+        }
+    }
+
+    /// Drops all cached results, called when the tables changed are unknown.
+    pub fn invalidate_all(&self) {
+        let _ = self.handle.generation.fetch_add(1, Ordering::AcqRel);
+        self.handle.cache.invalidate_all();
+    }
+}
+
+#[derive(Clone)]
+struct CacheHandle {
+    cache: Cache<QueryCacheKey, Arc<CachedResult>>,
+    generation: Arc<AtomicU64>,
+}
+
+impl CacheHandle {
+    fn insert(&self, slot: CacheSlot, schema: SchemaRef, batches: Vec<RecordBatch>) {
+        // The tables may have changed while the query was executing.
+        if self.generation.load(Ordering::Acquire) != slot.generation {
+            return;
+        }
+        let cached = CachedResult {
+            schema,
+            batches,
+            tables: slot.tables,
+        };
+        self.cache.insert(slot.key, Arc::new(cached));
+    }
+}
+
+/// Passes the batches of a stream through and caches them once the stream ends.
+struct CachingStream {
+    inner: SendableRecordBatchStream,
+    cache: CacheHandle,
+    /// The slot to fill, taken once the result is cached or becomes uncacheable.
+    slot: Option<CacheSlot>,
+    batches: Vec<RecordBatch>,
+    size: usize,
+    max_result_size: usize,
+}
+
+impl RecordBatchStream for CachingStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.inner.metrics()
+    }
+}
+
+impl Stream for CachingStream {
+    type Item = common_recordbatch::error::Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let poll = this.inner.as_mut().poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(batch))) if this.slot.is_some() => {
+                this.size += batch_size(batch);
+                if this.size > this.max_result_size {
+                    this.slot = None;
+                    this.batches.clear();
+                } else {
+                    this.batches.push(batch.clone());
+                }
+            }
+            Poll::Ready(Some(Err(_))) => {
+                this.slot = None;
+                this.batches.clear();
+            }
+            Poll::Ready(None) => {
+                if let Some(slot) = this.slot.take() {
+                    let batches = std::mem::take(&mut this.batches);
+                    this.cache.insert(slot, this.inner.schema(), batches);
+                }
+            }
+            _ => {}
+        }
+        poll
+    }
+}
+
+fn batch_size(batch: &RecordBatch) -> usize {
+    batch.df_record_batch().get_array_memory_size()
+}
+
+fn batches_size(batches: &[RecordBatch]) -> usize {
+    batches.iter().map(batch_size).sum()
+}
+
+fn is_non_deterministic(query: &Query) -> bool {
+    visit_expressions(&query.inner, |expr| match expr {
+        Expr::Function(func) if is_non_deterministic_function(&func.name) => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    })
+    .is_break()
+}
+
+fn is_non_deterministic_function(name: &ObjectName) -> bool {
+    name.0.last().is_some_and(|ident| {
+        NON_DETERMINISTIC_FUNCTIONS.contains(&ident.value.to_lowercase().as_str())
+    })
+}
+
+/// Returns the tables read by `query`, or `None` if any table name is invalid.
+fn read_tables(query: &Query, query_ctx: &QueryContextRef) -> Option<HashSet<TableName>> {
+    let mut tables = HashSet::new();
+    let flow = visit_relations(&query.inner, |name| match to_table_name(name, query_ctx) {
+        Some(table) => {
+            let _ = tables.insert(table);
+            ControlFlow::Continue(())
+        }
+        None => ControlFlow::Break(()),
+    });
+    (!flow.is_break()).then_some(tables)
+}
+
+/// Returns the tables whose data or schema may be changed by `stmt`, or `None`
+/// if they are unknown.
+pub(crate) fn written_tables(
+    stmt: &Statement,
+    query_ctx: &QueryContextRef,
+) -> Option<Vec<TableName>> {
+    let name = match stmt {
+        Statement::Insert(insert) => insert.table_name(),
+        Statement::Delete(delete) => {
+            let mut tables = Vec::new();
+            let flow =
+                visit_relations(&delete.inner, |name| match to_table_name(name, query_ctx) {
+                    Some(table) => {
+                        tables.push(table);
+                        ControlFlow::Continue(())
+                    }
+                    None => ControlFlow::Break(()),
+                });
+            return (!flow.is_break()).then_some(tables);
+        }
+        Statement::CreateTable(create) => &create.name,
+        Statement::DropTable(drop) => drop.table_name(),
+        Statement::Alter(alter) => alter.table_name(),
+        Statement::TruncateTable(truncate) => truncate.table_name(),
+        Statement::Copy(copy::Copy::CopyTable(CopyTable::From(copy_from))) => &copy_from.table_name,
+        _ => return None,
+    };
+    to_table_name(name, query_ctx).map(|table| vec![table])
+}
+
+fn to_table_name(name: &ObjectName, query_ctx: &QueryContextRef) -> Option<TableName> {
+    let (catalog, schema, table) = table_idents_to_full_name(name, query_ctx.clone()).ok()?;
+    Some(TableName::new(catalog, schema, table))
+}
+
+/// Returns true if executing `stmt` won't change any data or schema, so
+/// cached results stay valid.
+pub(crate) fn is_read_only(stmt: &Statement) -> bool {
+    matches!(
+        stmt,
+        Statement::Query(_)
+            | Statement::ShowDatabases(_)
+            | Statement::ShowTables(_)
+            | Statement::ShowCreateTable(_)
+            | Statement::DescribeTable(_)
+            | Statement::Explain(_)
+            | Statement::Tql(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common_base::readable_size::ReadableSize;
+    use common_recordbatch::{util, RecordBatches};
+    use datatypes::prelude::*;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::UInt32Vector;
+    use session::context::QueryContext;
+    use sql::dialect::GreptimeDbDialect;
+    use sql::parser::ParserContext;
+
+    use super::*;
+
+    fn parse(sql: &str) -> Statement {
+        ParserContext::create_with_dialect(sql, &GreptimeDbDialect {})
+            .unwrap()
+            .remove(0)
+    }
+
+    fn new_batches() -> RecordBatches {
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "n",
+            ConcreteDataType::uint32_datatype(),
+            false,
+        )]));
+        RecordBatches::try_from_columns(
+            schema,
+            vec![Arc::new(UInt32Vector::from_slice([1, 2, 3])) as _],
+        )
+        .unwrap()
+    }
+
+    fn new_cache(max_result_size: ReadableSize) -> QueryResultCache {
+        QueryResultCache::new(&QueryCacheOptions {
+            enable: true,
+            size: ReadableSize::mb(1),
+            max_result_size,
+            ttl: Duration::from_secs(60),
+        })
+    }
+
+    fn miss(cache: &QueryResultCache, stmt: &Statement, ctx: &QueryContextRef) -> CacheSlot {
+        match cache.lookup(stmt, ctx) {
+            Lookup::Miss(slot) => slot,
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_hit(cache: &QueryResultCache, stmt: &Statement, ctx: &QueryContextRef) -> bool {
+        matches!(cache.lookup(stmt, ctx), Lookup::Hit(_))
+    }
+
+    #[test]
+    fn test_non_deterministic() {
+        let is_non_deterministic = |sql| {
+            let Statement::Query(query) = parse(sql) else {
+                unreachable!()
+            };
+            is_non_deterministic(&query)
+        };
+        assert!(is_non_deterministic("SELECT * FROM t WHERE ts > now() - 1"));
+        assert!(is_non_deterministic("SELECT RANDOM()"));
+        assert!(is_non_deterministic(
+            "SELECT * FROM (SELECT n FROM t WHERE ts > current_timestamp) AS s"
+        ));
+        assert!(!is_non_deterministic("SELECT snow FROM t"));
+        assert!(!is_non_deterministic("SELECT 'now()' FROM t"));
+    }
+
+    #[test]
+    fn test_cache_hit_and_invalidate() {
+        let cache = new_cache(ReadableSize::mb(1));
+        let ctx = QueryContext::arc();
+        let stmt = parse("SELECT n FROM t WHERE ts >= 0 AND ts < 1000");
+
+        let slot = miss(&cache, &stmt, &ctx);
+        let output = cache.fill(slot, Output::RecordBatches(new_batches()));
+        assert!(matches!(output, Output::RecordBatches(_)));
+
+        // Same query written differently hits the cache.
+        let same = parse("select n from t where ts >= 0 and ts < 1000");
+        let Lookup::Hit(Output::RecordBatches(batches)) = cache.lookup(&same, &ctx) else {
+            unreachable!()
+        };
+        assert_eq!(3, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+
+        // A different time range is a different entry.
+        let other = parse("SELECT n FROM t WHERE ts >= 0 AND ts < 2000");
+        assert!(!is_hit(&cache, &other, &ctx));
+
+        // Writes to other tables keep the entry.
+        let tables = written_tables(&parse("INSERT INTO t2 VALUES (1)"), &ctx).unwrap();
+        cache.invalidate_tables(tables);
+        assert!(is_hit(&cache, &stmt, &ctx));
+
+        let tables = written_tables(&parse("INSERT INTO public.t VALUES (1)"), &ctx).unwrap();
+        cache.invalidate_tables(tables);
+        assert!(!is_hit(&cache, &stmt, &ctx));
+    }
+
+    #[test]
+    fn test_bypass_uncacheable() {
+        let cache = new_cache(ReadableSize::mb(1));
+        let ctx = QueryContext::arc();
+
+        let stmt = parse("SELECT n FROM t WHERE ts > now()");
+        assert!(matches!(cache.lookup(&stmt, &ctx), Lookup::Bypass));
+
+        let stmt = parse("INSERT INTO t VALUES (1)");
+        assert!(!is_read_only(&stmt));
+        assert!(matches!(cache.lookup(&stmt, &ctx), Lookup::Bypass));
+
+        // Results over the size cap aren't cached.
+        let cache = new_cache(ReadableSize(1));
+        let stmt = parse("SELECT n FROM t");
+        let slot = miss(&cache, &stmt, &ctx);
+        let _ = cache.fill(slot, Output::RecordBatches(new_batches()));
+        assert!(!is_hit(&cache, &stmt, &ctx));
+    }
+
+    #[tokio::test]
+    async fn test_cache_stream() {
+        let cache = new_cache(ReadableSize::mb(1));
+        let ctx = QueryContext::arc();
+        let stmt = parse("SELECT n FROM t");
+
+        let slot = miss(&cache, &stmt, &ctx);
+        let Output::Stream(stream) = cache.fill(slot, Output::Stream(new_batches().as_stream()))
+        else {
+            unreachable!()
+        };
+        // Not cached until the stream is consumed.
+        assert!(!is_hit(&cache, &stmt, &ctx));
+        let batches = util::collect(stream).await.unwrap();
+        assert_eq!(1, batches.len());
+        assert!(is_hit(&cache, &stmt, &ctx));
+
+        // Results of queries racing with writes aren't cached.
+        cache.invalidate_all();
+        let slot = miss(&cache, &stmt, &ctx);
+        cache.invalidate_tables([TableName::new("greptime", "public", "t")]);
+        let _ = cache.fill(slot, Output::RecordBatches(new_batches()));
+        assert!(!is_hit(&cache, &stmt, &ctx));
+    }
+}
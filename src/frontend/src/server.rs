@@ -17,23 +17,28 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use auth::UserProviderRef;
+use common_base::readable_size::ReadableSize;
 use common_base::Plugins;
-use common_runtime::Builder as RuntimeBuilder;
+use common_error::ext::set_error_verbosity;
+use common_runtime::{Builder as RuntimeBuilder, Runtime};
 use common_telemetry::info;
-use servers::error::InternalIoSnafu;
+use servers::connection_limit::{ConnectionLimitOptions, ConnectionLimiter};
 use servers::grpc::{GrpcServer, GrpcServerConfig};
-use servers::http::HttpServerBuilder;
+use servers::http::readiness::{Liveness, Readiness};
+use servers::http::{AdminRoutes, HttpServerBuilder};
 use servers::metrics_handler::MetricsHandler;
 use servers::mysql::server::{MysqlServer, MysqlSpawnConfig, MysqlSpawnRef};
 use servers::opentsdb::OpentsdbServer;
 use servers::postgres::PostgresServer;
 use servers::query_handler::grpc::ServerGrpcQueryHandlerAdaptor;
 use servers::query_handler::sql::ServerSqlQueryHandlerAdaptor;
-use servers::server::Server;
+use servers::query_handler::{BackupHandlerRef, CardinalityHandlerRef};
+use servers::server::{set_tcp_buffer_sizes, Server};
+use servers::tls::{maybe_watch_tls_config, ReloadableTlsServerConfig};
 use snafu::ResultExt;
 
 use crate::error::{self, Result, StartServerSnafu};
-use crate::frontend::{FrontendOptions, TomlSerializable};
+use crate::frontend::{FrontendOptions, IngestProtocol, TomlSerializable};
 use crate::instance::FrontendInstance;
 
 pub(crate) struct Services;
@@ -54,6 +59,12 @@ impl Services {
     {
         let toml = opts.to_toml()?;
         let opts: FrontendOptions = opts.into();
+        // Errors are formatted for clients by the servers of the process.
+        set_error_verbosity(opts.error_verbosity);
+        set_tcp_buffer_sizes(
+            opts.tcp_send_buffer.map(socket_buffer_size),
+            opts.tcp_recv_buffer.map(socket_buffer_size),
+        );
         let mut result = Vec::<ServerHandler>::with_capacity(plugins.len());
         let user_provider = plugins.get::<UserProviderRef>();
 
@@ -62,17 +73,15 @@ impl Services {
             let opts = &opts.grpc;
             let grpc_addr = parse_addr(&opts.addr)?;
 
-            let grpc_runtime = Arc::new(
-                RuntimeBuilder::default()
-                    .worker_threads(opts.runtime_size)
-                    .thread_name("grpc-handlers")
-                    .build()
-                    .context(error::RuntimeResourceSnafu)?,
-            );
+            let grpc_runtime = build_runtime(opts.runtime_size, "grpc-handlers")?;
 
             let grpc_config = GrpcServerConfig {
                 max_recv_message_size: opts.max_recv_message_size.as_bytes() as usize,
                 max_send_message_size: opts.max_send_message_size.as_bytes() as usize,
+                tcp_keepalive: opts.tcp_keepalive,
+                http2_keepalive_interval: opts.http2_keepalive_interval,
+                http2_keepalive_timeout: opts.http2_keepalive_timeout,
+                max_connection_idle: opts.max_connection_idle,
             };
             let grpc_server = GrpcServer::new(
                 Some(grpc_config),
@@ -92,69 +101,130 @@ impl Services {
             let http_options = &opts.http;
             let http_addr = parse_addr(&http_options.addr)?;
 
+            // The admin APIs are served by another listener when it's configured.
+            let admin_routes = match &http_options.admin_addr {
+                Some(_) => AdminRoutes::Excluded,
+                None => AdminRoutes::Included,
+            };
             let mut http_server_builder = HttpServerBuilder::new(http_options.clone());
             let _ = http_server_builder
                 .with_sql_handler(ServerSqlQueryHandlerAdaptor::arc(instance.clone()))
-                .with_grpc_handler(ServerGrpcQueryHandlerAdaptor::arc(instance.clone()));
+                .with_grpc_handler(ServerGrpcQueryHandlerAdaptor::arc(instance.clone()))
+                .with_admin_routes(admin_routes);
 
             if let Some(user_provider) = user_provider.clone() {
                 let _ = http_server_builder.with_user_provider(user_provider);
             }
 
-            if opts.opentsdb.enable {
+            if opts.opentsdb.enable && opts.is_ingest_allowed(IngestProtocol::Opentsdb) {
                 let _ = http_server_builder.with_opentsdb_handler(instance.clone());
             }
 
-            if opts.influxdb.enable {
-                let _ = http_server_builder.with_influxdb_handler(instance.clone());
+            if opts.influxdb.enable && opts.is_ingest_allowed(IngestProtocol::Influxdb) {
+                let _ = http_server_builder
+                    .with_influxdb_handler(instance.clone())
+                    .with_influxdb_v2_api(opts.influxdb.v2_api);
             }
 
             if opts.prom_store.enable {
-                let _ = http_server_builder
-                    .with_prom_handler(instance.clone())
-                    .with_prometheus_handler(instance.clone());
+                // The PromQL APIs are still served if the remote storage is disallowed.
+                if opts.is_ingest_allowed(IngestProtocol::PromStore) {
+                    let _ = http_server_builder
+                        .with_prom_handler(instance.clone())
+                        .with_prom_native_histogram(opts.prom_store.native_histogram)
+                        .with_prom_external_labels(opts.prom_store.external_labels.clone());
+                }
+                let _ = http_server_builder.with_prometheus_handler(instance.clone());
             }
 
-            if opts.otlp.enable {
+            if opts.otlp.enable && opts.is_ingest_allowed(IngestProtocol::Otlp) {
                 let _ = http_server_builder.with_otlp_handler(instance.clone());
             }
 
+            // The backup handler is only registered by the standalone mode.
+            if let Some(backup_handler) = plugins.get::<BackupHandlerRef>() {
+                let _ = http_server_builder.with_backup_handler(backup_handler);
+            }
+
+            // The probes are only tracked by the standalone mode, other servers are
+            // alive and ready once they are up.
+            if let Some(readiness) = plugins.get::<Readiness>() {
+                let _ = http_server_builder.with_readiness(readiness);
+            }
+            if let Some(liveness) = plugins.get::<Liveness>() {
+                let _ = http_server_builder.with_liveness(liveness);
+            }
+
+            // The cardinality handler is registered when the estimation is enabled.
+            if let Some(cardinality_handler) = plugins.get::<CardinalityHandlerRef>() {
+                let _ = http_server_builder.with_cardinality_handler(cardinality_handler);
+            }
+
             let http_server = http_server_builder
                 .with_metrics_handler(MetricsHandler)
                 .with_script_handler(instance.clone())
-                .with_plugins(plugins)
+                .with_plugins(plugins.clone())
                 .with_greptime_config_options(toml)
                 .build();
             result.push((Box::new(http_server), http_addr));
+
+            if let Some(admin_addr) = &http_options.admin_addr {
+                let admin_addr = http_options.admin_bind_addr(parse_addr(admin_addr)?);
+
+                let mut admin_server_builder = HttpServerBuilder::new(http_options.clone());
+                let _ = admin_server_builder.with_admin_routes(AdminRoutes::Only);
+                if let Some(user_provider) = user_provider.clone() {
+                    let _ = admin_server_builder.with_user_provider(user_provider);
+                }
+                if let Some(backup_handler) = plugins.get::<BackupHandlerRef>() {
+                    let _ = admin_server_builder.with_backup_handler(backup_handler);
+                }
+                if let Some(cardinality_handler) = plugins.get::<CardinalityHandlerRef>() {
+                    let _ = admin_server_builder.with_cardinality_handler(cardinality_handler);
+                }
+
+                result.push((Box::new(admin_server_builder.build()), admin_addr));
+            }
         }
 
+        // MySQL and PostgreSQL connections share the limits.
+        let connection_limiter = Arc::new(ConnectionLimiter::new(ConnectionLimitOptions {
+            max_connections_per_user: opts.max_connections_per_user,
+            max_unauthenticated_connections: opts.max_unauthenticated_connections,
+        }));
+
         if opts.mysql.enable {
             // Init MySQL server
             let opts = &opts.mysql;
             let mysql_addr = parse_addr(&opts.addr)?;
 
-            let mysql_io_runtime = Arc::new(
-                RuntimeBuilder::default()
-                    .worker_threads(opts.runtime_size)
-                    .thread_name("mysql-io-handlers")
-                    .build()
-                    .context(error::RuntimeResourceSnafu)?,
+            let mysql_io_runtime = build_runtime(opts.runtime_size, "mysql-io-handlers")?;
+
+            // will not watch if watch is disabled in tls option
+            let tls_server_config = Arc::new(
+                ReloadableTlsServerConfig::try_new(opts.tls.clone()).context(StartServerSnafu)?,
             );
+            maybe_watch_tls_config(tls_server_config.clone()).context(StartServerSnafu)?;
+
             let mysql_server = MysqlServer::create_server(
                 mysql_io_runtime,
                 Arc::new(MysqlSpawnRef::new(
                     ServerSqlQueryHandlerAdaptor::arc(instance.clone()),
                     user_provider.clone(),
+                    connection_limiter.clone(),
                 )),
-                Arc::new(MysqlSpawnConfig::new(
-                    opts.tls.should_force_tls(),
-                    opts.tls
-                        .setup()
-                        .context(InternalIoSnafu)
-                        .context(StartServerSnafu)?
-                        .map(Arc::new),
-                    opts.reject_no_database.unwrap_or(false),
-                )),
+                Arc::new(
+                    MysqlSpawnConfig::new(
+                        opts.tls.should_force_tls(),
+                        tls_server_config,
+                        opts.reject_no_database.unwrap_or(false),
+                        opts.connection_init_sql.clone(),
+                        opts.proxy_protocol,
+                        opts.server_version.clone(),
+                        opts.idle_in_transaction_timeout,
+                    )
+                    .with_unix_socket(opts.unix_socket.clone(), opts.unix_socket_mode.clone()),
+                ),
             );
             result.push((mysql_server, mysql_addr));
         }
@@ -164,38 +234,36 @@ impl Services {
             let opts = &opts.postgres;
             let pg_addr = parse_addr(&opts.addr)?;
 
-            let pg_io_runtime = Arc::new(
-                RuntimeBuilder::default()
-                    .worker_threads(opts.runtime_size)
-                    .thread_name("pg-io-handlers")
-                    .build()
-                    .context(error::RuntimeResourceSnafu)?,
+            let pg_io_runtime = build_runtime(opts.runtime_size, "pg-io-handlers")?;
+
+            let tls_server_config = Arc::new(
+                ReloadableTlsServerConfig::try_new(opts.tls.clone()).context(StartServerSnafu)?,
             );
+            maybe_watch_tls_config(tls_server_config.clone()).context(StartServerSnafu)?;
 
             let pg_server = Box::new(PostgresServer::new(
                 ServerSqlQueryHandlerAdaptor::arc(instance.clone()),
-                opts.tls.clone(),
+                opts.tls.should_force_tls(),
+                tls_server_config,
                 pg_io_runtime,
                 user_provider.clone(),
+                opts.connection_init_sql.clone(),
+                opts.proxy_protocol,
+                connection_limiter.clone(),
             )) as Box<dyn Server>;
 
             result.push((pg_server, pg_addr));
         }
 
-        if opts.opentsdb.enable {
+        if opts.opentsdb.enable && opts.is_ingest_allowed(IngestProtocol::Opentsdb) {
             // Init OpenTSDB server
             let opts = &opts.opentsdb;
             let addr = parse_addr(&opts.addr)?;
 
-            let io_runtime = Arc::new(
-                RuntimeBuilder::default()
-                    .worker_threads(opts.runtime_size)
-                    .thread_name("opentsdb-io-handlers")
-                    .build()
-                    .context(error::RuntimeResourceSnafu)?,
-            );
+            let io_runtime = build_runtime(opts.runtime_size, "opentsdb-io-handlers")?;
 
-            let server = OpentsdbServer::create_server(instance.clone(), io_runtime);
+            let server =
+                OpentsdbServer::create_server(instance.clone(), io_runtime, opts.proxy_protocol);
 
             result.push((server, addr));
         }
@@ -207,6 +275,19 @@ impl Services {
     }
 }
 
+pub(crate) fn build_runtime(worker_threads: usize, thread_name: &str) -> Result<Arc<Runtime>> {
+    let runtime = RuntimeBuilder::default()
+        .worker_threads(worker_threads)
+        .thread_name(thread_name)
+        .build()
+        .context(error::RuntimeResourceSnafu)?;
+    Ok(Arc::new(runtime))
+}
+
+fn socket_buffer_size(size: ReadableSize) -> u32 {
+    u32::try_from(size.as_bytes()).unwrap_or(u32::MAX)
+}
+
 fn parse_addr(addr: &str) -> Result<SocketAddr> {
     addr.parse().context(error::ParseAddrSnafu { addr })
 }
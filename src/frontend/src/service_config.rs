@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod cardinality;
 pub mod datanode;
 pub mod grpc;
 pub mod influxdb;
@@ -20,7 +21,12 @@ pub mod opentsdb;
 pub mod otlp;
 pub mod postgres;
 pub mod prom_store;
+pub mod query_admission;
+pub mod query_cache;
+pub mod self_monitoring;
+pub mod slow_query;
 
+pub use cardinality::CardinalityOptions;
 pub use grpc::GrpcOptions;
 pub use influxdb::InfluxdbOptions;
 pub use mysql::MysqlOptions;
@@ -28,5 +34,9 @@ pub use opentsdb::OpentsdbOptions;
 pub use otlp::OtlpOptions;
 pub use postgres::PostgresOptions;
 pub use prom_store::PromStoreOptions;
+pub use query_admission::{QueryAdmissionOptions, QueryOverflowPolicy};
+pub use query_cache::QueryCacheOptions;
+pub use self_monitoring::SelfMonitoringOptions;
+pub use slow_query::{QueryLogFormat, SlowQueryOptions};
 
 pub use self::datanode::DatanodeOptions;
@@ -0,0 +1,60 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_WARN_THRESHOLD: u64 = 100_000;
+const DEFAULT_MAX_COLUMNS: usize = 1000;
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct CardinalityOptions {
+    pub enable: bool,
+    /// Estimated number of distinct values of a tag column, above which a warning
+    /// is logged.
+    pub warn_threshold: u64,
+    /// Max number of tag columns tracked, the columns seen after are ignored.
+    pub max_columns: usize,
+    /// How often the estimates are checked against the `warn_threshold`.
+    #[serde(with = "humantime_serde")]
+    pub check_interval: Duration,
+}
+
+impl Default for CardinalityOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            warn_threshold: DEFAULT_WARN_THRESHOLD,
+            max_columns: DEFAULT_MAX_COLUMNS,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cardinality_options() {
+        let default = CardinalityOptions::default();
+        assert!(!default.enable);
+        assert_eq!(100_000, default.warn_threshold);
+        assert_eq!(1000, default.max_columns);
+        assert_eq!(Duration::from_secs(60), default.check_interval);
+    }
+}
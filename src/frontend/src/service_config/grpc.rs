@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use common_base::readable_size::ReadableSize;
 use common_grpc::channel_manager::{
     DEFAULT_MAX_GRPC_RECV_MESSAGE_SIZE, DEFAULT_MAX_GRPC_SEND_MESSAGE_SIZE,
@@ -19,13 +21,32 @@ use common_grpc::channel_manager::{
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
 pub struct GrpcOptions {
     pub addr: String,
     pub runtime_size: usize,
-    // Max gRPC receiving(decoding) message size
+    /// Max gRPC receiving(decoding) message size, a larger request fails with the
+    /// `OUT_OF_RANGE` status.
     pub max_recv_message_size: ReadableSize,
-    // Max gRPC sending(encoding) message size
+    /// Max gRPC sending(encoding) message size, a larger response fails with the
+    /// `OUT_OF_RANGE` status.
     pub max_send_message_size: ReadableSize,
+    /// Interval of the TCP keep-alive probes of the accepted connections, disabled if
+    /// not set.
+    #[serde(with = "humantime_serde")]
+    pub tcp_keepalive: Option<Duration>,
+    /// Interval of the HTTP/2 keep-alive pings sent to the clients, disabled if not
+    /// set. A client not acknowledging a ping within `http2_keepalive_timeout` is
+    /// considered dead and its connection closed.
+    #[serde(with = "humantime_serde")]
+    pub http2_keepalive_interval: Option<Duration>,
+    /// Timeout of the acknowledgement of a HTTP/2 keep-alive ping, 20s if not set.
+    #[serde(with = "humantime_serde")]
+    pub http2_keepalive_timeout: Option<Duration>,
+    /// Closes the connections without any call in flight and no traffic for this long,
+    /// never if not set. The keep-alive pings of either side are traffic too.
+    #[serde(with = "humantime_serde")]
+    pub max_connection_idle: Option<Duration>,
 }
 
 impl Default for GrpcOptions {
@@ -35,6 +56,10 @@ impl Default for GrpcOptions {
             runtime_size: 8,
             max_recv_message_size: DEFAULT_MAX_GRPC_RECV_MESSAGE_SIZE,
             max_send_message_size: DEFAULT_MAX_GRPC_SEND_MESSAGE_SIZE,
+            tcp_keepalive: None,
+            http2_keepalive_interval: None,
+            http2_keepalive_timeout: None,
+            max_connection_idle: None,
         }
     }
 }
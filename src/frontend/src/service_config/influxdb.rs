@@ -13,25 +13,50 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::influxdb::{TimeIndexOptions, TimeIndexPolicy};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
 pub struct InfluxdbOptions {
     pub enable: bool,
+    /// Integer field of a line used as its time index instead of the timestamp of
+    /// the line, the time index column is still `ts`.
+    pub time_index_field: Option<String>,
+    /// What to do with a line without the time index field nor a timestamp.
+    pub time_index_policy: TimeIndexPolicy,
+    /// Whether to serve the InfluxDB 2.x `/api/v2/write` endpoint.
+    pub v2_api: bool,
 }
 
 impl Default for InfluxdbOptions {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            enable: true,
+            time_index_field: None,
+            time_index_policy: TimeIndexPolicy::default(),
+            v2_api: true,
+        }
+    }
+}
+
+impl InfluxdbOptions {
+    pub fn time_index_options(&self) -> TimeIndexOptions {
+        TimeIndexOptions {
+            field: self.time_index_field.clone(),
+            policy: self.time_index_policy,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::InfluxdbOptions;
+    use super::*;
 
     #[test]
     fn test_influxdb_options() {
         let default = InfluxdbOptions::default();
         assert!(default.enable);
+        assert!(default.v2_api);
+        assert_eq!(TimeIndexOptions::default(), default.time_index_options());
     }
 }
@@ -12,17 +12,45 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use servers::mysql::server::DEFAULT_MYSQL_SERVER_VERSION;
+use servers::proxy_protocol::ProxyProtocolOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct MysqlOptions {
     pub enable: bool,
     pub addr: String,
+    /// Also accepts the connections on the Unix socket at this path, removed when the
+    /// server stops.
+    pub unix_socket: Option<String>,
+    /// Permissions of the `unix_socket` file, an octal mode like `660`. As the umask
+    /// of the process allows if not set.
+    pub unix_socket_mode: Option<String>,
     pub runtime_size: usize,
+    /// Server version advertised in the handshake. Some clients gate features on it,
+    /// so it must start with a MySQL version like `8.4.2`. Defaults to
+    /// [DEFAULT_MYSQL_SERVER_VERSION].
+    #[serde(default = "default_server_version")]
+    pub server_version: String,
     #[serde(default = "Default::default")]
     pub tls: TlsOption,
     pub reject_no_database: Option<bool>,
+    /// SQL executed on every new connection right after it's authenticated.
+    pub connection_init_sql: Option<String>,
+    /// PROXY protocol header parsing of accepted connections.
+    #[serde(default)]
+    pub proxy_protocol: ProxyProtocolOptions,
+    /// Closes the connections idle in an open transaction for longer than it, the
+    /// connections outside a transaction are never closed for idling.
+    #[serde(default, with = "humantime_serde")]
+    pub idle_in_transaction_timeout: Option<Duration>,
+}
+
+fn default_server_version() -> String {
+    DEFAULT_MYSQL_SERVER_VERSION.to_string()
 }
 
 impl Default for MysqlOptions {
@@ -30,9 +58,15 @@ impl Default for MysqlOptions {
         Self {
             enable: true,
             addr: "127.0.0.1:4002".to_string(),
+            unix_socket: None,
+            unix_socket_mode: None,
             runtime_size: 2,
+            server_version: default_server_version(),
             tls: TlsOption::default(),
             reject_no_database: None,
+            connection_init_sql: None,
+            proxy_protocol: ProxyProtocolOptions::default(),
+            idle_in_transaction_timeout: None,
         }
     }
 }
@@ -13,12 +13,16 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::proxy_protocol::ProxyProtocolOptions;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct OpentsdbOptions {
     pub enable: bool,
     pub addr: String,
     pub runtime_size: usize,
+    /// PROXY protocol header parsing of accepted connections.
+    #[serde(default)]
+    pub proxy_protocol: ProxyProtocolOptions,
 }
 
 impl Default for OpentsdbOptions {
@@ -27,6 +31,7 @@ impl Default for OpentsdbOptions {
             enable: true,
             addr: "127.0.0.1:4242".to_string(),
             runtime_size: 2,
+            proxy_protocol: ProxyProtocolOptions::default(),
         }
     }
 }
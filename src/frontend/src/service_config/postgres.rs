@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::proxy_protocol::ProxyProtocolOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -22,6 +23,11 @@ pub struct PostgresOptions {
     pub runtime_size: usize,
     #[serde(default = "Default::default")]
     pub tls: TlsOption,
+    /// SQL executed on every new connection once it's authenticated.
+    pub connection_init_sql: Option<String>,
+    /// PROXY protocol header parsing of accepted connections.
+    #[serde(default)]
+    pub proxy_protocol: ProxyProtocolOptions,
 }
 
 impl Default for PostgresOptions {
@@ -31,6 +37,8 @@ impl Default for PostgresOptions {
             addr: "127.0.0.1:4003".to_string(),
             runtime_size: 2,
             tls: Default::default(),
+            connection_init_sql: None,
+            proxy_protocol: ProxyProtocolOptions::default(),
         }
     }
 }
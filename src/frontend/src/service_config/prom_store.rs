@@ -12,26 +12,156 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use humantime_serde::re::humantime;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PromStoreOptions {
     pub enable: bool,
+    /// Whether native histograms of remote write are converted to classic
+    /// histograms. They are dropped otherwise.
+    #[serde(default)]
+    pub native_histogram: bool,
+    /// Ingest-time downsampling rules for metrics written by remote write.
+    #[serde(default)]
+    pub downsampling: Vec<DownsamplingRule>,
+    /// Labels added to every series of remote write, like the region of the instance
+    /// for federation. They override the labels of the same names in the series.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external_labels: BTreeMap<String, String>,
 }
 
 impl Default for PromStoreOptions {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            enable: true,
+            native_histogram: false,
+            downsampling: vec![],
+            external_labels: BTreeMap::new(),
+        }
+    }
+}
+
+/// Rolls up samples of a metric into lower resolution tables when they are
+/// written, optionally bounding how long the raw samples are kept.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownsamplingRule {
+    /// The metric name the rule applies to, or `*` for all metrics.
+    pub metric: String,
+    /// TTL of the raw metric table, applied when the table is created.
+    #[serde(default, with = "humantime_serde")]
+    pub raw_ttl: Option<Duration>,
+    #[serde(default)]
+    pub rollups: Vec<RollupRule>,
+}
+
+impl DownsamplingRule {
+    pub fn matches(&self, metric: &str) -> bool {
+        self.metric == "*" || self.metric == metric
+    }
+}
+
+/// A single rollup, written into table `{metric}_{interval}_{aggregation}`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RollupRule {
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    pub aggregation: RollupAggregation,
+    /// TTL of the rollup table, applied when the table is created.
+    #[serde(default, with = "humantime_serde")]
+    pub ttl: Option<Duration>,
+}
+
+impl RollupRule {
+    pub fn table_name(&self, metric: &str) -> String {
+        // Compound durations are formatted like "1m 30s", drop the spaces.
+        let interval = humantime::format_duration(self.interval)
+            .to_string()
+            .replace(' ', "");
+        format!("{metric}_{interval}_{}", self.aggregation.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupAggregation {
+    Avg,
+    Min,
+    Max,
+    Sum,
+    Count,
+}
+
+impl RollupAggregation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupAggregation::Avg => "avg",
+            RollupAggregation::Min => "min",
+            RollupAggregation::Max => "max",
+            RollupAggregation::Sum => "sum",
+            RollupAggregation::Count => "count",
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PromStoreOptions;
+    use super::*;
 
     #[test]
     fn test_prom_store_options() {
         let default = PromStoreOptions::default();
         assert!(default.enable);
+        assert!(default.downsampling.is_empty());
+        assert!(default.external_labels.is_empty());
+    }
+
+    #[test]
+    fn test_downsampling_rules() {
+        let toml_str = r#"
+            enable = true
+
+            [[downsampling]]
+            metric = "node_cpu"
+            raw_ttl = "7d"
+
+            [[downsampling.rollups]]
+            interval = "1m"
+            aggregation = "avg"
+            ttl = "30d"
+
+            [[downsampling.rollups]]
+            interval = "1h"
+            aggregation = "max"
+        "#;
+        let opts: PromStoreOptions = toml::from_str(toml_str).unwrap();
+        let rule = &opts.downsampling[0];
+        assert!(rule.matches("node_cpu"));
+        assert!(!rule.matches("node_memory"));
+        assert_eq!(Some(Duration::from_secs(7 * 24 * 3600)), rule.raw_ttl);
+        assert_eq!("node_cpu_1m_avg", rule.rollups[0].table_name("node_cpu"));
+        assert_eq!("node_cpu_1h_max", rule.rollups[1].table_name("node_cpu"));
+        assert_eq!(None, rule.rollups[1].ttl);
+    }
+
+    #[test]
+    fn test_external_labels() {
+        let toml_str = r#"
+            enable = true
+
+            [external_labels]
+            region = "us-west"
+        "#;
+        let opts: PromStoreOptions = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            BTreeMap::from([("region".to_string(), "us-west".to_string())]),
+            opts.external_labels
+        );
+        assert!(!toml::to_string(&PromStoreOptions::default())
+            .unwrap()
+            .contains("external_labels"));
     }
 }
@@ -0,0 +1,67 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueryAdmissionOptions {
+    /// Max number of the queries executing at the same time, unlimited if not set.
+    pub concurrency_limit: Option<usize>,
+    /// What to do with the queries beyond the limit.
+    pub overflow: QueryOverflowPolicy,
+    /// Whether the metadata statements, `SHOW` and `DESCRIBE`, bypass the limit, so
+    /// the monitoring isn't starved by a query storm.
+    pub exempt_metadata: bool,
+}
+
+impl Default for QueryAdmissionOptions {
+    fn default() -> Self {
+        Self {
+            concurrency_limit: None,
+            overflow: QueryOverflowPolicy::Queue,
+            exempt_metadata: true,
+        }
+    }
+}
+
+/// What to do with a query beyond the concurrency limit.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumString)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryOverflowPolicy {
+    /// Waits until a running query finishes.
+    #[default]
+    #[strum(serialize = "queue")]
+    Queue,
+    /// Fails with a retryable "server busy" error.
+    #[strum(serialize = "reject")]
+    Reject,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_admission_options() {
+        let default = QueryAdmissionOptions::default();
+        assert_eq!(None, default.concurrency_limit);
+        assert_eq!(QueryOverflowPolicy::Queue, default.overflow);
+        assert!(default.exempt_metadata);
+
+        assert_eq!(QueryOverflowPolicy::Reject, "reject".parse().unwrap());
+        assert!("drop".parse::<QueryOverflowPolicy>().is_err());
+    }
+}
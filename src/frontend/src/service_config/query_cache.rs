@@ -0,0 +1,61 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::readable_size::ReadableSize;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_QUERY_CACHE_SIZE: ReadableSize = ReadableSize::mb(64);
+const DEFAULT_MAX_RESULT_SIZE: ReadableSize = ReadableSize::mb(1);
+const DEFAULT_QUERY_CACHE_TTL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueryCacheOptions {
+    pub enable: bool,
+    /// Max total memory used by the cached query results.
+    pub size: ReadableSize,
+    /// Results larger than this are never cached.
+    pub max_result_size: ReadableSize,
+    /// How long a cached result may be served. Writes issued through other
+    /// frontends are only visible after the cached entry expires.
+    #[serde(with = "humantime_serde")]
+    pub ttl: Duration,
+}
+
+impl Default for QueryCacheOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            size: DEFAULT_QUERY_CACHE_SIZE,
+            max_result_size: DEFAULT_MAX_RESULT_SIZE,
+            ttl: DEFAULT_QUERY_CACHE_TTL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_cache_options() {
+        let default = QueryCacheOptions::default();
+        assert!(!default.enable);
+        assert_eq!(ReadableSize::mb(64), default.size);
+        assert_eq!(ReadableSize::mb(1), default.max_result_size);
+        assert_eq!(Duration::from_secs(10), default.ttl);
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_DATABASE: &str = "greptime_private";
+const DEFAULT_TABLE: &str = "metrics";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SelfMonitoringOptions {
+    pub enable: bool,
+    /// How often the metrics of the instance are written.
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    /// Database of the metrics table, created if it doesn't exist.
+    pub database: String,
+    /// Table the metrics are written into, created on the first write.
+    pub table: String,
+}
+
+impl Default for SelfMonitoringOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            interval: DEFAULT_INTERVAL,
+            database: DEFAULT_DATABASE.to_string(),
+            table: DEFAULT_TABLE.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_monitoring_options() {
+        let default = SelfMonitoringOptions::default();
+        assert!(!default.enable);
+        assert_eq!(Duration::from_secs(30), default.interval);
+        assert_eq!("greptime_private", default.database);
+        assert_eq!("metrics", default.table);
+    }
+}
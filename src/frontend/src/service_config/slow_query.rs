@@ -0,0 +1,75 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumString;
+
+const DEFAULT_THRESHOLD: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_STATEMENT_LENGTH: usize = 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SlowQueryOptions {
+    pub enable: bool,
+    /// Execution time of a SQL statement, until its output is consumed, above which
+    /// it's logged.
+    #[serde(with = "humantime_serde")]
+    pub threshold: Duration,
+    pub format: QueryLogFormat,
+    /// Max number of characters of the statement text in a line, the rest is cut.
+    pub max_statement_length: usize,
+}
+
+impl Default for SlowQueryOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            threshold: DEFAULT_THRESHOLD,
+            format: QueryLogFormat::Text,
+            max_statement_length: DEFAULT_MAX_STATEMENT_LENGTH,
+        }
+    }
+}
+
+/// Format of the slow query log lines.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumString)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryLogFormat {
+    /// Human readable text.
+    #[default]
+    #[strum(serialize = "text")]
+    Text,
+    /// A JSON object per line.
+    #[strum(serialize = "json")]
+    Json,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_query_options() {
+        let default = SlowQueryOptions::default();
+        assert!(!default.enable);
+        assert_eq!(Duration::from_secs(5), default.threshold);
+        assert_eq!(QueryLogFormat::Text, default.format);
+        assert_eq!(1024, default.max_statement_length);
+
+        assert_eq!(QueryLogFormat::Json, "json".parse().unwrap());
+        assert!("yaml".parse::<QueryLogFormat>().is_err());
+    }
+}
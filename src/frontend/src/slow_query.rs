@@ -0,0 +1,220 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logs the SQL statements executing longer than a threshold, under the log target
+//! `slow_query`.
+//!
+//! A statement is timed until its output is consumed, so a streamed result counts the
+//! time the client reads it, and is logged once the stream is done or dropped.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use common_query::Output;
+use common_recordbatch::RecordBatchStreamAdaptor;
+use common_telemetry::logging::warn;
+use futures::StreamExt;
+use serde::Serialize;
+use session::context::QueryContextRef;
+use sql::util::redact_sql_secrets;
+
+use crate::service_config::{QueryLogFormat, SlowQueryOptions};
+
+/// Suffix of a statement cut at the `max_statement_length`.
+const ELLIPSIS: &str = "...";
+
+#[derive(Debug, Serialize)]
+struct SlowQuery<'a> {
+    statement: &'a str,
+    duration_ms: u64,
+    /// Number of rows returned or affected, the rows read so far of a stream dropped
+    /// before its end.
+    rows: usize,
+    protocol: Option<String>,
+    user: Option<String>,
+    timestamp: String,
+}
+
+pub(crate) struct SlowQueryLogger {
+    opts: SlowQueryOptions,
+}
+
+impl SlowQueryLogger {
+    /// Creates the logger if it's enabled in `opts`.
+    pub(crate) fn from_options(opts: &SlowQueryOptions) -> Option<Arc<Self>> {
+        opts.enable.then(|| Arc::new(Self { opts: opts.clone() }))
+    }
+
+    /// Logs the `statement` started at `start` if its `output` takes longer than the
+    /// threshold to be consumed. The stream of the output is wrapped to be timed.
+    pub(crate) fn observe(
+        self: &Arc<Self>,
+        statement: String,
+        start: Instant,
+        output: Output,
+        ctx: &QueryContextRef,
+    ) -> Output {
+        let rows = match output {
+            Output::AffectedRows(rows) => rows,
+            Output::RecordBatches(ref batches) => {
+                batches.iter().map(|batch| batch.num_rows()).sum()
+            }
+            Output::Stream(stream) => {
+                let schema = stream.schema();
+                let output_ordering = stream.output_ordering().map(|ordering| ordering.to_vec());
+                let mut observed = ObservedStream {
+                    logger: self.clone(),
+                    statement,
+                    start,
+                    rows: 0,
+                    ctx: ctx.clone(),
+                };
+                let stream = stream.map(move |batch| {
+                    if let Ok(batch) = &batch {
+                        observed.rows += batch.num_rows();
+                    }
+                    batch
+                });
+                return Output::Stream(Box::pin(RecordBatchStreamAdaptor {
+                    schema,
+                    stream,
+                    output_ordering,
+                }));
+            }
+        };
+        self.log(&statement, start.elapsed(), rows, ctx);
+        output
+    }
+
+    /// Logs the statement if it's executed longer than the threshold, with the secrets
+    /// redacted.
+    fn log(&self, statement: &str, elapsed: Duration, rows: usize, ctx: &QueryContextRef) {
+        if elapsed < self.opts.threshold {
+            return;
+        }
+        let statement = redact_sql_secrets(statement);
+        let query = SlowQuery {
+            statement: &truncate(&statement, self.opts.max_statement_length),
+            duration_ms: elapsed.as_millis() as u64,
+            rows,
+            protocol: ctx.channel().map(|channel| channel.to_string()),
+            user: ctx.current_user().map(|user| user.username().to_string()),
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        };
+        warn!(target: "slow_query", "{}", self.format(&query));
+    }
+
+    fn format(&self, query: &SlowQuery) -> String {
+        match self.opts.format {
+            QueryLogFormat::Text => format!(
+                "Slow query took {}ms, rows: {}, protocol: {}, user: {}, statement: {}",
+                query.duration_ms,
+                query.rows,
+                query.protocol.as_deref().unwrap_or("unknown"),
+                query.user.as_deref().unwrap_or("unknown"),
+                // Keeps a statement in one line.
+                query.statement.replace(['\r', '\n'], " "),
+            ),
+            // Serializing the struct can't fail.
+            QueryLogFormat::Json => serde_json::to_string(query).unwrap(),
+        }
+    }
+}
+
+/// Logs a statement whose output is streamed once the stream is dropped.
+struct ObservedStream {
+    logger: Arc<SlowQueryLogger>,
+    statement: String,
+    start: Instant,
+    rows: usize,
+    ctx: QueryContextRef,
+}
+
+impl Drop for ObservedStream {
+    fn drop(&mut self) {
+        self.logger
+            .log(&self.statement, self.start.elapsed(), self.rows, &self.ctx);
+    }
+}
+
+/// Cuts the statement to at most `max_len` characters, not counting the ellipsis.
+fn truncate(statement: &str, max_len: usize) -> Cow<'_, str> {
+    match statement.char_indices().nth(max_len) {
+        Some((end, _)) => Cow::Owned(format!("{}{ELLIPSIS}", &statement[..end])),
+        None => Cow::Borrowed(statement),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use session::context::{Channel, QueryContextBuilder};
+
+    use super::*;
+
+    fn new_logger(format: QueryLogFormat) -> SlowQueryLogger {
+        SlowQueryLogger {
+            opts: SlowQueryOptions {
+                enable: true,
+                threshold: Duration::ZERO,
+                format,
+                max_statement_length: 17,
+            },
+        }
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!("select 1", truncate("select 1", 8));
+        assert_eq!("select...", truncate("select 1", 6));
+        // Cut at a character boundary.
+        assert_eq!("'数据...", truncate("'数据库'", 3));
+    }
+
+    #[test]
+    fn test_format() {
+        let ctx = QueryContextBuilder::default()
+            .channel(Channel::Mysql)
+            .build();
+        let statement = "select \"a\"\nfrom t where x = '\\'";
+        let query = SlowQuery {
+            statement: &truncate(
+                statement,
+                new_logger(QueryLogFormat::Text).opts.max_statement_length,
+            ),
+            duration_ms: 1200,
+            rows: 3,
+            protocol: ctx.channel().map(|channel| channel.to_string()),
+            user: None,
+            timestamp: "2023-11-01T00:00:00.000Z".to_string(),
+        };
+
+        assert_eq!(
+            "Slow query took 1200ms, rows: 3, protocol: mysql, user: unknown, \
+            statement: select \"a\" from t...",
+            new_logger(QueryLogFormat::Text).format(&query)
+        );
+
+        let line = new_logger(QueryLogFormat::Json).format(&query);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!("select \"a\"\nfrom t...", value["statement"]);
+        assert_eq!(1200, value["duration_ms"]);
+        assert_eq!(3, value["rows"]);
+        assert_eq!("mysql", value["protocol"]);
+        assert!(value["user"].is_null());
+        assert_eq!("2023-11-01T00:00:00.000Z", value["timestamp"]);
+        assert!(!line.contains('\n'));
+    }
+}
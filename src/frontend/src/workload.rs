@@ -0,0 +1,229 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dedicated runtimes of the ingest and query workloads.
+//!
+//! Requests are routed by what they do rather than by the server receiving them,
+//! since the gRPC and HTTP servers serve both writes and queries. The writes of
+//! all protocols run on the ingest runtime, and the SQL and PromQL queries, as
+//! well as the streams of their results, run on the query runtime.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use common_query::Output;
+use common_recordbatch::error::Result as RecordBatchResult;
+use common_recordbatch::{
+    OrderOption, RecordBatch, RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream,
+};
+use common_runtime::Runtime;
+use datatypes::schema::SchemaRef;
+use futures::{Stream, StreamExt};
+use snafu::ResultExt;
+use tokio::sync::mpsc;
+
+use crate::error::{JoinTaskSnafu, Result};
+use crate::server::build_runtime;
+
+/// Number of batches buffered between the query runtime and the server polling
+/// the results.
+const RELAY_BUFFER_SIZE: usize = 2;
+
+#[derive(Default)]
+pub(crate) struct WorkloadRuntimes {
+    ingest: Option<Arc<Runtime>>,
+    query: Option<Arc<Runtime>>,
+}
+
+impl WorkloadRuntimes {
+    /// Builds the runtimes of the workloads whose worker threads are set, the
+    /// others run on the runtimes of the servers receiving them.
+    pub(crate) fn try_new(
+        ingest_worker_threads: Option<usize>,
+        query_worker_threads: Option<usize>,
+    ) -> Result<Self> {
+        Ok(Self {
+            ingest: ingest_worker_threads
+                .map(|threads| build_runtime(threads, "ingest-handlers"))
+                .transpose()?,
+            query: query_worker_threads
+                .map(|threads| build_runtime(threads, "query-handlers"))
+                .transpose()?,
+        })
+    }
+
+    /// Runs the write `future` on the ingest runtime.
+    pub(crate) async fn ingest<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        spawn(self.ingest.as_ref(), future).await
+    }
+
+    /// Runs the query `future` on the query runtime. The outputs of the query
+    /// should be passed to [WorkloadRuntimes::relay] to poll their streams there too.
+    pub(crate) async fn query<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        spawn(self.query.as_ref(), future).await
+    }
+
+    /// Polls the stream of the query `output` on the query runtime.
+    pub(crate) fn relay(&self, output: Output) -> Output {
+        match (&self.query, output) {
+            (Some(runtime), Output::Stream(stream)) => {
+                Output::Stream(relay_stream(runtime, stream))
+            }
+            (_, output) => output,
+        }
+    }
+}
+
+async fn spawn<F, T>(runtime: Option<&Arc<Runtime>>, future: F) -> Result<T>
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    match runtime {
+        Some(runtime) => runtime.spawn(future).await.context(JoinTaskSnafu),
+        None => Ok(future.await),
+    }
+}
+
+/// Polls `stream` on `runtime`, so the plan producing it executes there, and
+/// relays its batches to the returned stream.
+fn relay_stream(
+    runtime: &Runtime,
+    mut stream: SendableRecordBatchStream,
+) -> SendableRecordBatchStream {
+    let schema = stream.schema();
+    let output_ordering = stream.output_ordering().map(|ordering| ordering.to_vec());
+    let metrics = Arc::new(Mutex::new(None));
+    let (tx, rx) = mpsc::channel(RELAY_BUFFER_SIZE);
+
+    let stream_metrics = metrics.clone();
+    let _handle = runtime.spawn(async move {
+        while let Some(batch) = stream.next().await {
+            if tx.send(batch).await.is_err() {
+                // The receiver is dropped.
+                return;
+            }
+        }
+        // Set before the sender is dropped, so they are complete once the relay
+        // stream ends.
+        *stream_metrics.lock().unwrap() = stream.metrics();
+    });
+
+    Box::pin(RelayStream {
+        schema,
+        output_ordering,
+        metrics,
+        rx,
+    })
+}
+
+struct RelayStream {
+    schema: SchemaRef,
+    output_ordering: Option<Vec<OrderOption>>,
+    metrics: Arc<Mutex<Option<RecordBatchMetrics>>>,
+    rx: mpsc::Receiver<RecordBatchResult<RecordBatch>>,
+}
+
+impl RecordBatchStream for RelayStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.output_ordering.as_deref()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.metrics.lock().unwrap().clone()
+    }
+}
+
+impl Stream for RelayStream {
+    type Item = RecordBatchResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use common_recordbatch::{util, RecordBatches};
+    use datatypes::prelude::*;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::UInt32Vector;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_queries_not_starving_writes() {
+        let runtimes = Arc::new(WorkloadRuntimes::try_new(Some(1), Some(1)).unwrap());
+
+        // The query blocks the only thread of the query runtime.
+        let query_runtimes = runtimes.clone();
+        let query = tokio::spawn(async move {
+            query_runtimes
+                .query(async {
+                    std::thread::sleep(Duration::from_secs(2));
+                    0
+                })
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let start = Instant::now();
+        let rows = runtimes.ingest(async { 1 }).await.unwrap();
+        assert_eq!(1, rows);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert_eq!(0, query.await.unwrap().unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_relay_stream() {
+        let runtimes = WorkloadRuntimes::try_new(None, Some(1)).unwrap();
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "n",
+            ConcreteDataType::uint32_datatype(),
+            false,
+        )]));
+        let batches = RecordBatches::try_from_columns(
+            schema,
+            vec![Arc::new(UInt32Vector::from_slice([1, 2, 3])) as _],
+        )
+        .unwrap();
+
+        let output = runtimes
+            .query(async move { Output::Stream(batches.as_stream()) })
+            .await
+            .unwrap();
+        let Output::Stream(stream) = runtimes.relay(output) else {
+            unreachable!()
+        };
+        let batches = util::collect(stream).await.unwrap();
+        assert_eq!(3, batches[0].num_rows());
+    }
+}
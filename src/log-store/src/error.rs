@@ -84,6 +84,61 @@ pub enum Error {
         attempt_index: u64,
         location: Location,
     },
+
+    #[snafu(display("Invalid Kafka WAL config: {}", msg))]
+    InvalidKafkaConfig { msg: String, location: Location },
+
+    #[snafu(display("Failed to connect to Kafka brokers: {:?}", broker_endpoints))]
+    BuildKafkaClient {
+        broker_endpoints: Vec<String>,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to create Kafka topic: {}", topic))]
+    CreateKafkaTopic {
+        topic: String,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to list Kafka topics"))]
+    ListKafkaTopics {
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to get the partition client of Kafka topic: {}", topic))]
+    GetKafkaPartitionClient {
+        topic: String,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to produce records to Kafka topic: {}", topic))]
+    ProduceKafkaRecords {
+        topic: String,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Failed to fetch records from Kafka topic: {}, offset: {}",
+        topic,
+        offset
+    ))]
+    FetchKafkaRecords {
+        topic: String,
+        offset: i64,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
 }
 
 impl ErrorExt for Error {
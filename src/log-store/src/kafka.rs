@@ -0,0 +1,140 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A WAL on Kafka topics.
+//!
+//! Namespaces (regions) share a fixed set of single partition topics. A record of an
+//! entry is keyed by its namespace id and carries the entry id in a header, so the
+//! entries of a namespace are found by scanning its topic.
+
+pub mod log_store;
+
+use std::collections::BTreeMap;
+
+use rskafka::record::Record;
+use store_api::logstore::entry::{Entry, Id};
+use store_api::logstore::namespace::{Id as NamespaceId, Namespace};
+
+use crate::error::Error;
+
+const ENTRY_ID_HEADER: &str = "entry_id";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamespaceImpl {
+    pub id: NamespaceId,
+}
+
+impl Namespace for NamespaceImpl {
+    fn id(&self) -> NamespaceId {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryImpl {
+    pub id: Id,
+    pub namespace_id: NamespaceId,
+    pub data: Vec<u8>,
+}
+
+impl Entry for EntryImpl {
+    type Error = Error;
+    type Namespace = NamespaceImpl;
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn namespace(&self) -> Self::Namespace {
+        NamespaceImpl {
+            id: self.namespace_id,
+        }
+    }
+}
+
+/// Returns the topic of the namespace `id` among the `num_topics` topics.
+pub(crate) fn topic_of(prefix: &str, num_topics: usize, id: NamespaceId) -> String {
+    format!("{prefix}_{}", id % num_topics as u64)
+}
+
+impl From<EntryImpl> for Record {
+    fn from(entry: EntryImpl) -> Self {
+        Record {
+            key: Some(entry.namespace_id.to_be_bytes().to_vec()),
+            value: Some(entry.data),
+            headers: BTreeMap::from([(
+                ENTRY_ID_HEADER.to_string(),
+                entry.id.to_be_bytes().to_vec(),
+            )]),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Decodes the entry of the `record`, or returns `None` if the record isn't an entry,
+/// e.g. it's produced by someone else.
+pub(crate) fn decode_record(record: Record) -> Option<EntryImpl> {
+    let namespace_id = u64::from_be_bytes(record.key?.try_into().ok()?);
+    let id = u64::from_be_bytes(
+        record
+            .headers
+            .get(ENTRY_ID_HEADER)?
+            .as_slice()
+            .try_into()
+            .ok()?,
+    );
+    Some(EntryImpl {
+        id,
+        namespace_id,
+        data: record.value.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_codec() {
+        let entry = EntryImpl {
+            id: 42,
+            namespace_id: (1024 << 32) | 1,
+            data: b"hello".to_vec(),
+        };
+        let record = Record::from(entry.clone());
+        assert_eq!(Some(entry), decode_record(record.clone()));
+
+        let foreign = Record {
+            headers: BTreeMap::new(),
+            ..record.clone()
+        };
+        assert_eq!(None, decode_record(foreign));
+        let foreign = Record {
+            key: Some(b"key".to_vec()),
+            ..record
+        };
+        assert_eq!(None, decode_record(foreign));
+    }
+
+    #[test]
+    fn test_topic_of() {
+        assert_eq!("wal_0", topic_of("wal", 4, 0));
+        assert_eq!("wal_3", topic_of("wal", 4, 7));
+        assert_eq!("wal_1", topic_of("wal", 4, (1024 << 32) | 1));
+    }
+}
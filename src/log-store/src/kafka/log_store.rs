@@ -0,0 +1,618 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use async_stream::stream;
+use backon::{BackoffBuilder, ExponentialBuilder};
+use common_config::{KafkaWalConfig, WalConfig, WalProvider};
+use common_telemetry::{info, warn};
+use rskafka::client::error::{Error as KafkaError, ProtocolError};
+use rskafka::client::partition::{Compression, OffsetAt, PartitionClient, UnknownTopicHandling};
+use rskafka::client::{Client, ClientBuilder};
+use rskafka::record::Record;
+use rskafka::BackoffConfig;
+use snafu::{ensure, ResultExt};
+use store_api::logstore::entry::Id;
+use store_api::logstore::entry_stream::SendableEntryStream;
+use store_api::logstore::namespace::Id as NamespaceId;
+use store_api::logstore::{AppendResponse, LogStore};
+use tokio::sync::RwLock;
+
+use crate::error::{
+    BuildKafkaClientSnafu, CreateKafkaTopicSnafu, Error, FetchKafkaRecordsSnafu,
+    GetKafkaPartitionClientSnafu, InvalidKafkaConfigSnafu, ListKafkaTopicsSnafu,
+    ProduceKafkaRecordsSnafu, Result,
+};
+use crate::kafka::{decode_record, topic_of, EntryImpl, NamespaceImpl};
+
+/// Max time a fetch waits for the records, the fetch returns earlier once there are
+/// records.
+const FETCH_MAX_WAIT_MS: i32 = 500;
+
+/// A [LogStore] appending the entries to Kafka topics.
+///
+/// Entries are never deleted by the log store as the topics are shared by namespaces,
+/// so the obsolete entries are reclaimed by the retention of the topics.
+///
+/// The offsets of the entries appended or read since the log store is started are
+/// tracked, so a namespace is read from its first entry not obsoleted, or from the
+/// earliest offset of its topic if it hasn't been obsoleted since.
+pub struct KafkaLogStore {
+    config: KafkaWalConfig,
+    client: Client,
+    partition_clients: RwLock<HashMap<String, Arc<PartitionClient>>>,
+    offsets: Mutex<HashMap<NamespaceId, EntryOffsets>>,
+}
+
+/// Offsets of the entries of a namespace in its topic.
+#[derive(Debug, Default)]
+struct EntryOffsets {
+    /// Offsets of the entries not obsoleted yet, by their ids.
+    entries: BTreeMap<Id, i64>,
+    /// The offset before which all the entries are obsoleted.
+    start: i64,
+}
+
+impl EntryOffsets {
+    /// Obsoletes the entries up to `id`.
+    fn obsolete(&mut self, id: Id) {
+        let retained = self.entries.split_off(&id.saturating_add(1));
+        // Entries are appended in the order of their ids.
+        let start = match retained.values().next() {
+            Some(offset) => *offset,
+            None => match self.entries.values().next_back() {
+                Some(offset) => offset + 1,
+                None => self.start,
+            },
+        };
+        self.start = self.start.max(start);
+        self.entries = retained;
+    }
+}
+
+impl KafkaLogStore {
+    /// Connects to the brokers and creates the topics, retrying with backoff until
+    /// the `backoff_deadline` as Kafka may be unavailable on startup.
+    pub async fn try_new(config: WalConfig) -> Result<Self> {
+        validate_config(&config)?;
+        let config = config.kafka;
+
+        let backoff_config = BackoffConfig {
+            init_backoff: config.backoff_init,
+            max_backoff: config.backoff_max,
+            base: config.backoff_base as f64,
+            deadline: Some(config.backoff_deadline),
+        };
+        let client = retry_with_backoff(&config, "connect to Kafka brokers", || {
+            ClientBuilder::new(config.broker_endpoints.clone())
+                .backoff_config(backoff_config.clone())
+                .build()
+        })
+        .await
+        .context(BuildKafkaClientSnafu {
+            broker_endpoints: config.broker_endpoints.clone(),
+        })?;
+
+        let log_store = Self {
+            config,
+            client,
+            partition_clients: RwLock::new(HashMap::new()),
+            offsets: Mutex::new(HashMap::new()),
+        };
+        log_store.check_num_topics().await?;
+        log_store.create_topics().await?;
+        info!(
+            "Kafka log store is started, brokers: {:?}, topics: {}",
+            log_store.config.broker_endpoints, log_store.config.num_topics
+        );
+        Ok(log_store)
+    }
+
+    /// Checks that the number of topics isn't changed, as the namespaces would be
+    /// mapped to other topics than their entries are in. The topics can still be
+    /// changed if none of them has records, e.g. their creation was interrupted.
+    async fn check_num_topics(&self) -> Result<()> {
+        let topics = retry_with_backoff(&self.config, "list Kafka topics", || {
+            self.client.list_topics()
+        })
+        .await
+        .context(ListKafkaTopicsSnafu)?;
+        let prefix = format!("{}_", self.config.topic_name_prefix);
+        let existing = topics
+            .iter()
+            .filter_map(|topic| topic.name.strip_prefix(&prefix)?.parse::<usize>().ok())
+            .collect::<Vec<_>>();
+        // Topics are created in the order of their indexes.
+        let Some(num_topics) = existing.iter().max().map(|index| index + 1) else {
+            return Ok(());
+        };
+        if num_topics == self.config.num_topics {
+            return Ok(());
+        }
+
+        for index in existing {
+            let topic = format!("{prefix}{index}");
+            let client = self.partition_client(&topic).await?;
+            let (earliest, latest) = self.offset_range(&topic, &client).await?;
+            ensure!(
+                earliest == latest,
+                InvalidKafkaConfigSnafu {
+                    msg: format!(
+                        "the number of topics is changed from {num_topics} to {}, the WAL entries of the regions would be lost",
+                        self.config.num_topics
+                    ),
+                }
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the earliest and the latest offsets of the `topic`.
+    async fn offset_range(&self, topic: &str, client: &PartitionClient) -> Result<(i64, i64)> {
+        let earliest = client
+            .get_offset(OffsetAt::Earliest)
+            .await
+            .context(FetchKafkaRecordsSnafu { topic, offset: -1 })?;
+        let latest = client
+            .get_offset(OffsetAt::Latest)
+            .await
+            .context(FetchKafkaRecordsSnafu { topic, offset: -1 })?;
+        Ok((earliest, latest))
+    }
+
+    async fn create_topics(&self) -> Result<()> {
+        let controller = retry_with_backoff(&self.config, "get Kafka controller", || async {
+            self.client.controller_client()
+        })
+        .await
+        .context(CreateKafkaTopicSnafu {
+            topic: &self.config.topic_name_prefix,
+        })?;
+
+        for index in 0..self.config.num_topics {
+            let topic = topic_of(
+                &self.config.topic_name_prefix,
+                self.config.num_topics,
+                index as NamespaceId,
+            );
+            let result = retry_with_backoff(&self.config, "create Kafka topic", || {
+                controller.create_topic(
+                    topic.clone(),
+                    1,
+                    self.config.replication_factor,
+                    self.config.create_topic_timeout.as_millis() as i32,
+                )
+            })
+            .await;
+            match result {
+                Ok(()) => info!("Created Kafka topic {topic}"),
+                Err(KafkaError::ServerError {
+                    protocol_error: ProtocolError::TopicAlreadyExists,
+                    ..
+                }) => {}
+                Err(error) => return Err(error).context(CreateKafkaTopicSnafu { topic }),
+            }
+        }
+        Ok(())
+    }
+
+    fn topic(&self, namespace_id: NamespaceId) -> String {
+        topic_of(
+            &self.config.topic_name_prefix,
+            self.config.num_topics,
+            namespace_id,
+        )
+    }
+
+    async fn partition_client(&self, topic: &str) -> Result<Arc<PartitionClient>> {
+        if let Some(client) = self.partition_clients.read().await.get(topic) {
+            return Ok(client.clone());
+        }
+
+        let mut partition_clients = self.partition_clients.write().await;
+        if let Some(client) = partition_clients.get(topic) {
+            return Ok(client.clone());
+        }
+        let client = self
+            .client
+            .partition_client(topic, 0, UnknownTopicHandling::Retry)
+            .await
+            .context(GetKafkaPartitionClientSnafu { topic })?;
+        let client = Arc::new(client);
+        let _ = partition_clients.insert(topic.to_string(), client.clone());
+        Ok(client)
+    }
+
+    /// Produces the `entries` to the `topic`, split into requests of at most
+    /// `max_batch_size` bytes, and tracks their offsets.
+    async fn produce(&self, topic: &str, entries: Vec<EntryImpl>) -> Result<()> {
+        let client = self.partition_client(topic).await?;
+        let max_batch_size = self.config.max_batch_size.as_bytes() as usize;
+        let ids = entries
+            .iter()
+            .map(|entry| (entry.namespace_id, entry.id))
+            .collect::<Vec<_>>();
+
+        let mut offsets = Vec::with_capacity(ids.len());
+        let mut batch = Vec::new();
+        let mut batch_size = 0;
+        for entry in entries {
+            let record = Record::from(entry);
+            let size = record.approximate_size();
+            if !batch.is_empty() && batch_size + size > max_batch_size {
+                offsets.extend(
+                    client
+                        .produce(std::mem::take(&mut batch), Compression::NoCompression)
+                        .await
+                        .context(ProduceKafkaRecordsSnafu { topic })?,
+                );
+                batch_size = 0;
+            }
+            batch_size += size;
+            batch.push(record);
+        }
+        if !batch.is_empty() {
+            offsets.extend(
+                client
+                    .produce(batch, Compression::NoCompression)
+                    .await
+                    .context(ProduceKafkaRecordsSnafu { topic })?,
+            );
+        }
+
+        self.track_offsets(ids.into_iter().zip(offsets));
+        Ok(())
+    }
+
+    /// Tracks the offsets of the entries, by their namespace and entry ids.
+    fn track_offsets(&self, offsets: impl IntoIterator<Item = ((NamespaceId, Id), i64)>) {
+        let mut tracked = self.offsets.lock().unwrap();
+        for ((namespace_id, id), offset) in offsets {
+            let tracked = tracked.entry(namespace_id).or_default();
+            if offset >= tracked.start {
+                let _ = tracked.entries.insert(id, offset);
+            }
+        }
+    }
+}
+
+/// Checks that the Kafka WAL is selected without the options of the local WAL.
+fn validate_config(config: &WalConfig) -> Result<()> {
+    ensure!(
+        config.provider == WalProvider::Kafka,
+        InvalidKafkaConfigSnafu {
+            msg: format!("the WAL provider is {:?}", config.provider),
+        }
+    );
+    ensure!(
+        config.dir.is_none(),
+        InvalidKafkaConfigSnafu {
+            msg: "the WAL dir of the raft-engine provider must not be set",
+        }
+    );
+    ensure!(
+        !config.kafka.broker_endpoints.is_empty(),
+        InvalidKafkaConfigSnafu {
+            msg: "broker endpoints are empty",
+        }
+    );
+    ensure!(
+        config.kafka.num_topics > 0,
+        InvalidKafkaConfigSnafu {
+            msg: "the number of topics must be positive",
+        }
+    );
+    Ok(())
+}
+
+/// Retries the `operation` with exponential backoff until the backoff deadline.
+async fn retry_with_backoff<T, F, Fut>(
+    config: &KafkaWalConfig,
+    name: &str,
+    mut operation: F,
+) -> std::result::Result<T, KafkaError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, KafkaError>>,
+{
+    let start = Instant::now();
+    let mut backoff = backoff_builder(config).build();
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            // The topic exists, which won't change by retrying.
+            Err(
+                error @ KafkaError::ServerError {
+                    protocol_error: ProtocolError::TopicAlreadyExists,
+                    ..
+                },
+            ) => return Err(error),
+            Err(error) => error,
+        };
+        match backoff.next() {
+            Some(delay) if start.elapsed() + delay <= config.backoff_deadline => {
+                warn!("Failed to {name}, retry after {delay:?}, error: {error}");
+                tokio::time::sleep(delay).await;
+            }
+            _ => return Err(error),
+        }
+    }
+}
+
+fn backoff_builder(config: &KafkaWalConfig) -> ExponentialBuilder {
+    ExponentialBuilder::default()
+        .with_min_delay(config.backoff_init)
+        .with_max_delay(config.backoff_max)
+        .with_factor(config.backoff_base as f32)
+        .with_max_times(usize::MAX)
+}
+
+impl Debug for KafkaLogStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaLogStore")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl LogStore for KafkaLogStore {
+    type Error = Error;
+    type Namespace = NamespaceImpl;
+    type Entry = EntryImpl;
+
+    async fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn append(&self, entry: Self::Entry) -> Result<AppendResponse> {
+        let entry_id = entry.id;
+        let topic = self.topic(entry.namespace_id);
+        self.produce(&topic, vec![entry]).await?;
+        Ok(AppendResponse { entry_id })
+    }
+
+    /// Append a batch of entries, the entries of a topic are produced in order but
+    /// the batch isn't atomic across topics. The entries are persisted once the
+    /// brokers acknowledge them, so `sync` is ignored.
+    async fn append_batch(&self, entries: Vec<Self::Entry>, _sync: bool) -> Result<()> {
+        let mut entries_by_topic: HashMap<String, Vec<EntryImpl>> = HashMap::new();
+        for entry in entries {
+            entries_by_topic
+                .entry(self.topic(entry.namespace_id))
+                .or_default()
+                .push(entry);
+        }
+
+        futures::future::try_join_all(
+            entries_by_topic
+                .into_iter()
+                .map(|(topic, entries)| async move { self.produce(&topic, entries).await }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Create a stream of entries of the namespace from its topic, starting at its first
+    /// entry not obsoleted if it's tracked. The end of stream is determined by the latest
+    /// offset of the topic when the stream is created.
+    async fn read(
+        &self,
+        ns: &Self::Namespace,
+        id: Id,
+    ) -> Result<SendableEntryStream<'_, Self::Entry, Self::Error>> {
+        let topic = self.topic(ns.id);
+        let client = self.partition_client(&topic).await?;
+        let (earliest, end) = self.offset_range(&topic, &client).await?;
+        let start = self
+            .offsets
+            .lock()
+            .unwrap()
+            .get(&ns.id)
+            .map_or(earliest, |tracked| tracked.start);
+        // The records before the start may be deleted by the retention.
+        let mut offset = start.max(earliest);
+        info!(
+            "Read Kafka log store, namespace: {}, start: {}, topic: {}, offsets: {}..{}",
+            ns.id, id, topic, offset, end
+        );
+
+        let namespace_id = ns.id;
+        let max_bytes = (self.config.max_batch_size.as_bytes() as i32).max(1);
+        let stream = stream!({
+            while offset < end {
+                let records = match client
+                    .fetch_records(offset, 1..max_bytes, FETCH_MAX_WAIT_MS)
+                    .await
+                    .context(FetchKafkaRecordsSnafu {
+                        topic: &topic,
+                        offset,
+                    }) {
+                    Ok((records, _high_watermark)) => records,
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                };
+                let Some(last) = records.last() else {
+                    break;
+                };
+                offset = last.offset + 1;
+
+                let (entries, offsets): (Vec<_>, Vec<_>) = records
+                    .into_iter()
+                    .filter_map(|record| {
+                        let offset = record.offset;
+                        decode_record(record.record).map(|entry| (entry, offset))
+                    })
+                    .filter(|(entry, _)| entry.namespace_id == namespace_id && entry.id >= id)
+                    .unzip();
+                // The entries read are obsoleted once the namespace is flushed.
+                self.track_offsets(
+                    entries
+                        .iter()
+                        .map(|entry| (entry.namespace_id, entry.id))
+                        .zip(offsets),
+                );
+                if !entries.is_empty() {
+                    yield Ok(entries);
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    /// Namespaces are assigned to the topics created on startup.
+    async fn create_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
+        Ok(())
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<Self::Namespace>> {
+        Ok(vec![])
+    }
+
+    fn entry<D: AsRef<[u8]>>(&self, data: D, id: Id, ns: Self::Namespace) -> Self::Entry {
+        EntryImpl {
+            id,
+            namespace_id: ns.id,
+            data: data.as_ref().to_vec(),
+        }
+    }
+
+    fn namespace(&self, id: NamespaceId) -> Self::Namespace {
+        NamespaceImpl { id }
+    }
+
+    /// Obsolete entries are skipped by the next reads of the namespace, and are removed
+    /// by the retention of the topics.
+    async fn obsolete(&self, namespace: Self::Namespace, id: Id) -> Result<()> {
+        self.offsets
+            .lock()
+            .unwrap()
+            .entry(namespace.id)
+            .or_default()
+            .obsolete(id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_config() {
+        let mut config = WalConfig {
+            provider: WalProvider::Kafka,
+            ..Default::default()
+        };
+        validate_config(&config).unwrap();
+
+        config.dir = Some("/tmp/wal".to_string());
+        assert!(validate_config(&config).is_err());
+        config.dir = None;
+
+        config.kafka.broker_endpoints = vec![];
+        assert!(validate_config(&config).is_err());
+        config.kafka.broker_endpoints = vec!["127.0.0.1:9092".to_string()];
+
+        config.kafka.num_topics = 0;
+        assert!(validate_config(&config).is_err());
+
+        assert!(validate_config(&WalConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_obsolete_entry_offsets() {
+        let mut offsets = EntryOffsets {
+            entries: BTreeMap::from([(3, 10), (4, 12), (5, 15)]),
+            start: 0,
+        };
+
+        offsets.obsolete(1);
+        assert_eq!(10, offsets.start);
+        assert_eq!(3, offsets.entries.len());
+
+        offsets.obsolete(4);
+        assert_eq!(15, offsets.start);
+        assert_eq!(vec![5], offsets.entries.keys().copied().collect::<Vec<_>>());
+
+        offsets.obsolete(5);
+        assert_eq!(16, offsets.start);
+        assert!(offsets.entries.is_empty());
+
+        // Never moves backward.
+        offsets.obsolete(2);
+        assert_eq!(16, offsets.start);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = KafkaWalConfig {
+            backoff_init: Duration::from_millis(100),
+            backoff_max: Duration::from_millis(500),
+            backoff_base: 2,
+            ..Default::default()
+        };
+        let delays = backoff_builder(&config).build().take(5).collect::<Vec<_>>();
+        assert_eq!(
+            vec![100, 200, 400, 500, 500],
+            delays.iter().map(|d| d.as_millis()).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_deadline() {
+        let config = KafkaWalConfig {
+            backoff_init: Duration::from_millis(10),
+            backoff_max: Duration::from_millis(20),
+            backoff_deadline: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let mut attempts = 0;
+        let result = retry_with_backoff(&config, "test", || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(KafkaError::InvalidResponse("unavailable".to_string()))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(3, result.unwrap());
+
+        let start = Instant::now();
+        let result: std::result::Result<(), _> = retry_with_backoff(&config, "test", || async {
+            Err(KafkaError::InvalidResponse("unavailable".to_string()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert!(start.elapsed() <= Duration::from_millis(200));
+    }
+}
@@ -15,6 +15,7 @@
 #![feature(let_chains)]
 
 pub mod error;
+pub mod kafka;
 mod noop;
 pub mod raft_engine;
 pub mod test_util;
@@ -65,7 +65,7 @@ impl LogStore for NoopLogStore {
         Ok(AppendResponse { entry_id: 0 })
     }
 
-    async fn append_batch(&self, _e: Vec<Self::Entry>) -> Result<()> {
+    async fn append_batch(&self, _e: Vec<Self::Entry>, _sync: bool) -> Result<()> {
         Ok(())
     }
 
@@ -131,7 +131,7 @@ mod tests {
         let store = NoopLogStore;
         let e = store.entry("".as_bytes(), 1, NamespaceImpl);
         let _ = store.append(e.clone()).await.unwrap();
-        assert!(store.append_batch(vec![e]).await.is_ok());
+        assert!(store.append_batch(vec![e], false).await.is_ok());
         store.create_namespace(&NamespaceImpl).await.unwrap();
         assert_eq!(0, store.list_namespaces().await.unwrap().len());
         store.delete_namespace(&NamespaceImpl).await.unwrap();
@@ -183,7 +183,7 @@ impl LogStore for RaftEngineLogStore {
 
     /// Append a batch of entries to logstore. `RaftEngineLogStore` assures the atomicity of
     /// batch append.
-    async fn append_batch(&self, entries: Vec<Self::Entry>) -> Result<()> {
+    async fn append_batch(&self, entries: Vec<Self::Entry>, sync: bool) -> Result<()> {
         ensure!(self.started(), IllegalStateSnafu);
         if entries.is_empty() {
             return Ok(());
@@ -201,7 +201,7 @@ impl LogStore for RaftEngineLogStore {
 
         let _ = self
             .engine
-            .write(&mut batch, self.config.sync_write)
+            .write(&mut batch, sync || self.config.sync_write)
             .context(RaftEngineSnafu)?;
         Ok(())
     }
@@ -598,7 +598,7 @@ mod tests {
             })
             .collect();
 
-        logstore.append_batch(entries).await.unwrap();
+        logstore.append_batch(entries, false).await.unwrap();
         for ns_id in 0..8 {
             let namespace = Namespace::with_id(ns_id);
             let (first, last) = logstore.span(&namespace);
@@ -630,7 +630,7 @@ mod tests {
             Entry::create(1, 1, [b'1'; 4096].to_vec()),
         ];
 
-        logstore.append_batch(entries).await.unwrap();
+        logstore.append_batch(entries, false).await.unwrap();
 
         assert_eq!((Some(0), Some(2)), logstore.span(&Namespace::with_id(0)));
         assert_eq!((Some(0), Some(1)), logstore.span(&Namespace::with_id(1)));
@@ -26,6 +26,7 @@ pub mod mock {
     use common_meta::peer::Peer;
     use common_runtime::{Builder as RuntimeBuilder, Runtime};
     use servers::grpc::region_server::{RegionServerHandler, RegionServerRequestHandler};
+    use store_api::region_request::WriteAckLevel;
     use tokio::sync::mpsc;
     use tonic::transport::Server;
     use tower::service_fn;
@@ -82,6 +83,7 @@ pub mod mock {
         async fn handle(
             &self,
             request: region_request::Body,
+            _ack_level: WriteAckLevel,
         ) -> servers::error::Result<RegionResponse> {
             self.received_requests.send(request).await.unwrap();
 
@@ -22,7 +22,7 @@ use datatypes::vectors::StringVector;
 use mito2::engine::MitoEngine;
 use snafu::ResultExt;
 use store_api::region_engine::RegionEngine;
-use store_api::region_request::{RegionPutRequest, RegionReadRequest};
+use store_api::region_request::{RegionPutRequest, RegionReadRequest, WriteAckLevel};
 use store_api::storage::{RegionId, ScanRequest, TableId};
 
 use crate::engine::{
@@ -296,7 +296,10 @@ impl MetadataRegion {
             }],
         };
 
-        RegionPutRequest { rows }
+        RegionPutRequest {
+            rows,
+            ack_level: WriteAckLevel::default(),
+        }
     }
 }
 
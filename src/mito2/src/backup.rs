@@ -0,0 +1,147 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backup of the flushed data of a region.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_compat::CompatExt;
+use common_datasource::compression::CompressionType;
+use common_telemetry::info;
+use object_store::util::join_dir;
+use object_store::ObjectStore;
+use snafu::ResultExt;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{CopyObjectSnafu, OpenDalSnafu, PinRegionVersionSnafu, Result};
+use crate::manifest::action::{RegionCheckpoint, RegionManifest};
+use crate::manifest::storage::ManifestObjectStore;
+use crate::region::opener::new_manifest_dir;
+use crate::region::version::VersionRef;
+use crate::region::MitoRegionRef;
+
+/// Statistics of a region backup.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RegionBackupStat {
+    /// Number of SST files copied.
+    pub files: usize,
+    /// Number of bytes copied.
+    pub bytes: u64,
+}
+
+/// Times to pin the version of a region before giving up.
+const MAX_PIN_ATTEMPTS: usize = 3;
+/// Interval to wait for the edit of a region to be applied to its version.
+const PIN_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Copies the SSTs of the current version of the `region` to the region directory under
+/// `target_root` in `target`, then writes the manifest of the version as a checkpoint,
+/// so the backup can be opened as a region once the checkpoint exists.
+///
+/// The version is a snapshot, so writes, flushes and compactions of the region go on
+/// while copying. Holding the version keeps its SSTs from being purged until the copy
+/// finishes.
+pub(crate) async fn backup_region(
+    region: &MitoRegionRef,
+    target: &ObjectStore,
+    target_root: &str,
+) -> Result<RegionBackupStat> {
+    let (version, manifest) = pin_version(region).await?;
+    let source = region.access_layer.object_store();
+    let source_dir = region.access_layer.region_dir();
+    let target_dir = join_dir(target_root, source_dir);
+
+    let mut stat = RegionBackupStat::default();
+    for level in version.ssts.levels() {
+        for file in level.files() {
+            let from = file.file_path(source_dir);
+            let to = file.file_path(&target_dir);
+            stat.bytes += copy_object(source, &from, target, &to).await?;
+            stat.files += 1;
+        }
+    }
+
+    let manifest_version = manifest.manifest_version;
+    let checkpoint = RegionCheckpoint {
+        last_version: manifest_version,
+        compacted_actions: 0,
+        checkpoint: Some(RegionManifest::clone(&manifest)),
+    };
+    let mut manifest_store = ManifestObjectStore::new(
+        &new_manifest_dir(&target_dir),
+        target.clone(),
+        CompressionType::Uncompressed,
+    );
+    manifest_store
+        .save_checkpoint(manifest_version, &checkpoint.encode()?)
+        .await?;
+
+    info!(
+        "Backed up region {} to {}, manifest version: {}, files: {}, bytes: {}",
+        region.region_id, target_dir, manifest_version, stat.files, stat.bytes
+    );
+
+    Ok(stat)
+}
+
+/// Returns the current version of the `region` and the manifest with the same files.
+///
+/// The manifest is updated before the version on flush and compaction, so they may
+/// differ while an edit is applied. The files of the manifest are copied while the
+/// version keeps them from being purged.
+async fn pin_version(region: &MitoRegionRef) -> Result<(VersionRef, Arc<RegionManifest>)> {
+    for _ in 0..MAX_PIN_ATTEMPTS {
+        let version = region.version();
+        let manifest = region.manifest_manager.manifest().await;
+        let files: HashSet<_> = version
+            .ssts
+            .levels()
+            .iter()
+            .flat_map(|level| level.files().map(|file| file.file_id()))
+            .collect();
+        if files.len() == manifest.files.len()
+            && manifest.files.keys().all(|file_id| files.contains(file_id))
+        {
+            return Ok((version, manifest));
+        }
+        tokio::time::sleep(PIN_RETRY_INTERVAL).await;
+    }
+
+    PinRegionVersionSnafu {
+        region_id: region.region_id,
+    }
+    .fail()
+}
+
+/// Copies the object `from` in `source` to `to` in `target`, returns the number of bytes copied.
+async fn copy_object(
+    source: &ObjectStore,
+    from: &str,
+    target: &ObjectStore,
+    to: &str,
+) -> Result<u64> {
+    let mut reader = source.reader(from).await.context(OpenDalSnafu)?.compat();
+    let mut writer = target.writer(to).await.context(OpenDalSnafu)?;
+    let bytes = tokio::io::copy(&mut reader, &mut writer)
+        .await
+        .context(CopyObjectSnafu { from, to })?;
+    writer
+        .shutdown()
+        .await
+        .context(CopyObjectSnafu { from, to })?;
+
+    Ok(bytes)
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod leveled;
 mod output;
 mod picker;
 #[cfg(test)]
@@ -30,12 +31,14 @@ use store_api::storage::RegionId;
 use tokio::sync::mpsc::{self, Sender};
 
 use crate::access_layer::AccessLayerRef;
+use crate::compaction::leveled::LeveledPicker;
 use crate::compaction::twcs::TwcsPicker;
 use crate::config::MitoConfig;
 use crate::error::{
     CompactRegionSnafu, Error, RegionClosedSnafu, RegionDroppedSnafu, RegionTruncatedSnafu, Result,
 };
 use crate::metrics::COMPACTION_STAGE_ELAPSED;
+use crate::read::version::KeepVersions;
 use crate::region::options::CompactionOptions;
 use crate::region::version::{VersionControlRef, VersionRef};
 use crate::request::{OptionOutputTx, OutputTx, WorkerRequest};
@@ -55,6 +58,11 @@ pub struct CompactionRequest {
     pub(crate) start_time: Instant,
     /// Buffering threshold while writing SST files.
     pub(crate) sst_write_buffer_size: ReadableSize,
+    /// Versions of rows to keep in compaction outputs.
+    pub(crate) keep_versions: KeepVersions,
+    /// Compaction options of the region, or the engine's default strategy if the
+    /// region doesn't set them.
+    pub(crate) compaction_options: CompactionOptions,
 }
 
 impl CompactionRequest {
@@ -78,6 +86,9 @@ pub fn compaction_options_to_picker(strategy: &CompactionOptions) -> CompactionP
             twcs_opts.max_inactive_window_files,
             twcs_opts.time_window_seconds(),
         )) as Arc<_>,
+        CompactionOptions::Leveled(leveled_opts) => {
+            Arc::new(LeveledPicker::new(leveled_opts.max_l0_files)) as Arc<_>
+        }
     }
 }
 
@@ -186,7 +197,9 @@ impl CompactionScheduler {
     ///
     /// If the region has nothing to compact, it removes the region from the status map.
     fn schedule_compaction_request(&mut self, request: CompactionRequest) -> Result<()> {
-        let picker = compaction_options_to_picker(&request.current_version.options.compaction);
+        // The strategy is resolved for each compaction, so a changed strategy applies to
+        // the next compaction once the running one finishes.
+        let picker = compaction_options_to_picker(&request.compaction_options);
         let region_id = request.region_id();
         debug!(
             "Pick compaction strategy {:?} for region: {}",
@@ -316,6 +329,13 @@ impl CompactionStatus {
         engine_config: Arc<MitoConfig>,
     ) -> CompactionRequest {
         let current_version = self.version_control.current().version;
+        let compaction_options = current_version
+            .options
+            .compaction
+            .clone()
+            .unwrap_or_else(|| engine_config.compaction_strategy.into());
+        let keep_versions = KeepVersions::new(&engine_config)
+            .with_policy(current_version.options.duplicate_timestamp_policy());
         let start_time = Instant::now();
         let mut req = CompactionRequest {
             current_version,
@@ -325,6 +345,8 @@ impl CompactionStatus {
             file_purger: self.file_purger.clone(),
             start_time,
             sst_write_buffer_size: engine_config.sst_write_buffer_size,
+            keep_versions,
+            compaction_options,
         };
 
         if let Some(pending) = self.pending_compaction.take() {
@@ -0,0 +1,261 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Debug, Formatter};
+
+use common_query::Output;
+use common_telemetry::{debug, info};
+use common_time::Timestamp;
+
+use crate::compaction::output::CompactionOutput;
+use crate::compaction::picker::{CompactionTask, Picker};
+use crate::compaction::twcs::{get_expired_ssts, TwcsCompactionTask};
+use crate::compaction::CompactionRequest;
+use crate::sst::file::{FileHandle, FileId, FileTimeRange};
+use crate::sst::version::LevelMeta;
+
+/// `LeveledPicker` merges all files in level 0 with the files in level 1 overlapping
+/// them, once level 0 has more than `max_l0_files` files. It keeps level 1 free of
+/// overlapping files, so a row is only updated in one file of level 1.
+///
+/// The files are merged by groups of files with overlapping time ranges, each group
+/// into its own output, so a compaction doesn't rewrite unrelated time ranges into a
+/// single file. A group isn't picked while one of its files is being compacted, as the
+/// output of the running compaction could overlap the output of the group.
+pub struct LeveledPicker {
+    max_l0_files: usize,
+}
+
+impl Debug for LeveledPicker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LeveledPicker")
+            .field("max_l0_files", &self.max_l0_files)
+            .finish()
+    }
+}
+
+impl LeveledPicker {
+    pub fn new(max_l0_files: usize) -> Self {
+        Self { max_l0_files }
+    }
+
+    /// Builds compaction output from files in level 0 and level 1.
+    fn build_output(&self, levels: &[LevelMeta]) -> Vec<CompactionOutput> {
+        let num_l0_files = levels[0].files().filter(|f| !f.compacting()).count();
+        if num_l0_files <= self.max_l0_files {
+            debug!(
+                "No enough files in level 0, current: {}, max_l0_files: {}",
+                num_l0_files, self.max_l0_files
+            );
+            return vec![];
+        }
+
+        let l0_files: Vec<_> = levels[0].files().cloned().collect();
+        let mut files = l0_files.clone();
+        files.extend(
+            levels[1]
+                .files()
+                .filter(|f| {
+                    l0_files
+                        .iter()
+                        .any(|l0_file| overlaps(&f.time_range(), &l0_file.time_range()))
+                })
+                .cloned(),
+        );
+
+        group_overlapping(files)
+            .into_iter()
+            .filter(|group| {
+                let compacting = group.iter().any(|f| f.compacting());
+                if compacting {
+                    debug!(
+                        "Skip files {:?} as some of them are being compacted",
+                        group.iter().map(|f| f.file_id()).collect::<Vec<_>>()
+                    );
+                }
+                !compacting
+            })
+            .map(|inputs| CompactionOutput {
+                output_file_id: FileId::random(),
+                output_level: 1,
+                inputs,
+            })
+            .collect()
+    }
+}
+
+impl Picker for LeveledPicker {
+    fn pick(&self, req: CompactionRequest) -> Option<Box<dyn CompactionTask>> {
+        let CompactionRequest {
+            current_version,
+            access_layer,
+            request_sender,
+            waiters,
+            file_purger,
+            start_time,
+            sst_write_buffer_size,
+            keep_versions,
+            compaction_options: _,
+        } = req;
+
+        let region_metadata = current_version.metadata.clone();
+        let region_id = region_metadata.region_id;
+
+        let levels = current_version.ssts.levels();
+        let ttl = current_version.options.ttl;
+        let expired_ssts = get_expired_ssts(levels, ttl, Timestamp::current_millis());
+        if !expired_ssts.is_empty() {
+            info!("Expired SSTs in region {}: {:?}", region_id, expired_ssts);
+            // here we mark expired SSTs as compacting to avoid them being picked.
+            expired_ssts.iter().for_each(|f| f.set_compacting(true));
+        }
+
+        let outputs = self.build_output(levels);
+        if outputs.is_empty() && expired_ssts.is_empty() {
+            // Nothing to compact, we are done. Notifies all waiters as we consume the compaction request.
+            for waiter in waiters {
+                waiter.send(Ok(Output::AffectedRows(0)));
+            }
+            return None;
+        }
+        let task = TwcsCompactionTask {
+            region_id,
+            schema: region_metadata,
+            sst_layer: access_layer,
+            outputs,
+            expired_ssts,
+            sst_write_buffer_size,
+            keep_versions,
+            // Leaves the compaction time window of the region unchanged, in case the
+            // region switches back to the time window compaction.
+            compaction_time_window: None,
+            request_sender,
+            waiters,
+            file_purger,
+            start_time,
+        };
+        Some(Box::new(task))
+    }
+}
+
+/// Splits the `files` into groups whose time ranges overlap transitively, the time
+/// ranges of different groups don't overlap.
+fn group_overlapping(mut files: Vec<FileHandle>) -> Vec<Vec<FileHandle>> {
+    files.sort_unstable_by_key(|f| f.time_range().0);
+
+    let mut groups: Vec<(Timestamp, Vec<FileHandle>)> = Vec::new();
+    for file in files {
+        let (start, end) = file.time_range();
+        match groups.last_mut() {
+            Some((group_end, group)) if start <= *group_end => {
+                *group_end = (*group_end).max(end);
+                group.push(file);
+            }
+            _ => groups.push((end, vec![file])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+/// Returns whether two inclusive time ranges overlap.
+fn overlaps(lhs: &FileTimeRange, rhs: &FileTimeRange) -> bool {
+    lhs.0 <= rhs.1 && rhs.0 <= lhs.1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::compaction::test_util::new_file_handle;
+
+    fn new_levels(files: &[FileHandle]) -> Vec<LevelMeta> {
+        let mut levels = vec![LevelMeta::new(0), LevelMeta::new(1)];
+        for file in files {
+            let level = file.meta().level as usize;
+            levels[level].files.insert(file.file_id(), file.clone());
+        }
+        levels
+    }
+
+    fn output_inputs(output: &CompactionOutput) -> HashSet<FileId> {
+        output.inputs.iter().map(|f| f.file_id()).collect()
+    }
+
+    #[test]
+    fn test_build_leveled_output() {
+        let file_ids = (0..7).map(|_| FileId::random()).collect::<Vec<_>>();
+        let files = [
+            new_file_handle(file_ids[0], 0, 999, 0),
+            new_file_handle(file_ids[1], 500, 1999, 0),
+            new_file_handle(file_ids[2], 10000, 10999, 0),
+            // Overlaps files in level 0.
+            new_file_handle(file_ids[3], 1500, 5000, 1),
+            new_file_handle(file_ids[4], 10500, 12000, 1),
+            // Doesn't overlap files in level 0.
+            new_file_handle(file_ids[5], 6000, 7000, 1),
+            new_file_handle(file_ids[6], -1000, -1, 1),
+        ];
+        let levels = new_levels(&files);
+
+        // No enough files in level 0.
+        assert!(LeveledPicker::new(3).build_output(&levels).is_empty());
+
+        // Files of different time ranges are merged into different outputs.
+        let mut outputs = LeveledPicker::new(2).build_output(&levels);
+        outputs.sort_unstable_by_key(|output| output.inputs.len());
+        assert_eq!(2, outputs.len());
+        assert!(outputs.iter().all(|output| output.output_level == 1));
+        assert_eq!(
+            HashSet::from([file_ids[2], file_ids[4]]),
+            output_inputs(&outputs[0])
+        );
+        assert_eq!(
+            HashSet::from([file_ids[0], file_ids[1], file_ids[3]]),
+            output_inputs(&outputs[1])
+        );
+
+        // Files overlapping files being compacted are skipped, e.g. the ones picked
+        // by the previous strategy.
+        files[4].set_compacting(true);
+        let outputs = LeveledPicker::new(2).build_output(&levels);
+        assert_eq!(1, outputs.len());
+        assert_eq!(
+            HashSet::from([file_ids[0], file_ids[1], file_ids[3]]),
+            output_inputs(&outputs[0])
+        );
+
+        // Compacting files in level 0 aren't counted.
+        files[0].set_compacting(true);
+        assert!(LeveledPicker::new(2).build_output(&levels).is_empty());
+        assert!(LeveledPicker::new(1).build_output(&levels).is_empty());
+    }
+
+    #[test]
+    fn test_group_overlapping() {
+        let files = [
+            new_file_handle(FileId::random(), 0, 999, 0),
+            new_file_handle(FileId::random(), 2000, 2999, 0),
+            new_file_handle(FileId::random(), 500, 1500, 1),
+            new_file_handle(FileId::random(), 1500, 1800, 0),
+            new_file_handle(FileId::random(), 3000, 3999, 0),
+        ];
+        let groups = group_overlapping(files.to_vec());
+        let ranges = groups
+            .iter()
+            .map(|group| group.iter().map(|f| f.time_range().0.value()).collect())
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(vec![vec![0, 500, 1500], vec![2000], vec![3000]], ranges);
+    }
+}
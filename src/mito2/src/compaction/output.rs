@@ -20,6 +20,7 @@ use crate::access_layer::AccessLayerRef;
 use crate::error;
 use crate::read::projection::ProjectionMapper;
 use crate::read::seq_scan::SeqScan;
+use crate::read::version::KeepVersions;
 use crate::read::{BoxedBatchReader, Source};
 use crate::sst::file::{FileHandle, FileId, FileMeta, Level};
 use crate::sst::parquet::{SstInfo, WriteOptions};
@@ -40,8 +41,15 @@ impl CompactionOutput {
         schema: RegionMetadataRef,
         sst_layer: AccessLayerRef,
         sst_write_buffer_size: ReadableSize,
+        keep_versions: KeepVersions,
     ) -> error::Result<Option<FileMeta>> {
-        let reader = build_sst_reader(schema.clone(), sst_layer.clone(), &self.inputs).await?;
+        let reader = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &self.inputs,
+            keep_versions,
+        )
+        .await?;
 
         let opts = WriteOptions {
             write_buffer_size: sst_write_buffer_size,
@@ -63,6 +71,7 @@ impl CompactionOutput {
                     time_range,
                     level: self.output_level,
                     file_size,
+                    multi_versions: !keep_versions.latest_only(),
                 }
             },
         );
@@ -76,9 +85,11 @@ async fn build_sst_reader(
     schema: RegionMetadataRef,
     sst_layer: AccessLayerRef,
     inputs: &[FileHandle],
+    keep_versions: KeepVersions,
 ) -> error::Result<BoxedBatchReader> {
     SeqScan::new(sst_layer, ProjectionMapper::all(&schema)?)
         .with_files(inputs.to_vec())
+        .with_keep_versions(keep_versions)
         .build_reader()
         .await
 }
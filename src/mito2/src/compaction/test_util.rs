@@ -35,6 +35,7 @@ pub fn new_file_handle(
             ),
             level,
             file_size: 0,
+            multi_versions: false,
         },
         file_purger,
     )
@@ -35,6 +35,7 @@ use crate::compaction::CompactionRequest;
 use crate::error;
 use crate::error::CompactRegionSnafu;
 use crate::metrics::{COMPACTION_FAILURE_COUNT, COMPACTION_STAGE_ELAPSED};
+use crate::read::version::KeepVersions;
 use crate::request::{
     BackgroundNotify, CompactionFailed, CompactionFinished, OutputTx, WorkerRequest,
 };
@@ -121,6 +122,8 @@ impl Picker for TwcsPicker {
             file_purger,
             start_time,
             sst_write_buffer_size,
+            keep_versions,
+            compaction_options: _,
         } = req;
 
         let region_metadata = current_version.metadata.clone();
@@ -169,6 +172,7 @@ impl Picker for TwcsPicker {
             outputs,
             expired_ssts,
             sst_write_buffer_size,
+            keep_versions,
             compaction_time_window: Some(time_window_size),
             request_sender,
             waiters,
@@ -226,6 +230,8 @@ pub(crate) struct TwcsCompactionTask {
     pub outputs: Vec<CompactionOutput>,
     pub expired_ssts: Vec<FileHandle>,
     pub sst_write_buffer_size: ReadableSize,
+    /// Versions of rows to keep in outputs.
+    pub keep_versions: KeepVersions,
     pub compaction_time_window: Option<i64>,
     pub file_purger: FilePurgerRef,
     /// Request sender to notify the worker.
@@ -272,6 +278,7 @@ impl TwcsCompactionTask {
             let schema = self.schema.clone();
             let sst_layer = self.sst_layer.clone();
             let sst_write_buffer_size = self.sst_write_buffer_size;
+            let keep_versions = self.keep_versions;
             compacted_inputs.extend(output.inputs.iter().map(FileHandle::meta));
 
             info!(
@@ -289,7 +296,13 @@ impl TwcsCompactionTask {
             // TODO(hl): Maybe spawn to runtime to exploit in-job parallelism.
             futs.push(async move {
                 output
-                    .build(region_id, schema, sst_layer, sst_write_buffer_size)
+                    .build(
+                        region_id,
+                        schema,
+                        sst_layer,
+                        sst_write_buffer_size,
+                        keep_versions,
+                    )
                     .await
             });
         }
@@ -455,7 +468,7 @@ pub(crate) const TIME_BUCKETS: TimeBuckets = TimeBuckets([
 ]);
 
 /// Finds all expired SSTs across levels.
-fn get_expired_ssts(
+pub(crate) fn get_expired_ssts(
     levels: &[LevelMeta],
     ttl: Option<Duration>,
     now: Timestamp,
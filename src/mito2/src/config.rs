@@ -21,6 +21,8 @@ use common_datasource::compression::CompressionType;
 use common_telemetry::warn;
 use serde::{Deserialize, Serialize};
 
+use crate::region::options::{CompactionStrategy, DuplicateTimestampPolicy};
+
 /// Default region worker num.
 const DEFAULT_NUM_WORKERS: usize = 1;
 /// Default max running background job.
@@ -55,10 +57,17 @@ pub struct MitoConfig {
     /// Interval to auto flush a region if it has not flushed yet (default 30 min).
     #[serde(with = "humantime_serde")]
     pub auto_flush_interval: Duration,
+    /// Interval to flush a region periodically regardless of the size of its memtables,
+    /// regions without data to flush are skipped. Disabled if it is not set.
+    #[serde(with = "humantime_serde")]
+    pub periodic_flush_interval: Option<Duration>,
     /// Global write buffer size threshold to trigger flush (default 1G).
     pub global_write_buffer_size: ReadableSize,
     /// Global write buffer size threshold to reject write requests (default 2G).
     pub global_write_buffer_reject_size: ReadableSize,
+    /// Handling of the rows with the same primary key and timestamp in the regions that
+    /// don't set the `duplicate_timestamp_policy` option (default `keep_last`).
+    pub duplicate_timestamp_policy: DuplicateTimestampPolicy,
 
     // Cache configs:
     /// Cache size for SST metadata (default 128MB). Setting it to 0 to disable the cache.
@@ -67,6 +76,19 @@ pub struct MitoConfig {
     pub vector_cache_size: ReadableSize,
     /// Buffer size for SST writing.
     pub sst_write_buffer_size: ReadableSize,
+
+    // Compaction configs:
+    /// Compaction strategy of the regions that don't set the `compaction.type` option
+    /// (default `twcs`). Changing it applies to the next compactions of the regions.
+    pub compaction_strategy: CompactionStrategy,
+    /// Number of versions of a row to keep while compacting (default 1, only the
+    /// latest version is kept).
+    pub compaction_keep_versions: usize,
+    /// Only keeps multiple versions for rows whose timestamps are within this duration
+    /// from now. Older rows are collapsed to the latest version. Keeps versions for all
+    /// rows if it is not set.
+    #[serde(with = "humantime_serde")]
+    pub compaction_keep_versions_duration: Option<Duration>,
 }
 
 impl Default for MitoConfig {
@@ -79,11 +101,16 @@ impl Default for MitoConfig {
             manifest_compress_type: CompressionType::Uncompressed,
             max_background_jobs: DEFAULT_MAX_BG_JOB,
             auto_flush_interval: Duration::from_secs(30 * 60),
+            periodic_flush_interval: None,
             global_write_buffer_size: ReadableSize::gb(1),
             global_write_buffer_reject_size: ReadableSize::gb(2),
+            duplicate_timestamp_policy: DuplicateTimestampPolicy::KeepLast,
             sst_meta_cache_size: ReadableSize::mb(128),
             vector_cache_size: ReadableSize::mb(512),
             sst_write_buffer_size: ReadableSize::mb(8),
+            compaction_strategy: CompactionStrategy::Twcs,
+            compaction_keep_versions: 1,
+            compaction_keep_versions_duration: None,
         }
     }
 }
@@ -123,6 +150,11 @@ impl MitoConfig {
             );
         }
 
+        if self.periodic_flush_interval == Some(Duration::ZERO) {
+            warn!("Sanitize periodic flush interval 0 to disabled");
+            self.periodic_flush_interval = None;
+        }
+
         if self.sst_write_buffer_size < MULTIPART_UPLOAD_MINIMUM_SIZE {
             self.sst_write_buffer_size = MULTIPART_UPLOAD_MINIMUM_SIZE;
             warn!(
@@ -130,5 +162,10 @@ impl MitoConfig {
                 self.sst_write_buffer_size
             );
         }
+
+        if self.compaction_keep_versions == 0 {
+            warn!("Sanitize compaction keep versions 0 to 1");
+            self.compaction_keep_versions = 1;
+        }
     }
 }
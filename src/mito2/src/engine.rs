@@ -17,6 +17,8 @@
 #[cfg(test)]
 mod alter_test;
 #[cfg(test)]
+mod backup_test;
+#[cfg(test)]
 mod basic_test;
 #[cfg(test)]
 mod close_test;
@@ -27,6 +29,8 @@ mod create_test;
 #[cfg(test)]
 mod drop_test;
 #[cfg(test)]
+mod duplicate_test;
+#[cfg(test)]
 mod flush_test;
 #[cfg(any(test, feature = "test"))]
 pub mod listener;
@@ -46,6 +50,7 @@ use common_error::ext::BoxedError;
 use common_query::Output;
 use common_recordbatch::SendableRecordBatchStream;
 use object_store::manager::ObjectStoreManagerRef;
+use object_store::ObjectStore;
 use snafu::{OptionExt, ResultExt};
 use store_api::logstore::LogStore;
 use store_api::metadata::RegionMetadataRef;
@@ -53,6 +58,7 @@ use store_api::region_engine::{RegionEngine, RegionRole};
 use store_api::region_request::RegionRequest;
 use store_api::storage::{RegionId, ScanRequest};
 
+use crate::backup::{self, RegionBackupStat};
 use crate::config::MitoConfig;
 use crate::error::{RecvSnafu, RegionNotFoundSnafu, Result};
 use crate::metrics::HANDLE_REQUEST_ELAPSED;
@@ -99,6 +105,25 @@ impl MitoEngine {
         Ok(region.region_usage().await)
     }
 
+    /// Backs up the flushed data of the region to `target`, the region directory is
+    /// kept under `target_root`.
+    ///
+    /// Data still in memtables is not included, flush the region first to back it up.
+    pub async fn backup_region(
+        &self,
+        region_id: RegionId,
+        target: &ObjectStore,
+        target_root: &str,
+    ) -> Result<RegionBackupStat> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+
+        backup::backup_region(&region, target, target_root).await
+    }
+
     /// Returns a scanner to scan for `request`.
     fn scanner(&self, region_id: RegionId, request: ScanRequest) -> Result<Scanner> {
         self.inner.handle_query(region_id, request)
@@ -22,7 +22,7 @@ use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use common_recordbatch::RecordBatches;
 use datatypes::prelude::ConcreteDataType;
-use store_api::region_request::{RegionOpenRequest, RegionPutRequest};
+use store_api::region_request::{RegionOpenRequest, RegionPutRequest, WriteAckLevel};
 use store_api::storage::RegionId;
 
 use super::*;
@@ -452,7 +452,13 @@ async fn test_absent_and_invalid_columns() {
         rows,
     };
     let err = engine
-        .handle_request(region_id, RegionRequest::Put(RegionPutRequest { rows }))
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                ack_level: WriteAckLevel::default(),
+            }),
+        )
         .await
         .unwrap_err();
     assert_eq!(StatusCode::InvalidArguments, err.status_code());
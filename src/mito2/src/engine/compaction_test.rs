@@ -21,7 +21,7 @@ use datatypes::prelude::ScalarVector;
 use datatypes::vectors::TimestampMillisecondVector;
 use store_api::region_engine::RegionEngine;
 use store_api::region_request::{
-    RegionCompactRequest, RegionDeleteRequest, RegionFlushRequest, RegionRequest,
+    RegionCompactRequest, RegionDeleteRequest, RegionFlushRequest, RegionRequest, WriteAckLevel,
 };
 use store_api::storage::{RegionId, ScanRequest};
 
@@ -73,7 +73,10 @@ async fn delete_and_flush(
     let deleted = engine
         .handle_request(
             region_id,
-            RegionRequest::Delete(RegionDeleteRequest { rows }),
+            RegionRequest::Delete(RegionDeleteRequest {
+                rows,
+                ack_level: WriteAckLevel::default(),
+            }),
         )
         .await
         .unwrap();
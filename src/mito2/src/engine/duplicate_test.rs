@@ -0,0 +1,190 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the duplicate timestamp policies.
+
+use api::v1::{ColumnSchema, Rows};
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_recordbatch::RecordBatches;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::{RegionPutRequest, RegionRequest, WriteAckLevel};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::region::options::DuplicateTimestampPolicy;
+use crate::test_util::{
+    build_delete_rows_for_key, build_rows_for_key, delete_rows, delete_rows_schema, flush_region,
+    put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+/// Creates a region with the `policy` option, returns the schema of rows to put.
+async fn create_region(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    policy: Option<&str>,
+) -> Vec<ColumnSchema> {
+    let mut builder = CreateRequestBuilder::new();
+    if let Some(policy) = policy {
+        builder = builder.insert_option("duplicate_timestamp_policy", policy);
+    }
+    let request = builder.build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    column_schemas
+}
+
+async fn scan_to_string(engine: &MitoEngine, region_id: RegionId) -> String {
+    let stream = engine
+        .handle_query(region_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_keep_first() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let column_schemas = create_region(&engine, region_id, Some("keep_first")).await;
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 0, 3, 0),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    // Put (a, 0) => 5.0, (a, 1) => 6.0, ignored.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 0, 2, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+    // Delete (a, 0) then put (a, 0) => 7.0 again.
+    let rows = Rows {
+        schema: delete_rows_schema(&CreateRequestBuilder::new().build()),
+        rows: build_delete_rows_for_key("a", 0, 1),
+    };
+    delete_rows(&engine, region_id, rows).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_for_key("a", 0, 1, 7),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 7.0     | 1970-01-01T00:00:00 |
+| a     | 1.0     | 1970-01-01T00:00:01 |
+| a     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_to_string(&engine, region_id).await);
+
+    // Flushing the memtable doesn't change the result.
+    flush_region(&engine, region_id, None).await;
+    assert_eq!(expected, scan_to_string(&engine, region_id).await);
+}
+
+#[tokio::test]
+async fn test_keep_both() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let column_schemas = create_region(&engine, region_id, Some("keep_both")).await;
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 0, 2, 0),
+    };
+    put_rows(&engine, region_id, rows).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_for_key("a", 0, 1, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 5.0     | 1970-01-01T00:00:00 |
+| a     | 0.0     | 1970-01-01T00:00:00 |
+| a     | 1.0     | 1970-01-01T00:00:01 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_to_string(&engine, region_id).await);
+
+    flush_region(&engine, region_id, None).await;
+    assert_eq!(expected, scan_to_string(&engine, region_id).await);
+}
+
+#[tokio::test]
+async fn test_reject_duplicate_rows() {
+    let mut env = TestEnv::new();
+    // The engine's default policy applies to the region without the option.
+    let engine = env
+        .create_engine(MitoConfig {
+            duplicate_timestamp_policy: DuplicateTimestampPolicy::Error,
+            ..Default::default()
+        })
+        .await;
+
+    let region_id = RegionId::new(1, 1);
+    let column_schemas = create_region(&engine, region_id, None).await;
+    let mut rows = build_rows_for_key("a", 0, 2, 0);
+    rows.extend(build_rows_for_key("a", 1, 2, 5));
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows,
+    };
+    let err = engine
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                ack_level: WriteAckLevel::default(),
+            }),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+    // Rows of different requests are overwritten.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 0, 2, 0),
+    };
+    put_rows(&engine, region_id, rows).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_for_key("a", 1, 2, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 0.0     | 1970-01-01T00:00:00 |
+| a     | 5.0     | 1970-01-01T00:00:01 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_to_string(&engine, region_id).await);
+}
@@ -15,11 +15,13 @@
 //! Flush tests for mito engine.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::v1::Rows;
+use common_query::Output;
 use common_recordbatch::RecordBatches;
 use store_api::region_engine::RegionEngine;
-use store_api::region_request::RegionRequest;
+use store_api::region_request::{RegionPutRequest, RegionRequest, WriteAckLevel};
 use store_api::storage::{RegionId, ScanRequest};
 
 use crate::config::MitoConfig;
@@ -68,6 +70,72 @@ async fn test_manual_flush() {
     assert_eq!(expected, batches.pretty_print().unwrap());
 }
 
+#[tokio::test]
+async fn test_write_ack_level_flush() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Acknowledged after the WAL sync, the rows are still in the memtable.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 2),
+    };
+    let output = engine
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                ack_level: WriteAckLevel::Wal,
+            }),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(output, Output::AffectedRows(2)));
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    assert_eq!(1, scanner.num_memtables());
+    assert_eq!(0, scanner.num_files());
+
+    // Acknowledged after the flush, all rows are in the SST.
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(2, 3),
+    };
+    let output = engine
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                ack_level: WriteAckLevel::Flush,
+            }),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(output, Output::AffectedRows(1)));
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    assert_eq!(0, scanner.num_memtables());
+    assert_eq!(1, scanner.num_files());
+    let stream = scanner.scan().await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 1     | 1.0     | 1970-01-01T00:00:01 |
+| 2     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, batches.pretty_print().unwrap());
+}
+
 #[tokio::test]
 async fn test_flush_engine() {
     let mut env = TestEnv::new();
@@ -127,6 +195,58 @@ async fn test_flush_engine() {
     assert_eq!(expected, batches.pretty_print().unwrap());
 }
 
+#[tokio::test]
+async fn test_flush_periodically() {
+    let mut env = TestEnv::new();
+    let listener = Arc::new(FlushListener::default());
+    let engine = env
+        .create_engine_with(
+            MitoConfig {
+                periodic_flush_interval: Some(Duration::from_millis(100)),
+                ..Default::default()
+            },
+            None,
+            Some(listener.clone()),
+        )
+        .await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    // Region without data.
+    let idle_region_id = RegionId::new(1, 2);
+    engine
+        .handle_request(
+            idle_region_id,
+            RegionRequest::Create(CreateRequestBuilder::new().build()),
+        )
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    // Wait until flush is finished.
+    listener.wait().await;
+    // Waits more intervals, the flushed region has no data to flush again.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    assert_eq!(0, scanner.num_memtables());
+    assert_eq!(1, scanner.num_files());
+    let scanner = engine
+        .scanner(idle_region_id, ScanRequest::default())
+        .unwrap();
+    assert_eq!(0, scanner.num_files());
+}
+
 #[tokio::test]
 async fn test_write_stall() {
     let mut env = TestEnv::new();
@@ -20,7 +20,7 @@ use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use store_api::region_engine::{RegionEngine, RegionRole};
 use store_api::region_request::{
-    RegionCloseRequest, RegionOpenRequest, RegionPutRequest, RegionRequest,
+    RegionCloseRequest, RegionOpenRequest, RegionPutRequest, RegionRequest, WriteAckLevel,
 };
 use store_api::storage::RegionId;
 
@@ -120,7 +120,10 @@ async fn test_engine_open_readonly() {
     let err = engine
         .handle_request(
             region_id,
-            RegionRequest::Put(RegionPutRequest { rows: rows.clone() }),
+            RegionRequest::Put(RegionPutRequest {
+                rows: rows.clone(),
+                ack_level: WriteAckLevel::default(),
+            }),
         )
         .await
         .unwrap_err();
@@ -402,6 +402,24 @@ pub enum Error {
         error: ArrowError,
         location: Location,
     },
+
+    #[snafu(display("Failed to copy object from {} to {}", from, to))]
+    CopyObject {
+        from: String,
+        to: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Region {} keeps changing, failed to pin a version matching its manifest",
+        region_id
+    ))]
+    PinRegionVersion {
+        region_id: RegionId,
+        location: Location,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -474,6 +492,8 @@ impl ErrorExt for Error {
             JsonOptions { .. } => StatusCode::InvalidArguments,
             EmptyRegionDir { .. } => StatusCode::RegionNotFound,
             ArrowReader { .. } => StatusCode::StorageUnavailable,
+            CopyObject { .. } => StatusCode::StorageUnavailable,
+            PinRegionVersion { .. } => StatusCode::Unexpected,
         }
     }
 
@@ -175,6 +175,10 @@ pub enum FlushReason {
     Manual,
     /// Flush to alter table.
     Alter,
+    /// Periodic flush by the flush interval.
+    Periodic,
+    /// Flush to acknowledge writes after the flush.
+    WriteAck,
 }
 
 impl FlushReason {
@@ -326,6 +330,10 @@ impl RegionFlushTask {
                 time_range: sst_info.time_range,
                 level: 0,
                 file_size: sst_info.file_size,
+                multi_versions: !version
+                    .options
+                    .duplicate_timestamp_policy()
+                    .keeps_last_only(),
             });
         }
 
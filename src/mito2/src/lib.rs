@@ -23,6 +23,7 @@
 pub mod test_util;
 
 mod access_layer;
+pub mod backup;
 mod cache;
 mod compaction;
 pub mod config;
@@ -150,6 +151,7 @@ mod worker;
 ///     +Option&lt;Timestamp, Timestamp&gt; time_range
 ///     +Level level
 ///     +u64 file_size
+///     +bool multi_versions
 /// }
 /// VersionControl o-- Version
 /// Version o-- RegionMetadata
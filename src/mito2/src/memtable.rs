@@ -92,8 +92,9 @@ pub type MemtableRef = Arc<dyn Memtable>;
 
 /// Builder to build a new [Memtable].
 pub trait MemtableBuilder: Send + Sync + fmt::Debug {
-    /// Builds a new memtable instance.
-    fn build(&self, metadata: &RegionMetadataRef) -> MemtableRef;
+    /// Builds a new memtable instance. If `dedup` is true, the memtable only keeps
+    /// the latest row of the same primary key and timestamp.
+    fn build(&self, metadata: &RegionMetadataRef, dedup: bool) -> MemtableRef;
 }
 
 pub type MemtableBuilderRef = Arc<dyn MemtableBuilder>;
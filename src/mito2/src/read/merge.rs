@@ -29,7 +29,7 @@ use crate::metrics::{MERGE_FILTER_ROWS_TOTAL, READ_STAGE_ELAPSED};
 use crate::read::{Batch, BatchReader, BoxedBatchReader, Source};
 
 /// Minimum batch size to output.
-const MIN_BATCH_SIZE: usize = 64;
+pub(crate) const MIN_BATCH_SIZE: usize = 64;
 
 /// Reader to merge sorted batches.
 ///
@@ -25,6 +25,7 @@ use crate::cache::CacheManagerRef;
 use crate::error::Result;
 use crate::read::projection::ProjectionMapper;
 use crate::read::seq_scan::SeqScan;
+use crate::read::version::KeepVersions;
 use crate::region::version::VersionRef;
 use crate::sst::file::FileHandle;
 
@@ -196,7 +197,11 @@ impl ScanRegion {
             .with_predicate(Some(predicate))
             .with_memtables(memtables)
             .with_files(files)
-            .with_cache(self.cache_manager);
+            .with_cache(self.cache_manager)
+            .with_keep_versions(
+                KeepVersions::default()
+                    .with_policy(self.version.options.duplicate_timestamp_policy()),
+            );
 
         Ok(seq_scan)
     }
@@ -34,7 +34,9 @@ use crate::metrics::READ_STAGE_ELAPSED;
 use crate::read::compat::{self, CompatReader};
 use crate::read::merge::MergeReaderBuilder;
 use crate::read::projection::ProjectionMapper;
-use crate::read::{BatchReader, BoxedBatchReader};
+use crate::read::version::{DedupReader, FirstVersionReader, KeepVersions, VersionMergeReader};
+use crate::read::{BatchReader, BoxedBatchReader, Source};
+use crate::region::options::DuplicateTimestampPolicy;
 use crate::sst::file::FileHandle;
 
 /// Scans a region and returns rows in a sorted sequence.
@@ -55,6 +57,8 @@ pub struct SeqScan {
     files: Vec<FileHandle>,
     /// Cache.
     cache_manager: Option<CacheManagerRef>,
+    /// Versions of rows to keep.
+    keep_versions: KeepVersions,
 }
 
 impl SeqScan {
@@ -69,6 +73,7 @@ impl SeqScan {
             memtables: Vec::new(),
             files: Vec::new(),
             cache_manager: None,
+            keep_versions: KeepVersions::default(),
         }
     }
 
@@ -106,6 +111,13 @@ impl SeqScan {
         self
     }
 
+    /// Sets versions of rows to keep. Only the latest version is kept by default.
+    #[must_use]
+    pub(crate) fn with_keep_versions(mut self, keep_versions: KeepVersions) -> Self {
+        self.keep_versions = keep_versions;
+        self
+    }
+
     /// Builds a stream for the query.
     pub async fn build_stream(&self) -> Result<SendableRecordBatchStream> {
         let start = Instant::now();
@@ -140,12 +152,50 @@ impl SeqScan {
 
     /// Builds a [BoxedBatchReader] from sequential scan.
     pub async fn build_reader(&self) -> Result<BoxedBatchReader> {
+        if !self.keep_versions.latest_only() {
+            return self.build_version_reader().await;
+        }
+
         // Scans all memtables and SSTs. Builds a merge reader to merge results.
         let mut builder = MergeReaderBuilder::new();
         for mem in &self.memtables {
             let iter = mem.iter(Some(self.mapper.column_ids()), self.predicate.clone());
             builder.push_batch_iter(iter);
         }
+        for (file, reader) in self.files.iter().zip(self.build_sst_readers().await?) {
+            if file.multi_versions() {
+                // The merge reader requires each source to have no duplicate rows.
+                builder.push_batch_reader(Box::new(DedupReader::new(reader)));
+            } else {
+                builder.push_batch_reader(reader);
+            }
+        }
+        Ok(Box::new(builder.build().await?))
+    }
+
+    /// Builds a reader that keeps multiple versions of rows.
+    async fn build_version_reader(&self) -> Result<BoxedBatchReader> {
+        let mut sources = Vec::with_capacity(self.memtables.len() + self.files.len());
+        for mem in &self.memtables {
+            let iter = mem.iter(Some(self.mapper.column_ids()), self.predicate.clone());
+            sources.push(Source::Iter(iter));
+        }
+        for reader in self.build_sst_readers().await? {
+            sources.push(Source::Reader(reader));
+        }
+        if self.keep_versions.first_only {
+            // Merges all versions to find the first one of each row.
+            let keep_all = KeepVersions::default().with_policy(DuplicateTimestampPolicy::KeepBoth);
+            let reader = VersionMergeReader::new(sources, keep_all).await?;
+            return Ok(Box::new(FirstVersionReader::new(reader)));
+        }
+        let reader = VersionMergeReader::new(sources, self.keep_versions).await?;
+        Ok(Box::new(reader))
+    }
+
+    /// Builds readers for all SST files to scan, in the order of the files.
+    async fn build_sst_readers(&self) -> Result<Vec<BoxedBatchReader>> {
+        let mut readers: Vec<BoxedBatchReader> = Vec::with_capacity(self.files.len());
         for file in &self.files {
             let reader = self
                 .access_layer
@@ -157,16 +207,16 @@ impl SeqScan {
                 .build()
                 .await?;
             if compat::has_same_columns(self.mapper.metadata(), reader.metadata()) {
-                builder.push_batch_reader(Box::new(reader));
+                readers.push(Box::new(reader));
             } else {
                 // They have different schema. We need to adapt the batch first so the
                 // mapper can convert the it.
                 let compat_reader =
                     CompatReader::new(&self.mapper, reader.metadata().clone(), reader)?;
-                builder.push_batch_reader(Box::new(compat_reader));
+                readers.push(Box::new(compat_reader));
             }
         }
-        Ok(Box::new(builder.build().await?))
+        Ok(readers)
     }
 
     /// Fetch a batch from the reader and convert it into a record batch.
@@ -0,0 +1,721 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Readers to keep multiple versions of rows.
+//!
+//! Compaction may keep more than one version of a row (rows with the same primary key
+//! and timestamp) in its outputs, so an SST file may contain duplicate rows.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+use api::v1::OpType;
+use async_trait::async_trait;
+use common_time::Timestamp;
+use datatypes::prelude::{ConcreteDataType, ScalarVector};
+use datatypes::vectors::BooleanVector;
+
+use crate::config::MitoConfig;
+use crate::error::Result;
+use crate::read::merge::MIN_BATCH_SIZE;
+use crate::read::{Batch, BatchReader, Source};
+use crate::region::options::DuplicateTimestampPolicy;
+
+/// Versions of rows to keep while compacting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeepVersions {
+    /// Max number of versions to keep for each row.
+    pub(crate) max_versions: usize,
+    /// Only keeps multiple versions for rows whose timestamps are within this
+    /// duration from now.
+    pub(crate) duration: Option<Duration>,
+    /// Keeps the first written version of each row instead of the latest ones.
+    pub(crate) first_only: bool,
+}
+
+impl Default for KeepVersions {
+    fn default() -> Self {
+        KeepVersions {
+            max_versions: 1,
+            duration: None,
+            first_only: false,
+        }
+    }
+}
+
+impl KeepVersions {
+    /// Returns the versions to keep configured by the engine `config`.
+    pub(crate) fn new(config: &MitoConfig) -> KeepVersions {
+        KeepVersions {
+            max_versions: config.compaction_keep_versions.max(1),
+            duration: config.compaction_keep_versions_duration,
+            first_only: false,
+        }
+    }
+
+    /// Returns the versions to keep under the duplicate timestamp `policy` of a region.
+    pub(crate) fn with_policy(self, policy: DuplicateTimestampPolicy) -> KeepVersions {
+        match policy {
+            DuplicateTimestampPolicy::KeepLast | DuplicateTimestampPolicy::Error => self,
+            DuplicateTimestampPolicy::KeepFirst => KeepVersions {
+                max_versions: 1,
+                duration: None,
+                first_only: true,
+            },
+            DuplicateTimestampPolicy::KeepBoth => KeepVersions {
+                max_versions: usize::MAX,
+                duration: None,
+                first_only: false,
+            },
+        }
+    }
+
+    /// Returns true if only the latest version of each row is kept.
+    pub(crate) fn latest_only(&self) -> bool {
+        self.max_versions <= 1 && !self.first_only
+    }
+}
+
+/// Reader that only returns the latest version of each row of a sorted source.
+///
+/// Rows of the same primary key and timestamp must be sorted by sequence desc, which
+/// holds for SST files.
+pub(crate) struct DedupReader<R> {
+    reader: R,
+    /// Primary key and timestamp of the last row returned.
+    last_key: Option<(Vec<u8>, i64)>,
+}
+
+impl<R> DedupReader<R> {
+    /// Creates a new reader from the `reader`.
+    pub(crate) fn new(reader: R) -> DedupReader<R> {
+        DedupReader {
+            reader,
+            last_key: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: BatchReader> BatchReader for DedupReader<R> {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(mut batch) = self.reader.next_batch().await? {
+            let Some(timestamps) = batch.timestamps_native() else {
+                continue;
+            };
+
+            let mut prev = match &self.last_key {
+                Some((key, ts)) if key == batch.primary_key() => Some(*ts),
+                _ => None,
+            };
+            let mut has_duplicate = false;
+            let mask: Vec<_> = timestamps
+                .iter()
+                .map(|ts| {
+                    let keep = prev != Some(*ts);
+                    has_duplicate |= !keep;
+                    prev = Some(*ts);
+                    keep
+                })
+                .collect();
+            let last_ts = *timestamps.last().unwrap();
+            match &mut self.last_key {
+                Some((key, ts)) if key == batch.primary_key() => *ts = last_ts,
+                _ => self.last_key = Some((batch.primary_key().to_vec(), last_ts)),
+            }
+
+            if has_duplicate {
+                batch.filter(&BooleanVector::from(mask))?;
+            }
+            if !batch.is_empty() {
+                return Ok(Some(batch));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Reader that only returns the first written version of each row of a sorted source.
+///
+/// Rows of the same primary key and timestamp must be sorted by sequence desc, and
+/// rows deleted must be removed, which holds for the [VersionMergeReader] keeping all
+/// versions.
+pub(crate) struct FirstVersionReader<R> {
+    reader: R,
+    /// Versions of the last row fetched, which may continue in the next batch.
+    pending: Option<Batch>,
+}
+
+impl<R> FirstVersionReader<R> {
+    /// Creates a new reader from the `reader`.
+    pub(crate) fn new(reader: R) -> FirstVersionReader<R> {
+        FirstVersionReader {
+            reader,
+            pending: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: BatchReader> BatchReader for FirstVersionReader<R> {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        loop {
+            let Some(batch) = self.reader.next_batch().await? else {
+                return self.pending.take().map(first_versions).transpose();
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            let batch = match self.pending.take() {
+                Some(pending) if pending.primary_key() == batch.primary_key() => {
+                    Batch::concat(vec![pending, batch])?
+                }
+                Some(pending) => {
+                    self.pending = Some(batch);
+                    return first_versions(pending).map(Some);
+                }
+                None => batch,
+            };
+
+            // Holds the versions of the last row until the next batch is fetched.
+            let timestamps = batch.timestamps_native().unwrap();
+            let last_ts = timestamps[timestamps.len() - 1];
+            let num_rows = timestamps.partition_point(|ts| *ts < last_ts);
+            self.pending = Some(batch.slice(num_rows, batch.num_rows() - num_rows));
+            if num_rows > 0 {
+                return first_versions(batch.slice(0, num_rows)).map(Some);
+            }
+        }
+    }
+}
+
+/// Only keeps the first written version of each row in the `batch`, which is the
+/// last one of the rows with the same timestamp.
+fn first_versions(mut batch: Batch) -> Result<Batch> {
+    let Some(timestamps) = batch.timestamps_native() else {
+        return Ok(batch);
+    };
+    let mask: Vec<_> = timestamps
+        .iter()
+        .enumerate()
+        .map(|(i, ts)| timestamps.get(i + 1) != Some(ts))
+        .collect();
+    if mask.iter().any(|keep| !keep) {
+        batch.filter(&BooleanVector::from(mask))?;
+    }
+    Ok(batch)
+}
+
+/// Reader that merges sources and keeps up to N versions of each row.
+///
+/// Unlike [MergeReader](crate::read::merge::MergeReader), sources may contain
+/// duplicate rows. Rows whose latest version is deleted are removed.
+pub(crate) struct VersionMergeReader {
+    /// Nodes that still have batches to read.
+    heap: BinaryHeap<Node>,
+    /// Filter to select versions to keep.
+    filter: VersionFilter,
+    /// Filtered rows fetched but not returned yet.
+    ///
+    /// It has another primary key than the last returned batch.
+    pending: Option<Batch>,
+    /// Batch size of the reader.
+    batch_size: usize,
+}
+
+#[async_trait]
+impl BatchReader for VersionMergeReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        let mut batches: Vec<_> = self.pending.take().into_iter().collect();
+        let mut num_rows = batches.iter().map(Batch::num_rows).sum::<usize>();
+        while num_rows < self.batch_size {
+            let Some(batch) = self.fetch_rows().await? else {
+                break;
+            };
+            if batches
+                .first()
+                .map(|b| b.primary_key() != batch.primary_key())
+                .unwrap_or(false)
+            {
+                self.pending = Some(batch);
+                break;
+            }
+            num_rows += batch.num_rows();
+            batches.push(batch);
+        }
+
+        match batches.len() {
+            0 => Ok(None),
+            1 => Ok(batches.pop()),
+            _ => Batch::concat(batches).map(Some),
+        }
+    }
+}
+
+impl VersionMergeReader {
+    /// Creates and initializes a new reader.
+    pub(crate) async fn new(
+        sources: Vec<Source>,
+        keep_versions: KeepVersions,
+    ) -> Result<VersionMergeReader> {
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for source in sources {
+            if let Some(node) = Node::new(source).await? {
+                heap.push(node);
+            }
+        }
+
+        Ok(VersionMergeReader {
+            heap,
+            filter: VersionFilter::new(keep_versions),
+            pending: None,
+            batch_size: MIN_BATCH_SIZE,
+        })
+    }
+
+    /// Fetches next non-empty sorted rows of the same primary key from all sources
+    /// and filters them.
+    async fn fetch_rows(&mut self) -> Result<Option<Batch>> {
+        while let Some(mut node) = self.heap.pop() {
+            // Takes rows before the first row of the next node.
+            let num_rows = match self.heap.peek() {
+                Some(next) if next.batch.primary_key() == node.batch.primary_key() => {
+                    node.num_rows_before(&next.batch)
+                }
+                _ => node.batch.num_rows(),
+            };
+            let batch = node.batch.slice(0, num_rows);
+            if node.advance(num_rows).await? {
+                self.heap.push(node);
+            }
+
+            if let Some(batch) = self.filter.filter(batch)? {
+                return Ok(Some(batch));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// A source to merge.
+struct Node {
+    source: Source,
+    /// Current batch of the source. The node ensures the batch is not empty.
+    batch: Batch,
+}
+
+impl Node {
+    /// Creates a node, returns `None` if the `source` is empty.
+    async fn new(mut source: Source) -> Result<Option<Node>> {
+        let Some(batch) = next_non_empty(&mut source).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Node { source, batch }))
+    }
+
+    /// Key of the first row in the current batch.
+    fn first_key(&self) -> (&[u8], i64, u64) {
+        (
+            self.batch.primary_key(),
+            self.batch.timestamps_native().unwrap()[0],
+            self.batch.get_sequence(0),
+        )
+    }
+
+    /// Returns the number of rows in front of the first row of `next`. `next` must
+    /// have the same primary key and not be in front of this node.
+    fn num_rows_before(&self, next: &Batch) -> usize {
+        let next_ts = next.timestamps_native().unwrap()[0];
+        let next_sequence = next.get_sequence(0);
+        let timestamps = self.batch.timestamps_native().unwrap();
+        timestamps
+            .iter()
+            .enumerate()
+            .take_while(|(i, ts)| {
+                **ts < next_ts || (**ts == next_ts && self.batch.get_sequence(*i) >= next_sequence)
+            })
+            .count()
+            .max(1)
+    }
+
+    /// Skips `num_rows` rows of the current batch. Returns false if the source is EOF.
+    async fn advance(&mut self, num_rows: usize) -> Result<bool> {
+        let remaining = self.batch.num_rows() - num_rows;
+        if remaining > 0 {
+            self.batch = self.batch.slice(num_rows, remaining);
+            return Ok(true);
+        }
+
+        match next_non_empty(&mut self.source).await? {
+            Some(batch) => {
+                self.batch = batch;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Node) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Node) -> Ordering {
+        let (pk, ts, sequence) = self.first_key();
+        let (other_pk, other_ts, other_sequence) = other.first_key();
+        // BinaryHeap is a max heap, so we reverse the order of primary key and timestamp
+        // to pop the smallest row first. Rows with larger sequences come first.
+        other_pk
+            .cmp(pk)
+            .then_with(|| other_ts.cmp(&ts))
+            .then_with(|| sequence.cmp(&other_sequence))
+    }
+}
+
+/// Returns next non-empty batch from the `source`.
+async fn next_non_empty(source: &mut Source) -> Result<Option<Batch>> {
+    while let Some(batch) = source.next_batch().await? {
+        if !batch.is_empty() {
+            return Ok(Some(batch));
+        }
+    }
+    Ok(None)
+}
+
+/// Selects versions to keep from sorted rows.
+struct VersionFilter {
+    keep_versions: KeepVersions,
+    /// Rows whose timestamps are before this value only keep the latest version.
+    ///
+    /// It is initialized on the first batch as we need the unit of timestamps.
+    cutoff: Option<i64>,
+    /// Primary key of current row.
+    primary_key: Vec<u8>,
+    /// Timestamp of current row.
+    timestamp: Option<i64>,
+    /// Number of versions kept for current row.
+    num_versions: usize,
+    /// Whether current row is deleted by a newer version.
+    deleted: bool,
+}
+
+impl VersionFilter {
+    fn new(keep_versions: KeepVersions) -> VersionFilter {
+        VersionFilter {
+            keep_versions,
+            cutoff: None,
+            primary_key: Vec::new(),
+            timestamp: None,
+            num_versions: 0,
+            deleted: false,
+        }
+    }
+
+    /// Filters a sorted `batch` following previous batches. Returns `None` if all rows
+    /// are removed.
+    fn filter(&mut self, mut batch: Batch) -> Result<Option<Batch>> {
+        if batch.primary_key() != self.primary_key {
+            self.primary_key = batch.primary_key().to_vec();
+            self.timestamp = None;
+        }
+        let cutoff = *self
+            .cutoff
+            .get_or_insert_with(|| versions_cutoff(&batch, self.keep_versions.duration));
+
+        let Some(timestamps) = batch.timestamps_native() else {
+            return Ok(None);
+        };
+        let op_types = batch.op_types();
+        let mut mask = Vec::with_capacity(timestamps.len());
+        for (i, ts) in timestamps.iter().enumerate() {
+            let is_delete = op_types.get_data(i) == Some(OpType::Delete as u8);
+            if self.timestamp != Some(*ts) {
+                self.timestamp = Some(*ts);
+                self.num_versions = 0;
+                self.deleted = false;
+            }
+            // Older versions are shadowed by the delete.
+            self.deleted |= is_delete;
+            let max_versions = if *ts >= cutoff {
+                self.keep_versions.max_versions
+            } else {
+                1
+            };
+            let keep = !self.deleted && self.num_versions < max_versions;
+            if keep {
+                self.num_versions += 1;
+            }
+            mask.push(keep);
+        }
+
+        if mask.iter().all(|keep| !keep) {
+            return Ok(None);
+        }
+        if mask.iter().any(|keep| !keep) {
+            batch.filter(&BooleanVector::from(mask))?;
+        }
+        Ok(Some(batch))
+    }
+}
+
+/// Returns the timestamp in the unit of `batch` timestamps before which rows only
+/// keep their latest version.
+fn versions_cutoff(batch: &Batch, duration: Option<Duration>) -> i64 {
+    let Some(duration) = duration else {
+        return i64::MIN;
+    };
+    let ConcreteDataType::Timestamp(ts_type) = batch.timestamps().data_type() else {
+        return i64::MIN;
+    };
+    let now = Timestamp::current_millis().value();
+    let millis = i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+    Timestamp::new_millisecond(now.saturating_sub(millis))
+        .convert_to(ts_type.unit())
+        .map(|ts| ts.value())
+        .unwrap_or(i64::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{check_reader_result, new_batch, VecBatchReader};
+
+    fn new_source(batches: &[Batch]) -> Source {
+        Source::Reader(Box::new(VecBatchReader::new(batches)))
+    }
+
+    #[tokio::test]
+    async fn test_dedup_reader() {
+        let reader = VecBatchReader::new(&[
+            new_batch(
+                b"k1",
+                &[1, 1, 2],
+                &[12, 11, 13],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[21, 22, 23],
+            ),
+            new_batch(
+                b"k1",
+                &[2, 3],
+                &[10, 14],
+                &[OpType::Put, OpType::Put],
+                &[24, 25],
+            ),
+            new_batch(b"k1", &[3], &[9], &[OpType::Put], &[26]),
+            new_batch(b"k2", &[3], &[15], &[OpType::Delete], &[27]),
+        ]);
+        let mut reader = DedupReader::new(reader);
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(
+                    b"k1",
+                    &[1, 2],
+                    &[12, 13],
+                    &[OpType::Put, OpType::Put],
+                    &[21, 23],
+                ),
+                new_batch(b"k1", &[3], &[14], &[OpType::Put], &[25]),
+                new_batch(b"k2", &[3], &[15], &[OpType::Delete], &[27]),
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_first_version_reader() {
+        let reader = VecBatchReader::new(&[
+            new_batch(
+                b"k1",
+                &[1, 1, 2],
+                &[13, 12, 14],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[21, 22, 23],
+            ),
+            new_batch(
+                b"k1",
+                &[2, 2, 3],
+                &[11, 10, 15],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[24, 25, 26],
+            ),
+            new_batch(b"k2", &[1], &[16], &[OpType::Put], &[27]),
+        ]);
+        let mut reader = FirstVersionReader::new(reader);
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(b"k1", &[1], &[12], &[OpType::Put], &[22]),
+                new_batch(b"k1", &[2], &[10], &[OpType::Put], &[25]),
+                new_batch(b"k1", &[3], &[15], &[OpType::Put], &[26]),
+                new_batch(b"k2", &[1], &[16], &[OpType::Put], &[27]),
+            ],
+        )
+        .await;
+    }
+
+    #[test]
+    fn test_keep_versions_with_policy() {
+        let keep_versions = KeepVersions {
+            max_versions: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            keep_versions,
+            keep_versions.with_policy(DuplicateTimestampPolicy::Error)
+        );
+        let keep_first = keep_versions.with_policy(DuplicateTimestampPolicy::KeepFirst);
+        assert!(keep_first.first_only);
+        assert!(!keep_first.latest_only());
+        let keep_both = KeepVersions::default().with_policy(DuplicateTimestampPolicy::KeepBoth);
+        assert_eq!(usize::MAX, keep_both.max_versions);
+        assert!(!keep_both.latest_only());
+    }
+
+    #[tokio::test]
+    async fn test_version_merge_reader() {
+        let source1 = new_source(&[
+            new_batch(
+                b"k1",
+                &[1, 2, 3],
+                &[11, 12, 13],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[21, 22, 23],
+            ),
+            new_batch(b"k2", &[1], &[14], &[OpType::Put], &[24]),
+        ]);
+        let source2 = new_source(&[
+            new_batch(
+                b"k1",
+                &[1, 2, 3],
+                &[31, 32, 33],
+                &[OpType::Put, OpType::Delete, OpType::Put],
+                &[41, 42, 43],
+            ),
+            new_batch(b"k2", &[1], &[34], &[OpType::Put], &[44]),
+        ]);
+        let source3 = new_source(&[new_batch(b"k1", &[1], &[21], &[OpType::Put], &[51])]);
+        let keep_versions = KeepVersions {
+            max_versions: 2,
+            ..Default::default()
+        };
+        let mut reader = VersionMergeReader::new(vec![source1, source2, source3], keep_versions)
+            .await
+            .unwrap();
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(
+                    b"k1",
+                    &[1, 1, 3, 3],
+                    &[31, 21, 33, 13],
+                    &[OpType::Put, OpType::Put, OpType::Put, OpType::Put],
+                    &[41, 51, 43, 23],
+                ),
+                new_batch(
+                    b"k2",
+                    &[1, 1],
+                    &[34, 14],
+                    &[OpType::Put, OpType::Put],
+                    &[44, 24],
+                ),
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_version_merge_reader_duplicate_source() {
+        // The source contains multiple versions itself, e.g. an output of a previous compaction.
+        let source1 = new_source(&[new_batch(
+            b"k1",
+            &[1, 1, 2],
+            &[12, 11, 13],
+            &[OpType::Put, OpType::Put, OpType::Put],
+            &[21, 22, 23],
+        )]);
+        let source2 = new_source(&[new_batch(b"k1", &[1], &[14], &[OpType::Put], &[24])]);
+        let keep_versions = KeepVersions {
+            max_versions: 2,
+            ..Default::default()
+        };
+        let mut reader = VersionMergeReader::new(vec![source1, source2], keep_versions)
+            .await
+            .unwrap();
+        check_reader_result(
+            &mut reader,
+            &[new_batch(
+                b"k1",
+                &[1, 1, 2],
+                &[14, 12, 13],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[24, 21, 23],
+            )],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_version_merge_reader_duration() {
+        let now = Timestamp::current_millis().value();
+        let old = now - 3_600_000;
+        let source1 = new_source(&[new_batch(
+            b"k1",
+            &[old, now],
+            &[11, 12],
+            &[OpType::Put, OpType::Put],
+            &[21, 22],
+        )]);
+        let source2 = new_source(&[new_batch(
+            b"k1",
+            &[old, now],
+            &[13, 14],
+            &[OpType::Put, OpType::Put],
+            &[23, 24],
+        )]);
+        let keep_versions = KeepVersions {
+            max_versions: 3,
+            duration: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let mut reader = VersionMergeReader::new(vec![source1, source2], keep_versions)
+            .await
+            .unwrap();
+        check_reader_result(
+            &mut reader,
+            &[new_batch(
+                b"k1",
+                &[old, now, now],
+                &[13, 14, 12],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[23, 24, 22],
+            )],
+        )
+        .await;
+    }
+}
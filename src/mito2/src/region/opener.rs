@@ -135,7 +135,7 @@ impl RegionOpener {
                 );
             }
         }
-        let options = RegionOptions::try_from(&self.options)?;
+        let options = self.region_options(config)?;
         let object_store = self.object_store(&options.storage)?.clone();
 
         // Create a manifest manager for this region and writes regions to the manifest file.
@@ -144,7 +144,10 @@ impl RegionOpener {
         let manifest_manager =
             RegionManifestManager::new(metadata.clone(), region_manifest_options).await?;
 
-        let mutable = self.memtable_builder.build(&metadata);
+        let mutable = self.memtable_builder.build(
+            &metadata,
+            options.duplicate_timestamp_policy().keeps_last_only(),
+        );
 
         let version = VersionBuilder::new(metadata, mutable)
             .options(options)
@@ -205,7 +208,7 @@ impl RegionOpener {
         config: &MitoConfig,
         wal: &Wal<S>,
     ) -> Result<Option<MitoRegion>> {
-        let region_options = RegionOptions::try_from(&self.options)?;
+        let region_options = self.region_options(config)?;
         let region_manifest_options = self.manifest_options(config, &region_options)?;
         let Some(manifest_manager) = RegionManifestManager::open(region_manifest_options).await?
         else {
@@ -223,7 +226,12 @@ impl RegionOpener {
             access_layer.clone(),
             self.cache_manager.clone(),
         ));
-        let mutable = self.memtable_builder.build(&metadata);
+        let mutable = self.memtable_builder.build(
+            &metadata,
+            region_options
+                .duplicate_timestamp_policy()
+                .keeps_last_only(),
+        );
         let version = VersionBuilder::new(metadata, mutable)
             .add_files(file_purger.clone(), manifest.files.values().cloned())
             .flushed_entry_id(manifest.flushed_entry_id)
@@ -249,6 +257,16 @@ impl RegionOpener {
         Ok(Some(region))
     }
 
+    /// Parses the options of the region, the unset ones fall back to the engine's
+    /// defaults in `config`.
+    fn region_options(&self, config: &MitoConfig) -> Result<RegionOptions> {
+        let mut options = RegionOptions::try_from(&self.options)?;
+        options
+            .duplicate_timestamp_policy
+            .get_or_insert(config.duplicate_timestamp_policy);
+        Ok(options)
+    }
+
     /// Returns a new manifest options.
     fn manifest_options(
         &self,
@@ -366,6 +384,6 @@ async fn replay_memtable<S: LogStore>(
 }
 
 /// Returns the directory to the manifest files.
-fn new_manifest_dir(region_dir: &str) -> String {
+pub(crate) fn new_manifest_dir(region_dir: &str) -> String {
     join_dir(region_dir, "manifest")
 }
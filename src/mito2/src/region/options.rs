@@ -17,10 +17,11 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_with::{serde_as, with_prefix, DisplayFromStr};
 use snafu::ResultExt;
+use strum::EnumString;
 
 use crate::error::{Error, JsonOptionsSnafu, Result};
 
@@ -33,10 +34,20 @@ pub struct RegionOptions {
     /// Region SST files TTL.
     #[serde(with = "humantime_serde")]
     pub ttl: Option<Duration>,
-    /// Compaction options.
-    pub compaction: CompactionOptions,
+    /// Compaction options, the engine's default strategy is used if they're not set.
+    pub compaction: Option<CompactionOptions>,
     /// Custom storage.
     pub storage: Option<String>,
+    /// Handling of the rows with the same primary key and timestamp. The engine's
+    /// default policy is filled in when opening the region if it's not set.
+    pub duplicate_timestamp_policy: Option<DuplicateTimestampPolicy>,
+}
+
+impl RegionOptions {
+    /// Returns the duplicate timestamp policy of the region.
+    pub fn duplicate_timestamp_policy(&self) -> DuplicateTimestampPolicy {
+        self.duplicate_timestamp_policy.unwrap_or_default()
+    }
 }
 
 impl TryFrom<&HashMap<String, String>> for RegionOptions {
@@ -51,12 +62,13 @@ impl TryFrom<&HashMap<String, String>> for RegionOptions {
         // See https://github.com/serde-rs/serde/issues/1626
         let options: RegionOptionsWithoutEnum =
             serde_json::from_str(&json).context(JsonOptionsSnafu)?;
-        let compaction: CompactionOptions = serde_json::from_str(&json).unwrap_or_default();
+        let compaction: Option<CompactionOptions> = serde_json::from_str(&json).ok();
 
         Ok(RegionOptions {
             ttl: options.ttl,
             compaction,
             storage: options.storage,
+            duplicate_timestamp_policy: options.duplicate_timestamp_policy,
         })
     }
 }
@@ -69,6 +81,9 @@ pub enum CompactionOptions {
     /// Time window compaction strategy.
     #[serde(with = "prefix_twcs")]
     Twcs(TwcsOptions),
+    /// Leveled compaction strategy.
+    #[serde(with = "prefix_leveled")]
+    Leveled(LeveledOptions),
 }
 
 impl Default for CompactionOptions {
@@ -77,6 +92,55 @@ impl Default for CompactionOptions {
     }
 }
 
+impl From<CompactionStrategy> for CompactionOptions {
+    fn from(strategy: CompactionStrategy) -> Self {
+        match strategy {
+            CompactionStrategy::Twcs => Self::Twcs(TwcsOptions::default()),
+            CompactionStrategy::Leveled => Self::Leveled(LeveledOptions::default()),
+        }
+    }
+}
+
+/// Compaction strategy of the regions that don't set the `compaction.type` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum CompactionStrategy {
+    /// Time window compaction, for the append-heavy time-series.
+    #[default]
+    Twcs,
+    /// Leveled compaction, for the upsert-heavy workloads.
+    Leveled,
+}
+
+/// Handling of the rows with the same primary key and timestamp, which are written
+/// by different requests or by the same request.
+///
+/// A delete always removes all the rows of the timestamp written before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DuplicateTimestampPolicy {
+    /// Keeps the last written row and the previous rows are overwritten.
+    #[default]
+    KeepLast,
+    /// Keeps the first written row and the later rows are ignored.
+    KeepFirst,
+    /// Rejects a write request containing more than one row of the same primary key
+    /// and timestamp. Rows written by different requests are handled like `KeepLast`,
+    /// as checking them requires reading the region on each write.
+    Error,
+    /// Keeps all the rows, like an append-only table.
+    KeepBoth,
+}
+
+impl DuplicateTimestampPolicy {
+    /// Returns true if only the last written row of a timestamp is read.
+    pub fn keeps_last_only(&self) -> bool {
+        matches!(self, Self::KeepLast | Self::Error)
+    }
+}
+
 /// Time window compaction options.
 #[serde_as]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -94,6 +158,7 @@ pub struct TwcsOptions {
 }
 
 with_prefix!(prefix_twcs "compaction.twcs.");
+with_prefix!(prefix_leveled "compaction.leveled.");
 
 impl TwcsOptions {
     /// Returns time window in second resolution.
@@ -119,6 +184,23 @@ impl Default for TwcsOptions {
     }
 }
 
+/// Leveled compaction options.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LeveledOptions {
+    /// Max num of files that can be kept in level 0, above which they're merged with
+    /// the overlapping files in level 1.
+    #[serde_as(as = "DisplayFromStr")]
+    pub max_l0_files: usize,
+}
+
+impl Default for LeveledOptions {
+    fn default() -> Self {
+        Self { max_l0_files: 4 }
+    }
+}
+
 /// We need to define a new struct without enum fields as `#[serde(default)]` does not
 /// support external tagging.
 #[derive(Debug, Deserialize)]
@@ -128,6 +210,7 @@ struct RegionOptionsWithoutEnum {
     #[serde(with = "humantime_serde")]
     ttl: Option<Duration>,
     storage: Option<String>,
+    duplicate_timestamp_policy: Option<DuplicateTimestampPolicy>,
 }
 
 impl Default for RegionOptionsWithoutEnum {
@@ -136,6 +219,7 @@ impl Default for RegionOptionsWithoutEnum {
         RegionOptionsWithoutEnum {
             ttl: options.ttl,
             storage: options.storage,
+            duplicate_timestamp_policy: options.duplicate_timestamp_policy,
         }
     }
 }
@@ -222,14 +306,68 @@ mod tests {
         ]);
         let options = RegionOptions::try_from(&map).unwrap();
         let expect = RegionOptions {
-            compaction: CompactionOptions::Twcs(TwcsOptions {
+            compaction: Some(CompactionOptions::Twcs(TwcsOptions {
                 max_active_window_files: 8,
                 time_window: Some(Duration::from_secs(3600 * 2)),
                 ..Default::default()
-            }),
+            })),
+            ..Default::default()
+        };
+        assert_eq!(expect, options);
+    }
+
+    #[test]
+    fn test_with_leveled_compaction() {
+        let map = make_map(&[
+            ("compaction.type", "Leveled"),
+            ("compaction.leveled.max_l0_files", "8"),
+        ]);
+        let options = RegionOptions::try_from(&map).unwrap();
+        let expect = RegionOptions {
+            compaction: Some(CompactionOptions::Leveled(LeveledOptions {
+                max_l0_files: 8,
+            })),
             ..Default::default()
         };
         assert_eq!(expect, options);
+
+        let map = make_map(&[("compaction.type", "leveled")]);
+        let options = RegionOptions::try_from(&map).unwrap();
+        assert_eq!(
+            Some(CompactionOptions::from(CompactionStrategy::Leveled)),
+            options.compaction
+        );
+    }
+
+    #[test]
+    fn test_compaction_strategy() {
+        assert_eq!(CompactionStrategy::Twcs, CompactionStrategy::default());
+        assert_eq!(CompactionStrategy::Leveled, "leveled".parse().unwrap());
+        assert!("stcs".parse::<CompactionStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_with_duplicate_timestamp_policy() {
+        let map = make_map(&[("duplicate_timestamp_policy", "Keep_First")]);
+        let options = RegionOptions::try_from(&map).unwrap();
+        assert_eq!(
+            Some(DuplicateTimestampPolicy::KeepFirst),
+            options.duplicate_timestamp_policy
+        );
+        assert_eq!(
+            DuplicateTimestampPolicy::KeepLast,
+            RegionOptions::default().duplicate_timestamp_policy()
+        );
+
+        let map = make_map(&[("duplicate_timestamp_policy", "keep_none")]);
+        assert!(RegionOptions::try_from(&map).is_err());
+
+        assert_eq!(
+            DuplicateTimestampPolicy::KeepBoth,
+            "keep_both".parse().unwrap()
+        );
+        assert!(DuplicateTimestampPolicy::Error.keeps_last_only());
+        assert!(!DuplicateTimestampPolicy::KeepFirst.keeps_last_only());
     }
 
     #[test]
@@ -241,16 +379,18 @@ mod tests {
             ("compaction.twcs.time_window", "2h"),
             ("compaction.type", "twcs"),
             ("storage", "S3"),
+            ("duplicate_timestamp_policy", "keep_both"),
         ]);
         let options = RegionOptions::try_from(&map).unwrap();
         let expect = RegionOptions {
             ttl: Some(Duration::from_secs(3600 * 24 * 7)),
-            compaction: CompactionOptions::Twcs(TwcsOptions {
+            compaction: Some(CompactionOptions::Twcs(TwcsOptions {
                 max_active_window_files: 8,
                 max_inactive_window_files: 2,
                 time_window: Some(Duration::from_secs(3600 * 2)),
-            }),
+            })),
             storage: Some("s3".to_string()),
+            duplicate_timestamp_policy: Some(DuplicateTimestampPolicy::KeepBoth),
         };
         assert_eq!(expect, options);
     }
@@ -81,7 +81,13 @@ impl VersionControl {
         if version.memtables.mutable.is_empty() {
             return;
         }
-        let new_mutable = builder.build(&version.metadata);
+        let new_mutable = builder.build(
+            &version.metadata,
+            version
+                .options
+                .duplicate_timestamp_policy()
+                .keeps_last_only(),
+        );
         // Safety: Immutable memtable is None.
         let new_memtables = version.memtables.freeze_mutable(new_mutable).unwrap();
         // Create a new version with memtable switched.
@@ -117,7 +123,13 @@ impl VersionControl {
     /// Mark all opened files as deleted and set the delete marker in [VersionControlData]
     pub(crate) fn mark_dropped(&self, memtable_builder: &MemtableBuilderRef) {
         let version = self.current().version;
-        let new_mutable = memtable_builder.build(&version.metadata);
+        let new_mutable = memtable_builder.build(
+            &version.metadata,
+            version
+                .options
+                .duplicate_timestamp_policy()
+                .keeps_last_only(),
+        );
 
         let mut data = self.data.write().unwrap();
         data.is_dropped = true;
@@ -133,8 +145,14 @@ impl VersionControl {
     /// It replaces existing mutable memtable with a memtable that uses the
     /// new schema. Memtables of the version must be empty.
     pub(crate) fn alter_schema(&self, metadata: RegionMetadataRef, builder: &MemtableBuilderRef) {
-        let new_mutable = builder.build(&metadata);
         let version = self.current().version;
+        let new_mutable = builder.build(
+            &metadata,
+            version
+                .options
+                .duplicate_timestamp_policy()
+                .keeps_last_only(),
+        );
         debug_assert!(version.memtables.mutable.is_empty());
         debug_assert!(version.memtables.immutables().is_empty());
         let new_version = Arc::new(
@@ -157,7 +175,13 @@ impl VersionControl {
     ) {
         let version = self.current().version;
 
-        let new_mutable = memtable_builder.build(&version.metadata);
+        let new_mutable = memtable_builder.build(
+            &version.metadata,
+            version
+                .options
+                .duplicate_timestamp_policy()
+                .keeps_last_only(),
+        );
         let new_version = Arc::new(
             VersionBuilder::new(version.metadata.clone(), new_mutable)
                 .flushed_entry_id(truncated_entry_id)
@@ -14,13 +14,14 @@
 
 //! Worker requests.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::iter;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use api::helper::{
-    is_column_type_value_eq, is_semantic_type_eq, proto_value_type, to_column_data_type,
-    to_proto_value,
+    is_column_type_value_eq, is_semantic_type_eq, pb_value_to_value_ref, proto_value_type,
+    to_column_data_type, to_proto_value,
 };
 use api::v1::{ColumnDataType, ColumnSchema, OpType, Rows, SemanticType, Value};
 use common_query::Output;
@@ -36,6 +37,7 @@ use store_api::metadata::{ColumnMetadata, RegionMetadata};
 use store_api::region_request::{
     RegionAlterRequest, RegionCloseRequest, RegionCompactRequest, RegionCreateRequest,
     RegionDropRequest, RegionFlushRequest, RegionOpenRequest, RegionRequest, RegionTruncateRequest,
+    WriteAckLevel,
 };
 use store_api::storage::{RegionId, SequenceNumber};
 use tokio::sync::oneshot::{self, Receiver, Sender};
@@ -46,6 +48,7 @@ use crate::error::{
 };
 use crate::memtable::MemtableId;
 use crate::metrics::COMPACTION_ELAPSED_TOTAL;
+use crate::row_converter::{McmpRowCodec, RowCodec, SortField};
 use crate::sst::file::FileMeta;
 use crate::sst::file_purger::{FilePurgerRef, PurgeRequest};
 use crate::wal::EntryId;
@@ -222,6 +225,49 @@ impl WriteRequest {
         Ok(())
     }
 
+    /// Checks the rows to put don't have the same primary key and timestamp. The schema
+    /// of rows must be checked and filled before.
+    pub(crate) fn check_duplicate_rows(&self, metadata: &RegionMetadata) -> Result<()> {
+        if self.op_type != OpType::Put {
+            return Ok(());
+        }
+
+        let key_columns: Vec<_> = metadata
+            .primary_key_columns()
+            .chain(iter::once(metadata.time_index_column()))
+            .collect();
+        let codec = McmpRowCodec::new(
+            key_columns
+                .iter()
+                .map(|column| SortField::new(column.column_schema.data_type.clone()))
+                .collect(),
+        );
+        // Safety: Rows have all columns of the region after filling.
+        let key_indices: Vec<_> = key_columns
+            .iter()
+            .map(|column| self.name_to_index[&column.column_schema.name])
+            .collect();
+        let mut keys = HashSet::with_capacity(self.rows.rows.len());
+        for (i, row) in self.rows.rows.iter().enumerate() {
+            let key = codec.encode(
+                key_indices
+                    .iter()
+                    .map(|index| pb_value_to_value_ref(&row.values[*index])),
+            )?;
+            ensure!(
+                keys.insert(key),
+                InvalidRequestSnafu {
+                    region_id: self.region_id,
+                    reason: format!(
+                        "row {i} has the same primary key and timestamp as a previous row"
+                    ),
+                }
+            );
+        }
+
+        Ok(())
+    }
+
     /// Tries to fill missing columns.
     ///
     /// Currently, our protobuf format might be inefficient when we need to fill lots of null
@@ -449,6 +495,8 @@ pub(crate) struct SenderWriteRequest {
     /// Result sender.
     pub(crate) sender: OptionOutputTx,
     pub(crate) request: WriteRequest,
+    /// When the result is sent.
+    pub(crate) ack_level: WriteAckLevel,
 }
 
 /// Request sent to a worker
@@ -485,6 +533,7 @@ impl WorkerRequest {
                 WorkerRequest::Write(SenderWriteRequest {
                     sender: sender.into(),
                     request: write_request,
+                    ack_level: v.ack_level,
                 })
             }
             RegionRequest::Delete(v) => {
@@ -492,6 +541,7 @@ impl WorkerRequest {
                 WorkerRequest::Write(SenderWriteRequest {
                     sender: sender.into(),
                     request: write_request,
+                    ack_level: v.ack_level,
                 })
             }
             RegionRequest::Create(v) => WorkerRequest::Ddl(SenderDdlRequest {
@@ -826,6 +876,43 @@ mod tests {
         request.check_schema(&metadata).unwrap();
     }
 
+    #[test]
+    fn test_check_duplicate_rows() {
+        let new_rows = |values: Vec<(i64, i64)>| Rows {
+            schema: vec![
+                new_column_schema(
+                    "ts",
+                    ColumnDataType::TimestampMillisecond,
+                    SemanticType::Timestamp,
+                ),
+                new_column_schema("k0", ColumnDataType::Int64, SemanticType::Tag),
+            ],
+            rows: values
+                .into_iter()
+                .map(|(ts, k0)| Row {
+                    values: vec![ts_ms_value(ts), i64_value(k0)],
+                })
+                .collect(),
+        };
+        let metadata = new_region_metadata();
+
+        let rows = new_rows(vec![(1, 1), (1, 2), (2, 1)]);
+        let request = WriteRequest::new(RegionId::new(1, 1), OpType::Put, rows).unwrap();
+        request.check_duplicate_rows(&metadata).unwrap();
+
+        let rows = new_rows(vec![(1, 1), (2, 1), (1, 1)]);
+        let request = WriteRequest::new(RegionId::new(1, 1), OpType::Put, rows.clone()).unwrap();
+        let err = request.check_duplicate_rows(&metadata).unwrap_err();
+        check_invalid_request(
+            &err,
+            "row 2 has the same primary key and timestamp as a previous row",
+        );
+
+        // Deleting a row twice is harmless.
+        let request = WriteRequest::new(RegionId::new(1, 1), OpType::Delete, rows).unwrap();
+        request.check_duplicate_rows(&metadata).unwrap();
+    }
+
     #[test]
     fn test_column_type() {
         let rows = Rows {
@@ -90,6 +90,8 @@ pub struct FileMeta {
     pub level: Level,
     /// Size of the file.
     pub file_size: u64,
+    /// Whether the file may keep multiple versions of a row.
+    pub multi_versions: bool,
 }
 
 /// Handle to a SST file.
@@ -139,6 +141,11 @@ impl FileHandle {
         self.inner.meta.time_range
     }
 
+    /// Returns true if the file may keep multiple versions of a row.
+    pub fn multi_versions(&self) -> bool {
+        self.inner.meta.multi_versions
+    }
+
     /// Mark the file as deleted and will delete it on drop asynchronously
     pub fn mark_deleted(&self) {
         self.inner.deleted.store(true, Ordering::Relaxed);
@@ -231,6 +238,7 @@ mod tests {
             time_range: FileTimeRange::default(),
             level,
             file_size: 0,
+            multi_versions: false,
         }
     }
 
@@ -43,7 +43,7 @@ use store_api::metadata::{ColumnMetadata, RegionMetadataRef};
 use store_api::region_engine::RegionEngine;
 use store_api::region_request::{
     RegionCloseRequest, RegionCreateRequest, RegionDeleteRequest, RegionFlushRequest,
-    RegionOpenRequest, RegionPutRequest, RegionRequest,
+    RegionOpenRequest, RegionPutRequest, RegionRequest, WriteAckLevel,
 };
 use store_api::storage::{ColumnId, RegionId};
 
@@ -567,7 +567,13 @@ pub fn delete_rows_schema(request: &RegionCreateRequest) -> Vec<api::v1::ColumnS
 pub async fn put_rows(engine: &MitoEngine, region_id: RegionId, rows: Rows) {
     let num_rows = rows.rows.len();
     let output = engine
-        .handle_request(region_id, RegionRequest::Put(RegionPutRequest { rows }))
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                ack_level: WriteAckLevel::default(),
+            }),
+        )
         .await
         .unwrap();
     let Output::AffectedRows(rows_inserted) = output else {
@@ -618,7 +624,10 @@ pub async fn delete_rows(engine: &MitoEngine, region_id: RegionId, rows: Rows) {
     let output = engine
         .handle_request(
             region_id,
-            RegionRequest::Delete(RegionDeleteRequest { rows }),
+            RegionRequest::Delete(RegionDeleteRequest {
+                rows,
+                ack_level: WriteAckLevel::default(),
+            }),
         )
         .await
         .unwrap();
@@ -142,13 +142,14 @@ impl<S: LogStore> WalWriter<S> {
         Ok(())
     }
 
-    /// Write all buffered entries to the WAL.
-    pub async fn write_to_wal(&mut self) -> Result<()> {
+    /// Write all buffered entries to the WAL, which are synced to the disk before
+    /// returning if `sync` is set.
+    pub async fn write_to_wal(&mut self, sync: bool) -> Result<()> {
         // TODO(yingwen): metrics.
 
         let entries = mem::take(&mut self.entries);
         self.store
-            .append_batch(entries)
+            .append_batch(entries, sync)
             .await
             .map_err(BoxedError::new)
             .context(WriteWalSnafu)
@@ -249,7 +250,7 @@ mod tests {
         writer.add_entry(RegionId::new(1, 1), 2, &entry).unwrap();
 
         // Test writing multiple region to wal.
-        writer.write_to_wal().await.unwrap();
+        writer.write_to_wal(false).await.unwrap();
     }
 
     fn sample_entries() -> Vec<WalEntry> {
@@ -304,7 +305,7 @@ mod tests {
         writer.add_entry(id1, 3, &entries[2]).unwrap();
         writer.add_entry(id1, 4, &entries[3]).unwrap();
 
-        writer.write_to_wal().await.unwrap();
+        writer.write_to_wal(false).await.unwrap();
 
         // Scan all contents region1
         let stream = wal.scan(id1, 1).unwrap();
@@ -334,7 +335,7 @@ mod tests {
         writer.add_entry(region_id, 2, &entries[1]).unwrap();
         writer.add_entry(region_id, 3, &entries[2]).unwrap();
 
-        writer.write_to_wal().await.unwrap();
+        writer.write_to_wal(false).await.unwrap();
 
         // Delete 1, 2.
         wal.obsolete(region_id, 2).await.unwrap();
@@ -342,7 +343,7 @@ mod tests {
         // Put 4.
         let mut writer = wal.writer();
         writer.add_entry(region_id, 4, &entries[3]).unwrap();
-        writer.write_to_wal().await.unwrap();
+        writer.write_to_wal(false).await.unwrap();
 
         // Scan all
         let stream = wal.scan(region_id, 1).unwrap();
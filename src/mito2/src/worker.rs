@@ -39,6 +39,7 @@ use store_api::logstore::LogStore;
 use store_api::storage::RegionId;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, Mutex};
+use tokio::time::MissedTickBehavior;
 
 use crate::cache::{CacheManager, CacheManagerRef};
 use crate::compaction::CompactionScheduler;
@@ -59,6 +60,9 @@ pub(crate) type WorkerId = u32;
 
 pub(crate) const DROPPING_MARKER_FILE: &str = ".dropping";
 
+/// Max interval to check whether regions need a periodic flush.
+const MAX_FLUSH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 #[cfg_attr(doc, aquamarine::aquamarine)]
 /// A fixed size group of [RegionWorkers](RegionWorker).
 ///
@@ -455,12 +459,28 @@ impl<S: LogStore> RegionWorkerLoop<S> {
 
         // Buffer to retrieve requests from receiver.
         let mut buffer = RequestBuffer::with_capacity(self.config.worker_request_batch_size);
+        // Ticker to check regions to flush periodically.
+        let mut flush_ticker = self.config.periodic_flush_interval.map(|interval| {
+            let mut ticker = tokio::time::interval(interval.min(MAX_FLUSH_CHECK_INTERVAL));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            ticker
+        });
 
         while self.running.load(Ordering::Relaxed) {
             // Clear the buffer before handling next batch of requests.
             buffer.clear();
 
-            match self.receiver.recv().await {
+            let request = match &mut flush_ticker {
+                Some(ticker) => tokio::select! {
+                    request = self.receiver.recv() => request,
+                    _ = ticker.tick() => {
+                        self.flush_periodically();
+                        continue;
+                    }
+                },
+                None => self.receiver.recv().await,
+            };
+            match request {
                 Some(request) => buffer.push(request),
                 None => break,
             }
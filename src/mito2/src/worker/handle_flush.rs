@@ -15,6 +15,7 @@
 //! Handling flush related requests.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_telemetry::{error, info, warn};
 use common_time::util::current_time_millis;
@@ -129,6 +130,42 @@ impl<S> RegionWorkerLoop<S> {
         Ok(())
     }
 
+    /// Flushes regions that have not flushed for the periodic flush interval.
+    pub(crate) fn flush_periodically(&mut self) {
+        let Some(interval) = self.config.periodic_flush_interval else {
+            return;
+        };
+
+        if let Err(e) = self.flush_regions_periodically(interval) {
+            error!(e; "Failed to flush regions periodically");
+        }
+    }
+
+    fn flush_regions_periodically(&mut self, interval: Duration) -> Result<()> {
+        let regions = self.regions.list_regions();
+        let min_last_flush_time = current_time_millis() - interval.as_millis() as i64;
+
+        for region in &regions {
+            if !region.is_writable()
+                || self.flush_scheduler.is_flush_requested(region.region_id)
+                || region.last_flush_millis() > min_last_flush_time
+            {
+                continue;
+            }
+            if region.version().memtables.is_empty() {
+                // Nothing to flush, we don't create tiny SSTs for an idle region.
+                continue;
+            }
+
+            let task =
+                self.new_flush_task(region, FlushReason::Periodic, None, self.config.clone());
+            self.flush_scheduler
+                .schedule_flush(region.region_id, &region.version_control, task)?;
+        }
+
+        Ok(())
+    }
+
     /// Create a flush task with specific `reason` for the `region`.
     pub(crate) fn new_flush_task(
         &self,
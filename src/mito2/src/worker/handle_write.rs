@@ -17,18 +17,34 @@
 use std::collections::{hash_map, HashMap};
 use std::sync::Arc;
 
+use common_query::Output;
+use common_telemetry::error;
+use snafu::ResultExt;
 use store_api::logstore::LogStore;
 use store_api::metadata::RegionMetadata;
+use store_api::region_request::WriteAckLevel;
 use store_api::storage::RegionId;
+use tokio::sync::oneshot::{self, Receiver};
 
-use crate::error::{RejectWriteSnafu, Result};
+use crate::error::{RecvSnafu, RejectWriteSnafu, Result};
+use crate::flush::{FlushReason, RegionFlushTask};
 use crate::metrics::{
     WRITE_REJECT_TOTAL, WRITE_ROWS_TOTAL, WRITE_STAGE_ELAPSED, WRITE_STALL_TOTAL,
 };
+use crate::region::options::DuplicateTimestampPolicy;
 use crate::region_write_ctx::RegionWriteCtx;
-use crate::request::{SenderWriteRequest, WriteRequest};
+use crate::request::{OptionOutputTx, SenderWriteRequest, WriteRequest};
 use crate::worker::RegionWorkerLoop;
 
+/// A write acknowledged after its region is flushed.
+struct FlushWaiter {
+    region_id: RegionId,
+    /// Sender of the write request.
+    sender: OptionOutputTx,
+    /// Receiver of the write result.
+    write_rx: Receiver<Result<Output>>,
+}
+
 impl<S: LogStore> RegionWorkerLoop<S> {
     /// Takes and handles all write requests.
     pub(crate) async fn handle_write_requests(
@@ -60,7 +76,11 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             return;
         }
 
-        let mut region_ctxs = self.prepare_region_write_ctx(write_requests);
+        // Syncs the WAL once for all requests acknowledged after the WAL sync.
+        let sync_wal = write_requests
+            .iter()
+            .any(|req| req.ack_level >= WriteAckLevel::Wal);
+        let (mut region_ctxs, flush_waiters) = self.prepare_region_write_ctx(write_requests);
 
         // Write to WAL.
         {
@@ -73,11 +93,13 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                     region_ctx.set_error(e);
                 }
             }
-            if let Err(e) = wal_writer.write_to_wal().await.map_err(Arc::new) {
+            if let Err(e) = wal_writer.write_to_wal(sync_wal).await.map_err(Arc::new) {
                 // Failed to write wal.
                 for mut region_ctx in region_ctxs.into_values() {
                     region_ctx.set_error(e.clone());
                 }
+                // Sends the errors to the waiters.
+                self.flush_for_waiters(flush_waiters);
                 return;
             }
         }
@@ -100,17 +122,21 @@ impl<S: LogStore> RegionWorkerLoop<S> {
         WRITE_ROWS_TOTAL
             .with_label_values(&["delete"])
             .inc_by(delete_rows as u64);
+
+        self.flush_for_waiters(flush_waiters);
     }
 }
 
 impl<S> RegionWorkerLoop<S> {
-    /// Validates and groups requests by region.
+    /// Validates and groups requests by region, returns the contexts and the waiters of
+    /// the requests acknowledged after the flush.
     fn prepare_region_write_ctx(
         &mut self,
         write_requests: Vec<SenderWriteRequest>,
-    ) -> HashMap<RegionId, RegionWriteCtx> {
+    ) -> (HashMap<RegionId, RegionWriteCtx>, Vec<FlushWaiter>) {
         // Initialize region write context map.
         let mut region_ctxs = HashMap::new();
+        let mut flush_waiters = Vec::new();
         for mut sender_req in write_requests {
             let region_id = sender_req.request.region_id;
 
@@ -150,15 +176,95 @@ impl<S> RegionWorkerLoop<S> {
                 continue;
             }
 
+            // Checks duplicate rows if the region rejects them.
+            let version = region_ctx.version();
+            if version.options.duplicate_timestamp_policy() == DuplicateTimestampPolicy::Error {
+                if let Err(e) = sender_req.request.check_duplicate_rows(&version.metadata) {
+                    sender_req.sender.send(Err(e));
+
+                    continue;
+                }
+            }
+
+            // The write result of a request acknowledged after the flush is held until
+            // the flush finishes.
+            let sender = if sender_req.ack_level == WriteAckLevel::Flush {
+                let (write_tx, write_rx) = oneshot::channel();
+                flush_waiters.push(FlushWaiter {
+                    region_id,
+                    sender: sender_req.sender,
+                    write_rx,
+                });
+                write_tx.into()
+            } else {
+                sender_req.sender
+            };
+
             // Collect requests by region.
             region_ctx.push_mutation(
                 sender_req.request.op_type as i32,
                 Some(sender_req.request.rows),
-                sender_req.sender,
+                sender,
             );
         }
 
-        region_ctxs
+        (region_ctxs, flush_waiters)
+    }
+
+    /// Flushes the regions of the succeeded writes of the `waiters`, and acknowledges
+    /// each of them once the flush finishes. The failed writes are acknowledged with
+    /// their errors immediately.
+    ///
+    /// It must be called after the write contexts are dropped, which send the results.
+    fn flush_for_waiters(&mut self, waiters: Vec<FlushWaiter>) {
+        let mut tasks: HashMap<RegionId, RegionFlushTask> = HashMap::new();
+        for FlushWaiter {
+            region_id,
+            sender,
+            mut write_rx,
+        } in waiters
+        {
+            let output = match write_rx.try_recv() {
+                Ok(Ok(output)) => output,
+                Ok(Err(e)) => {
+                    sender.send(Err(e));
+                    continue;
+                }
+                // The result is always sent, or the sender is dropped with the request.
+                Err(_) => continue,
+            };
+            let Some(region) = self.regions.get_region(region_id) else {
+                continue;
+            };
+
+            let (flush_tx, flush_rx) = oneshot::channel();
+            tasks
+                .entry(region_id)
+                .or_insert_with(|| {
+                    self.new_flush_task(&region, FlushReason::WriteAck, None, self.config.clone())
+                })
+                .push_sender(flush_tx.into());
+            common_runtime::spawn_bg(async move {
+                // The sender is dropped if the flush fails to schedule, or the region is
+                // closed before flushing.
+                let result = match flush_rx.await.context(RecvSnafu) {
+                    Ok(Ok(_)) => Ok(output),
+                    Ok(Err(e)) | Err(e) => Err(e),
+                };
+                sender.send(result);
+            });
+        }
+
+        for (region_id, task) in tasks {
+            // Safety: The task is created for the existing region.
+            let region = self.regions.get_region(region_id).unwrap();
+            if let Err(e) =
+                self.flush_scheduler
+                    .schedule_flush(region_id, &region.version_control, task)
+            {
+                error!(e; "Failed to schedule flush task for region {}", region_id);
+            }
+        }
     }
 
     /// Returns true if the engine needs to reject some write requests.
@@ -13,9 +13,10 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::task::{ready, Context, Poll};
 
 use async_trait::async_trait;
-use opendal::raw::oio::Read;
+use opendal::raw::oio::{self, Read};
 use opendal::raw::{
     Accessor, Layer, LayeredAccessor, OpDelete, OpList, OpRead, OpWrite, RpDelete, RpList, RpRead,
     RpWrite,
@@ -79,7 +80,7 @@ impl<I: Accessor, C: Accessor + Clone> LayeredAccessor for LruCacheAccessor<I, C
     type Inner = I;
     type Reader = Box<dyn Read>;
     type BlockingReader = I::BlockingReader;
-    type Writer = I::Writer;
+    type Writer = InvalidateWriter<I::Writer, C>;
     type BlockingWriter = I::BlockingWriter;
     type Pager = I::Pager;
     type BlockingPager = I::BlockingPager;
@@ -95,19 +96,22 @@ impl<I: Accessor, C: Accessor + Clone> LayeredAccessor for LruCacheAccessor<I, C
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
         let result = self.inner.write(path, args).await;
 
-        self.read_cache
-            .invalidate_entries_with_prefix(format!("{:x}", md5::compute(path)))
-            .await;
+        self.read_cache.invalidate_path(path);
 
-        result
+        result.map(|(rp, writer)| {
+            let writer = InvalidateWriter {
+                inner: writer,
+                path: path.to_string(),
+                read_cache: self.read_cache.clone(),
+            };
+            (rp, writer)
+        })
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
         let result = self.inner.delete(path, args).await;
 
-        self.read_cache
-            .invalidate_entries_with_prefix(format!("{:x}", md5::compute(path)))
-            .await;
+        self.read_cache.invalidate_path(path);
 
         result
     }
@@ -124,8 +128,7 @@ impl<I: Accessor, C: Accessor + Clone> LayeredAccessor for LruCacheAccessor<I, C
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
         let result = self.inner.blocking_write(path, args);
 
-        self.read_cache
-            .blocking_invalidate_entries_with_prefix(format!("{:x}", md5::compute(path)));
+        self.read_cache.invalidate_path(path);
 
         result
     }
@@ -134,3 +137,30 @@ impl<I: Accessor, C: Accessor + Clone> LayeredAccessor for LruCacheAccessor<I, C
         self.inner.blocking_list(path, args)
     }
 }
+
+/// Invalidates the cached reads of the written file again once it's closed. The new
+/// content is only visible after closing, so the reads while writing may have
+/// cached the old content.
+pub struct InvalidateWriter<W, C: Clone> {
+    inner: W,
+    path: String,
+    read_cache: ReadCache<C>,
+}
+
+impl<W: oio::Write, C: Accessor + Clone> oio::Write for InvalidateWriter<W, C> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let result = ready!(self.inner.poll_close(cx));
+
+        self.read_cache.invalidate_path(&self.path);
+
+        Poll::Ready(result)
+    }
+}
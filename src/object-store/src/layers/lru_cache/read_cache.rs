@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use common_telemetry::logging::debug;
@@ -67,6 +68,9 @@ pub(crate) struct ReadCache<C: Clone> {
     file_cache: Arc<C>,
     /// Local memory cache to track local cache files
     mem_cache: Cache<String, ReadResult>,
+    /// Number of the objects overwritten or deleted, to detect the reads racing with
+    /// them.
+    write_epoch: Arc<AtomicU64>,
 }
 
 impl<C: Accessor + Clone> ReadCache<C> {
@@ -104,6 +108,7 @@ impl<C: Accessor + Clone> ReadCache<C> {
                 .async_eviction_listener(eviction_listener)
                 .support_invalidation_closures()
                 .build(),
+            write_epoch: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -114,16 +119,13 @@ impl<C: Accessor + Clone> ReadCache<C> {
         (self.mem_cache.entry_count(), self.mem_cache.weighted_size())
     }
 
-    /// Invalidte all cache items which key starts with `prefix`.
-    pub(crate) async fn invalidate_entries_with_prefix(&self, prefix: String) {
-        // Safety: always ok when building cache with `support_invalidation_closures`.
-        self.mem_cache
-            .invalidate_entries_if(move |k: &String, &_v| k.starts_with(&prefix))
-            .ok();
-    }
-
-    /// Blocking version of `invalidate_entries_with_prefix`.
-    pub(crate) fn blocking_invalidate_entries_with_prefix(&self, prefix: String) {
+    /// Invalidate all cache items of the file at `path`, which is overwritten or
+    /// deleted.
+    pub(crate) fn invalidate_path(&self, path: &str) {
+        // Bumped first, the invalidation below misses the entries of the reads still
+        // loading.
+        let _ = self.write_epoch.fetch_add(1, Ordering::AcqRel);
+        let prefix = format!("{:x}", md5::compute(path));
         // Safety: always ok when building cache with `support_invalidation_closures`.
         self.mem_cache
             .invalidate_entries_if(move |k: &String, &_v| k.starts_with(&prefix))
@@ -185,14 +187,19 @@ impl<C: Accessor + Clone> ReadCache<C> {
 
         let read_key = read_cache_key(path, &args);
 
-        let read_result = self
+        let write_epoch = self.write_epoch.load(Ordering::Acquire);
+        let entry = self
             .mem_cache
-            .try_get_with(
-                read_key.clone(),
-                self.read_remote(inner, &read_key, path, args.clone()),
-            )
+            .entry_by_ref(&read_key)
+            .or_try_insert_with(self.read_remote(inner, &read_key, path, args.clone()))
             .await
             .map_err(|e| OpendalError::new(e.kind(), &e.to_string()))?;
+        if entry.is_fresh() && self.write_epoch.load(Ordering::Acquire) != write_epoch {
+            // A file is overwritten or deleted while loading the entry, which may be
+            // the old content of this file, so it's only returned to this read.
+            self.mem_cache.invalidate(&read_key).await;
+        }
+        let read_result = entry.into_value();
 
         match read_result {
             ReadResult::Success(_) => {
@@ -448,3 +448,42 @@ async fn test_object_store_cache_policy() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_object_store_cache_overwrite() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let root_dir = create_temp_dir("test_object_store_cache_overwrite");
+    let store = OperatorBuilder::new(
+        Fs::default()
+            .root(&root_dir.path().to_string_lossy())
+            .atomic_write_dir(&root_dir.path().to_string_lossy())
+            .build()
+            .unwrap(),
+    )
+    .finish();
+
+    let cache_dir = create_temp_dir("test_object_store_cache_overwrite_cache");
+    let mut builder = Fs::default();
+    let _ = builder
+        .root(&cache_dir.path().to_string_lossy())
+        .atomic_write_dir(&cache_dir.path().to_string_lossy());
+    let file_cache = Arc::new(builder.build().unwrap());
+    let cache_layer = LruCacheLayer::new(file_cache, 1024).await.unwrap();
+    let store = store.layer(cache_layer.clone());
+
+    let path = "test_file";
+    store.write(path, "Hello, object!").await?;
+    assert_eq!(b"Hello, object!", store.read(path).await?.as_slice());
+
+    // The old content is still visible and cached while overwriting.
+    let mut writer = store.writer(path).await?;
+    writer.write("Hello, new object!").await?;
+    assert_eq!(b"Hello, object!", store.read(path).await?.as_slice());
+    writer.close().await?;
+
+    // Never served from the cache after the overwrite.
+    assert_eq!(b"Hello, new object!", store.read(path).await?.as_slice());
+    assert_eq!(b"Hello, new object!", store.read(path).await?.as_slice());
+
+    Ok(())
+}
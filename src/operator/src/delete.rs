@@ -26,6 +26,7 @@ use futures_util::future;
 use partition::manager::PartitionRuleManagerRef;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
+use store_api::region_request::WriteAckLevel;
 use table::requests::DeleteRequest as TableDeleteRequest;
 use table::TableRef;
 
@@ -33,6 +34,7 @@ use crate::error::{
     CatalogSnafu, FindRegionLeaderSnafu, InvalidDeleteRequestSnafu, JoinTaskSnafu,
     MissingTimeIndexColumnSnafu, RequestDeletesSnafu, Result, TableNotFoundSnafu,
 };
+use crate::insert::{resolve_write_ack_level, DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL};
 use crate::region_req_factory::RegionRequestFactory;
 use crate::req_convert::delete::{ColumnToRow, RowToRegion, TableToRegion};
 
@@ -40,6 +42,10 @@ pub struct Deleter {
     catalog_manager: CatalogManagerRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_manager: DatanodeManagerRef,
+    /// When the writes are acknowledged if the query doesn't override it.
+    write_ack_level: WriteAckLevel,
+    /// Max level a query can override [Deleter::write_ack_level] with.
+    max_request_write_ack_level: WriteAckLevel,
 }
 
 pub type DeleterRef = Arc<Deleter>;
@@ -49,14 +55,22 @@ impl Deleter {
         catalog_manager: CatalogManagerRef,
         partition_manager: PartitionRuleManagerRef,
         datanode_manager: DatanodeManagerRef,
+        write_ack_level: WriteAckLevel,
     ) -> Self {
         Self {
             catalog_manager,
             partition_manager,
             datanode_manager,
+            write_ack_level,
+            max_request_write_ack_level: DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL,
         }
     }
 
+    pub fn with_max_request_write_ack_level(mut self, level: WriteAckLevel) -> Self {
+        self.max_request_write_ack_level = level;
+        self
+    }
+
     pub async fn handle_column_deletes(
         &self,
         requests: DeleteRequests,
@@ -121,6 +135,8 @@ impl Deleter {
     ) -> Result<AffectedRows> {
         let header: RegionRequestHeader = ctx.as_ref().into();
         let request_factory = RegionRequestFactory::new(header);
+        let ack_level =
+            resolve_write_ack_level(ctx, self.write_ack_level, self.max_request_write_ack_level)?;
 
         let tasks = self
             .group_requests_by_peer(requests)
@@ -133,7 +149,7 @@ impl Deleter {
                     datanode_manager
                         .datanode(&peer)
                         .await
-                        .handle(request)
+                        .handle_write(request, ack_level)
                         .await
                         .context(RequestDeletesSnafu)
                 })
@@ -23,6 +23,7 @@ use datatypes::arrow::error::ArrowError;
 use datatypes::value::Value;
 use servers::define_into_tonic_status;
 use snafu::{Location, Snafu};
+use store_api::region_request::WriteAckLevel;
 
 #[derive(Snafu)]
 #[snafu(visibility(pub))]
@@ -106,6 +107,17 @@ pub enum Error {
     #[snafu(display("Invalid InsertRequest, reason: {}", reason))]
     InvalidInsertRequest { reason: String, location: Location },
 
+    #[snafu(display(
+        "Invalid value {} of column {}, NaN and infinite floats are rejected",
+        value,
+        column
+    ))]
+    NonFiniteFloat {
+        column: String,
+        value: f64,
+        location: Location,
+    },
+
     #[snafu(display("Invalid DeleteRequest, reason: {}", reason))]
     InvalidDeleteRequest { reason: String, location: Location },
 
@@ -489,6 +501,41 @@ pub enum Error {
         location: Location,
         source: query::error::Error,
     },
+
+    #[snafu(display(
+        "Failed to create table '{}', the instance reaches the max table count {}",
+        table,
+        max
+    ))]
+    TableCountExceeded {
+        table: String,
+        max: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Table '{}' would have {} columns, exceeds the max column count {}",
+        table,
+        columns,
+        max
+    ))]
+    ColumnCountExceeded {
+        table: String,
+        columns: usize,
+        max: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Write ack level {} isn't allowed for a request, the max level is {}",
+        level,
+        max
+    ))]
+    WriteAckLevelNotAllowed {
+        level: WriteAckLevel,
+        max: WriteAckLevel,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -498,6 +545,7 @@ impl ErrorExt for Error {
         match self {
             Error::InvalidSql { .. }
             | Error::InvalidInsertRequest { .. }
+            | Error::NonFiniteFloat { .. }
             | Error::InvalidDeleteRequest { .. }
             | Error::IllegalPrimaryKeysDef { .. }
             | Error::SchemaNotFound { .. }
@@ -513,10 +561,15 @@ impl ErrorExt for Error {
             | Error::InvalidPartitionColumns { .. }
             | Error::PrepareFileTable { .. }
             | Error::InferFileTableSchema { .. }
-            | Error::SchemaIncompatible { .. } => StatusCode::InvalidArguments,
+            | Error::SchemaIncompatible { .. }
+            | Error::WriteAckLevelNotAllowed { .. } => StatusCode::InvalidArguments,
 
             Error::TableAlreadyExists { .. } => StatusCode::TableAlreadyExists,
 
+            Error::TableCountExceeded { .. } | Error::ColumnCountExceeded { .. } => {
+                StatusCode::RuntimeResourcesExhausted
+            }
+
             Error::NotSupported { .. } => StatusCode::Unsupported,
 
             Error::TableMetadataManager { source, .. } => source.status_code(),
@@ -0,0 +1,191 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handling of the NaN, +Inf and -Inf values of the float columns on ingest.
+
+use api::v1::value::ValueData;
+use api::v1::{ColumnDataType, Rows, Value};
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+use strum::{Display, EnumString};
+
+use crate::error::{NonFiniteFloatSnafu, Result};
+
+/// What to do with a NaN or infinite float value on ingest.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumString, Display,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum FloatSpecialPolicy {
+    /// Writes the value as is.
+    #[default]
+    Store,
+    /// Drops the rows having such a value.
+    Drop,
+    /// Rejects the request.
+    Error,
+    /// Writes NULL instead.
+    ToNull,
+}
+
+/// Returns the value if it's a NaN or infinite float.
+fn non_finite(value: &Value) -> Option<f64> {
+    let value = match value.value_data {
+        Some(ValueData::F64Value(v)) => v,
+        Some(ValueData::F32Value(v)) => v as f64,
+        _ => return None,
+    };
+    (!value.is_finite()).then_some(value)
+}
+
+/// Applies the `policy` to the NaN and infinite values of the float columns in `rows`.
+pub fn apply_float_special_policy(policy: FloatSpecialPolicy, rows: &mut Rows) -> Result<()> {
+    if policy == FloatSpecialPolicy::Store {
+        return Ok(());
+    }
+    let float_columns = rows
+        .schema
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| {
+            matches!(
+                ColumnDataType::try_from(column.datatype),
+                Ok(ColumnDataType::Float32 | ColumnDataType::Float64)
+            )
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if float_columns.is_empty() {
+        return Ok(());
+    }
+
+    match policy {
+        FloatSpecialPolicy::Store => {}
+        FloatSpecialPolicy::Error => {
+            for row in &rows.rows {
+                for &i in &float_columns {
+                    let value = row.values.get(i).and_then(non_finite);
+                    ensure!(
+                        value.is_none(),
+                        NonFiniteFloatSnafu {
+                            column: &rows.schema[i].column_name,
+                            value: value.unwrap_or_default(),
+                        }
+                    );
+                }
+            }
+        }
+        FloatSpecialPolicy::Drop => rows.rows.retain(|row| {
+            float_columns
+                .iter()
+                .all(|&i| row.values.get(i).and_then(non_finite).is_none())
+        }),
+        FloatSpecialPolicy::ToNull => {
+            for row in &mut rows.rows {
+                for &i in &float_columns {
+                    if let Some(value) = row.values.get_mut(i) {
+                        if non_finite(value).is_some() {
+                            value.value_data = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use api::v1::{ColumnSchema, Row, SemanticType};
+
+    use super::*;
+
+    fn rows(values: &[f64]) -> Rows {
+        Rows {
+            schema: vec![
+                ColumnSchema {
+                    column_name: "host".to_string(),
+                    datatype: ColumnDataType::String as i32,
+                    semantic_type: SemanticType::Tag as i32,
+                    ..Default::default()
+                },
+                ColumnSchema {
+                    column_name: "cpu".to_string(),
+                    datatype: ColumnDataType::Float64 as i32,
+                    semantic_type: SemanticType::Field as i32,
+                    ..Default::default()
+                },
+            ],
+            rows: values
+                .iter()
+                .map(|v| Row {
+                    values: vec![
+                        Value {
+                            value_data: Some(ValueData::StringValue("a".to_string())),
+                        },
+                        Value {
+                            value_data: Some(ValueData::F64Value(*v)),
+                        },
+                    ],
+                })
+                .collect(),
+        }
+    }
+
+    fn cpu_values(rows: &Rows) -> Vec<Option<f64>> {
+        rows.rows
+            .iter()
+            .map(|row| match row.values[1].value_data {
+                Some(ValueData::F64Value(v)) => Some(v),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_float_special_policy() {
+        let values = [1.0, f64::INFINITY, f64::NEG_INFINITY, f64::NAN];
+
+        let mut stored = rows(&values);
+        apply_float_special_policy(FloatSpecialPolicy::Store, &mut stored).unwrap();
+        assert_eq!(4, stored.rows.len());
+
+        let mut dropped = rows(&values);
+        apply_float_special_policy(FloatSpecialPolicy::Drop, &mut dropped).unwrap();
+        assert_eq!(vec![Some(1.0)], cpu_values(&dropped));
+
+        let mut nulls = rows(&values);
+        apply_float_special_policy(FloatSpecialPolicy::ToNull, &mut nulls).unwrap();
+        assert_eq!(vec![Some(1.0), None, None, None], cpu_values(&nulls));
+
+        let err =
+            apply_float_special_policy(FloatSpecialPolicy::Error, &mut rows(&values)).unwrap_err();
+        assert!(err.to_string().contains("Invalid value inf of column cpu"));
+        apply_float_special_policy(FloatSpecialPolicy::Error, &mut rows(&[1.0, -2.5])).unwrap();
+    }
+
+    #[test]
+    fn test_parse_float_special_policy() {
+        assert_eq!(
+            FloatSpecialPolicy::ToNull,
+            FloatSpecialPolicy::from_str("to-null").unwrap()
+        );
+        assert_eq!("drop", FloatSpecialPolicy::Drop.to_string());
+        assert!(FloatSpecialPolicy::from_str("ignore").is_err());
+    }
+}
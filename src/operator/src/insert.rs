@@ -34,23 +34,49 @@ use partition::manager::PartitionRuleManagerRef;
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::statements::insert::Insert;
+use store_api::region_request::WriteAckLevel;
 use table::engine::TableReference;
 use table::requests::InsertRequest as TableInsertRequest;
 use table::TableRef;
 
 use crate::error::{
     CatalogSnafu, FindNewColumnsOnInsertionSnafu, FindRegionLeaderSnafu, InvalidInsertRequestSnafu,
-    JoinTaskSnafu, RequestInsertsSnafu, Result, TableNotFoundSnafu,
+    JoinTaskSnafu, RequestInsertsSnafu, Result, TableNotFoundSnafu, WriteAckLevelNotAllowedSnafu,
 };
 use crate::expr_factory::CreateExprFactory;
+use crate::float_special::{apply_float_special_policy, FloatSpecialPolicy};
 use crate::region_req_factory::RegionRequestFactory;
 use crate::req_convert::insert::{ColumnToRow, RowToRegion, StatementToRegion, TableToRegion};
 use crate::statement::StatementExecutor;
 
+/// Flushing the regions on each write is expensive, so the requests can't ask for the
+/// flush level unless it's allowed.
+pub const DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL: WriteAckLevel = WriteAckLevel::Wal;
+
+/// Returns the level the query asks for, or the `default` level if it doesn't ask for
+/// one. The level asked for can't be higher than `max`.
+pub(crate) fn resolve_write_ack_level(
+    ctx: &QueryContextRef,
+    default: WriteAckLevel,
+    max: WriteAckLevel,
+) -> Result<WriteAckLevel> {
+    let Some(level) = ctx.write_ack_level() else {
+        return Ok(default);
+    };
+    ensure!(level <= max, WriteAckLevelNotAllowedSnafu { level, max });
+    Ok(level)
+}
+
 pub struct Inserter {
     catalog_manager: CatalogManagerRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_manager: DatanodeManagerRef,
+    /// When the writes are acknowledged if the query doesn't override it.
+    write_ack_level: WriteAckLevel,
+    /// Max level a query can override [Inserter::write_ack_level] with.
+    max_request_write_ack_level: WriteAckLevel,
+    /// Policy of the NaN and infinite floats of the SQL inserts.
+    float_special_policy: FloatSpecialPolicy,
 }
 
 pub type InserterRef = Arc<Inserter>;
@@ -60,14 +86,28 @@ impl Inserter {
         catalog_manager: CatalogManagerRef,
         partition_manager: PartitionRuleManagerRef,
         datanode_manager: DatanodeManagerRef,
+        write_ack_level: WriteAckLevel,
     ) -> Self {
         Self {
             catalog_manager,
             partition_manager,
             datanode_manager,
+            write_ack_level,
+            max_request_write_ack_level: DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL,
+            float_special_policy: FloatSpecialPolicy::default(),
         }
     }
 
+    pub fn with_float_special_policy(mut self, policy: FloatSpecialPolicy) -> Self {
+        self.float_special_policy = policy;
+        self
+    }
+
+    pub fn with_max_request_write_ack_level(mut self, level: WriteAckLevel) -> Self {
+        self.max_request_write_ack_level = level;
+        self
+    }
+
     pub async fn handle_column_inserts(
         &self,
         requests: InsertRequests,
@@ -122,9 +162,10 @@ impl Inserter {
         })?;
         let table_info = table.table_info();
 
-        let inserts = TableToRegion::new(&table_info, &self.partition_manager)
+        let mut inserts = TableToRegion::new(&table_info, &self.partition_manager)
             .convert(request)
             .await?;
+        self.apply_float_special_policy(&mut inserts)?;
 
         let affected_rows = self.do_request(inserts, &ctx).await?;
         Ok(affected_rows as _)
@@ -135,10 +176,11 @@ impl Inserter {
         insert: &Insert,
         ctx: &QueryContextRef,
     ) -> Result<Output> {
-        let inserts =
+        let mut inserts =
             StatementToRegion::new(self.catalog_manager.as_ref(), &self.partition_manager, ctx)
                 .convert(insert)
                 .await?;
+        self.apply_float_special_policy(&mut inserts)?;
 
         let affected_rows = self.do_request(inserts, ctx).await?;
         Ok(Output::AffectedRows(affected_rows as _))
@@ -146,6 +188,20 @@ impl Inserter {
 }
 
 impl Inserter {
+    /// Applies the policy of the SQL inserts, the other protocols apply theirs to the
+    /// requests before [Inserter::handle_row_inserts].
+    fn apply_float_special_policy(&self, requests: &mut RegionInsertRequests) -> Result<()> {
+        for request in &mut requests.requests {
+            if let Some(rows) = &mut request.rows {
+                apply_float_special_policy(self.float_special_policy, rows)?;
+            }
+        }
+        requests
+            .requests
+            .retain(|request| request.rows.as_ref().is_some_and(|r| !r.rows.is_empty()));
+        Ok(())
+    }
+
     async fn do_request(
         &self,
         requests: RegionInsertRequests,
@@ -154,6 +210,8 @@ impl Inserter {
         write_meter!(ctx.current_catalog(), ctx.current_schema(), requests);
         let header: RegionRequestHeader = ctx.as_ref().into();
         let request_factory = RegionRequestFactory::new(header);
+        let ack_level =
+            resolve_write_ack_level(ctx, self.write_ack_level, self.max_request_write_ack_level)?;
 
         let tasks = self
             .group_requests_by_peer(requests)
@@ -166,7 +224,7 @@ impl Inserter {
                     datanode_manager
                         .datanode(&peer)
                         .await
-                        .handle(request)
+                        .handle_write(request, ack_level)
                         .await
                         .context(RequestInsertsSnafu)
                 })
@@ -388,8 +446,10 @@ fn build_create_table_expr(
 mod tests {
     use datatypes::prelude::{ConcreteDataType, Value as DtValue};
     use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema as DtColumnSchema};
+    use session::context::QueryContextBuilder;
 
     use super::*;
+    use crate::error::Error;
 
     #[test]
     fn test_validate_required_columns() {
@@ -431,4 +491,33 @@ mod tests {
         // Neither of the above cases.
         assert!(validate_required_columns(request_schema, &schema).is_err());
     }
+
+    #[test]
+    fn test_resolve_write_ack_level() {
+        let ctx = QueryContextBuilder::default().build();
+        assert_eq!(
+            WriteAckLevel::Flush,
+            resolve_write_ack_level(&ctx, WriteAckLevel::Flush, WriteAckLevel::Wal).unwrap()
+        );
+
+        let ctx = QueryContextBuilder::default()
+            .write_ack_level(WriteAckLevel::Wal)
+            .build();
+        assert_eq!(
+            WriteAckLevel::Wal,
+            resolve_write_ack_level(&ctx, WriteAckLevel::Memtable, WriteAckLevel::Wal).unwrap()
+        );
+
+        let ctx = QueryContextBuilder::default()
+            .write_ack_level(WriteAckLevel::Flush)
+            .build();
+        assert!(matches!(
+            resolve_write_ack_level(&ctx, WriteAckLevel::Memtable, WriteAckLevel::Wal),
+            Err(Error::WriteAckLevelNotAllowed { .. })
+        ));
+        assert_eq!(
+            WriteAckLevel::Flush,
+            resolve_write_ack_level(&ctx, WriteAckLevel::Memtable, WriteAckLevel::Flush).unwrap()
+        );
+    }
 }
@@ -15,6 +15,7 @@
 pub mod delete;
 pub mod error;
 pub mod expr_factory;
+pub mod float_special;
 pub mod insert;
 pub mod metrics;
 pub mod region_req_factory;
@@ -57,6 +57,16 @@ use crate::insert::InserterRef;
 use crate::statement::backup::{COPY_DATABASE_TIME_END_KEY, COPY_DATABASE_TIME_START_KEY};
 use crate::table::table_idents_to_full_name;
 
+/// Limits of the schemas, which prevent unbounded metadata growth, e.g. from the
+/// tables and columns created on demand by schemaless ingest.
+#[derive(Debug, Default, Clone)]
+pub struct SchemaLimits {
+    /// Max number of tables of the instance.
+    pub max_table_count: Option<usize>,
+    /// Max number of columns of a table.
+    pub max_column_count: Option<usize>,
+}
+
 #[derive(Clone)]
 pub struct StatementExecutor {
     catalog_manager: CatalogManagerRef,
@@ -66,6 +76,7 @@ pub struct StatementExecutor {
     partition_manager: PartitionRuleManagerRef,
     cache_invalidator: CacheInvalidatorRef,
     inserter: InserterRef,
+    schema_limits: SchemaLimits,
 }
 
 impl StatementExecutor {
@@ -85,9 +96,16 @@ impl StatementExecutor {
             partition_manager: Arc::new(PartitionRuleManager::new(kv_backend)),
             cache_invalidator,
             inserter,
+            schema_limits: SchemaLimits::default(),
         }
     }
 
+    /// Sets the limits checked while creating and altering tables.
+    pub fn with_schema_limits(mut self, schema_limits: SchemaLimits) -> Self {
+        self.schema_limits = schema_limits;
+        self
+    }
+
     pub async fn execute_stmt(
         &self,
         stmt: QueryStatement,
@@ -16,6 +16,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::helper::ColumnDataTypeWrapper;
+use api::v1::alter_expr::Kind;
 use api::v1::{column_def, AlterExpr, CreateTableExpr};
 use catalog::CatalogManagerRef;
 use chrono::Utc;
@@ -31,6 +32,7 @@ use common_query::Output;
 use common_telemetry::info;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::RawSchema;
+use futures_util::TryStreamExt;
 use partition::partition::{PartitionBound, PartitionDef};
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
@@ -126,6 +128,9 @@ impl StatementExecutor {
             &create_table.table_name,
         );
 
+        self.check_table_count(&table_name).await?;
+        self.check_column_count(&table_name, create_table.column_defs.len())?;
+
         let (partitions, partition_cols) = parse_partitions(create_table, partitions)?;
 
         validate_partition_columns(create_table, &partition_cols)?;
@@ -254,6 +259,13 @@ impl StatementExecutor {
                 table_name: format_full_table_name(catalog_name, schema_name, table_name),
             })?;
 
+        if let Some(Kind::AddColumns(add_columns)) = &expr.kind {
+            self.check_column_count(
+                &TableName::new(catalog_name, schema_name, table_name),
+                table.schema().num_columns() + add_columns.add_columns.len(),
+            )?;
+        }
+
         let table_id = table.table_info().ident.table_id;
         self.verify_alter(table_id, table.table_info(), expr.clone())?;
 
@@ -289,6 +301,69 @@ impl StatementExecutor {
         Ok(Output::AffectedRows(0))
     }
 
+    /// Checks that the instance doesn't reach the max table count before creating the
+    /// table `table_name`. The tables in the metadata are counted, so the tables created
+    /// before restarting count while the system tables don't.
+    async fn check_table_count(&self, table_name: &TableName) -> Result<()> {
+        let Some(max) = self.schema_limits.max_table_count else {
+            return Ok(());
+        };
+
+        let catalogs = self
+            .table_metadata_manager
+            .catalog_manager()
+            .catalog_names()
+            .await
+            .try_collect::<Vec<_>>()
+            .await
+            .context(TableMetadataManagerSnafu)?;
+        let mut count = 0;
+        for catalog in catalogs {
+            let schemas = self
+                .table_metadata_manager
+                .schema_manager()
+                .schema_names(&catalog)
+                .await
+                .try_collect::<Vec<_>>()
+                .await
+                .context(TableMetadataManagerSnafu)?;
+            for schema in schemas {
+                count += self
+                    .table_metadata_manager
+                    .table_name_manager()
+                    .tables(&catalog, &schema)
+                    .await
+                    .context(TableMetadataManagerSnafu)?
+                    .len();
+            }
+        }
+
+        ensure!(
+            count < max,
+            error::TableCountExceededSnafu {
+                table: table_name.to_string(),
+                max,
+            }
+        );
+        Ok(())
+    }
+
+    /// Checks that the table `table_name` with `columns` columns doesn't exceed the max
+    /// column count.
+    fn check_column_count(&self, table_name: &TableName, columns: usize) -> Result<()> {
+        if let Some(max) = self.schema_limits.max_column_count {
+            ensure!(
+                columns <= max,
+                error::ColumnCountExceededSnafu {
+                    table: table_name.to_string(),
+                    columns,
+                    max,
+                }
+            );
+        }
+        Ok(())
+    }
+
     async fn create_table_procedure(
         &self,
         create_table: &CreateTableExpr,
@@ -16,6 +16,9 @@ use auth::UserProviderRef;
 use common_base::Plugins;
 use frontend::error::{IllegalAuthConfigSnafu, Result};
 use frontend::frontend::FrontendOptions;
+use operator::statement::SchemaLimits;
+use query::query_engine::options::{FillPolicy, QueryOptions};
+use servers::influxdb::TimeIndexOptions;
 use snafu::ResultExt;
 
 pub async fn setup_frontend_plugins(opts: &FrontendOptions) -> Result<Plugins> {
@@ -27,6 +30,54 @@ pub async fn setup_frontend_plugins(opts: &FrontendOptions) -> Result<Plugins> {
         plugins.insert::<UserProviderRef>(provider);
     }
 
+    if opts.query_max_parallelism.is_some() {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.max_parallelism = opts.query_max_parallelism;
+        plugins.insert(query_options);
+    }
+
+    if opts.deny_full_table_scan {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.deny_full_table_scan = true;
+        query_options.full_table_scan_exempt_tables = opts.full_table_scan_exempt_tables.clone();
+        plugins.insert(query_options);
+    }
+
+    if opts.default_fill_policy != FillPolicy::default() {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.default_fill_policy = opts.default_fill_policy;
+        plugins.insert(query_options);
+    }
+
+    if opts.deny_sql_writes {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.deny_sql_writes = true;
+        plugins.insert(query_options);
+    }
+
+    if opts.sql_statement_allowlist.is_some() || !opts.sql_statement_blocklist.is_empty() {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.statement_allowlist = opts.sql_statement_allowlist.clone();
+        query_options.statement_blocklist = opts.sql_statement_blocklist.clone();
+        plugins.insert(query_options);
+    }
+
+    if opts.max_table_count.is_some() || opts.max_column_count.is_some() {
+        plugins.insert(SchemaLimits {
+            max_table_count: opts.max_table_count,
+            max_column_count: opts.max_column_count,
+        });
+    }
+
+    if let Some(policy) = opts.float_special_policy {
+        plugins.insert(policy);
+    }
+
+    let time_index = opts.influxdb.time_index_options();
+    if time_index != TimeIndexOptions::default() {
+        plugins.insert(time_index);
+    }
+
     Ok(plugins)
 }
 
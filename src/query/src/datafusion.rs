@@ -30,7 +30,7 @@ use common_function::scalars::FunctionRef;
 use common_query::physical_plan::{DfPhysicalPlanAdapter, PhysicalPlan, PhysicalPlanAdapter};
 use common_query::prelude::ScalarUdf;
 use common_query::Output;
-use common_recordbatch::adapter::RecordBatchStreamAdapter;
+use common_recordbatch::adapter::{PlanMetricsStream, RecordBatchStreamAdapter};
 use common_recordbatch::{
     EmptyRecordBatchStream, RecordBatch, RecordBatches, SendableRecordBatchStream,
 };
@@ -58,6 +58,7 @@ use crate::error::{
     TableNotFoundSnafu, UnimplementedSnafu, UnsupportedExprSnafu,
 };
 use crate::executor::QueryExecutor;
+use crate::full_table_scan::check_full_table_scan;
 use crate::logical_optimizer::LogicalOptimizer;
 use crate::physical_optimizer::PhysicalOptimizer;
 use crate::physical_planner::PhysicalPlanner;
@@ -82,6 +83,11 @@ impl DatafusionQueryEngine {
         plan: LogicalPlan,
         query_ctx: QueryContextRef,
     ) -> Result<Output> {
+        if let Some(exempt_tables) = self.state.full_table_scan_exempt_tables() {
+            let LogicalPlan::DfPlan(df_plan) = &plan;
+            check_full_table_scan(df_plan, &exempt_tables)?;
+        }
+
         let mut ctx = QueryEngineContext::new(self.state.session_state(), query_ctx.clone());
 
         // `create_physical_plan` will optimize logical plan internally
@@ -393,13 +399,13 @@ impl QueryExecutor for DatafusionQueryEngine {
         let _timer = metrics::METRIC_EXEC_PLAN_ELAPSED.start_timer();
         let task_ctx = ctx.build_task_ctx();
 
-        match plan.output_partitioning().partition_count() {
-            0 => Ok(Box::pin(EmptyRecordBatchStream::new(plan.schema()))),
-            1 => Ok(plan
+        let stream: SendableRecordBatchStream = match plan.output_partitioning().partition_count() {
+            0 => return Ok(Box::pin(EmptyRecordBatchStream::new(plan.schema()))),
+            1 => plan
                 .execute(0, task_ctx)
                 .context(error::ExecutePhysicalPlanSnafu)
                 .map_err(BoxedError::new)
-                .context(QueryExecutionSnafu))?,
+                .context(QueryExecutionSnafu)?,
             _ => {
                 // merge into a single partition
                 let plan =
@@ -415,8 +421,18 @@ impl QueryExecutor for DatafusionQueryEngine {
                     .context(error::ConvertDfRecordBatchStreamSnafu)
                     .map_err(BoxedError::new)
                     .context(QueryExecutionSnafu)?;
-                Ok(Box::pin(stream))
+                Box::pin(stream)
             }
+        };
+
+        if !ctx.query_ctx().explain_analyze() {
+            return Ok(stream);
+        }
+        // The DataFusion plan executed by the adapter records the metrics of its
+        // operators, which are reported once the stream is exhausted.
+        match plan.as_any().downcast_ref::<PhysicalPlanAdapter>() {
+            Some(adapter) => Ok(Box::pin(PlanMetricsStream::new(stream, adapter.df_plan()))),
+            None => Ok(stream),
         }
     }
 }
@@ -96,6 +96,17 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Full table scan on table {} is denied, add a time range filter on the time index like `WHERE {} > now() - INTERVAL '1 hour'`",
+        table,
+        time_index
+    ))]
+    FullTableScanDenied {
+        table: String,
+        time_index: String,
+        location: Location,
+    },
+
     #[snafu(display("The SQL string has multiple statements, query: {}", query))]
     MultipleStatements { query: String, location: Location },
 
@@ -309,6 +320,7 @@ impl ErrorExt for Error {
             ParseFileFormat { source, .. } | InferSchema { source, .. } => source.status_code(),
 
             QueryAccessDenied { .. } => StatusCode::AccessDenied,
+            FullTableScanDenied { .. } => StatusCode::InvalidArguments,
             Catalog { source, .. } => source.status_code(),
             VectorComputation { source, .. } | ConvertDatafusionSchema { source, .. } => {
                 source.status_code()
@@ -0,0 +1,292 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rejects the queries that scan a table without a lower bound on its time index.
+
+use std::collections::HashSet;
+
+use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use datafusion::datasource::DefaultTableSource;
+use datafusion_common::tree_node::{TreeNode, VisitRecursion};
+use datafusion_common::{OwnedTableReference, ScalarValue};
+use datafusion_expr::expr::{Exists, InList, InSubquery};
+use datafusion_expr::utils::expr_to_columns;
+use datafusion_expr::{Between, BinaryExpr, Expr, LogicalPlan, Operator, TableScan};
+use snafu::ensure;
+use table::table::adapter::DfTableProviderAdapter;
+
+use crate::error::{FullTableScanDeniedSnafu, Result};
+
+/// Checks that every table the `plan` scans is filtered by a lower bound on its time index,
+/// like `WHERE ts > now() - INTERVAL '1 hour'`, either in the scan or in a filter above it.
+/// The tables scanned by the subqueries must be bounded by the subqueries themselves.
+///
+/// Tables of the `information_schema`, tables without a time index and the `exempt_tables`
+/// (`table` or `schema.table`) may be fully scanned. An `EXPLAIN` doesn't scan anything.
+pub(crate) fn check_full_table_scan(plan: &LogicalPlan, exempt_tables: &[String]) -> Result<()> {
+    if matches!(plan, LogicalPlan::Explain(_)) {
+        return Ok(());
+    }
+    check_plan(plan, &mut Vec::new(), exempt_tables)
+}
+
+/// A predicate of a filter above a scan, with the qualifier its columns refer to the
+/// scanned table by, `None` for the name of the table itself.
+type Predicate<'a> = (&'a Expr, Option<&'a OwnedTableReference>);
+
+fn check_plan<'a>(
+    plan: &'a LogicalPlan,
+    predicates: &mut Vec<Predicate<'a>>,
+    exempt_tables: &[String],
+) -> Result<()> {
+    check_subqueries(plan, exempt_tables)?;
+
+    match plan {
+        LogicalPlan::Filter(filter) => {
+            predicates.push((&filter.predicate, None));
+            let result = check_plan(&filter.input, predicates, exempt_tables);
+            let _ = predicates.pop();
+            result
+        }
+        LogicalPlan::SubqueryAlias(alias) => {
+            // The filters above the alias refer to the table by the alias.
+            let mut predicates = predicates
+                .iter()
+                .map(|(expr, qualifier)| (*expr, qualifier.or(Some(&alias.alias))))
+                .collect();
+            check_plan(&alias.input, &mut predicates, exempt_tables)
+        }
+        LogicalPlan::TableScan(scan) => check_scan(scan, predicates, exempt_tables),
+        _ => plan
+            .inputs()
+            .into_iter()
+            .try_for_each(|input| check_plan(input, predicates, exempt_tables)),
+    }
+}
+
+/// Checks the plans of the subqueries in the expressions of the `plan`, which aren't
+/// inputs of it. The filters of the outer query don't bound the subqueries.
+fn check_subqueries(plan: &LogicalPlan, exempt_tables: &[String]) -> Result<()> {
+    let mut result = Ok(());
+    for expr in plan.expressions() {
+        // The closure never fails.
+        let _ = expr.apply(&mut |expr| {
+            let subquery = match expr {
+                Expr::ScalarSubquery(subquery)
+                | Expr::Exists(Exists { subquery, .. })
+                | Expr::InSubquery(InSubquery { subquery, .. }) => subquery,
+                _ => return Ok(VisitRecursion::Continue),
+            };
+            result = check_plan(&subquery.subquery, &mut Vec::new(), exempt_tables);
+            Ok(if result.is_ok() {
+                VisitRecursion::Continue
+            } else {
+                VisitRecursion::Stop
+            })
+        });
+        result?;
+    }
+    Ok(())
+}
+
+fn check_scan(scan: &TableScan, predicates: &[Predicate], exempt_tables: &[String]) -> Result<()> {
+    let Some(table) = scan
+        .source
+        .as_any()
+        .downcast_ref::<DefaultTableSource>()
+        .and_then(|source| {
+            source
+                .table_provider
+                .as_any()
+                .downcast_ref::<DfTableProviderAdapter>()
+        })
+        .map(|adapter| adapter.table())
+    else {
+        return Ok(());
+    };
+
+    let table_info = table.table_info();
+    if table_info.schema_name == INFORMATION_SCHEMA_NAME
+        || exempt_tables.iter().any(|x| {
+            *x == table_info.name || *x == format!("{}.{}", table_info.schema_name, table_info.name)
+        })
+    {
+        return Ok(());
+    }
+    let schema = table.schema();
+    let Some(time_index) = schema.timestamp_column() else {
+        return Ok(());
+    };
+
+    let bounded = scan
+        .filters
+        .iter()
+        .map(|expr| (expr, &scan.table_name))
+        .chain(
+            predicates
+                .iter()
+                .map(|(expr, qualifier)| (*expr, qualifier.unwrap_or(&scan.table_name))),
+        )
+        .any(|(expr, qualifier)| {
+            has_lower_bound(
+                expr,
+                &TimeIndex {
+                    qualifier,
+                    name: &time_index.name,
+                },
+            )
+        });
+    ensure!(
+        bounded,
+        FullTableScanDeniedSnafu {
+            table: table_info.full_table_name(),
+            time_index: &time_index.name,
+        }
+    );
+    Ok(())
+}
+
+/// The time index column of a scanned table, referred to by the `qualifier`.
+struct TimeIndex<'a> {
+    qualifier: &'a OwnedTableReference,
+    name: &'a str,
+}
+
+/// Returns whether the `expr` only keeps the rows whose `time_index` is greater than a
+/// value, i.e. the value doesn't depend on any column, like `now() - INTERVAL '1 hour'`,
+/// so it's constant while scanning.
+fn has_lower_bound(expr: &Expr, time_index: &TimeIndex) -> bool {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And => {
+                has_lower_bound(left, time_index) || has_lower_bound(right, time_index)
+            }
+            Operator::Or => has_lower_bound(left, time_index) && has_lower_bound(right, time_index),
+            Operator::Gt | Operator::GtEq => is_time_index(left, time_index) && is_constant(right),
+            Operator::Lt | Operator::LtEq => is_constant(left) && is_time_index(right, time_index),
+            Operator::Eq => {
+                (is_time_index(left, time_index) && is_constant(right))
+                    || (is_constant(left) && is_time_index(right, time_index))
+            }
+            _ => false,
+        },
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            ..
+        }) => is_time_index(expr, time_index) && is_constant(low),
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => is_time_index(expr, time_index) && list.iter().all(is_constant),
+        _ => false,
+    }
+}
+
+/// Returns whether the `expr` is the `time_index` column, or a cast of it. A column
+/// qualified by another table, like the other side of a join, isn't the time index even
+/// if it has the same name.
+fn is_time_index(expr: &Expr, time_index: &TimeIndex) -> bool {
+    match expr {
+        Expr::Column(column) => {
+            column.name == time_index.name
+                && column.relation.as_ref().map_or(true, |relation| {
+                    is_same_table(relation, time_index.qualifier)
+                })
+        }
+        Expr::Cast(cast) => is_time_index(&cast.expr, time_index),
+        Expr::TryCast(cast) => is_time_index(&cast.expr, time_index),
+        _ => false,
+    }
+}
+
+/// Returns whether the `relation` and the `qualifier` refer to the same table, the parts
+/// only one of them has, like the schema of `public.m` against `m`, are ignored.
+fn is_same_table(relation: &OwnedTableReference, qualifier: &OwnedTableReference) -> bool {
+    relation.table() == qualifier.table()
+        && relation
+            .schema()
+            .zip(qualifier.schema())
+            .map_or(true, |(a, b)| a == b)
+        && relation
+            .catalog()
+            .zip(qualifier.catalog())
+            .map_or(true, |(a, b)| a == b)
+}
+
+fn is_constant(expr: &Expr) -> bool {
+    if matches!(
+        expr,
+        Expr::ScalarSubquery(_) | Expr::Literal(ScalarValue::Null)
+    ) {
+        return false;
+    }
+    let mut columns = HashSet::new();
+    expr_to_columns(expr, &mut columns).is_ok() && columns.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::DataType;
+    use datafusion_expr::{cast, col, lit, now};
+
+    use super::*;
+
+    #[test]
+    fn test_has_lower_bound() {
+        let qualifier = OwnedTableReference::partial("public", "m");
+        let time_index = TimeIndex {
+            qualifier: &qualifier,
+            name: "ts",
+        };
+
+        let bounded = [
+            col("ts").gt(lit(1)),
+            col("ts").gt_eq(now() - lit(3600)),
+            lit(1).lt(col("ts")),
+            col("ts").eq(lit(1)),
+            col("ts").between(lit(1), lit(2)),
+            col("ts").in_list(vec![lit(1), lit(2)], false),
+            cast(col("ts"), DataType::Int64).gt(lit(1)),
+            col("host").eq(lit("a")).and(col("ts").gt(lit(1))),
+            col("ts").gt(lit(1)).or(col("ts").eq(lit(0))),
+            col("m.ts").gt(lit(1)),
+            col("public.m.ts").gt(lit(1)),
+            col("greptime.public.m.ts").gt(lit(1)),
+        ];
+        for expr in bounded {
+            assert!(has_lower_bound(&expr, &time_index), "{expr}");
+        }
+
+        let unbounded = [
+            col("ts").lt(lit(1)),
+            col("ts").gt(col("other_ts")),
+            col("ts").not_between(lit(1), lit(2)),
+            col("ts").in_list(vec![lit(1), col("other_ts")], false),
+            col("ts").gt(lit(ScalarValue::Null)),
+            col("host").eq(lit("a")),
+            col("ts").gt(lit(1)).or(col("host").eq(lit("a"))),
+            col("other_ts").gt(lit(1)),
+            col("ts").not_eq(lit(1)),
+            col("n.ts").gt(lit(1)),
+            col("other.m.ts").gt(lit(1)),
+            col("m.ts").gt(lit(1)).or(col("n.ts").gt(lit(1))),
+        ];
+        for expr in unbounded {
+            assert!(!has_lower_bound(&expr, &time_index), "{expr}");
+        }
+    }
+}
@@ -19,6 +19,7 @@ pub mod datafusion;
 pub mod dist_plan;
 pub mod error;
 pub mod executor;
+mod full_table_scan;
 pub mod logical_optimizer;
 mod metrics;
 mod optimizer;
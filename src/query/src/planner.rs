@@ -79,7 +79,7 @@ impl DfLogicalPlanner {
         let result = sql_to_rel
             .statement_to_plan(df_stmt)
             .context(PlanSqlSnafu)?;
-        let plan = RangePlanRewriter::new(table_provider)
+        let plan = RangePlanRewriter::new(table_provider, self.engine_state.default_fill_policy())
             .rewrite(result)
             .await?;
         Ok(LogicalPlan::DfPlan(plan))
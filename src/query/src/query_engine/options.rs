@@ -13,14 +13,53 @@
 // limitations under the License.
 
 use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use serde::{Deserialize, Serialize};
 use session::context::QueryContextRef;
 use snafu::ensure;
+use sql::statements::statement::StatementKind;
+use strum::EnumString;
 
 use crate::error::{QueryAccessDeniedSnafu, Result};
 
 #[derive(Default, Clone)]
 pub struct QueryOptions {
     pub disallow_cross_schema_query: bool,
+    /// Max degree of parallelism (partitions) of a query, 1 executes queries serially.
+    /// Defaults to the number of CPU cores, which 0 also falls back to.
+    pub max_parallelism: Option<usize>,
+    /// Rejects the queries that scan a table without a lower bound on its time index.
+    pub deny_full_table_scan: bool,
+    /// Tables (`table` or `schema.table`) that may be fully scanned even if
+    /// `deny_full_table_scan` is set.
+    pub full_table_scan_exempt_tables: Vec<String>,
+    /// Rejects the SQL statements writing data, while the ingest protocols still write.
+    pub deny_sql_writes: bool,
+    /// Kinds of SQL statements that may be executed, all of them if not set.
+    pub statement_allowlist: Option<Vec<StatementKind>>,
+    /// Kinds of SQL statements that are rejected, even if allowed by the allowlist.
+    pub statement_blocklist: Vec<StatementKind>,
+    /// How the range queries without a `FILL` option fill the missing time buckets.
+    pub default_fill_policy: FillPolicy,
+}
+
+/// Fill strategy of the range queries without a `FILL` option, like
+/// `avg(a) RANGE '5m'`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumString)]
+#[serde(rename_all = "snake_case")]
+pub enum FillPolicy {
+    /// Leaves the missing buckets NULL.
+    #[default]
+    #[strum(serialize = "null")]
+    Null,
+    /// The value of the previous bucket.
+    #[strum(serialize = "prev")]
+    Prev,
+    /// Interpolates between the adjacent buckets, only for the numeric values.
+    #[strum(serialize = "linear")]
+    Linear,
+    /// Zero, only for the numeric values.
+    #[strum(serialize = "zero")]
+    Zero,
 }
 
 // TODO(shuiyisong): remove one method after #559 is done
@@ -42,7 +42,7 @@ use crate::dist_plan::{DistExtensionPlanner, DistPlannerAnalyzer};
 use crate::optimizer::order_hint::OrderHintRule;
 use crate::optimizer::string_normalization::StringNormalizationRule;
 use crate::optimizer::type_conversion::TypeConversionRule;
-use crate::query_engine::options::QueryOptions;
+use crate::query_engine::options::{FillPolicy, QueryOptions};
 use crate::range_select::planner::RangeSelectPlanner;
 use crate::region_query::RegionQueryHandlerRef;
 use crate::table_mutation::TableMutationHandlerRef;
@@ -77,7 +77,15 @@ impl QueryEngineState {
         plugins: Plugins,
     ) -> Self {
         let runtime_env = Arc::new(RuntimeEnv::default());
-        let session_config = SessionConfig::new().with_create_default_catalog_and_schema(false);
+        let mut session_config = SessionConfig::new().with_create_default_catalog_and_schema(false);
+        if let Some(max_parallelism) = plugins
+            .map::<QueryOptions, _, _>(|x| x.max_parallelism)
+            .flatten()
+            .filter(|x| *x > 0)
+        {
+            let target_partitions = session_config.target_partitions().min(max_parallelism);
+            session_config = session_config.with_target_partitions(target_partitions);
+        }
         // Apply the type conversion rule first.
         let mut analyzer = Analyzer::new();
         analyzer.rules.insert(0, Arc::new(TypeConversionRule));
@@ -169,6 +177,23 @@ impl QueryEngineState {
             .unwrap_or(false)
     }
 
+    /// Returns the tables exempt from the check if full table scans are denied.
+    pub(crate) fn full_table_scan_exempt_tables(&self) -> Option<Vec<String>> {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| {
+                x.deny_full_table_scan
+                    .then(|| x.full_table_scan_exempt_tables.clone())
+            })
+            .flatten()
+    }
+
+    /// Returns the fill strategy of the range queries without a `FILL` option.
+    pub(crate) fn default_fill_policy(&self) -> FillPolicy {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.default_fill_policy)
+            .unwrap_or_default()
+    }
+
     pub(crate) fn session_state(&self) -> SessionState {
         self.df_context.state()
     }
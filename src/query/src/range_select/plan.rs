@@ -55,6 +55,7 @@ use futures_util::StreamExt;
 use snafu::ResultExt;
 
 use crate::error::{DataFusionSnafu, Result};
+use crate::query_engine::options::FillPolicy;
 
 type Millisecond = <TimestampMillisecondType as ArrowPrimitiveType>::Native;
 
@@ -104,6 +105,21 @@ impl Fill {
         }
     }
 
+    /// Returns the fill of a range without the `FILL` option. `LINEAR` and zero
+    /// fall back to `NULL` for the non-numeric values, rather than failing the
+    /// queries not asking for them.
+    pub fn from_policy(policy: FillPolicy, datatype: &DataType) -> DfResult<Self> {
+        match policy {
+            FillPolicy::Null => Ok(Self::Null),
+            FillPolicy::Prev => Ok(Self::Prev),
+            FillPolicy::Linear if datatype.is_numeric() => Ok(Self::Linear),
+            FillPolicy::Zero if datatype.is_numeric() => {
+                ScalarValue::try_from_string("0".to_string(), datatype).map(Self::Const)
+            }
+            FillPolicy::Linear | FillPolicy::Zero => Ok(Self::Null),
+        }
+    }
+
     /// The input `data` contains data on a complete time series.
     /// If the filling strategy is `PREV` or `LINEAR`, caller must be ensured that the incoming `data` is ascending time order.
     pub fn apply_fill_strategy(&self, data: &mut [ScalarValue]) -> DfResult<()> {
@@ -1264,6 +1280,40 @@ mod test {
         .await;
     }
 
+    #[test]
+    fn fill_policy_test() {
+        assert_eq!(
+            Fill::Null,
+            Fill::from_policy(FillPolicy::Null, &DataType::Float64).unwrap()
+        );
+        assert_eq!(
+            Fill::Prev,
+            Fill::from_policy(FillPolicy::Prev, &DataType::Utf8).unwrap()
+        );
+        assert_eq!(
+            Fill::Linear,
+            Fill::from_policy(FillPolicy::Linear, &DataType::Int64).unwrap()
+        );
+        // Zero is a value, distinguishable from the missing buckets filled by NULL.
+        assert_eq!(
+            Fill::Const(ScalarValue::Float64(Some(0.0))),
+            Fill::from_policy(FillPolicy::Zero, &DataType::Float64).unwrap()
+        );
+        assert_eq!(
+            Fill::Const(ScalarValue::UInt8(Some(0))),
+            Fill::from_policy(FillPolicy::Zero, &DataType::UInt8).unwrap()
+        );
+        // Falls back to NULL for the non-numeric values.
+        assert_eq!(
+            Fill::Null,
+            Fill::from_policy(FillPolicy::Linear, &DataType::Boolean).unwrap()
+        );
+        assert_eq!(
+            Fill::Null,
+            Fill::from_policy(FillPolicy::Zero, &DataType::Utf8).unwrap()
+        );
+    }
+
     #[test]
     fn fill_test() {
         assert!(Fill::try_from_str("Linear", &DataType::UInt8).unwrap() == Fill::Linear);
@@ -39,6 +39,7 @@ use crate::error::{
     CatalogSnafu, DataFusionSnafu, RangeQuerySnafu, Result, TimeIndexNotFoundSnafu,
     UnknownTableSnafu,
 };
+use crate::query_engine::options::FillPolicy;
 use crate::range_select::plan::{RangeFn, RangeSelect};
 
 /// `RangeExprRewriter` will recursively search certain `Expr`, find all `range_fn` scalar udf contained in `Expr`,
@@ -51,6 +52,7 @@ pub struct RangeExprRewriter<'a> {
     /// Use `BTreeSet` to avoid in case like `avg(a) RANGE '5m' + avg(a) RANGE '5m'`, duplicate range expr `avg(a) RANGE '5m'` be calculate twice
     range_fn: BTreeSet<RangeFn>,
     sub_aggr: &'a Aggregate,
+    default_fill: FillPolicy,
 }
 
 #[inline]
@@ -128,7 +130,11 @@ impl<'a> TreeNodeRewriter for RangeExprRewriter<'a> {
                     .map_err(DataFusionError::Plan)?;
                 let mut data_type = range_expr.get_type(self.input_plan.schema())?;
                 let mut need_cast = false;
-                let fill = Fill::try_from_str(parse_str_expr(&func.args, 2)?, &data_type)?;
+                // An empty fill is the range without a `FILL` option.
+                let fill = match parse_str_expr(&func.args, 2)? {
+                    "" => Fill::from_policy(self.default_fill, &data_type)?,
+                    fill => Fill::try_from_str(fill, &data_type)?,
+                };
                 if matches!(fill, Fill::Linear) && data_type.is_integer() {
                     data_type = DataType::Float64;
                     need_cast = true;
@@ -177,11 +183,15 @@ impl<'a> TreeNodeRewriter for RangeExprRewriter<'a> {
 /// collecting info we need to generate RangeSelect Query LogicalPlan and rewrite th original LogicalPlan.
 pub struct RangePlanRewriter {
     table_provider: DfTableSourceProvider,
+    default_fill: FillPolicy,
 }
 
 impl RangePlanRewriter {
-    pub fn new(table_provider: DfTableSourceProvider) -> Self {
-        Self { table_provider }
+    pub fn new(table_provider: DfTableSourceProvider, default_fill: FillPolicy) -> Self {
+        Self {
+            table_provider,
+            default_fill,
+        }
     }
 
     pub async fn rewrite(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
@@ -224,6 +234,7 @@ impl RangePlanRewriter {
                     by: vec![],
                     range_fn: BTreeSet::new(),
                     sub_aggr: aggr_plan,
+                    default_fill: self.default_fill,
                 };
                 let new_expr = expr
                     .iter()
@@ -18,7 +18,8 @@ use catalog::memory::MemoryCatalogManager;
 use catalog::RegisterTableRequest;
 use common_base::Plugins;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, NUMBERS_TABLE_ID};
-use common_error::ext::BoxedError;
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
 use common_query::prelude::{create_udf, make_scalar_function, Volatility};
 use common_query::Output;
 use common_recordbatch::{util, RecordBatch};
@@ -26,7 +27,7 @@ use datafusion::datasource::DefaultTableSource;
 use datafusion_expr::logical_plan::builder::LogicalPlanBuilder;
 use datatypes::prelude::*;
 use datatypes::schema::{ColumnSchema, Schema};
-use datatypes::vectors::UInt32Vector;
+use datatypes::vectors::{Int64Vector, TimestampMillisecondVector, UInt32Vector};
 use session::context::QueryContext;
 use snafu::ResultExt;
 use table::table::adapter::DfTableProviderAdapter;
@@ -126,6 +127,7 @@ async fn test_query_validate() -> Result<()> {
     let plugins = Plugins::new();
     plugins.insert(QueryOptions {
         disallow_cross_schema_query: true,
+        ..Default::default()
     });
 
     let factory = QueryEngineFactory::new_with_plugins(catalog_list, None, None, false, plugins);
@@ -147,6 +149,85 @@ async fn test_query_validate() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_deny_full_table_scan() {
+    common_telemetry::init_default_ut_logging();
+    let schema = Arc::new(Schema::new(vec![
+        ColumnSchema::new("v", ConcreteDataType::int64_datatype(), false),
+        ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        )
+        .with_time_index(true),
+    ]));
+    let columns: Vec<VectorRef> = vec![
+        Arc::new(Int64Vector::from_slice([1, 2, 3])),
+        Arc::new(TimestampMillisecondVector::from_slice([1, 2, 3])),
+    ];
+    let recordbatch = RecordBatch::new(schema, columns).unwrap();
+    let table = MemTable::table("m", recordbatch);
+
+    let new_engine = |exempt_tables: Vec<String>| {
+        let plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            deny_full_table_scan: true,
+            full_table_scan_exempt_tables: exempt_tables,
+            ..Default::default()
+        });
+        let catalog_manager = MemoryCatalogManager::new_with_table(table.clone());
+        QueryEngineFactory::new_with_plugins(catalog_manager, None, None, false, plugins)
+            .query_engine()
+    };
+    let execute = |engine: crate::QueryEngineRef, sql: &str| {
+        let stmt = QueryLanguageParser::parse_sql(sql).unwrap();
+        async move {
+            let plan = engine
+                .planner()
+                .plan(stmt, QueryContext::arc())
+                .await
+                .unwrap();
+            engine.execute(plan, QueryContext::arc()).await
+        }
+    };
+
+    let engine = new_engine(vec![]);
+    for sql in [
+        "select * from m",
+        "select * from m where v > 1",
+        "select * from m where ts < 2",
+        "select * from m where ts > 1 or v > 1",
+        "select count(*) from m",
+        "select * from m a join m b on a.ts = b.ts where a.ts > 1",
+        "select * from m, m as n where m.ts > 1",
+        "select * from m where ts > 1 and v in (select v from m)",
+        "select * from m where ts > 1 and exists (select 1 from m as n where n.v = m.v)",
+        "select * from m where ts > (select max(ts) from m)",
+    ] {
+        let err = execute(engine.clone(), sql).await.unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code(), "{sql}");
+        assert!(err.to_string().contains("WHERE ts >"), "{sql}: {err}");
+    }
+    for sql in [
+        "select * from m where ts > 1",
+        "select * from m where v > 1 and ts >= '1970-01-01 00:00:00'",
+        "select * from m where ts > now() - interval '1 hour'",
+        "select * from m where ts between 1 and 2",
+        "select * from (select * from m) where ts > 1",
+        "select * from (select * from m) as t where t.ts > 1",
+        "select * from m a join m b on a.ts = b.ts where a.ts > 1 and b.ts > 1",
+        "select * from m where ts > 1 and v in (select v from m where ts > 1)",
+        "explain select * from m",
+    ] {
+        let _ = execute(engine.clone(), sql).await.unwrap();
+    }
+
+    for exempt_table in ["m", "public.m"] {
+        let engine = new_engine(vec![exempt_table.to_string()]);
+        let _ = execute(engine, "select * from m").await.unwrap();
+    }
+}
+
 #[tokio::test]
 async fn test_udf() -> Result<()> {
     common_telemetry::init_default_ut_logging();
@@ -0,0 +1,202 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Limits of the connections of the MySQL and PostgreSQL servers, so a single user
+//! can't take all the connections of a shared instance.
+//!
+//! A connection is unauthenticated until the client logs in, and counted as one of
+//! its user afterwards. The connections of both protocols share the limits.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::metrics::METRIC_REJECTED_CONNECTIONS;
+
+/// Limits of the connections, unlimited if not set.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimitOptions {
+    /// Max number of the authenticated connections of a user.
+    pub max_connections_per_user: Option<usize>,
+    /// Max number of the connections not authenticated yet. It's usually tighter
+    /// than the limit of a user, so a flood of connections that never log in can't
+    /// exhaust the server.
+    pub max_unauthenticated_connections: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    unauthenticated: usize,
+    users: HashMap<String, usize>,
+}
+
+/// Counts the connections against the [ConnectionLimitOptions].
+#[derive(Debug, Default)]
+pub struct ConnectionLimiter {
+    options: ConnectionLimitOptions,
+    counts: Mutex<Counts>,
+}
+
+pub type ConnectionLimiterRef = Arc<ConnectionLimiter>;
+
+impl ConnectionLimiter {
+    pub fn new(options: ConnectionLimitOptions) -> Self {
+        Self {
+            options,
+            counts: Mutex::default(),
+        }
+    }
+
+    /// Admits a new unauthenticated connection, returns `None` if there are too many
+    /// unauthenticated connections.
+    pub fn try_admit(self: &Arc<Self>) -> Option<ConnectionPermit> {
+        let mut counts = self.counts.lock();
+        if let Some(max) = self.options.max_unauthenticated_connections {
+            if counts.unauthenticated >= max {
+                METRIC_REJECTED_CONNECTIONS
+                    .with_label_values(&["unauthenticated"])
+                    .inc();
+                return None;
+            }
+        }
+        counts.unauthenticated += 1;
+        Some(ConnectionPermit {
+            limiter: self.clone(),
+            user: None,
+        })
+    }
+
+    /// Returns the number of the connections of `user`.
+    pub fn user_connections(&self, user: &str) -> usize {
+        self.counts.lock().users.get(user).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of the unauthenticated connections.
+    pub fn unauthenticated_connections(&self) -> usize {
+        self.counts.lock().unauthenticated
+    }
+
+    fn release(counts: &mut Counts, user: Option<&str>) {
+        match user {
+            None => counts.unauthenticated -= 1,
+            Some(user) => {
+                if let Some(count) = counts.users.get_mut(user) {
+                    *count -= 1;
+                    if *count == 0 {
+                        let _ = counts.users.remove(user);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A connection counted by the [ConnectionLimiter], released on drop.
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    limiter: ConnectionLimiterRef,
+    /// The user of the connection, `None` before the client logs in.
+    user: Option<String>,
+}
+
+impl ConnectionPermit {
+    /// Counts the connection as one of `user` once the client logs in, returns false
+    /// if the user has too many connections, then the connection is unchanged.
+    pub fn try_authenticate(&mut self, user: &str) -> bool {
+        if self.user.as_deref() == Some(user) {
+            return true;
+        }
+
+        let mut counts = self.limiter.counts.lock();
+        let count = counts.users.get(user).copied().unwrap_or(0);
+        if let Some(max) = self.limiter.options.max_connections_per_user {
+            if count >= max {
+                METRIC_REJECTED_CONNECTIONS
+                    .with_label_values(&["user"])
+                    .inc();
+                return false;
+            }
+        }
+        ConnectionLimiter::release(&mut counts, self.user.as_deref());
+        *counts.users.entry(user.to_string()).or_default() += 1;
+        self.user = Some(user.to_string());
+        true
+    }
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let mut counts = self.limiter.counts.lock();
+        ConnectionLimiter::release(&mut counts, self.user.as_deref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_limiter() {
+        let limiter = Arc::new(ConnectionLimiter::new(ConnectionLimitOptions {
+            max_connections_per_user: Some(2),
+            max_unauthenticated_connections: Some(3),
+        }));
+
+        let mut permits = (0..3)
+            .map(|_| limiter.try_admit().unwrap())
+            .collect::<Vec<_>>();
+        assert!(limiter.try_admit().is_none());
+
+        // Logged in connections leave the capacity for new connections.
+        assert!(permits[0].try_authenticate("alice"));
+        assert!(permits[1].try_authenticate("alice"));
+        assert_eq!(2, limiter.user_connections("alice"));
+        assert_eq!(1, limiter.unauthenticated_connections());
+        let mut permit = limiter.try_admit().unwrap();
+
+        // Alice is over the limit, while others can still log in.
+        assert!(!permits[2].try_authenticate("alice"));
+        assert_eq!(2, limiter.unauthenticated_connections());
+        assert!(permits[2].try_authenticate("bob"));
+        assert!(permits[2].try_authenticate("bob"));
+        assert_eq!(1, limiter.user_connections("bob"));
+
+        // Changing the user moves the connection.
+        assert!(!permit.try_authenticate("alice"));
+        drop(permits.remove(0));
+        assert!(permit.try_authenticate("alice"));
+        assert!(permit.try_authenticate("bob"));
+        assert_eq!(1, limiter.user_connections("alice"));
+        assert_eq!(2, limiter.user_connections("bob"));
+
+        drop(permits);
+        drop(permit);
+        assert_eq!(0, limiter.user_connections("alice"));
+        assert_eq!(0, limiter.user_connections("bob"));
+        assert_eq!(0, limiter.unauthenticated_connections());
+    }
+
+    #[test]
+    fn test_unlimited_connections() {
+        let limiter = Arc::new(ConnectionLimiter::default());
+        let mut permits = (0..100)
+            .map(|_| limiter.try_admit().unwrap())
+            .collect::<Vec<_>>();
+        assert!(permits
+            .iter_mut()
+            .all(|permit| permit.try_authenticate("alice")));
+        assert_eq!(100, limiter.user_connections("alice"));
+    }
+}
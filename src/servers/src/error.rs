@@ -18,7 +18,7 @@ use std::string::FromUtf8Error;
 
 use axum::http::StatusCode as HttpStatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::{http, Json};
+use axum::{http, BoxError, Json};
 use base64::DecodeError;
 use catalog;
 use common_error::ext::{BoxedError, ErrorExt};
@@ -79,6 +79,29 @@ pub enum Error {
         error: std::io::Error,
     },
 
+    #[snafu(display("Failed to bind Unix socket {}", path))]
+    UnixSocketBind {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to watch TLS cert {} and key {}", cert_path, key_path))]
+    WatchTlsFiles {
+        cert_path: String,
+        key_path: String,
+        #[snafu(source)]
+        error: notify::Error,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Invalid permissions of Unix socket: {}, expect an octal mode like 660",
+        mode
+    ))]
+    InvalidUnixSocketMode { mode: String, location: Location },
+
     #[snafu(display("Failed to convert to TcpIncoming"))]
     TcpIncoming {
         #[snafu(source)]
@@ -142,6 +165,16 @@ pub enum Error {
     #[snafu(display("Invalid query: {}", reason))]
     InvalidQuery { reason: String, location: Location },
 
+    #[snafu(display("Request body exceeds the limit of {} bytes", limit))]
+    PayloadTooLarge { limit: usize, location: Location },
+
+    #[snafu(display("Failed to read the request body"))]
+    ReadRequestBody {
+        #[snafu(source)]
+        error: BoxError,
+        location: Location,
+    },
+
     #[snafu(display("Failed to parse InfluxDB line protocol"))]
     InfluxdbLineProtocol {
         location: Location,
@@ -149,6 +182,13 @@ pub enum Error {
         error: influxdb_line_protocol::Error,
     },
 
+    #[snafu(display("Invalid time index of table {}: {}", table, msg))]
+    InvalidTimeIndex {
+        table: String,
+        msg: String,
+        location: Location,
+    },
+
     #[snafu(display("Failed to write InfluxDB line protocol"))]
     InfluxdbLinesWrite {
         location: Location,
@@ -230,6 +270,12 @@ pub enum Error {
     #[snafu(display("Tls is required for {}, plain connection is rejected", server))]
     TlsRequired { server: String },
 
+    #[snafu(display("Failed to execute connection init SQL, error: {}", err_msg))]
+    ConnectionInitSql { err_msg: String, location: Location },
+
+    #[snafu(display("Invalid PROXY protocol header, reason: {}", reason))]
+    InvalidProxyProtocolHeader { reason: String, location: Location },
+
     #[snafu(display("Failed to get user info"))]
     Auth {
         location: Location,
@@ -382,6 +428,12 @@ pub enum Error {
         actual: i32,
         location: Location,
     },
+
+    #[snafu(display("Failed to handle backup request"))]
+    HandleBackupRequest {
+        source: BoxedError,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -398,6 +450,8 @@ impl ErrorExt for Error {
             | AlreadyStarted { .. }
             | InvalidPromRemoteReadQueryResult { .. }
             | TcpBind { .. }
+            | UnixSocketBind { .. }
+            | WatchTlsFiles { .. }
             | TcpIncoming { .. }
             | CatalogError { .. }
             | GrpcReflectionService { .. }
@@ -416,7 +470,11 @@ impl ErrorExt for Error {
             NotSupported { .. }
             | InvalidParameter { .. }
             | InvalidQuery { .. }
+            | PayloadTooLarge { .. }
+            | ReadRequestBody { .. }
+            | InvalidUnixSocketMode { .. }
             | InfluxdbLineProtocol { .. }
+            | InvalidTimeIndex { .. }
             | ConnResetByPeer { .. }
             | InvalidOpentsdbLine { .. }
             | InvalidOpentsdbJsonRequest { .. }
@@ -429,7 +487,9 @@ impl ErrorExt for Error {
             | DataFrame { .. }
             | PreparedStmtTypeMismatch { .. }
             | TimePrecision { .. }
-            | IncompatibleSchema { .. } => StatusCode::InvalidArguments,
+            | IncompatibleSchema { .. }
+            | ConnectionInitSql { .. }
+            | InvalidProxyProtocolHeader { .. } => StatusCode::InvalidArguments,
 
             InfluxdbLinesWrite { source, .. }
             | PromSeriesWrite { source, .. }
@@ -475,6 +535,8 @@ impl ErrorExt for Error {
             Metrics { source } => source.status_code(),
 
             ConvertScalarValue { source, .. } => source.status_code(),
+
+            HandleBackupRequest { source, .. } => source.status_code(),
         }
     }
 
@@ -560,6 +622,7 @@ impl IntoResponse for Error {
         let error_msg = self.output_msg();
         let status = match self {
             Error::InfluxdbLineProtocol { .. }
+            | Error::InvalidTimeIndex { .. }
             | Error::InfluxdbLinesWrite { .. }
             | Error::PromSeriesWrite { .. }
             | Error::InvalidOpentsdbLine { .. }
@@ -569,7 +632,14 @@ impl IntoResponse for Error {
             | Error::DecompressPromRemoteRequest { .. }
             | Error::InvalidPromRemoteRequest { .. }
             | Error::InvalidQuery { .. }
-            | Error::TimePrecision { .. } => HttpStatusCode::BAD_REQUEST,
+            | Error::TimePrecision { .. }
+            | Error::ReadRequestBody { .. } => HttpStatusCode::BAD_REQUEST,
+            Error::PayloadTooLarge { .. } => HttpStatusCode::PAYLOAD_TOO_LARGE,
+            Error::HandleBackupRequest { ref source, .. }
+                if source.status_code() == StatusCode::RateLimited =>
+            {
+                HttpStatusCode::CONFLICT
+            }
             _ => {
                 logging::error!(self; "Failed to handle HTTP request");
 
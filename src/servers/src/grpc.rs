@@ -15,11 +15,13 @@
 mod database;
 pub mod flight;
 pub mod greptime_handler;
+mod keep_alive;
 pub mod prom_query_gateway;
 pub mod region_server;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::v1::greptime_database_server::GreptimeDatabaseServer;
 use api::v1::health_check_server::{HealthCheck, HealthCheckServer};
@@ -39,9 +41,8 @@ use common_grpc::channel_manager::{
 use common_runtime::Runtime;
 use common_telemetry::logging::info;
 use common_telemetry::{error, warn};
-use futures::FutureExt;
+use futures::{FutureExt, TryStreamExt};
 use snafu::{ensure, OptionExt, ResultExt};
-use tokio::net::TcpListener;
 use tokio::sync::oneshot::{self, Receiver, Sender};
 use tokio::sync::Mutex;
 use tonic::transport::server::TcpIncoming;
@@ -49,6 +50,7 @@ use tonic::{Request, Response, Status};
 use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
 
 use self::flight::{FlightCraftRef, FlightCraftWrapper};
+use self::keep_alive::InFlightLayer;
 use self::prom_query_gateway::PrometheusGatewayService;
 use self::region_server::{RegionServerHandlerRef, RegionServerRequestHandler};
 use crate::error::{
@@ -56,9 +58,10 @@ use crate::error::{
 };
 use crate::grpc::database::DatabaseService;
 use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::http::keep_alive::KeepAliveStream;
 use crate::prometheus_handler::PrometheusHandlerRef;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
-use crate::server::Server;
+use crate::server::{bind_tcp_listener, Server};
 
 type TonicResult<T> = std::result::Result<T, Status>;
 
@@ -90,6 +93,15 @@ pub struct GrpcServerConfig {
     pub max_recv_message_size: usize,
     // Max gRPC sending(encoding) message size
     pub max_send_message_size: usize,
+    /// Interval of the TCP keep-alive probes, disabled if not set.
+    pub tcp_keepalive: Option<Duration>,
+    /// Interval of the HTTP/2 keep-alive pings, disabled if not set.
+    pub http2_keepalive_interval: Option<Duration>,
+    /// Timeout of the acknowledgement of a HTTP/2 keep-alive ping, tonic's default if
+    /// not set.
+    pub http2_keepalive_timeout: Option<Duration>,
+    /// Idle connections are closed after this timeout, never if not set.
+    pub max_connection_idle: Option<Duration>,
 }
 
 impl Default for GrpcServerConfig {
@@ -97,6 +109,10 @@ impl Default for GrpcServerConfig {
         Self {
             max_recv_message_size: DEFAULT_MAX_GRPC_RECV_MESSAGE_SIZE.as_bytes() as usize,
             max_send_message_size: DEFAULT_MAX_GRPC_SEND_MESSAGE_SIZE.as_bytes() as usize,
+            tcp_keepalive: None,
+            http2_keepalive_interval: None,
+            http2_keepalive_timeout: None,
+            max_connection_idle: None,
         }
     }
 }
@@ -218,12 +234,12 @@ impl Server for GrpcServer {
                 AlreadyStartedSnafu { server: "gRPC" }
             );
 
-            let listener = TcpListener::bind(addr)
-                .await
-                .context(TcpBindSnafu { addr })?;
+            let listener = bind_tcp_listener(addr, "gRPC").context(TcpBindSnafu { addr })?;
             let addr = listener.local_addr().context(TcpBindSnafu { addr })?;
-            let incoming =
-                TcpIncoming::from_listener(listener, true, None).context(TcpIncomingSnafu)?;
+            let max_connection_idle = self.config.max_connection_idle.unwrap_or(Duration::ZERO);
+            let incoming = TcpIncoming::from_listener(listener, true, self.config.tcp_keepalive)
+                .context(TcpIncomingSnafu)?
+                .map_ok(move |conn| KeepAliveStream::new(conn, max_connection_idle));
             info!("gRPC server is bound to {}", addr);
 
             *shutdown_tx = Some(tx);
@@ -232,6 +248,9 @@ impl Server for GrpcServer {
         };
 
         let mut builder = tonic::transport::Server::builder()
+            .http2_keepalive_interval(self.config.http2_keepalive_interval)
+            .http2_keepalive_timeout(self.config.http2_keepalive_timeout)
+            .layer(InFlightLayer)
             .add_service(self.create_healthcheck_service())
             .add_service(self.create_reflection_service());
         if let Some(database_handler) = &self.database_handler {
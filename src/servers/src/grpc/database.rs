@@ -21,7 +21,7 @@ use common_query::Output;
 use futures::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::grpc::greptime_handler::{write_ack_level_from_metadata, GreptimeRequestHandler};
 use crate::grpc::TonicResult;
 
 pub(crate) struct DatabaseService {
@@ -40,8 +40,12 @@ impl GreptimeDatabase for DatabaseService {
         &self,
         request: Request<GreptimeRequest>,
     ) -> TonicResult<Response<GreptimeResponse>> {
+        let write_ack_level = write_ack_level_from_metadata(request.metadata())?;
         let request = request.into_inner();
-        let output = self.handler.handle_request(request).await?;
+        let output = self
+            .handler
+            .handle_request(request, write_ack_level)
+            .await?;
         let message = match output {
             Output::AffectedRows(rows) => GreptimeResponse {
                 header: Some(ResponseHeader {
@@ -65,10 +69,14 @@ impl GreptimeDatabase for DatabaseService {
     ) -> Result<Response<GreptimeResponse>, Status> {
         let mut affected_rows = 0;
 
+        let write_ack_level = write_ack_level_from_metadata(request.metadata())?;
         let mut stream = request.into_inner();
         while let Some(request) = stream.next().await {
             let request = request?;
-            let output = self.handler.handle_request(request).await?;
+            let output = self
+                .handler
+                .handle_request(request, write_ack_level)
+                .await?;
             match output {
                 Output::AffectedRows(rows) => affected_rows += rows,
                 Output::Stream(_) | Output::RecordBatches(_) => {
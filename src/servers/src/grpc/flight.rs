@@ -33,7 +33,7 @@ use tonic::{Request, Response, Status, Streaming};
 
 use crate::error;
 pub use crate::grpc::flight::stream::FlightRecordBatchStream;
-use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::grpc::greptime_handler::{write_ack_level_from_metadata, GreptimeRequestHandler};
 use crate::grpc::TonicResult;
 
 pub type TonicStream<T> = Pin<Box<dyn Stream<Item = TonicResult<T>> + Send + Sync + 'static>>;
@@ -147,6 +147,7 @@ impl FlightCraft for GreptimeRequestHandler {
         &self,
         request: Request<Ticket>,
     ) -> TonicResult<Response<TonicStream<FlightData>>> {
+        let write_ack_level = write_ack_level_from_metadata(request.metadata())?;
         let ticket = request.into_inner().ticket;
         let request =
             GreptimeRequest::decode(ticket.as_ref()).context(error::InvalidFlightTicketSnafu)?;
@@ -156,7 +157,7 @@ impl FlightCraft for GreptimeRequestHandler {
             .map(|h| h.trace_id)
             .unwrap_or_default();
 
-        let output = self.handle_request(request).await?;
+        let output = self.handle_request(request, write_ack_level).await?;
 
         let stream: Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + Sync>> =
             to_flight_data_stream(output, trace_id);
@@ -28,8 +28,10 @@ use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_runtime::Runtime;
 use common_telemetry::{logging, TRACE_ID};
-use session::context::{QueryContextBuilder, QueryContextRef};
+use session::context::{Channel, QueryContextBuilder, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
+use store_api::region_request::{WriteAckLevel, WRITE_ACK_LEVEL_KEY};
+use tonic::metadata::MetadataMap;
 
 use crate::error::Error::UnsupportedAuthScheme;
 use crate::error::{AuthSnafu, InvalidQuerySnafu, JoinTaskSnafu, NotFoundAuthHeaderSnafu, Result};
@@ -56,13 +58,19 @@ impl GreptimeRequestHandler {
         }
     }
 
-    pub(crate) async fn handle_request(&self, request: GreptimeRequest) -> Result<Output> {
+    /// Handles the `request`, acknowledging its writes at the `write_ack_level` if it's
+    /// given, or the default level of the frontend.
+    pub(crate) async fn handle_request(
+        &self,
+        request: GreptimeRequest,
+        write_ack_level: Option<WriteAckLevel>,
+    ) -> Result<Output> {
         let query = request.request.context(InvalidQuerySnafu {
             reason: "Expecting non-empty GreptimeRequest.",
         })?;
 
         let header = request.header.as_ref();
-        let query_ctx = create_query_context(header);
+        let query_ctx = create_query_context(header, write_ack_level);
         let user_info = auth(self.user_provider.clone(), header, &query_ctx).await?;
         query_ctx.set_current_user(user_info);
 
@@ -139,7 +147,29 @@ pub(crate) async fn auth(
     })
 }
 
-pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryContextRef {
+/// Parses the [WriteAckLevel] of the `x-greptime-write-ack-level` metadata, if any.
+pub(crate) fn write_ack_level_from_metadata(
+    metadata: &MetadataMap,
+) -> Result<Option<WriteAckLevel>> {
+    let Some(value) = metadata.get(WRITE_ACK_LEVEL_KEY) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map(Some)
+        .with_context(|| InvalidQuerySnafu {
+            reason: format!(
+                "Invalid {WRITE_ACK_LEVEL_KEY}: {value:?}, expecting memtable, wal or flush"
+            ),
+        })
+}
+
+pub(crate) fn create_query_context(
+    header: Option<&RequestHeader>,
+    write_ack_level: Option<WriteAckLevel>,
+) -> QueryContextRef {
     let (catalog, schema) = header
         .map(|header| {
             // We provide dbname field in newer versions of protos/sdks
@@ -163,11 +193,15 @@ pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryConte
         })
         .unwrap_or((DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME));
 
-    QueryContextBuilder::default()
+    let mut builder = QueryContextBuilder::default()
         .current_catalog(catalog.to_string())
         .current_schema(schema.to_string())
-        .try_trace_id(header.map(|h| h.trace_id))
-        .build()
+        .channel(Channel::Grpc)
+        .try_trace_id(header.map(|h| h.trace_id));
+    if let Some(write_ack_level) = write_ack_level {
+        builder = builder.write_ack_level(write_ack_level);
+    }
+    builder.build()
 }
 
 /// Histogram timer for handling gRPC request.
@@ -0,0 +1,162 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reaping of the idle gRPC connections, which tonic doesn't provide.
+//!
+//! The connections are [KeepAliveStream]s like the HTTP/1 ones, idle once no call is
+//! in flight and nothing is read or written for the `max_connection_idle`. A call is
+//! in flight until its response, possibly streamed, ends.
+//!
+//! The HTTP/2 keep-alive pings are traffic too, so the idle timeout only reaps the
+//! connections pinged less often than it, by either side. The same goes for the load
+//! balancers doing their own keep-alive, which should ping less often than the idle
+//! timeout, or close the idle connections before the server does.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::http::{HeaderMap, Request, Response};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http_body::{Body, SizeHint};
+use hyper::server::conn::AddrStream;
+use tonic::transport::server::Connected;
+use tower::{Layer, Service};
+
+use crate::http::keep_alive::{ConnectionState, InFlightGuard, KeepAliveStream};
+
+impl Connected for KeepAliveStream<AddrStream> {
+    /// The state of the connection, in the extensions of its calls.
+    type ConnectInfo = Arc<ConnectionState>;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.state()
+    }
+}
+
+/// Tracks the calls in flight of the connections.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct InFlightLayer;
+
+impl<S> Layer<S> for InFlightLayer {
+    type Service = InFlightService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InFlightService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct InFlightService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for InFlightService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<InFlightBody<ResBody>>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let guard = req
+            .extensions()
+            .get::<Arc<ConnectionState>>()
+            .map(|state| state.start_request().1);
+
+        let future = self.inner.call(req);
+        async move {
+            let response = future.await?;
+            Ok(response.map(|inner| InFlightBody {
+                inner,
+                _guard: guard,
+            }))
+        }
+        .boxed()
+    }
+}
+
+/// A response body keeping its call in flight until it's dropped.
+pub(crate) struct InFlightBody<B> {
+    inner: B,
+    _guard: Option<InFlightGuard>,
+}
+
+impl<B: Body + Unpin> Body for InFlightBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use http_body::Full;
+    use tokio::io::AsyncReadExt;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_not_reaped_until_response_ends() {
+        let (_client, server) = tokio::io::duplex(64);
+        let mut stream = KeepAliveStream::new(server, Duration::from_millis(100));
+
+        let inner = tower::service_fn(|_req: Request<()>| async {
+            Ok::<_, Infallible>(Response::new(Full::new(Bytes::from_static(b"pong"))))
+        });
+        let mut request = Request::new(());
+        let _ = request.extensions_mut().insert(stream.state());
+        let response = InFlightLayer.layer(inner).oneshot(request).await.unwrap();
+
+        // Not reaped while the response is being sent.
+        let mut buf = [0; 4];
+        let read = tokio::time::timeout(Duration::from_millis(200), stream.read(&mut buf)).await;
+        assert!(read.is_err());
+
+        drop(response);
+        assert_eq!(0, stream.read(&mut buf).await.unwrap());
+    }
+}
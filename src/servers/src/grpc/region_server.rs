@@ -21,14 +21,21 @@ use common_error::ext::ErrorExt;
 use common_runtime::Runtime;
 use common_telemetry::{debug, error, TRACE_ID};
 use snafu::{OptionExt, ResultExt};
+use store_api::region_request::WriteAckLevel;
 use tonic::{Request, Response};
 
 use crate::error::{InvalidQuerySnafu, JoinTaskSnafu, Result};
+use crate::grpc::greptime_handler::write_ack_level_from_metadata;
 use crate::grpc::TonicResult;
 
 #[async_trait]
 pub trait RegionServerHandler: Send + Sync {
-    async fn handle(&self, request: region_request::Body) -> Result<RegionResponse>;
+    /// Handles the `request`, acknowledging its writes at the `ack_level`.
+    async fn handle(
+        &self,
+        request: region_request::Body,
+        ack_level: WriteAckLevel,
+    ) -> Result<RegionResponse>;
 }
 
 pub type RegionServerHandlerRef = Arc<dyn RegionServerHandler>;
@@ -44,7 +51,11 @@ impl RegionServerRequestHandler {
         Self { handler, runtime }
     }
 
-    async fn handle(&self, request: RegionRequest) -> Result<RegionResponse> {
+    async fn handle(
+        &self,
+        request: RegionRequest,
+        ack_level: WriteAckLevel,
+    ) -> Result<RegionResponse> {
         let trace_id = request
             .header
             .context(InvalidQuerySnafu {
@@ -65,7 +76,7 @@ impl RegionServerRequestHandler {
         //     From its docs, `JoinHandle` is cancel safe. The task keeps running even it's handle been dropped.
         // 2. avoid the handler blocks the gRPC runtime incidentally.
         let handle = self.runtime.spawn(TRACE_ID.scope(trace_id, async move {
-            handler.handle(query).await.map_err(|e| {
+            handler.handle(query, ack_level).await.map_err(|e| {
                 if e.status_code().should_log_error() {
                     error!(e; "Failed to handle request");
                 } else {
@@ -86,8 +97,10 @@ impl RegionServer for RegionServerRequestHandler {
         &self,
         request: Request<RegionRequest>,
     ) -> TonicResult<Response<RegionResponse>> {
+        // The frontend sends the level of the writes by the metadata.
+        let ack_level = write_ack_level_from_metadata(request.metadata())?.unwrap_or_default();
         let request = request.into_inner();
-        let response = self.handle(request).await?;
+        let response = self.handle(request, ack_level).await?;
         Ok(Response::new(response))
     }
 }
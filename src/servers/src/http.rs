@@ -13,60 +13,86 @@
 // limitations under the License.
 
 pub mod authorize;
+pub mod backup;
+pub mod cardinality;
 pub mod handler;
 pub mod header;
 pub mod influxdb;
+pub(crate) mod keep_alive;
+pub mod logs;
 pub mod mem_prof;
 pub mod opentsdb;
 pub mod otlp;
 pub mod pprof;
 pub mod prom_store;
 pub mod prometheus;
+pub mod range;
+pub mod readiness;
 pub mod script;
 
 #[cfg(feature = "dashboard")]
 mod dashboard;
 
-use std::net::SocketAddr;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use aide::axum::{routing as apirouting, ApiRouter, IntoApiResponse};
 use aide::openapi::{Info, OpenApi, Server as OpenAPIServer};
 use async_trait::async_trait;
 use auth::UserProviderRef;
-use axum::body::BoxBody;
+use axum::body::{Body, BoxBody};
 use axum::error_handling::HandleErrorLayer;
-use axum::extract::{DefaultBodyLimit, MatchedPath};
-use axum::http::Request;
+use axum::extract::{DefaultBodyLimit, MatchedPath, State};
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::{HeaderValue, Request};
 use axum::middleware::{self, Next};
-use axum::response::{Html, IntoResponse, Json};
+use axum::response::{Html, IntoResponse, Json, Response};
 use axum::{routing, BoxError, Extension, Router};
 use common_base::readable_size::ReadableSize;
 use common_base::Plugins;
-use common_error::ext::ErrorExt;
+use common_error::ext::{error_verbosity, minimal_msg, ErrorExt, ErrorVerbosity};
 use common_error::status_code::StatusCode;
 use common_query::Output;
-use common_recordbatch::{util, RecordBatch};
+use common_recordbatch::{PlanMetrics, RecordBatch};
 use common_telemetry::logging::{self, info};
 use datatypes::data_type::DataType;
-use futures::FutureExt;
+use futures::{future, stream, FutureExt, Stream, StreamExt, TryStreamExt};
+use hyper::server::accept;
+use hyper::server::conn::AddrIncoming;
+use hyper::service::make_service_fn;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use snafu::{ensure, ResultExt};
+use snafu::{ensure, IntoError, ResultExt};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::oneshot::{self, Sender};
 use tokio::sync::Mutex;
 use tower::timeout::TimeoutLayer;
 use tower::ServiceBuilder;
 use tower_http::auth::AsyncRequireAuthorizationLayer;
+use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::trace::TraceLayer;
 
 use self::authorize::HttpAuth;
+use self::header::GREPTIME_TIMEOUT_HEADER_NAME;
 use self::influxdb::{influxdb_health, influxdb_ping, influxdb_write_v1, influxdb_write_v2};
+use self::keep_alive::{KeepAliveService, KeepAliveStream};
+use self::readiness::{Liveness, ProbeState, Readiness};
 use crate::configurator::ConfiguratorRef;
-use crate::error::{AlreadyStartedSnafu, Result, StartHttpSnafu};
+use crate::error::{
+    AlreadyStartedSnafu, PayloadTooLargeSnafu, ReadRequestBodySnafu, Result, StartHttpSnafu,
+    TcpBindSnafu,
+};
+use crate::http::prom_store::PromStoreState;
 use crate::http::prometheus::{
     format_query, instant_query, label_values_query, labels_query, range_query, series_query,
+    PrometheusApiState,
 };
 use crate::metrics::{
     HTTP_TRACK_METRICS, METRIC_HTTP_REQUESTS_ELAPSED, METRIC_HTTP_REQUESTS_TOTAL,
@@ -76,15 +102,19 @@ use crate::prometheus_handler::PrometheusHandlerRef;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::query_handler::{
-    InfluxdbLineProtocolHandlerRef, OpenTelemetryProtocolHandlerRef, OpentsdbProtocolHandlerRef,
-    PromStoreProtocolHandlerRef, ScriptHandlerRef,
+    BackupHandlerRef, CardinalityHandlerRef, InfluxdbLineProtocolHandlerRef,
+    OpenTelemetryProtocolHandlerRef, OpentsdbProtocolHandlerRef, PromStoreProtocolHandlerRef,
+    ScriptHandlerRef,
 };
-use crate::server::Server;
+use crate::server::{bind_tcp_listener, Server};
+#[cfg(unix)]
+use crate::unix_socket;
 
 pub const HTTP_API_VERSION: &str = "v1";
 pub const HTTP_API_PREFIX: &str = "/v1/";
 /// Default http body limit (64M).
 const DEFAULT_BODY_LIMIT: ReadableSize = ReadableSize::mb(64);
+const DEFAULT_METRICS_PATH: &str = "/metrics";
 
 // TODO(fys): This is a temporary workaround, it will be improved later
 pub static PUBLIC_APIS: [&str; 2] = ["/v1/influxdb/ping", "/v1/influxdb/health"];
@@ -95,15 +125,25 @@ pub struct HttpServer {
     grpc_handler: Option<ServerGrpcQueryHandlerRef>,
     options: HttpOptions,
     influxdb_handler: Option<InfluxdbLineProtocolHandlerRef>,
+    influxdb_v2_api: bool,
     opentsdb_handler: Option<OpentsdbProtocolHandlerRef>,
     prom_handler: Option<PromStoreProtocolHandlerRef>,
+    prom_native_histogram: bool,
+    prom_external_labels: Arc<BTreeMap<String, String>>,
     prometheus_handler: Option<PrometheusHandlerRef>,
     otlp_handler: Option<OpenTelemetryProtocolHandlerRef>,
     script_handler: Option<ScriptHandlerRef>,
+    backup_handler: Option<BackupHandlerRef>,
+    cardinality_handler: Option<CardinalityHandlerRef>,
+    admin_routes: AdminRoutes,
     shutdown_tx: Mutex<Option<Sender<()>>>,
     user_provider: Option<UserProviderRef>,
     metrics_handler: Option<MetricsHandler>,
     greptime_config_options: Option<String>,
+    /// Readiness reported by `/readyz`, ready as soon as the server is up if not set.
+    readiness: Option<Readiness>,
+    /// Liveness reported by `/livez`, always alive if not set.
+    liveness: Option<Liveness>,
     plugins: Plugins,
 }
 
@@ -112,26 +152,150 @@ pub struct HttpServer {
 pub struct HttpOptions {
     pub addr: String,
 
+    /// Also serves on the Unix socket at this path, removed when the server stops.
+    pub unix_socket: Option<String>,
+
+    /// Permissions of the `unix_socket` file, an octal mode like `660`. As the umask
+    /// of the process allows if not set.
+    pub unix_socket_mode: Option<String>,
+
     #[serde(with = "humantime_serde")]
     pub timeout: Duration,
 
     #[serde(skip)]
     pub disable_dashboard: bool,
 
+    /// Max size of a request body, larger requests fail with "413 Payload Too Large".
     pub body_limit: ReadableSize,
+
+    /// Body limit of the InfluxDB write APIs, `body_limit` if not set.
+    pub influxdb_body_limit: Option<ReadableSize>,
+
+    /// Body limit of the Prometheus remote write and read APIs, `body_limit` if not set.
+    pub prom_store_body_limit: Option<ReadableSize>,
+
+    /// Body limit of the OpenTSDB put API, `body_limit` if not set.
+    pub opentsdb_body_limit: Option<ReadableSize>,
+
+    /// Body limit of the OTLP APIs, `body_limit` if not set.
+    pub otlp_body_limit: Option<ReadableSize>,
+
+    /// Makes Prometheus queries on nonexistent tables or columns fail, instead of
+    /// returning an empty result like Prometheus does for unknown metrics. The SQL
+    /// and the other query APIs always fail on them, so only the Prometheus HTTP API
+    /// is affected.
+    pub fail_fast_on_missing_table: bool,
+
+    /// Whether HTTP/1 connections are kept alive to serve more requests.
+    pub keep_alive: bool,
+
+    /// Idle keep-alive connections are closed after this timeout, never if zero.
+    #[serde(with = "humantime_serde")]
+    pub keep_alive_timeout: Duration,
+
+    /// Max number of requests served by a keep-alive connection, unlimited if zero.
+    pub max_requests_per_connection: usize,
+
+    /// Whether responses carry the `timeout` in the `x-greptime-timeout-ms` header, so
+    /// clients can set their own timeouts accordingly.
+    pub response_timeout_header: bool,
+
+    /// Whether the responses with a complete content, like the dashboard assets, are
+    /// served by the `Range` requests, so clients can resume interrupted downloads.
+    /// Streamed query results are never served by ranges.
+    pub accept_ranges: bool,
+
+    /// Serves the `/admin` APIs on this address instead of `addr`.
+    pub admin_addr: Option<String>,
+
+    /// Binds the `admin_addr` listener to the loopback address, whatever host it
+    /// names. Only lifted together with `admin_allow_remote`.
+    pub admin_bind_localhost_only: bool,
+
+    /// Explicitly allows binding the `admin_addr` listener to a non-loopback address.
+    pub admin_allow_remote: bool,
+
+    /// Whether the metrics of the process are served for Prometheus to scrape.
+    pub enable_metrics: bool,
+
+    /// Path the metrics are served on, in the Prometheus text format.
+    pub metrics_path: String,
+
+    /// Whether the log lines are tailed by `/admin/logs/stream`, they are only
+    /// broadcast if the logging enables the log stream too.
+    pub enable_log_stream: bool,
 }
 
 impl Default for HttpOptions {
     fn default() -> Self {
         Self {
             addr: "127.0.0.1:4000".to_string(),
+            unix_socket: None,
+            unix_socket_mode: None,
             timeout: Duration::from_secs(30),
             disable_dashboard: false,
             body_limit: DEFAULT_BODY_LIMIT,
+            influxdb_body_limit: None,
+            prom_store_body_limit: None,
+            opentsdb_body_limit: None,
+            otlp_body_limit: None,
+            fail_fast_on_missing_table: false,
+            keep_alive: true,
+            keep_alive_timeout: Duration::ZERO,
+            max_requests_per_connection: 0,
+            response_timeout_header: false,
+            accept_ranges: false,
+            admin_addr: None,
+            admin_bind_localhost_only: true,
+            admin_allow_remote: false,
+            enable_metrics: true,
+            metrics_path: DEFAULT_METRICS_PATH.to_string(),
+            enable_log_stream: false,
         }
     }
 }
 
+impl HttpOptions {
+    /// Returns the address the admin listener binds for the `admin_addr`, which is
+    /// the loopback address of its IP family and port unless a remote access is
+    /// allowed by both `admin_allow_remote` and `admin_bind_localhost_only`.
+    pub fn admin_bind_addr(&self, admin_addr: SocketAddr) -> SocketAddr {
+        if self.admin_allow_remote && !self.admin_bind_localhost_only {
+            return admin_addr;
+        }
+        if !self.admin_bind_localhost_only {
+            logging::warn!(
+                "The admin listener still binds the loopback address, expose it with admin_allow_remote"
+            );
+        }
+        if admin_addr.ip().is_loopback() {
+            return admin_addr;
+        }
+
+        let ip = if admin_addr.is_ipv4() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        };
+        let addr = SocketAddr::new(ip, admin_addr.port());
+        info!("The admin listener binds {addr} instead of {admin_addr}, only local clients may connect");
+        addr
+    }
+}
+
+/// Which of the routes an [HttpServer] serves, so the `/admin` APIs may be served
+/// on another listener.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AdminRoutes {
+    /// All the routes, including the `/admin` APIs.
+    #[default]
+    Included,
+    /// All the routes but the `/admin` APIs.
+    Excluded,
+    /// Only the `/admin` APIs and the health check.
+    Only,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Eq, PartialEq)]
 pub struct ColumnSchema {
     name: String,
@@ -161,6 +325,32 @@ impl Schema {
 pub struct HttpRecordsOutput {
     schema: Option<Schema>,
     rows: Vec<Vec<Value>>,
+    /// Execution metrics of the query, only present if they are asked for by the
+    /// `x-greptime-explain: analyze` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metrics: Option<Vec<HttpPlanMetrics>>,
+}
+
+/// Execution metrics of one operator in the query plan.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Eq, PartialEq)]
+pub struct HttpPlanMetrics {
+    /// One line description of the operator.
+    pub plan: String,
+    /// Depth of the operator in the plan tree, the root is at level 0.
+    pub level: usize,
+    /// Metrics such as `output_rows` and `elapsed_compute`, time metrics are in
+    /// nanoseconds.
+    pub metrics: BTreeMap<String, usize>,
+}
+
+impl From<PlanMetrics> for HttpPlanMetrics {
+    fn from(metrics: PlanMetrics) -> Self {
+        HttpPlanMetrics {
+            plan: metrics.plan,
+            level: metrics.level,
+            metrics: metrics.metrics.into_iter().collect(),
+        }
+    }
 }
 
 impl HttpRecordsOutput {
@@ -182,6 +372,10 @@ impl HttpRecordsOutput {
     pub fn rows(&self) -> &Vec<Vec<Value>> {
         &self.rows
     }
+
+    pub fn metrics(&self) -> Option<&[HttpPlanMetrics]> {
+        self.metrics.as_deref()
+    }
 }
 
 impl TryFrom<Vec<RecordBatch>> for HttpRecordsOutput {
@@ -195,6 +389,7 @@ impl TryFrom<Vec<RecordBatch>> for HttpRecordsOutput {
             Ok(HttpRecordsOutput {
                 schema: None,
                 rows: vec![],
+                metrics: None,
             })
         } else {
             // safety ensured by previous empty check
@@ -228,6 +423,7 @@ impl TryFrom<Vec<RecordBatch>> for HttpRecordsOutput {
             Ok(HttpRecordsOutput {
                 schema: Some(schema),
                 rows,
+                metrics: None,
             })
         }
     }
@@ -277,6 +473,15 @@ impl JsonResponse {
 
     /// Create a json response from query result
     pub async fn from_output(outputs: Vec<Result<Output>>) -> Self {
+        Self::from_output_with_metrics(outputs, false).await
+    }
+
+    /// Create a json response from query result, including the execution metrics of
+    /// the queries if `with_metrics` is true.
+    pub async fn from_output_with_metrics(
+        outputs: Vec<Result<Output>>,
+        with_metrics: bool,
+    ) -> Self {
         // TODO(sunng87): this api response structure cannot represent error
         // well. It hides successful execution results from error response
         let mut results = Vec::with_capacity(outputs.len());
@@ -285,11 +490,21 @@ impl JsonResponse {
                 Ok(Output::AffectedRows(rows)) => {
                     results.push(JsonOutput::AffectedRows(rows));
                 }
-                Ok(Output::Stream(stream)) => {
+                Ok(Output::Stream(mut stream)) => {
                     // TODO(sunng87): streaming response
-                    match util::collect(stream).await {
+                    match stream.by_ref().try_collect::<Vec<_>>().await {
                         Ok(rows) => match HttpRecordsOutput::try_from(rows) {
-                            Ok(rows) => {
+                            Ok(mut rows) => {
+                                // Metrics are complete now that the stream is exhausted.
+                                if with_metrics {
+                                    rows.metrics = stream.metrics().map(|metrics| {
+                                        metrics
+                                            .plan_metrics
+                                            .into_iter()
+                                            .map(HttpPlanMetrics::from)
+                                            .collect()
+                                    });
+                                }
                                 results.push(JsonOutput::Records(rows));
                             }
                             Err(err) => {
@@ -375,14 +590,22 @@ impl HttpServerBuilder {
                 options,
                 opentsdb_handler: None,
                 influxdb_handler: None,
+                influxdb_v2_api: true,
                 prom_handler: None,
+                prom_native_histogram: false,
+                prom_external_labels: Arc::default(),
                 prometheus_handler: None,
                 otlp_handler: None,
                 user_provider: None,
                 script_handler: None,
+                backup_handler: None,
+                cardinality_handler: None,
+                admin_routes: AdminRoutes::default(),
                 metrics_handler: None,
                 shutdown_tx: Mutex::new(None),
                 greptime_config_options: None,
+                readiness: None,
+                liveness: None,
                 plugins: Default::default(),
             },
         }
@@ -413,11 +636,29 @@ impl HttpServerBuilder {
         self
     }
 
+    /// Serves the InfluxDB 2.x `/api/v2/write` endpoint, true by default.
+    pub fn with_influxdb_v2_api(&mut self, enable: bool) -> &mut Self {
+        self.inner.influxdb_v2_api = enable;
+        self
+    }
+
     pub fn with_prom_handler(&mut self, handler: PromStoreProtocolHandlerRef) -> &mut Self {
         let _ = self.inner.prom_handler.get_or_insert(handler);
         self
     }
 
+    /// Converts the native histograms of remote write to classic histograms.
+    pub fn with_prom_native_histogram(&mut self, enable: bool) -> &mut Self {
+        self.inner.prom_native_histogram = enable;
+        self
+    }
+
+    /// Adds the `labels` to every series of remote write.
+    pub fn with_prom_external_labels(&mut self, labels: BTreeMap<String, String>) -> &mut Self {
+        self.inner.prom_external_labels = Arc::new(labels);
+        self
+    }
+
     pub fn with_prometheus_handler(&mut self, handler: PrometheusHandlerRef) -> &mut Self {
         let _ = self.inner.prometheus_handler.get_or_insert(handler);
         self
@@ -433,6 +674,21 @@ impl HttpServerBuilder {
         self
     }
 
+    pub fn with_backup_handler(&mut self, handler: BackupHandlerRef) -> &mut Self {
+        let _ = self.inner.backup_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_cardinality_handler(&mut self, handler: CardinalityHandlerRef) -> &mut Self {
+        let _ = self.inner.cardinality_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_admin_routes(&mut self, admin_routes: AdminRoutes) -> &mut Self {
+        self.inner.admin_routes = admin_routes;
+        self
+    }
+
     pub fn with_metrics_handler(&mut self, handler: MetricsHandler) -> &mut Self {
         let _ = self.inner.metrics_handler.get_or_insert(handler);
         self
@@ -448,6 +704,16 @@ impl HttpServerBuilder {
         self
     }
 
+    pub fn with_readiness(&mut self, readiness: Readiness) -> &mut Self {
+        self.inner.readiness = Some(readiness);
+        self
+    }
+
+    pub fn with_liveness(&mut self, liveness: Liveness) -> &mut Self {
+        self.inner.liveness = Some(liveness);
+        self
+    }
+
     pub fn build(&mut self) -> HttpServer {
         std::mem::take(self).inner
     }
@@ -455,6 +721,39 @@ impl HttpServerBuilder {
 
 impl HttpServer {
     pub fn make_app(&self) -> Router {
+        let router = match self.admin_routes {
+            AdminRoutes::Included => self.make_admin_app(self.make_main_app()),
+            AdminRoutes::Excluded => self.make_main_app(),
+            AdminRoutes::Only => self
+                .make_admin_app(Router::new())
+                .route(
+                    "/health",
+                    routing::get(handler::health).post(handler::health),
+                )
+                .merge(self.route_probes()),
+        };
+        // Add a layer to collect HTTP metrics for axum.
+        router.route_layer(middleware::from_fn(track_metrics))
+    }
+
+    /// Adds the `/admin` APIs to the `router`.
+    fn make_admin_app(&self, mut router: Router) -> Router {
+        if let Some(backup_handler) = self.backup_handler.clone() {
+            router = router.nest("/admin", self.route_backup(backup_handler));
+        }
+
+        if let Some(cardinality_handler) = self.cardinality_handler.clone() {
+            router = router.nest("/admin", self.route_cardinality(cardinality_handler));
+        }
+
+        if self.options.enable_log_stream {
+            router = router.nest("/admin", self.route_logs());
+        }
+
+        router
+    }
+
+    fn make_main_app(&self) -> Router {
         let mut api = OpenApi {
             info: Info {
                 title: "GreptimeDB HTTP API".to_string(),
@@ -517,14 +816,17 @@ impl HttpServer {
             );
         }
 
-        if let Some(metrics_handler) = self.metrics_handler {
+        if let Some(metrics_handler) = self.metrics_handler.filter(|_| self.options.enable_metrics)
+        {
             router = router.nest("", self.route_metrics(metrics_handler));
         }
 
-        router = router.route(
-            "/health",
-            routing::get(handler::health).post(handler::health),
-        );
+        router = router
+            .route(
+                "/health",
+                routing::get(handler::health).post(handler::health),
+            )
+            .merge(self.route_probes());
 
         let config_router = self
             .route_config(GreptimeOptionsConfigState {
@@ -543,39 +845,47 @@ impl HttpServer {
         {
             if !self.options.disable_dashboard {
                 info!("Enable dashboard service at '/dashboard'");
-                router = router.nest("/dashboard", dashboard::dashboard());
+                let state = dashboard::DashboardState {
+                    accept_ranges: self.options.accept_ranges,
+                };
+                router = router.nest("/dashboard", dashboard::dashboard(state));
 
                 // "/dashboard" and "/dashboard/" are two different paths in Axum.
                 // We cannot nest "/dashboard/", because we already mapping "/dashboard/*x" while nesting "/dashboard".
                 // So we explicitly route "/dashboard/" here.
                 router = router.route(
                     "/dashboard/",
-                    routing::get(dashboard::static_handler).post(dashboard::static_handler),
+                    routing::get(dashboard::static_handler)
+                        .post(dashboard::static_handler)
+                        .with_state(state),
                 );
             }
         }
 
-        // Add a layer to collect HTTP metrics for axum.
-        router = router.route_layer(middleware::from_fn(track_metrics));
-
         router
     }
 
     pub fn build(&self, router: Router) -> Router {
+        // Only the routes below the timeout layer advertise the timeout.
+        let timeout_header = self
+            .options
+            .response_timeout_header
+            .then(|| HeaderValue::from(self.options.timeout.as_millis() as u64));
+
         router
             // middlewares
             .layer(
                 ServiceBuilder::new()
+                    .layer(SetResponseHeaderLayer::overriding(
+                        GREPTIME_TIMEOUT_HEADER_NAME.clone(),
+                        timeout_header,
+                    ))
                     .layer(HandleErrorLayer::new(handle_error))
                     .layer(TraceLayer::new_for_http())
                     .layer(TimeoutLayer::new(self.options.timeout))
-                    .layer(DefaultBodyLimit::max(
-                        self.options
-                            .body_limit
-                            .0
-                            .try_into()
-                            .unwrap_or_else(|_| DEFAULT_BODY_LIMIT.as_bytes() as usize),
-                    ))
+                    .layer(DefaultBodyLimit::max(body_limit_bytes(
+                        self.options.body_limit,
+                    )))
                     // custom layer
                     .layer(AsyncRequireAuthorizationLayer::new(
                         HttpAuth::<BoxBody>::new(self.user_provider.clone()),
@@ -597,11 +907,30 @@ impl HttpServer {
     }
 
     fn route_metrics<S>(&self, metrics_handler: MetricsHandler) -> Router<S> {
+        let path = &self.options.metrics_path;
+        let path = if path.starts_with('/') {
+            path.clone()
+        } else {
+            format!("/{path}")
+        };
         Router::new()
-            .route("/metrics", routing::get(handler::metrics))
+            .route(&path, routing::get(handler::metrics))
             .with_state(metrics_handler)
     }
 
+    fn route_probes<S>(&self) -> Router<S> {
+        let state = ProbeState {
+            readiness: self.readiness.clone().unwrap_or_else(Readiness::ready),
+            liveness: self.liveness.clone(),
+        };
+        Router::new()
+            .route("/livez", routing::get(readiness::livez))
+            .route("/health/live", routing::get(readiness::livez))
+            .route("/readyz", routing::get(readiness::readyz))
+            .route("/health/ready", routing::get(readiness::readyz))
+            .with_state(state)
+    }
+
     fn route_sql<S>(&self, api_state: ApiState) -> ApiRouter<S> {
         ApiRouter::new()
             .api_route(
@@ -609,6 +938,7 @@ impl HttpServer {
                 apirouting::get_with(handler::sql, handler::sql_docs)
                     .post_with(handler::sql, handler::sql_docs),
             )
+            .api_route("/sql/validate", apirouting::post(handler::validate_sql))
             .api_route(
                 "/promql",
                 apirouting::get_with(handler::promql, handler::sql_docs)
@@ -635,36 +965,94 @@ impl HttpServer {
                 "/label/:label_name/values",
                 routing::get(label_values_query),
             )
-            .with_state(prometheus_handler)
+            .with_state(PrometheusApiState {
+                prometheus_handler,
+                fail_fast_on_missing_table: self.options.fail_fast_on_missing_table,
+            })
     }
 
-    fn route_prom<S>(&self, prom_handler: PromStoreProtocolHandlerRef) -> Router<S> {
-        Router::new()
+    /// Limits the bodies of the requests to the ingest `router` to `limit`, or the
+    /// `body_limit` if not set.
+    ///
+    /// The `DefaultBodyLimit` only applies to the body extractors, while some ingest
+    /// handlers read the raw body. So the body is also read up to the limit before the
+    /// handlers.
+    fn limit_body<S>(&self, router: Router<S>, limit: Option<ReadableSize>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let limit = body_limit_bytes(limit.unwrap_or(self.options.body_limit));
+        router
+            .layer(middleware::from_fn_with_state(limit, read_limited_body))
+            .layer(DefaultBodyLimit::max(limit))
+    }
+
+    fn route_prom<S>(&self, prom_handler: PromStoreProtocolHandlerRef) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let router = Router::new()
             .route("/write", routing::post(prom_store::remote_write))
             .route("/read", routing::post(prom_store::remote_read))
-            .with_state(prom_handler)
+            .with_state(PromStoreState {
+                prom_store_handler: prom_handler,
+                native_histogram: self.prom_native_histogram,
+                external_labels: self.prom_external_labels.clone(),
+            });
+        self.limit_body(router, self.options.prom_store_body_limit)
     }
 
-    fn route_influxdb<S>(&self, influxdb_handler: InfluxdbLineProtocolHandlerRef) -> Router<S> {
-        Router::new()
-            .route("/write", routing::post(influxdb_write_v1))
-            .route("/api/v2/write", routing::post(influxdb_write_v2))
+    fn route_influxdb<S>(&self, influxdb_handler: InfluxdbLineProtocolHandlerRef) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let mut router = Router::new().route("/write", routing::post(influxdb_write_v1));
+        if self.influxdb_v2_api {
+            router = router.route("/api/v2/write", routing::post(influxdb_write_v2));
+        }
+        let router = router
             .route("/ping", routing::get(influxdb_ping))
             .route("/health", routing::get(influxdb_health))
-            .with_state(influxdb_handler)
+            .with_state(influxdb_handler);
+        self.limit_body(router, self.options.influxdb_body_limit)
     }
 
-    fn route_opentsdb<S>(&self, opentsdb_handler: OpentsdbProtocolHandlerRef) -> Router<S> {
-        Router::new()
+    fn route_opentsdb<S>(&self, opentsdb_handler: OpentsdbProtocolHandlerRef) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let router = Router::new()
             .route("/api/put", routing::post(opentsdb::put))
-            .with_state(opentsdb_handler)
+            .with_state(opentsdb_handler);
+        self.limit_body(router, self.options.opentsdb_body_limit)
     }
 
-    fn route_otlp<S>(&self, otlp_handler: OpenTelemetryProtocolHandlerRef) -> Router<S> {
-        Router::new()
+    fn route_otlp<S>(&self, otlp_handler: OpenTelemetryProtocolHandlerRef) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let router = Router::new()
             .route("/v1/metrics", routing::post(otlp::metrics))
             .route("/v1/traces", routing::post(otlp::traces))
-            .with_state(otlp_handler)
+            .with_state(otlp_handler);
+        self.limit_body(router, self.options.otlp_body_limit)
+    }
+
+    fn route_backup<S>(&self, backup_handler: BackupHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/backup", routing::post(backup::start_backup))
+            .route("/backup/:id", routing::get(backup::backup_status))
+            .with_state(backup_handler)
+    }
+
+    fn route_cardinality<S>(&self, cardinality_handler: CardinalityHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/cardinality", routing::get(cardinality::cardinality))
+            .with_state(cardinality_handler)
+    }
+
+    fn route_logs<S>(&self) -> Router<S> {
+        Router::new().route("/logs/stream", routing::get(logs::stream_logs))
     }
 
     fn route_config<S>(&self, state: GreptimeOptionsConfigState) -> ApiRouter<S> {
@@ -676,6 +1064,43 @@ impl HttpServer {
 
 /// A middleware to record metrics for HTTP.
 // Based on https://github.com/tokio-rs/axum/blob/axum-v0.6.16/examples/prometheus-metrics/src/main.rs
+/// Converts the `limit` to bytes, the default limit if it doesn't fit in `usize`.
+fn body_limit_bytes(limit: ReadableSize) -> usize {
+    limit
+        .0
+        .try_into()
+        .unwrap_or_else(|_| DEFAULT_BODY_LIMIT.as_bytes() as usize)
+}
+
+/// Reads the request body up to `limit` bytes before the handler, fails with
+/// "413 Payload Too Large" once the body, or its `Content-Length`, exceeds the limit.
+async fn read_limited_body(
+    State(limit): State<usize>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if content_length.is_some_and(|len| len > limit as u64) {
+        return PayloadTooLargeSnafu { limit }.build().into_response();
+    }
+
+    let (parts, body) = req.into_parts();
+    match hyper::body::to_bytes(http_body::Limited::new(body, limit)).await {
+        Ok(bytes) => {
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        Err(e) if e.is::<http_body::LengthLimitError>() => {
+            PayloadTooLargeSnafu { limit }.build().into_response()
+        }
+        Err(e) => ReadRequestBodySnafu.into_error(e).into_response(),
+    }
+}
+
 pub(crate) async fn track_metrics<B>(req: Request<B>, next: Next<B>) -> impl IntoResponse {
     let _timer = HTTP_TRACK_METRICS
         .with_label_values(&["value"])
@@ -704,6 +1129,7 @@ pub(crate) async fn track_metrics<B>(req: Request<B>, next: Next<B>) -> impl Int
 }
 
 pub const HTTP_SERVER: &str = "HTTP_SERVER";
+pub const HTTP_ADMIN_SERVER: &str = "HTTP_ADMIN_SERVER";
 
 #[async_trait]
 impl Server for HttpServer {
@@ -720,8 +1146,29 @@ impl Server for HttpServer {
     }
 
     async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
+        // The socket file is removed once the server stops, or fails to start.
+        #[cfg(unix)]
+        let unix_incoming = self
+            .options
+            .unix_socket
+            .as_deref()
+            .map(|path| {
+                unix_socket::bind_unix_listener(
+                    path,
+                    self.options.unix_socket_mode.as_deref(),
+                    "HTTP",
+                )
+            })
+            .transpose()?;
+        #[cfg(not(unix))]
+        if self.options.unix_socket.is_some() {
+            logging::warn!(
+                "Unix sockets aren't supported on this platform, http.unix_socket is ignored"
+            );
+        }
+
         let (tx, rx) = oneshot::channel();
-        let server = {
+        let (app, incoming, listening) = {
             let mut shutdown_tx = self.shutdown_tx.lock().await;
             ensure!(
                 shutdown_tx.is_none(),
@@ -733,36 +1180,81 @@ impl Server for HttpServer {
                 app = configurator.config_http(app);
             }
             let app = self.build(app);
-            let server = axum::Server::bind(&listening)
-                .tcp_nodelay(true)
-                .serve(app.into_make_service());
+
+            let listener =
+                bind_tcp_listener(listening, "HTTP").context(TcpBindSnafu { addr: listening })?;
+            let mut incoming = AddrIncoming::from_listener(listener).context(StartHttpSnafu)?;
+            incoming.set_nodelay(true);
+            let listening = incoming.local_addr();
+            let incoming = stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx));
 
             *shutdown_tx = Some(tx);
 
-            server
+            (app, incoming, listening)
         };
-        let listening = server.local_addr();
         info!("HTTP server is bound to {}", listening);
 
-        let graceful = server.with_graceful_shutdown(rx.map(drop));
-        graceful.await.context(StartHttpSnafu)?;
+        let shutdown = rx.map(drop).shared();
+        let server = serve_connections(app.clone(), incoming, &self.options, shutdown.clone());
+        #[cfg(unix)]
+        if let Some((unix_incoming, _guard)) = unix_incoming {
+            let unix_server = serve_connections(app, unix_incoming, &self.options, shutdown);
+            let _ = future::try_join(server, unix_server)
+                .await
+                .context(StartHttpSnafu)?;
+            return Ok(listening);
+        }
+        server.await.context(StartHttpSnafu)?;
 
         Ok(listening)
     }
 
     fn name(&self) -> &str {
-        HTTP_SERVER
+        match self.admin_routes {
+            AdminRoutes::Only => HTTP_ADMIN_SERVER,
+            AdminRoutes::Included | AdminRoutes::Excluded => HTTP_SERVER,
+        }
     }
 }
 
+/// Serves the `app` on the connections of `incoming` until `shutdown`, with the
+/// keep-alive tuning of the `options`.
+async fn serve_connections<IO>(
+    app: Router,
+    incoming: impl Stream<Item = io::Result<IO>> + Send,
+    options: &HttpOptions,
+    shutdown: impl Future<Output = ()>,
+) -> hyper::Result<()>
+where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let keep_alive_timeout = options.keep_alive_timeout;
+    let max_requests = options.max_requests_per_connection;
+    let incoming = accept::from_stream(
+        incoming.map_ok(move |conn| KeepAliveStream::new(conn, keep_alive_timeout)),
+    );
+    let make_service = make_service_fn(move |conn: &KeepAliveStream<IO>| {
+        let service =
+            KeepAliveService::new(app.clone(), conn.state(), keep_alive_timeout, max_requests);
+        future::ready(Ok::<_, Infallible>(service))
+    });
+    hyper::Server::builder(incoming)
+        .http1_keepalive(options.keep_alive)
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
 /// handle error middleware
 async fn handle_error(err: BoxError) -> Json<JsonResponse> {
     logging::error!("Unhandled internal error: {}", err);
 
-    Json(JsonResponse::with_error(
-        format!("Unhandled internal error: {err}"),
-        StatusCode::Unexpected,
-    ))
+    let status_code = StatusCode::Unexpected;
+    let error_msg = match error_verbosity() {
+        ErrorVerbosity::Minimal => minimal_msg(status_code),
+        _ => format!("Unhandled internal error: {err}"),
+    };
+    Json(JsonResponse::with_error(error_msg, status_code))
 }
 
 #[cfg(test)]
@@ -876,7 +1368,126 @@ mod test {
     fn test_http_options_default() {
         let default = HttpOptions::default();
         assert_eq!("127.0.0.1:4000".to_string(), default.addr);
-        assert_eq!(Duration::from_secs(30), default.timeout)
+        assert_eq!(Duration::from_secs(30), default.timeout);
+        assert!(default.enable_metrics);
+        assert_eq!("/metrics", default.metrics_path);
+    }
+
+    #[test]
+    fn test_admin_bind_addr() {
+        let default = HttpOptions::default();
+        assert!(default.admin_bind_localhost_only);
+        assert_eq!(
+            "127.0.0.1:4010".parse::<SocketAddr>().unwrap(),
+            default.admin_bind_addr("0.0.0.0:4010".parse().unwrap())
+        );
+        assert_eq!(
+            "[::1]:4010".parse::<SocketAddr>().unwrap(),
+            default.admin_bind_addr("[::]:4010".parse().unwrap())
+        );
+
+        // The remote access must be allowed explicitly.
+        let opts = HttpOptions {
+            admin_bind_localhost_only: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            "127.0.0.1:4010".parse::<SocketAddr>().unwrap(),
+            opts.admin_bind_addr("10.0.0.1:4010".parse().unwrap())
+        );
+        let opts = HttpOptions {
+            admin_allow_remote: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            "127.0.0.1:4010".parse::<SocketAddr>().unwrap(),
+            opts.admin_bind_addr("10.0.0.1:4010".parse().unwrap())
+        );
+        let opts = HttpOptions {
+            admin_bind_localhost_only: false,
+            admin_allow_remote: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            "10.0.0.1:4010".parse::<SocketAddr>().unwrap(),
+            opts.admin_bind_addr("10.0.0.1:4010".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes() {
+        let new_client = |admin_routes| {
+            let options = HttpOptions {
+                enable_log_stream: true,
+                ..Default::default()
+            };
+            let server = HttpServerBuilder::new(options)
+                .with_admin_routes(admin_routes)
+                .build();
+            TestClient::new(server.build(server.make_app()))
+        };
+
+        let client = new_client(AdminRoutes::Excluded);
+        let res = client.get("/admin/logs/stream").send().await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let res = client.get("/status").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let client = new_client(AdminRoutes::Only);
+        let res = client.get("/admin/logs/stream").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = client.get("/health").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = client.get("/status").send().await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_probes() {
+        let server = HttpServerBuilder::new(HttpOptions::default()).build();
+        let client = TestClient::new(server.build(server.make_app()));
+        assert_eq!(StatusCode::OK, client.get("/readyz").send().await.status());
+
+        let readiness = Readiness::new();
+        let server = HttpServerBuilder::new(HttpOptions::default())
+            .with_readiness(readiness.clone())
+            .build();
+        let client = TestClient::new(server.build(server.make_app()));
+        assert_eq!(StatusCode::OK, client.get("/livez").send().await.status());
+        let res = client.get("/readyz").send().await;
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
+        assert_eq!(
+            r#"{"phase":"starting","dependencies_available":true}"#,
+            res.text().await
+        );
+
+        readiness.advance(readiness::StartupPhase::Ready);
+        assert_eq!(StatusCode::OK, client.get("/readyz").send().await.status());
+        readiness.set_dependencies_available(false);
+        let res = client.get("/health/ready").send().await;
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
+        assert_eq!(
+            StatusCode::OK,
+            client.get("/health/live").send().await.status()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_liveness_probe() {
+        let liveness = Liveness::new(Duration::from_millis(50));
+        liveness.beat();
+        let server = HttpServerBuilder::new(HttpOptions::default())
+            .with_liveness(liveness.clone())
+            .build();
+        let client = TestClient::new(server.build(server.make_app()));
+        assert_eq!(
+            StatusCode::OK,
+            client.get("/health/live").send().await.status()
+        );
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let res = client.get("/livez").send().await;
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
     }
 
     #[tokio::test]
@@ -888,6 +1499,106 @@ mod test {
         assert_eq!(res.status(), StatusCode::REQUEST_TIMEOUT);
     }
 
+    #[tokio::test]
+    async fn test_response_timeout_header() {
+        let server = HttpServerBuilder::new(HttpOptions::default()).build();
+        let client = TestClient::new(server.build(server.make_app()));
+        let res = client.get("/health").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(&GREPTIME_TIMEOUT_HEADER_NAME).is_none());
+
+        let server = HttpServerBuilder::new(HttpOptions {
+            timeout: Duration::from_secs(10),
+            response_timeout_header: true,
+            ..Default::default()
+        })
+        .build();
+        let client = TestClient::new(server.build(server.make_app()));
+        let res = client.get("/health").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            "10000",
+            res.headers().get(&GREPTIME_TIMEOUT_HEADER_NAME).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_route() {
+        let mut builder = HttpServerBuilder::new(HttpOptions::default());
+        let server = builder.with_metrics_handler(MetricsHandler).build();
+        let client = TestClient::new(server.build(server.make_app()));
+        let res = client.get("/metrics").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut builder = HttpServerBuilder::new(HttpOptions {
+            metrics_path: "internal/metrics".to_string(),
+            ..Default::default()
+        });
+        let server = builder.with_metrics_handler(MetricsHandler).build();
+        let client = TestClient::new(server.build(server.make_app()));
+        assert_eq!(
+            client.get("/internal/metrics").send().await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            client.get("/metrics").send().await.status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let mut builder = HttpServerBuilder::new(HttpOptions {
+            enable_metrics: false,
+            ..Default::default()
+        });
+        let server = builder.with_metrics_handler(MetricsHandler).build();
+        let client = TestClient::new(server.build(server.make_app()));
+        assert_eq!(
+            client.get("/metrics").send().await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_unix_socket() {
+        use common_test_util::temp_dir::create_temp_dir;
+        use hyper::Body;
+        use tokio::net::UnixStream;
+
+        let dir = create_temp_dir("test_serve_unix_socket");
+        let path = dir.path().join("http.sock");
+        let server = Arc::new(
+            HttpServerBuilder::new(HttpOptions {
+                unix_socket: Some(path.to_str().unwrap().to_string()),
+                unix_socket_mode: Some("600".to_string()),
+                ..Default::default()
+            })
+            .build(),
+        );
+        let handle = tokio::spawn({
+            let server = server.clone();
+            async move { server.start("127.0.0.1:0".parse().unwrap()).await }
+        });
+
+        let stream = loop {
+            if let Ok(stream) = UnixStream::connect(&path).await {
+                break stream;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        let (mut sender, conn) = hyper::client::conn::handshake(stream).await.unwrap();
+        let _conn = tokio::spawn(conn);
+        let request = axum::http::Request::get("/health")
+            .body(Body::empty())
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        drop(sender);
+
+        server.shutdown().await.unwrap();
+        handle.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+
     #[tokio::test]
     async fn test_recordbatches_conversion() {
         let column_schemas = vec![
@@ -27,11 +27,12 @@ use futures::future::BoxFuture;
 use headers::Header;
 use http_body::Body;
 use secrecy::SecretString;
-use session::context::QueryContext;
+use session::context::{Channel, QueryContextBuilder};
 use snafu::{ensure, OptionExt, ResultExt};
+use store_api::region_request::WriteAckLevel;
 use tower_http::auth::AsyncAuthorizeRequest;
 
-use super::header::GreptimeDbName;
+use super::header::{is_explain_analyze, GreptimeDbName, GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME};
 use super::PUBLIC_APIS;
 use crate::error::{
     self, InvalidAuthorizationHeaderSnafu, InvalidParameterSnafu, InvisibleASCIISnafu,
@@ -75,7 +76,22 @@ where
         let user_provider = self.user_provider.clone();
         Box::pin(async move {
             let (catalog, schema) = extract_catalog_and_schema(&request);
-            let query_ctx = QueryContext::with(catalog, schema);
+            let mut query_ctx_builder = QueryContextBuilder::default()
+                .current_catalog(catalog.to_string())
+                .current_schema(schema.to_string())
+                .channel(Channel::Http)
+                .explain_analyze(is_explain_analyze(request.headers()));
+            match extract_write_ack_level(&request) {
+                Ok(Some(write_ack_level)) => {
+                    query_ctx_builder = query_ctx_builder.write_ack_level(write_ack_level);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("extract write ack level failed: {}", e);
+                    return Err(bad_request_resp());
+                }
+            }
+            let query_ctx = query_ctx_builder.build();
             let need_auth = need_auth(&request);
 
             let user_provider = if let Some(user_provider) = user_provider.filter(|_| need_auth) {
@@ -132,7 +148,12 @@ fn extract_catalog_and_schema<B: Send + Sync + 'static>(request: &Request<B>) ->
         .and_then(|header| header.to_str().ok())
         .or_else(|| {
             let query = request.uri().query().unwrap_or_default();
-            extract_db_from_query(query)
+            if is_influxdb_v2_path(request.uri().path()) {
+                // The org of InfluxDB 2.x is ignored, a bucket is a database.
+                extract_bucket_from_query(query)
+            } else {
+                extract_db_from_query(query)
+            }
         })
         .unwrap_or(DEFAULT_SCHEMA_NAME);
 
@@ -195,6 +216,35 @@ fn extract_username_and_password<B: Send + Sync + 'static>(
     })
 }
 
+/// Parses the [WriteAckLevel] of the `x-greptime-write-ack-level` header, if any.
+fn extract_write_ack_level<B: Send + Sync + 'static>(
+    request: &Request<B>,
+) -> Result<Option<WriteAckLevel>> {
+    let Some(value) = request.headers().get(&GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME) else {
+        return Ok(None);
+    };
+    let value = value.to_str().context(InvisibleASCIISnafu)?;
+    value
+        .trim()
+        .parse()
+        .map(Some)
+        .ok()
+        .context(InvalidParameterSnafu {
+            reason: format!(
+                "invalid {GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME}: {value}, expecting memtable, wal or flush"
+            ),
+        })
+}
+
+fn bad_request_resp<RespBody>() -> Response<RespBody>
+where
+    RespBody: Body + Default,
+{
+    let mut res = Response::new(RespBody::default());
+    *res.status_mut() = StatusCode::BAD_REQUEST;
+    res
+}
+
 fn unauthorized_resp<RespBody>() -> Response<RespBody>
 where
     RespBody: Body + Default,
@@ -270,10 +320,22 @@ fn need_auth<B>(req: &Request<B>) -> bool {
     path.starts_with(HTTP_API_PREFIX)
 }
 
+fn is_influxdb_v2_path(path: &str) -> bool {
+    path.contains("influxdb") && path.ends_with("/api/v2/write")
+}
+
 fn extract_db_from_query(query: &str) -> Option<&str> {
+    extract_param_from_query(query, "db=")
+}
+
+fn extract_bucket_from_query(query: &str) -> Option<&str> {
+    extract_param_from_query(query, "bucket=")
+}
+
+fn extract_param_from_query<'a>(query: &'a str, prefix: &str) -> Option<&'a str> {
     for pair in query.split('&') {
-        if let Some(db) = pair.strip_prefix("db=") {
-            return if db.is_empty() { None } else { Some(db) };
+        if let Some(value) = pair.strip_prefix(prefix) {
+            return if value.is_empty() { None } else { Some(value) };
         }
     }
     None
@@ -325,6 +387,30 @@ mod tests {
         assert!(need_auth(&req));
     }
 
+    #[test]
+    fn test_extract_write_ack_level() {
+        let req = Request::builder().body(()).unwrap();
+        assert_eq!(None, extract_write_ack_level(&req).unwrap());
+
+        let req = Request::builder()
+            .header(&GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME, "wal")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            Some(WriteAckLevel::Wal),
+            extract_write_ack_level(&req).unwrap()
+        );
+
+        let req = Request::builder()
+            .header(&GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME, "disk")
+            .body(())
+            .unwrap();
+        assert_matches!(
+            extract_write_ack_level(&req),
+            Err(error::Error::InvalidParameter { .. })
+        );
+    }
+
     #[test]
     fn test_decode_basic() {
         // base64encode("username:password") == "dXNlcm5hbWU6cGFzc3dvcmQ="
@@ -415,6 +501,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_bucket() {
+        assert!(is_influxdb_v2_path("/v1/influxdb/api/v2/write"));
+        assert!(!is_influxdb_v2_path("/v1/influxdb/write"));
+        assert_matches!(extract_bucket_from_query("org=o&bucket="), None);
+        assert_matches!(extract_bucket_from_query("db=foo"), None);
+        assert_matches!(
+            extract_bucket_from_query("org=o&bucket=foo&precision=ms"),
+            Some("foo")
+        );
+    }
+
     #[test]
     fn test_extract_user() {
         assert_matches!(extract_influxdb_user_from_query(""), (None, None));
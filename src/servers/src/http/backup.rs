@@ -0,0 +1,83 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admin APIs to trigger a backup and poll its status.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode as HttpStatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::query_handler::BackupHandlerRef;
+
+/// State of a backup.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupState {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Status of a backup, returned by the backup APIs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BackupStatus {
+    pub id: String,
+    pub state: BackupState,
+    /// Directory of the backup in the backup destination.
+    pub location: String,
+    pub total_regions: usize,
+    pub finished_regions: usize,
+    /// Number of SST files copied so far.
+    pub files: usize,
+    /// Number of bytes copied so far.
+    pub bytes: u64,
+    pub start_time_ms: i64,
+    /// The single point in time the backup is taken at. Writes acknowledged before it
+    /// are in the backup of every region. Set once all the regions are flushed.
+    pub snapshot_time_ms: Option<i64>,
+    pub end_time_ms: Option<i64>,
+    /// Error message if the backup failed.
+    pub error: Option<String>,
+}
+
+/// Handler of `POST /admin/backup`, which starts a backup in the background.
+#[axum_macros::debug_handler]
+pub async fn start_backup(State(handler): State<BackupHandlerRef>) -> Response {
+    match handler.start_backup().await {
+        Ok(status) => (HttpStatusCode::ACCEPTED, Json(status)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Handler of `GET /admin/backup/{id}`, which returns the status of the backup.
+#[axum_macros::debug_handler]
+pub async fn backup_status(
+    State(handler): State<BackupHandlerRef>,
+    Path(id): Path<String>,
+) -> Response {
+    match handler.backup_status(&id).await {
+        Ok(Some(status)) => Json(status).into_response(),
+        Ok(None) => (
+            HttpStatusCode::NOT_FOUND,
+            Json(json!({
+                "error": format!("Backup not found: {id}"),
+            })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
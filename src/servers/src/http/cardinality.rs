@@ -0,0 +1,45 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admin API to inspect the estimated cardinality of tag columns.
+
+use axum::extract::State;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::query_handler::CardinalityHandlerRef;
+
+/// Estimated number of distinct values of a tag column.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ColumnCardinality {
+    /// Full name of the table, `catalog.schema.table`.
+    pub table: String,
+    pub column: String,
+    pub estimate: u64,
+}
+
+/// Handler of `GET /admin/cardinality`, which returns the estimates of all tracked
+/// tag columns, highest first.
+#[axum_macros::debug_handler]
+pub async fn cardinality(
+    State(handler): State<CardinalityHandlerRef>,
+) -> Json<Vec<ColumnCardinality>> {
+    let mut columns = handler.cardinality();
+    columns.sort_unstable_by(|a, b| {
+        b.estimate
+            .cmp(&a.estimate)
+            .then_with(|| (&a.table, &a.column).cmp(&(&b.table, &b.column)))
+    });
+    Json(columns)
+}
@@ -13,7 +13,8 @@
 // limitations under the License.
 
 use axum::body::{boxed, Full};
-use axum::http::{header, StatusCode, Uri};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode, Uri};
 use axum::response::Response;
 use axum::routing;
 use axum::routing::Router;
@@ -22,19 +23,31 @@ use rust_embed::RustEmbed;
 use snafu::ResultExt;
 
 use crate::error::{BuildHttpResponseSnafu, Result};
+use crate::http::range::range_response;
 
 #[derive(RustEmbed)]
 #[folder = "dashboard/dist/"]
 pub struct Assets;
 
-pub(crate) fn dashboard() -> Router {
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DashboardState {
+    /// Whether the assets are served by the `Range` requests.
+    pub(crate) accept_ranges: bool,
+}
+
+pub(crate) fn dashboard(state: DashboardState) -> Router {
     Router::new()
         .route("/", routing::get(static_handler).post(static_handler))
         .route("/*x", routing::get(static_handler).post(static_handler))
+        .with_state(state)
 }
 
 #[axum_macros::debug_handler]
-pub async fn static_handler(uri: Uri) -> Result<Response> {
+pub async fn static_handler(
+    State(state): State<DashboardState>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response> {
     debug!("[dashboard] requesting: {}", uri.path());
 
     let mut path = uri.path().trim_start_matches('/');
@@ -42,7 +55,9 @@ pub async fn static_handler(uri: Uri) -> Result<Response> {
         path = "index.html";
     }
 
-    match get_assets(path) {
+    // The range of a missing asset isn't applied to the index page.
+    let range_headers = state.accept_ranges.then_some(&headers);
+    match get_assets(path, range_headers) {
         Ok(response) if response.status() == StatusCode::NOT_FOUND => index_page(),
         Ok(response) => Ok(response),
         Err(e) => Err(e),
@@ -50,18 +65,29 @@ pub async fn static_handler(uri: Uri) -> Result<Response> {
 }
 
 fn index_page() -> Result<Response> {
-    get_assets("index.html")
+    get_assets("index.html", None)
 }
 
-fn get_assets(path: &str) -> Result<Response> {
+/// Returns the asset at `path`, serving the `Range` of the `headers` if given.
+fn get_assets(path: &str, headers: Option<&HeaderMap>) -> Result<Response> {
     match Assets::get(path) {
         Some(content) => {
-            let body = boxed(Full::from(content.data));
             let mime = mime_guess::from_path(path).first_or_octet_stream();
-
-            Response::builder()
-                .header(header::CONTENT_TYPE, mime.as_ref())
-                .body(body)
+            match headers {
+                Some(headers) => {
+                    // The assets are embedded, so the hash identifies the content.
+                    let etag = format!("\"{}\"", hex::encode(content.metadata.sha256_hash()));
+                    range_response(
+                        headers,
+                        content.data.into_owned().into(),
+                        mime.as_ref(),
+                        &etag,
+                    )
+                }
+                None => Response::builder()
+                    .header(header::CONTENT_TYPE, mime.as_ref())
+                    .body(boxed(Full::from(content.data))),
+            }
         }
         None => Response::builder()
             .status(StatusCode::NOT_FOUND)
@@ -20,11 +20,13 @@ use aide::transform::TransformOperation;
 use axum::extract::{Json, Query, State};
 use axum::response::{IntoResponse, Response};
 use axum::{Extension, Form};
+use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use query::parser::PromQuery;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use session::context::QueryContextRef;
+use sql::parser::ParserContext;
 
 use crate::http::{ApiState, GreptimeOptionsConfigState, JsonResponse};
 use crate::metrics_handler::MetricsHandler;
@@ -58,7 +60,9 @@ pub async fn sql(
             return Json(resp);
         }
 
-        JsonResponse::from_output(sql_handler.do_query(sql, query_ctx).await).await
+        let explain_analyze = query_ctx.explain_analyze();
+        let outputs = sql_handler.do_query(sql, query_ctx).await;
+        JsonResponse::from_output_with_metrics(outputs, explain_analyze).await
     } else {
         JsonResponse::with_error(
             "sql parameter is required.".to_string(),
@@ -69,6 +73,90 @@ pub async fn sql(
     Json(resp.with_execution_time(start.elapsed().as_millis()))
 }
 
+/// Stage of the validation at which a statement fails.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStage {
+    /// The statement can't be parsed.
+    Syntax,
+    /// The statement is parsed but can't be planned, e.g. the table or column doesn't
+    /// exist, or the types mismatch.
+    Plan,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ValidationError {
+    pub stage: ValidationStage,
+    pub code: u32,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ValidateSqlResponse {
+    /// Number of statements parsed.
+    pub statements: usize,
+    /// Number of statements planned. Only queries, inserts and deletes are planned, the
+    /// other statements are only parsed.
+    pub planned: usize,
+    /// The first error, the statements after it are not validated.
+    pub error: Option<ValidationError>,
+}
+
+/// Handler to validate sql, which parses and plans the statements without executing them
+#[axum_macros::debug_handler]
+pub async fn validate_sql(
+    State(state): State<ApiState>,
+    Query(query_params): Query<SqlQuery>,
+    Extension(query_ctx): Extension<QueryContextRef>,
+    Form(form_params): Form<SqlQuery>,
+) -> Json<ValidateSqlResponse> {
+    let sql_handler = &state.sql_handler;
+    let Some(sql) = query_params.sql.or(form_params.sql) else {
+        return Json(ValidateSqlResponse {
+            error: Some(ValidationError {
+                stage: ValidationStage::Syntax,
+                code: StatusCode::InvalidArguments as u32,
+                error: "sql parameter is required.".to_string(),
+            }),
+            ..Default::default()
+        });
+    };
+
+    let stmts = match ParserContext::create_with_dialect(&sql, query_ctx.sql_dialect()) {
+        Ok(stmts) => stmts,
+        Err(e) => {
+            return Json(ValidateSqlResponse {
+                error: Some(ValidationError {
+                    stage: ValidationStage::Syntax,
+                    code: e.status_code() as u32,
+                    error: e.output_msg(),
+                }),
+                ..Default::default()
+            })
+        }
+    };
+
+    let mut resp = ValidateSqlResponse {
+        statements: stmts.len(),
+        ..Default::default()
+    };
+    for stmt in stmts {
+        match sql_handler.do_describe(stmt, query_ctx.clone()).await {
+            Ok(Some(_)) => resp.planned += 1,
+            Ok(None) => {}
+            Err(e) => {
+                resp.error = Some(ValidationError {
+                    stage: ValidationStage::Plan,
+                    code: e.status_code() as u32,
+                    error: e.output_msg(),
+                });
+                break;
+            }
+        }
+    }
+    Json(resp)
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PromqlQuery {
     pub query: String,
@@ -12,9 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use axum::http::HeaderMap;
 use headers::{Header, HeaderName, HeaderValue};
+use store_api::region_request::WRITE_ACK_LEVEL_KEY;
 
 pub static GREPTIME_DB_NAME_HEADER_NAME: HeaderName = HeaderName::from_static("x-greptime-db-name");
+pub static GREPTIME_EXPLAIN_HEADER_NAME: HeaderName = HeaderName::from_static("x-greptime-explain");
+/// The timeout of the request in milliseconds, advertised by the server if
+/// `response_timeout_header` is set.
+pub static GREPTIME_TIMEOUT_HEADER_NAME: HeaderName =
+    HeaderName::from_static("x-greptime-timeout-ms");
+/// Overrides when the writes of the request are acknowledged.
+pub static GREPTIME_WRITE_ACK_LEVEL_HEADER_NAME: HeaderName =
+    HeaderName::from_static(WRITE_ACK_LEVEL_KEY);
+
+/// Returns whether the client asks for the execution metrics of its queries, by
+/// sending the `x-greptime-explain: analyze` header.
+pub fn is_explain_analyze(headers: &HeaderMap) -> bool {
+    headers
+        .get(&GREPTIME_EXPLAIN_HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().eq_ignore_ascii_case("analyze"))
+        .unwrap_or(false)
+}
 
 pub struct GreptimeDbName(Option<String>);
 
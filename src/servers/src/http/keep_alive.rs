@@ -0,0 +1,324 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keep-alive tuning of the HTTP/1 connections, which hyper doesn't provide on the
+//! server side: idle connections are reaped after the keep-alive timeout, and a
+//! connection is closed after serving the max number of requests.
+//!
+//! The server advertises a keep-alive timeout a second shorter than the reaper in
+//! the `Keep-Alive` response header, so clients retire an idle connection before the
+//! server closes it, instead of reusing a connection that is being closed.
+//!
+//! The gRPC server reaps its idle HTTP/2 connections with the same [KeepAliveStream].
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::http::header::{HeaderName, HeaderValue, CONNECTION};
+use axum::http::{Request, Response, Version};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::{Instant, Sleep};
+use tower::Service;
+
+const KEEP_ALIVE: &str = "keep-alive";
+
+/// Requests served by a connection.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionState {
+    requests: AtomicUsize,
+    in_flight: AtomicUsize,
+}
+
+impl ConnectionState {
+    /// Counts a request, in flight until the returned guard is dropped. Returns the
+    /// number of requests served by the connection, including this one.
+    pub(crate) fn start_request(self: &Arc<Self>) -> (usize, InFlightGuard) {
+        let requests = self.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.in_flight.fetch_add(1, Ordering::Relaxed);
+        (requests, InFlightGuard(self.clone()))
+    }
+}
+
+/// A connection whose read side reaches EOF once no request is in flight and
+/// nothing is read or written for the keep-alive timeout, so hyper closes it.
+pub(crate) struct KeepAliveStream<T> {
+    inner: T,
+    /// The keep-alive timeout and the deadline of the idle connection.
+    idle: Option<(Duration, Pin<Box<Sleep>>)>,
+    state: Arc<ConnectionState>,
+}
+
+impl<T> KeepAliveStream<T> {
+    /// Creates a connection reaped after idle for `timeout`, or never if `timeout`
+    /// is zero.
+    pub(crate) fn new(inner: T, timeout: Duration) -> Self {
+        let idle = (!timeout.is_zero()).then(|| {
+            (
+                timeout,
+                Box::pin(tokio::time::sleep_until(Instant::now() + timeout)),
+            )
+        });
+        Self {
+            inner,
+            idle,
+            state: Arc::new(ConnectionState::default()),
+        }
+    }
+
+    pub(crate) fn state(&self) -> Arc<ConnectionState> {
+        self.state.clone()
+    }
+
+    fn reset_deadline(&mut self) {
+        if let Some((timeout, sleep)) = &mut self.idle {
+            sleep.as_mut().reset(Instant::now() + *timeout);
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for KeepAliveStream<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if let Poll::Ready(result) = Pin::new(&mut this.inner).poll_read(cx, buf) {
+            this.reset_deadline();
+            return Poll::Ready(result);
+        }
+
+        if this.state.in_flight.load(Ordering::Relaxed) > 0 {
+            // The connection isn't idle until the response is sent, hyper reads it
+            // again after that.
+            this.reset_deadline();
+            return Poll::Pending;
+        }
+        match &mut this.idle {
+            // Returns EOF without filling the buffer.
+            Some((_, sleep)) => sleep.as_mut().poll(cx).map(Ok),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for KeepAliveStream<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.reset_deadline();
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.reset_deadline();
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Tracks the requests of a connection, asks the client to close the connection
+/// after the max number of requests, and advertises the keep-alive timeout.
+#[derive(Clone)]
+pub(crate) struct KeepAliveService<S> {
+    inner: S,
+    state: Arc<ConnectionState>,
+    /// Max number of requests of the connection, unlimited if zero.
+    max_requests: usize,
+    /// The keep-alive timeout advertised to clients, in seconds.
+    advertised_timeout: Option<u64>,
+}
+
+impl<S> KeepAliveService<S> {
+    pub(crate) fn new(
+        inner: S,
+        state: Arc<ConnectionState>,
+        timeout: Duration,
+        max_requests: usize,
+    ) -> Self {
+        let advertised_timeout = Some(timeout.as_secs().saturating_sub(1)).filter(|x| *x > 0);
+        Self {
+            inner,
+            state,
+            max_requests,
+            advertised_timeout,
+        }
+    }
+
+    fn keep_alive_header(&self, requests: usize) -> Option<HeaderValue> {
+        let timeout = self.advertised_timeout.map(|x| format!("timeout={x}"));
+        let max = (self.max_requests > 0).then(|| format!("max={}", self.max_requests - requests));
+        let value = match (timeout, max) {
+            (Some(timeout), Some(max)) => format!("{timeout}, {max}"),
+            (Some(value), None) | (None, Some(value)) => value,
+            (None, None) => return None,
+        };
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+/// Decreases the in flight requests of the connection on drop, also when the
+/// request is cancelled.
+pub(crate) struct InFlightGuard(Arc<ConnectionState>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let _ = self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for KeepAliveService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let (requests, guard) = self.state.start_request();
+
+        // Connection specific headers are illegal in HTTP/2.
+        let header = if req.version() <= Version::HTTP_11 {
+            if self.max_requests > 0 && requests >= self.max_requests {
+                Some((CONNECTION, HeaderValue::from_static("close")))
+            } else {
+                self.keep_alive_header(requests)
+                    .map(|value| (HeaderName::from_static(KEEP_ALIVE), value))
+            }
+        } else {
+            None
+        };
+
+        let future = self.inner.call(req);
+        async move {
+            let mut response = future.await?;
+            drop(guard);
+            if let Some((name, value)) = header {
+                let _ = response.headers_mut().insert(name, value);
+            }
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn call<S>(service: &mut S) -> Response<()>
+    where
+        S: Service<Request<()>, Response = Response<()>, Error = Infallible>,
+    {
+        service
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(()))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_headers() {
+        let inner =
+            tower::service_fn(|_req: Request<()>| async { Ok::<_, Infallible>(Response::new(())) });
+        let state = Arc::new(ConnectionState::default());
+        let mut service =
+            KeepAliveService::new(inner.clone(), state.clone(), Duration::from_secs(10), 3);
+
+        let response = call(&mut service).await;
+        assert_eq!("timeout=9, max=2", response.headers()[KEEP_ALIVE]);
+        let response = call(&mut service).await;
+        assert_eq!("timeout=9, max=1", response.headers()[KEEP_ALIVE]);
+        let response = call(&mut service).await;
+        assert_eq!("close", response.headers()[CONNECTION]);
+        assert!(response.headers().get(KEEP_ALIVE).is_none());
+        assert_eq!(3, state.requests.load(Ordering::Relaxed));
+        assert_eq!(0, state.in_flight.load(Ordering::Relaxed));
+
+        // Nothing to advertise without limits.
+        let mut service = KeepAliveService::new(inner, Arc::default(), Duration::from_secs(1), 0);
+        let response = call(&mut service).await;
+        assert!(response.headers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reap_idle_connection() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut stream = KeepAliveStream::new(server, Duration::from_millis(100));
+        let (mut client_read, mut client_write) = tokio::io::split(client);
+
+        client_write.write_all(b"ping").await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(b"ping", &buf);
+
+        // Not reaped while a request is in flight.
+        let _ = stream.state.in_flight.fetch_add(1, Ordering::Relaxed);
+        let read = tokio::time::timeout(Duration::from_millis(200), stream.read(&mut buf)).await;
+        assert!(read.is_err());
+        let _ = stream.state.in_flight.fetch_sub(1, Ordering::Relaxed);
+
+        assert_eq!(0, stream.read(&mut buf).await.unwrap());
+
+        stream.write_all(b"pong").await.unwrap();
+        client_read.read_exact(&mut buf).await.unwrap();
+        assert_eq!(b"pong", &buf);
+    }
+
+    #[tokio::test]
+    async fn test_never_reap_without_timeout() {
+        let (_client, server) = tokio::io::duplex(64);
+        let mut stream = KeepAliveStream::new(server, Duration::ZERO);
+        let mut buf = [0; 4];
+        let read = tokio::time::timeout(Duration::from_millis(100), stream.read(&mut buf)).await;
+        assert!(read.is_err());
+    }
+}
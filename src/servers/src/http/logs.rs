@@ -0,0 +1,63 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admin API to tail the server logs.
+
+use std::convert::Infallible;
+
+use axum::extract::Query;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use common_telemetry::log_stream;
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Number of recent lines sent before the live lines by default.
+const DEFAULT_RECENT_LINES: usize = 100;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LogStreamQuery {
+    /// Number of recent lines sent before the live lines.
+    recent: Option<usize>,
+}
+
+/// Handler of `GET /admin/logs/stream`, which streams the recent and live log lines
+/// passing the log filter as server-sent events, one event per line.
+///
+/// A client reading slower than the server logs loses lines, and receives a line
+/// telling how many lines are dropped instead.
+pub async fn stream_logs(
+    Query(query): Query<LogStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (recent, receiver) = log_stream::subscribe(query.recent.unwrap_or(DEFAULT_RECENT_LINES));
+
+    let live = BroadcastStream::new(receiver).map(|line| match line {
+        Ok(line) => line,
+        Err(BroadcastStreamRecvError::Lagged(n)) => {
+            format!("... {n} lines dropped, the client is too slow").into()
+        }
+    });
+    let stream = futures::stream::iter(recent)
+        .chain(live)
+        .map(|line| Ok(log_event(&line)));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn log_event(line: &str) -> Event {
+    // A carriage return can't be sent in an event, while a line feed splits the data
+    // into lines, which are joined by the client.
+    Event::default().data(line.replace('\r', ""))
+}
@@ -91,13 +91,8 @@ pub async fn put(
         .collect::<Vec<_>>();
 
     let response = if !summary && !details {
-        if let Err(e) = opentsdb_handler.exec(data_points, ctx.clone()).await {
-            // Not debugging purpose, failed fast.
-            return error::InternalSnafu {
-                err_msg: e.to_string(),
-            }
-            .fail();
-        }
+        // Not debugging purpose, failed fast.
+        let _ = opentsdb_handler.exec(data_points, ctx.clone()).await?;
         (HttpStatusCode::NO_CONTENT, Json(OpentsdbPutResponse::Empty))
     } else {
         let mut response = OpentsdbDebuggingResponse {
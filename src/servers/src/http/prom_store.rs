@@ -12,12 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use api::prom_store::remote::{ReadRequest, WriteRequest};
 use axum::extract::{Query, RawBody, State};
 use axum::http::{header, StatusCode};
 use axum::response::IntoResponse;
 use axum::Extension;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_telemetry::warn;
 use hyper::Body;
 use prost::Message;
 use schemars::JsonSchema;
@@ -26,9 +30,21 @@ use session::context::QueryContextRef;
 use snafu::prelude::*;
 
 use crate::error::{self, Result};
-use crate::prom_store::snappy_decompress;
+use crate::prom_store::native_histogram::decode_native_histograms;
+use crate::prom_store::{inject_external_labels, snappy_decompress};
 use crate::query_handler::{PromStoreProtocolHandlerRef, PromStoreResponse};
 
+#[derive(Clone)]
+pub struct PromStoreState {
+    pub prom_store_handler: PromStoreProtocolHandlerRef,
+    /// Whether the native histograms of remote write are converted to classic
+    /// histograms, instead of being dropped.
+    pub native_histogram: bool,
+    /// Labels added to every series of remote write, see
+    /// [inject_external_labels].
+    pub external_labels: Arc<BTreeMap<String, String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DatabaseQuery {
     pub db: Option<String>,
@@ -44,19 +60,26 @@ impl Default for DatabaseQuery {
 
 #[axum_macros::debug_handler]
 pub async fn remote_write(
-    State(handler): State<PromStoreProtocolHandlerRef>,
+    State(state): State<PromStoreState>,
     Query(params): Query<DatabaseQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     RawBody(body): RawBody,
 ) -> Result<(StatusCode, ())> {
-    let request = decode_remote_write_request(body).await?;
+    let mut request = decode_remote_write_request(body, state.native_histogram).await?;
+    let overridden = inject_external_labels(&mut request, &state.external_labels);
+    if !overridden.is_empty() {
+        warn!(
+            "Labels {:?} of the remote write are overridden by the external labels",
+            overridden
+        );
+    }
     let db = params.db.clone().unwrap_or_default();
 
     let _timer = crate::metrics::METRIC_HTTP_PROM_STORE_WRITE_ELAPSED
         .with_label_values(&[db.as_str()])
         .start_timer();
 
-    handler.write(request, query_ctx).await?;
+    state.prom_store_handler.write(request, query_ctx).await?;
     Ok((StatusCode::NO_CONTENT, ()))
 }
 
@@ -75,7 +98,7 @@ impl IntoResponse for PromStoreResponse {
 
 #[axum_macros::debug_handler]
 pub async fn remote_read(
-    State(handler): State<PromStoreProtocolHandlerRef>,
+    State(state): State<PromStoreState>,
     Query(params): Query<DatabaseQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     RawBody(body): RawBody,
@@ -86,17 +109,22 @@ pub async fn remote_read(
     let _timer = crate::metrics::METRIC_HTTP_PROM_STORE_READ_ELAPSED
         .with_label_values(&[db.as_str()])
         .start_timer();
-    handler.read(request, query_ctx).await
+    state.prom_store_handler.read(request, query_ctx).await
 }
 
-async fn decode_remote_write_request(body: Body) -> Result<WriteRequest> {
+async fn decode_remote_write_request(body: Body, native_histogram: bool) -> Result<WriteRequest> {
     let body = hyper::body::to_bytes(body)
         .await
         .context(error::HyperSnafu)?;
 
     let buf = snappy_decompress(&body[..])?;
 
-    WriteRequest::decode(&buf[..]).context(error::DecodePromRemoteRequestSnafu)
+    let mut request =
+        WriteRequest::decode(&buf[..]).context(error::DecodePromRemoteRequestSnafu)?;
+    if native_histogram {
+        request.timeseries.extend(decode_native_histograms(&buf)?);
+    }
+    Ok(request)
 }
 
 async fn decode_remote_read_request(body: Body) -> Result<ReadRequest> {
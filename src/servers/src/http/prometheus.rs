@@ -46,6 +46,15 @@ use crate::error::{
 use crate::prom_store::{FIELD_COLUMN_NAME, METRIC_NAME_LABEL, TIMESTAMP_COLUMN_NAME};
 use crate::prometheus_handler::PrometheusHandlerRef;
 
+#[derive(Clone)]
+pub struct PrometheusApiState {
+    pub prometheus_handler: PrometheusHandlerRef,
+    /// Whether queries on nonexistent tables or columns fail, instead of returning
+    /// an empty result. Only the Prometheus API needs it, the other query APIs
+    /// always fail on them.
+    pub fail_fast_on_missing_table: bool,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct PromSeries {
     pub metric: HashMap<String, String>,
@@ -154,9 +163,7 @@ impl PrometheusJsonResponse {
             Ok(resp) => resp,
             Err(err) => {
                 // Prometheus won't report error if querying nonexist label and metric
-                if err.status_code() == StatusCode::TableNotFound
-                    || err.status_code() == StatusCode::TableColumnNotFound
-                {
+                if is_missing_table_or_column(&err) {
                     Self::success(PrometheusResponse::PromData(PromData {
                         result_type: result_type_string,
                         ..Default::default()
@@ -298,7 +305,7 @@ pub struct FormatQuery {
 
 #[axum_macros::debug_handler]
 pub async fn format_query(
-    State(_handler): State<PrometheusHandlerRef>,
+    State(_state): State<PrometheusApiState>,
     Query(params): Query<InstantQuery>,
     Extension(_query_ctx): Extension<QueryContextRef>,
     Form(form_params): Form<InstantQuery>,
@@ -328,12 +335,13 @@ pub struct InstantQuery {
 
 #[axum_macros::debug_handler]
 pub async fn instant_query(
-    State(handler): State<PrometheusHandlerRef>,
+    State(state): State<PrometheusApiState>,
     Query(params): Query<InstantQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     Form(form_params): Form<InstantQuery>,
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_INSTANT_QUERY_ELAPSED.start_timer();
+    let handler = &state.prometheus_handler;
     // Extract time from query string, or use current server time if not specified.
     let time = params
         .time
@@ -347,6 +355,9 @@ pub async fn instant_query(
     };
 
     let result = handler.do_query(&prom_query, query_ctx).await;
+    if let Some(resp) = fail_fast_on_missing_table(state.fail_fast_on_missing_table, &result) {
+        return resp;
+    }
     let (metric_name, result_type) = match retrieve_metric_name_and_result_type(&prom_query.query) {
         Ok((metric_name, result_type)) => (metric_name.unwrap_or_default(), result_type),
         Err(err) => {
@@ -368,12 +379,13 @@ pub struct RangeQuery {
 
 #[axum_macros::debug_handler]
 pub async fn range_query(
-    State(handler): State<PrometheusHandlerRef>,
+    State(state): State<PrometheusApiState>,
     Query(params): Query<RangeQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     Form(form_params): Form<RangeQuery>,
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_RANGE_QUERY_ELAPSED.start_timer();
+    let handler = &state.prometheus_handler;
     let prom_query = PromQuery {
         query: params.query.or(form_params.query).unwrap_or_default(),
         start: params.start.or(form_params.start).unwrap_or_default(),
@@ -382,6 +394,9 @@ pub async fn range_query(
     };
 
     let result = handler.do_query(&prom_query, query_ctx).await;
+    if let Some(resp) = fail_fast_on_missing_table(state.fail_fast_on_missing_table, &result) {
+        return resp;
+    }
     let metric_name = match retrieve_metric_name_and_result_type(&prom_query.query) {
         Err(err) => {
             return PrometheusJsonResponse::error(err.status_code().to_string(), err.output_msg())
@@ -391,6 +406,30 @@ pub async fn range_query(
     PrometheusJsonResponse::from_query_result(result, metric_name, ValueType::Matrix).await
 }
 
+/// Whether the error is caused by querying a nonexistent table (metric) or column
+/// (label), which Prometheus answers with an empty result.
+fn is_missing_table_or_column(err: &Error) -> bool {
+    matches!(
+        err.status_code(),
+        StatusCode::TableNotFound | StatusCode::TableColumnNotFound
+    )
+}
+
+/// Returns the error response for a query on a nonexistent table or column, if
+/// `fail_fast` is enabled. Otherwise [PrometheusJsonResponse::from_query_result]
+/// answers it with an empty result.
+fn fail_fast_on_missing_table(
+    fail_fast: bool,
+    result: &Result<Output>,
+) -> Option<Json<PrometheusJsonResponse>> {
+    match result {
+        Err(err) if fail_fast && is_missing_table_or_column(err) => Some(
+            PrometheusJsonResponse::error(err.status_code().to_string(), err.output_msg()),
+        ),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Default, Serialize, JsonSchema)]
 struct Matches(Vec<String>);
 
@@ -437,12 +476,13 @@ impl<'de> Deserialize<'de> for Matches {
 
 #[axum_macros::debug_handler]
 pub async fn labels_query(
-    State(handler): State<PrometheusHandlerRef>,
+    State(state): State<PrometheusApiState>,
     Query(params): Query<LabelsQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     Form(form_params): Form<LabelsQuery>,
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_LABEL_QUERY_ELAPSED.start_timer();
+    let handler = &state.prometheus_handler;
 
     let db = &params.db.unwrap_or(DEFAULT_SCHEMA_NAME.to_string());
     let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
@@ -488,9 +528,7 @@ pub async fn labels_query(
 
         if let Err(err) = response {
             // Prometheus won't report error if querying nonexist label and metric
-            if err.status_code() != StatusCode::TableNotFound
-                && err.status_code() != StatusCode::TableColumnNotFound
-            {
+            if !is_missing_table_or_column(&err) {
                 return PrometheusJsonResponse::error(
                     err.status_code().to_string(),
                     err.output_msg(),
@@ -703,12 +741,13 @@ pub struct LabelValueQuery {
 
 #[axum_macros::debug_handler]
 pub async fn label_values_query(
-    State(handler): State<PrometheusHandlerRef>,
+    State(state): State<PrometheusApiState>,
     Path(label_name): Path<String>,
     Extension(query_ctx): Extension<QueryContextRef>,
     Query(params): Query<LabelValueQuery>,
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_LABEL_VALUE_QUERY_ELAPSED.start_timer();
+    let handler = &state.prometheus_handler;
 
     let db = &params.db.unwrap_or(DEFAULT_SCHEMA_NAME.to_string());
     let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
@@ -745,9 +784,7 @@ pub async fn label_values_query(
         let result = retrieve_label_values(result, &label_name, &mut label_values).await;
         if let Err(err) = result {
             // Prometheus won't report error if querying nonexist label and metric
-            if err.status_code() != StatusCode::TableNotFound
-                && err.status_code() != StatusCode::TableColumnNotFound
-            {
+            if !is_missing_table_or_column(&err) {
                 return PrometheusJsonResponse::error(
                     err.status_code().to_string(),
                     err.output_msg(),
@@ -829,12 +866,13 @@ pub struct SeriesQuery {
 
 #[axum_macros::debug_handler]
 pub async fn series_query(
-    State(handler): State<PrometheusHandlerRef>,
+    State(state): State<PrometheusApiState>,
     Query(params): Query<SeriesQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     Form(form_params): Form<SeriesQuery>,
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_SERIES_QUERY_ELAPSED.start_timer();
+    let handler = &state.prometheus_handler;
     let mut queries: Vec<String> = params.matches.0;
     if queries.is_empty() {
         queries = form_params.matches.0;
@@ -869,3 +907,44 @@ pub async fn series_query(
     }
     PrometheusJsonResponse::success(PrometheusResponse::Series(series))
 }
+
+#[cfg(test)]
+mod tests {
+    use common_error::ext::BoxedError;
+    use common_error::mock::MockError;
+
+    use super::*;
+    use crate::error::ExecuteQuerySnafu;
+
+    fn missing_table() -> Result<Output> {
+        Err(BoxedError::new(MockError::new(StatusCode::TableNotFound))).context(ExecuteQuerySnafu {
+            query: "unknown_metric",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_on_missing_table() {
+        // An empty result like Prometheus by default.
+        assert!(fail_fast_on_missing_table(false, &missing_table()).is_none());
+        let Json(resp) = PrometheusJsonResponse::from_query_result(
+            missing_table(),
+            "unknown_metric".to_string(),
+            ValueType::Vector,
+        )
+        .await;
+        assert_eq!("success", resp.status);
+        let PrometheusResponse::PromData(data) = resp.data else {
+            unreachable!()
+        };
+        assert!(data.result.is_empty());
+
+        let Json(resp) = fail_fast_on_missing_table(true, &missing_table()).unwrap();
+        assert_eq!("error", resp.status);
+        assert_eq!(Some(StatusCode::TableNotFound.to_string()), resp.error_type);
+
+        // The other errors are left to the query result.
+        let other: Result<Output> = Err(BoxedError::new(MockError::new(StatusCode::Internal)))
+            .context(ExecuteQuerySnafu { query: "up" });
+        assert!(fail_fast_on_missing_table(true, &other).is_none());
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `Range` requests of the responses whose content is complete before sending, so a
+//! client can resume an interrupted download by the byte offset.
+//!
+//! Query results are streamed, and running a query again may return other rows, so
+//! they are never served by ranges. Only a single range is supported, a request of
+//! multiple ranges is served the whole content.
+
+use std::ops::Range;
+
+use axum::body::{boxed, Full};
+use axum::http::header::{ACCEPT_RANGES, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_RANGE, RANGE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use bytes::Bytes;
+
+const BYTES_UNIT: &str = "bytes";
+
+/// Range of a `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// The range in the content.
+    Satisfiable(Range<usize>),
+    /// The range starts after the end of the content, or is empty.
+    Unsatisfiable,
+}
+
+/// Parses the `Range` header of a content of `len` bytes, returns `None` if it's not a
+/// single range of bytes, which is ignored.
+fn parse_range(value: &str, len: usize) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix(BYTES_UNIT)?.strip_prefix('=')?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.trim().split_once('-')?;
+    let range = match (start.trim(), end.trim()) {
+        ("", "") => return None,
+        // The last `suffix` bytes.
+        ("", suffix) => {
+            let suffix = suffix.parse::<usize>().ok()?;
+            if suffix == 0 || len == 0 {
+                return Some(ByteRange::Unsatisfiable);
+            }
+            len.saturating_sub(suffix)..len
+        }
+        (start, end) => {
+            let start = start.parse::<usize>().ok()?;
+            // The last byte position is inclusive.
+            let end = if end.is_empty() {
+                len
+            } else {
+                let end = end.parse::<usize>().ok()?;
+                if end < start {
+                    return None;
+                }
+                end.saturating_add(1).min(len)
+            };
+            if start >= len {
+                return Some(ByteRange::Unsatisfiable);
+            }
+            start..end
+        }
+    };
+    Some(ByteRange::Satisfiable(range))
+}
+
+/// Builds the response of the `content` identified by the `etag`, serving the range of
+/// the request if any. The range is ignored if the `If-Range` of the request doesn't
+/// match the `etag`, since the content has changed.
+pub fn range_response(
+    headers: &HeaderMap,
+    content: Bytes,
+    content_type: &str,
+    etag: &str,
+) -> axum::http::Result<Response> {
+    let builder = Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(ACCEPT_RANGES, BYTES_UNIT)
+        .header(ETAG, etag);
+
+    let if_range_matches = headers
+        .get(IF_RANGE)
+        .map_or(true, |value| value.as_bytes() == etag.as_bytes());
+    let range = headers
+        .get(RANGE)
+        .filter(|_| if_range_matches)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_range(value, content.len()));
+
+    match range {
+        None => builder.body(boxed(Full::from(content))),
+        Some(ByteRange::Satisfiable(range)) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(
+                CONTENT_RANGE,
+                format!(
+                    "{BYTES_UNIT} {}-{}/{}",
+                    range.start,
+                    range.end - 1,
+                    content.len()
+                ),
+            )
+            .body(boxed(Full::from(content.slice(range)))),
+        Some(ByteRange::Unsatisfiable) => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(CONTENT_RANGE, format!("{BYTES_UNIT} */{}", content.len()))
+            .body(boxed(Full::from(Bytes::new()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::HttpBody;
+    use axum::http::header::HeaderName;
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let satisfiable = |range| Some(ByteRange::Satisfiable(range));
+        assert_eq!(satisfiable(0..5), parse_range("bytes=0-4", 10));
+        assert_eq!(satisfiable(5..10), parse_range("bytes=5-", 10));
+        assert_eq!(satisfiable(5..10), parse_range("bytes=5-100", 10));
+        assert_eq!(satisfiable(7..10), parse_range("bytes=-3", 10));
+        assert_eq!(satisfiable(0..10), parse_range("bytes=-30", 10));
+        assert_eq!(Some(ByteRange::Unsatisfiable), parse_range("bytes=10-", 10));
+        assert_eq!(Some(ByteRange::Unsatisfiable), parse_range("bytes=-0", 10));
+        assert_eq!(Some(ByteRange::Unsatisfiable), parse_range("bytes=-3", 0));
+
+        assert_eq!(None, parse_range("bytes=0-1,3-4", 10));
+        assert_eq!(None, parse_range("bytes=4-3", 10));
+        assert_eq!(None, parse_range("bytes=-", 10));
+        assert_eq!(None, parse_range("items=0-1", 10));
+    }
+
+    async fn body_of(response: Response) -> Bytes {
+        response.into_body().data().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_range_response() {
+        let content = Bytes::from_static(b"0123456789");
+        let etag = "\"abc\"";
+        let response = |headers: &[(HeaderName, &str)]| {
+            let mut map = HeaderMap::new();
+            for (name, value) in headers {
+                let _ = map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+            }
+            range_response(&map, content.clone(), "text/plain", etag).unwrap()
+        };
+
+        let resp = response(&[]);
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!("bytes", resp.headers()[ACCEPT_RANGES]);
+        assert_eq!(etag, resp.headers()[ETAG]);
+        assert_eq!(content, body_of(resp).await);
+
+        let resp = response(&[(RANGE, "bytes=2-4")]);
+        assert_eq!(StatusCode::PARTIAL_CONTENT, resp.status());
+        assert_eq!("bytes 2-4/10", resp.headers()[CONTENT_RANGE]);
+        assert_eq!(&b"234"[..], body_of(resp).await);
+
+        // Resumes only if the content is unchanged.
+        let resp = response(&[(RANGE, "bytes=8-"), (IF_RANGE, etag)]);
+        assert_eq!(StatusCode::PARTIAL_CONTENT, resp.status());
+        assert_eq!(&b"89"[..], body_of(resp).await);
+        let resp = response(&[(RANGE, "bytes=8-"), (IF_RANGE, "\"def\"")]);
+        assert_eq!(StatusCode::OK, resp.status());
+        assert_eq!(content, body_of(resp).await);
+
+        let resp = response(&[(RANGE, "bytes=20-")]);
+        assert_eq!(StatusCode::RANGE_NOT_SATISFIABLE, resp.status());
+        assert_eq!("bytes */10", resp.headers()[CONTENT_RANGE]);
+    }
+}
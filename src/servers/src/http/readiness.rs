@@ -0,0 +1,320 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Probes of the HTTP server for the orchestrators.
+//!
+//! - `/livez` (or `/health/live`) confirms the process isn't stuck, by the heartbeat
+//!   of a watchdog task.
+//! - `/readyz` (or `/health/ready`) if all the components of the process are started,
+//!   and the dependencies like the metadata store are reachable.
+//!
+//! An unreachable dependency only fails the readiness, as restarting the process
+//! doesn't fix it.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use common_meta::kv_backend::KvBackendRef;
+use common_runtime::{RepeatedTask, TaskFunction};
+use common_telemetry::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Interval of the heartbeat of the liveness watchdog.
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+/// A process without a heartbeat for this long isn't alive.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval to check the metadata store is reachable.
+const METADATA_STORE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Timeout of a check of the metadata store.
+const METADATA_STORE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Key read by the metadata store check, doesn't need to exist.
+const METADATA_STORE_CHECK_KEY: &[u8] = b"__readiness_check";
+
+/// Phases of the startup, in the order the components are started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    Starting = 0,
+    DatanodeStarted = 1,
+    ProcedureManagerStarted = 2,
+    Ready = 3,
+}
+
+impl StartupPhase {
+    fn from_u8(phase: u8) -> Self {
+        match phase {
+            0 => StartupPhase::Starting,
+            1 => StartupPhase::DatanodeStarted,
+            2 => StartupPhase::ProcedureManagerStarted,
+            _ => StartupPhase::Ready,
+        }
+    }
+}
+
+/// Startup phase of the process and availability of its dependencies, shared by the
+/// components starting it and the `/readyz` handler.
+#[derive(Debug, Clone)]
+pub struct Readiness {
+    phase: Arc<AtomicU8>,
+    dependencies_available: Arc<AtomicBool>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Readiness {
+    /// Creates the readiness of a process just starting.
+    pub fn new() -> Self {
+        Self {
+            phase: Arc::new(AtomicU8::new(StartupPhase::Starting as u8)),
+            dependencies_available: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Creates the readiness of a process ready once its servers are started.
+    pub fn ready() -> Self {
+        let readiness = Self::new();
+        readiness.advance(StartupPhase::Ready);
+        readiness
+    }
+
+    /// Moves to `phase`, the phase never goes backwards.
+    pub fn advance(&self, phase: StartupPhase) {
+        let _ = self.phase.fetch_max(phase as u8, Ordering::Relaxed);
+    }
+
+    pub fn phase(&self) -> StartupPhase {
+        StartupPhase::from_u8(self.phase.load(Ordering::Relaxed))
+    }
+
+    /// Sets whether the dependencies of the process are reachable, returns the
+    /// previous value.
+    pub fn set_dependencies_available(&self, available: bool) -> bool {
+        self.dependencies_available
+            .swap(available, Ordering::Relaxed)
+    }
+
+    pub fn dependencies_available(&self) -> bool {
+        self.dependencies_available.load(Ordering::Relaxed)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.phase() == StartupPhase::Ready && self.dependencies_available()
+    }
+}
+
+/// Heartbeat of the watchdog task, the process is alive if it's recent.
+#[derive(Debug, Clone)]
+pub struct Liveness {
+    start: Instant,
+    /// Millis since `start` of the last heartbeat.
+    last_beat_ms: Arc<AtomicU64>,
+    timeout: Duration,
+}
+
+impl Default for Liveness {
+    fn default() -> Self {
+        Self::new(LIVENESS_TIMEOUT)
+    }
+}
+
+impl Liveness {
+    /// Creates the liveness of a process not alive after `timeout` without a heartbeat.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            last_beat_ms: Arc::new(AtomicU64::new(0)),
+            timeout,
+        }
+    }
+
+    pub fn beat(&self) {
+        let now = self.start.elapsed().as_millis() as u64;
+        self.last_beat_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// Returns the time since the last heartbeat.
+    pub fn since_last_beat(&self) -> Duration {
+        let last_beat = Duration::from_millis(self.last_beat_ms.load(Ordering::Relaxed));
+        self.start.elapsed().saturating_sub(last_beat)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.since_last_beat() < self.timeout
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReadinessResponse {
+    pub phase: StartupPhase,
+    pub dependencies_available: bool,
+}
+
+/// Handler of `/readyz`, returns "503 Service Unavailable" until the process is ready.
+#[axum_macros::debug_handler]
+pub async fn readyz(State(probes): State<ProbeState>) -> impl IntoResponse {
+    let readiness = probes.readiness;
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let response = ReadinessResponse {
+        phase: readiness.phase(),
+        dependencies_available: readiness.dependencies_available(),
+    };
+    (status, Json(response))
+}
+
+/// Handler of `/livez`, returns "503 Service Unavailable" if the watchdog has no
+/// heartbeat for a while, "200 OK" otherwise.
+#[axum_macros::debug_handler]
+pub async fn livez(State(probes): State<ProbeState>) -> StatusCode {
+    match probes.liveness {
+        Some(liveness) if !liveness.is_alive() => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeState {
+    pub readiness: Readiness,
+    /// Always alive if not set.
+    pub liveness: Option<Liveness>,
+}
+
+/// Creates the watchdog task beating the heartbeat of `liveness`.
+pub fn new_liveness_watchdog(liveness: Liveness) -> RepeatedTask<Error> {
+    liveness.beat();
+    RepeatedTask::new(WATCHDOG_INTERVAL, Box::new(LivenessWatchdog { liveness }))
+}
+
+struct LivenessWatchdog {
+    liveness: Liveness,
+}
+
+#[async_trait::async_trait]
+impl TaskFunction<Error> for LivenessWatchdog {
+    async fn call(&mut self) -> Result<()> {
+        self.liveness.beat();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "LivenessWatchdog"
+    }
+}
+
+/// Creates the task checking the metadata store is reachable, the dependencies of
+/// `readiness` are unavailable while it isn't.
+pub fn new_metadata_store_check(
+    kv_backend: KvBackendRef,
+    readiness: Readiness,
+) -> RepeatedTask<Error> {
+    RepeatedTask::new(
+        METADATA_STORE_CHECK_INTERVAL,
+        Box::new(MetadataStoreCheck {
+            kv_backend,
+            readiness,
+        }),
+    )
+}
+
+struct MetadataStoreCheck {
+    kv_backend: KvBackendRef,
+    readiness: Readiness,
+}
+
+#[async_trait::async_trait]
+impl TaskFunction<Error> for MetadataStoreCheck {
+    async fn call(&mut self) -> Result<()> {
+        let available = match tokio::time::timeout(
+            METADATA_STORE_CHECK_TIMEOUT,
+            self.kv_backend.get(METADATA_STORE_CHECK_KEY),
+        )
+        .await
+        {
+            Ok(Ok(_)) => true,
+            Ok(Err(e)) => {
+                warn!(e; "Metadata store is unreachable");
+                false
+            }
+            Err(_) => {
+                warn!(
+                    "Metadata store is unreachable, check timed out after {:?}",
+                    METADATA_STORE_CHECK_TIMEOUT
+                );
+                false
+            }
+        };
+        if self.readiness.set_dependencies_available(available) != available && available {
+            info!("Metadata store is reachable again");
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "MetadataStoreCheck"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness() {
+        let readiness = Readiness::new();
+        assert_eq!(StartupPhase::Starting, readiness.phase());
+
+        readiness
+            .clone()
+            .advance(StartupPhase::ProcedureManagerStarted);
+        assert_eq!(StartupPhase::ProcedureManagerStarted, readiness.phase());
+        readiness.advance(StartupPhase::DatanodeStarted);
+        assert_eq!(StartupPhase::ProcedureManagerStarted, readiness.phase());
+        assert!(!readiness.is_ready());
+
+        readiness.advance(StartupPhase::Ready);
+        assert!(readiness.is_ready());
+        assert!(Readiness::ready().is_ready());
+
+        // An unreachable dependency only fails the readiness.
+        assert!(readiness.set_dependencies_available(false));
+        assert!(!readiness.is_ready());
+        assert_eq!(StartupPhase::Ready, readiness.phase());
+    }
+
+    #[test]
+    fn test_liveness() {
+        let liveness = Liveness::new(Duration::from_millis(50));
+        liveness.beat();
+        assert!(liveness.is_alive());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!liveness.is_alive());
+        liveness.beat();
+        assert!(liveness.is_alive());
+    }
+}
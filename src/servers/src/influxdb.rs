@@ -15,10 +15,12 @@
 use api::v1::value::ValueData;
 use api::v1::{ColumnDataType, RowInsertRequests};
 use common_grpc::writer::Precision;
-use influxdb_line_protocol::{parse_lines, FieldValue};
-use snafu::ResultExt;
+use influxdb_line_protocol::{parse_lines, FieldValue, ParsedLine};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use strum::EnumString;
 
-use crate::error::{Error, InfluxdbLineProtocolSnafu};
+use crate::error::{Error, InfluxdbLineProtocolSnafu, InvalidTimeIndexSnafu};
 use crate::row_writer::{self, MultiTableData};
 
 pub const INFLUXDB_TIMESTAMP_COLUMN_NAME: &str = "ts";
@@ -30,35 +32,70 @@ pub struct InfluxdbRequest {
     pub lines: String,
 }
 
+/// What to do with a line without a timestamp.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TimeIndexPolicy {
+    /// Uses the time the line arrives at the server.
+    #[default]
+    ArrivalTime,
+    /// Rejects the request.
+    Reject,
+}
+
+/// How the time index of a line is chosen.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TimeIndexOptions {
+    /// Integer field used as the time index instead of the timestamp of the line, in
+    /// the precision of the request. The field isn't written as a column.
+    pub field: Option<String>,
+    /// What to do with a line without the field nor a timestamp.
+    pub policy: TimeIndexPolicy,
+}
+
 impl TryFrom<InfluxdbRequest> for RowInsertRequests {
     type Error = Error;
 
     fn try_from(value: InfluxdbRequest) -> Result<Self, Self::Error> {
-        let lines = parse_lines(&value.lines)
-            .collect::<influxdb_line_protocol::Result<Vec<_>>>()
-            .context(InfluxdbLineProtocolSnafu)?;
-
-        let mut multi_table_data = MultiTableData::new();
-
-        for line in &lines {
-            let table_name = line.series.measurement.as_str();
-            let tags = &line.series.tag_set;
-            let fields = &line.field_set;
-            let ts = line.timestamp;
-            // tags.len + fields.len + timestamp(+1)
-            let num_columns = tags.as_ref().map(|x| x.len()).unwrap_or(0) + fields.len() + 1;
-
-            let table_data = multi_table_data.get_or_default_table_data(table_name, num_columns, 0);
-            let mut one_row = table_data.alloc_one_row();
-
-            // tags
-            if let Some(tags) = tags {
-                let kvs = tags.iter().map(|(k, v)| (k.to_string(), v.as_str()));
-                row_writer::write_tags(table_data, kvs, &mut one_row)?;
-            }
+        to_row_insert_requests(value, &TimeIndexOptions::default())
+    }
+}
 
-            // fields
-            let fields = fields.iter().map(|(k, v)| {
+/// Converts the lines of the request to insert requests, choosing the time index of
+/// each line according to `time_index`.
+pub fn to_row_insert_requests(
+    value: InfluxdbRequest,
+    time_index: &TimeIndexOptions,
+) -> Result<RowInsertRequests, Error> {
+    let lines = parse_lines(&value.lines)
+        .collect::<influxdb_line_protocol::Result<Vec<_>>>()
+        .context(InfluxdbLineProtocolSnafu)?;
+
+    let mut multi_table_data = MultiTableData::new();
+
+    for line in &lines {
+        let table_name = line.series.measurement.as_str();
+        let tags = &line.series.tag_set;
+        let fields = &line.field_set;
+        let ts = time_index_of(line, time_index)?;
+        // tags.len + fields.len + timestamp(+1)
+        let num_columns = tags.as_ref().map(|x| x.len()).unwrap_or(0) + fields.len() + 1;
+
+        let table_data = multi_table_data.get_or_default_table_data(table_name, num_columns, 0);
+        let mut one_row = table_data.alloc_one_row();
+
+        // tags
+        if let Some(tags) = tags {
+            let kvs = tags.iter().map(|(k, v)| (k.to_string(), v.as_str()));
+            row_writer::write_tags(table_data, kvs, &mut one_row)?;
+        }
+
+        // fields, excluding the time index
+        let fields = fields
+            .iter()
+            .filter(|(k, _)| Some(k.as_str()) != time_index.field.as_deref())
+            .map(|(k, v)| {
                 let (datatype, value) = match v {
                     FieldValue::I64(v) => (ColumnDataType::Int64, ValueData::I64Value(*v)),
                     FieldValue::U64(v) => (ColumnDataType::Uint64, ValueData::U64Value(*v)),
@@ -71,23 +108,66 @@ impl TryFrom<InfluxdbRequest> for RowInsertRequests {
                 };
                 (k.to_string(), datatype, value)
             });
-            row_writer::write_fields(table_data, fields, &mut one_row)?;
-
-            // timestamp
-            let precision = unwrap_or_default_precision(value.precision);
-            row_writer::write_ts_precision(
-                table_data,
-                INFLUXDB_TIMESTAMP_COLUMN_NAME,
-                ts,
-                precision,
-                &mut one_row,
-            )?;
-
-            table_data.add_row(one_row);
+        row_writer::write_fields(table_data, fields, &mut one_row)?;
+
+        // timestamp
+        let precision = unwrap_or_default_precision(value.precision);
+        row_writer::write_ts_precision(
+            table_data,
+            INFLUXDB_TIMESTAMP_COLUMN_NAME,
+            ts,
+            precision,
+            &mut one_row,
+        )?;
+
+        table_data.add_row(one_row);
+    }
+
+    Ok(multi_table_data.into_row_insert_requests().0)
+}
+
+/// Returns the timestamp of the line, from the time index field if it's set, or
+/// `None` if the line arrival time should be used.
+fn time_index_of(line: &ParsedLine, time_index: &TimeIndexOptions) -> Result<Option<i64>, Error> {
+    let table = line.series.measurement.as_str();
+
+    let mut ts = line.timestamp;
+    if let Some(name) = &time_index.field {
+        let value = line
+            .field_set
+            .iter()
+            .find(|(k, _)| k.as_str() == name)
+            .map(|(_, v)| match v {
+                FieldValue::I64(v) => Some(*v),
+                FieldValue::U64(v) => i64::try_from(*v).ok(),
+                _ => None,
+            });
+        if let Some(value) = value {
+            let value = value.with_context(|| InvalidTimeIndexSnafu {
+                table,
+                msg: format!("field {name} is not an integer timestamp"),
+            })?;
+            if let Some(timestamp) = ts.filter(|timestamp| *timestamp != value) {
+                return InvalidTimeIndexSnafu {
+                    table,
+                    msg: format!(
+                        "field {name} ({value}) conflicts with the timestamp of the line ({timestamp})"
+                    ),
+                }
+                .fail();
+            }
+            ts = Some(value);
         }
+    }
 
-        Ok(multi_table_data.into_row_insert_requests().0)
+    if ts.is_none() && time_index.policy == TimeIndexPolicy::Reject {
+        let msg = match &time_index.field {
+            Some(name) => format!("a line has neither field {name} nor a timestamp"),
+            None => "a line has no timestamp".to_string(),
+        };
+        return InvalidTimeIndexSnafu { table, msg }.fail();
     }
+    Ok(ts)
 }
 
 #[inline]
@@ -282,6 +362,63 @@ monitor2,host=host4 cpu=66.3,memory=1029 1663840496400340003";
         }
     }
 
+    #[test]
+    fn test_time_index_options() {
+        let convert = |lines: &str, time_index: &TimeIndexOptions| {
+            let request = InfluxdbRequest {
+                precision: Some(Precision::Millisecond),
+                lines: lines.to_string(),
+            };
+            to_row_insert_requests(request, time_index)
+        };
+        let ts_of = |requests: &RowInsertRequests| {
+            let rows = requests.inserts[0].rows.as_ref().unwrap();
+            let index = rows
+                .schema
+                .iter()
+                .position(|c| c.column_name == INFLUXDB_TIMESTAMP_COLUMN_NAME)
+                .unwrap();
+            rows.rows
+                .iter()
+                .map(|row| extract_ts_millis_value(row.values[index].value_data.as_ref().unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        // Lines without a timestamp use the arrival time by default.
+        let requests = convert("monitor,host=a cpu=1.0", &TimeIndexOptions::default()).unwrap();
+        assert!(ts_of(&requests)[0] > 0);
+
+        let reject = TimeIndexOptions {
+            field: None,
+            policy: TimeIndexPolicy::Reject,
+        };
+        let err = convert("monitor,host=a cpu=1.0", &reject).unwrap_err();
+        assert!(matches!(err, Error::InvalidTimeIndex { .. }), "{err:?}");
+        assert!(convert("monitor,host=a cpu=1.0 1000", &reject).is_ok());
+
+        let field = TimeIndexOptions {
+            field: Some("time".to_string()),
+            policy: TimeIndexPolicy::Reject,
+        };
+        let requests = convert(
+            "monitor,host=a cpu=1.0,time=2000i\nmonitor,host=b cpu=2.0 3000\nmonitor,host=c cpu=3.0,time=4000i 4000",
+            &field,
+        )
+        .unwrap();
+        assert_eq!(vec![2000, 3000, 4000], ts_of(&requests));
+        // The time index field isn't written as a column.
+        let rows = requests.inserts[0].rows.as_ref().unwrap();
+        assert!(rows.schema.iter().all(|c| c.column_name != "time"));
+
+        // The field conflicts with the timestamp of the line.
+        let err = convert("monitor,host=a cpu=1.0,time=2000i 3000", &field).unwrap_err();
+        assert!(err.to_string().contains("conflicts"), "{err}");
+        let err = convert("monitor,host=a cpu=1.0,time=\"now\"", &field).unwrap_err();
+        assert!(err.to_string().contains("not an integer"), "{err}");
+        let err = convert("monitor,host=a cpu=1.0", &field).unwrap_err();
+        assert!(matches!(err, Error::InvalidTimeIndex { .. }), "{err:?}");
+    }
+
     fn extract_string_value(value: &ValueData) -> &str {
         match value {
             ValueData::StringValue(v) => v,
@@ -21,6 +21,7 @@ use query::plan::LogicalPlan;
 use serde::{Deserialize, Serialize};
 
 pub mod configurator;
+pub mod connection_limit;
 pub mod error;
 pub mod grpc;
 pub mod heartbeat_options;
@@ -36,11 +37,14 @@ pub mod otlp;
 pub mod postgres;
 pub mod prom_store;
 pub mod prometheus_handler;
+pub mod proxy_protocol;
 pub mod query_handler;
 mod row_writer;
 pub mod server;
 mod shutdown;
 pub mod tls;
+#[cfg(unix)]
+mod unix_socket;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -63,6 +63,12 @@ lazy_static! {
         &[METRIC_CODE_LABEL]
     )
     .unwrap();
+    pub static ref METRIC_REJECTED_CONNECTIONS: IntCounterVec = register_int_counter_vec!(
+        "servers_rejected_connection_count",
+        "servers connections rejected by the connection limits",
+        &["limit"]
+    )
+    .unwrap();
     pub static ref METRIC_HTTP_INFLUXDB_WRITE_ELAPSED: HistogramVec = register_histogram_vec!(
         "servers_http_influxdb_write_elapsed",
         "servers http influxdb write elapsed",
@@ -24,7 +24,7 @@ impl MetricsHandler {
         let mut buffer = Vec::new();
         let encoder = TextEncoder::new();
         // Gather the metrics.
-        let metric_families = prometheus::gather();
+        let metric_families = common_telemetry::metric::gather();
         // Encode them to send.
         match encoder.encode(&metric_families, &mut buffer) {
             Ok(_) => match String::from_utf8(buffer) {
@@ -15,5 +15,6 @@
 mod federated;
 pub mod handler;
 mod helper;
+mod idle;
 pub mod server;
 pub mod writer;
@@ -59,12 +59,22 @@ static SHOW_SQL_MODE_PATTERN: Lazy<Regex> =
 static SET_TIME_ZONE_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?i)^SET TIME_ZONE\s*=\s*'(\S+)'").unwrap());
 
+// Transaction statements, which are tracked for the idle in transaction timeout.
+static BEGIN_TRANSACTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(BEGIN(\s+WORK)?|START\s+TRANSACTION\b.*)\s*;?\s*$").unwrap()
+});
+static END_TRANSACTION_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(COMMIT|ROLLBACK)(\s+WORK)?(\s+AND\s+(NO\s+)?CHAIN)?(\s+(NO\s+)?RELEASE)?\s*;?\s*$")
+        .unwrap()
+});
+
 static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
     RegexSet::new([
         // Txn.
         "(?i)^(ROLLBACK(.*))",
         "(?i)^(COMMIT(.*))",
         "(?i)^(START(.*))",
+        "(?i)^(BEGIN(.*))",
 
         // Set.
         "(?i)^(SET NAMES(.*))",
@@ -299,6 +309,17 @@ fn check_others(query: &str, query_ctx: QueryContextRef) -> Option<Output> {
     recordbatches.map(Output::RecordBatches)
 }
 
+/// Tracks whether the session has an open transaction. `ROLLBACK TO SAVEPOINT` keeps
+/// the transaction open, while `COMMIT AND CHAIN` opens a new one.
+fn track_transaction(query: &str, session: &SessionRef) {
+    if BEGIN_TRANSACTION_PATTERN.is_match(query) {
+        session.set_in_transaction(true);
+    } else if let Some(captures) = END_TRANSACTION_PATTERN.captures(query) {
+        let chain = captures.get(3).is_some() && captures.get(4).is_none();
+        session.set_in_transaction(chain);
+    }
+}
+
 // Check whether the query is a federated or driver setup command,
 // and return some faked results if there are any.
 pub(crate) fn check(
@@ -312,6 +333,8 @@ pub(crate) fn check(
         return None;
     }
 
+    track_transaction(query, &session);
+
     // First to check the query is like "select @@variables".
     check_select_variable(query, query_ctx.clone())
         // Then to check "show variables like ...".
@@ -336,6 +359,30 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_track_transaction() {
+        let session = Arc::new(Session::new(None, Channel::Mysql));
+        for (query, in_transaction) in [
+            ("BEGIN", true),
+            ("commit", false),
+            ("start transaction read only;", true),
+            ("ROLLBACK TO SAVEPOINT a", true),
+            ("begin work", true),
+            ("ROLLBACK", false),
+            ("select 1", false),
+            ("START TRANSACTION", true),
+            ("COMMIT AND CHAIN", true),
+            ("COMMIT AND NO CHAIN", false),
+            ("select 1", false),
+        ] {
+            let output = check(query, QueryContext::arc(), session.clone());
+            assert_eq!(in_transaction, session.in_transaction(), "{query}");
+            if query != "select 1" {
+                assert!(output.is_some(), "{query}");
+            }
+        }
+    }
+
     #[test]
     fn test_check() {
         let session = Arc::new(Session::new(None, Channel::Mysql));
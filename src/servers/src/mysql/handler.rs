@@ -30,7 +30,7 @@ use opensrv_mysql::{
     AsyncMysqlShim, Column, ErrorKind, InitWriter, ParamParser, ParamValue, QueryResultWriter,
     StatementMetaWriter, ValueInner,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use query::plan::LogicalPlan;
 use query::query_engine::DescribeResult;
 use rand::RngCore;
@@ -42,6 +42,7 @@ use sql::parser::ParserContext;
 use sql::statements::statement::Statement;
 use tokio::io::AsyncWrite;
 
+use crate::connection_limit::ConnectionPermit;
 use crate::error::{self, InvalidPrepareStatementSnafu, Result};
 use crate::metrics::METRIC_AUTH_FAILURE;
 use crate::mysql::helper::{
@@ -49,7 +50,7 @@ use crate::mysql::helper::{
 };
 use crate::mysql::writer;
 use crate::mysql::writer::create_mysql_column;
-use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::query_handler::sql::{check_connection_init_sql, ServerSqlQueryHandlerRef};
 use crate::SqlPlan;
 
 // An intermediate shim for executing MySQL queries.
@@ -60,6 +61,11 @@ pub struct MysqlInstanceShim {
     user_provider: Option<UserProviderRef>,
     prepared_stmts: Arc<RwLock<HashMap<u32, SqlPlan>>>,
     prepared_stmts_counter: AtomicU32,
+    /// SQL executed once the client is authenticated.
+    connection_init_sql: Option<String>,
+    server_version: String,
+    /// Counts the connection against the connection limits until it's closed.
+    connection_permit: Mutex<ConnectionPermit>,
 }
 
 impl MysqlInstanceShim {
@@ -67,6 +73,9 @@ impl MysqlInstanceShim {
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
         client_addr: SocketAddr,
+        connection_init_sql: Option<String>,
+        server_version: String,
+        connection_permit: ConnectionPermit,
     ) -> MysqlInstanceShim {
         // init a random salt
         let mut bs = vec![0u8; 20];
@@ -88,9 +97,27 @@ impl MysqlInstanceShim {
             user_provider,
             prepared_stmts: Default::default(),
             prepared_stmts_counter: AtomicU32::new(1),
+            connection_init_sql,
+            server_version,
+            connection_permit: Mutex::new(connection_permit),
         }
     }
 
+    /// Returns the session of the connection.
+    pub(crate) fn session(&self) -> SessionRef {
+        self.session.clone()
+    }
+
+    /// Runs the connection init SQL, if any, like the queries of the client, so
+    /// the statements answered by the federated layer work too.
+    async fn init_connection(&self) -> Result<()> {
+        let Some(sql) = &self.connection_init_sql else {
+            return Ok(());
+        };
+        let query_ctx = self.session.new_query_context();
+        check_connection_init_sql(self.do_query(sql, query_ctx).await)
+    }
+
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
         if let Some(output) =
             crate::mysql::federated::check(query, query_ctx.clone(), self.session.clone())
@@ -150,6 +177,10 @@ impl MysqlInstanceShim {
 impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShim {
     type Error = error::Error;
 
+    fn version(&self) -> String {
+        self.server_version.clone()
+    }
+
     fn salt(&self) -> [u8; 20] {
         self.salt
     }
@@ -196,8 +227,29 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         let user_info =
             user_info.unwrap_or_else(|| auth::userinfo_by_name(Some(username.to_string())));
 
+        if !self
+            .connection_permit
+            .lock()
+            .try_authenticate(user_info.username())
+        {
+            warn!(
+                "Rejected MySQL connection of user {}, too many connections",
+                user_info.username()
+            );
+            return false;
+        }
+
         self.session.set_user_info(user_info);
 
+        if let Err(e) = self.init_connection().await {
+            warn!(
+                "Rejected MySQL connection of user {}, failed to run the connection init SQL: {}",
+                username,
+                e.output_msg()
+            );
+            return false;
+        }
+
         true
     }
 
@@ -355,7 +407,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                 return w
                     .error(
                         ErrorKind::ER_DBACCESS_DENIED_ERROR,
-                        e.to_string().as_bytes(),
+                        e.output_msg().as_bytes(),
                     )
                     .await
                     .map_err(|e| e.into());
@@ -0,0 +1,136 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reaping of the MySQL connections idle in a transaction, like the
+//! `idle_in_transaction_session_timeout` of PostgreSQL.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use common_telemetry::logging::info;
+use session::SessionRef;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+/// The read side of a connection, which reaches EOF once the session has an open
+/// transaction and nothing is read for the timeout, so the connection is closed and
+/// the transaction is aborted. Connections without an open transaction are never
+/// reaped by it.
+pub(crate) struct IdleInTransactionReader<R> {
+    inner: R,
+    session: SessionRef,
+    /// The timeout and the deadline of the idle connection.
+    idle: Option<(Duration, Pin<Box<Sleep>>)>,
+    /// Whether the last read is waiting for the client. The deadline is armed when a
+    /// read starts to wait, so the time spent executing a query doesn't count.
+    waiting: bool,
+}
+
+impl<R> IdleInTransactionReader<R> {
+    /// Creates a reader that is never reaped if `timeout` is not set or zero.
+    pub(crate) fn new(inner: R, session: SessionRef, timeout: Option<Duration>) -> Self {
+        let idle = timeout
+            .filter(|timeout| !timeout.is_zero())
+            .map(|timeout| (timeout, Box::pin(tokio::time::sleep(timeout))));
+        Self {
+            inner,
+            session,
+            idle,
+            waiting: false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for IdleInTransactionReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if let Poll::Ready(result) = Pin::new(&mut this.inner).poll_read(cx, buf) {
+            this.waiting = false;
+            return Poll::Ready(result);
+        }
+
+        let Some((timeout, sleep)) = &mut this.idle else {
+            return Poll::Pending;
+        };
+        if !this.waiting {
+            this.waiting = true;
+            sleep.as_mut().reset(Instant::now() + *timeout);
+        }
+        if !this.session.in_transaction() || sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        info!(
+            "Closing MySQL connection from {:?}, idle in transaction for {:?}",
+            this.session.conn_info().client_addr,
+            timeout
+        );
+        this.session.set_in_transaction(false);
+        // Returns EOF without filling the buffer.
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use session::context::Channel;
+    use session::Session;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reap_idle_in_transaction() {
+        let session = Arc::new(Session::new(None, Channel::Mysql));
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader =
+            IdleInTransactionReader::new(server, session.clone(), Some(Duration::from_millis(100)));
+        let mut buf = [0; 5];
+
+        // Not reaped without a transaction.
+        let read = tokio::time::timeout(Duration::from_millis(200), reader.read(&mut buf)).await;
+        assert!(read.is_err());
+
+        client.write_all(b"begin").await.unwrap();
+        reader.read_exact(&mut buf).await.unwrap();
+        session.set_in_transaction(true);
+        // Executing the query doesn't count as idle.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let read = tokio::time::timeout(Duration::from_millis(50), reader.read(&mut buf)).await;
+        assert!(read.is_err());
+
+        assert_eq!(0, reader.read(&mut buf).await.unwrap());
+        assert!(!session.in_transaction());
+    }
+
+    #[tokio::test]
+    async fn test_never_reap_without_timeout() {
+        let session = Arc::new(Session::new(None, Channel::Mysql));
+        session.set_in_transaction(true);
+        let (_client, server) = tokio::io::duplex(64);
+        let mut reader = IdleInTransactionReader::new(server, session, None);
+        let mut buf = [0; 5];
+        let read = tokio::time::timeout(Duration::from_millis(200), reader.read(&mut buf)).await;
+        assert!(read.is_err());
+    }
+}
@@ -15,44 +15,71 @@
 use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use auth::UserProviderRef;
 use common_runtime::Runtime;
 use common_telemetry::error;
 use common_telemetry::logging::{info, warn};
+#[cfg(unix)]
+use futures::future::{self, AbortHandle, Abortable};
 use futures::StreamExt;
 use opensrv_mysql::{
     plain_run_with_options, secure_run_with_options, AsyncMysqlIntermediary, IntermediaryOptions,
 };
 use tokio;
-use tokio::io::BufWriter;
+use tokio::io::{AsyncRead, AsyncWrite, BufWriter};
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+#[cfg(unix)]
+use tokio::sync::Mutex;
+#[cfg(unix)]
+use tokio::task::JoinHandle;
 use tokio_rustls::rustls::ServerConfig;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
 
+use crate::connection_limit::{ConnectionLimiterRef, ConnectionPermit};
 use crate::error::{Error, Result};
 use crate::mysql::handler::MysqlInstanceShim;
+use crate::mysql::idle::IdleInTransactionReader;
+use crate::proxy_protocol::{self, ProxyProtocolOptions};
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
+use crate::tls::ReloadableTlsServerConfig;
+#[cfg(unix)]
+use crate::unix_socket::{self, UnixSocketGuard};
 
 // Default size of ResultSet write buffer: 100KB
 const DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE: usize = 100 * 1024;
 
+/// Address of the clients of the Unix socket, which are local.
+#[cfg(unix)]
+const UNIX_SOCKET_CLIENT_ADDR: SocketAddr = SocketAddr::V4(std::net::SocketAddrV4::new(
+    std::net::Ipv4Addr::LOCALHOST,
+    0,
+));
+
 /// [`MysqlSpawnRef`] stores arc refs
 /// that should be passed to new [`MysqlInstanceShim`]s.
 pub struct MysqlSpawnRef {
     query_handler: ServerSqlQueryHandlerRef,
     user_provider: Option<UserProviderRef>,
+    connection_limiter: ConnectionLimiterRef,
 }
 
 impl MysqlSpawnRef {
     pub fn new(
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
+        connection_limiter: ConnectionLimiterRef,
     ) -> MysqlSpawnRef {
         MysqlSpawnRef {
             query_handler,
             user_provider,
+            connection_limiter,
         }
     }
 
@@ -69,26 +96,49 @@ impl MysqlSpawnRef {
 pub struct MysqlSpawnConfig {
     // tls config
     force_tls: bool,
-    tls: Option<Arc<ServerConfig>>,
+    tls: Arc<ReloadableTlsServerConfig>,
     // other shim config
     reject_no_database: bool,
+    connection_init_sql: Option<String>,
+    proxy_protocol: ProxyProtocolOptions,
+    server_version: String,
+    /// Closes the connections idle in a transaction for longer than it.
+    idle_in_transaction_timeout: Option<Duration>,
+    /// Path of the Unix socket also accepting connections, and its permissions.
+    unix_socket: Option<(String, Option<String>)>,
 }
 
 impl MysqlSpawnConfig {
     pub fn new(
         force_tls: bool,
-        tls: Option<Arc<ServerConfig>>,
+        tls: Arc<ReloadableTlsServerConfig>,
         reject_no_database: bool,
+        connection_init_sql: Option<String>,
+        proxy_protocol: ProxyProtocolOptions,
+        server_version: String,
+        idle_in_transaction_timeout: Option<Duration>,
     ) -> MysqlSpawnConfig {
         MysqlSpawnConfig {
             force_tls,
             tls,
             reject_no_database,
+            connection_init_sql,
+            proxy_protocol,
+            server_version,
+            idle_in_transaction_timeout,
+            unix_socket: None,
         }
     }
 
+    /// Also accepts the connections on the Unix socket at `path` if set, with the
+    /// permissions `mode`, an octal mode like `660`.
+    pub fn with_unix_socket(mut self, path: Option<String>, mode: Option<String>) -> Self {
+        self.unix_socket = path.map(|path| (path, mode));
+        self
+    }
+
     fn tls(&self) -> Option<Arc<ServerConfig>> {
-        self.tls.clone()
+        self.tls.get_server_config()
     }
 }
 
@@ -105,6 +155,9 @@ pub struct MysqlServer {
     base_server: BaseTcpServer,
     spawn_ref: Arc<MysqlSpawnRef>,
     spawn_config: Arc<MysqlSpawnConfig>,
+    /// The task accepting the connections of the Unix socket, and its abort handle.
+    #[cfg(unix)]
+    unix_accept_task: Mutex<Option<(AbortHandle, JoinHandle<()>)>>,
 }
 
 impl MysqlServer {
@@ -117,6 +170,8 @@ impl MysqlServer {
             base_server: BaseTcpServer::create_server("MySQL", io_runtime),
             spawn_ref,
             spawn_config,
+            #[cfg(unix)]
+            unix_accept_task: Mutex::new(None),
         })
     }
 
@@ -151,13 +206,50 @@ impl MysqlServer {
         })
     }
 
+    /// Accepts the connections of the Unix socket until aborted, then removes the socket
+    /// file.
+    #[cfg(unix)]
+    fn accept_unix(
+        &self,
+        io_runtime: Arc<Runtime>,
+        stream: Abortable<UnixListenerStream>,
+        guard: UnixSocketGuard,
+    ) -> impl Future<Output = ()> {
+        let spawn_ref = self.spawn_ref.clone();
+        let spawn_config = self.spawn_config.clone();
+
+        let accept = stream.for_each(move |unix_stream| {
+            let spawn_ref = spawn_ref.clone();
+            let spawn_config = spawn_config.clone();
+
+            match unix_stream {
+                Err(error) => warn!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
+                Ok(io_stream) => {
+                    let _handle = io_runtime.spawn(async move {
+                        crate::metrics::METRIC_MYSQL_CONNECTIONS.inc();
+                        if let Err(e) =
+                            Self::do_handle_unix(io_stream, spawn_ref, spawn_config).await
+                        {
+                            warn!(e; "Internal error occurred during query exec, server actively close the channel to let client try next time")
+                        }
+                        crate::metrics::METRIC_MYSQL_CONNECTIONS.dec();
+                    });
+                }
+            }
+            future::ready(())
+        });
+        async move {
+            accept.await;
+            drop(guard);
+        }
+    }
+
     async fn handle(
         stream: TcpStream,
         io_runtime: Arc<Runtime>,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Result<()> {
-        info!("MySQL connection coming from: {}", stream.peer_addr()?);
         let _handle = io_runtime.spawn(async move {
             crate::metrics::METRIC_MYSQL_CONNECTIONS.inc();
             if let Err(e)  = Self::do_handle(stream, spawn_ref, spawn_config).await {
@@ -172,23 +264,96 @@ impl MysqlServer {
     }
 
     async fn do_handle(
-        stream: TcpStream,
+        mut stream: TcpStream,
+        spawn_ref: Arc<MysqlSpawnRef>,
+        spawn_config: Arc<MysqlSpawnConfig>,
+    ) -> Result<()> {
+        let Some(connection_permit) = spawn_ref.connection_limiter.try_admit() else {
+            warn!("Rejected MySQL connection, too many unauthenticated connections");
+            return Ok(());
+        };
+
+        // Behind a proxy, the peer is the proxy rather than the client.
+        let client_addr =
+            match proxy_protocol::read_proxy_header(&mut stream, &spawn_config.proxy_protocol)
+                .await?
+            {
+                Some(addr) => addr,
+                None => stream.peer_addr()?,
+            };
+        info!("MySQL connection coming from: {}", client_addr);
+
+        let (r, w) = stream.into_split();
+        Self::serve(
+            r,
+            w,
+            client_addr,
+            connection_permit,
+            spawn_ref,
+            spawn_config,
+        )
+        .await
+    }
+
+    #[cfg(unix)]
+    async fn do_handle_unix(
+        stream: UnixStream,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Result<()> {
+        let Some(connection_permit) = spawn_ref.connection_limiter.try_admit() else {
+            warn!("Rejected MySQL connection, too many unauthenticated connections");
+            return Ok(());
+        };
+        info!("MySQL connection coming from Unix socket");
+
+        let (r, w) = stream.into_split();
+        Self::serve(
+            r,
+            w,
+            UNIX_SOCKET_CLIENT_ADDR,
+            connection_permit,
+            spawn_ref,
+            spawn_config,
+        )
+        .await
+    }
+
+    /// Serves the MySQL protocol on a connection from `client_addr`.
+    async fn serve<R, W>(
+        r: R,
+        w: W,
+        client_addr: SocketAddr,
+        connection_permit: ConnectionPermit,
+        spawn_ref: Arc<MysqlSpawnRef>,
+        spawn_config: Arc<MysqlSpawnConfig>,
+    ) -> Result<()>
+    where
+        R: AsyncRead + Unpin + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
         let mut shim = MysqlInstanceShim::create(
             spawn_ref.query_handler(),
             spawn_ref.user_provider(),
-            stream.peer_addr()?,
+            client_addr,
+            spawn_config.connection_init_sql.clone(),
+            spawn_config.server_version.clone(),
+            connection_permit,
+        );
+        let mut r = IdleInTransactionReader::new(
+            r,
+            shim.session(),
+            spawn_config.idle_in_transaction_timeout,
         );
-        let (mut r, w) = stream.into_split();
         let mut w = BufWriter::with_capacity(DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE, w);
 
         let ops = spawn_config.as_ref().into();
 
+        // Take the config once so the handshake and the session use the same
+        // cert even if it's reloaded in between.
+        let tls_conf = spawn_config.tls();
         let (client_tls, init_params) =
-            AsyncMysqlIntermediary::init_before_ssl(&mut shim, &mut r, &mut w, &spawn_config.tls())
-                .await?;
+            AsyncMysqlIntermediary::init_before_ssl(&mut shim, &mut r, &mut w, &tls_conf).await?;
 
         if spawn_config.force_tls && !client_tls {
             return Err(Error::TlsRequired {
@@ -196,7 +361,7 @@ impl MysqlServer {
             });
         }
 
-        match spawn_config.tls() {
+        match tls_conf {
             Some(tls_conf) if client_tls => {
                 secure_run_with_options(shim, w, ops, tls_conf, init_params).await
             }
@@ -207,9 +372,41 @@ impl MysqlServer {
 
 pub const MYSQL_SERVER: &str = "MYSQL_SERVER";
 
+/// Default server version advertised in the handshake, the one the server has
+/// always advertised. Clients gating features on a recent MySQL may be given one like
+/// `8.4.2` instead.
+pub const DEFAULT_MYSQL_SERVER_VERSION: &str = "5.1.10-alpha-msql-proxy";
+
+/// Returns whether strict clients can parse `version` as a MySQL server version,
+/// that is `major.minor.patch` optionally followed by a `-suffix` of printable
+/// ASCII characters, e.g. `8.4.2` or `5.7.40-greptimedb`.
+pub fn is_valid_server_version(version: &str) -> bool {
+    let (number, suffix) = match version.split_once('-') {
+        Some((number, suffix)) => (number, Some(suffix)),
+        None => (version, None),
+    };
+    let parts: Vec<_> = number.split('.').collect();
+    let number_valid = parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| part.bytes().all(|b| b.is_ascii_digit()) && part.parse::<u16>().is_ok());
+    let suffix_valid = suffix.map_or(true, |suffix| {
+        !suffix.is_empty() && suffix.bytes().all(|b| b.is_ascii_graphic())
+    });
+    number_valid && suffix_valid
+}
+
 #[async_trait]
 impl Server for MysqlServer {
     async fn shutdown(&self) -> Result<()> {
+        self.spawn_config.tls.stop_watching();
+        #[cfg(unix)]
+        if let Some((abort_handle, join_handle)) = self.unix_accept_task.lock().await.take() {
+            abort_handle.abort();
+            if let Err(error) = join_handle.await {
+                error!("Unexpected error during shutdown MySQL Unix socket, error: {error}");
+            }
+        }
         self.base_server.shutdown().await
     }
 
@@ -217,6 +414,24 @@ impl Server for MysqlServer {
         let (stream, addr) = self.base_server.bind(listening).await?;
         let io_runtime = self.base_server.io_runtime();
 
+        #[cfg(unix)]
+        if let Some((path, mode)) = &self.spawn_config.unix_socket {
+            let (unix_stream, guard) =
+                unix_socket::bind_unix_listener(path, mode.as_deref(), "MySQL")?;
+            let (abort_handle, registration) = AbortHandle::new_pair();
+            let unix_stream = Abortable::new(unix_stream, registration);
+            let join_handle = common_runtime::spawn_read(self.accept_unix(
+                io_runtime.clone(),
+                unix_stream,
+                guard,
+            ));
+            *self.unix_accept_task.lock().await = Some((abort_handle, join_handle));
+        }
+        #[cfg(not(unix))]
+        if self.spawn_config.unix_socket.is_some() {
+            warn!("Unix sockets aren't supported on this platform, mysql.unix_socket is ignored");
+        }
+
         let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream));
         self.base_server.start_with(join_handle).await?;
         Ok(addr)
@@ -226,3 +441,25 @@ impl Server for MysqlServer {
         MYSQL_SERVER
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_server_version() {
+        assert!(is_valid_server_version(DEFAULT_MYSQL_SERVER_VERSION));
+        assert!(is_valid_server_version("5.7.40"));
+        assert!(is_valid_server_version("5.7.40-greptimedb-0.4.1"));
+
+        assert!(!is_valid_server_version(""));
+        assert!(!is_valid_server_version("8.0"));
+        assert!(!is_valid_server_version("8.0.x"));
+        assert!(!is_valid_server_version("8.0.26.1"));
+        assert!(!is_valid_server_version("v8.0.26"));
+        assert!(!is_valid_server_version("8.0.99999"));
+        assert!(!is_valid_server_version("8.0.26-"));
+        assert!(!is_valid_server_version("8.0.26-greptime db"));
+        assert!(!is_valid_server_version("8.0.26 greptimedb"));
+    }
+}
@@ -147,7 +147,7 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
                             .await?
                         }
                         Err(e) => {
-                            let err = e.to_string();
+                            let err = e.output_msg();
                             row_writer
                                 .finish_error(ErrorKind::ER_INTERNAL_ERROR, &err.as_bytes())
                                 .await?;
@@ -23,7 +23,7 @@ use std::sync::Arc;
 use api::v1::RowInsertRequests;
 use async_trait::async_trait;
 use common_runtime::Runtime;
-use common_telemetry::logging::error;
+use common_telemetry::logging::{debug, error};
 use futures::StreamExt;
 use tokio::sync::broadcast;
 
@@ -32,6 +32,7 @@ use crate::error::Result;
 use crate::opentsdb::connection::Connection;
 use crate::opentsdb::handler::Handler;
 use crate::prom_store::{FIELD_COLUMN_NAME, TIMESTAMP_COLUMN_NAME};
+use crate::proxy_protocol::{self, ProxyProtocolOptions};
 use crate::query_handler::OpentsdbProtocolHandlerRef;
 use crate::row_writer::{self, MultiTableData};
 use crate::server::{AbortableStream, BaseTcpServer, Server};
@@ -40,6 +41,7 @@ use crate::shutdown::Shutdown;
 pub struct OpentsdbServer {
     base_server: BaseTcpServer,
     query_handler: OpentsdbProtocolHandlerRef,
+    proxy_protocol: ProxyProtocolOptions,
 
     /// Broadcasts a shutdown signal to all active connections.
     ///
@@ -53,6 +55,7 @@ impl OpentsdbServer {
     pub fn create_server(
         query_handler: OpentsdbProtocolHandlerRef,
         io_runtime: Arc<Runtime>,
+        proxy_protocol: ProxyProtocolOptions,
     ) -> Box<dyn Server> {
         // When the provided `shutdown` future completes, we must send a shutdown
         // message to all active connections. We use a broadcast channel for this
@@ -64,6 +67,7 @@ impl OpentsdbServer {
         Box::new(OpentsdbServer {
             base_server: BaseTcpServer::create_server("OpenTSDB", io_runtime),
             query_handler,
+            proxy_protocol,
             notify_shutdown: Some(notify_shutdown),
         })
     }
@@ -74,6 +78,7 @@ impl OpentsdbServer {
         stream: AbortableStream,
     ) -> impl Future<Output = ()> {
         let query_handler = self.query_handler.clone();
+        let proxy_protocol = self.proxy_protocol;
         let notify_shutdown = self
             .notify_shutdown
             .clone()
@@ -84,14 +89,28 @@ impl OpentsdbServer {
             let shutdown = Shutdown::new(notify_shutdown.subscribe());
             async move {
                 match stream {
-                    Ok(stream) => {
+                    Ok(mut stream) => {
                         if let Err(e) = stream.set_nodelay(true) {
                             error!(e; "Failed to set TCP nodelay");
                         }
-                        let connection = Connection::new(stream);
-                        let mut handler = Handler::new(query_handler, connection, shutdown);
 
                         let _handle = io_runtime.spawn(async move {
+                            // The header must be stripped before the lines are decoded.
+                            match proxy_protocol::read_proxy_header(&mut stream, &proxy_protocol)
+                                .await
+                            {
+                                Ok(Some(addr)) => {
+                                    debug!("OpenTSDB connection coming from: {}", addr)
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    error!(e; "Rejected OpenTSDB connection");
+                                    return;
+                                }
+                            }
+
+                            let connection = Connection::new(stream);
+                            let mut handler = Handler::new(query_handler, connection, shutdown);
                             if let Err(e) = handler.run().await {
                                 error!(e; "Unexpected error when handling OpenTSDB connection");
                             }
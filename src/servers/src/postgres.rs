@@ -30,6 +30,7 @@ use std::sync::Arc;
 
 use ::auth::UserProviderRef;
 use derive_builder::Builder;
+use parking_lot::Mutex;
 use pgwire::api::auth::ServerParameterProvider;
 use pgwire::api::store::MemPortalStore;
 use pgwire::api::ClientInfo;
@@ -39,6 +40,7 @@ use session::Session;
 
 use self::auth_handler::PgLoginVerifier;
 use self::handler::DefaultQueryParser;
+use crate::connection_limit::{ConnectionLimiterRef, ConnectionPermit};
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::SqlPlan;
 
@@ -78,6 +80,9 @@ pub struct PostgresServerHandler {
     session: Arc<Session>,
     portal_store: Arc<MemPortalStore<SqlPlan>>,
     query_parser: Arc<DefaultQueryParser>,
+    connection_init_sql: Option<String>,
+    /// Counts the connection against the connection limits until it's closed.
+    connection_permit: Mutex<ConnectionPermit>,
 }
 
 #[derive(Builder)]
@@ -87,10 +92,23 @@ pub(crate) struct MakePostgresServerHandler {
     #[builder(default = "Arc::new(GreptimeDBStartupParameters::new())")]
     param_provider: Arc<GreptimeDBStartupParameters>,
     force_tls: bool,
+    #[builder(default)]
+    connection_init_sql: Option<String>,
+    #[builder(default)]
+    connection_limiter: ConnectionLimiterRef,
 }
 
 impl MakePostgresServerHandler {
-    fn make(&self, addr: Option<SocketAddr>) -> PostgresServerHandler {
+    /// Returns the limiter admitting the connections of the handlers.
+    fn connection_limiter(&self) -> &ConnectionLimiterRef {
+        &self.connection_limiter
+    }
+
+    fn make(
+        &self,
+        addr: Option<SocketAddr>,
+        connection_permit: ConnectionPermit,
+    ) -> PostgresServerHandler {
         let session = Arc::new(Session::new(addr, Channel::Postgres));
         PostgresServerHandler {
             query_handler: self.query_handler.clone(),
@@ -101,6 +119,8 @@ impl MakePostgresServerHandler {
             session: session.clone(),
             portal_store: Arc::new(MemPortalStore::new()),
             query_parser: Arc::new(DefaultQueryParser::new(self.query_handler.clone(), session)),
+            connection_init_sql: self.connection_init_sql.clone(),
+            connection_permit: Mutex::new(connection_permit),
         }
     }
 }
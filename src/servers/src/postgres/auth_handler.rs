@@ -32,7 +32,7 @@ use snafu::IntoError;
 use super::PostgresServerHandler;
 use crate::error::{AuthSnafu, Result};
 use crate::metrics::METRIC_AUTH_FAILURE;
-use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::query_handler::sql::{execute_connection_init_sql, ServerSqlQueryHandlerRef};
 
 pub(crate) struct PgLoginVerifier {
     user_provider: Option<UserProviderRef>,
@@ -125,6 +125,28 @@ where
     // set userinfo outside
 }
 
+impl PostgresServerHandler {
+    /// Counts the connection as one of the logged in user, returns false if the user
+    /// has too many connections.
+    fn admit_user(&self) -> bool {
+        let user_info = self.session.user_info();
+        self.connection_permit
+            .lock()
+            .try_authenticate(user_info.username())
+    }
+
+    /// Runs the connection init SQL, if any, once the client is authenticated.
+    async fn init_connection(&self) -> Result<()> {
+        match &self.connection_init_sql {
+            Some(sql) => {
+                let query_ctx = self.session.new_query_context();
+                execute_connection_init_sql(&self.query_handler, sql, query_ctx).await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
 #[async_trait]
 impl StartupHandler for PostgresServerHandler {
     async fn on_startup<C>(
@@ -171,7 +193,13 @@ impl StartupHandler for PostgresServerHandler {
                     self.session.set_user_info(userinfo_by_name(
                         client.metadata().get(super::METADATA_USER).cloned(),
                     ));
+                    if !self.admit_user() {
+                        return send_too_many_connections(client).await;
+                    }
                     set_client_info(client, &self.session);
+                    if let Err(e) = self.init_connection().await {
+                        return send_error(client, "FATAL", "XX000", e.output_msg()).await;
+                    }
                     auth::finish_authentication(client, self.param_provider.as_ref()).await;
                 }
             }
@@ -188,7 +216,13 @@ impl StartupHandler for PostgresServerHandler {
 
                 if let Ok(Some(user_info)) = auth_result {
                     self.session.set_user_info(user_info);
+                    if !self.admit_user() {
+                        return send_too_many_connections(client).await;
+                    }
                     set_client_info(client, &self.session);
+                    if let Err(e) = self.init_connection().await {
+                        return send_error(client, "FATAL", "XX000", e.output_msg()).await;
+                    }
                     auth::finish_authentication(client, self.param_provider.as_ref()).await;
                 } else {
                     return send_error(
@@ -220,6 +254,22 @@ where
     Ok(())
 }
 
+/// Rejects the connection of a user over the connection limit.
+async fn send_too_many_connections<C>(client: &mut C) -> PgWireResult<()>
+where
+    C: ClientInfo + Sink<PgWireBackendMessage> + Unpin + Send,
+    C::Error: Debug,
+    PgWireError: From<<C as Sink<PgWireBackendMessage>>::Error>,
+{
+    send_error(
+        client,
+        "FATAL",
+        "53300",
+        "too many connections for the user".to_owned(),
+    )
+    .await
+}
+
 enum DbResolution {
     Resolved(String, String),
     NotFound(String),
@@ -26,37 +26,47 @@ use pgwire::tokio::process_socket;
 use tokio_rustls::TlsAcceptor;
 
 use super::{MakePostgresServerHandler, MakePostgresServerHandlerBuilder};
+use crate::connection_limit::ConnectionLimiterRef;
 use crate::error::Result;
+use crate::proxy_protocol::{self, ProxyProtocolOptions};
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
-use crate::tls::TlsOption;
+use crate::tls::ReloadableTlsServerConfig;
 
 pub struct PostgresServer {
     base_server: BaseTcpServer,
     make_handler: Arc<MakePostgresServerHandler>,
-    tls: TlsOption,
+    tls_server_config: Arc<ReloadableTlsServerConfig>,
+    proxy_protocol: ProxyProtocolOptions,
 }
 
 impl PostgresServer {
     /// Creates a new Postgres server with provided query_handler and async runtime
     pub fn new(
         query_handler: ServerSqlQueryHandlerRef,
-        tls: TlsOption,
+        force_tls: bool,
+        tls_server_config: Arc<ReloadableTlsServerConfig>,
         io_runtime: Arc<Runtime>,
         user_provider: Option<UserProviderRef>,
+        connection_init_sql: Option<String>,
+        proxy_protocol: ProxyProtocolOptions,
+        connection_limiter: ConnectionLimiterRef,
     ) -> PostgresServer {
         let make_handler = Arc::new(
             MakePostgresServerHandlerBuilder::default()
                 .query_handler(query_handler.clone())
                 .user_provider(user_provider.clone())
-                .force_tls(tls.should_force_tls())
+                .force_tls(force_tls)
+                .connection_init_sql(connection_init_sql)
+                .connection_limiter(connection_limiter)
                 .build()
                 .unwrap(),
         );
         PostgresServer {
             base_server: BaseTcpServer::create_server("Postgres", io_runtime),
             make_handler,
-            tls,
+            tls_server_config,
+            proxy_protocol,
         }
     }
 
@@ -64,35 +74,60 @@ impl PostgresServer {
         &self,
         io_runtime: Arc<Runtime>,
         accepting_stream: AbortableStream,
-        tls_acceptor: Option<Arc<TlsAcceptor>>,
     ) -> impl Future<Output = ()> {
         let handler_maker = self.make_handler.clone();
+        let tls_server_config = self.tls_server_config.clone();
+        let proxy_protocol = self.proxy_protocol;
         accepting_stream.for_each(move |tcp_stream| {
             let io_runtime = io_runtime.clone();
-            let tls_acceptor = tls_acceptor.clone();
             let handler_maker = handler_maker.clone();
+            // The config may be reloaded, so build the acceptor per connection.
+            let tls_acceptor = tls_server_config
+                .get_server_config()
+                .map(|server_conf| Arc::new(TlsAcceptor::from(server_conf)));
 
             async move {
                 match tcp_stream {
                     Err(error) => error!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
-                    Ok(io_stream) => {
-                        let addr = match io_stream.peer_addr() {
-                            Ok(addr) => {
-                                debug!("PostgreSQL client coming from {}", addr);
-                                Some(addr)
-                            }
-                            Err(e) => {
-                                warn!("Failed to get PostgreSQL client addr, err: {}", e);
-                                None
-                            }
-                        };
-
+                    Ok(mut io_stream) => {
                         let _handle = io_runtime.spawn(async move {
+                            let Some(connection_permit) =
+                                handler_maker.connection_limiter().try_admit()
+                            else {
+                                warn!("Rejected PostgreSQL connection, too many unauthenticated connections");
+                                return Ok(());
+                            };
+
+                            // Behind a proxy, the peer is the proxy rather than the client.
+                            let proxy_addr = match proxy_protocol::read_proxy_header(
+                                &mut io_stream,
+                                &proxy_protocol,
+                            )
+                            .await
+                            {
+                                Ok(addr) => addr,
+                                Err(e) => {
+                                    warn!(e; "Rejected PostgreSQL connection");
+                                    return Ok(());
+                                }
+                            };
+                            let addr =
+                                match proxy_addr.map(Ok).unwrap_or_else(|| io_stream.peer_addr()) {
+                                    Ok(addr) => {
+                                        debug!("PostgreSQL client coming from {}", addr);
+                                        Some(addr)
+                                    }
+                                    Err(e) => {
+                                        warn!("Failed to get PostgreSQL client addr, err: {}", e);
+                                        None
+                                    }
+                                };
+
                             crate::metrics::METRIC_POSTGRES_CONNECTIONS.inc();
-                            let pg_handler = Arc::new(handler_maker.make(addr));
+                            let pg_handler = Arc::new(handler_maker.make(addr, connection_permit));
                             let r = process_socket(
                                 io_stream,
-                                tls_acceptor.clone(),
+                                tls_acceptor,
                                 pg_handler.clone(),
                                 pg_handler.clone(),
                                 pg_handler,
@@ -113,20 +148,20 @@ pub const POSTGRES_SERVER: &str = "POSTGRES_SERVER";
 #[async_trait]
 impl Server for PostgresServer {
     async fn shutdown(&self) -> Result<()> {
+        self.tls_server_config.stop_watching();
         self.base_server.shutdown().await
     }
 
     async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
         let (stream, addr) = self.base_server.bind(listening).await?;
 
-        debug!("Starting PostgreSQL with TLS option: {:?}", self.tls);
-        let tls_acceptor = self
-            .tls
-            .setup()?
-            .map(|server_conf| Arc::new(TlsAcceptor::from(Arc::new(server_conf))));
+        debug!(
+            "Starting PostgreSQL with TLS option: {:?}",
+            self.tls_server_config.get_tls_option()
+        );
 
         let io_runtime = self.base_server.io_runtime();
-        let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream, tls_acceptor));
+        let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream));
 
         self.base_server.start_with(join_handle).await?;
         Ok(addr)
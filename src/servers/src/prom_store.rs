@@ -14,6 +14,9 @@
 
 //! prometheus protocol supportings
 //! handles prometheus remote_write, remote_read logic
+
+pub mod native_histogram;
+
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
@@ -357,6 +360,34 @@ pub fn to_grpc_row_insert_requests(request: WriteRequest) -> Result<(RowInsertRe
     Ok(multi_table_data.into_row_insert_requests())
 }
 
+/// Adds the external `labels` to every series of the remote write `request`, replacing
+/// the labels of the same names. Returns the names of the replaced labels.
+pub fn inject_external_labels(
+    request: &mut WriteRequest,
+    labels: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut overridden = Vec::new();
+    if labels.is_empty() {
+        return overridden;
+    }
+    for series in &mut request.timeseries {
+        for (name, value) in labels {
+            match series.labels.iter_mut().find(|label| &label.name == name) {
+                Some(label) => {
+                    if &label.value != value {
+                        if !overridden.contains(name) {
+                            overridden.push(name.clone());
+                        }
+                        label.value = value.clone();
+                    }
+                }
+                None => series.labels.push(new_label(name.clone(), value.clone())),
+            }
+        }
+    }
+    overridden
+}
+
 #[inline]
 pub fn snappy_decompress(buf: &[u8]) -> Result<Vec<u8>> {
     let mut decoder = Decoder::new();
@@ -783,4 +814,37 @@ mod tests {
             }]
         );
     }
+
+    #[test]
+    fn test_inject_external_labels() {
+        let mut request = WriteRequest {
+            timeseries: mock_timeseries(),
+            ..Default::default()
+        };
+        request.timeseries[0]
+            .labels
+            .push(new_label("region".to_string(), "us-east".to_string()));
+        request.timeseries[1]
+            .labels
+            .push(new_label("region".to_string(), "us-west".to_string()));
+
+        let labels = BTreeMap::from([
+            ("region".to_string(), "us-west".to_string()),
+            ("replica".to_string(), "a".to_string()),
+        ]);
+        let overridden = inject_external_labels(&mut request, &labels);
+        assert_eq!(vec!["region".to_string()], overridden);
+        for series in &request.timeseries {
+            let region = series.labels.iter().filter(|l| l.name == "region");
+            assert_eq!(
+                vec!["us-west"],
+                region.map(|l| l.value.as_str()).collect::<Vec<_>>()
+            );
+            assert!(series
+                .labels
+                .contains(&new_label("replica".to_string(), "a".to_string())));
+        }
+
+        assert!(inject_external_labels(&mut request, &BTreeMap::new()).is_empty());
+    }
 }
@@ -0,0 +1,426 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native histograms of the remote write protocol.
+//!
+//! A native histogram sample is converted to the samples of a classic histogram:
+//! `<metric>_bucket` with the cumulative count below each upper bound `le`,
+//! `<metric>_count` and `<metric>_sum`. So the native and classic histograms of a
+//! metric are written to the same tables with the same schema, and queried the same
+//! way, e.g. by `histogram_quantile`.
+
+use std::collections::BTreeMap;
+
+use api::prom_store::remote::{Label, Sample, TimeSeries};
+use prost::Message;
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{self, Result};
+use crate::prom_store::METRIC_NAME_LABEL;
+
+const BUCKET_LABEL: &str = "le";
+
+/// Range of the exponential schemas, the bucket boundaries of schema `n` grow by a
+/// factor of `2^(2^-n)`.
+const MIN_SCHEMA: i32 = -4;
+const MAX_SCHEMA: i32 = 8;
+
+/// The fields of `WriteRequest` holding native histograms, which aren't decoded by
+/// [WriteRequest](api::prom_store::remote::WriteRequest).
+#[derive(Clone, PartialEq, Message)]
+struct HistogramWriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<HistogramSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct HistogramSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "4")]
+    histograms: Vec<Histogram>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Histogram {
+    #[prost(oneof = "Count", tags = "1, 2")]
+    count: Option<Count>,
+    #[prost(double, tag = "3")]
+    sum: f64,
+    #[prost(sint32, tag = "4")]
+    schema: i32,
+    #[prost(double, tag = "5")]
+    zero_threshold: f64,
+    #[prost(oneof = "ZeroCount", tags = "6, 7")]
+    zero_count: Option<ZeroCount>,
+    #[prost(message, repeated, tag = "8")]
+    negative_spans: Vec<BucketSpan>,
+    /// Counts of the buckets of an integer histogram, each one is the delta to the
+    /// previous bucket.
+    #[prost(sint64, repeated, tag = "9")]
+    negative_deltas: Vec<i64>,
+    /// Absolute counts of the buckets of a float histogram.
+    #[prost(double, repeated, tag = "10")]
+    negative_counts: Vec<f64>,
+    #[prost(message, repeated, tag = "11")]
+    positive_spans: Vec<BucketSpan>,
+    #[prost(sint64, repeated, tag = "12")]
+    positive_deltas: Vec<i64>,
+    #[prost(double, repeated, tag = "13")]
+    positive_counts: Vec<f64>,
+    #[prost(int64, tag = "15")]
+    timestamp: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum Count {
+    #[prost(uint64, tag = "1")]
+    CountInt(u64),
+    #[prost(double, tag = "2")]
+    CountFloat(f64),
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ZeroCount {
+    #[prost(uint64, tag = "6")]
+    ZeroCountInt(u64),
+    #[prost(double, tag = "7")]
+    ZeroCountFloat(f64),
+}
+
+/// A run of consecutive buckets. The offset of the first span is the index of its
+/// first bucket, the offset of the others is the gap to the previous span.
+#[derive(Clone, PartialEq, Message)]
+struct BucketSpan {
+    #[prost(sint32, tag = "1")]
+    offset: i32,
+    #[prost(uint32, tag = "2")]
+    length: u32,
+}
+
+/// Decodes the native histograms of the remote write request `buf`, and converts them
+/// to the series of classic histograms.
+pub fn decode_native_histograms(buf: &[u8]) -> Result<Vec<TimeSeries>> {
+    let request =
+        HistogramWriteRequest::decode(buf).context(error::DecodePromRemoteRequestSnafu)?;
+
+    let mut timeseries = Vec::new();
+    for series in request.timeseries {
+        if series.histograms.is_empty() {
+            continue;
+        }
+        timeseries.extend(to_classic_series(&series.labels, &series.histograms)?);
+    }
+    Ok(timeseries)
+}
+
+fn to_classic_series(labels: &[Label], histograms: &[Histogram]) -> Result<Vec<TimeSeries>> {
+    let name = labels
+        .iter()
+        .find(|label| label.name == METRIC_NAME_LABEL)
+        .context(error::InvalidPromRemoteRequestSnafu {
+            msg: "missing '__name__' label in time-series",
+        })?
+        .value
+        .as_str();
+
+    // The layout of the buckets may change between the samples of a series.
+    let mut buckets: BTreeMap<String, Vec<Sample>> = BTreeMap::new();
+    let mut counts = Vec::with_capacity(histograms.len());
+    let mut sums = Vec::with_capacity(histograms.len());
+    for histogram in histograms {
+        let timestamp = histogram.timestamp;
+        let count = match histogram.count {
+            Some(Count::CountInt(count)) => count as f64,
+            Some(Count::CountFloat(count)) => count,
+            None => 0.0,
+        };
+
+        let mut cumulative = 0.0;
+        for (upper_bound, value) in histogram_buckets(histogram)? {
+            cumulative += value;
+            buckets
+                .entry(upper_bound.to_string())
+                .or_default()
+                .push(Sample {
+                    value: cumulative,
+                    timestamp,
+                });
+        }
+        buckets.entry("+Inf".to_string()).or_default().push(Sample {
+            value: count,
+            timestamp,
+        });
+        counts.push(Sample {
+            value: count,
+            timestamp,
+        });
+        sums.push(Sample {
+            value: histogram.sum,
+            timestamp,
+        });
+    }
+
+    let mut timeseries = Vec::with_capacity(buckets.len() + 2);
+    for (upper_bound, samples) in buckets {
+        let mut labels = series_labels(labels, &format!("{name}_bucket"));
+        labels.push(Label {
+            name: BUCKET_LABEL.to_string(),
+            value: upper_bound,
+        });
+        timeseries.push(TimeSeries {
+            labels,
+            samples,
+            ..Default::default()
+        });
+    }
+    timeseries.push(TimeSeries {
+        labels: series_labels(labels, &format!("{name}_count")),
+        samples: counts,
+        ..Default::default()
+    });
+    timeseries.push(TimeSeries {
+        labels: series_labels(labels, &format!("{name}_sum")),
+        samples: sums,
+        ..Default::default()
+    });
+    Ok(timeseries)
+}
+
+/// Returns the labels with the metric name replaced by `name`.
+fn series_labels(labels: &[Label], name: &str) -> Vec<Label> {
+    labels
+        .iter()
+        .map(|label| {
+            if label.name == METRIC_NAME_LABEL {
+                Label {
+                    name: METRIC_NAME_LABEL.to_string(),
+                    value: name.to_string(),
+                }
+            } else {
+                label.clone()
+            }
+        })
+        .collect()
+}
+
+/// Returns the upper bound and the count of the buckets, in ascending order of the
+/// bounds.
+fn histogram_buckets(histogram: &Histogram) -> Result<Vec<(f64, f64)>> {
+    ensure!(
+        (MIN_SCHEMA..=MAX_SCHEMA).contains(&histogram.schema),
+        error::InvalidPromRemoteRequestSnafu {
+            msg: format!("unsupported native histogram schema {}", histogram.schema),
+        }
+    );
+    let base = 2f64.powf(2f64.powi(-histogram.schema));
+    let is_float = matches!(histogram.count, Some(Count::CountFloat(_)));
+
+    let negative = expand_buckets(
+        &histogram.negative_spans,
+        &histogram.negative_deltas,
+        &histogram.negative_counts,
+        is_float,
+    )?;
+    let positive = expand_buckets(
+        &histogram.positive_spans,
+        &histogram.positive_deltas,
+        &histogram.positive_counts,
+        is_float,
+    )?;
+    let zero_count = match histogram.zero_count {
+        Some(ZeroCount::ZeroCountInt(count)) => count as f64,
+        Some(ZeroCount::ZeroCountFloat(count)) => count,
+        None => 0.0,
+    };
+
+    let mut buckets = Vec::with_capacity(negative.len() + positive.len() + 1);
+    // The negative bucket of index `i` is [-base^i, -base^(i-1)), so the bucket of
+    // the greatest index has the lowest bound.
+    for (index, count) in negative.into_iter().rev() {
+        buckets.push((-base.powi(index - 1), count));
+    }
+    buckets.push((histogram.zero_threshold, zero_count));
+    // The positive bucket of index `i` is (base^(i-1), base^i].
+    for (index, count) in positive {
+        buckets.push((base.powi(index), count));
+    }
+    Ok(buckets)
+}
+
+/// Returns the index and the absolute count of the buckets in the spans.
+fn expand_buckets(
+    spans: &[BucketSpan],
+    deltas: &[i64],
+    counts: &[f64],
+    is_float: bool,
+) -> Result<Vec<(i32, f64)>> {
+    let num_buckets = spans.iter().map(|span| span.length as usize).sum::<usize>();
+    let num_counts = if is_float { counts.len() } else { deltas.len() };
+    ensure!(
+        num_buckets == num_counts,
+        error::InvalidPromRemoteRequestSnafu {
+            msg: format!(
+                "native histogram has {num_counts} bucket counts for {num_buckets} buckets"
+            ),
+        }
+    );
+
+    let mut buckets = Vec::with_capacity(num_buckets);
+    let mut index = 0;
+    let mut count = 0;
+    for span in spans {
+        index += span.offset;
+        for _ in 0..span.length {
+            let value = if is_float {
+                counts[buckets.len()]
+            } else {
+                count += deltas[buckets.len()];
+                count as f64
+            };
+            buckets.push((index, value));
+            index += 1;
+        }
+    }
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use api::prom_store::remote::WriteRequest;
+
+    use super::*;
+
+    fn new_label(name: &str, value: &str) -> Label {
+        Label {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn series_of<'a>(timeseries: &'a [TimeSeries], name: &str, le: Option<&str>) -> &'a [Sample] {
+        &timeseries
+            .iter()
+            .find(|series| {
+                series
+                    .labels
+                    .iter()
+                    .any(|label| label.name == METRIC_NAME_LABEL && label.value == name)
+                    && le.map_or(true, |le| {
+                        series
+                            .labels
+                            .iter()
+                            .any(|label| label.name == BUCKET_LABEL && label.value == le)
+                    })
+            })
+            .unwrap()
+            .samples
+    }
+
+    #[test]
+    fn test_decode_native_histograms() {
+        let histogram = Histogram {
+            count: Some(Count::CountInt(13)),
+            sum: 18.4,
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: Some(ZeroCount::ZeroCountInt(2)),
+            negative_spans: vec![BucketSpan {
+                offset: 0,
+                length: 2,
+            }],
+            // Buckets [-1, -0.5) and [-2, -1).
+            negative_deltas: vec![1, 0],
+            // Buckets (0.5, 1], (1, 2], and (4, 8].
+            positive_spans: vec![
+                BucketSpan {
+                    offset: 0,
+                    length: 2,
+                },
+                BucketSpan {
+                    offset: 1,
+                    length: 1,
+                },
+            ],
+            positive_deltas: vec![2, 1, 1],
+            timestamp: 1000,
+            ..Default::default()
+        };
+        let request = HistogramWriteRequest {
+            timeseries: vec![HistogramSeries {
+                labels: vec![
+                    new_label(METRIC_NAME_LABEL, "latency"),
+                    new_label("job", "api"),
+                ],
+                histograms: vec![histogram],
+            }],
+        };
+        let buf = request.encode_to_vec();
+
+        // The samples of the request aren't affected.
+        assert!(WriteRequest::decode(&buf[..]).unwrap().timeseries[0]
+            .samples
+            .is_empty());
+
+        let timeseries = decode_native_histograms(&buf).unwrap();
+        // 2 negative buckets, the zero bucket, 3 positive buckets, +Inf, count and sum.
+        assert_eq!(9, timeseries.len());
+        let expected = [
+            ("-1", 1.0),
+            ("-0.5", 2.0),
+            ("0.001", 4.0),
+            ("1", 6.0),
+            ("2", 9.0),
+            ("8", 13.0),
+            ("+Inf", 13.0),
+        ];
+        for (le, value) in expected {
+            let samples = series_of(&timeseries, "latency_bucket", Some(le));
+            assert_eq!(
+                vec![Sample {
+                    value,
+                    timestamp: 1000
+                }],
+                samples,
+                "le: {le}"
+            );
+        }
+        assert_eq!(13.0, series_of(&timeseries, "latency_count", None)[0].value);
+        assert_eq!(18.4, series_of(&timeseries, "latency_sum", None)[0].value);
+        assert!(timeseries
+            .iter()
+            .all(|series| series.labels.contains(&new_label("job", "api"))));
+    }
+
+    #[test]
+    fn test_invalid_native_histograms() {
+        let histogram = Histogram {
+            count: Some(Count::CountFloat(1.0)),
+            schema: 0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 2,
+            }],
+            positive_counts: vec![1.0],
+            ..Default::default()
+        };
+        assert!(histogram_buckets(&histogram).is_err());
+
+        let histogram = Histogram {
+            schema: 9,
+            ..Default::default()
+        };
+        assert!(histogram_buckets(&histogram).is_err());
+    }
+}
@@ -0,0 +1,460 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parses the [PROXY protocol](https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt)
+//! header sent by L4 proxies (HAProxy, AWS NLB, etc.) to recover the address of the real
+//! client.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+use strum::EnumString;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+use crate::error::{InvalidProxyProtocolHeaderSnafu, Result};
+
+/// Max length of a v1 header, including the trailing CRLF.
+const V1_MAX_HEADER_LEN: usize = 107;
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// Length of the v2 signature, version/command, family and length fields.
+const V2_FIXED_LEN: usize = 16;
+
+/// Time to wait for the header if it's required. Proxies send it right after
+/// connecting, so it only bounds how long idle connections are kept.
+const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// PROXY protocol header version.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, EnumString)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocolVersion {
+    /// The human-readable header.
+    #[strum(to_string = "v1")]
+    V1,
+    /// The binary header.
+    #[strum(to_string = "v2")]
+    V2,
+}
+
+/// PROXY protocol options of a TCP server.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProxyProtocolOptions {
+    /// Version of the header to parse on accept. The PROXY protocol is disabled if
+    /// it's not set.
+    pub version: Option<ProxyProtocolVersion>,
+    /// Whether to accept connections without the header, e.g. direct connections
+    /// bypassing the proxy. They are rejected by default.
+    pub allow_missing_header: bool,
+    /// Time to wait for the header before treating a connection as a direct one, if
+    /// `allow_missing_header` is true. Clients of server-first protocols (MySQL)
+    /// don't send anything before the greeting, so direct ones are greeted after it.
+    #[serde(with = "humantime_serde")]
+    pub missing_header_timeout: Duration,
+}
+
+impl Default for ProxyProtocolOptions {
+    fn default() -> Self {
+        Self {
+            version: None,
+            allow_missing_header: false,
+            missing_header_timeout: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Result of parsing the bytes peeked from a connection.
+#[derive(Debug, PartialEq, Eq)]
+enum Peeked {
+    /// More bytes are required.
+    Incomplete,
+    /// The connection doesn't start with a header.
+    Missing,
+    /// A header of `len` bytes. `addr` is the address of the client, it's `None`
+    /// if the proxy doesn't tell (e.g. health checks of the proxy itself).
+    Header {
+        len: usize,
+        addr: Option<SocketAddr>,
+    },
+}
+
+/// Reads the PROXY protocol header from a newly accepted `stream` and returns the
+/// address of the client behind the proxy.
+///
+/// Returns `None` if the PROXY protocol is disabled, or the client address is unknown,
+/// in which case callers should use the peer address of the `stream`.
+pub(crate) async fn read_proxy_header(
+    stream: &mut TcpStream,
+    options: &ProxyProtocolOptions,
+) -> Result<Option<SocketAddr>> {
+    let Some(version) = options.version else {
+        return Ok(None);
+    };
+    let (signature, parse): (_, fn(&[u8]) -> Result<Peeked>) = match version {
+        ProxyProtocolVersion::V1 => (V1_PREFIX, parse_v1),
+        ProxyProtocolVersion::V2 => (V2_SIGNATURE, parse_v2),
+    };
+
+    // Only the first byte is peeked, no client of the protocols behind starts like
+    // a header, so the connection is read as one from then on.
+    let timeout = if options.allow_missing_header {
+        options.missing_header_timeout
+    } else {
+        HEADER_TIMEOUT
+    };
+    let mut first = [0; 1];
+    let has_header = match tokio::time::timeout(timeout, stream.peek(&mut first)).await {
+        Ok(n) => n? == 1 && first[0] == signature[0],
+        // Nothing is sent in time.
+        Err(_) => false,
+    };
+    if !has_header {
+        ensure!(
+            options.allow_missing_header,
+            InvalidProxyProtocolHeaderSnafu {
+                reason: format!("missing {version:?} header"),
+            }
+        );
+        return Ok(None);
+    }
+
+    let header = tokio::time::timeout(HEADER_TIMEOUT, read_header(stream, version))
+        .await
+        .map_err(|_| {
+            InvalidProxyProtocolHeaderSnafu {
+                reason: format!("incomplete {version:?} header"),
+            }
+            .build()
+        })??;
+    match parse(&header)? {
+        Peeked::Header { addr, .. } => Ok(addr),
+        _ => InvalidProxyProtocolHeaderSnafu {
+            reason: format!("invalid {version:?} header"),
+        }
+        .fail(),
+    }
+}
+
+/// Reads the whole header of `version` from the `stream`, without consuming any
+/// byte after it.
+async fn read_header(stream: &mut TcpStream, version: ProxyProtocolVersion) -> Result<Vec<u8>> {
+    match version {
+        ProxyProtocolVersion::V1 => {
+            // The length of a v1 header is unknown until the CRLF.
+            let mut header = Vec::with_capacity(V1_MAX_HEADER_LEN);
+            while !header.ends_with(b"\r\n") && header.len() < V1_MAX_HEADER_LEN {
+                header.push(stream.read_u8().await?);
+            }
+            Ok(header)
+        }
+        ProxyProtocolVersion::V2 => {
+            let mut header = vec![0; V2_FIXED_LEN];
+            let _ = stream.read_exact(&mut header).await?;
+            let len = u16::from_be_bytes([header[14], header[15]]) as usize;
+            header.resize(V2_FIXED_LEN + len, 0);
+            let _ = stream.read_exact(&mut header[V2_FIXED_LEN..]).await?;
+            Ok(header)
+        }
+    }
+}
+
+/// Parses a v1 header like `PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n`.
+fn parse_v1(buf: &[u8]) -> Result<Peeked> {
+    if !buf.starts_with(V1_PREFIX) {
+        return Ok(prefix_or_missing(buf, V1_PREFIX));
+    }
+    let Some(end) = buf.windows(2).position(|w| w == b"\r\n") else {
+        ensure!(
+            buf.len() < V1_MAX_HEADER_LEN,
+            InvalidProxyProtocolHeaderSnafu {
+                reason: "v1 header is too long",
+            }
+        );
+        return Ok(Peeked::Incomplete);
+    };
+
+    let line = std::str::from_utf8(&buf[..end])
+        .ok()
+        .filter(|line| line.is_ascii())
+        .ok_or_else(|| {
+            InvalidProxyProtocolHeaderSnafu {
+                reason: "v1 header is not ascii",
+            }
+            .build()
+        })?;
+    let parts = line.split(' ').collect::<Vec<_>>();
+    let addr = match parts.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => None,
+        ["PROXY", protocol @ ("TCP4" | "TCP6"), src, _dst, src_port, _dst_port] => {
+            let ip = IpAddr::from_str(src).ok().filter(|ip| {
+                (*protocol == "TCP4" && ip.is_ipv4()) || (*protocol == "TCP6" && ip.is_ipv6())
+            });
+            let port = u16::from_str(src_port).ok();
+            let (Some(ip), Some(port)) = (ip, port) else {
+                return InvalidProxyProtocolHeaderSnafu {
+                    reason: format!("invalid v1 header: {line}"),
+                }
+                .fail();
+            };
+            Some(SocketAddr::new(ip, port))
+        }
+        _ => {
+            return InvalidProxyProtocolHeaderSnafu {
+                reason: format!("invalid v1 header: {line}"),
+            }
+            .fail()
+        }
+    };
+
+    Ok(Peeked::Header { len: end + 2, addr })
+}
+
+/// Parses a binary v2 header.
+fn parse_v2(buf: &[u8]) -> Result<Peeked> {
+    if !buf.starts_with(V2_SIGNATURE) {
+        return Ok(prefix_or_missing(buf, V2_SIGNATURE));
+    }
+    if buf.len() < V2_FIXED_LEN {
+        return Ok(Peeked::Incomplete);
+    }
+
+    let version_command = buf[12];
+    ensure!(
+        version_command >> 4 == 2,
+        InvalidProxyProtocolHeaderSnafu {
+            reason: format!("unknown v2 header version {}", version_command >> 4),
+        }
+    );
+    let is_local = match version_command & 0x0F {
+        0 => true,
+        1 => false,
+        command => {
+            return InvalidProxyProtocolHeaderSnafu {
+                reason: format!("unknown v2 header command {command}"),
+            }
+            .fail()
+        }
+    };
+    let len = u16::from_be_bytes([buf[14], buf[15]]) as usize;
+    // Only TCP over IPv4 and IPv6 carry the client address we need.
+    let addr_len = match buf[13] {
+        0x11 => 12,
+        0x21 => 36,
+        _ => 0,
+    };
+    ensure!(
+        len >= addr_len,
+        InvalidProxyProtocolHeaderSnafu {
+            reason: format!("v2 header addresses are truncated, length: {len}"),
+        }
+    );
+    let header_len = V2_FIXED_LEN + len;
+    if is_local || addr_len == 0 {
+        return Ok(Peeked::Header {
+            len: header_len,
+            addr: None,
+        });
+    }
+    if buf.len() < V2_FIXED_LEN + addr_len {
+        return Ok(Peeked::Incomplete);
+    }
+
+    let addrs = &buf[V2_FIXED_LEN..];
+    let addr = if addr_len == 12 {
+        let ip = Ipv4Addr::new(addrs[0], addrs[1], addrs[2], addrs[3]);
+        let port = u16::from_be_bytes([addrs[8], addrs[9]]);
+        SocketAddr::new(IpAddr::V4(ip), port)
+    } else {
+        let mut octets = [0; 16];
+        octets.copy_from_slice(&addrs[..16]);
+        let port = u16::from_be_bytes([addrs[32], addrs[33]]);
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), port)
+    };
+
+    Ok(Peeked::Header {
+        len: header_len,
+        addr: Some(addr),
+    })
+}
+
+/// Returns [Peeked::Incomplete] if `buf` may still become a header starting with `prefix`.
+fn prefix_or_missing(buf: &[u8], prefix: &[u8]) -> Peeked {
+    if buf.len() < prefix.len() && prefix.starts_with(buf) {
+        Peeked::Incomplete
+    } else {
+        Peeked::Missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    async fn accept_with(client_data: &[u8]) -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        client.write_all(client_data).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_read_proxy_header() {
+        let options = ProxyProtocolOptions {
+            version: Some(ProxyProtocolVersion::V1),
+            ..Default::default()
+        };
+        let (_client, mut server) =
+            accept_with(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nabc").await;
+        let addr = read_proxy_header(&mut server, &options).await.unwrap();
+        assert_eq!(Some("192.168.0.1:56324".parse().unwrap()), addr);
+        // The data after the header is kept.
+        let mut data = [0; 3];
+        let _ = server.read_exact(&mut data).await.unwrap();
+        assert_eq!(b"abc", &data);
+
+        let (_client, mut server) = accept_with(b"abc").await;
+        assert!(read_proxy_header(&mut server, &options).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_missing_proxy_header() {
+        let options = ProxyProtocolOptions {
+            version: Some(ProxyProtocolVersion::V2),
+            allow_missing_header: true,
+            ..Default::default()
+        };
+        // Clients of server-first protocols send nothing, they are only delayed
+        // by the missing header timeout.
+        let (_client, mut server) = accept_with(b"").await;
+        let start = Instant::now();
+        assert_eq!(
+            None,
+            read_proxy_header(&mut server, &options).await.unwrap()
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let (_client, mut server) = accept_with(b"abc").await;
+        assert_eq!(
+            None,
+            read_proxy_header(&mut server, &options).await.unwrap()
+        );
+        let mut data = [0; 3];
+        let _ = server.read_exact(&mut data).await.unwrap();
+        assert_eq!(b"abc", &data);
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let header = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nSELECT 1";
+        assert_eq!(
+            Peeked::Header {
+                len: 47,
+                addr: Some("192.168.0.1:56324".parse().unwrap()),
+            },
+            parse_v1(header).unwrap()
+        );
+        let header = b"PROXY TCP6 ::1 ::2 4000 4002\r\n";
+        assert_eq!(
+            Peeked::Header {
+                len: header.len(),
+                addr: Some("[::1]:4000".parse().unwrap()),
+            },
+            parse_v1(header).unwrap()
+        );
+        let header = b"PROXY UNKNOWN\r\n";
+        assert_eq!(
+            Peeked::Header {
+                len: header.len(),
+                addr: None,
+            },
+            parse_v1(header).unwrap()
+        );
+
+        assert_eq!(Peeked::Incomplete, parse_v1(b"PRO").unwrap());
+        assert_eq!(Peeked::Incomplete, parse_v1(b"PROXY TCP4 1.1").unwrap());
+        assert_eq!(Peeked::Missing, parse_v1(b"put sys.cpu 1").unwrap());
+        assert!(parse_v1(b"PROXY TCP4 ::1 ::2 4000 4002\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 1.1.1.1\r\n").is_err());
+        assert_eq!(
+            Peeked::Missing,
+            parse_v1(&[b' '; V1_MAX_HEADER_LEN]).unwrap()
+        );
+        let mut too_long = V1_PREFIX.to_vec();
+        too_long.resize(V1_MAX_HEADER_LEN, b'1');
+        assert!(parse_v1(&too_long).is_err());
+    }
+
+    fn v2_header(command: u8, family: u8, addrs: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.push(0x20 | command);
+        header.push(family);
+        header.extend_from_slice(&(addrs.len() as u16).to_be_bytes());
+        header.extend_from_slice(addrs);
+        header
+    }
+
+    #[test]
+    fn test_parse_v2() {
+        let addrs = [10, 0, 0, 1, 10, 0, 0, 2, 0x1F, 0x90, 0x0F, 0xA2];
+        let header = v2_header(1, 0x11, &addrs);
+        assert_eq!(
+            Peeked::Header {
+                len: 28,
+                addr: Some("10.0.0.1:8080".parse().unwrap()),
+            },
+            parse_v2(&header).unwrap()
+        );
+        assert_eq!(Peeked::Incomplete, parse_v2(&header[..20]).unwrap());
+
+        let mut addrs = [0; 36];
+        addrs[15] = 1;
+        addrs[31] = 2;
+        addrs[32..34].copy_from_slice(&4000u16.to_be_bytes());
+        let header = v2_header(1, 0x21, &addrs);
+        assert_eq!(
+            Peeked::Header {
+                len: 52,
+                addr: Some("[::1]:4000".parse().unwrap()),
+            },
+            parse_v2(&header).unwrap()
+        );
+
+        // LOCAL command, e.g. health checks from the proxy.
+        let header = v2_header(0, 0x00, &[]);
+        assert_eq!(
+            Peeked::Header {
+                len: 16,
+                addr: None
+            },
+            parse_v2(&header).unwrap()
+        );
+
+        assert_eq!(Peeked::Incomplete, parse_v2(&V2_SIGNATURE[..5]).unwrap());
+        assert_eq!(Peeked::Missing, parse_v2(b"PROXY TCP4").unwrap());
+        assert!(parse_v2(&v2_header(2, 0x11, &[])).is_err());
+        assert!(parse_v2(&v2_header(1, 0x11, &[1, 2, 3])).is_err());
+    }
+}
@@ -40,6 +40,8 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
 use session::context::QueryContextRef;
 
 use crate::error::Result;
+use crate::http::backup::BackupStatus;
+use crate::http::cardinality::ColumnCardinality;
 use crate::influxdb::InfluxdbRequest;
 use crate::opentsdb::codec::DataPoint;
 use crate::prom_store::Metrics;
@@ -49,6 +51,8 @@ pub type InfluxdbLineProtocolHandlerRef = Arc<dyn InfluxdbLineProtocolHandler +
 pub type PromStoreProtocolHandlerRef = Arc<dyn PromStoreProtocolHandler + Send + Sync>;
 pub type OpenTelemetryProtocolHandlerRef = Arc<dyn OpenTelemetryProtocolHandler + Send + Sync>;
 pub type ScriptHandlerRef = Arc<dyn ScriptHandler + Send + Sync>;
+pub type BackupHandlerRef = Arc<dyn BackupHandler + Send + Sync>;
+pub type CardinalityHandlerRef = Arc<dyn CardinalityHandler + Send + Sync>;
 
 #[async_trait]
 pub trait ScriptHandler {
@@ -112,3 +116,17 @@ pub trait OpenTelemetryProtocolHandler {
         ctx: QueryContextRef,
     ) -> Result<ExportTraceServiceResponse>;
 }
+
+#[async_trait]
+pub trait BackupHandler {
+    /// Starts a backup of all regions in the background, returns its initial status.
+    async fn start_backup(&self) -> Result<BackupStatus>;
+
+    /// Returns the status of the backup `id`, or `None` if there is no such backup.
+    async fn backup_status(&self, id: &str) -> Result<Option<BackupStatus>>;
+}
+
+pub trait CardinalityHandler {
+    /// Returns the estimated cardinality of the tracked tag columns.
+    fn cardinality(&self) -> Vec<ColumnCardinality>;
+}
@@ -29,6 +29,29 @@ pub type SqlQueryHandlerRef<E> = Arc<dyn SqlQueryHandler<Error = E> + Send + Syn
 pub type ServerSqlQueryHandlerRef = SqlQueryHandlerRef<error::Error>;
 use query::query_engine::DescribeResult;
 
+/// Executes the init SQL of a new client connection, failing on the first
+/// statement that returns an error.
+pub(crate) async fn execute_connection_init_sql(
+    query_handler: &ServerSqlQueryHandlerRef,
+    sql: &str,
+    query_ctx: QueryContextRef,
+) -> Result<()> {
+    check_connection_init_sql(query_handler.do_query(sql, query_ctx).await)
+}
+
+/// Fails on the first error in the `results` of the connection init SQL.
+pub(crate) fn check_connection_init_sql(results: Vec<Result<Output>>) -> Result<()> {
+    for result in results {
+        if let Err(e) = result {
+            return error::ConnectionInitSqlSnafu {
+                err_msg: e.output_msg(),
+            }
+            .fail();
+        }
+    }
+    Ok(())
+}
+
 #[async_trait]
 pub trait SqlQueryHandler {
     type Error: ErrorExt;
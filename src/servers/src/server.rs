@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -20,6 +22,7 @@ use common_runtime::Runtime;
 use common_telemetry::logging::{error, info};
 use futures::future::{AbortHandle, AbortRegistration, Abortable};
 use snafu::{ensure, ResultExt};
+use tokio::net::{TcpListener, TcpSocket};
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tokio_stream::wrappers::TcpListenerStream;
@@ -28,6 +31,58 @@ use crate::error::{self, Result};
 
 pub(crate) type AbortableStream = Abortable<TcpListenerStream>;
 
+/// Max number of pending connections of a listener.
+const LISTEN_BACKLOG: u32 = 1024;
+
+/// Requested sizes of `SO_SNDBUF` and `SO_RCVBUF` of the server sockets, 0 for the
+/// OS defaults.
+static TCP_SEND_BUFFER_SIZE: AtomicU32 = AtomicU32::new(0);
+static TCP_RECV_BUFFER_SIZE: AtomicU32 = AtomicU32::new(0);
+
+/// Sets the socket buffer sizes of the servers of this process started after, the
+/// OS defaults are kept for `None`.
+pub fn set_tcp_buffer_sizes(send: Option<u32>, recv: Option<u32>) {
+    TCP_SEND_BUFFER_SIZE.store(send.unwrap_or_default(), Ordering::Relaxed);
+    TCP_RECV_BUFFER_SIZE.store(recv.unwrap_or_default(), Ordering::Relaxed);
+}
+
+/// Binds a listener on `addr` with the socket buffer sizes set by
+/// [set_tcp_buffer_sizes], which are inherited by the accepted connections.
+pub(crate) fn bind_tcp_listener(addr: SocketAddr, name: &str) -> io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    // Like `TcpListener::bind`, so a restarted server can bind the address of the
+    // connections left in TIME_WAIT.
+    #[cfg(not(windows))]
+    socket.set_reuseaddr(true)?;
+
+    let send = TCP_SEND_BUFFER_SIZE.load(Ordering::Relaxed);
+    let recv = TCP_RECV_BUFFER_SIZE.load(Ordering::Relaxed);
+    if send > 0 {
+        socket.set_send_buffer_size(send)?;
+    }
+    if recv > 0 {
+        // Set before listening, the window scaling of a connection is negotiated by
+        // the handshake.
+        socket.set_recv_buffer_size(recv)?;
+    }
+    if send > 0 || recv > 0 {
+        // The OS may clamp the requested sizes, or double them for its bookkeeping
+        // like Linux does.
+        info!(
+            "{name} server socket buffers: send {} bytes (requested {send}), recv {} bytes (requested {recv})",
+            socket.send_buffer_size()?,
+            socket.recv_buffer_size()?,
+        );
+    }
+
+    socket.bind(addr)?;
+    socket.listen(LISTEN_BACKLOG)
+}
+
 #[async_trait]
 pub trait Server: Send + Sync {
     /// Shutdown the server gracefully.
@@ -84,12 +139,9 @@ impl AcceptTask {
     ) -> Result<(Abortable<TcpListenerStream>, SocketAddr)> {
         match self.abort_registration.take() {
             Some(registration) => {
-                let listener =
-                    tokio::net::TcpListener::bind(addr)
-                        .await
-                        .context(error::TokioIoSnafu {
-                            err_msg: format!("{name} failed to bind addr {addr}"),
-                        })?;
+                let listener = bind_tcp_listener(addr, name).context(error::TokioIoSnafu {
+                    err_msg: format!("{name} failed to bind addr {addr}"),
+                })?;
                 // get actually bond addr in case input addr use port 0
                 let addr = listener.local_addr()?;
                 info!("{name} server started at {addr}");
@@ -159,3 +211,33 @@ impl BaseTcpServer {
         self.io_runtime.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bind_tcp_listener() {
+        set_tcp_buffer_sizes(Some(256 * 1024), Some(256 * 1024));
+        let listener = bind_tcp_listener("127.0.0.1:0".parse().unwrap(), "test").unwrap();
+        set_tcp_buffer_sizes(None, None);
+
+        let addr = listener.local_addr().unwrap();
+        let (client, accepted) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        let _client = client.unwrap();
+        let (stream, _) = accepted.unwrap();
+
+        // The accepted connection inherits the buffer sizes of the listener, which
+        // Linux doubles.
+        #[cfg(target_os = "linux")]
+        {
+            let socket = TcpSocket::from_std_stream(stream.into_std().unwrap());
+            assert!(socket.send_buffer_size().unwrap() >= 256 * 1024);
+            assert!(socket.recv_buffer_size().unwrap() >= 256 * 1024);
+        }
+        #[cfg(not(target_os = "linux"))]
+        drop(stream);
+    }
+}
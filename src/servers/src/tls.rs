@@ -12,14 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::{BufReader, Error, ErrorKind};
-
-use rustls::{Certificate, PrivateKey, ServerConfig};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+
+use common_telemetry::logging::{error, info};
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rustls::{Certificate, PrivateKey, ServerConfig, SignatureScheme};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
 use strum::EnumString;
 
+use crate::error::{InternalIoSnafu, Result, WatchTlsFilesSnafu};
+
+/// The files are reloaded once they stay unchanged for this long, so that a cert and key
+/// written one after another (or written in place in several steps) are picked up as a
+/// pair.
+const TLS_RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Message signed by the key and verified by the cert to check they pair.
+const KEY_PAIR_CHECK_MESSAGE: &[u8] = b"greptimedb tls key pair check";
+
 /// TlsMode is used for Mysql and Postgres server start up.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, EnumString)]
 #[serde(rename_all = "snake_case")]
@@ -51,6 +71,9 @@ pub struct TlsOption {
     pub cert_path: String,
     #[serde(default)]
     pub key_path: String,
+    /// Whether to watch the cert and key files and reload them on change.
+    #[serde(default)]
+    pub watch: bool,
 }
 
 impl TlsOption {
@@ -72,13 +95,13 @@ impl TlsOption {
         tls_option
     }
 
-    pub fn setup(&self) -> Result<Option<ServerConfig>, Error> {
+    pub fn setup(&self) -> std::result::Result<Option<ServerConfig>, Error> {
         if let TlsMode::Disable = self.mode {
             return Ok(None);
         }
         let cert = certs(&mut BufReader::new(File::open(&self.cert_path)?))
             .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid cert"))
-            .map(|mut certs| certs.drain(..).map(Certificate).collect())?;
+            .map(|mut certs| certs.drain(..).map(Certificate).collect::<Vec<_>>())?;
 
         let key = {
             let mut pkcs8 = pkcs8_private_keys(&mut BufReader::new(File::open(&self.key_path)?))
@@ -96,6 +119,10 @@ impl TlsOption {
             }
         };
 
+        if let Some(end_entity) = cert.first() {
+            check_key_pair(end_entity, &key)?;
+        }
+
         // TODO(SSebo): with_client_cert_verifier if TlsMode is Required.
         let config = ServerConfig::builder()
             .with_safe_defaults()
@@ -109,10 +136,224 @@ impl TlsOption {
     pub fn should_force_tls(&self) -> bool {
         !matches!(self.mode, TlsMode::Disable | TlsMode::Prefer)
     }
+
+    pub fn watch_enabled(&self) -> bool {
+        self.mode != TlsMode::Disable && self.watch
+    }
+}
+
+/// Checks the `key` is the private key of the `cert`, by verifying a signature of the
+/// key with the cert, as the server config doesn't check it until the first handshake.
+fn check_key_pair(cert: &Certificate, key: &PrivateKey) -> std::result::Result<(), Error> {
+    let invalid = |msg: &str| Error::new(ErrorKind::InvalidInput, msg.to_string());
+
+    let signing_key =
+        rustls::sign::any_supported_type(key).map_err(|_| invalid("unsupported key type"))?;
+    let signer = signing_key
+        .choose_scheme(&[
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            SignatureScheme::ED25519,
+            SignatureScheme::RSA_PSS_SHA256,
+        ])
+        .ok_or_else(|| invalid("unsupported key type"))?;
+    let algorithm = match signer.scheme() {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+        SignatureScheme::ED25519 => &webpki::ED25519,
+        SignatureScheme::RSA_PSS_SHA256 => &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+        _ => return Err(invalid("unsupported key type")),
+    };
+    let signature = signer
+        .sign(KEY_PAIR_CHECK_MESSAGE)
+        .map_err(|_| invalid("failed to sign with the key"))?;
+
+    webpki::EndEntityCert::try_from(cert.0.as_slice())
+        .map_err(|_| invalid("invalid cert"))?
+        .verify_signature(algorithm, KEY_PAIR_CHECK_MESSAGE, &signature)
+        .map_err(|_| invalid("the key doesn't match the cert"))
+}
+
+/// A TLS server config that can be reloaded at runtime.
+///
+/// Servers should call [ReloadableTlsServerConfig::get_server_config] on every
+/// new connection instead of caching the returned config.
+pub struct ReloadableTlsServerConfig {
+    tls_option: TlsOption,
+    config: RwLock<Option<Arc<ServerConfig>>>,
+    version: AtomicUsize,
+    /// The watcher of the files started by [maybe_watch_tls_config], stopped on drop.
+    watcher: Mutex<Option<TlsWatcher>>,
+}
+
+impl ReloadableTlsServerConfig {
+    /// Creates the config from the tls option. An invalid cert or key fails here.
+    pub fn try_new(tls_option: TlsOption) -> Result<ReloadableTlsServerConfig> {
+        let server_config = tls_option.setup().context(InternalIoSnafu)?;
+        Ok(Self {
+            tls_option,
+            config: RwLock::new(server_config.map(Arc::new)),
+            version: AtomicUsize::new(0),
+            watcher: Mutex::new(None),
+        })
+    }
+
+    /// Reloads the cert and key from disk. The new pair is only swapped in once the
+    /// server config is built from it, which checks the key belongs to the cert, so
+    /// the current config is kept if the new pair is invalid.
+    pub fn reload(&self) -> Result<()> {
+        let server_config = self.tls_option.setup().context(InternalIoSnafu)?;
+        *self.config.write().unwrap() = server_config.map(Arc::new);
+        let _ = self.version.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn get_server_config(&self) -> Option<Arc<ServerConfig>> {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn get_tls_option(&self) -> &TlsOption {
+        &self.tls_option
+    }
+
+    /// Returns how many times the config has been reloaded.
+    pub fn get_version(&self) -> usize {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    /// Stops watching the cert and key files, called on the shutdown of the server.
+    pub fn stop_watching(&self) {
+        let _ = self.watcher.lock().unwrap().take();
+    }
+}
+
+/// Watches the directories of the cert and key files, and stops the watcher thread
+/// on drop, which ends the notifications the thread waits on.
+struct TlsWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Starts a background thread watching the cert and key files if `watch` is
+/// enabled in the tls option, until [ReloadableTlsServerConfig::stop_watching].
+///
+/// The directories of the files are watched rather than the files themselves, to
+/// also see a secret volume swapping its `..data` link. A reload happens only after
+/// the files stay unchanged for [TLS_RELOAD_DEBOUNCE].
+pub fn maybe_watch_tls_config(tls_server_config: Arc<ReloadableTlsServerConfig>) -> Result<()> {
+    if !tls_server_config.get_tls_option().watch_enabled() {
+        return Ok(());
+    }
+
+    let tls_option = tls_server_config.get_tls_option().clone();
+    let watch_error = || WatchTlsFilesSnafu {
+        cert_path: &tls_option.cert_path,
+        key_path: &tls_option.key_path,
+    };
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).with_context(|_| watch_error())?;
+    let dirs: BTreeSet<_> = [&tls_option.cert_path, &tls_option.key_path]
+        .into_iter()
+        .map(|path| {
+            Path::new(path)
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+        })
+        .collect();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|_| watch_error())?;
+    }
+
+    let config = Arc::downgrade(&tls_server_config);
+    let thread_option = tls_option.clone();
+    let _handle = std::thread::Builder::new()
+        .name("tls-cert-watcher".to_string())
+        .spawn(move || watch_tls_files(&thread_option, &config, &rx))
+        .context(InternalIoSnafu)?;
+    *tls_server_config.watcher.lock().unwrap() = Some(TlsWatcher { _watcher: watcher });
+
+    info!(
+        "Watching TLS cert {} and key {} for changes",
+        tls_option.cert_path, tls_option.key_path
+    );
+    Ok(())
+}
+
+fn watch_tls_files(
+    tls_option: &TlsOption,
+    config: &Weak<ReloadableTlsServerConfig>,
+    events: &Receiver<notify::Result<Event>>,
+) {
+    let mut pending = false;
+    loop {
+        let event = if pending {
+            events.recv_timeout(TLS_RELOAD_DEBOUNCE)
+        } else {
+            events.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+        match event {
+            // Changed again, waits for the files to settle.
+            Ok(Ok(event)) => {
+                pending |= is_tls_file_change(tls_option, &event);
+                continue;
+            }
+            Ok(Err(e)) => {
+                error!(e; "Failed to watch the TLS cert and key files");
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            // The watcher is stopped.
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        pending = false;
+        let Some(config) = config.upgrade() else {
+            break;
+        };
+        match config.reload() {
+            Ok(_) => info!(
+                "Reloaded TLS cert {} and key {}",
+                tls_option.cert_path, tls_option.key_path
+            ),
+            Err(e) => {
+                error!(e; "Failed to reload TLS cert and key, keep using the previous ones")
+            }
+        }
+    }
+    info!(
+        "Stopped watching TLS cert {} and key {}",
+        tls_option.cert_path, tls_option.key_path
+    );
+}
+
+/// Returns true if `event` writes the cert or key file, or an entry of a secret volume
+/// like its `..data` link. The events of the other files in the directories, and the
+/// reads of the files by the reloads, are ignored.
+fn is_tls_file_change(tls_option: &TlsOption, event: &Event) -> bool {
+    let writes = matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Modify(_)
+            | EventKind::Remove(_)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    );
+    let file_names =
+        [&tls_option.cert_path, &tls_option.key_path].map(|path| Path::new(path).file_name());
+    writes
+        && event.paths.iter().any(|path| {
+            let name = path.file_name();
+            file_names.contains(&name)
+                || name.is_some_and(|name| name.to_string_lossy().starts_with(".."))
+        })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Instant;
+
+    use common_test_util::temp_dir::create_temp_dir;
+
     use super::*;
     use crate::tls::TlsMode::Disable;
 
@@ -131,6 +372,7 @@ mod tests {
                 mode: Disable,
                 cert_path: "/path/to/cert_path".to_string(),
                 key_path: "/path/to/key_path".to_string(),
+                watch: false,
             },
             TlsOption::new(
                 Some(Disable),
@@ -236,4 +478,91 @@ mod tests {
         assert!(!t.key_path.is_empty());
         assert!(!t.cert_path.is_empty());
     }
+
+    #[test]
+    fn test_tls_file_change_watch() {
+        let dir = create_temp_dir("tls");
+        let cert_path = dir.path().join("server.crt");
+        let key_path = dir.path().join("server.key");
+
+        std::fs::copy("tests/ssl/server.crt", &cert_path).unwrap();
+        std::fs::copy("tests/ssl/server-rsa.key", &key_path).unwrap();
+
+        let server_tls = TlsOption {
+            mode: TlsMode::Require,
+            cert_path: cert_path.to_str().unwrap().to_string(),
+            key_path: key_path.to_str().unwrap().to_string(),
+            watch: true,
+        };
+
+        let server_config = Arc::new(ReloadableTlsServerConfig::try_new(server_tls).unwrap());
+        maybe_watch_tls_config(server_config.clone()).unwrap();
+
+        assert_eq!(0, server_config.get_version());
+        assert!(server_config.get_server_config().is_some());
+
+        std::fs::copy("tests/ssl/server-pkcs8.key", &key_path).unwrap();
+        assert!(wait_until(|| server_config.get_version() > 0));
+        assert!(server_config.get_server_config().is_some());
+
+        // Not reloaded once the watcher is stopped.
+        server_config.stop_watching();
+        let version = server_config.get_version();
+        std::fs::copy("tests/ssl/server-rsa.key", &key_path).unwrap();
+        std::thread::sleep(TLS_RELOAD_DEBOUNCE * 4);
+        assert_eq!(version, server_config.get_version());
+    }
+
+    /// Polls `condition` until it holds, or fails after a deadline.
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !condition() {
+            if Instant::now() > deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        true
+    }
+
+    #[test]
+    fn test_tls_option_mismatched_key() {
+        let s = TlsOption {
+            mode: TlsMode::Require,
+            cert_path: "tests/ssl/server.crt".to_string(),
+            key_path: "tests/ssl/root-ca.key".to_string(),
+            watch: false,
+        };
+        let err = s.setup().unwrap_err();
+        assert!(err.to_string().contains("the key doesn't match the cert"));
+
+        let s = TlsOption {
+            key_path: "tests/ssl/server-pkcs8.key".to_string(),
+            ..s
+        };
+        assert!(s.setup().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_tls_reload_keeps_config_on_invalid_pair() {
+        let dir = create_temp_dir("tls");
+        let cert_path = dir.path().join("server.crt");
+        let key_path = dir.path().join("server.key");
+
+        std::fs::copy("tests/ssl/server.crt", &cert_path).unwrap();
+        std::fs::copy("tests/ssl/server-rsa.key", &key_path).unwrap();
+
+        let server_config = ReloadableTlsServerConfig::try_new(TlsOption {
+            mode: TlsMode::Require,
+            cert_path: cert_path.to_str().unwrap().to_string(),
+            key_path: key_path.to_str().unwrap().to_string(),
+            watch: false,
+        })
+        .unwrap();
+
+        std::fs::write(&key_path, "not a key").unwrap();
+        assert!(server_config.reload().is_err());
+        assert_eq!(0, server_config.get_version());
+        assert!(server_config.get_server_config().is_some());
+    }
 }
@@ -0,0 +1,142 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Unix domain socket listeners, for the local clients like sidecars to connect to
+//! the servers without a TCP port.
+
+use std::fs::{self, Permissions};
+use std::io;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use common_telemetry::logging::{info, warn};
+use snafu::{OptionExt, ResultExt};
+use tokio::net::UnixListener;
+use tokio_stream::wrappers::UnixListenerStream;
+
+use crate::error::{InvalidUnixSocketModeSnafu, Result, UnixSocketBindSnafu};
+
+/// Removes the socket file on drop, once the server stops accepting connections.
+#[derive(Debug)]
+pub(crate) struct UnixSocketGuard {
+    path: PathBuf,
+}
+
+impl Drop for UnixSocketGuard {
+    fn drop(&mut self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => info!("Removed Unix socket {}", self.path.display()),
+            Err(e) => warn!("Failed to remove Unix socket {}: {e}", self.path.display()),
+        }
+    }
+}
+
+/// Parses the permissions of a socket file, an octal mode like `660`.
+fn parse_mode(mode: &str) -> Result<u32> {
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .context(InvalidUnixSocketModeSnafu { mode })
+}
+
+/// Binds a listener on the socket file at `path`, with the permissions `mode` if set
+/// or as the umask of the process allows otherwise.
+///
+/// A socket file left by a server that didn't stop cleanly is replaced, while binding
+/// the socket of a running server fails. The socket file is removed once the returned
+/// guard is dropped.
+pub(crate) fn bind_unix_listener(
+    path: &str,
+    mode: Option<&str>,
+    name: &str,
+) -> Result<(UnixListenerStream, UnixSocketGuard)> {
+    let mode = mode.map(parse_mode).transpose()?;
+    let listener = bind(Path::new(path), mode).context(UnixSocketBindSnafu { path })?;
+    info!("{name} server is bound to Unix socket {path}");
+
+    let guard = UnixSocketGuard {
+        path: PathBuf::from(path),
+    };
+    Ok((UnixListenerStream::new(listener), guard))
+}
+
+fn bind(path: &Path, mode: Option<u32>) -> io::Result<UnixListener> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "the socket is in use by a running server",
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        // Binding fails on the other files.
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let listener = UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+    }
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+    use tokio::net::UnixStream;
+    use tokio_stream::StreamExt;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(0o660, parse_mode("660").unwrap());
+        assert_eq!(0o600, parse_mode("0600").unwrap());
+        assert!(parse_mode("rw-rw----").is_err());
+        assert!(parse_mode("1777").is_err());
+        assert!(parse_mode("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bind_unix_listener() {
+        let dir = create_temp_dir("test_bind_unix_listener");
+        let path = dir.path().join("greptimedb.sock");
+        let path = path.to_str().unwrap();
+
+        let (mut incoming, guard) = bind_unix_listener(path, Some("600"), "test").unwrap();
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+
+        let (client, accepted) = tokio::join!(UnixStream::connect(path), incoming.next());
+        let _client = client.unwrap();
+        let _accepted = accepted.unwrap().unwrap();
+
+        // The socket of a running server isn't replaced.
+        assert!(bind_unix_listener(path, None, "test").is_err());
+
+        // Removed on drop.
+        drop(incoming);
+        drop(guard);
+        assert!(!Path::new(path).exists());
+
+        // A stale socket file is replaced.
+        let stale = std::os::unix::net::UnixListener::bind(path).unwrap();
+        drop(stale);
+        let (_incoming, _guard) = bind_unix_listener(path, None, "test").unwrap();
+    }
+}
@@ -24,7 +24,7 @@ use servers::http::{
     JsonOutput,
 };
 use servers::metrics_handler::MetricsHandler;
-use session::context::QueryContext;
+use session::context::{QueryContext, QueryContextBuilder};
 use table::test_util::MemTable;
 
 use crate::{
@@ -103,6 +103,40 @@ async fn test_sql_output_rows() {
     }
 }
 
+#[tokio::test]
+async fn test_sql_explain_analyze() {
+    common_telemetry::init_default_ut_logging();
+
+    let query = create_query();
+    let sql_handler = create_testing_sql_query_handler(MemTable::default_numbers_table());
+
+    let ctx = QueryContextBuilder::default().explain_analyze(true).build();
+    ctx.set_current_user(Some(auth::userinfo_by_name(None)));
+    let Json(json) = http_handler::sql(
+        State(ApiState {
+            sql_handler,
+            script_handler: None,
+        }),
+        query,
+        axum::Extension(ctx),
+        Form(http_handler::SqlQuery::default()),
+    )
+    .await;
+    assert!(json.success(), "{json:?}");
+    match &json.output().expect("assertion failed")[0] {
+        JsonOutput::Records(records) => {
+            assert_eq!(1, records.num_rows());
+            let metrics = records.metrics().expect("metrics are asked for");
+            assert!(!metrics.is_empty());
+            assert_eq!(0, metrics[0].level);
+            assert!(metrics
+                .iter()
+                .any(|m| m.metrics.contains_key("output_rows")));
+        }
+        _ => unreachable!(),
+    }
+}
+
 #[tokio::test]
 async fn test_sql_form() {
     common_telemetry::init_default_ut_logging();
@@ -20,6 +20,7 @@ use async_trait::async_trait;
 use auth::tests::{DatabaseAuthInfo, MockUserProvider};
 use axum::{http, Router};
 use axum_test_helper::TestClient;
+use common_base::readable_size::ReadableSize;
 use common_query::Output;
 use common_test_util::ports;
 use query::parser::PromQuery;
@@ -103,12 +104,24 @@ impl SqlQueryHandler for DummyInstance {
     }
 }
 
-fn make_test_app(tx: Arc<mpsc::Sender<(String, String)>>, db_name: Option<&str>) -> Router {
+fn make_test_app(
+    tx: Arc<mpsc::Sender<(String, String)>>,
+    db_name: Option<&str>,
+    v2_api: bool,
+) -> Router {
     let http_opts = HttpOptions {
         addr: format!("127.0.0.1:{}", ports::get_port()),
         ..Default::default()
     };
+    make_test_app_with_opts(tx, db_name, v2_api, http_opts)
+}
 
+fn make_test_app_with_opts(
+    tx: Arc<mpsc::Sender<(String, String)>>,
+    db_name: Option<&str>,
+    v2_api: bool,
+    http_opts: HttpOptions,
+) -> Router {
     let instance = Arc::new(DummyInstance { tx });
     let mut user_provider = MockUserProvider::default();
     if let Some(name) = db_name {
@@ -123,6 +136,7 @@ fn make_test_app(tx: Arc<mpsc::Sender<(String, String)>>, db_name: Option<&str>)
         .with_grpc_handler(instance.clone())
         .with_user_provider(Arc::new(user_provider))
         .with_influxdb_handler(instance)
+        .with_influxdb_v2_api(v2_api)
         .build();
     server.build(server.make_app())
 }
@@ -132,7 +146,7 @@ async fn test_influxdb_write() {
     let (tx, mut rx) = mpsc::channel(100);
     let tx = Arc::new(tx);
 
-    let app = make_test_app(tx.clone(), None);
+    let app = make_test_app(tx.clone(), None, true);
     let client = TestClient::new(app);
 
     let result = client.get("/v1/influxdb/health").send().await;
@@ -178,7 +192,7 @@ async fn test_influxdb_write() {
     assert_eq!(result.status(), 401);
 
     // make new app for db=influxdb
-    let app = make_test_app(tx, Some("influxdb"));
+    let app = make_test_app(tx, Some("influxdb"), true);
     let client = TestClient::new(app);
 
     // right request
@@ -214,3 +228,114 @@ async fn test_influxdb_write() {
         ]
     );
 }
+
+#[tokio::test]
+async fn test_influxdb_write_v2() {
+    let (tx, mut rx) = mpsc::channel(100);
+    let tx = Arc::new(tx);
+
+    let app = make_test_app(tx.clone(), Some("influxdb"), true);
+    let client = TestClient::new(app);
+
+    // The bucket is the database.
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=influxdb&precision=ms")
+        .body("monitor,host=host1 cpu=1.2 1664370459457")
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+
+    // The token is checked against the user provider.
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=influxdb")
+        .body("monitor,host=host1 cpu=1.2 1664370459457010101")
+        .header(http::header::AUTHORIZATION, "token greptime:wrongpwd")
+        .send()
+        .await;
+    assert_eq!(result.status(), 401);
+
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=influxdb&precision=d")
+        .body("monitor,host=host1 cpu=1.2 1664370459")
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 400);
+
+    let app = make_test_app(tx, None, false);
+    let client = TestClient::new(app);
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=public")
+        .body("monitor,host=host1 cpu=1.2 1664370459457010101")
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 404);
+
+    let mut metrics = vec![];
+    while let Ok(s) = rx.try_recv() {
+        metrics.push(s);
+    }
+    assert_eq!(
+        metrics,
+        vec![("influxdb".to_string(), "monitor".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_influxdb_write_body_limit() {
+    let (tx, _rx) = mpsc::channel(100);
+    let tx = Arc::new(tx);
+
+    let line = "monitor,host=host1 cpu=1.2 1664370459457010101";
+    let limit = line.len() as u64 + 1;
+    let http_opts = HttpOptions {
+        addr: format!("127.0.0.1:{}", ports::get_port()),
+        body_limit: ReadableSize(limit),
+        ..Default::default()
+    };
+    let app = make_test_app_with_opts(tx.clone(), None, true, http_opts.clone());
+    let client = TestClient::new(app);
+
+    // Just under the limit.
+    let result = client
+        .post("/v1/influxdb/write?db=public")
+        .body(line)
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+
+    // Just over the limit.
+    let result = client
+        .post("/v1/influxdb/write?db=public")
+        .body(format!("{line}\n\n"))
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 413);
+    assert!(result.text().await.contains(&format!("{limit} bytes")));
+
+    // The limit of the InfluxDB APIs overrides the limit of the server.
+    let http_opts = HttpOptions {
+        influxdb_body_limit: Some(ReadableSize(limit + 1)),
+        ..http_opts
+    };
+    let app = make_test_app_with_opts(tx, None, true, http_opts);
+    let client = TestClient::new(app);
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=public")
+        .body(format!("{line}\n\n"))
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+    let result = client
+        .post("/v1/influxdb/api/v2/write?org=greptime&bucket=public")
+        .body(format!("{line}\n\n\n"))
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 413);
+}
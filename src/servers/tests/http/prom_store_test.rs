@@ -21,6 +21,7 @@ use api::v1::greptime_request::Request;
 use async_trait::async_trait;
 use axum::Router;
 use axum_test_helper::TestClient;
+use common_base::readable_size::ReadableSize;
 use common_query::Output;
 use common_test_util::ports;
 use prost::Message;
@@ -130,7 +131,10 @@ fn make_test_app(tx: mpsc::Sender<(String, Vec<u8>)>) -> Router {
         addr: format!("127.0.0.1:{}", ports::get_port()),
         ..Default::default()
     };
+    make_test_app_with_opts(tx, http_opts)
+}
 
+fn make_test_app_with_opts(tx: mpsc::Sender<(String, Vec<u8>)>, http_opts: HttpOptions) -> Router {
     let instance = Arc::new(DummyInstance { tx });
     let server = HttpServerBuilder::new(http_opts)
         .with_grpc_handler(instance.clone())
@@ -245,3 +249,42 @@ async fn test_prometheus_remote_write_read() {
         ReadRequest::decode(&(requests[3].1)[..]).unwrap()
     );
 }
+
+#[tokio::test]
+async fn test_prometheus_remote_write_body_limit() {
+    let (tx, _rx) = mpsc::channel(100);
+
+    let write_request = WriteRequest {
+        timeseries: prom_store::mock_timeseries(),
+        ..Default::default()
+    };
+    let body = snappy_compress(&write_request.encode_to_vec()[..]).unwrap();
+    // The remote write handler reads the raw body, only limited by the limit of the
+    // Prometheus remote storage APIs.
+    let http_opts = HttpOptions {
+        addr: format!("127.0.0.1:{}", ports::get_port()),
+        body_limit: ReadableSize(1),
+        prom_store_body_limit: Some(ReadableSize(body.len() as u64)),
+        ..Default::default()
+    };
+    let app = make_test_app_with_opts(tx, http_opts);
+    let client = TestClient::new(app);
+
+    // Just under the limit.
+    let result = client
+        .post("/v1/prometheus/write")
+        .body(body.clone())
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+
+    // Just over the limit.
+    let mut over_limit = body;
+    over_limit.push(0);
+    let result = client
+        .post("/v1/prometheus/write")
+        .body(over_limit)
+        .send()
+        .await;
+    assert_eq!(result.status(), 413);
+}
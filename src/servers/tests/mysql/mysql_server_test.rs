@@ -27,10 +27,14 @@ use mysql_async::prelude::*;
 use mysql_async::{Conn, Row, SslOpts};
 use rand::rngs::StdRng;
 use rand::Rng;
+use servers::connection_limit::{ConnectionLimitOptions, ConnectionLimiter};
 use servers::error::Result;
-use servers::mysql::server::{MysqlServer, MysqlSpawnConfig, MysqlSpawnRef};
+use servers::mysql::server::{
+    MysqlServer, MysqlSpawnConfig, MysqlSpawnRef, DEFAULT_MYSQL_SERVER_VERSION,
+};
+use servers::proxy_protocol::ProxyProtocolOptions;
 use servers::server::Server;
-use servers::tls::TlsOption;
+use servers::tls::{ReloadableTlsServerConfig, TlsOption};
 use table::test_util::MemTable;
 use table::TableRef;
 
@@ -42,6 +46,9 @@ struct MysqlOpts<'a> {
     tls: TlsOption,
     auth_info: Option<DatabaseAuthInfo<'a>>,
     reject_no_database: bool,
+    connection_init_sql: Option<String>,
+    server_version: Option<String>,
+    connection_limit: ConnectionLimitOptions,
 }
 
 fn create_mysql_server(table: TableRef, opts: MysqlOpts<'_>) -> Result<Box<dyn Server>> {
@@ -61,11 +68,20 @@ fn create_mysql_server(table: TableRef, opts: MysqlOpts<'_>) -> Result<Box<dyn S
 
     Ok(MysqlServer::create_server(
         io_runtime,
-        Arc::new(MysqlSpawnRef::new(query_handler, Some(Arc::new(provider)))),
+        Arc::new(MysqlSpawnRef::new(
+            query_handler,
+            Some(Arc::new(provider)),
+            Arc::new(ConnectionLimiter::new(opts.connection_limit)),
+        )),
         Arc::new(MysqlSpawnConfig::new(
             opts.tls.should_force_tls(),
-            opts.tls.setup()?.map(Arc::new),
+            Arc::new(ReloadableTlsServerConfig::try_new(opts.tls.clone())?),
             opts.reject_no_database,
+            opts.connection_init_sql,
+            ProxyProtocolOptions::default(),
+            opts.server_version
+                .unwrap_or_else(|| DEFAULT_MYSQL_SERVER_VERSION.to_string()),
+            None,
         )),
     ))
 }
@@ -112,6 +128,115 @@ async fn test_reject_no_database() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_connection_init_sql() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let table = MemTable::default_numbers_table();
+    let mysql_server = create_mysql_server(
+        table,
+        MysqlOpts {
+            // Answered by the federated layer.
+            connection_init_sql: Some("SET time_zone = '+08:00'".to_string()),
+            ..Default::default()
+        },
+    )?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+
+    let mut connection = create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .unwrap();
+    let result: u32 = connection
+        .query_first("SELECT uint32s FROM numbers LIMIT 1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result, 0);
+    let time_zone: String = connection
+        .query_first("SELECT @@time_zone")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!("+08:00", time_zone);
+    mysql_server.shutdown().await.unwrap();
+
+    let table = MemTable::default_numbers_table();
+    let mysql_server = create_mysql_server(
+        table,
+        MysqlOpts {
+            connection_init_sql: Some("SELECT * FROM not_exist".to_string()),
+            ..Default::default()
+        },
+    )?;
+    let server_addr = mysql_server.start(listening).await.unwrap();
+    assert!(create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .is_err());
+    mysql_server.shutdown().await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_version() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    for (server_version, expected) in [(None, (5, 1, 10)), (Some("8.4.2"), (8, 4, 2))] {
+        let table = MemTable::default_numbers_table();
+        let mysql_server = create_mysql_server(
+            table,
+            MysqlOpts {
+                server_version: server_version.map(|v| v.to_string()),
+                ..Default::default()
+            },
+        )?;
+        let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+        let server_addr = mysql_server.start(listening).await.unwrap();
+
+        let connection = create_connection_default_db_name(server_addr.port(), false)
+            .await
+            .unwrap();
+        assert_eq!(expected, connection.server_version());
+        mysql_server.shutdown().await.unwrap();
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_max_connections_per_user() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let table = MemTable::default_numbers_table();
+    let mysql_server = create_mysql_server(
+        table,
+        MysqlOpts {
+            connection_limit: ConnectionLimitOptions {
+                max_connections_per_user: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_port = mysql_server.start(listening).await.unwrap().port();
+
+    let connection = create_connection_default_db_name(server_port, false)
+        .await
+        .unwrap();
+    assert!(create_connection_default_db_name(server_port, false)
+        .await
+        .is_err());
+
+    // The user can connect again once the connection is closed.
+    connection.disconnect().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let _ = create_connection_default_db_name(server_port, false)
+        .await
+        .unwrap();
+    mysql_server.shutdown().await.unwrap();
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_schema_validation() -> Result<()> {
     async fn generate_server(auth_info: DatabaseAuthInfo<'_>) -> Result<(Box<dyn Server>, u16)> {
@@ -250,6 +375,7 @@ async fn test_server_required_secure_client_plain() -> Result<()> {
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-rsa.key".to_owned(),
+        watch: false,
     };
 
     let client_tls = false;
@@ -287,6 +413,7 @@ async fn test_server_required_secure_client_plain_with_pkcs8_priv_key() -> Resul
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-pkcs8.key".to_owned(),
+        watch: false,
     };
 
     let client_tls = false;
@@ -588,6 +715,7 @@ async fn do_test_query_all_datatypes_with_secure_server(
                 "tests/ssl/server-rsa.key".to_owned()
             }
         },
+        watch: false,
     };
 
     do_test_query_all_datatypes(server_tls, client_tls).await
@@ -25,9 +25,11 @@ use servers::error::{self as server_error, Error, Result};
 use servers::opentsdb::codec::DataPoint;
 use servers::opentsdb::connection::Connection;
 use servers::opentsdb::OpentsdbServer;
+use servers::proxy_protocol::{ProxyProtocolOptions, ProxyProtocolVersion};
 use servers::query_handler::OpentsdbProtocolHandler;
 use servers::server::Server;
 use session::context::QueryContextRef;
+use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Notify};
 
@@ -52,6 +54,13 @@ impl OpentsdbProtocolHandler for DummyOpentsdbInstance {
 }
 
 fn create_opentsdb_server(tx: mpsc::Sender<i32>) -> Result<Box<dyn Server>> {
+    create_opentsdb_server_with_proxy_protocol(tx, ProxyProtocolOptions::default())
+}
+
+fn create_opentsdb_server_with_proxy_protocol(
+    tx: mpsc::Sender<i32>,
+    proxy_protocol: ProxyProtocolOptions,
+) -> Result<Box<dyn Server>> {
     let query_handler = Arc::new(DummyOpentsdbInstance { tx });
     let io_runtime = Arc::new(
         RuntimeBuilder::default()
@@ -60,7 +69,11 @@ fn create_opentsdb_server(tx: mpsc::Sender<i32>) -> Result<Box<dyn Server>> {
             .build()
             .unwrap(),
     );
-    Ok(OpentsdbServer::create_server(query_handler, io_runtime))
+    Ok(OpentsdbServer::create_server(
+        query_handler,
+        io_runtime,
+        proxy_protocol,
+    ))
 }
 
 #[tokio::test]
@@ -233,6 +246,36 @@ async fn test_query() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_query_with_proxy_protocol() -> Result<()> {
+    let (tx, mut rx) = mpsc::channel(10);
+    let server = create_opentsdb_server_with_proxy_protocol(
+        tx,
+        ProxyProtocolOptions {
+            version: Some(ProxyProtocolVersion::V1),
+            ..Default::default()
+        },
+    )?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let addr = server.start(listening).await?;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 4242\r\n")
+        .await
+        .unwrap();
+    let mut connection = Connection::new(stream);
+    connection.write_line("put 100 1 1".to_string()).await?;
+    assert_eq!(rx.recv().await.unwrap(), 10000);
+
+    // Connections without the header are rejected.
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut connection = Connection::new(stream);
+    connection.write_line("put 100 1 1".to_string()).await?;
+    assert!(!matches!(connection.read_line().await, Ok(Some(_))));
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_query_concurrently() -> Result<()> {
     let threads = 4;
@@ -25,10 +25,12 @@ use rand::rngs::StdRng;
 use rand::Rng;
 use rustls::client::{ServerCertVerified, ServerCertVerifier};
 use rustls::{Certificate, Error, ServerName};
+use servers::connection_limit::ConnectionLimiter;
 use servers::error::Result;
 use servers::postgres::PostgresServer;
+use servers::proxy_protocol::ProxyProtocolOptions;
 use servers::server::Server;
-use servers::tls::TlsOption;
+use servers::tls::{ReloadableTlsServerConfig, TlsOption};
 use table::test_util::MemTable;
 use table::TableRef;
 use tokio_postgres::{Client, Error as PgError, NoTls, SimpleQueryMessage};
@@ -59,11 +61,17 @@ fn create_postgres_server(
         None
     };
 
+    let tls_server_config = Arc::new(ReloadableTlsServerConfig::try_new(tls.clone())?);
+
     Ok(Box::new(PostgresServer::new(
         instance,
-        tls,
+        tls.should_force_tls(),
+        tls_server_config,
         io_runtime,
         user_provider,
+        None,
+        ProxyProtocolOptions::default(),
+        Arc::new(ConnectionLimiter::default()),
     )))
 }
 
@@ -266,6 +274,7 @@ async fn test_server_secure_require_client_plain() -> Result<()> {
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-rsa.key".to_owned(),
+        watch: false,
     };
     let server_port = start_test_server(server_tls).await?;
     let r = create_plain_connection(server_port, false).await;
@@ -281,6 +290,7 @@ async fn test_server_secure_require_client_plain_with_pkcs8_priv_key() -> Result
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-pkcs8.key".to_owned(),
+        watch: false,
     };
     let server_port = start_test_server(server_tls).await?;
     let r = create_plain_connection(server_port, false).await;
@@ -485,6 +495,7 @@ async fn do_simple_query_with_secure_server(
                 "tests/ssl/server-rsa.key".to_owned()
             }
         },
+        watch: false,
     };
 
     do_simple_query(server_tls, client_tls).await
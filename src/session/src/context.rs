@@ -24,6 +24,7 @@ use common_catalog::{build_db_string, parse_catalog_and_schema_from_db_string};
 use common_time::TimeZone;
 use derive_builder::Builder;
 use sql::dialect::{Dialect, GreptimeDbDialect, MySqlDialect, PostgreSqlDialect};
+use store_api::region_request::WriteAckLevel;
 
 pub type QueryContextRef = Arc<QueryContext>;
 pub type ConnInfoRef = Arc<ConnInfo>;
@@ -37,6 +38,14 @@ pub struct QueryContext {
     current_user: ArcSwap<Option<UserInfoRef>>,
     time_zone: Option<TimeZone>,
     sql_dialect: Box<dyn Dialect + Send + Sync>,
+    /// The protocol the query is received by, if known.
+    #[builder(setter(strip_option))]
+    channel: Option<Channel>,
+    /// When the writes of the query are acknowledged, overrides the default of the server.
+    #[builder(setter(strip_option))]
+    write_ack_level: Option<WriteAckLevel>,
+    /// Whether the client asks for the execution metrics of the query.
+    explain_analyze: bool,
     trace_id: u64,
     span_id: u64,
 }
@@ -61,6 +70,9 @@ impl From<&RegionRequestHeader> for QueryContext {
             current_user: Default::default(),
             time_zone: Default::default(),
             sql_dialect: Box::new(GreptimeDbDialect {}),
+            channel: None,
+            write_ack_level: None,
+            explain_analyze: false,
             trace_id: value.trace_id,
             span_id: value.span_id,
         }
@@ -148,6 +160,21 @@ impl QueryContext {
         self.trace_id
     }
 
+    #[inline]
+    pub fn channel(&self) -> Option<Channel> {
+        self.channel
+    }
+
+    #[inline]
+    pub fn write_ack_level(&self) -> Option<WriteAckLevel> {
+        self.write_ack_level
+    }
+
+    #[inline]
+    pub fn explain_analyze(&self) -> bool {
+        self.explain_analyze
+    }
+
     #[inline]
     pub fn span_id(&self) -> u64 {
         self.span_id
@@ -170,6 +197,9 @@ impl QueryContextBuilder {
             sql_dialect: self
                 .sql_dialect
                 .unwrap_or_else(|| Box::new(GreptimeDbDialect {})),
+            channel: self.channel.unwrap_or_default(),
+            write_ack_level: self.write_ack_level.unwrap_or_default(),
+            explain_analyze: self.explain_analyze.unwrap_or_default(),
             trace_id: self.trace_id.unwrap_or_else(common_telemetry::gen_trace_id),
             span_id: self.span_id.unwrap_or_default(),
         })
@@ -210,10 +240,12 @@ impl ConnInfo {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Channel {
     Mysql,
     Postgres,
+    Http,
+    Grpc,
 }
 
 impl Channel {
@@ -221,6 +253,7 @@ impl Channel {
         match self {
             Channel::Mysql => Box::new(MySqlDialect {}),
             Channel::Postgres => Box::new(PostgreSqlDialect {}),
+            Channel::Http | Channel::Grpc => Box::new(GreptimeDbDialect {}),
         }
     }
 }
@@ -230,6 +263,8 @@ impl Display for Channel {
         match self {
             Channel::Mysql => write!(f, "mysql"),
             Channel::Postgres => write!(f, "postgres"),
+            Channel::Http => write!(f, "http"),
+            Channel::Grpc => write!(f, "grpc"),
         }
     }
 }
@@ -15,6 +15,7 @@
 pub mod context;
 
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use arc_swap::ArcSwap;
@@ -34,6 +35,8 @@ pub struct Session {
     user_info: ArcSwap<UserInfoRef>,
     conn_info: ConnInfo,
     time_zone: ArcSwap<Option<TimeZone>>,
+    /// Whether the client opened a transaction that isn't committed or rolled back yet.
+    in_transaction: AtomicBool,
 }
 
 pub type SessionRef = Arc<Session>;
@@ -46,6 +49,7 @@ impl Session {
             user_info: ArcSwap::new(Arc::new(auth::userinfo_by_name(None))),
             conn_info: ConnInfo::new(addr, channel),
             time_zone: ArcSwap::new(Arc::new(None)),
+            in_transaction: AtomicBool::new(false),
         }
     }
 
@@ -58,6 +62,7 @@ impl Session {
             .current_catalog(self.catalog.load().to_string())
             .current_schema(self.schema.load().to_string())
             .sql_dialect(self.conn_info.channel.dialect())
+            .channel(self.conn_info.channel)
             .time_zone((**self.time_zone.load()).clone())
             .build()
     }
@@ -102,6 +107,16 @@ impl Session {
         self.schema.store(Arc::new(schema));
     }
 
+    #[inline]
+    pub fn in_transaction(&self) -> bool {
+        self.in_transaction.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn set_in_transaction(&self, in_transaction: bool) {
+        self.in_transaction.store(in_transaction, Ordering::Relaxed);
+    }
+
     pub fn get_db_string(&self) -> String {
         build_db_string(self.catalog.load().as_ref(), self.schema.load().as_ref())
     }
@@ -13,8 +13,10 @@
 // limitations under the License.
 
 use datafusion_sql::parser::Statement as DfStatement;
+use serde::{Deserialize, Serialize};
 use sqlparser::ast::Statement as SpStatement;
 use sqlparser_derive::{Visit, VisitMut};
+use strum::{Display, EnumString};
 
 use crate::error::{ConvertToDfStatementSnafu, Error};
 use crate::statements::alter::AlterTable;
@@ -66,6 +68,52 @@ pub enum Statement {
     TruncateTable(TruncateTable),
 }
 
+/// Kind of a [Statement], by which statements can be allowed or denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum StatementKind {
+    Query,
+    Insert,
+    Delete,
+    CreateTable,
+    CreateExternalTable,
+    DropTable,
+    CreateDatabase,
+    Alter,
+    ShowDatabases,
+    ShowTables,
+    ShowCreateTable,
+    DescribeTable,
+    Explain,
+    Copy,
+    Tql,
+    TruncateTable,
+}
+
+impl Statement {
+    pub fn kind(&self) -> StatementKind {
+        match self {
+            Statement::Query(_) => StatementKind::Query,
+            Statement::Insert(_) => StatementKind::Insert,
+            Statement::Delete(_) => StatementKind::Delete,
+            Statement::CreateTable(_) => StatementKind::CreateTable,
+            Statement::CreateExternalTable(_) => StatementKind::CreateExternalTable,
+            Statement::DropTable(_) => StatementKind::DropTable,
+            Statement::CreateDatabase(_) => StatementKind::CreateDatabase,
+            Statement::Alter(_) => StatementKind::Alter,
+            Statement::ShowDatabases(_) => StatementKind::ShowDatabases,
+            Statement::ShowTables(_) => StatementKind::ShowTables,
+            Statement::ShowCreateTable(_) => StatementKind::ShowCreateTable,
+            Statement::DescribeTable(_) => StatementKind::DescribeTable,
+            Statement::Explain(_) => StatementKind::Explain,
+            Statement::Copy(_) => StatementKind::Copy,
+            Statement::Tql(_) => StatementKind::Tql,
+            Statement::TruncateTable(_) => StatementKind::TruncateTable,
+        }
+    }
+}
+
 /// Comment hints from SQL.
 /// It'll be enabled when using `--comment` in mysql client.
 /// Eg: `SELECT * FROM system.number LIMIT 1; -- { ErrorCode 25 }`
@@ -39,7 +39,10 @@ pub trait LogStore: Send + Sync + 'static + std::fmt::Debug {
     async fn append(&self, e: Self::Entry) -> Result<AppendResponse, Self::Error>;
 
     /// Append a batch of entries atomically and return the offset of first entry.
-    async fn append_batch(&self, e: Vec<Self::Entry>) -> Result<(), Self::Error>;
+    ///
+    /// The entries are synced to the disk before returning if `sync` is set, even if
+    /// the store doesn't sync each write.
+    async fn append_batch(&self, e: Vec<Self::Entry>, sync: bool) -> Result<(), Self::Error>;
 
     /// Create a new `EntryStream` to asynchronously generates `Entry` with ids
     /// starting from `id`.
@@ -18,8 +18,9 @@ use std::fmt::{self};
 use api::v1::add_column_location::LocationType;
 use api::v1::region::{alter_request, region_request, AlterRequest};
 use api::v1::{self, Rows, SemanticType};
+use serde::{Deserialize, Serialize};
 use snafu::{ensure, OptionExt};
-use strum::IntoStaticStr;
+use strum::{Display, EnumString, IntoStaticStr};
 
 use crate::metadata::{
     ColumnMetadata, InvalidRawRegionRequestSnafu, InvalidRegionRequestSnafu, MetadataError,
@@ -69,8 +70,15 @@ impl RegionRequest {
                 .into_iter()
                 .filter_map(|r| {
                     let region_id = r.region_id.into();
-                    r.rows
-                        .map(|rows| (region_id, Self::Put(RegionPutRequest { rows })))
+                    r.rows.map(|rows| {
+                        (
+                            region_id,
+                            Self::Put(RegionPutRequest {
+                                rows,
+                                ack_level: WriteAckLevel::default(),
+                            }),
+                        )
+                    })
                 })
                 .collect()),
             region_request::Body::Deletes(deletes) => Ok(deletes
@@ -78,8 +86,15 @@ impl RegionRequest {
                 .into_iter()
                 .filter_map(|r| {
                     let region_id = r.region_id.into();
-                    r.rows
-                        .map(|rows| (region_id, Self::Delete(RegionDeleteRequest { rows })))
+                    r.rows.map(|rows| {
+                        (
+                            region_id,
+                            Self::Delete(RegionDeleteRequest {
+                                rows,
+                                ack_level: WriteAckLevel::default(),
+                            }),
+                        )
+                    })
                 })
                 .collect()),
             region_request::Body::Create(create) => {
@@ -146,6 +161,15 @@ impl RegionRequest {
     pub fn type_name(&self) -> &'static str {
         self.into()
     }
+
+    /// Sets the level to acknowledge the request at, if it's a put or a delete.
+    pub fn set_write_ack_level(&mut self, ack_level: WriteAckLevel) {
+        match self {
+            RegionRequest::Put(put) => put.ack_level = ack_level,
+            RegionRequest::Delete(delete) => delete.ack_level = ack_level,
+            _ => {}
+        }
+    }
 }
 
 /// Request to put data into a region.
@@ -153,6 +177,8 @@ impl RegionRequest {
 pub struct RegionPutRequest {
     /// Rows to put.
     pub rows: Rows,
+    /// When the put is acknowledged.
+    pub ack_level: WriteAckLevel,
 }
 
 #[derive(Debug)]
@@ -167,6 +193,41 @@ pub struct RegionDeleteRequest {
     ///
     /// Each row only contains primary key columns and a time index column.
     pub rows: Rows,
+    /// When the delete is acknowledged.
+    pub ack_level: WriteAckLevel,
+}
+
+/// Key of the header, or the gRPC metadata, overriding the [WriteAckLevel] of a request.
+pub const WRITE_ACK_LEVEL_KEY: &str = "x-greptime-write-ack-level";
+
+/// When a write is acknowledged, from the fastest to the most durable level.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
+    IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum WriteAckLevel {
+    /// After the write is in the memtable. The WAL is written but only synced to the
+    /// disk if the log store syncs each write, so the writes not synced yet are lost
+    /// if the machine crashes.
+    #[default]
+    Memtable,
+    /// After the WAL synced to the disk.
+    Wal,
+    /// After the region flushed the write to the object store.
+    Flush,
 }
 
 #[derive(Debug, Clone)]
@@ -763,4 +824,24 @@ mod tests {
         metadata.schema_version = 1;
         request.validate(&metadata).unwrap();
     }
+
+    #[test]
+    fn test_write_ack_level() {
+        assert_eq!(WriteAckLevel::Memtable, WriteAckLevel::default());
+        assert_eq!(WriteAckLevel::Wal, "wal".parse().unwrap());
+        assert_eq!("flush", WriteAckLevel::Flush.to_string());
+        assert!("disk".parse::<WriteAckLevel>().is_err());
+        assert!(WriteAckLevel::Memtable < WriteAckLevel::Wal);
+        assert!(WriteAckLevel::Wal < WriteAckLevel::Flush);
+
+        let mut request = RegionRequest::Put(RegionPutRequest {
+            rows: Rows::default(),
+            ack_level: WriteAckLevel::default(),
+        });
+        request.set_write_ack_level(WriteAckLevel::Flush);
+        let RegionRequest::Put(put) = request else {
+            unreachable!()
+        };
+        assert_eq!(WriteAckLevel::Flush, put.ack_level);
+    }
 }
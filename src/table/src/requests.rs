@@ -84,6 +84,8 @@ pub struct TableOptions {
 pub const WRITE_BUFFER_SIZE_KEY: &str = "write_buffer_size";
 pub const TTL_KEY: &str = "ttl";
 pub const REGIONS_KEY: &str = "regions";
+/// Handling of the rows with the same primary key and timestamp in a table.
+pub const DUPLICATE_TIMESTAMP_POLICY_KEY: &str = "duplicate_timestamp_policy";
 
 impl TryFrom<&HashMap<String, String>> for TableOptions {
     type Error = error::Error;
@@ -340,6 +342,7 @@ pub fn valid_table_option(key: &str) -> bool {
             | WRITE_BUFFER_SIZE_KEY
             | TTL_KEY
             | REGIONS_KEY
+            | DUPLICATE_TIMESTAMP_POLICY_KEY
     ) | is_supported_in_s3(key)
 }
 
@@ -365,6 +368,7 @@ mod tests {
         assert!(valid_table_option(TTL_KEY));
         assert!(valid_table_option(REGIONS_KEY));
         assert!(valid_table_option(WRITE_BUFFER_SIZE_KEY));
+        assert!(valid_table_option(DUPLICATE_TIMESTAMP_POLICY_KEY));
         assert!(!valid_table_option("foo"));
     }
 
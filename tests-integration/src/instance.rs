@@ -22,6 +22,8 @@ mod tests {
     use api::v1::region::QueryRequest;
     use common_base::Plugins;
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_error::ext::ErrorExt;
+    use common_error::status_code::StatusCode;
     use common_meta::key::table_name::TableNameKey;
     use common_meta::rpc::router::region_distribution;
     use common_query::Output;
@@ -29,6 +31,7 @@ mod tests {
     use common_telemetry::debug;
     use frontend::error::{self, Error, Result};
     use frontend::instance::Instance;
+    use operator::statement::SchemaLimits;
     use query::parser::QueryLanguageParser;
     use query::plan::LogicalPlan;
     use servers::interceptor::{SqlQueryInterceptor, SqlQueryInterceptorRef};
@@ -442,4 +445,59 @@ mod tests {
             unreachable!();
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_schema_limits() {
+        let plugins = Plugins::new();
+        plugins.insert(SchemaLimits {
+            max_table_count: Some(1),
+            max_column_count: Some(3),
+        });
+
+        let standalone = GreptimeDbStandaloneBuilder::new("test_schema_limits")
+            .with_plugin(plugins)
+            .build()
+            .await;
+        let instance = standalone.instance;
+        let query_ctx = QueryContext::arc();
+        let execute = |sql: &'static str| {
+            let instance = instance.clone();
+            let query_ctx = query_ctx.clone();
+            async move {
+                SqlQueryHandler::do_query(&*instance, sql, query_ctx)
+                    .await
+                    .remove(0)
+            }
+        };
+
+        let err = execute(
+            "CREATE TABLE demo(host STRING, ts TIMESTAMP TIME INDEX, cpu DOUBLE, memory DOUBLE)",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(StatusCode::RuntimeResourcesExhausted, err.status_code());
+
+        let _ = execute("CREATE TABLE demo(host STRING, ts TIMESTAMP TIME INDEX, cpu DOUBLE)")
+            .await
+            .unwrap();
+        let err = execute("CREATE TABLE demo2(ts TIMESTAMP TIME INDEX)")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::RuntimeResourcesExhausted, err.status_code());
+        let err = execute("ALTER TABLE demo ADD COLUMN memory DOUBLE")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::RuntimeResourcesExhausted, err.status_code());
+
+        // The existing table is still served.
+        let _ = execute(
+            "CREATE TABLE IF NOT EXISTS demo(host STRING, ts TIMESTAMP TIME INDEX, cpu DOUBLE)",
+        )
+        .await
+        .unwrap();
+        let output = execute("INSERT INTO demo VALUES ('host1', 1000, 1.0)")
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(1)));
+    }
 }
@@ -13,11 +13,12 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use catalog::kvbackend::KvBackendCatalogManager;
 use cmd::options::MixOptions;
 use common_base::Plugins;
-use common_config::KvBackendConfig;
+use common_config::{KvBackendConfig, MetadataBackend};
 use common_meta::cache_invalidator::DummyKvCacheInvalidator;
 use common_procedure::options::ProcedureConfig;
 use common_telemetry::logging::LoggingOptions;
@@ -25,6 +26,8 @@ use datanode::config::DatanodeOptions;
 use datanode::datanode::DatanodeBuilder;
 use frontend::frontend::FrontendOptions;
 use frontend::instance::{FrontendInstance, Instance, StandaloneDatanodeManager};
+use operator::insert::DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL;
+use store_api::region_request::WriteAckLevel;
 
 use crate::test_util::{self, create_tmp_dir_and_datanode_opts, StorageType, TestGuard};
 
@@ -106,6 +109,8 @@ impl GreptimeDbStandaloneBuilder {
             catalog_manager,
             plugins,
             datanode.region_server(),
+            WriteAckLevel::default(),
+            DEFAULT_MAX_REQUEST_WRITE_ACK_LEVEL,
         )
         .await
         .unwrap();
@@ -119,8 +124,10 @@ impl GreptimeDbStandaloneBuilder {
             datanode_opts: opts.clone(),
             mix_options: MixOptions {
                 data_home: opts.storage.data_home.to_string(),
+                shutdown_grace_period: Duration::from_secs(30),
                 procedure: procedure_config,
                 metadata_store: kv_backend_config,
+                metadata_backend: MetadataBackend::default(),
                 frontend: FrontendOptions::default(),
                 datanode: opts,
                 logging: LoggingOptions::default(),
@@ -19,7 +19,7 @@ use axum::http::StatusCode;
 use axum_test_helper::TestClient;
 use common_error::status_code::StatusCode as ErrorCode;
 use serde_json::json;
-use servers::http::handler::HealthResponse;
+use servers::http::handler::{HealthResponse, ValidateSqlResponse, ValidationStage};
 use servers::http::prometheus::{PrometheusJsonResponse, PrometheusResponse};
 use servers::http::{JsonOutput, JsonResponse};
 use tests_integration::test_util::{
@@ -59,6 +59,7 @@ macro_rules! http_tests {
 
                 test_http_auth,
                 test_sql_api,
+                test_validate_sql_api,
                 test_prometheus_promql_api,
                 test_prom_http_api,
                 test_metrics_api,
@@ -319,6 +320,67 @@ pub async fn test_sql_api(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_validate_sql_api(store_type: StorageType) {
+    let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "validate_sql_api").await;
+    let client = TestClient::new(app);
+    let validate = |sql: &'static str| {
+        let client = &client;
+        async move {
+            let res = client
+                .post(&format!("/v1/sql/validate?sql={sql}"))
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .send()
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+            serde_json::from_str::<ValidateSqlResponse>(&res.text().await).unwrap()
+        }
+    };
+
+    let body =
+        validate("select cpu from demo; insert into demo values('host', 66.6, 1024, 0)").await;
+    assert_eq!(2, body.statements);
+    assert_eq!(2, body.planned);
+    assert!(body.error.is_none());
+
+    // DDL is only parsed.
+    let body = validate("create table t (ts timestamp time index)").await;
+    assert_eq!(1, body.statements);
+    assert_eq!(0, body.planned);
+    assert!(body.error.is_none());
+
+    let error = validate("selec cpu from demo").await.error.unwrap();
+    assert_eq!(ValidationStage::Syntax, error.stage);
+    assert_eq!(ErrorCode::InvalidSyntax as u32, error.code);
+
+    let error = validate("select cpu from not_exist").await.error.unwrap();
+    assert_eq!(ValidationStage::Plan, error.stage);
+    let error = validate("select not_exist from demo").await.error.unwrap();
+    assert_eq!(ValidationStage::Plan, error.stage);
+    let error = validate("select cpu - 'abc' from demo")
+        .await
+        .error
+        .unwrap();
+    assert_eq!(ValidationStage::Plan, error.stage);
+
+    // Validating doesn't execute the insert or the create table.
+    let res = client
+        .get("/v1/sql?sql=select count(*) as c from demo")
+        .send()
+        .await;
+    let body = serde_json::from_str::<JsonResponse>(&res.text().await).unwrap();
+    assert_eq!(
+        body.output().unwrap()[0],
+        serde_json::from_value::<JsonOutput>(json!({
+            "records":{"schema":{"column_schemas":[{"name":"c","data_type":"Int64"}]},"rows":[[0]]}
+        }))
+        .unwrap()
+    );
+    let error = validate("select * from t").await.error.unwrap();
+    assert_eq!(ValidationStage::Plan, error.stage);
+
+    guard.remove_all().await;
+}
+
 pub async fn test_prometheus_promql_api(store_type: StorageType) {
     let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "sql_api").await;
     let client = TestClient::new(app);
@@ -615,17 +677,30 @@ pub async fn test_config_api(store_type: StorageType) {
     let res_get = client.get("/config").send().await;
     assert_eq!(res_get.status(), StatusCode::OK);
     let expected_toml_str = format!(
-        r#"
+        r#"shutdown_grace_period = "30s"
+
 [procedure]
 max_retry_times = 3
 retry_delay = "500ms"
+max_running_procedures = 128
 
 [metadata_store]
 file_size = "256MiB"
 purge_threshold = "4GiB"
 
+[metadata_backend]
+type = "local"
+
 [frontend]
 mode = "standalone"
+deny_full_table_scan = false
+full_table_scan_exempt_tables = []
+default_fill_policy = "null"
+deny_sql_writes = false
+sql_statement_blocklist = []
+error_verbosity = "normal"
+write_ack_level = "memtable"
+max_request_write_ack_level = "wal"
 
 [frontend.heartbeat]
 interval = "18s"
@@ -635,6 +710,17 @@ retry_interval = "3s"
 addr = "127.0.0.1:4000"
 timeout = "30s"
 body_limit = "64MiB"
+fail_fast_on_missing_table = false
+keep_alive = true
+keep_alive_timeout = "0s"
+max_requests_per_connection = 0
+response_timeout_header = false
+accept_ranges = false
+admin_bind_localhost_only = true
+admin_allow_remote = false
+enable_metrics = true
+metrics_path = "/metrics"
+enable_log_stream = false
 
 [frontend.grpc]
 addr = "127.0.0.1:4001"
@@ -646,11 +732,17 @@ max_send_message_size = "512MiB"
 enable = true
 addr = "127.0.0.1:4002"
 runtime_size = 2
+server_version = "5.1.10-alpha-msql-proxy"
 
 [frontend.mysql.tls]
 mode = "disable"
 cert_path = ""
 key_path = ""
+watch = false
+
+[frontend.mysql.proxy_protocol]
+allow_missing_header = false
+missing_header_timeout = "50ms"
 
 [frontend.postgres]
 enable = true
@@ -661,23 +753,67 @@ runtime_size = 2
 mode = "disable"
 cert_path = ""
 key_path = ""
+watch = false
+
+[frontend.postgres.proxy_protocol]
+allow_missing_header = false
+missing_header_timeout = "50ms"
 
 [frontend.opentsdb]
 enable = true
 addr = "127.0.0.1:4242"
 runtime_size = 2
 
+[frontend.opentsdb.proxy_protocol]
+allow_missing_header = false
+missing_header_timeout = "50ms"
+
 [frontend.influxdb]
 enable = true
+time_index_policy = "arrival_time"
+v2_api = true
 
 [frontend.prom_store]
 enable = true
+native_histogram = false
+downsampling = []
 
 [frontend.otlp]
 enable = true
 
+[frontend.query_cache]
+enable = false
+size = "64MiB"
+max_result_size = "1MiB"
+ttl = "10s"
+
+[frontend.cardinality]
+enable = false
+warn_threshold = 100000
+max_columns = 1000
+check_interval = "1m"
+
+[frontend.slow_query]
+enable = false
+threshold = "5s"
+format = "text"
+max_statement_length = 1024
+
+[frontend.self_monitoring]
+enable = false
+interval = "30s"
+database = "greptime_private"
+table = "metrics"
+
+[frontend.query_admission]
+overflow = "queue"
+exempt_metadata = true
+
 [frontend.logging]
 enable_jaeger_tracing = false
+log_format = "pretty"
+rotation = "hourly"
+enable_log_stream = false
 
 [frontend.datanode.client]
 timeout = "10s"
@@ -688,11 +824,14 @@ tcp_nodelay = true
 mode = "standalone"
 node_id = 0
 require_lease_before_startup = true
+startup_open_regions_concurrency = 16
 rpc_addr = "127.0.0.1:3001"
 rpc_runtime_size = 8
 rpc_max_recv_message_size = "512MiB"
 rpc_max_send_message_size = "512MiB"
 enable_telemetry = true
+collect_os_metrics = false
+allow_version_mismatch = false
 
 [datanode.heartbeat]
 interval = "3s"
@@ -702,13 +841,28 @@ retry_interval = "3s"
 addr = "127.0.0.1:4000"
 timeout = "30s"
 body_limit = "64MiB"
+fail_fast_on_missing_table = false
 
 [datanode.wal]
+provider = "raft_engine"
 file_size = "256MiB"
 purge_threshold = "4GiB"
 purge_interval = "10m"
 read_batch_size = 128
 sync_write = false
+min_free_space = "256MiB"
+
+[datanode.wal.kafka]
+broker_endpoints = ["127.0.0.1:9092"]
+topic_name_prefix = "greptimedb_wal_topic"
+num_topics = 64
+replication_factor = 1
+create_topic_timeout = "30s"
+max_batch_size = "1MiB"
+backoff_init = "500ms"
+backoff_max = "10s"
+backoff_base = 2
+backoff_deadline = "5m"
 
 [datanode.storage]
 type = "{}"
@@ -729,6 +883,10 @@ region_write_buffer_size = "32MiB"
 picker_schedule_interval = "5m"
 auto_flush_interval = "1h"
 
+[datanode.backup]
+enable = false
+type = "File"
+
 [[datanode.region_engine]]
 
 [datanode.region_engine.mito]
@@ -741,9 +899,12 @@ max_background_jobs = 4
 auto_flush_interval = "30m"
 global_write_buffer_size = "1GiB"
 global_write_buffer_reject_size = "2GiB"
+duplicate_timestamp_policy = "keep_last"
 sst_meta_cache_size = "128MiB"
 vector_cache_size = "512MiB"
 sst_write_buffer_size = "8MiB"
+compaction_strategy = "twcs"
+compaction_keep_versions = 1
 
 [[datanode.region_engine]]
 
@@ -751,9 +912,15 @@ sst_write_buffer_size = "8MiB"
 
 [datanode.logging]
 enable_jaeger_tracing = false
+log_format = "pretty"
+rotation = "hourly"
+enable_log_stream = false
 
 [logging]
-enable_jaeger_tracing = false"#,
+enable_jaeger_tracing = false
+log_format = "pretty"
+rotation = "hourly"
+enable_log_stream = false"#,
         store_type
     );
     let body_text = drop_lines_with_inconsistent_results(res_get.text().await);